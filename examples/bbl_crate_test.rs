@@ -1,15 +1,9 @@
 use anyhow::Result;
-use bbl_parser::{parse_bbl_file_all_logs, ExportOptions};
+use bbl_parser::{parse_bbl_file_all_logs, BBLHeader, ExportOptions};
 use clap::Parser;
 use glob::glob;
 use std::path::{Path, PathBuf};
 
-// Type aliases to simplify complex PID tuples
-type PidValues = (i32, i32, i32);
-type PidWithFf = (i32, i32, i32, i32);
-type ThreePidValues = (PidValues, PidValues, PidValues);
-type ThreePidWithFf = (PidWithFf, PidWithFf, PidWithFf);
-
 #[derive(Parser)]
 #[command(name = "bbl_crate_test")]
 #[command(about = "Test program demonstrating BBL parser crate usage")]
@@ -122,7 +116,7 @@ fn process_file(file_path: &Path, debug: bool) -> Result<()> {
         println!("  Flight Duration: {:.1} seconds", duration);
 
         // PID settings from header (always shown)
-        display_pid_settings(&log.header.all_headers);
+        display_pid_settings(&log.header);
 
         if log.total_logs > 1 {
             println!();
@@ -132,164 +126,27 @@ fn process_file(file_path: &Path, debug: bool) -> Result<()> {
     Ok(())
 }
 
-/// Extract and display PID settings from system configuration
-fn display_pid_settings(all_headers: &[String]) {
+/// Display the PID settings from the log header
+fn display_pid_settings(header: &BBLHeader) {
     println!("  PID Settings:");
 
-    // First try to parse PID values with potential feedforward (4-value format for iNav)
-    if let Some((roll_pid, pitch_pid, yaw_pid)) = parse_pid_with_ff_from_headers(all_headers) {
-        // iNav 4-value format: P,I,D,FF
-        println!(
-            "    Roll: P={}, I={}, D={}, FF={}",
-            roll_pid.0, roll_pid.1, roll_pid.2, roll_pid.3
-        );
-        println!(
-            "    Pitch: P={}, I={}, D={}, FF={}",
-            pitch_pid.0, pitch_pid.1, pitch_pid.2, pitch_pid.3
-        );
-        println!(
-            "    Yaw: P={}, I={}, D={}, FF={}",
-            yaw_pid.0, yaw_pid.1, yaw_pid.2, yaw_pid.3
-        );
-    } else if let Some((roll_pid, pitch_pid, yaw_pid)) = parse_pid_from_headers(all_headers) {
-        // Check if we have Betaflight feedforward values (ff_weight)
-        if let Some((roll_ff, pitch_ff, yaw_ff)) = parse_feedforward_from_headers(all_headers) {
-            // Betaflight: P,I,D from rollPID + FF from ff_weight
-            println!(
-                "    Roll: P={}, I={}, D={}, FF={}",
-                roll_pid.0, roll_pid.1, roll_pid.2, roll_ff
-            );
-            println!(
-                "    Pitch: P={}, I={}, D={}, FF={}",
-                pitch_pid.0, pitch_pid.1, pitch_pid.2, pitch_ff
-            );
-            println!(
-                "    Yaw: P={}, I={}, D={}, FF={}",
-                yaw_pid.0, yaw_pid.1, yaw_pid.2, yaw_ff
-            );
-        } else {
-            // EmuFlight or older firmware: P,I,D only
-            println!(
-                "    Roll: P={}, I={}, D={}",
-                roll_pid.0, roll_pid.1, roll_pid.2
-            );
-            println!(
-                "    Pitch: P={}, I={}, D={}",
-                pitch_pid.0, pitch_pid.1, pitch_pid.2
-            );
-            println!("    Yaw: P={}, I={}, D={}", yaw_pid.0, yaw_pid.1, yaw_pid.2);
-        }
-    } else {
+    let Some(pids) = header.pid_config() else {
         println!("    PID values not found in expected format");
-    }
-}
-
-/// Parse PID values with feedforward from raw header lines (for iNav 4-value format)
-fn parse_pid_with_ff_from_headers(all_headers: &[String]) -> Option<ThreePidWithFf> {
-    let mut roll_pid = None;
-    let mut pitch_pid = None;
-    let mut yaw_pid = None;
-
-    for header in all_headers {
-        if header.starts_with("H rollPID:") {
-            if let Some(value_str) = header.strip_prefix("H rollPID:") {
-                roll_pid = parse_pid_with_ff_string(value_str.trim());
-            }
-        } else if header.starts_with("H pitchPID:") {
-            if let Some(value_str) = header.strip_prefix("H pitchPID:") {
-                pitch_pid = parse_pid_with_ff_string(value_str.trim());
-            }
-        } else if header.starts_with("H yawPID:") {
-            if let Some(value_str) = header.strip_prefix("H yawPID:") {
-                yaw_pid = parse_pid_with_ff_string(value_str.trim());
-            }
-        }
-    }
-
-    if let (Some(r), Some(p), Some(y)) = (roll_pid, pitch_pid, yaw_pid) {
-        Some((r, p, y))
-    } else {
-        None
-    }
-}
-
-/// Parse PID values from raw header lines (for Betaflight rollPID/pitchPID/yawPID format)
-fn parse_pid_from_headers(all_headers: &[String]) -> Option<ThreePidValues> {
-    let mut roll_pid = None;
-    let mut pitch_pid = None;
-    let mut yaw_pid = None;
-
-    for header in all_headers {
-        if header.starts_with("H rollPID:") {
-            if let Some(value_str) = header.strip_prefix("H rollPID:") {
-                roll_pid = parse_pid_string(value_str.trim());
-            }
-        } else if header.starts_with("H pitchPID:") {
-            if let Some(value_str) = header.strip_prefix("H pitchPID:") {
-                pitch_pid = parse_pid_string(value_str.trim());
-            }
-        } else if header.starts_with("H yawPID:") {
-            if let Some(value_str) = header.strip_prefix("H yawPID:") {
-                yaw_pid = parse_pid_string(value_str.trim());
-            }
-        }
-    }
-
-    if let (Some(r), Some(p), Some(y)) = (roll_pid, pitch_pid, yaw_pid) {
-        Some((r, p, y))
-    } else {
-        None
-    }
-}
-
-/// Parse feedforward values from raw header lines (Betaflight ff_weight format)
-fn parse_feedforward_from_headers(all_headers: &[String]) -> Option<PidValues> {
-    for header in all_headers {
-        if header.starts_with("H ff_weight:") {
-            if let Some(value_str) = header.strip_prefix("H ff_weight:") {
-                if let Some((roll_ff, pitch_ff, yaw_ff)) = parse_pid_string(value_str.trim()) {
-                    return Some((roll_ff, pitch_ff, yaw_ff));
-                }
-            }
-        }
-    }
-    None
-}
-
-/// Parse PID string in format "P,I,D,FF" and return (P, I, D, FF) tuple
-fn parse_pid_with_ff_string(pid_value: &str) -> Option<PidWithFf> {
-    // Remove quotes if present
-    let cleaned = pid_value.trim_matches('"');
-
-    let parts: Vec<&str> = cleaned.split(',').collect();
-    if parts.len() == 4 {
-        if let (Ok(p), Ok(i), Ok(d), Ok(ff)) = (
-            parts[0].trim().parse::<i32>(),
-            parts[1].trim().parse::<i32>(),
-            parts[2].trim().parse::<i32>(),
-            parts[3].trim().parse::<i32>(),
-        ) {
-            return Some((p, i, d, ff));
-        }
-    }
-    None
-}
-
-/// Parse PID string in format "P,I,D" or "P,I,D,FF" and return (P, I, D) tuple
-fn parse_pid_string(pid_value: &str) -> Option<PidValues> {
-    // Remove quotes if present
-    let cleaned = pid_value.trim_matches('"');
-
-    let parts: Vec<&str> = cleaned.split(',').collect();
-    if parts.len() >= 3 {
-        if let (Ok(p), Ok(i), Ok(d)) = (
-            parts[0].trim().parse::<i32>(),
-            parts[1].trim().parse::<i32>(),
-            parts[2].trim().parse::<i32>(),
-        ) {
-            return Some((p, i, d));
-            // Note: For iNav 4-value format "P,I,D,FF", we ignore the 4th value (feedforward)
+        return;
+    };
+    for (name, pid) in [
+        ("Roll", pids.roll),
+        ("Pitch", pids.pitch),
+        ("Yaw", pids.yaw),
+    ] {
+        match pid.ff {
+            // Betaflight (ff_weight) and iNav (4-value format) log feedforward
+            Some(ff) => println!(
+                "    {}: P={}, I={}, D={}, FF={}",
+                name, pid.p, pid.i, pid.d, ff
+            ),
+            // EmuFlight or older firmware: P,I,D only
+            None => println!("    {}: P={}, I={}, D={}", name, pid.p, pid.i, pid.d),
         }
     }
-    None
 }
@@ -0,0 +1,70 @@
+//! Catalog Scan Example
+//!
+//! Demonstrates how to index a directory of logs quickly: every `.BBL`, `.BFL`
+//! and `.TXT` file below the directory is read with
+//! `parse_bbl_file_headers_only`, which skips frame decoding, and one line per
+//! log is printed with its start time, firmware, board and craft name.
+
+use bbl_parser::{is_compressed_log_path, parse_bbl_file_headers_only};
+use std::path::{Path, PathBuf};
+
+fn main() -> anyhow::Result<()> {
+    // Get input directory from command line or show usage
+    let input_dir = std::env::args().nth(1).unwrap_or_else(|| {
+        println!("Usage: catalog_scan <directory>");
+        println!("Example: catalog_scan ./logs");
+        std::process::exit(1);
+    });
+
+    let mut files = Vec::new();
+    collect_log_files(Path::new(&input_dir), &mut files)?;
+    files.sort();
+
+    let mut log_count = 0;
+    for file in &files {
+        let headers = match parse_bbl_file_headers_only(file, false) {
+            Ok(headers) => headers,
+            Err(e) => {
+                eprintln!("{}: {e}", file.display());
+                continue;
+            }
+        };
+        for (index, header) in headers.iter().enumerate() {
+            println!(
+                "{} #{:<2} {:<20} {:<40} {:<12} {}",
+                file.display(),
+                index + 1,
+                header.log_start_datetime.as_deref().unwrap_or("-"),
+                header.firmware_revision,
+                header.board_info,
+                header.craft_name
+            );
+        }
+        log_count += headers.len();
+    }
+
+    println!("\n{log_count} log(s) in {} file(s)", files.len());
+    Ok(())
+}
+
+/// Recursively gather blackbox log files below `dir`
+fn collect_log_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_log_files(&path, files)?;
+        } else if is_log_file(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn is_log_file(path: &Path) -> bool {
+    let is_log_extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ["bbl", "bfl", "txt"].contains(&ext.to_ascii_lowercase().as_str()))
+        .unwrap_or(false);
+    is_log_extension || is_compressed_log_path(path)
+}
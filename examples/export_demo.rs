@@ -35,6 +35,7 @@ fn main() -> Result<()> {
         event: true,
         output_dir: output_dir.clone(),
         force_export: false,
+        ..Default::default()
     };
 
     // Parse the BBL file
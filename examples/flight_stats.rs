@@ -0,0 +1,67 @@
+//! Flight Statistics Example
+//!
+//! Demonstrates the analysis helpers on parsed logs: frame statistics,
+//! per-field min/max limits (as printed by `bbl_parser --limits`), motor output
+//! range and throttle percent.
+
+use bbl_parser::{parse_bbl_file_all_logs, ExportOptions};
+use std::path::Path;
+
+fn main() -> anyhow::Result<()> {
+    // Get input file from command line or show usage
+    let input_file = std::env::args().nth(1).unwrap_or_else(|| {
+        println!("Usage: flight_stats <input.BBL>");
+        println!("Example: flight_stats flight.BBL");
+        std::process::exit(1);
+    });
+
+    let logs = parse_bbl_file_all_logs(Path::new(&input_file), ExportOptions::default(), false)?;
+
+    for log in &logs {
+        println!("=== Log {} of {} ===", log.log_number, log.total_logs);
+        println!("Firmware: {}", log.header.firmware_revision);
+        println!("Duration: {:.2}s", log.duration_seconds());
+
+        // Frame counts straight from the parser
+        let stats = &log.stats;
+        println!(
+            "Frames: {} total ({} I, {} P, {} S, {} G, {} H, {} E), {} failed",
+            stats.total_frames,
+            stats.i_frames,
+            stats.p_frames,
+            stats.s_frames,
+            stats.g_frames,
+            stats.h_frames,
+            stats.e_frames,
+            stats.failed_frames
+        );
+
+        if let Some((min, max)) = log.header.motor_output_range() {
+            println!("Motor output range: {min}..{max}");
+        }
+
+        // Throttle percent derived from rcCommand[3]
+        if let Some(throttle) = log.throttle_percent() {
+            if !throttle.is_empty() {
+                let average = throttle.iter().sum::<f32>() / throttle.len() as f32;
+                let peak = throttle.iter().copied().fold(f32::MIN, f32::max);
+                println!("Throttle: average {average:.1}%, peak {peak:.1}%");
+            }
+        }
+
+        // Observed range of every main-frame field
+        println!("\nField limits:");
+        for limits in log.field_limits() {
+            println!(
+                "  {:<24} {:>8} .. {:<8} (range {})",
+                limits.name,
+                limits.min,
+                limits.max,
+                limits.max - limits.min
+            );
+        }
+        println!();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+//! GPS Pipeline Example
+//!
+//! Demonstrates the GPS path from a parsed log to mapping files: trip
+//! statistics (`BBLLog::gps_trip_stats`), distance from home
+//! (`BBLLog::home_distances`) and the `.gps.gpx`, `.gps.kml` and `.gps.csv`
+//! tracks written through the `export_*_to_writer` functions, for every log
+//! in the file.
+
+use bbl_parser::{
+    export_gps_csv_to_writer, export_gpx_to_writer, export_kml_to_writer, parse_bbl_file_all_logs,
+    ExportOptions,
+};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+fn main() -> anyhow::Result<()> {
+    // Get input file from command line or show usage
+    let input_file = std::env::args().nth(1).unwrap_or_else(|| {
+        println!("Usage: gps_pipeline <input.BBL> [output_dir]");
+        println!("Example: gps_pipeline flight.BBL ./output");
+        println!("Note: requires GPS data (G frames) in the BBL file");
+        std::process::exit(1);
+    });
+    let output_dir = PathBuf::from(std::env::args().nth(2).unwrap_or_else(|| ".".to_string()));
+    std::fs::create_dir_all(&output_dir)?;
+
+    let input_path = Path::new(&input_file);
+    let stem = input_path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("blackbox");
+    let logs = parse_bbl_file_all_logs(input_path, ExportOptions::default(), false)?;
+
+    for log in &logs {
+        println!("=== Log {} of {} ===", log.log_number, log.total_logs);
+        let Some(trip) = log.gps_trip_stats() else {
+            println!("No GPS fixes\n");
+            continue;
+        };
+        println!("GPS fixes: {}", log.gps_coordinates.len());
+        println!("Distance flown: {:.0} m", trip.distance_m);
+        println!("Max altitude: {:.1} m", trip.max_altitude_m);
+        if let Some(speed) = trip.max_speed_m_s {
+            println!("Max speed: {:.1} m/s", speed);
+        }
+
+        // Per-fix distance from home, when the log has H frames
+        let home_distances = log.home_distances().unwrap_or_default();
+        if let Some(farthest) = home_distances
+            .iter()
+            .max_by(|a, b| a.distance_m.total_cmp(&b.distance_m))
+        {
+            println!(
+                "Farthest from home: {:.0} m ({:.0} m straight line) at {:.1}s",
+                farthest.distance_m,
+                farthest.distance_3d_m,
+                farthest.timestamp_us as f64 / 1_000_000.0
+            );
+        }
+
+        // Same names as the CLI: <name>[.NN].gps.<ext>
+        let suffix = if log.total_logs > 1 {
+            format!(".{:02}", log.log_number)
+        } else {
+            String::new()
+        };
+        let path = |extension: &str| output_dir.join(format!("{stem}{suffix}.gps.{extension}"));
+
+        let gpx_path = path("gpx");
+        export_gpx_to_writer(log, BufWriter::new(File::create(&gpx_path)?))?;
+        let kml_path = path("kml");
+        export_kml_to_writer(log, BufWriter::new(File::create(&kml_path)?))?;
+        let csv_path = path("csv");
+        let report = export_gps_csv_to_writer(log, BufWriter::new(File::create(&csv_path)?))?;
+        println!("✓ Wrote {} fixes to:", report.rows_written);
+        for written in [gpx_path, kml_path, csv_path] {
+            println!("  {}", written.display());
+        }
+        println!();
+    }
+
+    Ok(())
+}
@@ -25,6 +25,7 @@ fn main() -> anyhow::Result<()> {
         event: false,
         output_dir: output_dir.clone(),
         force_export: false,
+        ..Default::default()
     };
 
     // Parse the BBL file
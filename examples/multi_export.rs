@@ -24,6 +24,7 @@ fn main() -> anyhow::Result<()> {
         event: true,
         output_dir: output_dir.clone(),
         force_export: false,
+        ..Default::default()
     };
 
     // Parse the BBL file
@@ -30,6 +30,7 @@ fn main() -> anyhow::Result<()> {
         event: false,
         output_dir: output_dir.clone(),
         force_export: false,
+        ..Default::default()
     };
 
     // Parse ALL logs from the BBL file (not just the first)
@@ -0,0 +1,54 @@
+//! Step Response Example
+//!
+//! Deconvolves the roll, pitch and yaw step responses of every log and prints
+//! their overshoot, delay and rise time, plus a coarse plot of each curve.
+//! Needs the `spectrum` feature:
+//! `cargo run --example step_response --features spectrum -- flight.BBL`
+
+use bbl_parser::{parse_bbl_file_all_logs, ExportOptions};
+use std::path::Path;
+
+const AXES: [&str; 3] = ["Roll", "Pitch", "Yaw"];
+
+fn main() -> anyhow::Result<()> {
+    let input_file = std::env::args().nth(1).unwrap_or_else(|| {
+        println!("Usage: step_response <input.BBL>");
+        println!("Example: step_response flight.BBL");
+        std::process::exit(1);
+    });
+
+    let logs = parse_bbl_file_all_logs(Path::new(&input_file), ExportOptions::default(), false)?;
+
+    for log in &logs {
+        println!("=== Log {} of {} ===", log.log_number, log.total_logs);
+        for (axis, name) in AXES.iter().enumerate() {
+            let step = match log.step_response(axis) {
+                Ok(step) => step,
+                Err(e) => {
+                    println!("{name}: {e}");
+                    continue;
+                }
+            };
+            let format_ms =
+                |ms: Option<f64>| ms.map_or("-".to_string(), |ms| format!("{ms:.1} ms"));
+            println!(
+                "{name}: steady {:.2}, overshoot {:.1}%, delay {}, rise {}, {} windows",
+                step.steady_state,
+                step.overshoot_percent,
+                format_ms(step.delay_ms),
+                format_ms(step.rise_time_ms),
+                step.windows
+            );
+
+            // One bar per 25 ms of the response
+            let every = (step.sample_rate_hz / 40.0).max(1.0) as usize;
+            for (index, value) in step.response.iter().enumerate().step_by(every) {
+                let bar = "#".repeat((value.clamp(0.0, 2.0) * 30.0) as usize);
+                println!("  {:>5.0} ms {value:>5.2} {bar}", step.time_ms(index));
+            }
+        }
+        println!();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+//! Streaming Log Reader Example
+//!
+//! Demonstrates how to parse logs one at a time from any `std::io::Read` source
+//! with `BBLLogReader`, so only the current log is held in memory. Parser
+//! warnings are collected through a `CollectingDiagnostics` sink instead of
+//! being printed by the parser.
+//!
+//! Pass `-` to read from stdin, e.g. `gunzip -c flight.BBL.gz | stream_logs -`.
+
+use bbl_parser::{BBLLogReader, CollectingDiagnostics, ExportOptions, ParserConfig};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::sync::Arc;
+
+fn main() -> anyhow::Result<()> {
+    // Get input file from command line or show usage
+    let input = std::env::args().nth(1).unwrap_or_else(|| {
+        println!("Usage: stream_logs <input.BBL | ->");
+        println!("Example: stream_logs flight.BBL");
+        std::process::exit(1);
+    });
+
+    let reader: Box<dyn Read> = if input == "-" {
+        Box::new(std::io::stdin().lock())
+    } else {
+        Box::new(BufReader::new(File::open(&input)?))
+    };
+
+    // Capture parser warnings instead of letting them go to stderr
+    let diagnostics = Arc::new(CollectingDiagnostics::default());
+    let config = ParserConfig {
+        diagnostics: Some(diagnostics.clone()),
+        ..Default::default()
+    };
+
+    let logs = BBLLogReader::new(reader, ExportOptions::default(), false).with_config(config);
+
+    let mut log_count = 0;
+    for log in logs {
+        let log = log?;
+        log_count += 1;
+
+        println!("Log {}:", log.log_number);
+        println!("  Firmware: {}", log.header.firmware_revision);
+        println!("  Main frames: {}", log.frames.len());
+        println!("  Duration: {:.2}s", log.duration_seconds());
+        if log.stats.truncated {
+            println!("  ⚠ Parsing stopped early at a parser limit");
+        }
+
+        for diagnostic in diagnostics.take() {
+            println!("  {:?}: {}", diagnostic.level, diagnostic.message);
+        }
+        // `log` is dropped here, before the next one is read
+    }
+
+    if log_count == 0 {
+        println!("No blackbox logs found in {}", input);
+    }
+
+    Ok(())
+}
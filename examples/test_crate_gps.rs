@@ -17,6 +17,7 @@ fn main() -> anyhow::Result<()> {
         event: true,
         output_dir,
         force_export: false,
+        ..Default::default()
     };
 
     println!("Parsing: {}", input_file);
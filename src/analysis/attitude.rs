@@ -0,0 +1,352 @@
+//! Attitude estimation from gyro and accelerometer, like blackbox_decode's
+//! `--simulate-imu`
+
+use crate::conversion::{
+    convert_acc_to_g, convert_gyro_to_deg_per_sec, convert_high_resolution_value,
+};
+use crate::types::{BBLHeader, BBLLog};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Weight of the gyro-propagated gravity vector against each accelerometer
+/// sample (`gyro_cmpf` of the Baseflight IMU)
+const GYRO_CMPF: f64 = 600.0;
+
+/// Weight of the gyro-propagated magnetic vector against each magnetometer sample
+const GYRO_CMPFM: f64 = 250.0;
+
+/// Accelerations whose squared magnitude in percent of 1 g² lies outside
+/// this range (about 0.85–1.15 g) are manoeuvres, not gravity, and are ignored
+const ACC_MAGNITUDE_RANGE: (f64, f64) = (72.0, 133.0);
+
+/// Estimated orientation of the craft
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Attitude {
+    pub roll_deg: f64,
+    pub pitch_deg: f64,
+    /// 0–360°, relative to the starting heading unless a magnetometer is logged
+    pub heading_deg: f64,
+}
+
+/// Complementary-filter IMU fed one main frame at a time
+///
+/// A port of the Baseflight IMU that blackbox_decode's `--simulate-imu` runs:
+/// the gravity (and magnetic or initial north) vector is rotated by the gyro
+/// and pulled towards the accelerometer (and magnetometer) readings.
+#[derive(Debug, Clone)]
+pub struct AttitudeEstimator {
+    gravity: [f64; 3],
+    north: [f64; 3],
+    magnetic: [f64; 3],
+    last_timestamp_us: Option<u64>,
+    gyro_scale: f32,
+    acc_1g: i32,
+    high_resolution: bool,
+    declination_deg: f64,
+}
+
+impl AttitudeEstimator {
+    /// Estimator for sensor values in physical units ([`AttitudeEstimator::update`])
+    pub fn new() -> Self {
+        Self {
+            gravity: [0.0; 3],
+            north: [1.0, 0.0, 0.0],
+            magnetic: [0.0; 3],
+            last_timestamp_us: None,
+            gyro_scale: 1.0,
+            acc_1g: 1,
+            high_resolution: false,
+            declination_deg: 0.0,
+        }
+    }
+
+    /// Estimator for the raw values of a log with this header
+    /// ([`AttitudeEstimator::update_raw`]); `None` without an `acc_1G` header
+    pub fn for_header(header: &BBLHeader) -> Option<Self> {
+        Some(Self {
+            gyro_scale: header.gyro_scale().unwrap_or(1.0),
+            acc_1g: header.acc_1g()?,
+            high_resolution: header.is_high_resolution(),
+            ..Self::new()
+        })
+    }
+
+    /// Add a magnetic declination in degrees (east positive) to every heading,
+    /// turning magnetic into true headings
+    pub fn with_declination(mut self, declination_deg: f64) -> Self {
+        self.declination_deg = declination_deg;
+        self
+    }
+
+    /// Advance to `timestamp_us` with raw `gyroADC`, `accSmooth` and,
+    /// when logged, `magADC` values
+    pub fn update_raw(
+        &mut self,
+        timestamp_us: u64,
+        gyro: [i32; 3],
+        acc: [i32; 3],
+        mag: Option<[i32; 3]>,
+    ) -> Attitude {
+        let gyro_deg_s = gyro.map(|raw| {
+            let value = if self.high_resolution {
+                convert_high_resolution_value(raw)
+            } else {
+                raw as f64
+            };
+            convert_gyro_to_deg_per_sec(value, self.gyro_scale)
+        });
+        let acc_g = acc.map(|raw| convert_acc_to_g(raw, self.acc_1g));
+        self.update(
+            timestamp_us,
+            gyro_deg_s,
+            acc_g,
+            mag.map(|mag| mag.map(f64::from)),
+        )
+    }
+
+    /// Advance to `timestamp_us` with rotation rates in deg/s, acceleration
+    /// in g and an optional magnetic field in any unit
+    pub fn update(
+        &mut self,
+        timestamp_us: u64,
+        gyro_deg_s: [f64; 3],
+        acc_g: [f64; 3],
+        mag: Option<[f64; 3]>,
+    ) -> Attitude {
+        // Frames out of time order (or the first one) add no rotation
+        let elapsed_s = match self.last_timestamp_us {
+            Some(last) if timestamp_us > last => (timestamp_us - last) as f64 / 1e6,
+            _ => 0.0,
+        };
+        self.last_timestamp_us = Some(timestamp_us);
+        let delta = gyro_deg_s.map(|rate| (rate * elapsed_s).to_radians());
+
+        rotate(&mut self.gravity, delta);
+        let acc_magnitude = acc_g.iter().map(|g| g * g).sum::<f64>() * 100.0;
+        if ACC_MAGNITUDE_RANGE.0 < acc_magnitude && acc_magnitude < ACC_MAGNITUDE_RANGE.1 {
+            for (estimate, acc) in self.gravity.iter_mut().zip(acc_g) {
+                *estimate = (*estimate * GYRO_CMPF + acc) / (GYRO_CMPF + 1.0);
+            }
+        }
+
+        let [x, y, z] = self.gravity;
+        let roll = y.atan2(z);
+        let pitch = (-x).atan2((y * y + z * z).sqrt());
+        let heading_vector = match mag {
+            Some(mag) => {
+                rotate(&mut self.magnetic, delta);
+                for (estimate, field) in self.magnetic.iter_mut().zip(mag) {
+                    *estimate = (*estimate * GYRO_CMPFM + field) / (GYRO_CMPFM + 1.0);
+                }
+                self.magnetic
+            }
+            None => {
+                rotate(&mut self.north, delta);
+                let length = self.north.iter().map(|v| v * v).sum::<f64>().sqrt();
+                if length > 0.0 {
+                    self.north = self.north.map(|v| v / length);
+                }
+                self.north
+            }
+        };
+
+        // Adding 0.0 turns -0.0 into 0.0
+        Attitude {
+            roll_deg: roll.to_degrees() + 0.0,
+            pitch_deg: pitch.to_degrees() + 0.0,
+            heading_deg: (heading(heading_vector, roll, pitch) + self.declination_deg)
+                .rem_euclid(360.0),
+        }
+    }
+}
+
+impl Default for AttitudeEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Rotate `v` by the roll, pitch and yaw angles `delta` in radians
+fn rotate(v: &mut [f64; 3], delta: [f64; 3]) {
+    let (sin_x, cos_x) = delta[0].sin_cos();
+    let (sin_y, cos_y) = delta[1].sin_cos();
+    let (sin_z, cos_z) = delta[2].sin_cos();
+    let matrix = [
+        [cos_z * cos_y, -cos_y * sin_z, sin_y],
+        [
+            sin_z * cos_x + cos_z * sin_x * sin_y,
+            cos_z * cos_x - sin_z * sin_x * sin_y,
+            -sin_x * cos_y,
+        ],
+        [
+            sin_z * sin_x - cos_z * cos_x * sin_y,
+            cos_z * sin_x + sin_z * cos_x * sin_y,
+            cos_y * cos_x,
+        ],
+    ];
+    let [x, y, z] = *v;
+    for (axis, value) in v.iter_mut().enumerate() {
+        *value = x * matrix[0][axis] + y * matrix[1][axis] + z * matrix[2][axis];
+    }
+}
+
+/// Tilt-compensated heading of `v` in degrees, 0–360
+fn heading(v: [f64; 3], roll: f64, pitch: f64) -> f64 {
+    let (sin_roll, cos_roll) = roll.sin_cos();
+    let (sin_pitch, cos_pitch) = pitch.sin_cos();
+    let x = v[0] * cos_pitch + v[1] * sin_roll * sin_pitch + v[2] * sin_pitch * cos_roll;
+    let y = v[1] * cos_roll - v[2] * sin_roll;
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+impl BBLLog {
+    /// Estimated attitude at every main frame, in frame order, from
+    /// `gyroADC`, `accSmooth` and, when logged, `magADC`.
+    ///
+    /// Returns `None` without gyro or accelerometer fields or an `acc_1G`
+    /// header. Tilt is taken from the first accelerometer reading in the
+    /// 0.85–1.15 g range and then corrected slowly.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bbl_parser::{parse_bbl_file, ExportOptions};
+    /// use std::path::Path;
+    ///
+    /// let log = parse_bbl_file(Path::new("flight.BBL"), ExportOptions::default(), false).unwrap();
+    /// if let Some(attitude) = log.estimate_attitude() {
+    ///     let steepest = attitude.iter().map(|a| a.pitch_deg.abs()).fold(0.0, f64::max);
+    ///     println!("Steepest pitch {steepest:.0} deg");
+    /// }
+    /// ```
+    pub fn estimate_attitude(&self) -> Option<Vec<Attitude>> {
+        self.estimate_attitude_with(0.0)
+    }
+
+    /// Tilt-compensated true heading in degrees at every main frame, from
+    /// `magADC` with `declination_deg` (east positive) added, as with
+    /// blackbox_decode's `--declination`
+    ///
+    /// Returns `None` without a magnetometer or the fields and headers
+    /// [`BBLLog::estimate_attitude`] needs.
+    pub fn magnetic_heading(&self, declination_deg: f64) -> Option<Vec<f64>> {
+        let has_mag = (0..3).all(|axis| {
+            self.frames
+                .field_index(&format!("magADC[{axis}]"))
+                .is_some()
+        });
+        if !has_mag {
+            return None;
+        }
+        Some(
+            self.estimate_attitude_with(declination_deg)?
+                .into_iter()
+                .map(|attitude| attitude.heading_deg)
+                .collect(),
+        )
+    }
+
+    fn estimate_attitude_with(&self, declination_deg: f64) -> Option<Vec<Attitude>> {
+        let frames = &self.frames;
+        let fields = |name: &str| -> Option<[usize; 3]> {
+            let mut columns = [0; 3];
+            for (axis, column) in columns.iter_mut().enumerate() {
+                *column = frames.field_index(&format!("{name}[{axis}]"))?;
+            }
+            Some(columns)
+        };
+        let gyro = fields("gyroADC")?;
+        let acc = fields("accSmooth")?;
+        let mag = fields("magADC");
+        let mut estimator =
+            AttitudeEstimator::for_header(&self.header)?.with_declination(declination_deg);
+
+        let timestamps = frames.timestamps();
+        let read = |row: usize, columns: [usize; 3]| {
+            columns.map(|column| frames.value(row, column).unwrap_or(0))
+        };
+        Some(
+            (0..frames.len())
+                .map(|row| {
+                    estimator.update_raw(
+                        timestamps[row],
+                        read(row, gyro),
+                        read(row, acc),
+                        mag.map(|mag| read(row, mag)),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attitude_estimator() {
+        // Held still at 30° of roll: the estimate follows the accelerometer
+        let mut estimator = AttitudeEstimator::new();
+        let tilted = [0.0, 30f64.to_radians().sin(), 30f64.to_radians().cos()];
+        let mut attitude = Attitude::default();
+        for i in 0..100 {
+            attitude = estimator.update(i * 1000, [0.0; 3], tilted, None);
+        }
+        assert!((attitude.roll_deg - 30.0).abs() < 1e-6, "{attitude:?}");
+        assert!(attitude.pitch_deg.abs() < 1e-6, "{attitude:?}");
+        assert_eq!(attitude.heading_deg, 0.0);
+
+        // Level and yawing at 90 deg/s for a second
+        let mut estimator = AttitudeEstimator::new();
+        for i in 0..=1000 {
+            attitude = estimator.update(i * 1000, [0.0, 0.0, 90.0], [0.0, 0.0, 1.0], None);
+        }
+        assert!((attitude.heading_deg - 270.0).abs() < 0.01, "{attitude:?}");
+        assert!(attitude.roll_deg.abs() < 1e-6, "{attitude:?}");
+
+        // Declination turns the magnetic heading of a level craft into true north
+        let mut compass = AttitudeEstimator::new().with_declination(-13.0);
+        for i in 0..10 {
+            attitude = compass.update(i * 1000, [0.0; 3], [0.0, 0.0, 1.0], Some([1.0, 0.0, 0.5]));
+        }
+        assert!((attitude.heading_deg - 347.0).abs() < 1e-6, "{attitude:?}");
+
+        // A 2 g pull-up is not taken for gravity
+        let before = estimator.update(1_001_000, [0.0; 3], [0.0, 0.0, 1.0], None);
+        let after = estimator.update(1_002_000, [0.0; 3], [0.0, 2.0, 0.0], None);
+        assert_eq!(before.roll_deg, after.roll_deg);
+    }
+
+    #[test]
+    fn test_estimate_attitude() {
+        use crate::types::SysConfigValue;
+
+        let mut log = BBLLog::new(1, 1);
+        assert_eq!(log.estimate_attitude(), None);
+        for i in 0..10 {
+            log.push_main_frame(
+                i * 1000,
+                0,
+                [
+                    ("gyroADC[0]", 0),
+                    ("gyroADC[1]", 0),
+                    ("gyroADC[2]", 0),
+                    ("accSmooth[0]", -2048),
+                    ("accSmooth[1]", 0),
+                    ("accSmooth[2]", 2048),
+                ],
+            );
+        }
+        assert_eq!(log.estimate_attitude(), None);
+
+        log.header
+            .sysconfig
+            .insert("acc_1G".to_string(), SysConfigValue::Int(2896));
+        let attitude = log.estimate_attitude().unwrap();
+        assert_eq!(attitude.len(), 10);
+        // Nose up 45°, with the gravity vector at about 1 g
+        assert!((attitude[9].pitch_deg - 45.0).abs() < 1e-6, "{attitude:?}");
+    }
+}
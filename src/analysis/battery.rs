@@ -0,0 +1,339 @@
+//! Battery analysis: cell count, per-cell voltage, voltage sag and charge used
+
+use crate::conversion::{convert_amperage_with_meter, convert_vbat_to_volts};
+use crate::types::BBLLog;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Cell voltage used for cell count detection when the log has no `vbatcellvoltage` header
+const DEFAULT_MAX_CELL_VOLTAGE: f32 = 4.3;
+
+/// Largest cell count Betaflight detects automatically
+const MAX_AUTO_DETECT_CELL_COUNT: u8 = 8;
+
+/// Lowest voltage taken as a connected battery
+pub(crate) const MIN_BATTERY_VOLTAGE: f32 = 1.0;
+
+/// Throttle at or below which the battery counts as resting
+const RESTING_THROTTLE_PERCENT: f32 = 10.0;
+
+/// Throttle at or above which the battery counts as loaded
+const LOADED_THROTTLE_PERCENT: f32 = 50.0;
+
+/// Running charge drawn from the battery, fed one current reading at a time
+///
+/// Each reading is charged for the time since the reading before it, the
+/// way the CSV's `energyCumulative (mAh)` column is computed.
+#[derive(Debug, Clone, Default)]
+pub struct EnergyIntegrator {
+    last_timestamp_us: u64,
+    mah: f32,
+}
+
+impl EnergyIntegrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a current reading in amps taken at `timestamp_us`; returns the total mAh
+    pub fn add(&mut self, timestamp_us: u64, amps: f32) -> f32 {
+        if self.last_timestamp_us > 0 && timestamp_us > self.last_timestamp_us {
+            let hours = (timestamp_us - self.last_timestamp_us) as f32 / 3_600_000_000.0;
+            self.mah += amps * hours * 1000.0;
+        }
+        self.last_timestamp_us = timestamp_us;
+        self.mah
+    }
+
+    /// Charge drawn so far in mAh
+    pub fn mah(&self) -> f32 {
+        self.mah
+    }
+}
+
+/// Battery behavior over a log ([`BBLLog::battery_analysis`])
+///
+/// Voltages are of the whole pack; the `_cell` figures divide them by the
+/// cell count when it is known.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BatteryAnalysis {
+    pub cell_count: Option<u8>,
+    pub min_voltage: f32,
+    pub max_voltage: f32,
+    /// Mean voltage at or below 10% throttle
+    pub resting_voltage: Option<f32>,
+    /// Mean voltage at or above 50% throttle
+    pub loaded_voltage: Option<f32>,
+    /// Largest drop below the last resting voltage while loaded
+    pub max_sag: Option<f32>,
+    /// Voltage lost going from zero to full throttle, fitted over all frames
+    pub sag_per_full_throttle: Option<f32>,
+    pub min_cell_voltage: Option<f32>,
+    pub resting_cell_voltage: Option<f32>,
+    pub loaded_cell_voltage: Option<f32>,
+    /// Charge drawn from the battery ([`BBLLog::mah_used`])
+    pub mah_used: Option<f32>,
+}
+
+impl BBLLog {
+    /// Number of battery cells.
+    ///
+    /// Uses an explicit `batteryCellCount` header when the firmware logs one.
+    /// Otherwise the count is detected from the first battery voltage the way
+    /// Betaflight does at arming: the voltage divided by the maximum cell
+    /// voltage (`vbatcellvoltage` header, 4.3 V by default), plus one. Returns
+    /// `None` when the log has no `vbatLatest` field or no battery voltage.
+    pub fn cell_count(&self) -> Option<u8> {
+        if let Some(cells) = self.header.sysconfig_int("batteryCellCount") {
+            return u8::try_from(cells).ok().filter(|&cells| cells > 0);
+        }
+        let firmware = &self.header.firmware_revision;
+        let voltage = self
+            .frames
+            .column("vbatLatest")?
+            .iter()
+            .map(|&raw| convert_vbat_to_volts(raw, firmware))
+            .find(|&volts| volts >= MIN_BATTERY_VOLTAGE)?;
+        let max_cell_voltage = self
+            .header
+            .max_cell_voltage()
+            .unwrap_or(DEFAULT_MAX_CELL_VOLTAGE);
+        let cells = (voltage / max_cell_voltage) as u8 + 1;
+        Some(cells.min(MAX_AUTO_DETECT_CELL_COUNT))
+    }
+
+    /// Battery voltage per cell, in volts, for every main frame.
+    ///
+    /// Returns `None` when [`BBLLog::cell_count`] cannot tell the cell count.
+    pub fn vbat_per_cell(&self) -> Option<Vec<f32>> {
+        let cells = self.cell_count()? as f32;
+        let firmware = &self.header.firmware_revision;
+        Some(
+            self.frames
+                .column("vbatLatest")?
+                .iter()
+                .map(|&raw| convert_vbat_to_volts(raw, firmware) / cells)
+                .collect(),
+        )
+    }
+
+    /// Charge drawn from the battery in mAh up to every main frame, the
+    /// values of the CSV's `energyCumulative (mAh)` column ([`EnergyIntegrator`]).
+    ///
+    /// Returns `None` when the log has no `amperageLatest` field.
+    pub fn energy_cumulative_mah(&self) -> Option<Vec<f32>> {
+        let firmware = &self.header.firmware_revision;
+        let current_meter = self.header.current_meter();
+        let amperage = self.frames.column("amperageLatest")?;
+        let mut energy = EnergyIntegrator::new();
+        Some(
+            amperage
+                .iter()
+                .zip(self.frames.timestamps())
+                .map(|(&raw, &timestamp_us)| {
+                    let amps = convert_amperage_with_meter(raw, firmware, current_meter);
+                    energy.add(timestamp_us, amps)
+                })
+                .collect(),
+        )
+    }
+
+    /// Charge drawn from the battery over the whole log in mAh
+    ///
+    /// Returns `None` when the log has no `amperageLatest` field.
+    pub fn mah_used(&self) -> Option<f32> {
+        Some(self.energy_cumulative_mah()?.last().copied().unwrap_or(0.0))
+    }
+
+    /// Voltage range, resting and loaded voltage, sag under throttle, per-cell
+    /// figures and charge used.
+    ///
+    /// Frames are classed as resting or loaded by [`BBLLog::throttle_percent`].
+    /// Returns `None` when the log has no battery voltage.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bbl_parser::{parse_bbl_file, ExportOptions};
+    /// use std::path::Path;
+    ///
+    /// let log = parse_bbl_file(Path::new("flight.BBL"), ExportOptions::default(), false).unwrap();
+    /// if let Some(battery) = log.battery_analysis() {
+    ///     println!("max sag {:?} V, used {:?} mAh", battery.max_sag, battery.mah_used);
+    /// }
+    /// ```
+    pub fn battery_analysis(&self) -> Option<BatteryAnalysis> {
+        let firmware = &self.header.firmware_revision;
+        let throttle = self.throttle_percent();
+        // (volts, throttle percent) of every frame with a battery connected
+        let readings: Vec<(f32, Option<f32>)> = self
+            .frames
+            .column("vbatLatest")?
+            .iter()
+            .enumerate()
+            .map(|(row, &raw)| {
+                let percent = throttle.as_ref().map(|throttle| throttle[row].abs());
+                (convert_vbat_to_volts(raw, firmware), percent)
+            })
+            .filter(|&(volts, _)| volts >= MIN_BATTERY_VOLTAGE)
+            .collect();
+        let (first, _) = *readings.first()?;
+
+        let mean = |values: &[f32]| {
+            (!values.is_empty()).then(|| values.iter().sum::<f32>() / values.len() as f32)
+        };
+        let mut resting = Vec::new();
+        let mut loaded = Vec::new();
+        let mut last_resting = None;
+        let mut max_sag: Option<f32> = None;
+        for &(volts, percent) in &readings {
+            match percent {
+                Some(percent) if percent <= RESTING_THROTTLE_PERCENT => {
+                    resting.push(volts);
+                    last_resting = Some(volts);
+                }
+                Some(percent) if percent >= LOADED_THROTTLE_PERCENT => {
+                    loaded.push(volts);
+                    if let Some(rest) = last_resting {
+                        max_sag = Some(max_sag.unwrap_or(0.0).max(rest - volts));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let cell_count = self.cell_count();
+        let per_cell = |volts: Option<f32>| Some(volts? / f32::from(cell_count?));
+        let min_voltage = readings
+            .iter()
+            .map(|&(volts, _)| volts)
+            .fold(first, f32::min);
+        let resting_voltage = mean(&resting);
+        let loaded_voltage = mean(&loaded);
+        Some(BatteryAnalysis {
+            cell_count,
+            min_voltage,
+            max_voltage: readings
+                .iter()
+                .map(|&(volts, _)| volts)
+                .fold(first, f32::max),
+            resting_voltage,
+            loaded_voltage,
+            max_sag,
+            sag_per_full_throttle: voltage_throttle_slope(&readings).map(|slope| -slope * 100.0),
+            min_cell_voltage: per_cell(Some(min_voltage)),
+            resting_cell_voltage: per_cell(resting_voltage),
+            loaded_cell_voltage: per_cell(loaded_voltage),
+            mah_used: self.mah_used(),
+        })
+    }
+}
+
+/// Least-squares slope of voltage over throttle percent, in volts per percent
+fn voltage_throttle_slope(readings: &[(f32, Option<f32>)]) -> Option<f32> {
+    let points: Vec<(f64, f64)> = readings
+        .iter()
+        .filter_map(|&(volts, percent)| Some((f64::from(percent?), f64::from(volts))))
+        .collect();
+    let count = points.len() as f64;
+    let mean_throttle = points.iter().map(|&(x, _)| x).sum::<f64>() / count;
+    let mean_volts = points.iter().map(|&(_, y)| y).sum::<f64>() / count;
+    let (covariance, variance) =
+        points
+            .iter()
+            .fold((0.0, 0.0), |(covariance, variance), &(x, y)| {
+                let dx = x - mean_throttle;
+                (covariance + dx * (y - mean_volts), variance + dx * dx)
+            });
+    (variance > 0.0).then(|| (covariance / variance) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_with_vbat(raw_values: &[i32]) -> BBLLog {
+        let mut log =
+            BBLLog::with_main_frames(raw_values.iter().map(|&raw| (0, [("vbatLatest", raw)])));
+        log.header.firmware_revision = "Betaflight 4.5.1 (77d01ba3b) STM32F7X2".to_string();
+        log
+    }
+
+    #[test]
+    fn test_cell_count() {
+        assert_eq!(BBLLog::new(1, 1).cell_count(), None);
+        assert_eq!(log_with_vbat(&[0, 50]).cell_count(), None);
+
+        // Readings without a battery are skipped; 16.8 V is a full 4S pack
+        let mut log = log_with_vbat(&[0, 30, 1680, 1400]);
+        assert_eq!(log.cell_count(), Some(4));
+        let per_cell = log.vbat_per_cell().unwrap();
+        for (volts, expected) in per_cell.iter().zip([0.0, 0.075, 4.2, 3.5]) {
+            assert!((volts - expected).abs() < 0.001, "{per_cell:?}");
+        }
+
+        // A charged 6S HV pack reads above 6 x 4.3 V
+        let mut hv_log = log_with_vbat(&[2590]);
+        assert_eq!(hv_log.cell_count(), Some(7));
+        hv_log.header.sysconfig = crate::parser::header::parse_headers_from_text(
+            "H vbatcellvoltage:330,350,435\n",
+            crate::parser::diagnostics::DiagnosticReporter::silent(),
+        )
+        .unwrap()
+        .sysconfig;
+        assert_eq!(hv_log.header.max_cell_voltage(), Some(4.35));
+        assert_eq!(hv_log.cell_count(), Some(6));
+
+        log.header
+            .sysconfig
+            .insert("batteryCellCount".to_string(), 6.into());
+        assert_eq!(log.cell_count(), Some(6));
+    }
+
+    #[test]
+    fn test_battery_analysis() {
+        let mut log = BBLLog::new(1, 1);
+        log.header.firmware_revision = "Betaflight 4.5.1 (77d01ba3b) STM32F7X2".to_string();
+        assert_eq!(log.battery_analysis(), None);
+        let rows = [
+            (1_000_000, 1680, 1000, 100),
+            (2_000_000, 1560, 1800, 3000),
+            (3_000_000, 1640, 1050, 200),
+            (4_000_000, 1500, 2000, 4000),
+        ];
+        for (time, vbat, throttle, amperage) in rows {
+            log.push_main_frame(
+                time,
+                0,
+                [
+                    ("vbatLatest", vbat),
+                    ("rcCommand[3]", throttle),
+                    ("amperageLatest", amperage),
+                ],
+            );
+        }
+
+        let energy = log.energy_cumulative_mah().unwrap();
+        for (mah, expected) in energy.iter().zip([0.0, 8.333, 8.889, 20.0]) {
+            assert!((mah - expected).abs() < 0.001, "{energy:?}");
+        }
+
+        let battery = log.battery_analysis().unwrap();
+        let close = |value: Option<f32>, expected: f32| {
+            assert!((value.unwrap() - expected).abs() < 0.001, "{battery:?}");
+        };
+        assert_eq!(battery.cell_count, Some(4));
+        close(Some(battery.min_voltage), 15.0);
+        close(Some(battery.max_voltage), 16.8);
+        close(battery.resting_voltage, 16.6);
+        close(battery.loaded_voltage, 15.3);
+        // 16.4 V at rest, then 15.0 V at full throttle
+        close(battery.max_sag, 1.4);
+        close(battery.sag_per_full_throttle, 1.5345);
+        close(battery.min_cell_voltage, 3.75);
+        close(battery.resting_cell_voltage, 4.15);
+        close(battery.loaded_cell_voltage, 3.825);
+        close(battery.mah_used, 20.0);
+    }
+}
@@ -0,0 +1,200 @@
+//! Climb rate (vario) from barometer or GPS altitude
+
+use crate::types::BBLLog;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Smoothing time constant of [`BBLLog::climb_rate`] and the CSV's
+/// `climbRate (m/s)` column
+pub const DEFAULT_CLIMB_RATE_TIME_CONSTANT_S: f64 = 0.5;
+
+/// Altitude a climb rate is derived from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AltitudeSource {
+    /// `baroAlt` of the main frames
+    Baro,
+    /// Altitude of the GPS fixes
+    Gps,
+}
+
+/// Smoothed vertical speed, one value per altitude sample ([`BBLLog::climb_rate`])
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClimbRate {
+    pub source: AltitudeSource,
+    pub timestamps_us: Vec<u64>,
+    /// Positive when climbing
+    pub climb_rate_m_s: Vec<f64>,
+}
+
+impl ClimbRate {
+    /// Climb rate of the last sample at or before `timestamp_us`, for
+    /// time-ordered samples
+    pub fn at(&self, timestamp_us: u64) -> Option<f64> {
+        let after = self
+            .timestamps_us
+            .partition_point(|&sample_us| sample_us <= timestamp_us);
+        self.climb_rate_m_s.get(after.checked_sub(1)?).copied()
+    }
+}
+
+/// Vertical speed estimator fed one altitude at a time
+///
+/// The altitude is low-pass filtered before it is differentiated, and the
+/// derivative is filtered again, both with the same time constant; a steady
+/// climb is reported after a few time constants.
+#[derive(Debug, Clone)]
+pub struct ClimbRateFilter {
+    time_constant_s: f64,
+    last: Option<(u64, f64)>,
+    climb_rate_m_s: f64,
+}
+
+impl ClimbRateFilter {
+    pub fn new(time_constant_s: f64) -> Self {
+        Self {
+            time_constant_s: time_constant_s.max(0.0),
+            last: None,
+            climb_rate_m_s: 0.0,
+        }
+    }
+
+    /// Add an altitude in meters measured at `timestamp_us`; returns the
+    /// climb rate in m/s
+    pub fn add(&mut self, timestamp_us: u64, altitude_m: f64) -> f64 {
+        match self.last {
+            None => self.last = Some((timestamp_us, altitude_m)),
+            // Samples out of time order carry no rate
+            Some((last_us, filtered_m)) if timestamp_us > last_us => {
+                let elapsed_s = (timestamp_us - last_us) as f64 / 1e6;
+                let alpha = elapsed_s / (self.time_constant_s + elapsed_s);
+                let altitude = filtered_m + alpha * (altitude_m - filtered_m);
+                let rate = (altitude - filtered_m) / elapsed_s;
+                self.climb_rate_m_s += alpha * (rate - self.climb_rate_m_s);
+                self.last = Some((timestamp_us, altitude));
+            }
+            Some(_) => {}
+        }
+        self.climb_rate_m_s
+    }
+}
+
+impl BBLLog {
+    /// Smoothed climb rate from `baroAlt`, or from the GPS fixes when the log
+    /// has no barometer; `None` without either
+    ///
+    /// `time_constant_s` sets the smoothing ([`ClimbRateFilter`]); see
+    /// [`DEFAULT_CLIMB_RATE_TIME_CONSTANT_S`].
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bbl_parser::{parse_bbl_file, ExportOptions, DEFAULT_CLIMB_RATE_TIME_CONSTANT_S};
+    /// use std::path::Path;
+    ///
+    /// let log = parse_bbl_file(Path::new("flight.BBL"), ExportOptions::default(), false).unwrap();
+    /// if let Some(vario) = log.climb_rate(DEFAULT_CLIMB_RATE_TIME_CONSTANT_S) {
+    ///     let fastest = vario.climb_rate_m_s.iter().copied().fold(0.0, f64::max);
+    ///     println!("Fastest climb {fastest:.1} m/s ({:?})", vario.source);
+    /// }
+    /// ```
+    pub fn climb_rate(&self, time_constant_s: f64) -> Option<ClimbRate> {
+        self.climb_rate_from(AltitudeSource::Baro, time_constant_s)
+            .or_else(|| self.climb_rate_from(AltitudeSource::Gps, time_constant_s))
+    }
+
+    /// Smoothed climb rate from one altitude source; `None` when the log has
+    /// no `baroAlt` field or no GPS fixes
+    pub fn climb_rate_from(
+        &self,
+        source: AltitudeSource,
+        time_constant_s: f64,
+    ) -> Option<ClimbRate> {
+        let samples: Vec<(u64, f64)> = match source {
+            AltitudeSource::Baro => self
+                .frames
+                .column("baroAlt")?
+                .iter()
+                .zip(self.frames.timestamps())
+                .map(|(&centimeters, &timestamp_us)| (timestamp_us, centimeters as f64 / 100.0))
+                .collect(),
+            AltitudeSource::Gps => self
+                .gps_coordinates
+                .iter()
+                .map(|fix| (fix.timestamp_us, fix.altitude))
+                .collect(),
+        };
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut filter = ClimbRateFilter::new(time_constant_s);
+        Some(ClimbRate {
+            source,
+            timestamps_us: samples
+                .iter()
+                .map(|&(timestamp_us, _)| timestamp_us)
+                .collect(),
+            climb_rate_m_s: samples
+                .iter()
+                .map(|&(timestamp_us, altitude_m)| filter.add(timestamp_us, altitude_m))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::GpsCoordinate;
+
+    #[test]
+    fn test_climb_rate_filter() {
+        // Climbing at 2 m/s for 10 s, sampled at 100 Hz
+        let mut filter = ClimbRateFilter::new(0.5);
+        let mut rate = 0.0;
+        for i in 0..=1000u64 {
+            rate = filter.add(i * 10_000, i as f64 * 0.02);
+        }
+        assert!((rate - 2.0).abs() < 1e-6, "{rate}");
+
+        // A repeated timestamp changes nothing
+        assert_eq!(filter.add(10_000_000, 50.0), rate);
+    }
+
+    #[test]
+    fn test_climb_rate_sources() {
+        let mut log = BBLLog::new(1, 1);
+        assert_eq!(log.climb_rate(0.5), None);
+
+        log.gps_coordinates = (0..50u64)
+            .map(|i| GpsCoordinate {
+                latitude: 45.0,
+                longitude: 10.0,
+                altitude: 100.0 - i as f64 * 0.1,
+                timestamp_us: i * 100_000,
+                num_sats: Some(10),
+                speed: None,
+                ground_course: None,
+            })
+            .collect();
+        let vario = log.climb_rate(0.5).unwrap();
+        assert_eq!(vario.source, AltitudeSource::Gps);
+        assert_eq!(vario.climb_rate_m_s[0], 0.0);
+        let sinking = vario.at(4_950_000).unwrap();
+        assert!((sinking + 1.0).abs() < 0.01, "{sinking}");
+        assert_eq!(vario.at(4_950_000), vario.climb_rate_m_s.last().copied());
+
+        // The barometer is preferred
+        for i in 0..3u64 {
+            log.push_main_frame(i * 1_000_000, 0, [("baroAlt", i as i32 * 100)]);
+        }
+        let vario = log.climb_rate(0.5).unwrap();
+        assert_eq!(vario.source, AltitudeSource::Baro);
+        assert_eq!(vario.climb_rate_m_s.len(), 3);
+        assert!(vario.climb_rate_m_s[2] > 0.0);
+        assert_eq!(vario.at(0), Some(0.0));
+    }
+}
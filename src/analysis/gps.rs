@@ -0,0 +1,220 @@
+//! Distances derived from GPS fixes and the home position
+
+use crate::analysis::summary::{gps_distance_m, haversine_distance_m};
+use crate::types::{BBLLog, GpsCoordinate, GpsHomeCoordinate};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Position of a GPS fix relative to home ([`BBLLog::home_distances`])
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HomeDistance {
+    pub timestamp_us: u64,
+    /// Ground distance from home
+    pub distance_m: f64,
+    /// Height above the home altitude
+    pub height_m: f64,
+    /// Straight-line distance from home, including the height
+    pub distance_3d_m: f64,
+}
+
+/// Trip figures of the GPS track ([`BBLLog::gps_trip_stats`])
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GpsTripStats {
+    /// Path length through the fixes
+    pub distance_m: f64,
+    /// Farthest ground distance from home, when the log has a home position
+    pub max_home_distance_m: Option<f64>,
+    /// Highest GPS ground speed
+    pub max_speed_m_s: Option<f64>,
+    /// Highest GPS altitude above home, or above the first fix without a home
+    pub max_altitude_m: f64,
+}
+
+/// Measures fixes against the home positions of H frames
+///
+/// Each fix is compared with the last home set at or before it (the first
+/// home for earlier fixes). H frames carry no altitude, so home is taken to
+/// be at the altitude of the first fix logged once home was set.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct HomeReference<'a> {
+    homes: &'a [GpsHomeCoordinate],
+    altitude_m: f64,
+}
+
+impl<'a> HomeReference<'a> {
+    /// `None` without a home position or GPS fixes
+    pub(crate) fn new(fixes: &[GpsCoordinate], homes: &'a [GpsHomeCoordinate]) -> Option<Self> {
+        let first_home = homes.first()?;
+        let home_fix = fixes
+            .iter()
+            .find(|fix| fix.timestamp_us >= first_home.timestamp_us)
+            .or(fixes.first())?;
+        Some(Self {
+            homes,
+            altitude_m: home_fix.altitude,
+        })
+    }
+
+    pub(crate) fn distance(&self, fix: &GpsCoordinate) -> HomeDistance {
+        let current = self
+            .homes
+            .partition_point(|home| home.timestamp_us <= fix.timestamp_us);
+        let home = &self.homes[current.saturating_sub(1)];
+        let distance_m = haversine_distance_m(
+            (home.home_latitude, home.home_longitude),
+            (fix.latitude, fix.longitude),
+        );
+        let height_m = fix.altitude - self.altitude_m;
+        HomeDistance {
+            timestamp_us: fix.timestamp_us,
+            distance_m,
+            height_m,
+            distance_3d_m: distance_m.hypot(height_m),
+        }
+    }
+}
+
+impl BBLLog {
+    /// Distance from home of every GPS fix, in fix order
+    ///
+    /// Returns `None` when the log has no home position (H frame) or no GPS
+    /// fixes. Heights are relative to the first fix after home was set.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bbl_parser::{parse_bbl_file, ExportOptions};
+    /// use std::path::Path;
+    ///
+    /// let log = parse_bbl_file(Path::new("flight.BBL"), ExportOptions::default(), false).unwrap();
+    /// if let Some(distances) = log.home_distances() {
+    ///     let farthest = distances.iter().map(|d| d.distance_m).fold(0.0, f64::max);
+    ///     println!("Farthest from home: {farthest:.0} m");
+    /// }
+    /// ```
+    pub fn home_distances(&self) -> Option<Vec<HomeDistance>> {
+        let reference = HomeReference::new(&self.gps_coordinates, &self.home_coordinates)?;
+        Some(
+            self.gps_coordinates
+                .iter()
+                .map(|fix| reference.distance(fix))
+                .collect(),
+        )
+    }
+
+    /// Distance flown, farthest distance from home, top speed and highest
+    /// altitude of the GPS track, or `None` without GPS fixes
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bbl_parser::{parse_bbl_file, ExportOptions};
+    /// use std::path::Path;
+    ///
+    /// let log = parse_bbl_file(Path::new("flight.BBL"), ExportOptions::default(), false).unwrap();
+    /// if let Some(trip) = log.gps_trip_stats() {
+    ///     println!("{:.0} m flown, {:?} m from home at most", trip.distance_m, trip.max_home_distance_m);
+    /// }
+    /// ```
+    pub fn gps_trip_stats(&self) -> Option<GpsTripStats> {
+        let fixes = &self.gps_coordinates;
+        let first = fixes.first()?;
+        let home = HomeReference::new(fixes, &self.home_coordinates);
+        let base_altitude_m = home.map_or(first.altitude, |home| home.altitude_m);
+        Some(GpsTripStats {
+            distance_m: gps_distance_m(fixes).unwrap_or(0.0),
+            max_home_distance_m: home.and_then(|home| {
+                fixes
+                    .iter()
+                    .map(|fix| home.distance(fix).distance_m)
+                    .reduce(f64::max)
+            }),
+            max_speed_m_s: fixes.iter().filter_map(|fix| fix.speed).reduce(f64::max),
+            max_altitude_m: fixes
+                .iter()
+                .map(|fix| fix.altitude - base_altitude_m)
+                .fold(f64::MIN, f64::max),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(timestamp_us: u64, latitude: f64, altitude: f64) -> GpsCoordinate {
+        GpsCoordinate {
+            latitude,
+            longitude: 10.0,
+            altitude,
+            timestamp_us,
+            num_sats: Some(10),
+            speed: None,
+            ground_course: None,
+        }
+    }
+
+    fn home(timestamp_us: u64, home_latitude: f64) -> GpsHomeCoordinate {
+        GpsHomeCoordinate {
+            home_latitude,
+            home_longitude: 10.0,
+            timestamp_us,
+        }
+    }
+
+    #[test]
+    fn test_home_distances() {
+        let mut log = BBLLog::new(1, 1);
+        log.gps_coordinates = vec![
+            fix(0, 45.0, 0.0),
+            fix(1_000_000, 45.0, 120.0),
+            fix(2_000_000, 45.001, 160.0),
+            fix(3_000_000, 45.001, 160.0),
+        ];
+        assert_eq!(log.home_distances(), None);
+
+        // Home set after the first fix, then moved north at the third
+        log.home_coordinates = vec![home(500_000, 45.0), home(3_000_000, 45.001)];
+        let distances = log.home_distances().unwrap();
+        assert_eq!(distances.len(), 4);
+        assert_eq!(distances[0].distance_m, 0.0);
+        assert_eq!(distances[0].height_m, -120.0);
+        assert_eq!(distances[1].distance_3d_m, 0.0);
+        // 0.001° of latitude is about 111 m
+        assert!(
+            (distances[2].distance_m - 111.2).abs() < 0.1,
+            "{distances:?}"
+        );
+        assert_eq!(distances[2].height_m, 40.0);
+        let expected_3d = distances[2].distance_m.hypot(40.0);
+        assert_eq!(distances[2].distance_3d_m, expected_3d);
+        assert_eq!(distances[3].distance_m, 0.0);
+    }
+
+    #[test]
+    fn test_gps_trip_stats() {
+        let mut log = BBLLog::new(1, 1);
+        assert_eq!(log.gps_trip_stats(), None);
+
+        log.gps_coordinates = vec![
+            fix(0, 45.0, 100.0),
+            fix(1_000_000, 45.002, 150.0),
+            fix(2_000_000, 45.001, 130.0),
+        ];
+        log.gps_coordinates[1].speed = Some(14.5);
+        let trip = log.gps_trip_stats().unwrap();
+        // Out 0.002° and back 0.001° of latitude
+        assert!((trip.distance_m - 333.6).abs() < 0.1, "{trip:?}");
+        assert_eq!(trip.max_home_distance_m, None);
+        assert_eq!(trip.max_speed_m_s, Some(14.5));
+        assert_eq!(trip.max_altitude_m, 50.0);
+
+        // Home set at the second fix
+        log.home_coordinates = vec![home(1_000_000, 45.002)];
+        let trip = log.gps_trip_stats().unwrap();
+        let farthest = trip.max_home_distance_m.unwrap();
+        assert!((farthest - 222.4).abs() < 0.1, "{trip:?}");
+        assert_eq!(trip.max_altitude_m, 0.0);
+    }
+}
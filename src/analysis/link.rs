@@ -0,0 +1,288 @@
+//! Radio link health: failsafe, RX signal loss and RSSI
+
+use crate::conversion::convert_rssi_to_percent;
+use crate::types::BBLLog;
+use anyhow::{anyhow, Result};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Failsafe and RX loss figures of a log ([`BBLLog::link_health`])
+///
+/// A failsafe lasts while `failsafePhase` is not idle; an RX loss lasts while
+/// `rxSignalReceived` or `rxFlightChannelsValid` is 0. Periods still running
+/// at the end of the log end at the last frame.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LinkHealth {
+    /// Times the failsafe engaged
+    pub failsafe_entries: u32,
+    pub failsafe_time_us: u64,
+    /// Times the receiver lost the signal or valid channels
+    pub rx_losses: u32,
+    pub rx_loss_time_us: u64,
+    pub longest_rx_loss_us: u64,
+}
+
+/// Distribution and downsampled series of the `rssi` field
+/// ([`BBLLog::rssi_stats`]), in percent
+///
+/// Receivers that report link quality instead of signal strength on the RSSI
+/// channel log LQ here, so the same figures serve for range tests with them.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RssiStats {
+    /// Main frames with an `rssi` value
+    pub count: usize,
+    pub min_percent: f32,
+    pub max_percent: f32,
+    pub mean_percent: f32,
+    /// Level the link stayed at or above in 99% of the frames
+    pub p1_percent: f32,
+    /// Level the link stayed at or above in 95% of the frames
+    pub p5_percent: f32,
+    pub median_percent: f32,
+    /// Time of the first series sample, the first frame with a value
+    pub series_start_us: u64,
+    pub series_rate_hz: f64,
+    /// Mean of the frames in each `1 / series_rate_hz` interval; intervals
+    /// without frames repeat the previous value
+    pub series: Vec<f32>,
+}
+
+impl BBLLog {
+    /// Failsafe entries and time, and RX losses with their total and longest
+    /// duration, from the slow-frame fields merged into the main frames.
+    ///
+    /// Returns `None` when the log has none of the `failsafePhase`,
+    /// `rxSignalReceived` and `rxFlightChannelsValid` fields.
+    pub fn link_health(&self) -> Option<LinkHealth> {
+        let frames = &self.frames;
+        let failsafe = frames.field_index("failsafePhase");
+        let rx_fields: Vec<usize> = ["rxSignalReceived", "rxFlightChannelsValid"]
+            .iter()
+            .filter_map(|name| frames.field_index(name))
+            .collect();
+        if failsafe.is_none() && rx_fields.is_empty() {
+            return None;
+        }
+
+        let rows = frames.rows_in_range(0, u64::MAX);
+        let failsafes = span_durations_us(self, &rows, |row| {
+            failsafe
+                .and_then(|field| frames.value(row, field))
+                .map(|phase| phase != 0)
+        });
+        let rx_losses = span_durations_us(self, &rows, |row| {
+            let values: Vec<i32> = rx_fields
+                .iter()
+                .filter_map(|&field| frames.value(row, field))
+                .collect();
+            (!values.is_empty()).then(|| values.contains(&0))
+        });
+
+        Some(LinkHealth {
+            failsafe_entries: failsafes.len() as u32,
+            failsafe_time_us: failsafes.iter().sum(),
+            rx_losses: rx_losses.len() as u32,
+            rx_loss_time_us: rx_losses.iter().sum(),
+            longest_rx_loss_us: rx_losses.iter().copied().max().unwrap_or(0),
+        })
+    }
+
+    /// Min, max, mean and percentiles of the logged RSSI, with a series of
+    /// `series_rate_hz` samples per second for plotting.
+    ///
+    /// Returns `Ok(None)` when the log has no `rssi` field, and fails when
+    /// `series_rate_hz` is not positive.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bbl_parser::{parse_bbl_file, ExportOptions};
+    /// use std::path::Path;
+    ///
+    /// let log = parse_bbl_file(Path::new("range.BBL"), ExportOptions::default(), false).unwrap();
+    /// if let Some(rssi) = log.rssi_stats(1.0).unwrap() {
+    ///     println!("RSSI min {:.0}%, 5th percentile {:.0}%", rssi.min_percent, rssi.p5_percent);
+    /// }
+    /// ```
+    pub fn rssi_stats(&self, series_rate_hz: f64) -> Result<Option<RssiStats>> {
+        if !(series_rate_hz.is_finite() && series_rate_hz > 0.0) {
+            return Err(anyhow!(
+                "RSSI series rate must be positive, got {series_rate_hz} Hz"
+            ));
+        }
+        let frames = &self.frames;
+        let Some(field) = frames.field_index("rssi") else {
+            return Ok(None);
+        };
+        let timestamps = frames.timestamps();
+        let samples: Vec<(u64, f32)> = frames
+            .rows_in_range(0, u64::MAX)
+            .into_iter()
+            .filter_map(|row| {
+                let raw = frames.value(row, field)?;
+                Some((timestamps[row], convert_rssi_to_percent(raw)))
+            })
+            .collect();
+        let (Some(&(start_us, _)), Some(&(end_us, _))) = (samples.first(), samples.last()) else {
+            return Ok(None);
+        };
+
+        let mut sorted: Vec<f32> = samples.iter().map(|&(_, percent)| percent).collect();
+        sorted.sort_by(f32::total_cmp);
+        let percentile = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+        let mean = samples
+            .iter()
+            .map(|&(_, percent)| percent as f64)
+            .sum::<f64>()
+            / samples.len() as f64;
+
+        let interval_us = 1_000_000.0 / series_rate_hz;
+        let buckets = ((end_us - start_us) as f64 / interval_us) as usize + 1;
+        let mut sums = vec![(0.0, 0usize); buckets];
+        for &(time_us, percent) in &samples {
+            let bucket = ((time_us - start_us) as f64 / interval_us) as usize;
+            let (sum, count) = &mut sums[bucket.min(buckets - 1)];
+            *sum += percent as f64;
+            *count += 1;
+        }
+        let mut previous = samples[0].1;
+        let series = sums
+            .into_iter()
+            .map(|(sum, count)| {
+                if count > 0 {
+                    previous = (sum / count as f64) as f32;
+                }
+                previous
+            })
+            .collect();
+
+        Ok(Some(RssiStats {
+            count: samples.len(),
+            min_percent: sorted[0],
+            max_percent: sorted[sorted.len() - 1],
+            mean_percent: mean as f32,
+            p1_percent: percentile(0.01),
+            p5_percent: percentile(0.05),
+            median_percent: percentile(0.5),
+            series_start_us: start_us,
+            series_rate_hz,
+            series,
+        }))
+    }
+}
+
+/// `(start, end)` times of the runs of `rows` (in time order) for which
+/// `active` is true; rows where it is `None` keep the previous state. A run
+/// ends at the first inactive row, or at the last row.
+pub(crate) fn active_spans(
+    log: &BBLLog,
+    rows: &[usize],
+    active: impl Fn(usize) -> Option<bool>,
+) -> Vec<(u64, u64)> {
+    let timestamps = log.frames.timestamps();
+    let mut spans = Vec::new();
+    let mut started_us = None;
+    for &row in rows {
+        let time_us = timestamps[row];
+        match (active(row), started_us) {
+            (Some(true), None) => started_us = Some(time_us),
+            (Some(false), Some(start_us)) => {
+                spans.push((start_us, time_us));
+                started_us = None;
+            }
+            _ => {}
+        }
+    }
+    if let (Some(start_us), Some(&last)) = (started_us, rows.last()) {
+        spans.push((start_us, timestamps[last]));
+    }
+    spans
+}
+
+/// Durations of the [`active_spans`]
+fn span_durations_us(
+    log: &BBLLog,
+    rows: &[usize],
+    active: impl Fn(usize) -> Option<bool>,
+) -> Vec<u64> {
+    active_spans(log, rows, active)
+        .into_iter()
+        .map(|(start_us, end_us)| end_us - start_us)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_health() {
+        let mut log = BBLLog::new(1, 1);
+        assert_eq!(log.link_health(), None);
+
+        // (time, failsafePhase, rxSignalReceived, rxFlightChannelsValid)
+        let rows = [
+            (1_000, 0, 1, 1),
+            (2_000, 0, 0, 1),
+            (3_000, 1, 0, 0),
+            (4_000, 2, 0, 0),
+            (5_000, 0, 1, 1),
+            (6_000, 0, 1, 0),
+            (7_000, 0, 1, 1),
+            (8_000, 4, 1, 1),
+        ];
+        for (time, phase, signal, channels) in rows {
+            log.push_main_frame(
+                time,
+                0,
+                [
+                    ("failsafePhase", phase),
+                    ("rxSignalReceived", signal),
+                    ("rxFlightChannelsValid", channels),
+                ],
+            );
+        }
+
+        assert_eq!(
+            log.link_health(),
+            Some(LinkHealth {
+                failsafe_entries: 2,
+                failsafe_time_us: 2_000,
+                rx_losses: 2,
+                rx_loss_time_us: 4_000,
+                longest_rx_loss_us: 3_000,
+            })
+        );
+    }
+
+    #[test]
+    fn test_rssi_stats() {
+        let log = BBLLog::with_main_frames([(0, [("gyroADC[0]", 0)])]);
+        assert_eq!(log.rssi_stats(1.0).unwrap(), None);
+
+        // 100% for a second, then 0% to 90% over 0.9 s, then nothing until 3 s
+        let rows = (0..10)
+            .map(|i: u64| (i * 100_000, 1023))
+            .chain((0..10).map(|i: i32| (1_000_000 + i as u64 * 100_000, i * 1023 / 10)))
+            .chain([(3_000_000, 1023)]);
+        let log = BBLLog::with_main_frames(rows.map(|(time, rssi)| (time, [("rssi", rssi)])));
+
+        let stats = log.rssi_stats(1.0).unwrap().unwrap();
+        assert_eq!(stats.count, 21);
+        assert_eq!((stats.min_percent, stats.max_percent), (0.0, 100.0));
+        assert_eq!(stats.p1_percent, 0.0);
+        assert!((stats.p5_percent - 9.97).abs() < 0.01, "{stats:?}");
+        assert_eq!(stats.median_percent, 100.0);
+        assert!((stats.mean_percent - 73.79).abs() < 0.01, "{stats:?}");
+        assert_eq!(stats.series_start_us, 0);
+        assert_eq!(stats.series.len(), 4);
+        assert_eq!(stats.series[0], 100.0);
+        assert!((stats.series[1] - 44.96).abs() < 0.01, "{stats:?}");
+        assert_eq!(stats.series[2], stats.series[1]);
+        assert_eq!(stats.series[3], 100.0);
+
+        assert!(log.rssi_stats(0.0).is_err());
+    }
+}
@@ -0,0 +1,33 @@
+//! Post-parse analysis of decoded logs
+//!
+//! These helpers work on a fully parsed [`crate::BBLLog`] and never modify it.
+
+pub mod attitude;
+pub mod battery;
+pub mod climb;
+pub mod gps;
+pub mod link;
+pub mod motors;
+pub mod resample;
+pub mod setpoint;
+#[cfg(feature = "spectrum")]
+pub mod spectrum;
+pub mod stats;
+#[cfg(feature = "spectrum")]
+pub mod step_response;
+pub mod summary;
+pub mod throttle;
+
+pub use attitude::*;
+pub use battery::*;
+pub use climb::*;
+pub use gps::*;
+pub use link::*;
+pub use motors::*;
+pub use resample::*;
+#[cfg(feature = "spectrum")]
+pub use spectrum::*;
+pub use stats::*;
+#[cfg(feature = "spectrum")]
+pub use step_response::*;
+pub use summary::*;
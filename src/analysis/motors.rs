@@ -0,0 +1,194 @@
+//! Motor saturation and desync detection
+
+use crate::analysis::link::active_spans;
+use crate::conversion::convert_motor_to_percent;
+use crate::types::BBLLog;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Gap between one motor and the mean of the others, in percent of the
+/// `motorOutput` range, above which the motor counts as diverging
+const DESYNC_DIVERGENCE_PERCENT: f32 = 50.0;
+
+/// Shortest divergence reported as a possible desync; hard flips and rolls
+/// push single motors apart for a few milliseconds
+const DESYNC_MIN_DURATION_US: u64 = 100_000;
+
+/// Period in which one motor was saturated or diverged from the others
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MotorPeriod {
+    /// Motor index, as in `motor[n]`
+    pub motor: usize,
+    pub start_us: u64,
+    pub end_us: u64,
+}
+
+impl MotorPeriod {
+    pub fn duration_us(&self) -> u64 {
+        self.end_us - self.start_us
+    }
+}
+
+/// Saturation and possible desyncs of the motors ([`BBLLog::motor_analysis`])
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MotorAnalysis {
+    /// Periods a motor output was pinned at the top of the `motorOutput` range
+    pub saturation: Vec<MotorPeriod>,
+    /// Time at least one motor was saturated
+    pub saturated_time_us: u64,
+    /// Periods of at least 100 ms in which a motor output was 50% of the
+    /// range or more away from the mean of the others, as a failing ESC or
+    /// desynced motor makes the PID controller push it to max
+    pub desyncs: Vec<MotorPeriod>,
+}
+
+impl BBLLog {
+    /// Motor saturation periods and possible desyncs, in time order.
+    ///
+    /// Returns `None` when the log has no `motor[n]` fields or no usable
+    /// `motorOutput` header.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bbl_parser::{parse_bbl_file, ExportOptions};
+    /// use std::path::Path;
+    ///
+    /// let log = parse_bbl_file(Path::new("flight.BBL"), ExportOptions::default(), false).unwrap();
+    /// if let Some(motors) = log.motor_analysis() {
+    ///     for desync in &motors.desyncs {
+    ///         println!("motor {} at {} us", desync.motor, desync.start_us);
+    ///     }
+    /// }
+    /// ```
+    pub fn motor_analysis(&self) -> Option<MotorAnalysis> {
+        let (min, max) = self.header.motor_output_range()?;
+        let frames = &self.frames;
+        let motors: Vec<usize> = (0..)
+            .map_while(|motor| frames.field_index(&format!("motor[{motor}]")))
+            .collect();
+        if motors.is_empty() {
+            return None;
+        }
+
+        let rows = frames.rows_in_range(0, u64::MAX);
+        let percent = |row: usize, field: usize| {
+            let raw = frames.value(row, field)?;
+            Some(convert_motor_to_percent(raw, min, max))
+        };
+        let periods = |motor: usize, spans: Vec<(u64, u64)>| {
+            spans
+                .into_iter()
+                .map(move |(start_us, end_us)| MotorPeriod {
+                    motor,
+                    start_us,
+                    end_us,
+                })
+        };
+
+        let mut saturation = Vec::new();
+        let mut desyncs = Vec::new();
+        for (motor, &field) in motors.iter().enumerate() {
+            let saturated = active_spans(self, &rows, |row| {
+                frames.value(row, field).map(|raw| raw >= max)
+            });
+            saturation.extend(periods(motor, saturated));
+
+            let diverged = active_spans(self, &rows, |row| {
+                let own = percent(row, field)?;
+                let others: Vec<f32> = motors
+                    .iter()
+                    .filter(|&&other| other != field)
+                    .filter_map(|&other| percent(row, other))
+                    .collect();
+                if others.is_empty() {
+                    return None;
+                }
+                let mean = others.iter().sum::<f32>() / others.len() as f32;
+                Some((own - mean).abs() >= DESYNC_DIVERGENCE_PERCENT)
+            });
+            desyncs.extend(
+                periods(motor, diverged)
+                    .filter(|period| period.duration_us() >= DESYNC_MIN_DURATION_US),
+            );
+        }
+        saturation.sort_by_key(|period| (period.start_us, period.motor));
+        desyncs.sort_by_key(|period| (period.start_us, period.motor));
+
+        let saturated_time_us = active_spans(self, &rows, |row| {
+            Some(
+                motors
+                    .iter()
+                    .any(|&field| frames.value(row, field).is_some_and(|raw| raw >= max)),
+            )
+        })
+        .iter()
+        .map(|(start_us, end_us)| end_us - start_us)
+        .sum();
+
+        Some(MotorAnalysis {
+            saturation,
+            saturated_time_us,
+            desyncs,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::SysConfigValue;
+
+    #[test]
+    fn test_motor_analysis() {
+        let mut log = BBLLog::new(1, 1);
+        assert_eq!(log.motor_analysis(), None);
+        log.header
+            .sysconfig
+            .insert("motorOutput".to_string(), SysConfigValue::parse("48,2047"));
+
+        // Every 50 ms: a short full-throttle punch on all motors, a one-frame
+        // spike on motor 1, then motor 2 pinned while the others idle
+        let rows = [
+            [500, 500, 500, 500],
+            [2047, 2047, 2047, 2047],
+            [1000, 1000, 1000, 1000],
+            [1000, 2047, 1000, 1000],
+            [1000, 1000, 1000, 1000],
+            [300, 300, 2047, 300],
+            [300, 300, 2047, 300],
+            [300, 300, 2047, 300],
+            [500, 500, 500, 500],
+        ];
+        for (i, motors) in rows.into_iter().enumerate() {
+            let fields = motors
+                .into_iter()
+                .enumerate()
+                .map(|(motor, raw)| (format!("motor[{motor}]"), raw));
+            log.push_main_frame(i as u64 * 50_000, 0, fields);
+        }
+
+        let motors = log.motor_analysis().unwrap();
+        assert_eq!(motors.saturation.len(), 6);
+        assert_eq!(
+            motors.saturation[4],
+            MotorPeriod {
+                motor: 1,
+                start_us: 150_000,
+                end_us: 200_000,
+            }
+        );
+        assert_eq!(motors.saturation[5].duration_us(), 150_000);
+        assert_eq!(motors.saturated_time_us, 250_000);
+        assert_eq!(
+            motors.desyncs,
+            vec![MotorPeriod {
+                motor: 2,
+                start_us: 250_000,
+                end_us: 400_000,
+            }]
+        );
+    }
+}
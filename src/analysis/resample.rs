@@ -0,0 +1,248 @@
+//! Resampling of main-frame fields to a uniform sample rate
+
+use crate::types::BBLLog;
+use anyhow::{anyhow, Result};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Highest sample rate [`BBLLog::resample`] accepts; no flight controller
+/// loop runs faster than 1 MHz
+pub const MAX_RESAMPLE_RATE_HZ: f64 = 1_000_000.0;
+
+/// Most samples per field [`BBLLog::resample`] produces, about two hours at 8 kHz
+pub const MAX_RESAMPLE_SAMPLES: usize = 64_000_000;
+
+/// How [`BBLLog::resample`] computes values between logged frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum ResampleMethod {
+    /// Straight line between the frames before and after
+    #[default]
+    Linear,
+    /// Value of the closest frame
+    Nearest,
+    /// Value of the last frame at or before the sample time
+    Previous,
+}
+
+/// Uniformly spaced series of main-frame fields
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ResampledData {
+    /// Time of the first sample, the first main frame's timestamp
+    pub start_us: u64,
+    pub rate_hz: f64,
+    /// Field names, in the order requested
+    pub fields: Vec<String>,
+    /// One series of raw values per field; samples before a field's first
+    /// frame are NaN
+    pub values: Vec<Vec<f64>>,
+}
+
+impl ResampledData {
+    /// Number of samples per field
+    pub fn len(&self) -> usize {
+        self.values.first().map_or(0, Vec::len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Time of sample `index` in microseconds
+    pub fn time_us(&self, index: usize) -> f64 {
+        self.start_us as f64 + index as f64 * 1_000_000.0 / self.rate_hz
+    }
+
+    /// Samples of the named field
+    pub fn series(&self, name: &str) -> Option<&[f64]> {
+        let index = self.fields.iter().position(|field| field == name)?;
+        Some(&self.values[index])
+    }
+}
+
+impl BBLLog {
+    /// Raw values of `fields` sampled every `1 / rate_hz` seconds from the
+    /// first to the last main frame.
+    ///
+    /// Loop timing jitters and frames can be missing, so logged frames are not
+    /// evenly spaced; FFT and filter analyses need a uniform grid. Values are
+    /// not converted to units; high-resolution logs keep their ×10 scale.
+    /// Fails when `rate_hz` is not positive or above [`MAX_RESAMPLE_RATE_HZ`],
+    /// when a field is not in the main frames, or when the log's time span
+    /// would need more than [`MAX_RESAMPLE_SAMPLES`] samples (e.g. after a
+    /// corrupt timestamp).
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bbl_parser::{parse_bbl_file, ExportOptions, ResampleMethod};
+    /// use std::path::Path;
+    ///
+    /// let log = parse_bbl_file(Path::new("flight.BBL"), ExportOptions::default(), false).unwrap();
+    /// let gyro = log.resample(2000.0, &["gyroADC[0]"], ResampleMethod::Linear).unwrap();
+    /// println!("{} samples", gyro.len());
+    /// ```
+    pub fn resample(
+        &self,
+        rate_hz: f64,
+        fields: &[&str],
+        method: ResampleMethod,
+    ) -> Result<ResampledData> {
+        if !(rate_hz.is_finite() && rate_hz > 0.0) {
+            return Err(anyhow!("resample rate must be positive, got {rate_hz} Hz"));
+        }
+        if rate_hz > MAX_RESAMPLE_RATE_HZ {
+            return Err(anyhow!(
+                "resample rate {rate_hz} Hz is above the {MAX_RESAMPLE_RATE_HZ} Hz limit"
+            ));
+        }
+        let frames = &self.frames;
+        let columns = fields
+            .iter()
+            .map(|&name| {
+                frames
+                    .field_index(name)
+                    .ok_or_else(|| anyhow!("no main-frame field {name:?} to resample"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let rows = frames.rows_in_range(0, u64::MAX);
+        let timestamps = frames.timestamps();
+        let (start_us, end_us) = match (rows.first(), rows.last()) {
+            (Some(&first), Some(&last)) => (timestamps[first], timestamps[last]),
+            _ => (0, 0),
+        };
+        let interval_us = 1_000_000.0 / rate_hz;
+        let count = if rows.is_empty() {
+            0
+        } else {
+            ((end_us - start_us) as f64 / interval_us) as usize + 1
+        };
+        if count > MAX_RESAMPLE_SAMPLES {
+            return Err(anyhow!(
+                "resampling {} s at {rate_hz} Hz needs {count} samples, more than {MAX_RESAMPLE_SAMPLES}",
+                (end_us - start_us) / 1_000_000
+            ));
+        }
+
+        let values = columns
+            .into_iter()
+            .map(|column| {
+                let samples: Vec<(f64, f64)> = rows
+                    .iter()
+                    .filter_map(|&row| {
+                        let value = frames.value(row, column)?;
+                        Some((timestamps[row] as f64, value as f64))
+                    })
+                    .collect();
+                resample_series(&samples, start_us as f64, interval_us, count, method)
+            })
+            .collect();
+
+        Ok(ResampledData {
+            start_us,
+            rate_hz,
+            fields: fields.iter().map(|name| name.to_string()).collect(),
+            values,
+        })
+    }
+}
+
+/// `count` values every `interval_us` from `start_us`, from `(time, value)`
+/// samples in time order
+fn resample_series(
+    samples: &[(f64, f64)],
+    start_us: f64,
+    interval_us: f64,
+    count: usize,
+    method: ResampleMethod,
+) -> Vec<f64> {
+    let mut next = 0;
+    (0..count)
+        .map(|index| {
+            let time = start_us + index as f64 * interval_us;
+            // First sample after `time`
+            while next < samples.len() && samples[next].0 <= time {
+                next += 1;
+            }
+            let Some(&(before_time, before)) = next.checked_sub(1).map(|i| &samples[i]) else {
+                return f64::NAN;
+            };
+            let Some(&(after_time, after)) = samples.get(next) else {
+                return before;
+            };
+            match method {
+                ResampleMethod::Linear => {
+                    before + (after - before) * (time - before_time) / (after_time - before_time)
+                }
+                ResampleMethod::Nearest if after_time - time < time - before_time => after,
+                ResampleMethod::Nearest | ResampleMethod::Previous => before,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resample() {
+        let log = BBLLog::with_main_frames(
+            [(10_000, 0), (11_000, 10), (12_900, 20)]
+                .map(|(time, gyro)| (time, [("gyroADC[0]", gyro)])),
+        );
+
+        let linear = log
+            .resample(1000.0, &["gyroADC[0]"], ResampleMethod::Linear)
+            .unwrap();
+        assert_eq!(linear.len(), 3);
+        assert_eq!(linear.start_us, 10_000);
+        assert_eq!(linear.time_us(2), 12_000.0);
+        let series = linear.series("gyroADC[0]").unwrap();
+        assert_eq!(series[..2], [0.0, 10.0]);
+        assert!((series[2] - 15.26).abs() < 0.01, "{series:?}");
+
+        let sample = |method| {
+            log.resample(1000.0, &["gyroADC[0]"], method)
+                .unwrap()
+                .values[0][2]
+        };
+        assert_eq!(sample(ResampleMethod::Nearest), 20.0);
+        assert_eq!(sample(ResampleMethod::Previous), 10.0);
+
+        assert!(log
+            .resample(0.0, &["gyroADC[0]"], ResampleMethod::Linear)
+            .is_err());
+        assert!(log
+            .resample(1000.0, &["motor[0]"], ResampleMethod::Linear)
+            .is_err());
+        assert!(BBLLog::new(1, 1)
+            .resample(1000.0, &[], ResampleMethod::Linear)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_resample_limits() {
+        // A corrupt timestamp stretches the log to about 28 hours
+        let log = BBLLog::with_main_frames(
+            [10_000, 100_000_000_000].map(|time| (time, [("gyroADC[0]", 0)])),
+        );
+
+        assert!(log
+            .resample(2_000_000.0, &["gyroADC[0]"], ResampleMethod::Linear)
+            .is_err());
+        assert!(log
+            .resample(1000.0, &["gyroADC[0]"], ResampleMethod::Linear)
+            .is_err());
+        assert_eq!(
+            log.resample(1.0, &["gyroADC[0]"], ResampleMethod::Linear)
+                .unwrap()
+                .len(),
+            100_000
+        );
+    }
+}
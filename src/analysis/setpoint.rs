@@ -0,0 +1,112 @@
+//! Setpoint rotation rates derived from the RC commands and rate settings
+
+use crate::conversion::{convert_high_resolution_value, convert_rc_command_to_setpoint};
+use crate::types::BBLLog;
+
+impl BBLLog {
+    /// Commanded rotation rate in deg/s of roll (0), pitch (1) or yaw (2) for
+    /// every main frame, computed from `rcCommand[axis]`.
+    ///
+    /// Applies the log's rate curve and settings ([`crate::BBLHeader::rates_type`],
+    /// [`crate::BBLHeader::axis_rates`]) like the firmware does, for logs that
+    /// do not record `setpoint`. Returns `None` when the log has no RC command
+    /// field or rate settings for the axis.
+    pub fn setpoint_deg_per_sec(&self, axis: usize) -> Option<Vec<f64>> {
+        let rates = self.header.axis_rates(axis)?;
+        let rates_type = self.header.rates_type();
+        let high_resolution = self.header.is_high_resolution();
+        let rc_command = self.frames.column(&format!("rcCommand[{axis}]"))?;
+        Some(
+            rc_command
+                .iter()
+                .map(|&raw| {
+                    let command = if high_resolution {
+                        convert_high_resolution_value(raw)
+                    } else {
+                        raw as f64
+                    };
+                    convert_rc_command_to_setpoint(command, rates, rates_type)
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversion::{AxisRates, RatesType};
+    use crate::parser::diagnostics::DiagnosticReporter;
+    use crate::parser::header::parse_headers_from_text;
+
+    #[test]
+    fn test_rate_curves() {
+        let rates = |rc_rate, super_rate, expo| AxisRates {
+            rc_rate,
+            super_rate,
+            expo,
+        };
+        let setpoint = |command, axis_rates, rates_type| {
+            convert_rc_command_to_setpoint(command, axis_rates, rates_type).round()
+        };
+
+        // Betaflight: 200 deg/s per unit of RC rate at the center, super rate
+        // stretching full stick
+        let betaflight = rates(100, 70, 0);
+        assert_eq!(setpoint(0.0, betaflight, RatesType::Betaflight), 0.0);
+        assert_eq!(setpoint(500.0, betaflight, RatesType::Betaflight), 667.0);
+        assert_eq!(setpoint(-250.0, betaflight, RatesType::Betaflight), -154.0);
+        assert_eq!(
+            setpoint(500.0, rates(100, 0, 0), RatesType::Betaflight),
+            200.0
+        );
+
+        // Actual: 70 deg/s center sensitivity, 670 deg/s max rate
+        let actual = rates(7, 67, 0);
+        assert_eq!(setpoint(500.0, actual, RatesType::Actual), 670.0);
+        assert_eq!(setpoint(-500.0, actual, RatesType::Actual), -670.0);
+        assert_eq!(setpoint(5.0, actual, RatesType::Actual), 1.0);
+
+        // KISS
+        assert_eq!(setpoint(500.0, rates(100, 70, 0), RatesType::Kiss), 667.0);
+
+        // Quick: 200 deg/s center sensitivity, 670 deg/s max rate
+        assert_eq!(setpoint(500.0, rates(100, 67, 0), RatesType::Quick), 670.0);
+
+        // Limited like the firmware
+        assert_eq!(
+            setpoint(500.0, rates(255, 100, 0), RatesType::Betaflight),
+            1998.0
+        );
+    }
+
+    #[test]
+    fn test_setpoint_deg_per_sec() {
+        let mut log = BBLLog::new(1, 1);
+        log.header = parse_headers_from_text(
+            "H rates:70,70,0\nH rc_rates:100,100,100\nH rc_expo:0,0,0\nH rates_type:0\n",
+            DiagnosticReporter::silent(),
+        )
+        .unwrap();
+        assert_eq!(log.header.rates_type(), RatesType::Betaflight);
+        assert_eq!(
+            log.header.axis_rates(2),
+            Some(AxisRates {
+                rc_rate: 100,
+                super_rate: 0,
+                expo: 0,
+            })
+        );
+        assert_eq!(log.header.axis_rates(3), None);
+        assert_eq!(log.setpoint_deg_per_sec(0), None);
+
+        for (roll, yaw) in [(0, 0), (500, -250)] {
+            log.push_main_frame(0, 0, [("rcCommand[0]", roll), ("rcCommand[2]", yaw)]);
+        }
+        let roll = log.setpoint_deg_per_sec(0).unwrap();
+        assert_eq!(roll[0], 0.0);
+        assert!((roll[1] - 666.7).abs() < 0.1, "{roll:?}");
+        assert_eq!(log.setpoint_deg_per_sec(2), Some(vec![0.0, -100.0]));
+        assert_eq!(log.setpoint_deg_per_sec(1), None);
+    }
+}
@@ -0,0 +1,468 @@
+//! Power spectral density of gyro, D-term and debug traces (`spectrum` feature)
+
+use crate::analysis::ResampleMethod;
+use crate::conversion::{
+    convert_throttle_to_percent, is_high_resolution_field, HIGH_RESOLUTION_SCALE,
+};
+use crate::types::BBLLog;
+use anyhow::{anyhow, Result};
+use realfft::{RealFftPlanner, RealToComplex};
+use std::io::Write;
+use std::sync::Arc;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Power spectral density of one main-frame field
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Spectrum {
+    pub field: String,
+    /// Rate the field was logged at, from the median frame interval
+    pub sample_rate_hz: f64,
+    /// Frequency of each bin, from 0 Hz to the Nyquist frequency
+    pub frequencies_hz: Vec<f64>,
+    /// Power spectral density of each bin, in squared field units per Hz
+    pub psd: Vec<f64>,
+    /// Number of segments averaged into `psd`; 0 leaves it all zero
+    pub segments: usize,
+}
+
+impl Spectrum {
+    /// Frequency and density of the strongest bin above 0 Hz
+    pub fn peak(&self) -> Option<(f64, f64)> {
+        self.frequencies_hz
+            .iter()
+            .zip(&self.psd)
+            .skip(1)
+            .map(|(&frequency, &density)| (frequency, density))
+            .reduce(|best, bin| if bin.1 > best.1 { bin } else { best })
+    }
+}
+
+/// [`Spectrum`] of the segments flown in one throttle range
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ThrottleSpectrum {
+    pub min_throttle_percent: f32,
+    pub max_throttle_percent: f32,
+    pub spectrum: Spectrum,
+}
+
+/// Throttle-by-frequency noise matrix ([`BBLLog::noise_heatmap`]), as
+/// plotted by PIDtoolbox and Plasmatree
+///
+/// Row `i` is the PSD of the segments flown between `throttle_percent[i]`
+/// and `throttle_percent[i + 1]`; renderers usually show it as
+/// `10 * log10(psd)`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NoiseHeatmap {
+    pub field: String,
+    /// Throttle bin edges, one more than there are rows
+    pub throttle_percent: Vec<f32>,
+    /// Frequency of each column
+    pub frequencies_hz: Vec<f64>,
+    /// PSD per throttle bin and frequency, in squared field units per Hz
+    pub psd: Vec<Vec<f64>>,
+    /// Segments averaged into each row; rows without any are all zero
+    pub segments: Vec<usize>,
+}
+
+impl NoiseHeatmap {
+    /// Write the matrix as CSV: a `throttle_min,throttle_max` pair and one
+    /// column per frequency, one row per throttle bin
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> Result<()> {
+        write!(writer, "throttle_min (%),throttle_max (%)")?;
+        for frequency in &self.frequencies_hz {
+            write!(writer, ",{frequency} Hz")?;
+        }
+        writeln!(writer)?;
+        for (bin, row) in self.psd.iter().enumerate() {
+            write!(
+                writer,
+                "{},{}",
+                self.throttle_percent[bin],
+                self.throttle_percent[bin + 1]
+            )?;
+            for density in row {
+                write!(writer, ",{density}")?;
+            }
+            writeln!(writer)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Serialize as a JSON object (`json` feature)
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+impl BBLLog {
+    /// Welch power spectral density of a main-frame field such as
+    /// `gyroADC[0]`, `axisD[1]` or `debug[0]`.
+    ///
+    /// The field is resampled to its logged rate and cut into Hann-windowed
+    /// `segment_len`-sample segments overlapping by half, whose periodograms
+    /// are averaged. Longer segments give finer frequency bins but fewer
+    /// averages. High-resolution values are brought back to normal units
+    /// first; other values are raw (see [`crate::types::field_unit`]). Fails
+    /// when the field is missing or the log is shorter than one segment.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bbl_parser::{parse_bbl_file, ExportOptions};
+    /// use std::path::Path;
+    ///
+    /// let log = parse_bbl_file(Path::new("flight.BBL"), ExportOptions::default(), false).unwrap();
+    /// let roll = log.spectrum("gyroADC[0]", 1024).unwrap();
+    /// if let Some((frequency, _)) = roll.peak() {
+    ///     println!("Roll noise peaks at {frequency:.0} Hz");
+    /// }
+    /// ```
+    pub fn spectrum(&self, field: &str, segment_len: usize) -> Result<Spectrum> {
+        let input = self.spectrum_input(field, segment_len, false)?;
+        let welch = Welch::new(segment_len, input.sample_rate_hz);
+        let mut sums = vec![0.0; welch.bins()];
+        let mut segments = 0;
+        for segment in input.segments(segment_len) {
+            welch.add_segment(&input.values[segment], &mut sums);
+            segments += 1;
+        }
+        if segments == 0 {
+            return Err(anyhow!(
+                "{field} has fewer than {segment_len} consecutive samples"
+            ));
+        }
+        Ok(welch.spectrum(field, sums, segments))
+    }
+
+    /// [`BBLLog::spectrum`] per throttle range: `bins` equal ranges from 0 to
+    /// 100% throttle, each averaging the segments whose mean throttle falls
+    /// in it. Ranges without segments have an all-zero PSD; 3D mode uses the
+    /// throttle magnitude. Fails without an `rcCommand[3]` field.
+    pub fn spectrum_by_throttle(
+        &self,
+        field: &str,
+        segment_len: usize,
+        bins: usize,
+    ) -> Result<Vec<ThrottleSpectrum>> {
+        if bins == 0 {
+            return Err(anyhow!("throttle bin count must be positive"));
+        }
+        let input = self.spectrum_input(field, segment_len, true)?;
+        let welch = Welch::new(segment_len, input.sample_rate_hz);
+        let mut sums = vec![(vec![0.0; welch.bins()], 0); bins];
+        for segment in input.segments(segment_len) {
+            let throttle = &input.throttle[segment.clone()];
+            let mean = throttle.iter().sum::<f32>() / throttle.len() as f32;
+            let bin = ((mean / 100.0 * bins as f32) as usize).min(bins - 1);
+            let (bin_sums, segments) = &mut sums[bin];
+            welch.add_segment(&input.values[segment], bin_sums);
+            *segments += 1;
+        }
+
+        let width = 100.0 / bins as f32;
+        Ok(sums
+            .into_iter()
+            .enumerate()
+            .map(|(bin, (bin_sums, segments))| ThrottleSpectrum {
+                min_throttle_percent: bin as f32 * width,
+                max_throttle_percent: (bin + 1) as f32 * width,
+                spectrum: welch.spectrum(field, bin_sums, segments),
+            })
+            .collect())
+    }
+
+    /// Throttle-by-frequency noise matrix of a field, from
+    /// [`BBLLog::spectrum_by_throttle`] with `throttle_bins` rows.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bbl_parser::{parse_bbl_file, ExportOptions};
+    /// use std::fs::File;
+    /// use std::path::Path;
+    ///
+    /// let log = parse_bbl_file(Path::new("flight.BBL"), ExportOptions::default(), false).unwrap();
+    /// let heatmap = log.noise_heatmap("gyroADC[0]", 512, 50).unwrap();
+    /// heatmap.write_csv(File::create("roll_noise.csv").unwrap()).unwrap();
+    /// ```
+    pub fn noise_heatmap(
+        &self,
+        field: &str,
+        segment_len: usize,
+        throttle_bins: usize,
+    ) -> Result<NoiseHeatmap> {
+        let bins = self.spectrum_by_throttle(field, segment_len, throttle_bins)?;
+        let mut throttle_percent: Vec<f32> =
+            bins.iter().map(|bin| bin.min_throttle_percent).collect();
+        throttle_percent.extend(bins.last().map(|bin| bin.max_throttle_percent));
+        let frequencies_hz = bins[0].spectrum.frequencies_hz.clone();
+        let (psd, segments) = bins
+            .into_iter()
+            .map(|bin| (bin.spectrum.psd, bin.spectrum.segments))
+            .unzip();
+
+        Ok(NoiseHeatmap {
+            field: field.to_string(),
+            throttle_percent,
+            frequencies_hz,
+            psd,
+            segments,
+        })
+    }
+
+    /// Uniformly sampled field values, and throttle percent when asked for
+    fn spectrum_input(
+        &self,
+        field: &str,
+        segment_len: usize,
+        with_throttle: bool,
+    ) -> Result<SpectrumInput> {
+        if segment_len < 2 {
+            return Err(anyhow!("spectrum segments need at least 2 samples"));
+        }
+        let sample_rate_hz = self
+            .logged_rate_hz()
+            .ok_or_else(|| anyhow!("not enough main frames for a spectrum"))?;
+        let fields: &[&str] = if with_throttle {
+            &[field, "rcCommand[3]"]
+        } else {
+            &[field]
+        };
+        let resampled = self.resample(sample_rate_hz, fields, ResampleMethod::Linear)?;
+
+        let scale = if self.header.is_high_resolution() && is_high_resolution_field(field) {
+            HIGH_RESOLUTION_SCALE
+        } else {
+            1.0
+        };
+        let values = resampled.values[0]
+            .iter()
+            .map(|&value| value / scale)
+            .collect();
+        let (min, max) = self.header.throttle_range();
+        let is_3d = self.header.is_3d_mode();
+        let throttle = resampled
+            .values
+            .get(1)
+            .map(|throttle| {
+                throttle
+                    .iter()
+                    .map(|&raw| {
+                        if raw.is_nan() {
+                            f32::NAN
+                        } else {
+                            convert_throttle_to_percent(raw as i32, min, max, is_3d).abs()
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(SpectrumInput {
+            sample_rate_hz,
+            values,
+            throttle,
+        })
+    }
+
+    /// Logging rate from the median interval between main frames
+    pub(crate) fn logged_rate_hz(&self) -> Option<f64> {
+        let timestamps = self.frames.timestamps();
+        let rows = self.frames.rows_in_range(0, u64::MAX);
+        let mut intervals: Vec<u64> = rows
+            .windows(2)
+            .map(|pair| timestamps[pair[1]] - timestamps[pair[0]])
+            .filter(|&interval| interval > 0)
+            .collect();
+        if intervals.is_empty() {
+            return None;
+        }
+        let mid = intervals.len() / 2;
+        let (_, &mut median, _) = intervals.select_nth_unstable(mid);
+        Some(1_000_000.0 / median as f64)
+    }
+}
+
+struct SpectrumInput {
+    sample_rate_hz: f64,
+    values: Vec<f64>,
+    /// Throttle percent per sample, empty unless requested
+    throttle: Vec<f32>,
+}
+
+impl SpectrumInput {
+    /// Half-overlapping segments without missing samples
+    fn segments(&self, segment_len: usize) -> impl Iterator<Item = std::ops::Range<usize>> + '_ {
+        let step = (segment_len / 2).max(1);
+        let last_start = self.values.len().checked_sub(segment_len);
+        last_start
+            .into_iter()
+            .flat_map(move |last_start| (0..=last_start).step_by(step))
+            .map(move |start| start..start + segment_len)
+            .filter(|segment| {
+                self.values[segment.clone()]
+                    .iter()
+                    .all(|value| !value.is_nan())
+                    && self.throttle.get(segment.clone()).map_or(true, |throttle| {
+                        throttle.iter().all(|percent| !percent.is_nan())
+                    })
+            })
+    }
+}
+
+/// Hann-windowed periodograms of fixed-length segments
+struct Welch {
+    fft: Arc<dyn RealToComplex<f64>>,
+    window: Vec<f64>,
+    /// One-sided PSD scale: 1 / (sample rate * window power)
+    scale: f64,
+    sample_rate_hz: f64,
+}
+
+impl Welch {
+    fn new(segment_len: usize, sample_rate_hz: f64) -> Self {
+        let window: Vec<f64> = (0..segment_len)
+            .map(|i| {
+                let phase = 2.0 * std::f64::consts::PI * i as f64 / segment_len as f64;
+                0.5 - 0.5 * phase.cos()
+            })
+            .collect();
+        let window_power: f64 = window.iter().map(|w| w * w).sum();
+        Self {
+            fft: RealFftPlanner::new().plan_fft_forward(segment_len),
+            window,
+            scale: 1.0 / (sample_rate_hz * window_power),
+            sample_rate_hz,
+        }
+    }
+
+    fn bins(&self) -> usize {
+        self.window.len() / 2 + 1
+    }
+
+    /// Add the periodogram of `samples` (minus their mean) to `sums`
+    fn add_segment(&self, samples: &[f64], sums: &mut [f64]) {
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let mut input: Vec<f64> = samples
+            .iter()
+            .zip(&self.window)
+            .map(|(value, w)| (value - mean) * w)
+            .collect();
+        let mut output = self.fft.make_output_vec();
+        self.fft
+            .process(&mut input, &mut output)
+            .expect("FFT buffers match the plan");
+
+        let nyquist = (self.window.len() % 2 == 0).then(|| output.len() - 1);
+        for (bin, (sum, value)) in sums.iter_mut().zip(&output).enumerate() {
+            // Fold the negative frequencies onto the positive ones
+            let one_sided = if bin == 0 || Some(bin) == nyquist {
+                1.0
+            } else {
+                2.0
+            };
+            *sum += value.norm_sqr() * self.scale * one_sided;
+        }
+    }
+
+    fn spectrum(&self, field: &str, sums: Vec<f64>, segments: usize) -> Spectrum {
+        let bin_width = self.sample_rate_hz / self.window.len() as f64;
+        Spectrum {
+            field: field.to_string(),
+            sample_rate_hz: self.sample_rate_hz,
+            frequencies_hz: (0..sums.len()).map(|bin| bin as f64 * bin_width).collect(),
+            psd: sums
+                .into_iter()
+                .map(|sum| sum / segments.max(1) as f64)
+                .collect(),
+            segments,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One second at 1 kHz per `(throttle, frequency)`: a 100-count gyro sine
+    fn sine_log(parts: &[(i32, f64)]) -> BBLLog {
+        let mut log = BBLLog::new(1, 1);
+        for (part, &(throttle, frequency)) in parts.iter().enumerate() {
+            for i in 0..1000 {
+                let t = i as f64 / 1000.0;
+                let gyro = (100.0 * (2.0 * std::f64::consts::PI * frequency * t).sin()).round();
+                log.push_main_frame(
+                    (part * 1000 + i) as u64 * 1000,
+                    0,
+                    [("gyroADC[0]", gyro as i32), ("rcCommand[3]", throttle)],
+                );
+            }
+        }
+        log
+    }
+
+    #[test]
+    fn test_spectrum() {
+        let log = sine_log(&[(1500, 100.0)]);
+        let spectrum = log.spectrum("gyroADC[0]", 200).unwrap();
+        assert_eq!(spectrum.sample_rate_hz, 1000.0);
+        assert_eq!(spectrum.psd.len(), 101);
+        assert_eq!(spectrum.frequencies_hz[100], 500.0);
+        assert_eq!(spectrum.segments, 9);
+        assert_eq!(spectrum.peak().unwrap().0, 100.0);
+
+        // The PSD integrates to the signal power, A² / 2
+        let power: f64 = spectrum.psd.iter().sum::<f64>() * 5.0;
+        assert!((power - 5000.0).abs() < 50.0, "{power}");
+
+        assert!(log.spectrum("gyroADC[1]", 200).is_err());
+        assert!(log.spectrum("gyroADC[0]", 2000).is_err());
+        assert!(log.spectrum("gyroADC[0]", 1).is_err());
+    }
+
+    #[test]
+    fn test_spectrum_by_throttle() {
+        let log = sine_log(&[(1200, 100.0), (1900, 250.0)]);
+        let bins = log.spectrum_by_throttle("gyroADC[0]", 200, 4).unwrap();
+        assert_eq!(bins.len(), 4);
+        assert_eq!(
+            (bins[0].min_throttle_percent, bins[0].max_throttle_percent),
+            (0.0, 25.0)
+        );
+        assert_eq!(bins[0].spectrum.peak().unwrap().0, 100.0);
+        assert_eq!(bins[3].spectrum.peak().unwrap().0, 250.0);
+        // The segment straddling both parts averages 55% throttle
+        assert_eq!(bins[2].spectrum.segments, 1);
+        assert_eq!(bins[1].spectrum.segments, 0);
+        assert!(bins[1].spectrum.psd.iter().all(|&density| density == 0.0));
+
+        assert!(log.spectrum_by_throttle("gyroADC[0]", 200, 0).is_err());
+    }
+
+    #[test]
+    fn test_noise_heatmap() {
+        let log = sine_log(&[(1200, 100.0), (1900, 250.0)]);
+        let heatmap = log.noise_heatmap("gyroADC[0]", 8, 2).unwrap();
+        assert_eq!(heatmap.throttle_percent, vec![0.0, 50.0, 100.0]);
+        assert_eq!(heatmap.frequencies_hz.len(), 5);
+        assert_eq!(heatmap.psd.len(), 2);
+        assert_eq!(heatmap.segments, vec![249, 250]);
+
+        let mut csv = Vec::new();
+        heatmap.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "throttle_min (%),throttle_max (%),0 Hz,125 Hz,250 Hz,375 Hz,500 Hz"
+        );
+        assert!(lines[2].starts_with("50,100,"));
+        assert_eq!(lines[1].split(',').count(), 7);
+    }
+}
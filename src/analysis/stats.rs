@@ -0,0 +1,181 @@
+//! Per-field statistics over main (I/P) frames
+
+use crate::types::BBLLog;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Observed value range of one main-frame field
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FieldLimits {
+    pub name: String,
+    pub min: i64,
+    pub max: i64,
+}
+
+/// Summary statistics of one main-frame field
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FieldStats {
+    pub name: String,
+    /// Number of frames carrying the field
+    pub count: usize,
+    pub min: i64,
+    pub max: i64,
+    pub mean: f64,
+    /// Population standard deviation
+    pub stddev: f64,
+    /// Time of the value farthest from zero (the first, on ties)
+    pub peak_time_us: u64,
+}
+
+impl BBLLog {
+    /// Minimum and maximum of every I-frame field across all main (I and P) frames.
+    ///
+    /// Fields are returned in header order, matching blackbox_decode's `--limits`.
+    /// Fields that never appear in a decoded frame are omitted. The ranges are
+    /// those of [`BBLLog::field_stats`].
+    pub fn field_limits(&self) -> Vec<FieldLimits> {
+        let stats = self.field_stats();
+        self.header
+            .i_frame_def
+            .field_names
+            .iter()
+            .filter_map(|name| {
+                let stats = stats.iter().find(|stats| stats.name == *name)?;
+                Some(FieldLimits {
+                    name: name.clone(),
+                    min: stats.min,
+                    max: stats.max,
+                })
+            })
+            .collect()
+    }
+
+    /// Count, min, max, mean, standard deviation and peak time of every
+    /// main-frame field, computed in one pass over each column.
+    ///
+    /// Fields are returned in column order (I-frame fields, then merged slow
+    /// fields); fields that never appear in a decoded frame are omitted.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bbl_parser::{parse_bbl_file, ExportOptions};
+    /// use std::path::Path;
+    ///
+    /// let log = parse_bbl_file(Path::new("flight.BBL"), ExportOptions::default(), false).unwrap();
+    /// for stats in log.field_stats() {
+    ///     println!("{}: max {} at {} us", stats.name, stats.max, stats.peak_time_us);
+    /// }
+    /// ```
+    pub fn field_stats(&self) -> Vec<FieldStats> {
+        let frames = &self.frames;
+        let timestamps = frames.timestamps();
+        frames
+            .field_names()
+            .iter()
+            .enumerate()
+            .filter_map(|(field, name)| {
+                let mut stats: Option<FieldStats> = None;
+                // Welford's running mean and sum of squared deviations
+                let mut squares = 0.0;
+                for (row, &time_us) in timestamps.iter().enumerate() {
+                    let Some(value) = frames.value(row, field) else {
+                        continue;
+                    };
+                    let value = i64::from(value);
+                    let stats = stats.get_or_insert_with(|| FieldStats {
+                        name: name.clone(),
+                        count: 0,
+                        min: value,
+                        max: value,
+                        mean: 0.0,
+                        stddev: 0.0,
+                        peak_time_us: time_us,
+                    });
+                    stats.count += 1;
+                    let delta = value as f64 - stats.mean;
+                    stats.mean += delta / stats.count as f64;
+                    squares += delta * (value as f64 - stats.mean);
+                    if value.abs() > stats.min.abs().max(stats.max.abs()) {
+                        stats.peak_time_us = time_us;
+                    }
+                    stats.min = stats.min.min(value);
+                    stats.max = stats.max.max(value);
+                }
+                stats.map(|stats| FieldStats {
+                    stddev: (squares / stats.count as f64).sqrt(),
+                    ..stats
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{DecodedFrame, FrameDefinition};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_field_limits() {
+        let mut log = BBLLog::new(1, 1);
+        log.header.i_frame_def = FrameDefinition::from_field_names(vec![
+            "time".to_string(),
+            "gyroADC[0]".to_string(),
+            "unused".to_string(),
+        ]);
+        for (frame_type, time, gyro) in [('I', 100, -20), ('P', 200, 35)] {
+            let mut data = HashMap::new();
+            data.insert("time".to_string(), time);
+            data.insert("gyroADC[0]".to_string(), gyro);
+            // Merged slow field, not an I-frame field
+            data.insert("flightModeFlags".to_string(), 1);
+            log.frames.push(DecodedFrame {
+                frame_type,
+                timestamp_us: time as u64,
+                loop_iteration: 0,
+                data,
+            });
+        }
+
+        // Header order, I-frame fields only
+        let limits = log.field_limits();
+        assert_eq!(limits.len(), 2);
+        assert_eq!(limits[0].name, "time");
+        assert_eq!(
+            limits[1],
+            FieldLimits {
+                name: "gyroADC[0]".to_string(),
+                min: -20,
+                max: 35,
+            }
+        );
+    }
+
+    #[test]
+    fn test_field_stats() {
+        let mut log = BBLLog::new(1, 1);
+        for (time, gyro) in [(100, 2), (200, -9), (300, 4), (400, 7)] {
+            let mut fields = vec![("gyroADC[0]", gyro)];
+            if time == 300 {
+                fields.push(("vbatLatest", 1650));
+            }
+            log.push_main_frame(time, 0, fields);
+        }
+
+        let stats = log.field_stats();
+        assert_eq!(stats.len(), 2);
+        let gyro = &stats[0];
+        assert_eq!(gyro.name, "gyroADC[0]");
+        assert_eq!((gyro.count, gyro.min, gyro.max), (4, -9, 7));
+        assert_eq!(gyro.mean, 1.0);
+        assert!((gyro.stddev - 6.042).abs() < 0.001, "{gyro:?}");
+        assert_eq!(gyro.peak_time_us, 200);
+
+        let vbat = &stats[1];
+        assert_eq!((vbat.count, vbat.stddev, vbat.peak_time_us), (1, 0.0, 300));
+    }
+}
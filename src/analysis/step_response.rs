@@ -0,0 +1,348 @@
+//! Step response of the rate loop from setpoint and gyro (`spectrum` feature)
+//!
+//! Follows PID-Analyzer: the log is cut into overlapping one-second windows,
+//! the impulse response from setpoint to gyro is recovered from each by Wiener
+//! deconvolution, and the integrated (step) responses are averaged.
+
+use crate::analysis::ResampleMethod;
+use crate::conversion::{
+    convert_gyro_to_deg_per_sec, is_high_resolution_field, HIGH_RESOLUTION_SCALE,
+};
+use crate::types::BBLLog;
+use anyhow::{anyhow, Result};
+use realfft::num_complex::Complex;
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+use std::sync::Arc;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Length of each deconvolved window
+const WINDOW_S: f64 = 1.0;
+
+/// Windows start every `1 / WINDOW_OVERLAP` of a window
+const WINDOW_OVERLAP: usize = 16;
+
+/// Length of the step response kept from each window
+const RESPONSE_S: f64 = 0.5;
+
+/// Frequency above which the Wiener filter treats the signal as noise
+const CUTOFF_HZ: f64 = 25.0;
+
+/// Windows whose setpoint never exceeds this carry too little input to
+/// deconvolve
+const MIN_INPUT_DEG_S: f64 = 20.0;
+
+/// The steady state is the mean response from this time on
+const STEADY_STATE_FROM_S: f64 = 0.2;
+
+/// Window responses settling outside this range are dominated by noise or
+/// saturation and left out of the average
+const SETTLED_RANGE: (f64, f64) = (0.5, 1.5);
+
+/// Averaged step response of one axis ([`BBLLog::step_response`])
+///
+/// A perfectly tracking loop steps straight to 1.0; the metrics are relative
+/// to `steady_state`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StepResponse {
+    /// 0 roll, 1 pitch, 2 yaw
+    pub axis: usize,
+    pub sample_rate_hz: f64,
+    /// Gyro response to a unit setpoint step, one value per sample
+    pub response: Vec<f64>,
+    /// Windows averaged into `response`
+    pub windows: usize,
+    /// Mean response from 200 ms on
+    pub steady_state: f64,
+    pub peak: f64,
+    pub peak_time_ms: f64,
+    /// How far the peak exceeds the steady state, 0 without overshoot
+    pub overshoot_percent: f64,
+    /// Time to reach 50% of the steady state
+    pub delay_ms: Option<f64>,
+    /// Time from 10% to 90% of the steady state
+    pub rise_time_ms: Option<f64>,
+}
+
+impl StepResponse {
+    /// Time of `response[index]` after the step
+    pub fn time_ms(&self, index: usize) -> f64 {
+        index as f64 * 1000.0 / self.sample_rate_hz
+    }
+
+    /// First time the response reaches `fraction` of the steady state,
+    /// interpolated between samples
+    fn crossing_ms(&self, fraction: f64) -> Option<f64> {
+        let level = fraction * self.steady_state;
+        let index = self.response.iter().position(|&value| value >= level)?;
+        let Some(before) = index.checked_sub(1).map(|i| self.response[i]) else {
+            return Some(0.0);
+        };
+        let part = (level - before) / (self.response[index] - before);
+        Some(self.time_ms(index - 1) + part * self.time_ms(1))
+    }
+}
+
+impl BBLLog {
+    /// Step response of the rate loop on `axis` (0 roll, 1 pitch, 2 yaw),
+    /// deconvolved from `setpoint[axis]` and `gyroADC[axis]`, with overshoot,
+    /// delay and rise time.
+    ///
+    /// Needs at least a second of flight with stick input; fails when the log
+    /// has no such window or lacks the fields.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bbl_parser::{parse_bbl_file, ExportOptions};
+    /// use std::path::Path;
+    ///
+    /// let log = parse_bbl_file(Path::new("flight.BBL"), ExportOptions::default(), false).unwrap();
+    /// let roll = log.step_response(0).unwrap();
+    /// println!("Roll overshoot {:.0}%, delay {:?} ms", roll.overshoot_percent, roll.delay_ms);
+    /// ```
+    pub fn step_response(&self, axis: usize) -> Result<StepResponse> {
+        if axis > 2 {
+            return Err(anyhow!("step response axis must be 0, 1 or 2, got {axis}"));
+        }
+        let sample_rate_hz = self
+            .logged_rate_hz()
+            .ok_or_else(|| anyhow!("not enough main frames for a step response"))?;
+        let setpoint_field = format!("setpoint[{axis}]");
+        let gyro_field = format!("gyroADC[{axis}]");
+        let resampled = self.resample(
+            sample_rate_hz,
+            &[&setpoint_field, &gyro_field],
+            ResampleMethod::Linear,
+        )?;
+
+        let high_resolution = self.header.is_high_resolution();
+        let scale = |field: &str| {
+            if high_resolution && is_high_resolution_field(field) {
+                HIGH_RESOLUTION_SCALE
+            } else {
+                1.0
+            }
+        };
+        let setpoint_scale = scale(&setpoint_field);
+        let setpoint: Vec<f64> = resampled.values[0]
+            .iter()
+            .map(|value| value / setpoint_scale)
+            .collect();
+        let gyro_scale = self.header.gyro_scale().unwrap_or(1.0);
+        let gyro_resolution = scale(&gyro_field);
+        let gyro: Vec<f64> = resampled.values[1]
+            .iter()
+            .map(|&value| convert_gyro_to_deg_per_sec(value / gyro_resolution, gyro_scale))
+            .collect();
+
+        let window_len = (WINDOW_S * sample_rate_hz).round() as usize;
+        let response_len = ((RESPONSE_S * sample_rate_hz).round() as usize).min(window_len);
+        if window_len < 2 || setpoint.len() < window_len {
+            return Err(anyhow!(
+                "step response needs at least {WINDOW_S} s of frames"
+            ));
+        }
+        let deconvolver = Deconvolver::new(window_len, sample_rate_hz);
+        let settled_from = (STEADY_STATE_FROM_S * sample_rate_hz) as usize;
+
+        let mut sums = vec![0.0; response_len];
+        let mut windows = 0;
+        let step = (window_len / WINDOW_OVERLAP).max(1);
+        for start in (0..=setpoint.len() - window_len).step_by(step) {
+            let input = &setpoint[start..start + window_len];
+            let output = &gyro[start..start + window_len];
+            if input.iter().chain(output).any(|value| value.is_nan())
+                || input.iter().all(|value| value.abs() < MIN_INPUT_DEG_S)
+            {
+                continue;
+            }
+            let response = deconvolver.step_response(input, output, response_len);
+            let settled = mean(&response[settled_from.min(response_len - 1)..]);
+            if (SETTLED_RANGE.0..=SETTLED_RANGE.1).contains(&settled) {
+                for (sum, value) in sums.iter_mut().zip(&response) {
+                    *sum += value;
+                }
+                windows += 1;
+            }
+        }
+        if windows == 0 {
+            return Err(anyhow!(
+                "no window on axis {axis} with setpoint above {MIN_INPUT_DEG_S} deg/s and a settled response"
+            ));
+        }
+
+        let response: Vec<f64> = sums.into_iter().map(|sum| sum / windows as f64).collect();
+        let steady_state = mean(&response[settled_from.min(response_len - 1)..]);
+        let (peak_index, peak) =
+            response
+                .iter()
+                .copied()
+                .enumerate()
+                .fold((0, f64::MIN), |best, (index, value)| {
+                    if value > best.1 {
+                        (index, value)
+                    } else {
+                        best
+                    }
+                });
+        let mut step_response = StepResponse {
+            axis,
+            sample_rate_hz,
+            response,
+            windows,
+            steady_state,
+            peak,
+            peak_time_ms: 0.0,
+            overshoot_percent: ((peak / steady_state - 1.0) * 100.0).max(0.0),
+            delay_ms: None,
+            rise_time_ms: None,
+        };
+        step_response.peak_time_ms = step_response.time_ms(peak_index);
+        step_response.delay_ms = step_response.crossing_ms(0.5);
+        step_response.rise_time_ms = step_response
+            .crossing_ms(0.9)
+            .zip(step_response.crossing_ms(0.1))
+            .map(|(high, low)| high - low);
+        Ok(step_response)
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Wiener deconvolution of Hann-windowed setpoint and gyro windows
+struct Deconvolver {
+    forward: Arc<dyn RealToComplex<f64>>,
+    inverse: Arc<dyn ComplexToReal<f64>>,
+    planner_len: usize,
+    window: Vec<f64>,
+    /// Noise-to-signal ratio per frequency bin
+    noise: Vec<f64>,
+}
+
+impl Deconvolver {
+    fn new(window_len: usize, sample_rate_hz: f64) -> Self {
+        let window = (0..window_len)
+            .map(|i| {
+                let phase = 2.0 * std::f64::consts::PI * i as f64 / window_len as f64;
+                0.5 - 0.5 * phase.cos()
+            })
+            .collect();
+        let planner_len = window_len.next_power_of_two();
+        let bin_width = sample_rate_hz / planner_len as f64;
+        // Signal-to-noise ratio 10 below the cutoff, falling smoothly to
+        // nothing above it
+        let noise = (0..planner_len / 2 + 1)
+            .map(|bin| {
+                let frequency = bin as f64 * bin_width;
+                let above =
+                    1.0 / (1.0 + (-1.702 * (frequency - CUTOFF_HZ) / (CUTOFF_HZ / 3.0)).exp());
+                1.0 / (10.0 * (1.0 - above) + 1e-9)
+            })
+            .collect();
+        let mut planner = RealFftPlanner::new();
+        Self {
+            forward: planner.plan_fft_forward(planner_len),
+            inverse: planner.plan_fft_inverse(planner_len),
+            planner_len,
+            window,
+            noise,
+        }
+    }
+
+    /// Integrated impulse response from `input` to `output`
+    fn step_response(&self, input: &[f64], output: &[f64], response_len: usize) -> Vec<f64> {
+        let spectrum = |samples: &[f64]| {
+            let mut padded = vec![0.0; self.planner_len];
+            for ((padded, value), w) in padded.iter_mut().zip(samples).zip(&self.window) {
+                *padded = value * w;
+            }
+            let mut bins = self.forward.make_output_vec();
+            self.forward
+                .process(&mut padded, &mut bins)
+                .expect("FFT buffers match the plan");
+            bins
+        };
+        let input_bins = spectrum(input);
+        let output_bins = spectrum(output);
+
+        let mut impulse_bins: Vec<Complex<f64>> = input_bins
+            .iter()
+            .zip(&output_bins)
+            .zip(&self.noise)
+            .map(|((input, output), noise)| output * input.conj() / (input.norm_sqr() + noise))
+            .collect();
+        // A real signal has real DC and Nyquist bins
+        let last = impulse_bins.len() - 1;
+        impulse_bins[0].im = 0.0;
+        impulse_bins[last].im = 0.0;
+        let mut impulse = self.inverse.make_output_vec();
+        self.inverse
+            .process(&mut impulse_bins, &mut impulse)
+            .expect("FFT buffers match the plan");
+
+        impulse
+            .iter()
+            .take(response_len)
+            .scan(0.0, |sum, value| {
+                *sum += value / self.planner_len as f64;
+                Some(*sum)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_response() {
+        // Random setpoint steps at 1 kHz, tracked by a first-order lag with a
+        // 10 ms time constant
+        let mut log = BBLLog::new(1, 1);
+        let alpha = 1.0 - (-0.1f64).exp();
+        let mut seed = 12345u64;
+        let mut random = move || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as f64 / (1u64 << 31) as f64
+        };
+        let (mut setpoint, mut gyro, mut hold) = (0.0, 0.0, 0);
+        for i in 0..6000u64 {
+            if hold == 0 {
+                setpoint = (random() - 0.5) * 800.0;
+                hold = 30 + (random() * 200.0) as u32;
+            }
+            hold -= 1;
+            gyro += (setpoint - gyro) * alpha;
+            log.push_main_frame(
+                i * 1000,
+                0,
+                [
+                    ("setpoint[0]", setpoint.round() as i32),
+                    ("gyroADC[0]", gyro.round() as i32),
+                ],
+            );
+        }
+
+        let step = log.step_response(0).unwrap();
+        assert_eq!(step.response.len(), 500);
+        assert_eq!(step.time_ms(500), 500.0);
+        assert!(step.windows > 50, "{}", step.windows);
+        assert!((step.steady_state - 1.0).abs() < 0.1, "{step:?}");
+        assert!(step.overshoot_percent < 3.0, "{}", step.overshoot_percent);
+        // ln(2) and ln(9) time constants
+        let delay = step.delay_ms.unwrap();
+        assert!((delay - 6.9).abs() < 2.0, "{delay}");
+        let rise = step.rise_time_ms.unwrap();
+        assert!((rise - 22.0).abs() < 4.0, "{rise}");
+
+        assert!(log.step_response(1).is_err());
+        assert!(log.step_response(3).is_err());
+    }
+}
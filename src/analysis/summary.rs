@@ -0,0 +1,221 @@
+//! One-glance summary of a flight
+
+use crate::analysis::battery::MIN_BATTERY_VOLTAGE;
+use crate::analysis::{GpsTripStats, LinkHealth, MotorAnalysis};
+use crate::conversion::{
+    convert_amperage_with_meter, convert_gyro_to_deg_per_sec, convert_high_resolution_value,
+    convert_vbat_to_volts,
+};
+use crate::types::{BBLLog, GpsCoordinate};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Mean Earth radius used for GPS distances
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+/// Headline numbers of a log, from its main frames and GPS fixes
+///
+/// Values the log has no data for are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FlightSummary {
+    pub duration_s: f64,
+    /// Highest `baroAlt`, or highest GPS altitude above the first fix
+    pub max_altitude_m: Option<f64>,
+    /// Highest GPS ground speed
+    pub max_speed_m_s: Option<f64>,
+    pub max_current_a: Option<f32>,
+    /// Lowest battery voltage while a battery was connected
+    pub min_voltage_v: Option<f32>,
+    /// Charge drawn from the battery ([`BBLLog::mah_used`])
+    pub mah_used: Option<f32>,
+    /// Highest rotation rate on any gyro axis
+    pub max_gyro_deg_s: Option<f64>,
+    /// Times the failsafe engaged (`failsafePhase` leaving idle)
+    pub failsafes: u32,
+    /// Failsafe and RX loss details ([`BBLLog::link_health`])
+    pub link: Option<LinkHealth>,
+    /// Motor saturation and possible desyncs ([`BBLLog::motor_analysis`])
+    pub motors: Option<MotorAnalysis>,
+    /// Distance flown between GPS fixes
+    pub gps_distance_m: Option<f64>,
+    /// Distance, distance from home, speed and altitude of the GPS track
+    /// ([`BBLLog::gps_trip_stats`])
+    pub gps: Option<GpsTripStats>,
+}
+
+impl FlightSummary {
+    /// Serialize as a JSON object (`json` feature)
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+impl BBLLog {
+    /// Duration, altitude, speed, battery, gyro, failsafe, motor and GPS
+    /// trip figures of the log, for a "how did the flight go" view.
+    ///
+    /// # Examples
+    /// ```rust,no_run
+    /// use bbl_parser::{parse_bbl_file, ExportOptions};
+    /// use std::path::Path;
+    ///
+    /// let log = parse_bbl_file(Path::new("flight.BBL"), ExportOptions::default(), false).unwrap();
+    /// let summary = log.flight_summary();
+    /// println!("{:.0} s, {:?} mAh", summary.duration_s, summary.mah_used);
+    /// ```
+    pub fn flight_summary(&self) -> FlightSummary {
+        let firmware = &self.header.firmware_revision;
+        let column = |name: &str| self.frames.column(name).filter(|values| !values.is_empty());
+        let max_of = |values: &mut dyn Iterator<Item = f64>| values.reduce(f64::max);
+
+        let max_altitude_m = match column("baroAlt") {
+            Some(baro_cm) => max_of(&mut baro_cm.iter().map(|&cm| cm as f64 / 100.0)),
+            None => self.gps_coordinates.first().and_then(|first| {
+                max_of(
+                    &mut self
+                        .gps_coordinates
+                        .iter()
+                        .map(|fix| fix.altitude - first.altitude),
+                )
+            }),
+        };
+        let max_speed_m_s = max_of(&mut self.gps_coordinates.iter().filter_map(|fix| fix.speed));
+
+        let current_meter = self.header.current_meter();
+        let max_current_a = column("amperageLatest").and_then(|raw| {
+            raw.iter()
+                .map(|&raw| convert_amperage_with_meter(raw, firmware, current_meter))
+                .reduce(f32::max)
+        });
+        let min_voltage_v = column("vbatLatest").and_then(|raw| {
+            raw.iter()
+                .map(|&raw| convert_vbat_to_volts(raw, firmware))
+                .filter(|&volts| volts >= MIN_BATTERY_VOLTAGE)
+                .reduce(f32::min)
+        });
+
+        let high_resolution = self.header.is_high_resolution();
+        let gyro_scale = self.header.gyro_scale().unwrap_or(1.0);
+        let max_gyro_deg_s = max_of(
+            &mut (0..3)
+                .filter_map(|axis| column(&format!("gyroADC[{axis}]")))
+                .flatten()
+                .map(|&raw| {
+                    let value = if high_resolution {
+                        convert_high_resolution_value(raw)
+                    } else {
+                        raw as f64
+                    };
+                    convert_gyro_to_deg_per_sec(value, gyro_scale).abs()
+                }),
+        );
+
+        let link = self.link_health();
+
+        FlightSummary {
+            duration_s: self.duration_seconds(),
+            max_altitude_m,
+            max_speed_m_s,
+            max_current_a,
+            min_voltage_v,
+            mah_used: self.mah_used(),
+            max_gyro_deg_s,
+            failsafes: link.as_ref().map_or(0, |link| link.failsafe_entries),
+            link,
+            motors: self.motor_analysis(),
+            gps_distance_m: gps_distance_m(&self.gps_coordinates),
+            gps: self.gps_trip_stats(),
+        }
+    }
+}
+
+/// Great-circle distance in meters between two `(latitude, longitude)` positions
+pub(crate) fn haversine_distance_m(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lat2) = (from.0.to_radians(), to.0.to_radians());
+    let delta_lat = lat2 - lat1;
+    let delta_lon = (to.1 - from.1).to_radians();
+    let a =
+        (delta_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (delta_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+}
+
+/// Path length through the fixes, or `None` without at least two fixes
+pub(crate) fn gps_distance_m(fixes: &[GpsCoordinate]) -> Option<f64> {
+    (fixes.len() >= 2).then(|| {
+        fixes
+            .windows(2)
+            .map(|pair| {
+                haversine_distance_m(
+                    (pair[0].latitude, pair[0].longitude),
+                    (pair[1].latitude, pair[1].longitude),
+                )
+            })
+            .sum()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fix(latitude: f64, altitude: f64, speed: f64) -> GpsCoordinate {
+        GpsCoordinate {
+            latitude,
+            longitude: 10.0,
+            altitude,
+            timestamp_us: 0,
+            num_sats: Some(12),
+            speed: Some(speed),
+            ground_course: None,
+        }
+    }
+
+    #[test]
+    fn test_flight_summary() {
+        let mut log = BBLLog::new(1, 1);
+        log.header.firmware_revision = "Betaflight 4.5.1 (77d01ba3b) STM32F7X2".to_string();
+        let rows = [
+            (1_000_000, 1680, 500, -300, 0),
+            (2_000_000, 1600, 2500, 650, 1),
+            (3_000_000, 1550, 1500, 120, 0),
+            (4_000_000, 30, 0, 0, 2),
+        ];
+        for (time, vbat, amperage, gyro, failsafe) in rows {
+            log.push_main_frame(
+                time,
+                0,
+                [
+                    ("vbatLatest", vbat),
+                    ("amperageLatest", amperage),
+                    ("gyroADC[1]", gyro),
+                    ("failsafePhase", failsafe),
+                ],
+            );
+        }
+        log.stats.start_time_us = 1_000_000;
+        log.stats.end_time_us = 4_000_000;
+        log.gps_coordinates = vec![fix(50.0, 100.0, 3.0), fix(50.001, 130.5, 12.5)];
+
+        let summary = log.flight_summary();
+        assert_eq!(summary.duration_s, 3.0);
+        assert_eq!(summary.max_altitude_m, Some(30.5));
+        assert_eq!(summary.max_speed_m_s, Some(12.5));
+        assert_eq!(summary.max_current_a, Some(25.0));
+        assert_eq!(summary.min_voltage_v, Some(15.5));
+        assert_eq!(summary.max_gyro_deg_s, Some(650.0));
+        assert_eq!(summary.failsafes, 2);
+        assert_eq!(summary.link.unwrap().failsafe_time_us, 1_000_000);
+        let distance = summary.gps_distance_m.unwrap();
+        assert!((distance - 111.2).abs() < 0.1, "{distance}");
+        assert_eq!(summary.gps.unwrap().max_altitude_m, 30.5);
+
+        // 25 A, 15 A and 0 A for a second each
+        let mah = summary.mah_used.unwrap();
+        assert!((mah - 11.111).abs() < 0.001, "{mah}");
+
+        assert_eq!(BBLLog::new(1, 1).flight_summary(), FlightSummary::default());
+    }
+}
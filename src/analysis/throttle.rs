@@ -0,0 +1,51 @@
+//! Throttle position derived from the throttle RC command
+
+use crate::conversion::convert_throttle_to_percent;
+use crate::types::BBLLog;
+
+impl BBLLog {
+    /// Throttle percent for every main frame, from `rcCommand[3]`.
+    ///
+    /// Honors the `minthrottle` / `maxthrottle` headers and 3D mode (see
+    /// [`convert_throttle_to_percent`]). Returns `None` when the log has no
+    /// throttle command field; frames without a value read as 0%.
+    pub fn throttle_percent(&self) -> Option<Vec<f32>> {
+        let throttle = self.frames.column("rcCommand[3]")?;
+        let (min, max) = self.header.throttle_range();
+        let is_3d = self.header.is_3d_mode();
+        Some(
+            throttle
+                .iter()
+                .map(|&raw| convert_throttle_to_percent(raw, min, max, is_3d))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_percent() {
+        let mut log = BBLLog::new(1, 1);
+        assert_eq!(log.throttle_percent(), None);
+
+        log.header
+            .sysconfig
+            .insert("minthrottle".to_string(), 1100.into());
+        log.header
+            .sysconfig
+            .insert("maxthrottle".to_string(), 2000.into());
+        for raw in [1100, 1550, 2000] {
+            log.push_main_frame(0, 0, [("rcCommand[3]", raw)]);
+        }
+        assert_eq!(log.throttle_percent(), Some(vec![0.0, 50.0, 100.0]));
+
+        // 3D mode centres zero throttle on the stick midpoint
+        log.header
+            .sysconfig
+            .insert("features".to_string(), (1 << 12).into());
+        assert_eq!(log.throttle_percent(), Some(vec![-100.0, 10.0, 100.0]));
+    }
+}
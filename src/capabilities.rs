@@ -0,0 +1,111 @@
+//! Runtime introspection of the crate version and build features
+//!
+//! Front-ends that bundle the crate can call [`capabilities()`] to find out
+//! which inputs, encodings, predictors and export formats this build supports.
+
+use crate::parser::decoder::{SUPPORTED_ENCODINGS, SUPPORTED_PREDICTORS};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// What this build of the crate supports
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Capabilities {
+    /// Crate version (`CARGO_PKG_VERSION`)
+    pub version: String,
+    /// Cargo features compiled into this build
+    pub features: Vec<String>,
+    /// Input file types accepted by `parse_bbl_file*`
+    pub input_formats: Vec<String>,
+    /// Field encoding IDs the frame decoder can read
+    pub encodings: Vec<u8>,
+    /// Field predictor IDs the frame decoder applies
+    pub predictors: Vec<u8>,
+    /// Export formats available from the `export` module
+    pub export_formats: Vec<String>,
+}
+
+impl Capabilities {
+    /// Whether the named Cargo feature is enabled in this build
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.features.iter().any(|f| f == feature)
+    }
+
+    /// Serialize as a JSON object (`json` feature)
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+/// Report the crate version, enabled features and supported formats
+///
+/// # Examples
+/// ```rust
+/// let caps = bbl_parser::capabilities();
+/// assert_eq!(caps.version, env!("CARGO_PKG_VERSION"));
+/// assert!(caps.export_formats.iter().any(|f| f == "csv"));
+/// ```
+pub fn capabilities() -> Capabilities {
+    let features = [
+        ("csv", cfg!(feature = "csv")),
+        ("cli", cfg!(feature = "cli")),
+        ("json", cfg!(feature = "json")),
+        ("serde", cfg!(feature = "serde")),
+        ("gzip", cfg!(feature = "gzip")),
+        ("zip", cfg!(feature = "zip")),
+        ("tracing", cfg!(feature = "tracing")),
+        ("arrow", cfg!(feature = "arrow")),
+        ("parquet", cfg!(feature = "parquet")),
+        ("spectrum", cfg!(feature = "spectrum")),
+    ];
+
+    let mut input_formats = vec!["bbl", "bfl", "txt"];
+    if cfg!(feature = "gzip") {
+        input_formats.push("gz");
+    }
+    if cfg!(feature = "zip") {
+        input_formats.push("zip");
+    }
+
+    let mut export_formats = vec![
+        "csv",
+        "gps-csv",
+        "gpx",
+        "kml",
+        "event",
+        "ndjson",
+        "headers-json",
+    ];
+    if cfg!(feature = "parquet") {
+        export_formats.push("parquet");
+    }
+
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        features: features
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| name.to_string())
+            .collect(),
+        input_formats: input_formats.into_iter().map(String::from).collect(),
+        encodings: SUPPORTED_ENCODINGS.to_vec(),
+        predictors: SUPPORTED_PREDICTORS.to_vec(),
+        export_formats: export_formats.into_iter().map(String::from).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_reflect_build() {
+        let caps = capabilities();
+        assert_eq!(caps.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(caps.has_feature("gzip"), cfg!(feature = "gzip"));
+        assert!(caps.encodings.contains(&crate::ENCODING_TAG8_8SVB));
+        assert!(caps.predictors.contains(&crate::PREDICT_MINMOTOR));
+    }
+}
@@ -5,16 +5,48 @@
 
 use semver::Version;
 
+/// Firmware family that recorded a log, which decides field units and flag names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FirmwareKind {
+    Betaflight,
+    EmuFlight,
+    Inav,
+    /// Helicopter firmware derived from Betaflight 4.3
+    Rotorflight,
+    Unknown,
+}
+
+impl FirmwareKind {
+    /// Firmware family named in a `Firmware revision` header value,
+    /// e.g. "INAV 7.1.0 (1a2b3c4d) MATEKF405"
+    pub fn from_revision(firmware_revision: &str) -> Self {
+        let revision = firmware_revision.to_lowercase();
+        if revision.contains("emuflight") {
+            Self::EmuFlight
+        } else if revision.contains("inav") {
+            Self::Inav
+        } else if revision.contains("rotorflight") {
+            Self::Rotorflight
+        } else if revision.contains("betaflight") {
+            Self::Betaflight
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
 /// Convert raw vbat value to volts with firmware-aware scaling
 pub fn convert_vbat_to_volts(raw_value: i32, firmware_revision: &str) -> f32 {
+    let firmware = FirmwareKind::from_revision(firmware_revision);
     // Determine scaling factor based on firmware
-    let scale_factor = if firmware_revision.contains("EmuFlight") {
+    let scale_factor = if firmware == FirmwareKind::EmuFlight {
         // EmuFlight always uses tenths
         0.1
-    } else if firmware_revision.contains("iNav") {
-        // iNav always uses hundredths
+    } else if matches!(firmware, FirmwareKind::Inav | FirmwareKind::Rotorflight) {
+        // INAV and Rotorflight always use hundredths
         0.01
-    } else if firmware_revision.contains("Betaflight") {
+    } else if firmware == FirmwareKind::Betaflight {
         // Betaflight version-dependent scaling
         if let Some(version) = extract_firmware_version(firmware_revision) {
             if version >= Version::new(4, 3, 0) {
@@ -53,6 +85,247 @@ pub fn convert_amperage_to_amps(raw_value: i32) -> f32 {
     raw_value as f32 / 100.0
 }
 
+/// Cleanflight's default virtual current meter calibration `(offset, scale)`
+pub const DEFAULT_VIRTUAL_CURRENT_METER: (i32, i32) = (0, 400);
+
+/// Current in amps that the Cleanflight virtual current meter, simulated by
+/// blackbox_decode's `--simulate-current-meter`, models for a raw
+/// `rcCommand[3]` throttle
+///
+/// With `t` the throttle above 1000, the meter draws
+/// `offset + (t + t² / 50) * scale / 1000` in 0.01 A units.
+pub fn convert_throttle_to_virtual_amps(throttle_raw: i32, offset: i32, scale: i32) -> f32 {
+    let throttle = (throttle_raw as i64 - 1000).max(0);
+    let factor = throttle + throttle * throttle / 50;
+    convert_amperage_to_amps((offset as i64 + factor * scale as i64 / 1000) as i32)
+}
+
+/// Whether the firmware logs `amperageLatest` as a raw current sensor ADC reading
+/// rather than in 0.01 A units
+///
+/// Betaflight switched to 0.01 A in 3.1.7; Cleanflight and Baseflight logs
+/// (detected as [`FirmwareKind::Unknown`]) always carry ADC readings.
+pub fn logs_amperage_adc(firmware_revision: &str) -> bool {
+    match FirmwareKind::from_revision(firmware_revision) {
+        FirmwareKind::Betaflight => extract_firmware_version(firmware_revision)
+            .is_some_and(|version| version < Version::new(3, 1, 7)),
+        FirmwareKind::Unknown => true,
+        _ => false,
+    }
+}
+
+/// Convert raw amperageLatest to amps, applying the `currentSensor` calibration
+/// `(offset, scale)` like blackbox_decode for firmware that logs ADC readings
+///
+/// Modern firmware applies the calibration before logging, so its values are
+/// converted with [`convert_amperage_to_amps`]; so are ADC readings of logs
+/// without a usable calibration header.
+pub fn convert_amperage_with_meter(
+    raw_value: i32,
+    firmware_revision: &str,
+    current_meter: Option<(i32, i32)>,
+) -> f32 {
+    match current_meter {
+        Some((offset, scale)) if scale != 0 && logs_amperage_adc(firmware_revision) => {
+            // 12-bit ADC against a 3.3 V reference; the scale is in 0.1 mV per amp
+            let millivolts = raw_value as f64 * 3300.0 / 4095.0 - offset as f64;
+            (millivolts * 10.0 / scale as f64) as f32
+        }
+        _ => convert_amperage_to_amps(raw_value),
+    }
+}
+
+/// Convert a raw motor output to percent of the `motorOutput` range
+///
+/// Values outside the range (e.g. 0 for a disarmed DShot motor) are clamped
+/// to 0–100%, so DShot and PWM logs compare directly.
+pub fn convert_motor_to_percent(raw_value: i32, min: i32, max: i32) -> f32 {
+    if max <= min {
+        return 0.0;
+    }
+    // i64 so corrupt values far outside the range cannot overflow
+    let percent = (i64::from(raw_value) - i64::from(min)) as f64
+        / (i64::from(max) - i64::from(min)) as f64
+        * 100.0;
+    (percent as f32).clamp(0.0, 100.0)
+}
+
+/// Throttle command midpoint used by 3D mode
+const THROTTLE_3D_MIDPOINT: i64 = 1500;
+
+/// Convert a raw `rcCommand[3]` value to throttle percent
+///
+/// Normal mode maps `min..max` to 0–100%. In 3D mode the stick centre is zero
+/// throttle, so the result runs from -100% (full reverse) to 100%.
+pub fn convert_throttle_to_percent(raw_value: i32, min: i32, max: i32, is_3d: bool) -> f32 {
+    if max <= min {
+        return 0.0;
+    }
+    // i64 so corrupt values far outside the range cannot overflow
+    let (raw_value, min, max) = (i64::from(raw_value), i64::from(min), i64::from(max));
+    if is_3d {
+        let percent = if raw_value >= THROTTLE_3D_MIDPOINT {
+            (raw_value - THROTTLE_3D_MIDPOINT) as f64 / (max - THROTTLE_3D_MIDPOINT).max(1) as f64
+        } else {
+            (raw_value - THROTTLE_3D_MIDPOINT) as f64 / (THROTTLE_3D_MIDPOINT - min).max(1) as f64
+        };
+        ((percent * 100.0) as f32).clamp(-100.0, 100.0)
+    } else {
+        (((raw_value - min) as f64 / (max - min) as f64 * 100.0) as f32).clamp(0.0, 100.0)
+    }
+}
+
+/// Factor applied by Betaflight's `blackbox_high_resolution` logging
+pub const HIGH_RESOLUTION_SCALE: f64 = 10.0;
+
+/// Whether a field is multiplied by [`HIGH_RESOLUTION_SCALE`] in high-resolution logs
+///
+/// Betaflight 4.4+ scales gyro rates and the roll/pitch/yaw RC command and
+/// setpoint; throttle (`rcCommand[3]`, `setpoint[3]`) keeps its normal range.
+pub fn is_high_resolution_field(field_name: &str) -> bool {
+    field_name.starts_with("gyroADC")
+        || field_name.starts_with("gyroUnfilt")
+        || matches!(
+            field_name,
+            "rcCommand[0]"
+                | "rcCommand[1]"
+                | "rcCommand[2]"
+                | "setpoint[0]"
+                | "setpoint[1]"
+                | "setpoint[2]"
+        )
+}
+
+/// Convert a high-resolution raw value back to its normal-resolution units
+pub fn convert_high_resolution_value(raw_value: i32) -> f64 {
+    raw_value as f64 / HIGH_RESOLUTION_SCALE
+}
+
+/// Convert a raw gyro value to degrees per second using the header's `gyro_scale`
+///
+/// Cleanflight-derived firmware (Betaflight, EmuFlight, INAV) logs the scale in
+/// degrees per second per raw unit, usually 1.0. High-resolution values must
+/// be brought back to normal resolution first ([`convert_high_resolution_value`]).
+pub fn convert_gyro_to_deg_per_sec(raw_value: f64, gyro_scale: f32) -> f64 {
+    raw_value * gyro_scale as f64
+}
+
+/// Convert a raw accelerometer value (`accSmooth[n]`) to g using the `acc_1G` header
+pub fn convert_acc_to_g(raw_value: i32, acc_1g: i32) -> f64 {
+    if acc_1g <= 0 {
+        return 0.0;
+    }
+    raw_value as f64 / acc_1g as f64
+}
+
+/// Full-scale raw `rssi` value
+const RSSI_MAX_VALUE: i32 = 1023;
+
+/// Convert a raw `rssi` value (0-1023) to percent
+pub fn convert_rssi_to_percent(raw_value: i32) -> f32 {
+    (raw_value as f32 / RSSI_MAX_VALUE as f32 * 100.0).clamp(0.0, 100.0)
+}
+
+/// Rate curve that turns stick deflection into a rotation rate (`rates_type` header)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum RatesType {
+    /// RC rate, super rate and expo; the only curve before Betaflight 4.1
+    #[default]
+    Betaflight,
+    Raceflight,
+    Kiss,
+    /// Center sensitivity, max rate and expo
+    Actual,
+    Quick,
+}
+
+impl RatesType {
+    /// Rate curve of a `rates_type` header value
+    pub fn from_header(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(Self::Betaflight),
+            1 => Some(Self::Raceflight),
+            2 => Some(Self::Kiss),
+            3 => Some(Self::Actual),
+            4 => Some(Self::Quick),
+            _ => None,
+        }
+    }
+}
+
+/// Rate settings of one axis, as logged in the `rc_rates`, `rates` and
+/// `rc_expo` headers
+///
+/// The meaning of each value depends on the [`RatesType`]; with Actual rates,
+/// `rc_rate` is the center sensitivity and `super_rate` the max rate, both in
+/// tens of deg/s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AxisRates {
+    pub rc_rate: i32,
+    pub super_rate: i32,
+    pub expo: i32,
+}
+
+/// Highest setpoint Betaflight commands, in deg/s
+const SETPOINT_RATE_LIMIT: f64 = 1998.0;
+
+/// Convert an `rcCommand[0..2]` value (±500 at full stick) to the setpoint it
+/// commands in deg/s, following Betaflight's rate curves
+///
+/// High-resolution values must be brought back to normal resolution first
+/// ([`convert_high_resolution_value`]).
+pub fn convert_rc_command_to_setpoint(
+    rc_command: f64,
+    rates: AxisRates,
+    rates_type: RatesType,
+) -> f64 {
+    let command = (rc_command / 500.0).clamp(-1.0, 1.0);
+    let command_abs = command.abs();
+    let rc_rate = rates.rc_rate as f64;
+    let super_rate = rates.super_rate as f64;
+    let expo = rates.expo as f64 / 100.0;
+    let super_factor =
+        |deflection: f64| 1.0 / (1.0 - deflection * super_rate / 100.0).clamp(0.01, 1.0);
+
+    let rate = match rates_type {
+        RatesType::Betaflight => {
+            let curved = command * command_abs.powi(3) * expo + command * (1.0 - expo);
+            let mut rc_rate = rc_rate / 100.0;
+            if rc_rate > 2.0 {
+                rc_rate += 14.54 * (rc_rate - 2.0);
+            }
+            200.0 * rc_rate * curved * super_factor(command_abs)
+        }
+        RatesType::Raceflight => {
+            let curved = (1.0 + expo * (command * command - 1.0)) * command;
+            10.0 * rc_rate * curved * (1.0 + command_abs * super_rate / 100.0)
+        }
+        RatesType::Kiss => {
+            let curved = (command.powi(3) * expo + command * (1.0 - expo)) * rc_rate / 1000.0;
+            2000.0 * super_factor(command_abs) * curved
+        }
+        RatesType::Actual => {
+            let curved = command_abs * (command.powi(5) * expo + command * (1.0 - expo));
+            let center_sensitivity = rc_rate * 10.0;
+            let stick_movement = (super_rate * 10.0 - center_sensitivity).max(0.0);
+            command * center_sensitivity + stick_movement * curved
+        }
+        RatesType::Quick => {
+            let rc_rate = rc_rate * 2.0;
+            let max_rate = (super_rate * 10.0).max(rc_rate);
+            if rc_rate <= 0.0 {
+                return 0.0;
+            }
+            let super_config = (max_rate / rc_rate - 1.0) / (max_rate / rc_rate);
+            let curved = command_abs.powi(3) * expo + command_abs * (1.0 - expo);
+            let super_factor = 1.0 / (1.0 - curved * super_config).clamp(0.01, 1.0);
+            command * rc_rate * super_factor
+        }
+    };
+    rate.clamp(-SETPOINT_RATE_LIMIT, SETPOINT_RATE_LIMIT)
+}
+
 /// Extract major firmware version number
 pub fn extract_major_firmware_version(firmware_revision: &str) -> u8 {
     // Extract major version from firmware string like "Betaflight 4.5.1 (77d01ba3b) AT32F435M"
@@ -64,7 +337,8 @@ pub fn extract_major_firmware_version(firmware_revision: &str) -> u8 {
             }
         }
     }
-    // Default to 4 if parsing fails (assume modern firmware)
+    // Default to 4 if parsing fails (assume modern firmware); this includes
+    // the calendar versions that followed Betaflight 4.5, such as 2025.12
     4
 }
 
@@ -76,6 +350,13 @@ pub fn convert_gps_coordinate(raw_value: i32) -> f64 {
 
 /// Convert GPS altitude with firmware-aware unit conversion
 pub fn convert_gps_altitude(raw_value: i32, firmware_revision: &str) -> f64 {
+    // INAV logs whole meters (`gpsSol.llh.alt / 100`)
+    match FirmwareKind::from_revision(firmware_revision) {
+        FirmwareKind::Inav => return raw_value as f64,
+        // Versioned independently of the Betaflight 4 code it logs with
+        FirmwareKind::Rotorflight => return raw_value as f64 / 10.0,
+        _ => {}
+    }
     // Altitude units changed between firmware versions:
     // Before Betaflight 4: centimeters (factor 0.01)
     // Betaflight 4+: decimeters (factor 0.1)
@@ -152,6 +433,208 @@ pub fn format_flight_mode_flags(flags: i32) -> String {
     }
 }
 
+/// INAV flight mode names, indexed by bit (`flightModeFlags_e` in INAV's `runtime_config.h`)
+const INAV_FLIGHT_MODES: [&str; 18] = [
+    "ANGLE_MODE",
+    "HORIZON_MODE",
+    "HEADING_MODE",
+    "NAV_ALTHOLD_MODE",
+    "NAV_RTH_MODE",
+    "NAV_POSHOLD_MODE",
+    "HEADFREE_MODE",
+    "NAV_LAUNCH_MODE",
+    "MANUAL_MODE",
+    "FAILSAFE_MODE",
+    "AUTO_TUNE",
+    "NAV_WP_MODE",
+    "NAV_COURSE_HOLD_MODE",
+    "FLAPERON",
+    "TURN_ASSISTANT",
+    "TURTLE_MODE",
+    "SOARING_MODE",
+    "ANGLEHOLD_MODE",
+];
+
+/// Join the names of the set bits in `flags` with `|`, or `"0"` when none is
+/// set; `names` is indexed by bit and empty names mark unused bits
+fn format_mode_flags(names: &[&str], flags: i32) -> String {
+    let modes: Vec<&str> = names
+        .iter()
+        .enumerate()
+        .filter(|&(bit, name)| flags & (1 << bit) != 0 && !name.is_empty())
+        .map(|(_, &name)| name)
+        .collect();
+    if modes.is_empty() {
+        "0".to_string()
+    } else {
+        modes.join("|")
+    }
+}
+
+/// Format INAV flight mode flags for CSV output, like [`format_flight_mode_flags`]
+pub fn format_inav_flight_mode_flags(flags: i32) -> String {
+    format_mode_flags(&INAV_FLIGHT_MODES, flags)
+}
+
+/// INAV navigation state names, indexed by the logged `navState` value
+/// (`navigationPersistentId_e` in INAV's `navigation_private.h`); retired
+/// states are empty
+const INAV_NAV_STATES: [&str; 49] = [
+    "UNDEFINED",
+    "IDLE",
+    "ALTHOLD_INITIALIZE",
+    "ALTHOLD_IN_PROGRESS",
+    "",
+    "",
+    "POSHOLD_3D_INITIALIZE",
+    "POSHOLD_3D_IN_PROGRESS",
+    "RTH_INITIALIZE",
+    "RTH_CLIMB_TO_SAFE_ALT",
+    "RTH_HEAD_HOME",
+    "RTH_LOITER_PRIOR_TO_LANDING",
+    "RTH_LANDING",
+    "RTH_FINISHING",
+    "RTH_FINISHED",
+    "WAYPOINT_INITIALIZE",
+    "WAYPOINT_PRE_ACTION",
+    "WAYPOINT_IN_PROGRESS",
+    "WAYPOINT_REACHED",
+    "WAYPOINT_NEXT",
+    "WAYPOINT_FINISHED",
+    "WAYPOINT_RTH_LAND",
+    "EMERGENCY_LANDING_INITIALIZE",
+    "EMERGENCY_LANDING_IN_PROGRESS",
+    "EMERGENCY_LANDING_FINISHED",
+    "LAUNCH_INITIALIZE",
+    "LAUNCH_WAIT",
+    "",
+    "LAUNCH_IN_PROGRESS",
+    "COURSE_HOLD_INITIALIZE",
+    "COURSE_HOLD_IN_PROGRESS",
+    "COURSE_HOLD_ADJUSTING",
+    "CRUISE_INITIALIZE",
+    "CRUISE_IN_PROGRESS",
+    "CRUISE_ADJUSTING",
+    "WAYPOINT_HOLD_TIME",
+    "RTH_LOITER_ABOVE_HOME",
+    "",
+    "RTH_TRACKBACK",
+    "MIXERAT_INITIALIZE",
+    "MIXERAT_IN_PROGRESS",
+    "MIXERAT_ABORT",
+    "FW_LANDING_CLIMB_TO_LOITER",
+    "FW_LANDING_LOITER",
+    "FW_LANDING_APPROACH",
+    "FW_LANDING_GLIDE",
+    "FW_LANDING_FLARE",
+    "FW_LANDING_ABORT",
+    "FW_LANDING_FINISHED",
+];
+
+/// Format an INAV `navState` value as its navigation state name
+pub fn format_inav_nav_state(state: i32) -> String {
+    usize::try_from(state)
+        .ok()
+        .and_then(|index| INAV_NAV_STATES.get(index))
+        .filter(|name| !name.is_empty())
+        .map_or_else(|| state.to_string(), |name| name.to_string())
+}
+
+/// Rotorflight flight mode names, indexed by bit (`flightModeFlags_e` in
+/// Rotorflight's `runtime_config.h`)
+const ROTORFLIGHT_FLIGHT_MODES: [&str; 9] = [
+    "ANGLE_MODE",
+    "HORIZON_MODE",
+    "MAG_MODE",
+    "TRAINER_MODE",
+    "ALT_HOLD_MODE",
+    "POS_HOLD_MODE",
+    "RESCUE_MODE",
+    "FAILSAFE_MODE",
+    "GPS_RESCUE_MODE",
+];
+
+/// Format Rotorflight flight mode flags for CSV output, like [`format_flight_mode_flags`]
+pub fn format_rotorflight_flight_mode_flags(flags: i32) -> String {
+    format_mode_flags(&ROTORFLIGHT_FLIGHT_MODES, flags)
+}
+
+/// Rotorflight governor state names, indexed by the logged `governorState`
+/// value (`govState_e` in Rotorflight's `governor.h`)
+const ROTORFLIGHT_GOVERNOR_STATES: [&str; 10] = [
+    "THROTTLE_OFF",
+    "THROTTLE_IDLE",
+    "SPOOLING_UP",
+    "RECOVERY",
+    "ACTIVE",
+    "THROTTLE_HOLD",
+    "FALLBACK",
+    "AUTOROTATION",
+    "BAILOUT",
+    "DISABLED",
+];
+
+/// Format a Rotorflight `governorState` value as its governor state name
+pub fn format_rotorflight_governor_state(state: i32) -> String {
+    usize::try_from(state)
+        .ok()
+        .and_then(|index| ROTORFLIGHT_GOVERNOR_STATES.get(index))
+        .map_or_else(|| state.to_string(), |name| name.to_string())
+}
+
+/// Whether a Rotorflight field is logged in revolutions per minute: the main
+/// and tail rotor speeds
+pub fn is_rotorflight_rpm_field(field_name: &str) -> bool {
+    matches!(field_name, "headspeed" | "tailspeed")
+}
+
+/// EmuFlight flight mode names, indexed by bit (`flightModeFlags_e` in
+/// EmuFlight's `runtime_config.h`); unused bits are empty
+const EMUFLIGHT_FLIGHT_MODES: [&str; 13] = [
+    "ANGLE_MODE",
+    "HORIZON_MODE",
+    "MAG_MODE",
+    "BARO_MODE",
+    "",
+    "GPS_HOLD_MODE",
+    "HEADFREE_MODE",
+    "",
+    "PASSTHRU_MODE",
+    "",
+    "FAILSAFE_MODE",
+    "GPS_RESCUE_MODE",
+    "NFE_RACE_MODE",
+];
+
+/// Format EmuFlight flight mode flags for CSV output, like [`format_flight_mode_flags`]
+pub fn format_emuflight_flight_mode_flags(flags: i32) -> String {
+    format_mode_flags(&EMUFLIGHT_FLIGHT_MODES, flags)
+}
+
+/// Whether a field holds flags or a state that [`format_flag_field`] writes as text
+pub fn is_flag_field(field_name: &str) -> bool {
+    field_name.contains("Flag")
+        || matches!(field_name, "failsafePhase" | "navState" | "governorState")
+}
+
+/// Format a flag or state field as text for the firmware that logged it;
+/// fields without names (e.g. INAV `navFlags`) keep their raw value
+pub fn format_flag_field(field_name: &str, value: i32, firmware: FirmwareKind) -> String {
+    match (field_name, firmware) {
+        ("flightModeFlags", FirmwareKind::Inav) => format_inav_flight_mode_flags(value),
+        ("flightModeFlags", FirmwareKind::EmuFlight) => format_emuflight_flight_mode_flags(value),
+        ("flightModeFlags", FirmwareKind::Rotorflight) => {
+            format_rotorflight_flight_mode_flags(value)
+        }
+        ("flightModeFlags", _) => format_flight_mode_flags(value),
+        ("stateFlags", _) => format_state_flags(value),
+        ("failsafePhase", _) => format_failsafe_phase(value),
+        ("navState", FirmwareKind::Inav) => format_inav_nav_state(value),
+        ("governorState", FirmwareKind::Rotorflight) => format_rotorflight_governor_state(value),
+        _ => value.to_string(),
+    }
+}
+
 /// Format state flags for CSV output
 pub fn format_state_flags(flags: i32) -> String {
     let mut states = Vec::new();
@@ -203,6 +686,164 @@ pub fn format_failsafe_phase(phase: i32) -> String {
     }
 }
 
+/// Betaflight `debug_mode` names, indexed by the header value
+/// (`debugModeNames` in Betaflight 4.3+ `build/debug.c`, up to 4.6/2025.12)
+const BETAFLIGHT_DEBUG_MODES: [&str; 98] = [
+    "NONE",
+    "CYCLETIME",
+    "BATTERY",
+    "GYRO_FILTERED",
+    "ACCELEROMETER",
+    "PIDLOOP",
+    "GYRO_SCALED",
+    "RC_INTERPOLATION",
+    "ANGLERATE",
+    "ESC_SENSOR",
+    "SCHEDULER",
+    "STACK",
+    "ESC_SENSOR_RPM",
+    "ESC_SENSOR_TMP",
+    "ALTITUDE",
+    "FFT",
+    "FFT_TIME",
+    "FFT_FREQ",
+    "RX_FRSKY_SPI",
+    "RX_SFHSS_SPI",
+    "GYRO_RAW",
+    "DUAL_GYRO_RAW",
+    "DUAL_GYRO_DIFF",
+    "MAX7456_SIGNAL",
+    "MAX7456_SPICLOCK",
+    "SBUS",
+    "FPORT",
+    "RANGEFINDER",
+    "RANGEFINDER_QUALITY",
+    "LIDAR_TF",
+    "ADC_INTERNAL",
+    "RUNAWAY_TAKEOFF",
+    "SDIO",
+    "CURRENT_SENSOR",
+    "USB",
+    "SMARTAUDIO",
+    "RTH",
+    "ITERM_RELAX",
+    "ACRO_TRAINER",
+    "RC_SMOOTHING",
+    "RX_SIGNAL_LOSS",
+    "RC_SMOOTHING_RATE",
+    "ANTI_GRAVITY",
+    "DYN_LPF",
+    "RX_SPEKTRUM_SPI",
+    "DSHOT_RPM_TELEMETRY",
+    "RPM_FILTER",
+    "D_MIN",
+    "AC_CORRECTION",
+    "AC_ERROR",
+    "DUAL_GYRO_SCALED",
+    "DSHOT_RPM_ERRORS",
+    "CRSF_LINK_STATISTICS_UPLINK",
+    "CRSF_LINK_STATISTICS_PWR",
+    "CRSF_LINK_STATISTICS_DOWN",
+    "BARO",
+    "GPS_RESCUE_THROTTLE_PID",
+    "DYN_IDLE",
+    "FEEDFORWARD_LIMIT",
+    "FEEDFORWARD",
+    "BLACKBOX_OUTPUT",
+    "GYRO_SAMPLE",
+    "RX_TIMING",
+    "D_LPF",
+    "VTX_TRAMP",
+    "GHST",
+    "GHST_MSP",
+    "SCHEDULER_DETERMINISM",
+    "TIMING_ACCURACY",
+    "RX_EXPRESSLRS_SPI",
+    "RX_EXPRESSLRS_PHASELOCK",
+    "RX_STATE_TIME",
+    "GPS_RESCUE_VELOCITY",
+    "GPS_RESCUE_HEADING",
+    "GPS_RESCUE_TRACKING",
+    "GPS_CONNECTION",
+    "ATTITUDE",
+    "VTX_MSP",
+    "GPS_DOP",
+    "FAILSAFE",
+    "GYRO_CALIBRATION",
+    "ANGLE_MODE",
+    "ANGLE_TARGET",
+    "CURRENT_ANGLE",
+    "DSHOT_TELEMETRY_COUNTS",
+    "RPM_LIMIT",
+    "RC_STATS",
+    "MAG_CALIB",
+    "MAG_TASK_RATE",
+    "EZLANDING",
+    "TPA",
+    "S_TERM",
+    "SPA",
+    "TASK",
+    "GIMBAL",
+    "WING_SETPOINT",
+    "AUTOPILOT_ALTITUDE",
+    "AUTOPILOT_POSITION",
+];
+
+/// Name of a Betaflight `debug_mode` header value, e.g. `6` → `GYRO_SCALED`
+pub fn betaflight_debug_mode_name(debug_mode: i32) -> Option<&'static str> {
+    usize::try_from(debug_mode)
+        .ok()
+        .and_then(|index| BETAFLIGHT_DEBUG_MODES.get(index))
+        .copied()
+}
+
+/// Betaflight feature names, indexed by bit (`features_e` in Betaflight's
+/// `feature.h`, named as in its CLI); unused bits are empty, and `VBAT`,
+/// `FAILSAFE`, `CURRENT_METER`, `SOFTSPI` and `DYNAMIC_FILTER` were retired
+/// by later versions
+const BETAFLIGHT_FEATURES: [&str; 30] = [
+    "RX_PPM",
+    "VBAT",
+    "INFLIGHT_ACC_CAL",
+    "RX_SERIAL",
+    "MOTOR_STOP",
+    "SERVO_TILT",
+    "SOFTSERIAL",
+    "GPS",
+    "FAILSAFE",
+    "RANGEFINDER",
+    "TELEMETRY",
+    "CURRENT_METER",
+    "3D",
+    "RX_PARALLEL_PWM",
+    "RX_MSP",
+    "RSSI_ADC",
+    "LED_STRIP",
+    "DISPLAY",
+    "OSD",
+    "",
+    "CHANNEL_FORWARDING",
+    "TRANSPONDER",
+    "AIRMODE",
+    "",
+    "",
+    "RX_SPI",
+    "SOFTSPI",
+    "ESC_SENSOR",
+    "ANTI_GRAVITY",
+    "DYNAMIC_FILTER",
+];
+
+/// Names of the features enabled in a Betaflight `features` header value, in bit order
+pub fn betaflight_feature_names(features: i32) -> Vec<&'static str> {
+    BETAFLIGHT_FEATURES
+        .iter()
+        .enumerate()
+        .filter(|&(bit, name)| features & (1 << bit) != 0 && !name.is_empty())
+        .map(|(_, &name)| name)
+        .collect()
+}
+
 // ============================================================================
 // GPX Timestamp Generation (for GPS export)
 // ============================================================================
@@ -451,8 +1092,188 @@ fn is_leap_year(year: u32) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_high_resolution_fields() {
+        assert!(is_high_resolution_field("gyroADC[0]"));
+        assert!(is_high_resolution_field("gyroUnfilt[2]"));
+        assert!(is_high_resolution_field("setpoint[2]"));
+        assert!(!is_high_resolution_field("rcCommand[3]"));
+        assert!(!is_high_resolution_field("setpoint[3]"));
+        assert!(!is_high_resolution_field("motor[0]"));
+        assert_eq!(convert_high_resolution_value(-1234), -123.4);
+    }
+
+    #[test]
+    fn test_amperage_with_current_meter() {
+        let header = crate::parser::header::parse_headers_from_text(
+            "H Firmware revision:Cleanflight v1.13.0\nH currentMeter:-50,400\n",
+            crate::parser::diagnostics::DiagnosticReporter::silent(),
+        )
+        .unwrap();
+        let meter = header.current_meter();
+        assert_eq!(meter, Some((-50, 400)));
+
+        // ADC 1000 reads 805.9 mV, plus the 50 mV offset, at 40 mV per amp
+        let amps = convert_amperage_with_meter(1000, &header.firmware_revision, meter);
+        assert!((amps - 21.40).abs() < 0.01, "{amps}");
+        assert!(logs_amperage_adc("Betaflight 3.1.6 (abc123) NAZE"));
+        assert_eq!(
+            convert_amperage_with_meter(1000, "Betaflight 3.1.6 (abc123) NAZE", Some((0, 0))),
+            10.0
+        );
+
+        // Modern firmware logs calibrated 0.01 A values
+        for firmware in [
+            "Betaflight 4.5.1 (77d01ba3b) STM32F7X2",
+            "INAV 7.1.0 (abc) F405",
+        ] {
+            assert!(!logs_amperage_adc(firmware));
+            assert_eq!(convert_amperage_with_meter(1500, firmware, meter), 15.0);
+        }
+    }
+
+    #[test]
+    fn test_convert_throttle_to_virtual_amps() {
+        let (offset, scale) = DEFAULT_VIRTUAL_CURRENT_METER;
+        assert_eq!(convert_throttle_to_virtual_amps(1000, offset, scale), 0.0);
+        assert_eq!(convert_throttle_to_virtual_amps(900, offset, scale), 0.0);
+        // Half throttle: (500 + 5000) * 400 / 1000 = 2200 cA
+        assert_eq!(convert_throttle_to_virtual_amps(1500, offset, scale), 22.0);
+        assert_eq!(convert_throttle_to_virtual_amps(2000, offset, scale), 84.0);
+        assert_eq!(convert_throttle_to_virtual_amps(1000, 150, scale), 1.5);
+    }
+
+    #[test]
+    fn test_convert_throttle_to_percent() {
+        assert_eq!(convert_throttle_to_percent(1000, 1000, 2000, false), 0.0);
+        assert_eq!(convert_throttle_to_percent(1535, 1070, 2000, false), 50.0);
+        assert_eq!(convert_throttle_to_percent(2100, 1000, 2000, false), 100.0);
+        assert_eq!(convert_throttle_to_percent(1500, 1000, 2000, true), 0.0);
+        assert_eq!(convert_throttle_to_percent(1750, 1000, 2000, true), 50.0);
+        assert_eq!(convert_throttle_to_percent(1000, 1000, 2000, true), -100.0);
+        // Corrupt values do not overflow
+        assert_eq!(
+            convert_throttle_to_percent(i32::MIN, i32::MAX - 1, i32::MAX, false),
+            0.0
+        );
+        assert_eq!(
+            convert_throttle_to_percent(i32::MAX, i32::MIN, 0, false),
+            100.0
+        );
+        assert_eq!(
+            convert_throttle_to_percent(i32::MIN, i32::MIN, i32::MAX, true),
+            -100.0
+        );
+        assert_eq!(
+            convert_throttle_to_percent(i32::MAX, i32::MIN, i32::MAX, true),
+            100.0
+        );
+    }
+
+    #[test]
+    fn test_inav_conversions() {
+        let revision = "INAV 7.1.2 (4e1e59eb) MATEKF405";
+        let firmware = FirmwareKind::from_revision(revision);
+        assert_eq!(firmware, FirmwareKind::Inav);
+        assert_eq!(
+            FirmwareKind::from_revision("Betaflight 4.5.1 (77d01ba3b) AT32F435M"),
+            FirmwareKind::Betaflight
+        );
+
+        assert_eq!(
+            format_flag_field("flightModeFlags", 0b1_0000_0001, firmware),
+            "ANGLE_MODE|MANUAL_MODE"
+        );
+        assert_eq!(
+            format_flag_field("flightModeFlags", 0b1_0000_0001, FirmwareKind::Betaflight),
+            "ANGLE_MODE|PASSTHRU"
+        );
+        assert_eq!(format_flag_field("navState", 38, firmware), "RTH_TRACKBACK");
+        assert_eq!(format_flag_field("navState", 4, firmware), "4");
+        assert_eq!(format_flag_field("navState", 99, firmware), "99");
+        assert_eq!(format_flag_field("navFlags", 3, firmware), "3");
+
+        assert_eq!(convert_gps_altitude(123, revision), 123.0);
+        assert_eq!(convert_vbat_to_volts(1680, revision), 16.8);
+    }
+
+    #[test]
+    fn test_rotorflight_conversions() {
+        let revision = "Rotorflight 2.0.0 (4a3f8e1c) STM32F7X2";
+        let firmware = FirmwareKind::from_revision(revision);
+        assert_eq!(firmware, FirmwareKind::Rotorflight);
+
+        assert_eq!(
+            format_flag_field("flightModeFlags", 0b1000_1000, firmware),
+            "TRAINER_MODE|FAILSAFE_MODE"
+        );
+        assert_eq!(format_flag_field("governorState", 4, firmware), "ACTIVE");
+        assert_eq!(format_flag_field("governorState", 20, firmware), "20");
+        assert!(is_rotorflight_rpm_field("headspeed"));
+        assert!(!is_rotorflight_rpm_field("motor[0]"));
+
+        assert_eq!(convert_gps_altitude(1234, revision), 123.4);
+        assert_eq!(convert_vbat_to_volts(2500, revision), 25.0);
+    }
+
+    #[test]
+    fn test_emuflight_flight_modes() {
+        let firmware = FirmwareKind::from_revision("EmuFlight 0.4.1 (9d8fe8bde) HELIOSPRING");
+        assert_eq!(firmware, FirmwareKind::EmuFlight);
+        assert_eq!(
+            format_flag_field("flightModeFlags", (1 << 12) | (1 << 3), firmware),
+            "BARO_MODE|NFE_RACE_MODE"
+        );
+        assert_eq!(format_flag_field("flightModeFlags", 1 << 4, firmware), "0");
+        assert_eq!(
+            format_flag_field("flightModeFlags", 1 << 3, FirmwareKind::Betaflight),
+            "BARO"
+        );
+    }
+
+    #[test]
+    fn test_betaflight_2025_logs() {
+        // Betaflight 4.6 is versioned by release date
+        let revision = "Betaflight 2025.12.1 (85d201376) STM32H743";
+        assert_eq!(
+            FirmwareKind::from_revision(revision),
+            FirmwareKind::Betaflight
+        );
+        assert_eq!(convert_vbat_to_volts(1680, revision), 16.8);
+        assert_eq!(convert_gps_altitude(1234, revision), 123.4);
+        assert_eq!(betaflight_debug_mode_name(89), Some("EZLANDING"));
+        assert_eq!(betaflight_debug_mode_name(90), Some("TPA"));
+        assert_eq!(betaflight_debug_mode_name(97), Some("AUTOPILOT_POSITION"));
+        assert_eq!(betaflight_debug_mode_name(98), None);
+    }
+
+    #[test]
+    fn test_convert_motor_to_percent() {
+        assert_eq!(convert_motor_to_percent(48, 48, 2047), 0.0);
+        assert_eq!(convert_motor_to_percent(2047, 48, 2047), 100.0);
+        assert_eq!(convert_motor_to_percent(1500, 1000, 2000), 50.0);
+        // Disarmed DShot motors log 0, below the range minimum
+        assert_eq!(convert_motor_to_percent(0, 48, 2047), 0.0);
+        assert_eq!(convert_motor_to_percent(1500, 2000, 1000), 0.0);
+        // Corrupt values do not overflow
+        assert_eq!(
+            convert_motor_to_percent(i32::MIN, i32::MAX - 1, i32::MAX),
+            0.0
+        );
+        assert_eq!(convert_motor_to_percent(i32::MAX, i32::MIN, 0), 100.0);
+        assert_eq!(convert_motor_to_percent(0, i32::MIN, i32::MAX), 50.0);
+    }
+
     // Tests for parse_datetime_to_epoch - locking in Betaflight datetime parsing behavior
 
+    #[test]
+    fn test_convert_rssi_to_percent() {
+        assert_eq!(convert_rssi_to_percent(0), 0.0);
+        assert_eq!(convert_rssi_to_percent(1023), 100.0);
+        assert!((convert_rssi_to_percent(512) - 50.05).abs() < 0.01);
+        assert_eq!(convert_rssi_to_percent(2000), 100.0);
+    }
+
     #[test]
     fn test_parse_datetime_utc_z_suffix() {
         // Standard Betaflight format with Z suffix (UTC)
@@ -1,77 +1,114 @@
-use std::fmt;
+use std::path::PathBuf;
 
 /// Custom error types for BBL parsing
-#[derive(Debug)]
+///
+/// The parsing and export functions return [`Result`], so callers match on
+/// the failure directly; `?` still converts it into `anyhow::Error` or
+/// `Box<dyn Error>`.
+///
+/// # Examples
+/// ```rust
+/// use bbl_parser::{parse_bbl_bytes, BBLError, ExportOptions};
+///
+/// let err = parse_bbl_bytes(b"not a log", ExportOptions::default(), false).unwrap_err();
+/// assert!(matches!(err, BBLError::NoLogsFound));
+/// ```
+#[derive(Debug, thiserror::Error)]
 pub enum BBLError {
     /// I/O errors
-    Io(std::io::Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
     /// UTF-8 parsing errors
-    Utf8(std::str::Utf8Error),
+    #[error("UTF-8 error: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
     /// Parse errors with context
+    #[error("Parse error: {0}")]
     Parse(String),
-    /// Invalid header format
-    InvalidHeader(String),
+    /// The input contains no blackbox log start marker
+    #[error("No blackbox log headers found")]
+    NoLogsFound,
+    /// Malformed header line or header section
+    #[error("Invalid header: {0}")]
+    HeaderParse(String),
     /// Invalid frame data
+    #[error("Invalid frame: {0}")]
     InvalidFrame(String),
+    /// A frame could not be decoded
+    #[error("Failed to decode {frame_type} frame at offset {offset}")]
+    FrameDecode { offset: usize, frame_type: char },
     /// Unsupported data version
+    #[error("Unsupported data version: {0}")]
     UnsupportedVersion(u8),
     /// End of file reached unexpectedly
+    #[error("Unexpected end of file")]
     UnexpectedEof,
-    /// Invalid encoding type
-    InvalidEncoding(u8),
+    /// Field encoding the decoder does not implement
+    #[error("Unsupported encoding type: {0}")]
+    UnsupportedEncoding(u8),
     /// Invalid predictor type
+    #[error("Invalid predictor type: {0}")]
     InvalidPredictor(u8),
-    /// Export format error
-    Export(String),
+    /// The requested log number is not in the input
+    #[error("Log {log_number} not found; the input contains {total_logs} log(s)")]
+    LogNotFound {
+        log_number: usize,
+        total_logs: usize,
+    },
+    /// A log's CSV columns differ from those of the first log in a concatenated CSV
+    #[error("Log {log_number} has different CSV columns than the first log")]
+    ColumnMismatch { log_number: usize },
+    /// A log is larger than [`crate::ParserConfig::max_log_size`]
+    #[error("Log {log_number} exceeds the configured maximum of {limit} bytes")]
+    LogTooLarge { log_number: usize, limit: usize },
+    /// Decompressing the input would exceed `limit` bytes
+    #[error("Decompressed input exceeds the maximum of {limit} bytes")]
+    InputTooLarge { limit: u64 },
+    /// Writing an export file failed because of `source`
+    #[error("Failed to export {}", path.display())]
+    ExportFailed {
+        path: PathBuf,
+        source: Box<BBLError>,
+    },
 }
 
-impl fmt::Display for BBLError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            BBLError::Io(err) => write!(f, "I/O error: {}", err),
-            BBLError::Utf8(err) => write!(f, "UTF-8 error: {}", err),
-            BBLError::Parse(msg) => write!(f, "Parse error: {}", msg),
-            BBLError::InvalidHeader(msg) => write!(f, "Invalid header: {}", msg),
-            BBLError::InvalidFrame(msg) => write!(f, "Invalid frame: {}", msg),
-            BBLError::UnsupportedVersion(version) => {
-                write!(f, "Unsupported data version: {}", version)
-            }
-            BBLError::UnexpectedEof => write!(f, "Unexpected end of file"),
-            BBLError::InvalidEncoding(encoding) => write!(f, "Invalid encoding type: {}", encoding),
-            BBLError::InvalidPredictor(predictor) => {
-                write!(f, "Invalid predictor type: {}", predictor)
-            }
-            BBLError::Export(msg) => write!(f, "Export error: {}", msg),
+impl BBLError {
+    /// [`BBLError::ExportFailed`] for `path`, caused by `source`
+    pub(crate) fn export_failed(path: impl Into<PathBuf>, source: impl Into<BBLError>) -> Self {
+        BBLError::ExportFailed {
+            path: path.into(),
+            source: Box::new(source.into()),
         }
     }
 }
 
-impl std::error::Error for BBLError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        match self {
-            BBLError::Io(err) => Some(err),
-            BBLError::Utf8(err) => Some(err),
-            _ => None,
-        }
+/// Recovers the typed error an internal `anyhow::Error` was raised with; any
+/// other error becomes [`BBLError::Parse`] with its full message chain
+impl From<anyhow::Error> for BBLError {
+    fn from(err: anyhow::Error) -> Self {
+        err.downcast::<BBLError>()
+            .unwrap_or_else(|err| BBLError::Parse(format!("{err:#}")))
     }
 }
 
-impl From<std::io::Error> for BBLError {
-    fn from(err: std::io::Error) -> Self {
-        BBLError::Io(err)
-    }
-}
+pub type Result<T> = std::result::Result<T, BBLError>;
 
-impl From<std::str::Utf8Error> for BBLError {
-    fn from(err: std::str::Utf8Error) -> Self {
-        BBLError::Utf8(err)
-    }
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
 
-impl From<anyhow::Error> for BBLError {
-    fn from(err: anyhow::Error) -> Self {
-        BBLError::Parse(err.to_string())
+    #[test]
+    fn test_typed_error_survives_context() {
+        let err = Err::<(), _>(BBLError::UnexpectedEof)
+            .context("while reading frame")
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<BBLError>(),
+            Some(BBLError::UnexpectedEof)
+        ));
+        assert!(matches!(BBLError::from(err), BBLError::UnexpectedEof));
+
+        let err = anyhow::anyhow!("plain message");
+        assert!(matches!(BBLError::from(err), BBLError::Parse(m) if m == "plain message"));
     }
 }
-
-pub type Result<T> = std::result::Result<T, BBLError>;
@@ -3,13 +3,21 @@
 //! Contains functions for exporting parsed BBL data to various formats
 //! including CSV, GPX, and Event files.
 
+use crate::analysis::{
+    Attitude, AttitudeEstimator, ClimbRate, EnergyIntegrator, HomeDistance, HomeReference,
+    DEFAULT_CLIMB_RATE_TIME_CONSTANT_S,
+};
 use crate::conversion::*;
+use crate::error::{BBLError, Result};
+use crate::filters::FilterThresholds;
+use crate::parser::FrameSink;
 use crate::types::*;
-use anyhow::{Context, Result};
+use anyhow::Context;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufWriter, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -22,8 +30,60 @@ use serde::{Deserialize, Serialize};
 /// - `csv`: Export flight data to CSV format (requires `csv` feature)
 /// - `gpx`: Export GPS coordinates to GPX format for mapping
 /// - `event`: Export events to JSON format
+/// - `kml`: Export the GPS track to KML for Google Earth
+/// - `gps_csv`: Export GPS fixes to a blackbox_decode-style `.gps.csv`
+/// - `ndjson`: Stream main frames to `<name>.ndjson` while parsing
+///   ([`NdjsonWriter`])
+/// - `parquet`: Export flight data to `<name>.parquet` ([`export_to_parquet`];
+///   requires the `parquet` feature)
 /// - `output_dir`: Optional custom output directory (defaults to input file's parent directory)
 /// - `force_export`: Skip all filtering heuristics and always export
+/// - `preserve_order`: Keep CSV rows in decode order instead of sorting by timestamp
+///   (default `true`, matching blackbox_decode)
+/// - `gap_fill`: How CSV export treats main frames missing from the log
+///   (default [`GapFillPolicy::Leave`])
+/// - `motor_percent`: Add `motor[n] (%)` CSV columns normalized to the log's
+///   `motorOutput` range
+/// - `throttle_percent`: Add a `throttle (%)` CSV column derived from `rcCommand[3]`
+/// - `gyro_deg_per_sec`: Write `gyroADC`/`gyroUnfilt` CSV columns in deg/s using
+///   the log's `gyro_scale` header, like Blackbox Explorer
+/// - `acc_g`: Write `accSmooth` CSV columns in g using the log's `acc_1G` header
+/// - `setpoint_deg_per_sec`: Add `setpoint[0..2] (deg/s)` CSV columns computed
+///   from `rcCommand` with the log's rate settings ([`BBLLog::setpoint_deg_per_sec`])
+/// - `vbat_per_cell`: Add a `vbatPerCell (V)` CSV column dividing the battery
+///   voltage by the log's cell count ([`BBLLog::cell_count`])
+/// - `simulate_imu`: Add `roll (deg)`, `pitch (deg)` and `heading (deg)` CSV
+///   columns estimated from gyro and accelerometer ([`crate::AttitudeEstimator`])
+/// - `mag_heading`: Add a tilt-compensated `heading (deg)` CSV column for logs
+///   with `magADC` fields ([`BBLLog::magnetic_heading`])
+/// - `declination_deg`: Magnetic declination added to the `heading (deg)` column
+/// - `climb_rate`: Add a smoothed `climbRate (m/s)` CSV column from `baroAlt`
+///   or GPS altitude ([`BBLLog::climb_rate`])
+/// - `simulate_current_meter`: For logs without `amperageLatest`, add
+///   `amperageLatest (A)` and `energyCumulative (mAh)` CSV columns from a
+///   virtual current meter driven by `rcCommand[3]`
+///   ([`crate::convert_throttle_to_virtual_amps`])
+/// - `sim_current_meter_offset` / `sim_current_meter_scale`: Virtual current
+///   meter calibration used instead of the log's `currentSensor` header
+/// - `merge_gps`: Append the nearest GPS fix to every flight data CSV row
+/// - `home_distance`: Add `homeDistance (m)` and `homeDistance3D (m)` columns
+///   to the `.gps.csv` and merged GPS columns ([`BBLLog::home_distances`])
+/// - `event_column`: Add an `event` CSV column naming the events logged at each row
+/// - `raw_units`: Write every flight data CSV field as its decoded integer
+///   (no volts/amps conversion, flag text or high-resolution scaling)
+/// - `debug_mode_names`: Name `debug[n]` CSV columns after the log's `debug_mode`
+/// - `csv_fields`: Only write flight data CSV columns matching these patterns
+///   (`*`/`?` wildcards, e.g. `gyroADC[*]`), in pattern order; empty writes all
+/// - `csv_options`: Delimiter, quoting, padding and line ending of the flight
+///   data CSV (default: blackbox_decode's `", "`-separated, padded layout)
+/// - `compat`: Reproduce another tool's output exactly ([`CompatMode`])
+/// - `split_frame_types`: Also write S and G frames at their own rate to
+///   `<name>.S.csv` and `<name>.G.csv`
+/// - `compress`: Gzip the CSV, event and custom exporter files (`.csv.gz`;
+///   requires the `gzip` feature)
+/// - `gps_gap_threshold_us`: Start a new GPX track segment after a GPS time gap
+///   longer than this (default 2 s, `0` disables)
+/// - `exporters`: Additional [`Exporter`]s run by [`export_registered`]
 ///
 /// # Examples
 /// ```rust
@@ -36,10 +96,12 @@ use serde::{Deserialize, Serialize};
 ///     event: false,
 ///     output_dir: None,
 ///     force_export: false,
+///     ..Default::default()
 /// };
 /// ```
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct ExportOptions {
     /// Enable CSV export of flight data
     pub csv: bool,
@@ -47,1053 +109,5495 @@ pub struct ExportOptions {
     pub gpx: bool,
     /// Enable JSON export of flight events
     pub event: bool,
+    /// Enable KML export of the GPS track
+    pub kml: bool,
+    /// Enable `.gps.csv` export of GPS fixes
+    pub gps_csv: bool,
+    /// Stream main frames to `<name>.ndjson` as they are decoded; the caller
+    /// sets up the [`NdjsonWriter`] frame sink, the flag names the output for
+    /// [`resolve_export_base_name`]
+    pub ndjson: bool,
+    /// Enable Parquet export of flight data ([`export_to_parquet`], `parquet` feature)
+    pub parquet: bool,
     /// Optional custom output directory (defaults to input file parent)
     pub output_dir: Option<String>,
     /// If true, export all logs without applying filtering heuristics
     pub force_export: bool,
+    /// Thresholds of the filtering heuristics ([`crate::should_skip_export_with_thresholds`])
+    pub filter_thresholds: FilterThresholds,
+    /// Write CSV rows in decode order; when false, rows are sorted by timestamp
+    pub preserve_order: bool,
+    /// How CSV export treats main frames missing from the log
+    pub gap_fill: GapFillPolicy,
+    /// Add `motor[n] (%)` CSV columns normalized to the `motorOutput` header range
+    pub motor_percent: bool,
+    /// Add a `throttle (%)` CSV column honoring min/max throttle and 3D mode
+    pub throttle_percent: bool,
+    /// Write gyro CSV columns as `gyroADC[n] (deg/s)` scaled by the `gyro_scale` header
+    pub gyro_deg_per_sec: bool,
+    /// Write accelerometer CSV columns as `accSmooth[n] (g)` scaled by the `acc_1G` header
+    pub acc_g: bool,
+    /// Add `setpoint[n] (deg/s)` CSV columns computed from `rcCommand[n]` and the rate headers
+    pub setpoint_deg_per_sec: bool,
+    /// Add a `vbatPerCell (V)` CSV column: battery voltage divided by [`BBLLog::cell_count`]
+    pub vbat_per_cell: bool,
+    /// Add `roll (deg)`, `pitch (deg)` and `heading (deg)` CSV columns from a
+    /// simulated IMU, like blackbox_decode's `--simulate-imu`
+    pub simulate_imu: bool,
+    /// Add a tilt-compensated `heading (deg)` CSV column when the log has
+    /// `magADC` fields
+    pub mag_heading: bool,
+    /// Declination in degrees (east positive) added to `heading (deg)`, for
+    /// true instead of magnetic headings
+    pub declination_deg: f64,
+    /// Add a `climbRate (m/s)` CSV column, from `baroAlt` or else the GPS altitude
+    pub climb_rate: bool,
+    /// Simulate `amperageLatest (A)` and `energyCumulative (mAh)` from the
+    /// throttle when the log has no current sensor, like blackbox_decode's
+    /// `--simulate-current-meter`
+    pub simulate_current_meter: bool,
+    /// Virtual current meter offset in 0.01 A, instead of the header's
+    pub sim_current_meter_offset: Option<i32>,
+    /// Virtual current meter scale, instead of the header's
+    pub sim_current_meter_scale: Option<i32>,
+    /// Append the nearest-in-time GPS fix as extra flight data CSV columns
+    pub merge_gps: bool,
+    /// Add the ground and straight-line distance from home to `.gps.csv` and
+    /// merged GPS CSV columns when the log has a home position
+    pub home_distance: bool,
+    /// Add an `event` CSV column with the E-frame events logged at each row
+    pub event_column: bool,
+    /// Write decoded integers to the flight data CSV: `vbatLatest`,
+    /// `amperageLatest` and flag fields keep their raw values and names, and
+    /// high-resolution fields are not scaled
+    pub raw_units: bool,
+    /// Suffix `debug[n]` CSV column names with the log's `debug_mode`, e.g.
+    /// `debug[0] (GYRO_SCALED)` (Betaflight logs)
+    pub debug_mode_names: bool,
+    /// Flight data CSV column patterns (`*` and `?` wildcards, brackets are
+    /// literal) matched against CSV names such as `vbatLatest (V)` or log field
+    /// names such as `vbatLatest`; columns are written in pattern order and an
+    /// empty list writes every column
+    pub csv_fields: Vec<String>,
+    /// Dialect of the flight data CSV
+    pub csv_options: CsvOptions,
+    /// Output conventions to follow; [`CompatMode::Native`] by default
+    pub compat: CompatMode,
+    /// Also write S frames to `<name>.S.csv` and raw G frames to
+    /// `<name>.G.csv`, one row per logged frame instead of forward-filled
+    /// onto every main frame row; enables S/G frame collection while parsing.
+    /// S frames carry no time and get that of the preceding main frame
+    pub split_frame_types: bool,
+    /// Gzip the flight data, headers, GPS CSV, event and custom exporter files
+    /// as they are written, appending `.gz` to their names (`gzip` feature)
+    pub compress: bool,
+    /// Split the GPX track into a new `<trkseg>` when consecutive GPS fixes
+    /// are further apart than this many microseconds (`0` disables); with
+    /// `merge_gps`, rows further than this from any fix get empty GPS columns
+    pub gps_gap_threshold_us: u64,
+    /// Part of each log to write to the flight data and per-frame-type CSVs;
+    /// the whole log by default
+    pub time_range: TimeRange,
+    /// What to do when a log's output files already exist; applied by
+    /// [`resolve_export_base_name`], whose result is passed to the export
+    /// functions as their `base_name_override`
+    pub overwrite: OverwritePolicy,
+    /// Custom exporters run by [`export_registered`], one output file each
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub exporters: Vec<Arc<dyn Exporter>>,
 }
 
-/// Result of an export operation, containing paths of all files that were created.
+/// Policy for main frames missing from the log when writing CSV
 ///
-/// Any path that is `None` indicates that export format was not requested or
-/// no data was available for export (e.g., empty GPS coordinates for GPX export).
-#[derive(Debug, Clone, Default)]
+/// Gaps are detected from `loopIteration` jumps larger than the log's usual
+/// step between main frames. Synthesized rows advance `loopIteration` and
+/// `time` evenly across the gap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-pub struct ExportReport {
-    /// Path to the main CSV data file (None if CSV export was not performed)
-    pub csv_path: Option<std::path::PathBuf>,
-    /// Path to the CSV headers file (None if CSV export was not performed)
-    pub headers_path: Option<std::path::PathBuf>,
-    /// Path to the GPX file (None if GPX export was not performed or GPS data was empty)
-    pub gpx_path: Option<std::path::PathBuf>,
-    /// Path to the event JSON file (None if event export was not performed or no events were found)
-    pub event_path: Option<std::path::PathBuf>,
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum GapFillPolicy {
+    /// Write only the frames present in the log
+    #[default]
+    Leave,
+    /// Repeat the last row's values for every missing frame
+    RepeatLast,
+    /// Linearly interpolate main-frame fields across the gap; slow-frame
+    /// (flag and state) fields repeat the last value
+    Interpolate,
 }
 
-/// Extract the base filename from an input path with consistent fallback.
-/// Used by all export functions and path computation helpers to ensure
-/// consistent naming across CSV, GPX, and event exports.
+/// Window of a log to export, as offsets from its first main frame
 ///
-/// Always returns "blackbox" as fallback for missing or non-UTF-8 filenames,
-/// ensuring compute_export_paths() predictions match actual export filenames.
-fn extract_base_name(input_path: &Path) -> &str {
-    input_path
-        .file_stem()
-        .and_then(|s| s.to_str())
-        .unwrap_or("blackbox")
+/// Either side may be left open. [`ExportOptions::time_range`] trims the
+/// flight data CSVs; pass [`TimeRange::gps_coordinates`] to the GPS exports to
+/// trim them the same way.
+///
+/// # Examples
+/// ```rust
+/// use bbl_parser::{ExportOptions, TimeRange};
+///
+/// // 1:05 to 2:30 into the flight
+/// let opts = ExportOptions {
+///     csv: true,
+///     time_range: TimeRange {
+///         start_us: Some(65_000_000),
+///         end_us: Some(150_000_000),
+///     },
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimeRange {
+    /// First offset to export, in microseconds
+    pub start_us: Option<u64>,
+    /// Last offset to export, in microseconds
+    pub end_us: Option<u64>,
 }
 
-/// Sanitize a `base_name_override` value for safe use in file path construction.
-/// Returns the final path component only, preventing directory traversal via `../`
-/// segments or absolute paths. Returns `None` if the value is empty or ends in `..`.
-fn sanitize_base_name_override(base_name_override: Option<&str>) -> Option<&str> {
-    let raw = base_name_override?;
-    Path::new(raw).file_name().and_then(|s| s.to_str())
-}
+impl TimeRange {
+    /// Whether neither side is set, so nothing is trimmed
+    pub fn is_unbounded(&self) -> bool {
+        self.start_us.is_none() && self.end_us.is_none()
+    }
 
-/// Return a human-readable vendor name for a known filename prefix.
-/// Falls back to `"Unknown"` for unrecognised prefixes.
-pub fn vendor_name_for_prefix(prefix: &str) -> &'static str {
-    match prefix {
-        "EMUF_" => "EmuFlight",
-        "BTFL_" => "Betaflight",
-        "INAV_" => "iNav",
-        "QUIC_" => "Quicksilver",
-        _ => "Unknown",
+    /// First and last timestamp of `log` inside the window, both inclusive
+    pub fn bounds(&self, log: &BBLLog) -> (u64, u64) {
+        let origin_us = log.stats.start_time_us;
+        (
+            self.start_us
+                .map_or(0, |start_us| origin_us.saturating_add(start_us)),
+            self.end_us
+                .map_or(u64::MAX, |end_us| origin_us.saturating_add(end_us)),
+        )
+    }
+
+    /// GPS fixes of `log` inside the window
+    pub fn gps_coordinates(&self, log: &BBLLog) -> Vec<GpsCoordinate> {
+        let (start_us, end_us) = self.bounds(log);
+        log.gps_coordinates
+            .iter()
+            .filter(|coordinate| (start_us..=end_us).contains(&coordinate.timestamp_us))
+            .cloned()
+            .collect()
     }
 }
 
-/// Known firmware vendor filename prefixes mapped to their revision keywords.
-/// To add a new firmware: append `("PREFIX_", "keyword")` where keyword is a
-/// lowercase substring of that firmware's `H Firmware revision:` header value.
-/// For forks that share a base firmware's revision string, add an entry here
-/// for filename detection and add a corresponding entry to `FORK_REVISION_MAP`.
-const KNOWN_FIRMWARE_PREFIXES: &[(&str, &str)] = &[
-    ("EMUF_", "emuflight"),
-    ("BTFL_", "betaflight"),
-    ("INAV_", "inav"),
-    ("QUIC_", "quicksilver"), // Quicksilver; see FORK_REVISION_MAP for rename exemption
-];
+/// Policy for export files that already exist
+///
+/// A log counts as exported when any of the files enabled in [`ExportOptions`]
+/// exists, so all outputs of a log keep sharing one base name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum OverwritePolicy {
+    /// Replace existing files
+    #[default]
+    Overwrite,
+    /// Leave logs whose outputs exist alone, for incremental re-runs
+    SkipExisting,
+    /// Write under the first free `<name>-N` base name instead
+    Rename,
+}
 
-/// Fork firmware prefixes that intentionally report another vendor's revision string.
-/// Sessions in files with these prefixes are NOT renamed even when the revision string
-/// maps to a different canonical prefix — the mismatch is by design.
+/// Dialect of the flight data CSV
 ///
-/// Each entry is `(file_prefix, reported_canonical_prefix)`.
-/// To add a fork: append `("FORK_", "BASE_")` where BASE_ is what
-/// `firmware_prefix_for_revision` returns for that fork's revision headers.
-const FORK_REVISION_MAP: &[(&str, &str)] = &[
-    // Quicksilver writes Betaflight revision headers for Blackbox Explorer compatibility.
-    ("QUIC_", "BTFL_"),
-];
+/// The default reproduces blackbox_decode: fields separated by `", "`, numbers
+/// right-aligned to fixed widths, fields quoted only when they contain a comma,
+/// quote or line break, and `\n` line endings. [`CsvOptions::rfc4180`] and
+/// [`CsvOptions::tsv`] give strict CSV and tab-separated output.
+///
+/// # Examples
+/// ```rust
+/// use bbl_parser::{CsvOptions, ExportOptions};
+///
+/// let opts = ExportOptions {
+///     csv: true,
+///     csv_options: CsvOptions::rfc4180(),
+///     ..Default::default()
+/// };
+/// assert_eq!(opts.csv_options.delimiter, ",");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CsvOptions {
+    /// Separator written between fields
+    pub delimiter: String,
+    /// When fields are wrapped in double quotes
+    pub quote_style: CsvQuoteStyle,
+    /// Right-align numeric fields to blackbox_decode's fixed widths
+    pub padding: bool,
+    /// Line terminator of every record
+    pub line_ending: CsvLineEnding,
+}
 
-/// Return the canonical filename prefix for a firmware revision string (e.g. `"BTFL_"`),
-/// or `None` if the vendor is not recognised.
-pub fn firmware_prefix_for_revision(revision: &str) -> Option<&'static str> {
-    let rev_lower = revision.trim().to_lowercase();
-    for &(prefix, keyword) in KNOWN_FIRMWARE_PREFIXES {
-        if rev_lower.contains(keyword) {
-            return Some(prefix);
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ", ".to_string(),
+            quote_style: CsvQuoteStyle::Necessary,
+            padding: true,
+            line_ending: CsvLineEnding::Lf,
         }
     }
-    None
 }
 
-fn detect_bbl_filename_prefix(stem: &str) -> Option<&'static str> {
-    KNOWN_FIRMWARE_PREFIXES
-        .iter()
-        .find(|&&(prefix, _)| stem.starts_with(prefix))
-        .map(|&(prefix, _)| prefix)
-}
+impl CsvOptions {
+    /// Strict RFC 4180 CSV: `,` separators, no padding, CRLF line endings
+    pub fn rfc4180() -> Self {
+        Self {
+            delimiter: ",".to_string(),
+            padding: false,
+            line_ending: CsvLineEnding::CrLf,
+            ..Default::default()
+        }
+    }
 
-/// Compute a corrected base name for a session whose firmware vendor differs from
-/// the BBL filename prefix.  Returns `Some(corrected_stem)` when a replacement is
-/// needed; `None` when the vendors match, either is unrecognised, or the file prefix
-/// is a known fork that intentionally uses a different base firmware's revision string.
-///
-/// # Examples
-/// - `EMUF_BLACKBOX_LOG_...BBL` + `EmuFlight 0.4.3`          → `None` (matches)
-/// - `EMUF_BLACKBOX_LOG_...BBL` + `Betaflight 2025.12.0-beta` → `Some("BTFL_BLACKBOX_LOG_...")`
-/// - `QUIC_Twiglet_...BFL`      + `Betaflight 4.3.0`          → `None` (fork exemption)
-pub fn corrected_session_base_name(bbl_path: &Path, firmware_revision: &str) -> Option<String> {
-    let stem = bbl_path.file_stem()?.to_str()?;
-    let bbl_prefix = detect_bbl_filename_prefix(stem)?;
-    let session_prefix = firmware_prefix_for_revision(firmware_revision)?;
-    if bbl_prefix == session_prefix {
-        return None;
+    /// Tab-separated values without padding
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: "\t".to_string(),
+            padding: false,
+            ..Default::default()
+        }
     }
-    // Known fork: the revision mismatch is by design — do not rename
-    if FORK_REVISION_MAP
-        .iter()
-        .any(|&(fp, bp)| fp == bbl_prefix && bp == session_prefix)
-    {
-        return None;
+
+    /// Write one field of a record, preceded by the delimiter unless `first`
+    fn write_field<W: Write>(
+        &self,
+        writer: &mut W,
+        first: bool,
+        field: &str,
+    ) -> std::io::Result<()> {
+        if !first {
+            writer.write_all(self.delimiter.as_bytes())?;
+        }
+        let field = if self.padding {
+            field
+        } else {
+            field.trim_start()
+        };
+        let quoted = match self.quote_style {
+            CsvQuoteStyle::Always => true,
+            CsvQuoteStyle::Never => false,
+            CsvQuoteStyle::Necessary => {
+                // Spaces around the separator are layout, not part of it
+                let separator = match self.delimiter.trim_matches(' ') {
+                    "" => self.delimiter.as_str(),
+                    separator => separator,
+                };
+                field.contains(['"', '\r', '\n'])
+                    || (!separator.is_empty() && field.contains(separator))
+            }
+        };
+        if quoted {
+            write!(writer, "\"{}\"", field.replace('"', "\"\""))
+        } else {
+            writer.write_all(field.as_bytes())
+        }
     }
-    Some(format!("{}{}", session_prefix, &stem[bbl_prefix.len()..]))
-}
 
-/// Helper to compute export file paths with consistent naming across all export types.
-/// Ensures CLI status messages match actual filenames written by export functions.
-///
-/// # Arguments
-/// * `input_path` - Path to the input BBL file (used to extract base filename)
-/// * `export_options` - Export configuration with optional output directory
-/// * `log_number` - 1-based log number (for .NN suffix when multiple logs)
-/// * `total_logs` - Total number of logs in the file
-/// * `base_name_override` - Optional replacement stem (e.g. from `corrected_session_base_name`)
-///
-/// # Returns
-/// Tuple of (csv_path, headers_path, gpx_path, event_path) using consistent naming
-pub fn compute_export_paths(
-    input_path: &Path,
-    export_options: &ExportOptions,
-    log_number: usize,
-    total_logs: usize,
-    base_name_override: Option<&str>,
-) -> (
-    std::path::PathBuf,
-    std::path::PathBuf,
-    std::path::PathBuf,
-    std::path::PathBuf,
-) {
-    let base_name = sanitize_base_name_override(base_name_override)
-        .unwrap_or_else(|| extract_base_name(input_path));
+    /// Terminate the current record
+    fn end_record<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(self.line_ending.as_str().as_bytes())
+    }
+}
 
-    let output_dir = if let Some(ref dir) = export_options.output_dir {
-        std::path::Path::new(dir)
-    } else {
-        input_path.parent().unwrap_or(std::path::Path::new("."))
-    };
+/// When [`CsvOptions`] wraps fields in double quotes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CsvQuoteStyle {
+    /// Quote fields containing the delimiter, a quote or a line break
+    #[default]
+    Necessary,
+    /// Quote every field
+    Always,
+    /// Never quote (fields are written verbatim)
+    Never,
+}
 
-    let log_suffix = if total_logs > 1 {
-        format!(".{:02}", log_number)
-    } else {
-        String::new()
-    };
+/// Record terminator written by [`CsvOptions`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CsvLineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`, as RFC 4180 requires
+    CrLf,
+}
 
-    let csv_path = output_dir.join(format!("{}{}.csv", base_name, log_suffix));
-    let headers_path = output_dir.join(format!("{}{}.headers.csv", base_name, log_suffix));
-    let gpx_path = output_dir.join(format!("{}{}.gps.gpx", base_name, log_suffix));
-    let event_path = output_dir.join(format!("{}{}.event", base_name, log_suffix));
+impl CsvLineEnding {
+    /// The terminator's characters
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CsvLineEnding::Lf => "\n",
+            CsvLineEnding::CrLf => "\r\n",
+        }
+    }
+}
 
-    (csv_path, headers_path, gpx_path, event_path)
+/// Output conventions an export follows
+///
+/// [`CompatMode::BlackboxDecode`] reproduces blackbox_decode for scripts that
+/// depend on column positions or exact formatting: every log is numbered
+/// (`<name>.01.csv` even for single-log files), no `.headers.csv` is written,
+/// the CSV holds exactly blackbox_decode's columns (`motor_percent`,
+/// `throttle_percent`, `gyro_deg_per_sec`, `acc_g`, `setpoint_deg_per_sec`,
+/// `vbat_per_cell`, `simulate_imu`, `mag_heading`, `climb_rate` and
+/// `simulate_current_meter` are ignored), integers are right-aligned to 3
+/// characters, volts and amps have 3 decimals and high-resolution fields are
+/// written unscaled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CompatMode {
+    /// This crate's own layout
+    #[default]
+    Native,
+    /// blackbox_decode's file names, columns and number formatting
+    BlackboxDecode,
 }
 
-/// Pre-computed CSV field mapping for performance
-#[derive(Debug)]
-struct CsvFieldMap {
-    field_name_to_lookup: Vec<(String, String)>, // (csv_name, lookup_name)
+/// Ready-made export settings for downstream analysis tools, applied with
+/// [`ExportOptions::with_preset`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ExportPreset {
+    /// PIDtoolbox: one blackbox_decode-compatible `<name>.NN.csv` per log
+    /// ([`CompatMode::BlackboxDecode`]) with every column, in decode order.
+    /// Every log is exported regardless of the skip heuristics, so the file
+    /// numbers always line up with the log numbers PIDtoolbox lists.
+    PidToolbox,
+    /// PID-Analyzer (Plasmatree): the [`ExportPreset::PidToolbox`] layout,
+    /// whose `loopIteration, time (us), axisP[0], ...` header and
+    /// `rcCommand[3]` throttle column PID-Analyzer reads, with `debug[n]`
+    /// columns named after the log's `debug_mode` ([`ExportOptions::debug_mode_names`])
+    PidAnalyzer,
 }
 
-impl CsvFieldMap {
-    fn new(header: &BBLHeader) -> Self {
-        let mut field_name_to_lookup = Vec::new();
+/// Default GPS time gap that starts a new GPX track segment (2 seconds)
+pub const DEFAULT_GPS_GAP_THRESHOLD_US: u64 = 2_000_000;
 
-        // I frame fields
-        for field_name in &header.i_frame_def.field_names {
-            let trimmed = field_name.trim();
-            let csv_name = if trimmed == "time" {
-                "time (us)".to_string()
-            } else if trimmed == "vbatLatest" {
-                "vbatLatest (V)".to_string()
-            } else if trimmed == "amperageLatest" {
-                "amperageLatest (A)".to_string()
-            } else {
-                trimmed.to_string()
-            };
+impl ExportOptions {
+    /// Whether any enabled export needs GPS and home coordinates collected
+    pub(crate) fn wants_gps(&self) -> bool {
+        self.gpx || self.kml || self.gps_csv || self.merge_gps
+    }
 
-            field_name_to_lookup.push((csv_name.clone(), trimmed.to_string()));
+    /// Name a CSV, event or custom exporter file is written under: `path`,
+    /// with `.gz` appended when [`ExportOptions::compress`] is set
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bbl_parser::ExportOptions;
+    /// use std::path::Path;
+    ///
+    /// let opts = ExportOptions {
+    ///     compress: true,
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(opts.output_path(Path::new("flight.csv")), Path::new("flight.csv.gz"));
+    /// ```
+    pub fn output_path(&self, path: &Path) -> PathBuf {
+        if self.compress {
+            let mut name = path.as_os_str().to_owned();
+            name.push(".gz");
+            PathBuf::from(name)
+        } else {
+            path.to_path_buf()
         }
+    }
 
-        // Add computed fields IMMEDIATELY after I frame fields (like blackbox_decode does)
-        if field_name_to_lookup
-            .iter()
-            .any(|(_, lookup)| lookup == "amperageLatest")
-        {
-            field_name_to_lookup.push(("energyCumulative (mAh)".to_string(), "".to_string()));
+    /// Apply the settings of `preset`, keeping the output directory, GPS and
+    /// event exports and custom exporters as they are
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bbl_parser::{CompatMode, ExportOptions, ExportPreset};
+    ///
+    /// let opts = ExportOptions {
+    ///     output_dir: Some("ptb".to_string()),
+    ///     ..Default::default()
+    /// }
+    /// .with_preset(ExportPreset::PidToolbox);
+    /// assert!(opts.csv && opts.force_export);
+    /// assert_eq!(opts.compat, CompatMode::BlackboxDecode);
+    /// ```
+    pub fn with_preset(mut self, preset: ExportPreset) -> Self {
+        match preset {
+            ExportPreset::PidToolbox => {
+                self.csv = true;
+                self.force_export = true;
+                self.compat = CompatMode::BlackboxDecode;
+                self.preserve_order = true;
+                self.gap_fill = GapFillPolicy::Leave;
+                self.raw_units = false;
+                self.debug_mode_names = false;
+                self.merge_gps = false;
+                self.event_column = false;
+                self.csv_fields.clear();
+                self.csv_options = CsvOptions::default();
+            }
+            ExportPreset::PidAnalyzer => {
+                self = self.with_preset(ExportPreset::PidToolbox);
+                self.debug_mode_names = true;
+            }
         }
+        self
+    }
 
-        // S frame fields (with flag formatting)
-        for field_name in &header.s_frame_def.field_names {
-            let trimmed = field_name.trim();
-            if trimmed == "time" {
-                continue;
-            } // Skip duplicate
-
-            let csv_name = if trimmed.contains("Flag") || trimmed == "failsafePhase" {
-                format!("{trimmed} (flags)")
-            } else {
-                trimmed.to_string()
-            };
-
-            field_name_to_lookup.push((csv_name.clone(), trimmed.to_string()));
-        }
+    /// Whether any enabled export needs event frames collected
+    pub(crate) fn wants_events(&self) -> bool {
+        self.event || self.event_column
+    }
+}
 
+impl Default for ExportOptions {
+    fn default() -> Self {
         Self {
-            field_name_to_lookup,
+            csv: false,
+            gpx: false,
+            event: false,
+            kml: false,
+            gps_csv: false,
+            ndjson: false,
+            parquet: false,
+            output_dir: None,
+            force_export: false,
+            filter_thresholds: FilterThresholds::default(),
+            preserve_order: true,
+            gap_fill: GapFillPolicy::Leave,
+            motor_percent: false,
+            throttle_percent: false,
+            gyro_deg_per_sec: false,
+            acc_g: false,
+            setpoint_deg_per_sec: false,
+            vbat_per_cell: false,
+            simulate_imu: false,
+            mag_heading: false,
+            declination_deg: 0.0,
+            climb_rate: false,
+            simulate_current_meter: false,
+            sim_current_meter_offset: None,
+            sim_current_meter_scale: None,
+            merge_gps: false,
+            home_distance: false,
+            event_column: false,
+            raw_units: false,
+            debug_mode_names: false,
+            csv_fields: Vec::new(),
+            csv_options: CsvOptions::default(),
+            compat: CompatMode::Native,
+            split_frame_types: false,
+            compress: false,
+            gps_gap_threshold_us: DEFAULT_GPS_GAP_THRESHOLD_US,
+            time_range: TimeRange::default(),
+            overwrite: OverwritePolicy::Overwrite,
+            exporters: Vec::new(),
         }
     }
 }
 
-/// Export BBL log to CSV format
+/// Result of an export operation, containing paths of all files that were created.
 ///
-/// # Returns
-/// An `ExportReport` containing paths to the CSV and headers files that were created,
-/// or an error if the export failed.
-pub fn export_to_csv(
-    log: &BBLLog,
-    input_path: &Path,
-    export_options: &ExportOptions,
-    base_name_override: Option<&str>,
-) -> Result<ExportReport> {
-    let base_name = sanitize_base_name_override(base_name_override)
-        .unwrap_or_else(|| extract_base_name(input_path));
-
-    let output_dir = if let Some(ref dir) = export_options.output_dir {
-        Path::new(dir)
-    } else {
-        input_path.parent().unwrap_or(Path::new("."))
-    };
-
-    // Create output directory if it doesn't exist
-    if !output_dir.exists() {
-        std::fs::create_dir_all(output_dir)?;
-    }
-
-    let log_suffix = if log.total_logs > 1 {
-        format!(".{:02}", log.log_number)
-    } else {
-        "".to_string()
-    };
+/// Any path that is `None` indicates that export format was not requested or
+/// no data was available for export (e.g., empty GPS coordinates for GPX export).
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExportReport {
+    /// Path to the main CSV data file (None if CSV export was not performed)
+    pub csv_path: Option<std::path::PathBuf>,
+    /// Path to the CSV headers file (None if CSV export was not performed)
+    pub headers_path: Option<std::path::PathBuf>,
+    /// Path to the GPX file (None if GPX export was not performed or GPS data was empty)
+    pub gpx_path: Option<std::path::PathBuf>,
+    /// Path to the event JSON file (None if event export was not performed or no events were found)
+    pub event_path: Option<std::path::PathBuf>,
+    /// Path to the GPS CSV file (None if GPS CSV export was not performed or GPS data was empty)
+    pub gps_csv_path: Option<std::path::PathBuf>,
+    /// Path to the KML file (None if KML export was not performed or GPS data was empty)
+    pub kml_path: Option<std::path::PathBuf>,
+    /// Path to the Parquet file (None if Parquet export was not performed)
+    pub parquet_path: Option<std::path::PathBuf>,
+    /// Path to the S-frame CSV (None unless `split_frame_types` is set and S frames were logged)
+    pub s_frame_csv_path: Option<std::path::PathBuf>,
+    /// Path to the G-frame CSV (None unless `split_frame_types` is set and G frames were logged)
+    pub g_frame_csv_path: Option<std::path::PathBuf>,
+    /// Data rows written: CSV flight data rows, GPX trackpoints or event lines
+    pub rows_written: usize,
+    /// Total bytes written across every file in this report
+    pub bytes_written: u64,
+    /// Files written by registered custom exporters
+    pub extra_paths: Vec<std::path::PathBuf>,
+}
 
-    // Export plaintext headers to separate CSV
-    let header_csv_path = output_dir.join(format!("{base_name}{log_suffix}.headers.csv"));
-    export_headers_to_csv(&log.header, &header_csv_path)?;
+/// An output format that writes one log to a byte sink
+///
+/// The built-in [`CsvExporter`], [`HeadersCsvExporter`], [`GpxExporter`] and
+/// [`EventExporter`] implement it, and downstream crates can add their own
+/// formats by registering implementations in [`ExportOptions::exporters`].
+///
+/// # Examples
+/// ```rust
+/// use bbl_parser::{BBLLog, ExportReport, Exporter};
+/// use std::io::Write;
+///
+/// struct FrameCountExporter;
+///
+/// impl Exporter for FrameCountExporter {
+///     fn name(&self) -> &str {
+///         "frame-count"
+///     }
+///
+///     fn extension(&self) -> &str {
+///         "count.txt"
+///     }
+///
+///     fn export(&self, log: &BBLLog, sink: &mut dyn Write) -> bbl_parser::Result<ExportReport> {
+///         writeln!(sink, "{}", log.frames.len())?;
+///         Ok(ExportReport {
+///             rows_written: 1,
+///             ..Default::default()
+///         })
+///     }
+/// }
+/// ```
+pub trait Exporter: Send + Sync {
+    /// Short format name for messages
+    fn name(&self) -> &str;
 
-    // Export flight data (I, P, S frames) to main CSV
-    let flight_csv_path = output_dir.join(format!("{base_name}{log_suffix}.csv"));
-    export_flight_data_to_csv(log, &flight_csv_path)?;
+    /// File name suffix after the log's base name, e.g. `"gps.gpx"` for
+    /// `flight.01.gps.gpx`
+    fn extension(&self) -> &str;
 
-    Ok(ExportReport {
-        csv_path: Some(flight_csv_path),
-        headers_path: Some(header_csv_path),
-        gpx_path: None,
-        event_path: None,
-    })
+    /// Write `log` to `sink`, reporting rows and bytes written
+    fn export(&self, log: &BBLLog, sink: &mut dyn Write) -> Result<ExportReport>;
 }
 
-/// Export headers to CSV file
-fn export_headers_to_csv(header: &BBLHeader, output_path: &Path) -> Result<()> {
-    let file = File::create(output_path)
-        .with_context(|| format!("Failed to create headers CSV file: {output_path:?}"))?;
-    let mut writer = BufWriter::new(file);
+impl std::fmt::Debug for dyn Exporter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Exporter({})", self.name())
+    }
+}
 
-    // Write CSV header
-    writeln!(writer, "Field,Value")?;
+/// Flight data CSV, as written to `<name>.csv` by [`export_to_csv`]
+///
+/// Honors the CSV settings in `options` (row order, gap filling, percent columns).
+#[derive(Debug, Clone, Default)]
+pub struct CsvExporter {
+    pub options: ExportOptions,
+}
 
-    // Parse and write all header lines
-    for header_line in &header.all_headers {
-        if let Some(content) = header_line.strip_prefix("H ") {
-            // Remove "H " prefix and find the colon separator
-            if let Some(colon_pos) = content.find(':') {
-                let field_name = content[..colon_pos].trim();
-                let field_value = content[colon_pos + 1..].trim();
-
-                // Escape commas in values by wrapping in quotes
-                let escaped_value = if field_value.contains(',') {
-                    format!("\"{}\"", field_value.replace('"', "\"\""))
-                } else {
-                    field_value.to_string()
-                };
+impl Exporter for CsvExporter {
+    fn name(&self) -> &str {
+        "csv"
+    }
 
-                writeln!(writer, "{field_name},{escaped_value}")?;
-            }
-        }
+    fn extension(&self) -> &str {
+        "csv"
     }
 
-    writer
-        .flush()
-        .with_context(|| format!("Failed to flush headers CSV file: {output_path:?}"))?;
+    fn export(&self, log: &BBLLog, sink: &mut dyn Write) -> Result<ExportReport> {
+        export_csv_to_writer(log, sink, &self.options)
+    }
+}
 
-    Ok(())
+/// Header table CSV, as written to `<name>.headers.csv` by [`export_to_csv`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeadersCsvExporter;
+
+impl Exporter for HeadersCsvExporter {
+    fn name(&self) -> &str {
+        "headers"
+    }
+
+    fn extension(&self) -> &str {
+        "headers.csv"
+    }
+
+    fn export(&self, log: &BBLLog, sink: &mut dyn Write) -> Result<ExportReport> {
+        export_headers_to_writer(log, sink)
+    }
 }
 
-/// Export flight data to CSV file
-fn export_flight_data_to_csv(log: &BBLLog, output_path: &Path) -> Result<()> {
-    let file = File::create(output_path)
-        .with_context(|| format!("Failed to create flight data CSV file: {output_path:?}"))?;
-    let mut writer = BufWriter::new(file);
+/// Header table as typed JSON in `<name>.headers.json`
+///
+/// See [`export_headers_json_to_writer`] for the value types.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeadersJsonExporter;
 
-    // Build optimized field mapping
-    let csv_map = CsvFieldMap::new(&log.header);
-    let field_names: Vec<String> = csv_map
-        .field_name_to_lookup
-        .iter()
-        .map(|(csv_name, _)| csv_name.clone())
-        .collect();
+impl Exporter for HeadersJsonExporter {
+    fn name(&self) -> &str {
+        "headers-json"
+    }
+
+    fn extension(&self) -> &str {
+        "headers.json"
+    }
 
-    // Collect all I and P frames in chronological order
-    let mut all_frames: Vec<(u64, char, &DecodedFrame)> = Vec::new();
+    fn export(&self, log: &BBLLog, sink: &mut dyn Write) -> Result<ExportReport> {
+        export_headers_json_to_writer(log, sink)
+    }
+}
 
-    // Use log.frames which contains all parsed frames
-    for frame in &log.frames {
-        if frame.frame_type == 'I' || frame.frame_type == 'P' {
-            all_frames.push((frame.timestamp_us, frame.frame_type, frame));
-        }
+/// GPS track as GPX, as written to `<name>.gps.gpx` by [`export_to_gpx`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GpxExporter;
+
+impl Exporter for GpxExporter {
+    fn name(&self) -> &str {
+        "gpx"
     }
 
-    // Sort by timestamp
-    all_frames.sort_by_key(|(timestamp, _, _)| *timestamp);
+    fn extension(&self) -> &str {
+        "gps.gpx"
+    }
 
-    if all_frames.is_empty() {
-        return Ok(()); // No data to export
+    fn export(&self, log: &BBLLog, sink: &mut dyn Write) -> Result<ExportReport> {
+        export_gpx_to_writer(log, sink)
     }
+}
 
-    // Write field names header
-    for (i, field_name) in field_names.iter().enumerate() {
-        if i > 0 {
-            write!(writer, ", ")?;
-        }
-        write!(writer, "{field_name}")?;
+/// GPS fixes as CSV, as written to `<name>.gps.csv` by [`export_gps_to_csv`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GpsCsvExporter;
+
+impl Exporter for GpsCsvExporter {
+    fn name(&self) -> &str {
+        "gps-csv"
     }
-    writeln!(writer)?;
 
-    // Optimized CSV writing with pre-computed mappings
-    let mut cumulative_energy_mah = 0f32;
-    let mut last_timestamp_us = 0u64;
-    let mut latest_s_frame_data: HashMap<String, i32> = HashMap::new();
+    fn extension(&self) -> &str {
+        "gps.csv"
+    }
 
-    for (output_iteration, (timestamp, frame_type, frame)) in all_frames.iter().enumerate() {
-        // Update latest S-frame data if this is an S frame
-        if *frame_type == 'S' {
-            for (key, value) in &frame.data {
-                latest_s_frame_data.insert(key.clone(), *value);
-            }
-        }
+    fn export(&self, log: &BBLLog, sink: &mut dyn Write) -> Result<ExportReport> {
+        export_gps_csv_to_writer(log, sink)
+    }
+}
 
-        // Calculate energyCumulative for this frame
-        if let Some(current_raw) = frame.data.get("amperageLatest").copied() {
-            if last_timestamp_us > 0 && *timestamp > last_timestamp_us {
-                let time_delta_hours = (*timestamp - last_timestamp_us) as f32 / 3_600_000_000.0;
-                let current_amps = convert_amperage_to_amps(current_raw);
-                cumulative_energy_mah += current_amps * time_delta_hours * 1000.0;
-            }
-            last_timestamp_us = *timestamp;
-        }
+/// GPS track as KML, as written to `<name>.gps.kml` by [`export_to_kml`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KmlExporter;
 
-        // Write data row using optimized field mapping
-        for (i, (csv_name, lookup_name)) in csv_map.field_name_to_lookup.iter().enumerate() {
-            if i > 0 {
-                write!(writer, ", ")?;
-            }
+impl Exporter for KmlExporter {
+    fn name(&self) -> &str {
+        "kml"
+    }
 
-            // Fast path for special fields using pre-computed indices
-            if csv_name == "time (us)" {
-                write!(writer, "{}", *timestamp as i32)?;
-            } else if csv_name == "loopIteration" {
-                let value = frame
-                    .data
-                    .get("loopIteration")
-                    .copied()
-                    .unwrap_or(output_iteration as i32);
-                write!(writer, "{value:4}")?;
-            } else if csv_name == "vbatLatest (V)" {
-                let raw_value = frame.data.get("vbatLatest").copied().unwrap_or(0);
-                write!(
-                    writer,
-                    "{:4.1}",
-                    convert_vbat_to_volts(raw_value, &log.header.firmware_revision)
-                )?;
-            } else if csv_name == "amperageLatest (A)" {
-                let raw_value = frame.data.get("amperageLatest").copied().unwrap_or(0);
-                write!(writer, "{:4.2}", convert_amperage_to_amps(raw_value))?;
-            } else if csv_name == "energyCumulative (mAh)" {
-                write!(writer, "{:5}", cumulative_energy_mah as i32)?;
-            } else if csv_name.ends_with(" (flags)") {
-                // Handle flag fields - output text values like blackbox_decode.c
-                let raw_value = frame
-                    .data
-                    .get(lookup_name)
-                    .copied()
-                    .or_else(|| latest_s_frame_data.get(lookup_name).copied())
-                    .unwrap_or(0);
-
-                let formatted = if lookup_name == "flightModeFlags" {
-                    format_flight_mode_flags(raw_value)
-                } else if lookup_name == "stateFlags" {
-                    format_state_flags(raw_value)
-                } else if lookup_name == "failsafePhase" {
-                    format_failsafe_phase(raw_value)
-                } else {
-                    raw_value.to_string()
-                };
-                write!(writer, "{formatted}")?;
-            } else {
-                // Regular field lookup with S-frame fallback
-                let value = frame
-                    .data
-                    .get(lookup_name)
-                    .copied()
-                    .or_else(|| latest_s_frame_data.get(lookup_name).copied())
-                    .unwrap_or(0);
-                write!(writer, "{value:4}")?;
-            }
-        }
-        writeln!(writer)?;
+    fn extension(&self) -> &str {
+        "gps.kml"
     }
 
-    writer
-        .flush()
-        .with_context(|| format!("Failed to flush flight data CSV file: {output_path:?}"))?;
+    fn export(&self, log: &BBLLog, sink: &mut dyn Write) -> Result<ExportReport> {
+        export_kml_to_writer(log, sink)
+    }
+}
 
-    Ok(())
+/// Flight events as JSONL, as written to `<name>.event` by [`export_to_event`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EventExporter;
+
+impl Exporter for EventExporter {
+    fn name(&self) -> &str {
+        "event"
+    }
+
+    fn extension(&self) -> &str {
+        "event"
+    }
+
+    fn export(&self, log: &BBLLog, sink: &mut dyn Write) -> Result<ExportReport> {
+        export_events_to_writer(log, sink)
+    }
 }
 
-/// Export GPS data to GPX format
+/// Main frames as NDJSON, one JSON object per line in `<name>.ndjson`
 ///
-/// # Arguments
-/// * `input_path` - Path to the input BBL file (used for output naming)
-/// * `log_index` - Index of the current log (0-based)
-/// * `total_logs` - Total number of logs in the file
-/// * `gps_coordinates` - GPS coordinate data to export
-/// * `home_coordinates` - Home coordinates from H frames (used for home waypoint marker)
-/// * `export_options` - Export configuration options
-/// * `log_start_datetime` - Optional log start datetime from header for accurate timestamps
+/// See [`export_ndjson_to_writer`] for the line format.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NdjsonExporter;
+
+impl Exporter for NdjsonExporter {
+    fn name(&self) -> &str {
+        "ndjson"
+    }
+
+    fn extension(&self) -> &str {
+        "ndjson"
+    }
+
+    fn export(&self, log: &BBLLog, sink: &mut dyn Write) -> Result<ExportReport> {
+        export_ndjson_to_writer(log, sink)
+    }
+}
+
+/// Write the flight data CSV for `log` to any writer
 ///
-/// # Features
-/// When home coordinates are available, adds a home position waypoint to the GPX file.
-/// This provides a visual reference point in GPS mapping tools.
+/// Produces the same bytes as the `.csv` file from [`export_to_csv`], honoring
+/// the CSV settings in `export_options`. Wrap unbuffered sinks such as files or
+/// sockets in a [`BufWriter`].
 ///
-/// # Performance Notes
-/// For very large GPS traces, the `log_start_datetime` is parsed via `generate_gpx_timestamp()`
-/// on each trackpoint. Future optimization: consider caching the parsed base epoch once per log
-/// to avoid repeated parsing overhead when exporting thousands of GPS points.
-#[allow(clippy::too_many_arguments)]
-pub fn export_to_gpx(
-    input_path: &Path,
-    log_index: usize,
-    total_logs: usize,
-    gps_coordinates: &[GpsCoordinate],
-    home_coordinates: &[GpsHomeCoordinate],
+/// # Examples
+/// ```rust
+/// use bbl_parser::{export_csv_to_writer, parse_bbl_bytes, ExportOptions};
+///
+/// # fn csv_bytes(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+/// let log = parse_bbl_bytes(data, ExportOptions::default(), false)?;
+/// let mut csv = Vec::new();
+/// let report = export_csv_to_writer(&log, &mut csv, &ExportOptions::default())?;
+/// assert_eq!(report.bytes_written, csv.len() as u64);
+/// # Ok(csv)
+/// # }
+/// ```
+pub fn export_csv_to_writer<W: Write>(
+    log: &BBLLog,
+    writer: W,
     export_options: &ExportOptions,
-    log_start_datetime: Option<&str>,
-    base_name_override: Option<&str>,
 ) -> Result<ExportReport> {
-    if gps_coordinates.is_empty() {
-        return Ok(ExportReport::default());
-    }
+    let mut writer = CountingWriter::new(writer);
+    let rows_written = write_flight_data_csv(log, &mut writer, export_options)?;
+    writer.flush()?;
+    Ok(ExportReport {
+        rows_written,
+        bytes_written: writer.bytes_written,
+        ..Default::default()
+    })
+}
 
-    // Use compute_export_paths to ensure consistent naming with CSV exports
-    let (_, _, gpx_path, _) = compute_export_paths(
-        input_path,
-        export_options,
-        log_index + 1,
-        total_logs,
-        base_name_override,
-    );
+/// Column header of the first log written to a concatenated CSV
+#[derive(Debug)]
+struct ConcatState {
+    columns: Option<Vec<String>>,
+    rebase_time: bool,
+    continuous_time: bool,
+    /// Logs written so far
+    logs_written: usize,
+    /// Time shift of the previous log, with the written time of its last
+    /// frame and its last frame interval
+    previous_log: Option<(i64, i64, i64)>,
+}
 
-    // Create output directory if it doesn't exist (match export_to_csv behavior)
-    if let Some(parent) = gpx_path.parent() {
-        if !parent.exists() {
-            std::fs::create_dir_all(parent)?;
+/// Writes the flight data of several logs into one CSV
+///
+/// Rows start with a `log_number` column, followed by the usual flight data
+/// columns, and the column header line is written once. Every log must produce
+/// the same columns as the first one; otherwise [`ConcatCsvWriter::write_log`]
+/// fails with [`BBLError::ColumnMismatch`] and writes nothing. With
+/// `rebase_time`, each log's `time (us)` starts at 0; with
+/// [`ConcatCsvWriter::with_continuous_time`], logs continue each other's time.
+///
+/// # Examples
+/// ```rust
+/// use bbl_parser::{parse_bbl_bytes_all_logs, ConcatCsvWriter, ExportOptions};
+///
+/// # fn session_csv(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+/// let opts = ExportOptions::default();
+/// let mut csv = Vec::new();
+/// let mut writer = ConcatCsvWriter::new(&mut csv, true);
+/// for log in parse_bbl_bytes_all_logs(data, opts.clone(), false)? {
+///     writer.write_log(&log, &opts)?;
+/// }
+/// writer.finish()?;
+/// # Ok(csv)
+/// # }
+/// ```
+pub struct ConcatCsvWriter<W: Write> {
+    writer: CountingWriter<W>,
+    state: ConcatState,
+    rows_written: usize,
+}
+
+impl<W: Write> ConcatCsvWriter<W> {
+    /// Start a concatenated CSV on `writer`
+    pub fn new(writer: W, rebase_time: bool) -> Self {
+        Self {
+            writer: CountingWriter::new(writer),
+            state: ConcatState {
+                columns: None,
+                rebase_time,
+                continuous_time: false,
+                logs_written: 0,
+                previous_log: None,
+            },
+            rows_written: 0,
         }
     }
 
-    let mut gpx_file = File::create(&gpx_path)?;
-    writeln!(gpx_file, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
-    writeln!(
-        gpx_file,
-        r#"<gpx creator="BBL Parser (Rust)" version="1.1" xmlns="http://www.topografix.com/GPX/1/1" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://www.topografix.com/GPX/1/1 http://www.topografix.com/GPX/1/1/gpx.xsd">"#
-    )?;
-    writeln!(
-        gpx_file,
-        "<metadata><name>Blackbox flight log</name></metadata>"
-    )?;
-
-    // Add home position waypoint if available
-    if let Some(home) = home_coordinates.first() {
-        writeln!(
-            gpx_file,
-            r#"  <wpt lat="{:.7}" lon="{:.7}">"#,
-            home.home_latitude, home.home_longitude
-        )?;
-        writeln!(gpx_file, r#"    <name>Home</name>"#)?;
-        writeln!(gpx_file, r#"    <sym>Flag</sym>"#)?;
-        writeln!(gpx_file, r#"    <desc>Home Position</desc>"#)?;
-        writeln!(gpx_file, r#"  </wpt>"#)?;
+    /// Write the logs as one continuous session, e.g. a flight split across
+    /// files by logging restarts
+    ///
+    /// A log whose `time (us)` does not run on after the previous log (the
+    /// flight controller restarted its clock) is shifted to start one frame
+    /// interval after the previous log's last frame; later logs keep that
+    /// shift while their time runs on. `log_number` counts the logs in the
+    /// order they are written, since logs from different files repeat numbers.
+    /// Replaces `rebase_time`.
+    pub fn with_continuous_time(mut self) -> Self {
+        self.state.rebase_time = false;
+        self.state.continuous_time = true;
+        self
     }
 
-    writeln!(gpx_file, "<trk><name>Blackbox flight log</name><trkseg>")?;
-
-    for coord in gps_coordinates {
-        // Only include coordinates with sufficient GPS satellite count (minimum 5)
-        if let Some(num_sats) = coord.num_sats {
-            if num_sats < 5 {
-                continue;
-            }
-        }
-
-        // Generate GPX timestamp from log_start_datetime + frame timestamp
-        // Following blackbox_decode approach: dateTime + (gpsFrameTime / 1000000)
-        let timestamp_str = generate_gpx_timestamp(log_start_datetime, coord.timestamp_us);
+    /// Append the main frames of `log`, returning the number of rows written
+    pub fn write_log(&mut self, log: &BBLLog, export_options: &ExportOptions) -> Result<usize> {
+        let rows = write_flight_data(log, &mut self.writer, export_options, Some(&mut self.state))?;
+        self.rows_written += rows;
+        Ok(rows)
+    }
 
-        writeln!(
-            gpx_file,
-            r#"  <trkpt lat="{:.7}" lon="{:.7}"><ele>{:.2}</ele><time>{}</time></trkpt>"#,
-            coord.latitude, coord.longitude, coord.altitude, timestamp_str
-        )?;
+    /// Flush the output and report the rows and bytes written across all logs
+    pub fn finish(self) -> Result<ExportReport> {
+        self.into_inner().map(|(_, report)| report)
     }
 
-    writeln!(gpx_file, "</trkseg></trk>")?;
-    writeln!(gpx_file, "</gpx>")?;
+    /// Like [`ConcatCsvWriter::finish`], also returning the writer, e.g. to
+    /// [`ExportFile::finish`] it
+    pub fn into_inner(mut self) -> Result<(W, ExportReport)> {
+        self.writer.flush()?;
+        let report = ExportReport {
+            rows_written: self.rows_written,
+            bytes_written: self.writer.bytes_written,
+            ..Default::default()
+        };
+        Ok((self.writer.inner, report))
+    }
+}
 
-    Ok(ExportReport {
-        csv_path: None,
-        headers_path: None,
-        gpx_path: Some(gpx_path),
-        event_path: None,
-    })
+impl<W: Write> std::fmt::Debug for ConcatCsvWriter<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConcatCsvWriter")
+            .field("rows_written", &self.rows_written)
+            .finish_non_exhaustive()
+    }
 }
 
-/// Export event data to file
+/// Export the flight data of every log in `logs` to one `<name>.csv` with a
+/// leading `log_number` column (see [`ConcatCsvWriter`])
 ///
-/// # Returns
-/// An `ExportReport` containing the path to the event file that was created,
-/// or an error if the export failed. Returns `None` for `event_path` if no events were exported.
-pub fn export_to_event(
+/// The file is named as for a single-log input, so `.01`/`.02` suffixes are
+/// not used. Per-log `.headers.csv` files are not written.
+pub fn export_logs_to_csv(
+    logs: &[BBLLog],
     input_path: &Path,
-    log_index: usize,
-    total_logs: usize,
-    event_frames: &[EventFrame],
     export_options: &ExportOptions,
+    rebase_time: bool,
     base_name_override: Option<&str>,
 ) -> Result<ExportReport> {
-    if event_frames.is_empty() {
-        return Ok(ExportReport::default());
-    }
-
-    // Use compute_export_paths to ensure consistent naming with CSV exports
-    let (_, _, _, event_path) = compute_export_paths(
-        input_path,
-        export_options,
-        log_index + 1,
-        total_logs,
-        base_name_override,
-    );
-
-    // Create output directory if it doesn't exist (match export_to_csv behavior)
-    if let Some(parent) = event_path.parent() {
+    let (csv_path, _, _, _) =
+        compute_export_paths(input_path, export_options, 1, 1, base_name_override);
+    let csv_path = export_options.output_path(&csv_path);
+    if let Some(parent) = csv_path.parent() {
         if !parent.exists() {
             std::fs::create_dir_all(parent)?;
         }
     }
 
-    let mut event_file = File::create(&event_path)?;
-
-    // Export as JSONL format (individual JSON objects per line) to match blackbox_decode
-    for event in event_frames.iter() {
-        writeln!(
-            event_file,
-            r#"{{"name":"{}", "time":{}}}"#,
-            event.event_name.replace('"', "\\\""),
-            event.timestamp_us
+    let file = ExportFile::create(&csv_path, export_options.compress)
+        .map_err(|e| BBLError::export_failed(csv_path.clone(), e))?;
+    let mut writer = ConcatCsvWriter::new(file, rebase_time);
+    let (bytes_written, report) = logs
+        .iter()
+        .try_for_each(|log| writer.write_log(log, export_options).map(drop))
+        .and_then(|()| writer.into_inner())
+        .and_then(|(file, report)| Ok((file.finish()?, report)))
+        .map_err(|e| BBLError::export_failed(csv_path.clone(), e))?;
+    Ok(ExportReport {
+        csv_path: Some(csv_path),
+        bytes_written,
+        ..report
+    })
+}
+
+/// Write the `Field,Value` header table for `log` to any writer, as in the
+/// `.headers.csv` file from [`export_to_csv`]
+///
+/// The `features` row is followed by a `features (names)` row naming the
+/// enabled features ([`BBLHeader::features`]).
+pub fn export_headers_to_writer<W: Write>(log: &BBLLog, writer: W) -> Result<ExportReport> {
+    let mut writer = CountingWriter::new(writer);
+    write_headers_csv(&log.header, &mut writer)?;
+    writer.flush()?;
+    Ok(ExportReport {
+        bytes_written: writer.bytes_written,
+        ..Default::default()
+    })
+}
+
+/// Write the header table of `log` as one JSON object to any writer
+///
+/// Keys are the header names in log order (the first of repeated names
+/// wins). Numeric values become JSON numbers, comma-separated numeric lists
+/// such as `rollPID` or `motorOutput` become arrays, `Field <type> name`
+/// lists become arrays of strings, and everything else is kept as a string, e.g. `{"looptime": 125, "rollPID": [45, 80, 30],
+/// "Firmware revision": "Betaflight 4.5.1 ..."}`. The `features` bitmask is
+/// followed by a `features (names)` array of the enabled feature names.
+pub fn export_headers_json_to_writer<W: Write>(log: &BBLLog, writer: W) -> Result<ExportReport> {
+    let mut writer = CountingWriter::new(writer);
+    let mut seen = std::collections::HashSet::new();
+    let mut rows_written = 0;
+    let feature_names = log.header.features();
+    write!(writer, "{{")?;
+    for (name, value) in header_entries(&log.header) {
+        if !seen.insert(name) {
+            continue;
+        }
+        let separator = if rows_written == 0 { "" } else { "," };
+        write!(
+            writer,
+            "{separator}\n  \"{}\": {}",
+            escape_json(name),
+            header_value_json(name, value)
         )?;
+        rows_written += 1;
+        if name == "features" && !feature_names.is_empty() {
+            let names: Vec<String> = feature_names
+                .iter()
+                .map(|feature| format!("\"{feature}\""))
+                .collect();
+            write!(
+                writer,
+                ",\n  \"{FEATURE_NAMES_ENTRY}\": [{}]",
+                names.join(", ")
+            )?;
+            rows_written += 1;
+        }
+    }
+    writeln!(writer, "\n}}")?;
+    writer.flush()?;
+    Ok(ExportReport {
+        rows_written,
+        bytes_written: writer.bytes_written,
+        ..Default::default()
+    })
+}
+
+/// JSON text of a header value: a number, an array of numbers or field
+/// names, or a string
+fn header_value_json(name: &str, value: &str) -> String {
+    if name.starts_with("Field ") && name.ends_with(" name") {
+        let names: Vec<String> = value
+            .split(',')
+            .map(|field| format!("\"{}\"", escape_json(field.trim())))
+            .collect();
+        return format!("[{}]", names.join(", "));
+    }
+    if let Some(number) = header_number_json(value) {
+        return number;
+    }
+    if value.contains(',') {
+        let items: Option<Vec<String>> = value.split(',').map(header_number_json).collect();
+        if let Some(items) = items {
+            return format!("[{}]", items.join(", "));
+        }
+    }
+    format!("\"{}\"", escape_json(value))
+}
+
+/// JSON number for a decimal integer or float header value
+fn header_number_json(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty()
+        || !value
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+    {
+        return None;
+    }
+    if let Ok(integer) = value.parse::<i64>() {
+        return Some(integer.to_string());
+    }
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|float| float.is_finite())
+        .map(|float| float.to_string())
+}
+
+/// Write the GPS track of `log` as GPX to any writer, as in the `.gps.gpx`
+/// file from [`export_to_gpx`]
+///
+/// Unlike [`export_to_gpx`], a document with no trackpoints is still written
+/// when the log has no GPS data. Track segments split at the default GPS gap
+/// threshold ([`DEFAULT_GPS_GAP_THRESHOLD_US`]).
+pub fn export_gpx_to_writer<W: Write>(log: &BBLLog, writer: W) -> Result<ExportReport> {
+    let mut writer = CountingWriter::new(writer);
+    let rows_written = write_gpx(
+        &mut writer,
+        &log.gps_coordinates,
+        &log.home_coordinates,
+        log.header.log_start_datetime.as_deref(),
+        DEFAULT_GPS_GAP_THRESHOLD_US,
+    )?;
+    writer.flush()?;
+    Ok(ExportReport {
+        rows_written,
+        bytes_written: writer.bytes_written,
+        ..Default::default()
+    })
+}
+
+/// Write the GPS fixes of `log` as CSV to any writer, as in the `.gps.csv`
+/// file from [`export_gps_to_csv`]
+pub fn export_gps_csv_to_writer<W: Write>(log: &BBLLog, writer: W) -> Result<ExportReport> {
+    let mut writer = CountingWriter::new(writer);
+    write_gps_csv(&mut writer, &log.gps_coordinates, None)?;
+    writer.flush()?;
+    Ok(ExportReport {
+        rows_written: log.gps_coordinates.len(),
+        bytes_written: writer.bytes_written,
+        ..Default::default()
+    })
+}
+
+/// Write the GPS track of `log` as KML to any writer, as in the `.gps.kml`
+/// file from [`export_to_kml`]
+///
+/// Unlike [`export_to_kml`], a document with an empty track is still written
+/// when the log has no GPS data.
+pub fn export_kml_to_writer<W: Write>(log: &BBLLog, writer: W) -> Result<ExportReport> {
+    let mut writer = CountingWriter::new(writer);
+    let rows_written = write_kml(&mut writer, &log.gps_coordinates, &log.home_coordinates)?;
+    writer.flush()?;
+    Ok(ExportReport {
+        rows_written,
+        bytes_written: writer.bytes_written,
+        ..Default::default()
+    })
+}
+
+/// Write the events of `log` as JSONL to any writer, as in the `.event` file
+/// from [`export_to_event`]
+pub fn export_events_to_writer<W: Write>(log: &BBLLog, writer: W) -> Result<ExportReport> {
+    let mut writer = CountingWriter::new(writer);
+    write_events(&mut writer, &log.event_frames)?;
+    writer.flush()?;
+    Ok(ExportReport {
+        rows_written: log.event_frames.len(),
+        bytes_written: writer.bytes_written,
+        ..Default::default()
+    })
+}
+
+/// Write the main frames of `log` as NDJSON to any writer
+///
+/// Each line is one JSON object holding the frame type, `time`,
+/// `loopIteration` and every field of the frame in name order, e.g.
+/// `{"type":"I","time":1000,"loopIteration":0,"axisP[0]":12,...}`.
+/// To write frames while the log is still being parsed, use [`NdjsonWriter`].
+pub fn export_ndjson_to_writer<W: Write>(log: &BBLLog, writer: W) -> Result<ExportReport> {
+    let mut writer = CountingWriter::new(writer);
+    for frame in &log.frames {
+        write_ndjson_frame(&mut writer, &frame)?;
+    }
+    writer.flush()?;
+    Ok(ExportReport {
+        rows_written: log.frames.len(),
+        bytes_written: writer.bytes_written,
+        ..Default::default()
+    })
+}
+
+/// Streams decoded frames as NDJSON while a log is parsed
+///
+/// Set it as the parser's [`ParserConfig::frame_sink`](crate::ParserConfig::frame_sink)
+/// to write one line per I, P, G, H and E frame (in the format of
+/// [`export_ndjson_to_writer`]) as soon as it is decoded, e.g. when piping to
+/// `jq` or a log indexer. The first write error stops further output and is
+/// returned by [`NdjsonWriter::finish`].
+///
+/// # Examples
+/// ```rust
+/// use bbl_parser::{parse_bbl_bytes_with_config, ExportOptions, NdjsonWriter, ParserConfig};
+/// use std::sync::Arc;
+///
+/// # fn stream(data: &[u8]) -> anyhow::Result<()> {
+/// let ndjson = Arc::new(NdjsonWriter::new(std::io::stdout()));
+/// let config = ParserConfig {
+///     frame_sink: Some(ndjson.clone()),
+///     ..Default::default()
+/// };
+/// parse_bbl_bytes_with_config(data, ExportOptions::default(), false, &config)?;
+/// let report = ndjson.finish()?;
+/// eprintln!("{} frames streamed", report.rows_written);
+/// # Ok(())
+/// # }
+/// ```
+pub struct NdjsonWriter<W: Write + Send> {
+    state: Mutex<NdjsonState<W>>,
+}
+
+struct NdjsonState<W: Write> {
+    writer: CountingWriter<W>,
+    rows_written: usize,
+    error: Option<std::io::Error>,
+}
+
+impl<W: Write + Send> NdjsonWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            state: Mutex::new(NdjsonState {
+                writer: CountingWriter::new(writer),
+                rows_written: 0,
+                error: None,
+            }),
+        }
+    }
+
+    /// Flush the output and report the frames and bytes written so far
+    pub fn finish(&self) -> Result<ExportReport> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(error) = state.error.take() {
+            return Err(error.into());
+        }
+        state.writer.flush()?;
+        Ok(ExportReport {
+            rows_written: state.rows_written,
+            bytes_written: state.writer.bytes_written,
+            ..Default::default()
+        })
+    }
+
+    /// Unwrap the underlying writer, e.g. to finish an [`ExportFile`] after
+    /// [`NdjsonWriter::finish`]
+    pub fn into_inner(self) -> W {
+        let state = self.state.into_inner().unwrap_or_else(|e| e.into_inner());
+        state.writer.inner
+    }
+}
+
+impl<W: Write + Send> FrameSink for NdjsonWriter<W> {
+    fn frame(&self, frame: &DecodedFrame) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.error.is_some() {
+            return;
+        }
+        match write_ndjson_frame(&mut state.writer, frame) {
+            Ok(()) => state.rows_written += 1,
+            Err(error) => state.error = Some(error),
+        }
+    }
+}
+
+impl<W: Write + Send> std::fmt::Debug for NdjsonWriter<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NdjsonWriter").finish_non_exhaustive()
+    }
+}
+
+/// Run every exporter registered in [`ExportOptions::exporters`] for one log
+///
+/// Each exporter writes `<name>[.NN].<extension>` next to the other exports
+/// (see [`compute_export_paths`]). The returned report lists those files in
+/// `extra_paths` and sums their rows and bytes.
+pub fn export_registered(
+    log: &BBLLog,
+    input_path: &Path,
+    export_options: &ExportOptions,
+    base_name_override: Option<&str>,
+) -> Result<ExportReport> {
+    let mut report = ExportReport::default();
+    if export_options.exporters.is_empty() {
+        return Ok(report);
+    }
+
+    let (csv_path, _, _, _) = compute_export_paths(
+        input_path,
+        export_options,
+        log.log_number,
+        log.total_logs,
+        base_name_override,
+    );
+    if let Some(parent) = csv_path.parent() {
+        if !parent.exists() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    for exporter in &export_options.exporters {
+        // csv_path is "<dir>/<name>[.NN].csv"; swap its extension for the exporter's
+        let path = export_options.output_path(&csv_path.with_extension(exporter.extension()));
+        let mut writer = ExportFile::create(&path, export_options.compress)
+            .map_err(|e| BBLError::export_failed(path.clone(), e))?;
+        let (exported, bytes_written) = exporter
+            .export(log, &mut writer)
+            .and_then(|exported| Ok((exported, writer.finish()?)))
+            .map_err(|e| BBLError::export_failed(path.clone(), e))?;
+
+        report.rows_written += exported.rows_written;
+        report.bytes_written += bytes_written;
+        report.extra_paths.push(path);
+    }
+
+    Ok(report)
+}
+
+/// Write adapter that counts the bytes passed through it
+struct CountingWriter<W: Write> {
+    inner: W,
+    bytes_written: u64,
+}
+
+impl<W: Write> CountingWriter<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            bytes_written: 0,
+        }
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes_written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Buffered output file of an export, gzip-compressed when requested
+///
+/// Call [`ExportFile::finish`] once everything is written: it writes the gzip
+/// trailer and reports errors that dropping the file would swallow.
+///
+/// # Examples
+/// ```rust,no_run
+/// use bbl_parser::{ExportFile, ExportOptions};
+/// use std::io::Write;
+/// use std::path::Path;
+///
+/// # fn main() -> anyhow::Result<()> {
+/// let opts = ExportOptions::default();
+/// let path = opts.output_path(Path::new("notes.csv"));
+/// let mut file = ExportFile::create(&path, opts.compress)?;
+/// writeln!(file, "time (us), note")?;
+/// let bytes_on_disk = file.finish()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ExportFile {
+    inner: ExportFileInner,
+}
+
+enum ExportFileInner {
+    Plain(CountingWriter<BufWriter<File>>),
+    #[cfg(feature = "gzip")]
+    Gzip(flate2::write::GzEncoder<CountingWriter<BufWriter<File>>>),
+}
+
+impl ExportFile {
+    /// Create `path`, gzip-compressing everything written when `compress` is
+    /// set (fails without the `gzip` feature)
+    pub fn create(path: &Path, compress: bool) -> std::io::Result<Self> {
+        #[cfg(not(feature = "gzip"))]
+        if compress {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Compressed export requested but bbl_parser was built without the `gzip` feature",
+            ));
+        }
+
+        let file = CountingWriter::new(BufWriter::new(File::create(path)?));
+        #[cfg(feature = "gzip")]
+        if compress {
+            return Ok(Self {
+                inner: ExportFileInner::Gzip(flate2::write::GzEncoder::new(
+                    file,
+                    flate2::Compression::default(),
+                )),
+            });
+        }
+        Ok(Self {
+            inner: ExportFileInner::Plain(file),
+        })
+    }
+
+    /// Finish the gzip stream and flush, returning the bytes stored on disk
+    #[cfg_attr(not(feature = "gzip"), allow(clippy::infallible_destructuring_match))]
+    pub fn finish(self) -> std::io::Result<u64> {
+        let mut file = match self.inner {
+            ExportFileInner::Plain(file) => file,
+            #[cfg(feature = "gzip")]
+            ExportFileInner::Gzip(encoder) => encoder.finish()?,
+        };
+        file.flush()?;
+        Ok(file.bytes_written)
+    }
+}
+
+impl Write for ExportFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match &mut self.inner {
+            ExportFileInner::Plain(file) => file.write(buf),
+            #[cfg(feature = "gzip")]
+            ExportFileInner::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match &mut self.inner {
+            ExportFileInner::Plain(file) => file.flush(),
+            #[cfg(feature = "gzip")]
+            ExportFileInner::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+impl std::fmt::Debug for ExportFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let compressed = !matches!(self.inner, ExportFileInner::Plain(_));
+        f.debug_struct("ExportFile")
+            .field("compressed", &compressed)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Extract the base filename from an input path with consistent fallback.
+/// Used by all export functions and path computation helpers to ensure
+/// consistent naming across CSV, GPX, and event exports.
+///
+/// Always returns "blackbox" as fallback for missing or non-UTF-8 filenames,
+/// ensuring compute_export_paths() predictions match actual export filenames.
+fn extract_base_name(input_path: &Path) -> &str {
+    let stem = input_path.file_stem().and_then(|s| s.to_str());
+
+    // "flight.BBL.gz" names its outputs after "flight"
+    let is_gzip = input_path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("gz"))
+        .unwrap_or(false);
+    let stem = if is_gzip {
+        stem.and_then(|s| Path::new(s).file_stem().and_then(|s| s.to_str()))
+    } else {
+        stem
+    };
+
+    stem.unwrap_or("blackbox")
+}
+
+/// Sanitize a `base_name_override` value for safe use in file path construction.
+/// Returns the final path component only, preventing directory traversal via `../`
+/// segments or absolute paths. Returns `None` if the value is empty or ends in `..`.
+fn sanitize_base_name_override(base_name_override: Option<&str>) -> Option<&str> {
+    let raw = base_name_override?;
+    Path::new(raw).file_name().and_then(|s| s.to_str())
+}
+
+/// Return a human-readable vendor name for a known filename prefix.
+/// Falls back to `"Unknown"` for unrecognised prefixes.
+pub fn vendor_name_for_prefix(prefix: &str) -> &'static str {
+    match prefix {
+        "EMUF_" => "EmuFlight",
+        "BTFL_" => "Betaflight",
+        "INAV_" => "iNav",
+        "QUIC_" => "Quicksilver",
+        _ => "Unknown",
+    }
+}
+
+/// Known firmware vendor filename prefixes mapped to their revision keywords.
+/// To add a new firmware: append `("PREFIX_", "keyword")` where keyword is a
+/// lowercase substring of that firmware's `H Firmware revision:` header value.
+/// For forks that share a base firmware's revision string, add an entry here
+/// for filename detection and add a corresponding entry to `FORK_REVISION_MAP`.
+const KNOWN_FIRMWARE_PREFIXES: &[(&str, &str)] = &[
+    ("EMUF_", "emuflight"),
+    ("BTFL_", "betaflight"),
+    ("INAV_", "inav"),
+    ("QUIC_", "quicksilver"), // Quicksilver; see FORK_REVISION_MAP for rename exemption
+];
+
+/// Fork firmware prefixes that intentionally report another vendor's revision string.
+/// Sessions in files with these prefixes are NOT renamed even when the revision string
+/// maps to a different canonical prefix — the mismatch is by design.
+///
+/// Each entry is `(file_prefix, reported_canonical_prefix)`.
+/// To add a fork: append `("FORK_", "BASE_")` where BASE_ is what
+/// `firmware_prefix_for_revision` returns for that fork's revision headers.
+const FORK_REVISION_MAP: &[(&str, &str)] = &[
+    // Quicksilver writes Betaflight revision headers for Blackbox Explorer compatibility.
+    ("QUIC_", "BTFL_"),
+];
+
+/// Return the canonical filename prefix for a firmware revision string (e.g. `"BTFL_"`),
+/// or `None` if the vendor is not recognised.
+pub fn firmware_prefix_for_revision(revision: &str) -> Option<&'static str> {
+    let rev_lower = revision.trim().to_lowercase();
+    for &(prefix, keyword) in KNOWN_FIRMWARE_PREFIXES {
+        if rev_lower.contains(keyword) {
+            return Some(prefix);
+        }
+    }
+    None
+}
+
+fn detect_bbl_filename_prefix(stem: &str) -> Option<&'static str> {
+    KNOWN_FIRMWARE_PREFIXES
+        .iter()
+        .find(|&&(prefix, _)| stem.starts_with(prefix))
+        .map(|&(prefix, _)| prefix)
+}
+
+/// Compute a corrected base name for a session whose firmware vendor differs from
+/// the BBL filename prefix.  Returns `Some(corrected_stem)` when a replacement is
+/// needed; `None` when the vendors match, either is unrecognised, or the file prefix
+/// is a known fork that intentionally uses a different base firmware's revision string.
+///
+/// # Examples
+/// - `EMUF_BLACKBOX_LOG_...BBL` + `EmuFlight 0.4.3`          → `None` (matches)
+/// - `EMUF_BLACKBOX_LOG_...BBL` + `Betaflight 2025.12.0-beta` → `Some("BTFL_BLACKBOX_LOG_...")`
+/// - `QUIC_Twiglet_...BFL`      + `Betaflight 4.3.0`          → `None` (fork exemption)
+pub fn corrected_session_base_name(bbl_path: &Path, firmware_revision: &str) -> Option<String> {
+    let stem = bbl_path.file_stem()?.to_str()?;
+    let bbl_prefix = detect_bbl_filename_prefix(stem)?;
+    let session_prefix = firmware_prefix_for_revision(firmware_revision)?;
+    if bbl_prefix == session_prefix {
+        return None;
+    }
+    // Known fork: the revision mismatch is by design — do not rename
+    if FORK_REVISION_MAP
+        .iter()
+        .any(|&(fp, bp)| fp == bbl_prefix && bp == session_prefix)
+    {
+        return None;
+    }
+    Some(format!("{}{}", session_prefix, &stem[bbl_prefix.len()..]))
+}
+
+/// Helper to compute export file paths with consistent naming across all export types.
+/// Ensures CLI status messages match actual filenames written by export functions.
+///
+/// # Arguments
+/// * `input_path` - Path to the input BBL file (used to extract base filename)
+/// * `export_options` - Export configuration with optional output directory
+/// * `log_number` - 1-based log number (for .NN suffix when multiple logs)
+/// * `total_logs` - Total number of logs in the file
+/// * `base_name_override` - Optional replacement stem (e.g. from `corrected_session_base_name`)
+///
+/// # Returns
+/// Tuple of (csv_path, headers_path, gpx_path, event_path) using consistent naming
+pub fn compute_export_paths(
+    input_path: &Path,
+    export_options: &ExportOptions,
+    log_number: usize,
+    total_logs: usize,
+    base_name_override: Option<&str>,
+) -> (
+    std::path::PathBuf,
+    std::path::PathBuf,
+    std::path::PathBuf,
+    std::path::PathBuf,
+) {
+    let base_name = sanitize_base_name_override(base_name_override)
+        .unwrap_or_else(|| extract_base_name(input_path));
+
+    let output_dir = if let Some(ref dir) = export_options.output_dir {
+        std::path::Path::new(dir)
+    } else {
+        input_path.parent().unwrap_or(std::path::Path::new("."))
+    };
+
+    let log_suffix = if total_logs > 1 || export_options.compat == CompatMode::BlackboxDecode {
+        format!(".{:02}", log_number)
+    } else {
+        String::new()
+    };
+
+    let csv_path = output_dir.join(format!("{}{}.csv", base_name, log_suffix));
+    let headers_path = output_dir.join(format!("{}{}.headers.csv", base_name, log_suffix));
+    let gpx_path = output_dir.join(format!("{}{}.gps.gpx", base_name, log_suffix));
+    let event_path = output_dir.join(format!("{}{}.event", base_name, log_suffix));
+
+    (csv_path, headers_path, gpx_path, event_path)
+}
+
+/// Apply [`ExportOptions::overwrite`] to the base name of a log's exports.
+///
+/// Returns the base name to pass as `base_name_override` to the export
+/// functions, or `None` when the log's outputs exist and
+/// [`OverwritePolicy::SkipExisting`] is set. Arguments are those of
+/// [`compute_export_paths`].
+///
+/// # Examples
+/// ```rust
+/// use bbl_parser::{resolve_export_base_name, ExportOptions, OverwritePolicy};
+/// use std::path::Path;
+///
+/// let opts = ExportOptions {
+///     csv: true,
+///     overwrite: OverwritePolicy::Rename,
+///     ..Default::default()
+/// };
+/// let base_name = resolve_export_base_name(Path::new("/nonexistent/flight.BBL"), &opts, 1, 1, None);
+/// assert_eq!(base_name.as_deref(), Some("flight"));
+/// ```
+pub fn resolve_export_base_name(
+    input_path: &Path,
+    export_options: &ExportOptions,
+    log_number: usize,
+    total_logs: usize,
+    base_name_override: Option<&str>,
+) -> Option<String> {
+    let base_name = sanitize_base_name_override(base_name_override)
+        .unwrap_or_else(|| extract_base_name(input_path))
+        .to_string();
+    let outputs_exist = |base_name: &str| {
+        planned_export_paths(
+            input_path,
+            export_options,
+            log_number,
+            total_logs,
+            base_name,
+        )
+        .iter()
+        .any(|path| path.exists())
+    };
+
+    match export_options.overwrite {
+        OverwritePolicy::Overwrite => Some(base_name),
+        OverwritePolicy::SkipExisting => (!outputs_exist(&base_name)).then_some(base_name),
+        OverwritePolicy::Rename => {
+            if !outputs_exist(&base_name) {
+                return Some(base_name);
+            }
+            (1..)
+                .map(|n| format!("{base_name}-{n}"))
+                .find(|candidate| !outputs_exist(candidate))
+        }
+    }
+}
+
+/// Files the export functions write for one log under `base_name`
+fn planned_export_paths(
+    input_path: &Path,
+    export_options: &ExportOptions,
+    log_number: usize,
+    total_logs: usize,
+    base_name: &str,
+) -> Vec<PathBuf> {
+    let (csv_path, headers_path, gpx_path, event_path) = compute_export_paths(
+        input_path,
+        export_options,
+        log_number,
+        total_logs,
+        Some(base_name),
+    );
+    let mut paths = Vec::new();
+    if export_options.csv {
+        paths.push(export_options.output_path(&csv_path));
+        paths.push(export_options.output_path(&headers_path));
+    }
+    if export_options.gpx {
+        paths.push(gpx_path.clone());
+    }
+    if export_options.kml {
+        paths.push(gpx_path.with_extension("kml"));
+    }
+    if export_options.gps_csv {
+        paths.push(export_options.output_path(&csv_path.with_extension("gps.csv")));
+    }
+    if export_options.event {
+        paths.push(export_options.output_path(&event_path));
+    }
+    if export_options.ndjson {
+        paths.push(export_options.output_path(&csv_path.with_extension("ndjson")));
+    }
+    if export_options.parquet {
+        paths.push(csv_path.with_extension("parquet"));
+    }
+    for exporter in &export_options.exporters {
+        paths.push(export_options.output_path(&csv_path.with_extension(exporter.extension())));
+    }
+    paths
+}
+
+/// Columns written by [`ExportOptions::simulate_imu`]; [`ExportOptions::mag_heading`]
+/// writes the last one
+const ATTITUDE_CSV_FIELDS: [&str; 3] = ["roll (deg)", "pitch (deg)", "heading (deg)"];
+
+/// Fields the simulated IMU reads, in [`AttitudeEstimator::update_raw`] order
+const ATTITUDE_INPUT_FIELDS: [&str; 9] = [
+    "gyroADC[0]",
+    "gyroADC[1]",
+    "gyroADC[2]",
+    "accSmooth[0]",
+    "accSmooth[1]",
+    "accSmooth[2]",
+    "magADC[0]",
+    "magADC[1]",
+    "magADC[2]",
+];
+
+/// What one log provides for the computed CSV columns
+struct CsvLogFacts<'a> {
+    header: &'a BBLHeader,
+    /// Battery cell count, for `vbatPerCell (V)`
+    cell_count: Option<u8>,
+    /// Simulated IMU columns the log's fields can feed
+    attitude_fields: &'a [&'a str],
+    /// A climb rate series was computed for `climbRate (m/s)`
+    has_climb_rate: bool,
+    /// The virtual current meter stands in for a missing `amperageLatest`
+    virtual_current_meter: bool,
+}
+
+/// Pre-computed CSV field mapping for performance
+#[derive(Debug)]
+struct CsvFieldMap {
+    field_name_to_lookup: Vec<(String, String)>, // (csv_name, lookup_name)
+}
+
+impl CsvFieldMap {
+    fn new(log: &CsvLogFacts, export_options: &ExportOptions) -> Self {
+        let header = log.header;
+        // blackbox_decode writes no computed columns
+        let computed = export_options.compat != CompatMode::BlackboxDecode;
+        let raw_units = export_options.raw_units;
+        let motor_percent =
+            export_options.motor_percent && header.motor_output_range().is_some() && computed;
+        let throttle_percent = export_options.throttle_percent && computed;
+        let setpoint_deg_per_sec = export_options.setpoint_deg_per_sec && computed;
+        let vbat_per_cell = export_options.vbat_per_cell && log.cell_count.is_some() && computed;
+        let gyro_deg_per_sec = export_options.gyro_deg_per_sec
+            && header.gyro_scale().is_some()
+            && !raw_units
+            && computed;
+        let acc_g = export_options.acc_g && header.acc_1g().is_some() && !raw_units && computed;
+
+        let debug_mode = export_options
+            .debug_mode_names
+            .then(|| header.debug_mode_name())
+            .flatten();
+        let rotorflight = header.firmware() == FirmwareKind::Rotorflight;
+        let mut field_name_to_lookup = Vec::new();
+
+        // I frame fields
+        for field_name in &header.i_frame_def.field_names {
+            let trimmed = field_name.trim();
+            let csv_name = if trimmed == "time" {
+                "time (us)".to_string()
+            } else if let Some(mode) = debug_mode.filter(|_| trimmed.starts_with("debug[")) {
+                format!("{trimmed} ({mode})")
+            } else if raw_units {
+                trimmed.to_string()
+            } else if trimmed == "vbatLatest" {
+                "vbatLatest (V)".to_string()
+            } else if trimmed == "amperageLatest" {
+                "amperageLatest (A)".to_string()
+            } else if is_flag_field(trimmed) {
+                format!("{trimmed} (flags)")
+            } else if gyro_deg_per_sec
+                && (trimmed.starts_with("gyroADC") || trimmed.starts_with("gyroUnfilt"))
+            {
+                format!("{trimmed} (deg/s)")
+            } else if acc_g && trimmed.starts_with("accSmooth") {
+                format!("{trimmed} (g)")
+            } else if rotorflight && is_rotorflight_rpm_field(trimmed) {
+                format!("{trimmed} (rpm)")
+            } else {
+                trimmed.to_string()
+            };
+
+            field_name_to_lookup.push((csv_name.clone(), trimmed.to_string()));
+        }
+
+        // Add computed fields IMMEDIATELY after I frame fields (like blackbox_decode does)
+        if log.virtual_current_meter
+            && !field_name_to_lookup
+                .iter()
+                .any(|(_, lookup)| lookup == "amperageLatest")
+        {
+            field_name_to_lookup.push((
+                "amperageLatest (A)".to_string(),
+                "amperageLatest".to_string(),
+            ));
+        }
+        if field_name_to_lookup
+            .iter()
+            .any(|(_, lookup)| lookup == "amperageLatest")
+        {
+            field_name_to_lookup.push(("energyCumulative (mAh)".to_string(), "".to_string()));
+        }
+
+        // Normalized motor outputs follow the other computed fields
+        if motor_percent {
+            let motors: Vec<String> = field_name_to_lookup
+                .iter()
+                .map(|(_, lookup)| lookup)
+                .filter(|lookup| lookup.starts_with("motor["))
+                .cloned()
+                .collect();
+            for motor in motors {
+                field_name_to_lookup.push((format!("{motor} (%)"), motor));
+            }
+        }
+        if throttle_percent
+            && field_name_to_lookup
+                .iter()
+                .any(|(_, lookup)| lookup == "rcCommand[3]")
+        {
+            field_name_to_lookup.push(("throttle (%)".to_string(), "rcCommand[3]".to_string()));
+        }
+        if setpoint_deg_per_sec {
+            for axis in 0..3 {
+                let lookup = format!("rcCommand[{axis}]");
+                if header.axis_rates(axis).is_some()
+                    && field_name_to_lookup.iter().any(|(_, l)| *l == lookup)
+                {
+                    field_name_to_lookup.push((format!("setpoint[{axis}] (deg/s)"), lookup));
+                }
+            }
+        }
+        if vbat_per_cell
+            && field_name_to_lookup
+                .iter()
+                .any(|(_, lookup)| lookup == "vbatLatest")
+        {
+            field_name_to_lookup.push(("vbatPerCell (V)".to_string(), "vbatLatest".to_string()));
+        }
+        for name in log.attitude_fields {
+            field_name_to_lookup.push((name.to_string(), "".to_string()));
+        }
+        if log.has_climb_rate {
+            field_name_to_lookup.push(("climbRate (m/s)".to_string(), "".to_string()));
+        }
+
+        // S frame fields (with flag formatting)
+        for field_name in &header.s_frame_def.field_names {
+            let trimmed = field_name.trim();
+            if trimmed == "time" {
+                continue;
+            } // Skip duplicate
+
+            let csv_name = if raw_units {
+                trimmed.to_string()
+            } else if is_flag_field(trimmed) {
+                format!("{trimmed} (flags)")
+            } else {
+                trimmed.to_string()
+            };
+
+            field_name_to_lookup.push((csv_name.clone(), trimmed.to_string()));
+        }
+
+        Self {
+            field_name_to_lookup,
+        }
+    }
+
+    /// Keep only the columns matching `patterns`, ordered by the first pattern
+    /// each one matches (all columns when `patterns` is empty)
+    ///
+    /// A pattern matches a column's CSV name or the log field it comes from.
+    fn select(&mut self, patterns: &[String]) {
+        if patterns.is_empty() {
+            return;
+        }
+        let mut columns: Vec<Option<(String, String)>> =
+            std::mem::take(&mut self.field_name_to_lookup)
+                .into_iter()
+                .map(Some)
+                .collect();
+        for pattern in patterns {
+            for column in &mut columns {
+                let matches = column.as_ref().is_some_and(|(csv_name, lookup)| {
+                    wildcard_match(pattern, csv_name)
+                        || (!lookup.is_empty() && wildcard_match(pattern, lookup))
+                });
+                if matches {
+                    self.field_name_to_lookup.extend(column.take());
+                }
+            }
+        }
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any run of characters
+/// and `?` a single character; everything else, including `[` and `]`, is
+/// literal so that `gyroADC[*]` selects every gyro axis
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let (pattern, text) = (pattern.as_bytes(), text.as_bytes());
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it currently covers up to
+    let mut backtrack: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(b'*') => {
+                p += 1;
+                backtrack = Some((p, t));
+            }
+            Some(&c) if c == b'?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    p = star_p;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, t));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// Export BBL log to CSV format
+///
+/// # Returns
+/// An `ExportReport` containing paths to the CSV and headers files that were created,
+/// or an error if the export failed.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "info", skip_all, fields(log_number = log.log_number))
+)]
+pub fn export_to_csv(
+    log: &BBLLog,
+    input_path: &Path,
+    export_options: &ExportOptions,
+    base_name_override: Option<&str>,
+) -> Result<ExportReport> {
+    #[cfg(feature = "tracing")]
+    let started = std::time::Instant::now();
+
+    let (flight_csv_path, header_csv_path, _, _) = compute_export_paths(
+        input_path,
+        export_options,
+        log.log_number,
+        log.total_logs,
+        base_name_override,
+    );
+    let flight_csv_path = export_options.output_path(&flight_csv_path);
+    let header_csv_path = export_options.output_path(&header_csv_path);
+
+    // Create output directory if it doesn't exist
+    if let Some(output_dir) = flight_csv_path.parent() {
+        if !output_dir.exists() {
+            std::fs::create_dir_all(output_dir)?;
+        }
+    }
+
+    // Export plaintext headers to separate CSV (blackbox_decode writes none)
+    let header_export = export_options.compat != CompatMode::BlackboxDecode;
+    let header_bytes = if header_export {
+        export_headers_to_csv(&log.header, &header_csv_path, export_options.compress)
+            .map_err(|e| BBLError::export_failed(header_csv_path.clone(), e))?
+    } else {
+        0
+    };
+
+    // Export flight data (I, P, S frames) to main CSV
+    let (rows_written, flight_bytes) =
+        export_flight_data_to_csv(log, &flight_csv_path, export_options)
+            .map_err(|e| BBLError::export_failed(flight_csv_path.clone(), e))?;
+
+    // S and G frames at their own rate
+    let mut stream_paths = [None, None];
+    let mut stream_bytes = 0;
+    if export_options.split_frame_types {
+        let (csv_path, _, _, _) = compute_export_paths(
+            input_path,
+            export_options,
+            log.log_number,
+            log.total_logs,
+            base_name_override,
+        );
+        for ((frames, extension), stream_path) in
+            [(&log.slow_frames, "S.csv"), (&log.gps_frames, "G.csv")]
+                .into_iter()
+                .zip(&mut stream_paths)
+        {
+            if frames.is_empty() {
+                continue;
+            }
+            let path = export_options.output_path(&csv_path.with_extension(extension));
+            stream_bytes += ExportFile::create(&path, export_options.compress)
+                .and_then(|mut writer| {
+                    write_frame_stream_csv(
+                        frames,
+                        log.header.firmware(),
+                        &mut writer,
+                        export_options,
+                        export_options.time_range.bounds(log),
+                    )?;
+                    writer.finish()
+                })
+                .map_err(|e| BBLError::export_failed(path.clone(), e))?;
+            *stream_path = Some(path);
+        }
+    }
+    let [s_frame_csv_path, g_frame_csv_path] = stream_paths;
+
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        path = %flight_csv_path.display(),
+        rows = rows_written,
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "exported CSV"
+    );
+
+    Ok(ExportReport {
+        csv_path: Some(flight_csv_path),
+        headers_path: header_export.then_some(header_csv_path),
+        gpx_path: None,
+        event_path: None,
+        s_frame_csv_path,
+        g_frame_csv_path,
+        rows_written,
+        bytes_written: header_bytes + flight_bytes + stream_bytes,
+        ..Default::default()
+    })
+}
+
+/// Write S or G frames as CSV: `time (us)` followed by the frame's fields,
+/// one row per logged frame
+///
+/// Flag fields are written as text like in the flight data CSV unless
+/// `raw_units` is set; fields a frame did not carry are left empty.
+fn write_frame_stream_csv<W: Write>(
+    frames: &FrameColumns,
+    firmware: FirmwareKind,
+    writer: &mut W,
+    export_options: &ExportOptions,
+    (start_us, end_us): (u64, u64),
+) -> std::io::Result<()> {
+    let dialect = &export_options.csv_options;
+    let int_width = if export_options.compat == CompatMode::BlackboxDecode {
+        3
+    } else {
+        4
+    };
+    let fields: Vec<(usize, &str)> = frames
+        .field_names()
+        .iter()
+        .map(|name| name.trim())
+        .enumerate()
+        .filter(|&(_, name)| name != "time")
+        .collect();
+    let is_flag = |name: &str| !export_options.raw_units && is_flag_field(name);
+
+    dialect.write_field(writer, true, "time (us)")?;
+    for &(_, name) in &fields {
+        if is_flag(name) {
+            dialect.write_field(writer, false, &format!("{name} (flags)"))?;
+        } else {
+            dialect.write_field(writer, false, name)?;
+        }
+    }
+    dialect.end_record(writer)?;
+
+    for (row, timestamp) in frames.timestamps().iter().enumerate() {
+        if !(start_us..=end_us).contains(timestamp) {
+            continue;
+        }
+        dialect.write_field(writer, true, &timestamp.to_string())?;
+        for &(field, name) in &fields {
+            let formatted = match frames.value(row, field) {
+                None => String::new(),
+                Some(value) if is_flag(name) => format_flag_field(name, value, firmware),
+                Some(value) => format!("{value:int_width$}"),
+            };
+            dialect.write_field(writer, false, &formatted)?;
+        }
+        dialect.end_record(writer)?;
+    }
+    Ok(())
+}
+
+/// Export headers to CSV file, returning the number of bytes written
+fn export_headers_to_csv(
+    header: &BBLHeader,
+    output_path: &Path,
+    compress: bool,
+) -> anyhow::Result<u64> {
+    let mut writer = ExportFile::create(output_path, compress)
+        .with_context(|| format!("Failed to create headers CSV file: {output_path:?}"))?;
+    write_headers_csv(header, &mut writer)?;
+
+    writer
+        .finish()
+        .with_context(|| format!("Failed to flush headers CSV file: {output_path:?}"))
+}
+
+/// Write the `Field,Value` header table
+fn write_headers_csv<W: Write>(header: &BBLHeader, mut writer: W) -> std::io::Result<()> {
+    // Write CSV header
+    writeln!(writer, "Field,Value")?;
+
+    for (field_name, field_value) in header_entries(header) {
+        // Escape commas in values by wrapping in quotes
+        let escaped_value = if field_value.contains(',') {
+            format!("\"{}\"", field_value.replace('"', "\"\""))
+        } else {
+            field_value.to_string()
+        };
+
+        writeln!(writer, "{field_name},{escaped_value}")?;
+        if field_name == "features" {
+            let names = header.features();
+            if !names.is_empty() {
+                writeln!(writer, "{FEATURE_NAMES_ENTRY},{}", names.join("|"))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Extra header export entry listing the names of the enabled `features` bits
+const FEATURE_NAMES_ENTRY: &str = "features (names)";
+
+/// `(name, value)` of every `H name:value` header line, trimmed, in log order
+fn header_entries(header: &BBLHeader) -> impl Iterator<Item = (&str, &str)> {
+    header.all_headers.iter().filter_map(|header_line| {
+        // Remove "H " prefix and split at the first colon
+        let (name, value) = header_line.strip_prefix("H ")?.split_once(':')?;
+        Some((name.trim(), value.trim()))
+    })
+}
+
+/// Export flight data to CSV file, returning the rows and bytes written
+fn export_flight_data_to_csv(
+    log: &BBLLog,
+    output_path: &Path,
+    export_options: &ExportOptions,
+) -> Result<(usize, u64)> {
+    let mut writer = ExportFile::create(output_path, export_options.compress)
+        .with_context(|| format!("Failed to create flight data CSV file: {output_path:?}"))?;
+    let rows_written = write_flight_data_csv(log, &mut writer, export_options)?;
+
+    let bytes_written = writer
+        .finish()
+        .with_context(|| format!("Failed to flush flight data CSV file: {output_path:?}"))?;
+
+    Ok((rows_written, bytes_written))
+}
+
+/// Write the flight data CSV (column header line plus one row per main frame),
+/// returning the number of data rows written
+fn write_flight_data_csv<W: Write>(
+    log: &BBLLog,
+    writer: W,
+    export_options: &ExportOptions,
+) -> Result<usize> {
+    write_flight_data(log, writer, export_options, None)
+}
+
+/// Write the flight data CSV of `log`; with `concat`, rows get a leading
+/// `log_number` column and the column header line is only written for the
+/// first log
+fn write_flight_data<W: Write>(
+    log: &BBLLog,
+    mut writer: W,
+    export_options: &ExportOptions,
+    mut concat: Option<&mut ConcatState>,
+) -> Result<usize> {
+    // Build optimized field mapping
+    let blackbox_decode = export_options.compat == CompatMode::BlackboxDecode;
+    let has_field = |name: &&str| log.frames.field_index(name).is_some();
+    let attitude_fields: &[&str] =
+        if blackbox_decode || !ATTITUDE_INPUT_FIELDS[..6].iter().all(has_field) {
+            &[]
+        } else if export_options.simulate_imu {
+            &ATTITUDE_CSV_FIELDS
+        } else if export_options.mag_heading && ATTITUDE_INPUT_FIELDS[6..].iter().all(has_field) {
+            &ATTITUDE_CSV_FIELDS[2..]
+        } else {
+            &[]
+        };
+    let attitude = AttitudeEstimator::for_header(&log.header)
+        .filter(|_| !attitude_fields.is_empty())
+        .map(|estimator| estimator.with_declination(export_options.declination_deg));
+    let climb_rate = (export_options.climb_rate && !blackbox_decode)
+        .then(|| log.climb_rate(DEFAULT_CLIMB_RATE_TIME_CONSTANT_S))
+        .flatten();
+    let virtual_current_meter = (export_options.simulate_current_meter
+        && !blackbox_decode
+        && log.frames.field_index("amperageLatest").is_none()
+        && log.frames.field_index("rcCommand[3]").is_some())
+    .then(|| {
+        let (offset, scale) = log
+            .header
+            .current_meter()
+            .unwrap_or(DEFAULT_VIRTUAL_CURRENT_METER);
+        (
+            export_options.sim_current_meter_offset.unwrap_or(offset),
+            export_options.sim_current_meter_scale.unwrap_or(scale),
+        )
+    });
+    let motor_range = log.header.motor_output_range();
+    let facts = CsvLogFacts {
+        header: &log.header,
+        cell_count: log.cell_count(),
+        attitude_fields: if attitude.is_some() {
+            attitude_fields
+        } else {
+            &[]
+        },
+        has_climb_rate: climb_rate.is_some(),
+        virtual_current_meter: virtual_current_meter.is_some(),
+    };
+    let mut csv_map = CsvFieldMap::new(&facts, export_options);
+    csv_map.select(&export_options.csv_fields);
+    let attitude = attitude.filter(|_| {
+        csv_map
+            .field_name_to_lookup
+            .iter()
+            .any(|(csv_name, _)| ATTITUDE_CSV_FIELDS.contains(&csv_name.as_str()))
+    });
+    let field_names: Vec<String> = csv_map
+        .field_name_to_lookup
+        .iter()
+        .map(|(csv_name, _)| csv_name.clone())
+        .collect();
+
+    let frames = &log.frames;
+    if frames.is_empty() {
+        return Ok(0); // No data to export
+    }
+
+    // Rows in decode order; blackbox_decode writes frames this way, so sorting is opt-in
+    let mut rows: Vec<usize> = (0..frames.len()).collect();
+    if !export_options.preserve_order {
+        rows.sort_by_key(|&row| frames.timestamps()[row]);
+    }
+
+    // Values of the CSV columns, followed by the fields that gap detection,
+    // energyCumulative, the virtual current meter and the simulated IMU need
+    // when they are not among the selected columns
+    let mut lookups: Vec<&str> = csv_map
+        .field_name_to_lookup
+        .iter()
+        .map(|(_, lookup)| lookup.as_str())
+        .collect();
+    let attitude_inputs: &[&str] = if attitude.is_some() {
+        &ATTITUDE_INPUT_FIELDS
+    } else {
+        &[]
+    };
+    let current_inputs: &[&str] = if virtual_current_meter.is_some() {
+        &["rcCommand[3]"]
+    } else {
+        &[]
+    };
+    for &support in ["loopIteration", "amperageLatest"]
+        .iter()
+        .chain(attitude_inputs)
+        .chain(current_inputs)
+    {
+        if !lookups.contains(&support) {
+            lookups.push(support);
+        }
+    }
+
+    // Resolve every lookup name to its column once instead of per row
+    let field_columns: Vec<Option<usize>> = lookups
+        .iter()
+        .map(|lookup_name| frames.field_index(lookup_name))
+        .collect();
+    let lookup_position = |name: &str| lookups.iter().position(|&lookup| lookup == name);
+    let loop_position = lookup_position("loopIteration");
+
+    // Write field names header
+    let dialect = &export_options.csv_options;
+    let merge_gps = export_options.merge_gps && !log.gps_coordinates.is_empty();
+    let gps_names: &[&str] = if merge_gps { &GPS_CSV_FIELDS } else { &[] };
+    let merged_home = HomeReference::new(&log.gps_coordinates, &log.home_coordinates)
+        .filter(|_| merge_gps && export_options.home_distance);
+    let home_names: &[&str] = if merged_home.is_some() {
+        &HOME_DISTANCE_CSV_FIELDS
+    } else {
+        &[]
+    };
+    let event_names: &[&str] = if export_options.event_column {
+        &["event"]
+    } else {
+        &[]
+    };
+    let extra_names = gps_names
+        .iter()
+        .chain(home_names)
+        .chain(event_names)
+        .copied();
+    let columns = field_names.iter().map(String::as_str).chain(extra_names);
+    let mut time_shift_us = 0;
+    let mut log_number = None;
+    let write_header = match concat.as_deref_mut() {
+        None => true,
+        Some(state) => {
+            let first_us = frames.timestamps()[rows[0]] as i64;
+            if state.rebase_time {
+                time_shift_us = -first_us;
+            }
+            if state.continuous_time {
+                log_number = Some(state.logs_written + 1);
+                if let Some((shift, last_us, interval)) = state.previous_log {
+                    time_shift_us = if first_us + shift > last_us {
+                        shift
+                    } else {
+                        last_us + interval - first_us
+                    };
+                }
+            } else {
+                log_number = Some(log.log_number);
+            }
+            match &state.columns {
+                None => {
+                    state.columns = Some(columns.clone().map(String::from).collect());
+                    true
+                }
+                Some(first) if first.iter().map(String::as_str).eq(columns.clone()) => false,
+                Some(_) => {
+                    return Err(BBLError::ColumnMismatch {
+                        log_number: log.log_number,
+                    })
+                }
+            }
+        }
+    };
+    if write_header {
+        let log_number_name = log_number.map(|_| "log_number");
+        for (i, field_name) in log_number_name.into_iter().chain(columns).enumerate() {
+            dialect.write_field(&mut writer, i == 0, field_name)?;
+        }
+        dialect.end_record(&mut writer)?;
+    }
+
+    let loop_step = match export_options.gap_fill {
+        GapFillPolicy::Leave => None,
+        _ => loop_position
+            .and_then(|position| field_columns[position])
+            .and_then(|column| typical_loop_step(frames, &rows, column)),
+    };
+    // Main-frame fields are interpolated; slow-frame state is repeated
+    let interpolated: Vec<bool> = lookups
+        .iter()
+        .map(|lookup| {
+            log.header
+                .i_frame_def
+                .field_names
+                .iter()
+                .any(|name| name == lookup)
+        })
+        .collect();
+
+    let mut row_writer = FlightRowWriter {
+        writer,
+        dialect,
+        field: String::new(),
+        blackbox_decode,
+        log_number,
+        time_shift_us,
+        csv_map: &csv_map,
+        firmware_revision: &log.header.firmware_revision,
+        firmware: log.header.firmware(),
+        loop_position,
+        amperage_position: lookup_position("amperageLatest"),
+        current_meter: log.header.current_meter(),
+        virtual_current_meter: virtual_current_meter
+            .map(|meter| (lookup_position("rcCommand[3]"), meter)),
+        motor_range,
+        throttle: (log.header.throttle_range(), log.header.is_3d_mode()),
+        cell_count: facts.cell_count.unwrap_or(1),
+        setpoint_rates: csv_map
+            .field_name_to_lookup
+            .iter()
+            .map(|(csv_name, lookup)| {
+                if !csv_name.starts_with("setpoint[") {
+                    return None;
+                }
+                let axis = lookup.strip_prefix("rcCommand[")?.strip_suffix(']')?;
+                log.header.axis_rates(axis.parse().ok()?)
+            })
+            .collect(),
+        rates_type: log.header.rates_type(),
+        high_resolution: csv_map
+            .field_name_to_lookup
+            .iter()
+            .map(|(_, lookup)| {
+                !export_options.raw_units
+                    && !blackbox_decode
+                    && log.header.is_high_resolution()
+                    && is_high_resolution_field(lookup)
+            })
+            .collect(),
+        gyro_scale: log.header.gyro_scale().unwrap_or(1.0),
+        acc_1g: log.header.acc_1g().unwrap_or(1),
+        energy: EnergyIntegrator::new(),
+        attitude: attitude.map(|estimator| {
+            (
+                estimator,
+                ATTITUDE_INPUT_FIELDS.map(|name| {
+                    lookup_position(name).filter(|&position| field_columns[position].is_some())
+                }),
+            )
+        }),
+        current_attitude: Attitude::default(),
+        climb_rate: climb_rate.as_ref(),
+        time_window: export_options.time_range.bounds(log),
+        rows_written: 0,
+        merged_gps: merge_gps.then_some((
+            log.gps_coordinates.as_slice(),
+            export_options.gps_gap_threshold_us,
+        )),
+        merged_home,
+        event_markers: export_options
+            .event_column
+            .then_some(log.event_frames.as_slice()),
+        next_event: 0,
+    };
+    let mut values: Vec<Option<i32>> = vec![None; field_columns.len()];
+    let mut previous: Option<(u64, Vec<Option<i32>>)> = None;
+    let mut gap_values: Vec<Option<i32>> = vec![None; field_columns.len()];
+
+    for &row in &rows {
+        let timestamp = frames.timestamps()[row];
+        for (value, &column) in values.iter_mut().zip(&field_columns) {
+            *value = column.and_then(|field| frames.value(row, field));
+        }
+
+        if let (Some(step), Some((previous_timestamp, previous_values))) = (loop_step, &previous) {
+            let loop_at = |values: &[Option<i32>]| loop_position.and_then(|p| values[p]);
+            if let (Some(from), Some(to)) = (loop_at(previous_values), loop_at(&values)) {
+                let span = i64::from(to) - i64::from(from);
+                let missing = (span - 1) / i64::from(step);
+                if span > 0 && missing > 0 && missing <= MAX_GAP_FILL_ROWS {
+                    for k in 1..=missing {
+                        let fraction = (k * i64::from(step)) as f64 / span as f64;
+                        for (i, value) in gap_values.iter_mut().enumerate() {
+                            *value = match (previous_values[i], values[i]) {
+                                _ if Some(i) == loop_position => {
+                                    Some(from + (k * i64::from(step)) as i32)
+                                }
+                                (Some(a), Some(b))
+                                    if export_options.gap_fill == GapFillPolicy::Interpolate
+                                        && interpolated[i] =>
+                                {
+                                    Some(interpolate_value(a, b, fraction))
+                                }
+                                (a, _) => a,
+                            };
+                        }
+                        let gap_timestamp = if timestamp > *previous_timestamp {
+                            previous_timestamp
+                                + ((timestamp - previous_timestamp) as f64 * fraction) as u64
+                        } else {
+                            *previous_timestamp
+                        };
+                        row_writer.write_row(gap_timestamp, &gap_values)?;
+                    }
+                }
+            }
+        }
+
+        row_writer.write_row(timestamp, &values)?;
+        if loop_step.is_some() {
+            match &mut previous {
+                Some((previous_timestamp, previous_values)) => {
+                    *previous_timestamp = timestamp;
+                    previous_values.copy_from_slice(&values);
+                }
+                None => previous = Some((timestamp, values.clone())),
+            }
+        }
+    }
+
+    if let Some(state) = concat {
+        state.logs_written += 1;
+        let last_us = frames.timestamps()[rows[rows.len() - 1]] as i64;
+        let interval = match rows.len() {
+            1 => 1,
+            n => (last_us - frames.timestamps()[rows[n - 2]] as i64).max(1),
+        };
+        state.previous_log = Some((time_shift_us, last_us + time_shift_us, interval));
+    }
+    Ok(row_writer.rows_written)
+}
+
+/// Value `fraction` of the way from `a` to `b`, rounded to the nearest integer
+///
+/// Works in i64 and f64 so that values of opposite sign near the ends of the
+/// i32 range cannot overflow; the result saturates at the i32 bounds.
+fn interpolate_value(a: i32, b: i32, fraction: f64) -> i32 {
+    let delta = i64::from(b) - i64::from(a);
+    (f64::from(a) + delta as f64 * fraction).round() as i32
+}
+
+/// Largest gap, in missing frames, that gap filling will synthesize rows for;
+/// longer gaps are logging pauses rather than lost frames and are left as-is
+const MAX_GAP_FILL_ROWS: i64 = 256;
+
+/// Most common positive `loopIteration` step between consecutive rows
+pub(crate) fn typical_loop_step(
+    frames: &FrameColumns,
+    rows: &[usize],
+    loop_column: usize,
+) -> Option<u32> {
+    let mut step_counts: HashMap<u32, usize> = HashMap::new();
+    for pair in rows.windows(2) {
+        if let (Some(a), Some(b)) = (
+            frames.value(pair[0], loop_column),
+            frames.value(pair[1], loop_column),
+        ) {
+            if b > a {
+                *step_counts.entry((b - a) as u32).or_insert(0) += 1;
+            }
+        }
+    }
+    step_counts
+        .into_iter()
+        .max_by_key(|&(step, count)| (count, std::cmp::Reverse(step)))
+        .map(|(step, _)| step)
+}
+
+/// Formats flight data rows, carrying the values derived across rows
+struct FlightRowWriter<'a, W: Write> {
+    writer: W,
+    dialect: &'a CsvOptions,
+    /// Scratch buffer for formatting one field
+    field: String,
+    /// Number formatting of [`CompatMode::BlackboxDecode`]
+    blackbox_decode: bool,
+    /// Leading `log_number` column of a concatenated CSV
+    log_number: Option<usize>,
+    /// Added to the written `time (us)` values
+    time_shift_us: i64,
+    csv_map: &'a CsvFieldMap,
+    firmware_revision: &'a str,
+    firmware: FirmwareKind,
+    loop_position: Option<usize>,
+    amperage_position: Option<usize>,
+    /// Current sensor calibration applied to ADC amperage readings
+    current_meter: Option<(i32, i32)>,
+    /// Position of `rcCommand[3]` and `(offset, scale)` of the virtual current
+    /// meter that replaces the missing `amperageLatest`
+    virtual_current_meter: Option<(Option<usize>, (i32, i32))>,
+    motor_range: Option<(i32, i32)>,
+    /// Throttle command range and 3D mode
+    throttle: ((i32, i32), bool),
+    /// Battery cells, for the `vbatPerCell (V)` column
+    cell_count: u8,
+    /// Per CSV field: rate settings of a computed `setpoint[n] (deg/s)` column
+    setpoint_rates: Vec<Option<AxisRates>>,
+    rates_type: RatesType,
+    /// Per CSV field: written in normal-resolution units with one decimal place
+    high_resolution: Vec<bool>,
+    /// Degrees per second per raw gyro unit, for the `(deg/s)` columns
+    gyro_scale: f32,
+    /// Raw accelerometer reading of 1 g, for the `(g)` columns
+    acc_1g: i32,
+    /// Running `energyCumulative (mAh)`
+    energy: EnergyIntegrator,
+    /// Simulated IMU with the positions of its input fields
+    /// ([`ATTITUDE_INPUT_FIELDS`]) among the row values
+    attitude: Option<(AttitudeEstimator, [Option<usize>; 9])>,
+    /// Attitude at the current row
+    current_attitude: Attitude,
+    /// Series of the `climbRate (m/s)` column
+    climb_rate: Option<&'a ClimbRate>,
+    /// Rows outside these timestamps are not written ([`ExportOptions::time_range`])
+    time_window: (u64, u64),
+    rows_written: usize,
+    /// GPS fixes to merge into each row, with the maximum distance in time
+    /// to the nearest fix (`0` for no limit)
+    merged_gps: Option<(&'a [GpsCoordinate], u64)>,
+    /// Home position for the merged distance-from-home columns
+    merged_home: Option<HomeReference<'a>>,
+    /// Events for the `event` column, each written on the first row at or
+    /// after its timestamp
+    event_markers: Option<&'a [EventFrame]>,
+    next_event: usize,
+}
+
+impl<W: Write> FlightRowWriter<'_, W> {
+    /// Write one row; `values` holds the raw value of each CSV field in order
+    fn write_row(&mut self, timestamp: u64, values: &[Option<i32>]) -> anyhow::Result<()> {
+        use std::fmt::Write as _;
+
+        let value_at = |position: Option<usize>| position.and_then(|p| values[p]);
+        let virtual_amps = self
+            .virtual_current_meter
+            .and_then(|(position, (offset, scale))| {
+                let throttle = value_at(position)?;
+                Some(convert_throttle_to_virtual_amps(throttle, offset, scale))
+            });
+
+        // Calculate energyCumulative for this frame
+        if let Some(current_amps) = virtual_amps {
+            self.energy.add(timestamp, current_amps);
+        } else if let Some(current_raw) = value_at(self.amperage_position) {
+            let current_amps = convert_amperage_with_meter(
+                current_raw,
+                self.firmware_revision,
+                self.current_meter,
+            );
+            self.energy.add(timestamp, current_amps);
+        }
+
+        // The IMU also keeps running outside the window
+        if let Some((estimator, positions)) = &mut self.attitude {
+            let read =
+                |axes: &[Option<usize>]| [0, 1, 2].map(|axis| value_at(axes[axis]).unwrap_or(0));
+            let mag = positions[6..]
+                .iter()
+                .all(Option::is_some)
+                .then(|| read(&positions[6..]));
+            self.current_attitude = estimator.update_raw(
+                timestamp,
+                read(&positions[..3]),
+                read(&positions[3..6]),
+                mag,
+            );
+        }
+
+        // energyCumulative keeps counting outside the window
+        let (window_start, window_end) = self.time_window;
+        if timestamp < window_start || timestamp > window_end {
+            // Events before the window are not carried onto its first row
+            if let Some(events) = self.event_markers {
+                while events
+                    .get(self.next_event)
+                    .is_some_and(|event| event.timestamp_us <= timestamp)
+                {
+                    self.next_event += 1;
+                }
+            }
+            return Ok(());
+        }
+
+        let writer = &mut self.writer;
+        let field = &mut self.field;
+        if let Some(log_number) = self.log_number {
+            self.dialect
+                .write_field(writer, true, &log_number.to_string())?;
+        }
+        let int_width = if self.blackbox_decode { 3 } else { 4 };
+        for (i, (csv_name, lookup_name)) in self.csv_map.field_name_to_lookup.iter().enumerate() {
+            field.clear();
+
+            // Fast path for special fields
+            if csv_name == "time (us)" {
+                write!(
+                    field,
+                    "{}",
+                    (timestamp as i64 + self.time_shift_us).max(0) as i32
+                )?;
+            } else if csv_name == "loopIteration" {
+                let value = value_at(self.loop_position).unwrap_or(self.rows_written as i32);
+                write!(field, "{value:int_width$}")?;
+            } else if csv_name == "vbatLatest (V)" {
+                let volts = convert_vbat_to_volts(values[i].unwrap_or(0), self.firmware_revision);
+                if self.blackbox_decode {
+                    write!(field, "{volts:.3}")?;
+                } else {
+                    write!(field, "{volts:4.1}")?;
+                }
+            } else if csv_name == "amperageLatest (A)" {
+                let amps = virtual_amps.unwrap_or_else(|| {
+                    convert_amperage_with_meter(
+                        values[i].unwrap_or(0),
+                        self.firmware_revision,
+                        self.current_meter,
+                    )
+                });
+                if self.blackbox_decode {
+                    write!(field, "{amps:.3}")?;
+                } else {
+                    write!(field, "{amps:4.2}")?;
+                }
+            } else if csv_name == "vbatPerCell (V)" {
+                let volts = convert_vbat_to_volts(values[i].unwrap_or(0), self.firmware_revision);
+                write!(field, "{:4.2}", volts / self.cell_count as f32)?;
+            } else if csv_name == "energyCumulative (mAh)" {
+                let energy = self.energy.mah() as i32;
+                if self.blackbox_decode {
+                    write!(field, "{energy}")?;
+                } else {
+                    write!(field, "{energy:5}")?;
+                }
+            } else if csv_name == "roll (deg)" {
+                write!(field, "{:6.1}", self.current_attitude.roll_deg)?;
+            } else if csv_name == "pitch (deg)" {
+                write!(field, "{:6.1}", self.current_attitude.pitch_deg)?;
+            } else if csv_name == "heading (deg)" {
+                write!(field, "{:5.1}", self.current_attitude.heading_deg)?;
+            } else if csv_name == "climbRate (m/s)" {
+                let climb_rate = self.climb_rate.and_then(|series| series.at(timestamp));
+                write!(field, "{:5.2}", climb_rate.unwrap_or(0.0))?;
+            } else if csv_name == "throttle (%)" {
+                let raw_value = values[i].unwrap_or(0);
+                let ((min, max), is_3d) = self.throttle;
+                write!(
+                    field,
+                    "{:5.1}",
+                    convert_throttle_to_percent(raw_value, min, max, is_3d)
+                )?;
+            } else if csv_name.ends_with(" (%)") {
+                let raw_value = values[i].unwrap_or(0);
+                let (min, max) = self.motor_range.unwrap_or_default();
+                write!(
+                    field,
+                    "{:5.1}",
+                    convert_motor_to_percent(raw_value, min, max)
+                )?;
+            } else if csv_name.ends_with(" (flags)") {
+                // Handle flag fields - output text values like blackbox_decode.c
+                let raw_value = values[i].unwrap_or(0);
+                field.push_str(&format_flag_field(lookup_name, raw_value, self.firmware));
+            } else if let Some(rates) = self.setpoint_rates[i] {
+                let raw_value = values[i].unwrap_or(0);
+                let command = if self.high_resolution[i] {
+                    convert_high_resolution_value(raw_value)
+                } else {
+                    raw_value as f64
+                };
+                write!(
+                    field,
+                    "{:6.1}",
+                    convert_rc_command_to_setpoint(command, rates, self.rates_type)
+                )?;
+            } else if csv_name.ends_with(" (deg/s)") {
+                let raw_value = values[i].unwrap_or(0);
+                let value = if self.high_resolution[i] {
+                    convert_high_resolution_value(raw_value)
+                } else {
+                    raw_value as f64
+                };
+                write!(
+                    field,
+                    "{:6.1}",
+                    convert_gyro_to_deg_per_sec(value, self.gyro_scale)
+                )?;
+            } else if csv_name.ends_with(" (g)") {
+                let raw_value = values[i].unwrap_or(0);
+                write!(field, "{:6.3}", convert_acc_to_g(raw_value, self.acc_1g))?;
+            } else if self.high_resolution[i] {
+                let raw_value = values[i].unwrap_or(0);
+                write!(field, "{:4.1}", convert_high_resolution_value(raw_value))?;
+            } else {
+                let value = values[i].unwrap_or(0);
+                write!(field, "{value:int_width$}")?;
+            }
+            let first = i == 0 && self.log_number.is_none();
+            self.dialect.write_field(writer, first, field)?;
+        }
+        if let Some((gps_coordinates, max_distance_us)) = self.merged_gps {
+            let fix = nearest_gps_fix(gps_coordinates, timestamp, max_distance_us);
+            for value in &gps_field_values(fix) {
+                self.dialect.write_field(writer, false, value)?;
+            }
+            if let Some(home) = self.merged_home {
+                for value in &home_distance_values(fix.map(|fix| home.distance(fix))) {
+                    self.dialect.write_field(writer, false, value)?;
+                }
+            }
+        }
+        if let Some(events) = self.event_markers {
+            let mut names = Vec::new();
+            while let Some(event) = events
+                .get(self.next_event)
+                .filter(|event| event.timestamp_us <= timestamp)
+            {
+                names.push(event.event_name.as_str());
+                self.next_event += 1;
+            }
+            self.dialect.write_field(writer, false, &names.join("; "))?;
+        }
+        self.dialect.end_record(writer)?;
+        self.rows_written += 1;
+        Ok(())
+    }
+}
+
+/// Export GPS data to GPX format
+///
+/// # Arguments
+/// * `input_path` - Path to the input BBL file (used for output naming)
+/// * `log_index` - Index of the current log (0-based)
+/// * `total_logs` - Total number of logs in the file
+/// * `gps_coordinates` - GPS coordinate data to export
+/// * `home_coordinates` - Home coordinates from H frames (used for home waypoint marker)
+/// * `export_options` - Export configuration options
+/// * `log_start_datetime` - Optional log start datetime from header for accurate timestamps
+///
+/// # Features
+/// When home coordinates are available, adds a home position waypoint to the GPX file.
+/// This provides a visual reference point in GPS mapping tools.
+/// Trackpoints carry the satellite count as `<sat>` and, when logged, ground
+/// speed (m/s) and course (degrees) in a Garmin `gpxtpx:TrackPointExtension`.
+/// With a home position, `bbl:homeDistance` and `bbl:homeDistance3D` give the
+/// distance from home in meters ([`BBLLog::home_distances`]).
+///
+/// # Performance Notes
+/// For very large GPS traces, the `log_start_datetime` is parsed via `generate_gpx_timestamp()`
+/// on each trackpoint. Future optimization: consider caching the parsed base epoch once per log
+/// to avoid repeated parsing overhead when exporting thousands of GPS points.
+#[allow(clippy::too_many_arguments)]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "info", skip_all, fields(log_number = log_index + 1))
+)]
+pub fn export_to_gpx(
+    input_path: &Path,
+    log_index: usize,
+    total_logs: usize,
+    gps_coordinates: &[GpsCoordinate],
+    home_coordinates: &[GpsHomeCoordinate],
+    export_options: &ExportOptions,
+    log_start_datetime: Option<&str>,
+    base_name_override: Option<&str>,
+) -> Result<ExportReport> {
+    if gps_coordinates.is_empty() {
+        return Ok(ExportReport::default());
+    }
+
+    // Use compute_export_paths to ensure consistent naming with CSV exports
+    let (_, _, gpx_path, _) = compute_export_paths(
+        input_path,
+        export_options,
+        log_index + 1,
+        total_logs,
+        base_name_override,
+    );
+
+    // Create output directory if it doesn't exist (match export_to_csv behavior)
+    if let Some(parent) = gpx_path.parent() {
+        if !parent.exists() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    let (rows_written, bytes_written) = File::create(&gpx_path)
+        .and_then(|gpx_file| {
+            let mut gpx_file = CountingWriter::new(gpx_file);
+            let points = write_gpx(
+                &mut gpx_file,
+                gps_coordinates,
+                home_coordinates,
+                log_start_datetime,
+                export_options.gps_gap_threshold_us,
+            )?;
+            Ok((points, gpx_file.bytes_written))
+        })
+        .map_err(|e| BBLError::export_failed(gpx_path.clone(), e))?;
+
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        path = %gpx_path.display(),
+        points = rows_written,
+        "exported GPX"
+    );
+
+    Ok(ExportReport {
+        csv_path: None,
+        headers_path: None,
+        gpx_path: Some(gpx_path),
+        event_path: None,
+        rows_written,
+        bytes_written,
+        ..Default::default()
+    })
+}
+
+/// Write a GPX document for `gps_coordinates` to `gpx_file`, returning the
+/// number of trackpoints written
+///
+/// A new track segment starts after a satellite dropout (skipped low-satellite
+/// fixes) or a time gap above `gap_threshold_us`, so mapping tools do not draw
+/// a straight line across the lost fix.
+fn write_gpx<W: Write>(
+    gpx_file: &mut W,
+    gps_coordinates: &[GpsCoordinate],
+    home_coordinates: &[GpsHomeCoordinate],
+    log_start_datetime: Option<&str>,
+    gap_threshold_us: u64,
+) -> std::io::Result<usize> {
+    let mut points = 0;
+    writeln!(gpx_file, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        gpx_file,
+        r#"<gpx creator="BBL Parser (Rust)" version="1.1" xmlns="http://www.topografix.com/GPX/1/1" xmlns:gpxtpx="http://www.garmin.com/xmlschemas/TrackPointExtension/v2" xmlns:bbl="https://github.com/nerdCopter/bbl_parser" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xsi:schemaLocation="http://www.topografix.com/GPX/1/1 http://www.topografix.com/GPX/1/1/gpx.xsd http://www.garmin.com/xmlschemas/TrackPointExtension/v2 https://www8.garmin.com/xmlschemas/TrackPointExtensionv2.xsd">"#
+    )?;
+    writeln!(
+        gpx_file,
+        "<metadata><name>Blackbox flight log</name></metadata>"
+    )?;
+
+    // Add home position waypoint if available
+    if let Some(home) = home_coordinates.first() {
+        writeln!(
+            gpx_file,
+            r#"  <wpt lat="{:.7}" lon="{:.7}">"#,
+            home.home_latitude, home.home_longitude
+        )?;
+        writeln!(gpx_file, r#"    <name>Home</name>"#)?;
+        writeln!(gpx_file, r#"    <sym>Flag</sym>"#)?;
+        writeln!(gpx_file, r#"    <desc>Home Position</desc>"#)?;
+        writeln!(gpx_file, r#"  </wpt>"#)?;
+    }
+
+    writeln!(gpx_file, "<trk><name>Blackbox flight log</name><trkseg>")?;
+    let home = HomeReference::new(gps_coordinates, home_coordinates);
+    let mut previous_timestamp: Option<u64> = None;
+    let mut fix_lost = false;
+
+    for coord in gps_coordinates {
+        if !has_usable_fix(coord) {
+            fix_lost = true;
+            continue;
+        }
+
+        if let Some(previous) = previous_timestamp {
+            let gap = gap_threshold_us > 0
+                && coord.timestamp_us.saturating_sub(previous) > gap_threshold_us;
+            if fix_lost || gap {
+                writeln!(gpx_file, "</trkseg><trkseg>")?;
+            }
+        }
+        fix_lost = false;
+        previous_timestamp = Some(coord.timestamp_us);
+
+        // Generate GPX timestamp from log_start_datetime + frame timestamp
+        // Following blackbox_decode approach: dateTime + (gpsFrameTime / 1000000)
+        let timestamp_str = generate_gpx_timestamp(log_start_datetime, coord.timestamp_us);
+
+        write!(
+            gpx_file,
+            r#"  <trkpt lat="{:.7}" lon="{:.7}"><ele>{:.2}</ele><time>{}</time>"#,
+            coord.latitude, coord.longitude, coord.altitude, timestamp_str
+        )?;
+        if let Some(num_sats) = coord.num_sats {
+            write!(gpx_file, "<sat>{num_sats}</sat>")?;
+        }
+        // Ground speed (m/s) and course (degrees) go in Garmin's TrackPointExtension
+        let track_point_extension = coord.speed.is_some() || coord.ground_course.is_some();
+        let home_distance = home.map(|home| home.distance(coord));
+        if track_point_extension || home_distance.is_some() {
+            write!(gpx_file, "<extensions>")?;
+        }
+        if track_point_extension {
+            write!(gpx_file, "<gpxtpx:TrackPointExtension>")?;
+            if let Some(speed) = coord.speed {
+                write!(gpx_file, "<gpxtpx:speed>{speed:.2}</gpxtpx:speed>")?;
+            }
+            if let Some(course) = coord.ground_course {
+                write!(
+                    gpx_file,
+                    "<gpxtpx:course>{:.1}</gpxtpx:course>",
+                    course.rem_euclid(360.0)
+                )?;
+            }
+            write!(gpx_file, "</gpxtpx:TrackPointExtension>")?;
+        }
+        if let Some(distance) = home_distance {
+            write!(
+                gpx_file,
+                "<bbl:homeDistance>{:.1}</bbl:homeDistance><bbl:homeDistance3D>{:.1}</bbl:homeDistance3D>",
+                distance.distance_m, distance.distance_3d_m
+            )?;
+        }
+        if track_point_extension || home_distance.is_some() {
+            write!(gpx_file, "</extensions>")?;
+        }
+        writeln!(gpx_file, "</trkpt>")?;
+        points += 1;
+    }
+
+    writeln!(gpx_file, "</trkseg></trk>")?;
+    writeln!(gpx_file, "</gpx>")?;
+    Ok(points)
+}
+
+/// Export GPS fixes to a `.gps.csv` file, as blackbox_decode does
+///
+/// Writes `<name>[.NN].gps.csv` next to the flight data CSV with one row per
+/// G frame: `time (us)`, `GPS_numSat`, `GPS_coord[0]` / `GPS_coord[1]`
+/// (degrees), `GPS_altitude` (m), `GPS_speed (m/s)` and `GPS_ground_course`
+/// (degrees). Unlike GPX and KML, low-satellite fixes are kept. With
+/// [`ExportOptions::home_distance`] and a home position from H frames,
+/// `homeDistance (m)` and `homeDistance3D (m)` follow.
+///
+/// # Returns
+/// An `ExportReport` with `gps_csv_path` set, or a default report when there
+/// are no GPS coordinates.
+pub fn export_gps_to_csv(
+    input_path: &Path,
+    log_index: usize,
+    total_logs: usize,
+    gps_coordinates: &[GpsCoordinate],
+    home_coordinates: &[GpsHomeCoordinate],
+    export_options: &ExportOptions,
+    base_name_override: Option<&str>,
+) -> Result<ExportReport> {
+    if gps_coordinates.is_empty() {
+        return Ok(ExportReport::default());
+    }
+
+    let (csv_path, _, _, _) = compute_export_paths(
+        input_path,
+        export_options,
+        log_index + 1,
+        total_logs,
+        base_name_override,
+    );
+    let gps_csv_path = export_options.output_path(&csv_path.with_extension("gps.csv"));
+
+    if let Some(parent) = gps_csv_path.parent() {
+        if !parent.exists() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    let bytes_written = ExportFile::create(&gps_csv_path, export_options.compress)
+        .and_then(|mut writer| {
+            let home = HomeReference::new(gps_coordinates, home_coordinates)
+                .filter(|_| export_options.home_distance);
+            write_gps_csv(&mut writer, gps_coordinates, home)?;
+            writer.finish()
+        })
+        .map_err(|e| BBLError::export_failed(gps_csv_path.clone(), e))?;
+
+    Ok(ExportReport {
+        gps_csv_path: Some(gps_csv_path),
+        rows_written: gps_coordinates.len(),
+        bytes_written,
+        ..Default::default()
+    })
+}
+
+/// Write the GPS CSV header line and one row per fix, with the distance
+/// from `home` when given
+fn write_gps_csv<W: Write>(
+    writer: &mut W,
+    gps_coordinates: &[GpsCoordinate],
+    home: Option<HomeReference>,
+) -> std::io::Result<()> {
+    write!(writer, "time (us), {}", GPS_CSV_FIELDS.join(", "))?;
+    if home.is_some() {
+        write!(writer, ", {}", HOME_DISTANCE_CSV_FIELDS.join(", "))?;
+    }
+    writeln!(writer)?;
+    for coord in gps_coordinates {
+        let values = gps_field_values(Some(coord));
+        write!(writer, "{}, {}", coord.timestamp_us, values.join(", "))?;
+        if let Some(home) = home {
+            let values = home_distance_values(Some(home.distance(coord)));
+            write!(writer, ", {}", values.join(", "))?;
+        }
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+/// GPS columns shared by `.gps.csv` and the merged flight data CSV
+const GPS_CSV_FIELDS: [&str; 6] = [
+    "GPS_numSat",
+    "GPS_coord[0]",
+    "GPS_coord[1]",
+    "GPS_altitude",
+    "GPS_speed (m/s)",
+    "GPS_ground_course",
+];
+
+/// The [`GPS_CSV_FIELDS`] values of `coord`, or empty fields without a fix
+fn gps_field_values(coord: Option<&GpsCoordinate>) -> [String; 6] {
+    let Some(coord) = coord else {
+        return Default::default();
+    };
+    let optional = |value: Option<String>| value.unwrap_or_default();
+    [
+        optional(coord.num_sats.map(|n| n.to_string())),
+        format!("{:.7}", coord.latitude),
+        format!("{:.7}", coord.longitude),
+        format!("{:.2}", coord.altitude),
+        optional(coord.speed.map(|v| format!("{v:.2}"))),
+        optional(coord.ground_course.map(|v| format!("{v:.1}"))),
+    ]
+}
+
+/// Columns written by [`ExportOptions::home_distance`]
+const HOME_DISTANCE_CSV_FIELDS: [&str; 2] = ["homeDistance (m)", "homeDistance3D (m)"];
+
+/// The [`HOME_DISTANCE_CSV_FIELDS`] values of `distance`, or empty fields
+fn home_distance_values(distance: Option<HomeDistance>) -> [String; 2] {
+    distance.map_or_else(Default::default, |distance| {
+        [
+            format!("{:.1}", distance.distance_m),
+            format!("{:.1}", distance.distance_3d_m),
+        ]
+    })
+}
+
+/// The fix closest in time to `timestamp_us` among time-ordered fixes, if it
+/// lies within `max_distance_us` (`0` for no limit)
+fn nearest_gps_fix(
+    gps_coordinates: &[GpsCoordinate],
+    timestamp_us: u64,
+    max_distance_us: u64,
+) -> Option<&GpsCoordinate> {
+    let after = gps_coordinates.partition_point(|coord| coord.timestamp_us <= timestamp_us);
+    let before = after.checked_sub(1).map(|i| &gps_coordinates[i]);
+    let nearest = match (before, gps_coordinates.get(after)) {
+        (Some(a), Some(b)) => {
+            if timestamp_us - a.timestamp_us <= b.timestamp_us - timestamp_us {
+                a
+            } else {
+                b
+            }
+        }
+        (a, b) => a.or(b)?,
+    };
+    (max_distance_us == 0 || nearest.timestamp_us.abs_diff(timestamp_us) <= max_distance_us)
+        .then_some(nearest)
+}
+
+/// Minimum satellite count for a GPS fix to be exported as a track point
+const MIN_TRACK_SATELLITES: i32 = 5;
+
+/// Whether a coordinate has enough satellites to be included in GPX/KML tracks
+/// (coordinates without a satellite count are kept)
+fn has_usable_fix(coord: &GpsCoordinate) -> bool {
+    coord
+        .num_sats
+        .map_or(true, |num_sats| num_sats >= MIN_TRACK_SATELLITES)
+}
+
+/// Export the GPS track to KML for Google Earth
+///
+/// Writes `<name>[.NN].gps.kml` next to the GPX output (see
+/// [`compute_export_paths`]). The track is an absolute-altitude line extruded
+/// to the ground, with a placemark at the home position when H frames provided
+/// one. Coordinates with fewer than 5 satellites are skipped, as in GPX.
+///
+/// # Returns
+/// An `ExportReport` with `kml_path` set, or a default report when there are
+/// no GPS coordinates.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "info", skip_all, fields(log_number = log_index + 1))
+)]
+pub fn export_to_kml(
+    input_path: &Path,
+    log_index: usize,
+    total_logs: usize,
+    gps_coordinates: &[GpsCoordinate],
+    home_coordinates: &[GpsHomeCoordinate],
+    export_options: &ExportOptions,
+    base_name_override: Option<&str>,
+) -> Result<ExportReport> {
+    if gps_coordinates.is_empty() {
+        return Ok(ExportReport::default());
+    }
+
+    let (_, _, gpx_path, _) = compute_export_paths(
+        input_path,
+        export_options,
+        log_index + 1,
+        total_logs,
+        base_name_override,
+    );
+    let kml_path = gpx_path.with_extension("kml");
+
+    if let Some(parent) = kml_path.parent() {
+        if !parent.exists() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    let (rows_written, bytes_written) = File::create(&kml_path)
+        .and_then(|kml_file| {
+            let mut kml_file = CountingWriter::new(BufWriter::new(kml_file));
+            let points = write_kml(&mut kml_file, gps_coordinates, home_coordinates)?;
+            kml_file.flush()?;
+            Ok((points, kml_file.bytes_written))
+        })
+        .map_err(|e| BBLError::export_failed(kml_path.clone(), e))?;
+
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        path = %kml_path.display(),
+        points = rows_written,
+        "exported KML"
+    );
+
+    Ok(ExportReport {
+        kml_path: Some(kml_path),
+        rows_written,
+        bytes_written,
+        ..Default::default()
+    })
+}
+
+/// Write a KML document for `gps_coordinates` to `kml_file`, returning the
+/// number of track points written
+fn write_kml<W: Write>(
+    kml_file: &mut W,
+    gps_coordinates: &[GpsCoordinate],
+    home_coordinates: &[GpsHomeCoordinate],
+) -> std::io::Result<usize> {
+    let mut points = 0;
+    writeln!(kml_file, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(kml_file, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+    writeln!(kml_file, "<Document>")?;
+    writeln!(kml_file, "  <name>Blackbox flight log</name>")?;
+    writeln!(
+        kml_file,
+        r#"  <Style id="track"><LineStyle><color>ff0000ff</color><width>3</width></LineStyle><PolyStyle><color>4d0000ff</color></PolyStyle></Style>"#
+    )?;
+
+    // KML coordinates are longitude,latitude[,altitude]
+    if let Some(home) = home_coordinates.first() {
+        writeln!(kml_file, "  <Placemark>")?;
+        writeln!(kml_file, "    <name>Home</name>")?;
+        writeln!(kml_file, "    <description>Home Position</description>")?;
+        writeln!(
+            kml_file,
+            "    <Point><coordinates>{:.7},{:.7}</coordinates></Point>",
+            home.home_longitude, home.home_latitude
+        )?;
+        writeln!(kml_file, "  </Placemark>")?;
+    }
+
+    writeln!(kml_file, "  <Placemark>")?;
+    writeln!(kml_file, "    <name>Flight track</name>")?;
+    writeln!(kml_file, "    <styleUrl>#track</styleUrl>")?;
+    writeln!(kml_file, "    <LineString>")?;
+    writeln!(kml_file, "      <extrude>1</extrude>")?;
+    writeln!(kml_file, "      <tessellate>1</tessellate>")?;
+    writeln!(kml_file, "      <altitudeMode>absolute</altitudeMode>")?;
+    writeln!(kml_file, "      <coordinates>")?;
+    for coord in gps_coordinates.iter().filter(|coord| has_usable_fix(coord)) {
+        writeln!(
+            kml_file,
+            "        {:.7},{:.7},{:.2}",
+            coord.longitude, coord.latitude, coord.altitude
+        )?;
+        points += 1;
+    }
+    writeln!(kml_file, "      </coordinates>")?;
+    writeln!(kml_file, "    </LineString>")?;
+    writeln!(kml_file, "  </Placemark>")?;
+    writeln!(kml_file, "</Document>")?;
+    writeln!(kml_file, "</kml>")?;
+    Ok(points)
+}
+
+/// Export event data to file
+///
+/// # Returns
+/// An `ExportReport` containing the path to the event file that was created,
+/// or an error if the export failed. Returns `None` for `event_path` if no events were exported.
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "info", skip_all, fields(log_number = log_index + 1))
+)]
+pub fn export_to_event(
+    input_path: &Path,
+    log_index: usize,
+    total_logs: usize,
+    event_frames: &[EventFrame],
+    export_options: &ExportOptions,
+    base_name_override: Option<&str>,
+) -> Result<ExportReport> {
+    if event_frames.is_empty() {
+        return Ok(ExportReport::default());
+    }
+
+    // Use compute_export_paths to ensure consistent naming with CSV exports
+    let (_, _, _, event_path) = compute_export_paths(
+        input_path,
+        export_options,
+        log_index + 1,
+        total_logs,
+        base_name_override,
+    );
+    let event_path = export_options.output_path(&event_path);
+
+    // Create output directory if it doesn't exist (match export_to_csv behavior)
+    if let Some(parent) = event_path.parent() {
+        if !parent.exists() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    let bytes_written = ExportFile::create(&event_path, export_options.compress)
+        .and_then(|mut event_file| {
+            write_events(&mut event_file, event_frames)?;
+            event_file.finish()
+        })
+        .map_err(|e| BBLError::export_failed(event_path.clone(), e))?;
+
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        path = %event_path.display(),
+        events = event_frames.len(),
+        "exported events"
+    );
+
+    Ok(ExportReport {
+        csv_path: None,
+        headers_path: None,
+        gpx_path: None,
+        event_path: Some(event_path),
+        rows_written: event_frames.len(),
+        bytes_written,
+        ..Default::default()
+    })
+}
+
+/// Write events as JSONL (one JSON object per line) to match blackbox_decode
+fn write_events<W: Write>(event_file: &mut W, event_frames: &[EventFrame]) -> std::io::Result<()> {
+    for event in event_frames.iter() {
+        writeln!(
+            event_file,
+            r#"{{"name":"{}", "time":{}}}"#,
+            event.event_name.replace('"', "\\\""),
+            event.timestamp_us
+        )?;
+    }
+    Ok(())
+}
+
+/// Rows per Arrow record batch (and so at most per row group) in Parquet output
+#[cfg(feature = "parquet")]
+const PARQUET_BATCH_ROWS: usize = 64 * 1024;
+
+/// Export flight data to a Parquet file (`parquet` feature)
+///
+/// Writes `<name>[.NN].parquet` next to the CSV outputs (see
+/// [`compute_export_paths`]). See [`export_parquet_to_writer`] for the columns.
+#[cfg(feature = "parquet")]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "info", skip_all, fields(log_number = log.log_number))
+)]
+pub fn export_to_parquet(
+    log: &BBLLog,
+    input_path: &Path,
+    export_options: &ExportOptions,
+    base_name_override: Option<&str>,
+) -> Result<ExportReport> {
+    let (csv_path, _, _, _) = compute_export_paths(
+        input_path,
+        export_options,
+        log.log_number,
+        log.total_logs,
+        base_name_override,
+    );
+    if let Some(parent) = csv_path.parent() {
+        if !parent.exists() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+
+    let parquet_path = csv_path.with_extension("parquet");
+    let report = File::create(&parquet_path)
+        .map_err(BBLError::Io)
+        .and_then(|file| export_parquet_to_writer(log, BufWriter::new(file), export_options))
+        .map_err(|e| BBLError::export_failed(parquet_path.clone(), e))?;
+
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        path = %parquet_path.display(),
+        rows = report.rows_written,
+        "exported Parquet"
+    );
+
+    Ok(ExportReport {
+        parquet_path: Some(parquet_path),
+        ..report
+    })
+}
+
+/// Write the flight data of `log` as Parquet to any writer (`parquet` feature)
+///
+/// Columns follow [`BBLLog::arrow_schema`], including its unit metadata. Rows
+/// follow `export_options.preserve_order`; gap filling and percent columns are
+/// CSV-only. Data is Snappy-compressed.
+#[cfg(feature = "parquet")]
+pub fn export_parquet_to_writer<W: Write + Send>(
+    log: &BBLLog,
+    writer: W,
+    export_options: &ExportOptions,
+) -> Result<ExportReport> {
+    Ok(write_parquet(log, writer, export_options)?)
+}
+
+#[cfg(feature = "parquet")]
+fn write_parquet<W: Write + Send>(
+    log: &BBLLog,
+    writer: W,
+    export_options: &ExportOptions,
+) -> anyhow::Result<ExportReport> {
+    use parquet::arrow::ArrowWriter;
+    use parquet::basic::Compression;
+    use parquet::file::properties::WriterProperties;
+
+    let frames = &log.frames;
+    let mut rows: Vec<usize> = (0..frames.len()).collect();
+    if !export_options.preserve_order {
+        rows.sort_by_key(|&row| frames.timestamps()[row]);
+    }
+
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(
+        CountingWriter::new(writer),
+        log.arrow_schema(),
+        Some(properties),
+    )?;
+    for batch in log.record_batches(&rows, PARQUET_BATCH_ROWS) {
+        writer.write(&batch?)?;
+    }
+
+    let mut writer = writer.into_inner()?;
+    writer.flush()?;
+    Ok(ExportReport {
+        rows_written: rows.len(),
+        bytes_written: writer.bytes_written,
+        ..Default::default()
+    })
+}
+
+/// Write one frame as a JSON object line, fields sorted by name
+fn write_ndjson_frame<W: Write>(writer: &mut W, frame: &DecodedFrame) -> std::io::Result<()> {
+    write!(
+        writer,
+        r#"{{"type":"{}","time":{},"loopIteration":{}"#,
+        frame.frame_type, frame.timestamp_us, frame.loop_iteration
+    )?;
+    let mut fields: Vec<(&String, &i32)> = frame
+        .data
+        .iter()
+        .filter(|(name, _)| !matches!(name.as_str(), "time" | "loopIteration"))
+        .collect();
+    fields.sort_unstable_by(|a, b| a.0.cmp(b.0));
+    for (name, value) in fields {
+        write!(writer, r#","{}":{}"#, escape_json(name), value)?;
+    }
+    writeln!(writer, "}}")
+}
+
+/// Escape a string for use inside JSON double quotes
+fn escape_json(value: &str) -> std::borrow::Cow<'_, str> {
+    if !value
+        .chars()
+        .any(|c| c == '"' || c == '\\' || c.is_control())
+    {
+        return value.into();
+    }
+    let mut escaped = String::with_capacity(value.len() + 2);
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+    use std::io::Read;
+    use tempfile::TempDir;
+
+    /// Test helper to create a minimal GPX export and read back the content
+    fn export_gpx_and_read(
+        gps_coords: &[GpsCoordinate],
+        home_coords: &[GpsHomeCoordinate],
+    ) -> Result<String> {
+        let temp_dir = TempDir::new()?;
+        let temp_input_path = temp_dir.path().join("test_input.bbl");
+
+        let export_opts = ExportOptions {
+            csv: false,
+            gpx: true,
+            event: false,
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            force_export: false,
+            ..Default::default()
+        };
+
+        export_to_gpx(
+            &temp_input_path,
+            0,
+            1,
+            gps_coords,
+            home_coords,
+            &export_opts,
+            None,
+            None,
+        )?;
+
+        // Read back the generated GPX file
+        let gpx_path = temp_dir.path().join("test_input.gps.gpx");
+        let mut gpx_content = String::new();
+        let mut gpx_file = File::open(&gpx_path)?;
+        gpx_file.read_to_string(&mut gpx_content)?;
+
+        Ok(gpx_content)
+    }
+
+    #[test]
+    fn test_gpx_home_waypoint_with_coordinates() -> Result<()> {
+        let home_coords = vec![GpsHomeCoordinate {
+            home_latitude: 40.7128,
+            home_longitude: -74.0060,
+            timestamp_us: 0,
+        }];
+
+        let gps_coords = vec![GpsCoordinate {
+            latitude: 40.7129,
+            longitude: -74.0061,
+            altitude: 100.0,
+            timestamp_us: 1_000_000,
+            num_sats: Some(10),
+            speed: Some(5.0),
+            ground_course: Some(180.0),
+        }];
+
+        let content = export_gpx_and_read(&gps_coords, &home_coords)?;
+
+        // Verify home waypoint element exists
+        assert!(
+            content.contains(r#"<wpt lat="40.7128000" lon="-74.0060000">"#),
+            "Home waypoint coordinates should be formatted to 7 decimal places"
+        );
+
+        // Verify home waypoint has correct structure
+        assert!(
+            content.contains("<name>Home</name>"),
+            "Home waypoint should have <name>Home</name>"
+        );
+        assert!(
+            content.contains("<sym>Flag</sym>"),
+            "Home waypoint should have <sym>Flag</sym>"
+        );
+        assert!(
+            content.contains("<desc>Home Position</desc>"),
+            "Home waypoint should have <desc>Home Position</desc>"
+        );
+
+        // Verify closing tag
+        assert!(
+            content.contains("</wpt>"),
+            "Home waypoint should have closing tag"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_gpx_home_waypoint_precision() -> Result<()> {
+        let home_coords = vec![GpsHomeCoordinate {
+            home_latitude: 51.5074123456789,
+            home_longitude: -0.1278123456789,
+            timestamp_us: 0,
+        }];
+
+        let gps_coords = vec![GpsCoordinate {
+            latitude: 51.5075,
+            longitude: -0.1280,
+            altitude: 50.0,
+            timestamp_us: 1_000_000,
+            num_sats: Some(5),
+            speed: None,
+            ground_course: None,
+        }];
+
+        let content = export_gpx_and_read(&gps_coords, &home_coords)?;
+
+        // Verify coordinates are truncated/rounded to 7 decimal places
+        assert!(
+            content.contains(r#"lat="51.5074123""#),
+            "Latitude should be formatted to 7 decimal places (truncated)"
+        );
+        assert!(
+            content.contains(r#"lon="-0.1278123""#),
+            "Longitude should be formatted to 7 decimal places (truncated)"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_gpx_no_home_waypoint_when_empty() -> Result<()> {
+        let home_coords = vec![];
+
+        let gps_coords = vec![GpsCoordinate {
+            latitude: 40.7129,
+            longitude: -74.0061,
+            altitude: 100.0,
+            timestamp_us: 1_000_000,
+            num_sats: Some(10),
+            speed: Some(5.0),
+            ground_course: Some(180.0),
+        }];
+
+        let content = export_gpx_and_read(&gps_coords, &home_coords)?;
+
+        // Verify no home waypoint appears when home_coordinates is empty
+        assert!(
+            !content.contains("<name>Home</name>"),
+            "No home waypoint should be present when home_coordinates is empty"
+        );
+        assert!(
+            !content.contains("<wpt"),
+            "No waypoint element should be present when home_coordinates is empty"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_gpx_home_waypoint_xml_structure() -> Result<()> {
+        let home_coords = vec![GpsHomeCoordinate {
+            home_latitude: 35.6762,
+            home_longitude: 139.6503,
+            timestamp_us: 0,
+        }];
+
+        let gps_coords = vec![GpsCoordinate {
+            latitude: 35.6763,
+            longitude: 139.6504,
+            altitude: 25.0,
+            timestamp_us: 1_000_000,
+            num_sats: Some(8),
+            speed: Some(2.0),
+            ground_course: Some(45.0),
+        }];
+
+        let content = export_gpx_and_read(&gps_coords, &home_coords)?;
+
+        // Verify basic GPX structure
+        assert!(
+            content.contains(r#"<?xml version="1.0" encoding="UTF-8"?>"#),
+            "XML declaration should be present"
+        );
+        assert!(
+            content.contains("version=\"1.1\""),
+            "GPX version should be 1.1"
+        );
+        assert!(
+            content.contains("xmlns=\"http://www.topografix.com/GPX/1/1\""),
+            "GPX namespace should be present"
+        );
+
+        // Verify home waypoint appears before track element
+        let wpt_index = content.find("<wpt").expect("Should have <wpt element");
+        let trk_index = content.find("<trk").expect("Should have <trk element");
+        assert!(
+            wpt_index < trk_index,
+            "Home waypoint should appear before track element in GPX file"
+        );
+
+        // Verify proper nesting and indentation
+        assert!(
+            content.contains("  <wpt"),
+            "Home waypoint should be properly indented"
+        );
+        assert!(
+            content.contains("    <name>Home</name>"),
+            "Home waypoint child elements should be properly indented"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_gpx_home_waypoint_with_negative_coordinates() -> Result<()> {
+        let home_coords = vec![GpsHomeCoordinate {
+            home_latitude: -33.8688,
+            home_longitude: 151.2093,
+            timestamp_us: 0,
+        }];
+
+        let gps_coords = vec![GpsCoordinate {
+            latitude: -33.8689,
+            longitude: 151.2094,
+            altitude: 150.0,
+            timestamp_us: 1_000_000,
+            num_sats: Some(12),
+            speed: Some(10.0),
+            ground_course: Some(270.0),
+        }];
+
+        let content = export_gpx_and_read(&gps_coords, &home_coords)?;
+
+        // Verify negative coordinates are correctly formatted
+        assert!(
+            content.contains(r#"lat="-33.8688000""#),
+            "Negative latitude should be correctly formatted"
+        );
+        assert!(
+            content.contains(r#"lon="151.2093000""#),
+            "Positive longitude should be correctly formatted"
+        );
+
+        // Verify structure is still correct with negative values
+        assert!(
+            content.contains("<name>Home</name>"),
+            "Structure should be correct"
+        );
+        assert!(content.contains("</wpt>"), "Closing tag should be present");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_gpx_only_first_home_coordinate_used() -> Result<()> {
+        let home_coords = vec![
+            GpsHomeCoordinate {
+                home_latitude: 40.7128,
+                home_longitude: -74.0060,
+                timestamp_us: 0,
+            },
+            GpsHomeCoordinate {
+                home_latitude: 51.5074,
+                home_longitude: -0.1278,
+                timestamp_us: 1_000_000,
+            },
+        ];
+
+        let gps_coords = vec![GpsCoordinate {
+            latitude: 40.7129,
+            longitude: -74.0061,
+            altitude: 100.0,
+            timestamp_us: 1_000_000,
+            num_sats: Some(10),
+            speed: Some(5.0),
+            ground_course: Some(180.0),
+        }];
+
+        let content = export_gpx_and_read(&gps_coords, &home_coords)?;
+
+        // Verify only first coordinate is used
+        assert!(
+            content.contains(r#"lat="40.7128000""#),
+            "First home coordinate should be used"
+        );
+        // Count occurrences of <wpt to ensure only one home waypoint
+        let wpt_count = content.matches("<wpt").count();
+        assert_eq!(
+            wpt_count, 1,
+            "Only one home waypoint should be present when multiple home_coordinates exist"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_gpx_empty_gps_coordinates_returns_ok() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let temp_input_path = temp_dir.path().join("test_input.bbl");
+
+        let export_opts = ExportOptions {
+            csv: false,
+            gpx: true,
+            event: false,
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            force_export: false,
+            ..Default::default()
+        };
+
+        let home_coords = vec![GpsHomeCoordinate {
+            home_latitude: 40.7128,
+            home_longitude: -74.0060,
+            timestamp_us: 0,
+        }];
+
+        // Should return Ok even with empty GPS coordinates
+        let result = export_to_gpx(
+            &temp_input_path,
+            0,
+            1,
+            &[],
+            &home_coords,
+            &export_opts,
+            None,
+            None,
+        );
+        assert!(
+            result.is_ok(),
+            "Export should succeed with empty GPS coordinates"
+        );
+
+        // Verify no GPX file is created when GPS coordinates are empty
+        let gpx_path = temp_dir.path().join("test_input.gps.gpx");
+        assert!(
+            !gpx_path.exists(),
+            "No GPX file should be created when GPS coordinates are empty"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_gpx_trackpoints_skip_low_satellite_count() -> Result<()> {
+        let home_coords = vec![];
+
+        let gps_coords = vec![
+            GpsCoordinate {
+                latitude: 40.7129,
+                longitude: -74.0061,
+                altitude: 100.0,
+                timestamp_us: 1_000_000,
+                num_sats: Some(3), // Below minimum of 5
+                speed: Some(5.0),
+                ground_course: Some(180.0),
+            },
+            GpsCoordinate {
+                latitude: 40.7130,
+                longitude: -74.0062,
+                altitude: 105.0,
+                timestamp_us: 2_000_000,
+                num_sats: Some(10), // Valid
+                speed: Some(5.0),
+                ground_course: Some(180.0),
+            },
+        ];
+
+        let content = export_gpx_and_read(&gps_coords, &home_coords)?;
+
+        // Verify that the trackpoint with low satellite count is not in output
+        assert!(
+            !content.contains("40.7129"),
+            "Trackpoint with low satellite count should be excluded"
+        );
+
+        // Verify that the valid trackpoint is in output
+        assert!(
+            content.contains("40.7130"),
+            "Trackpoint with sufficient satellites should be included"
+        );
+
+        Ok(())
     }
 
-    Ok(ExportReport {
-        csv_path: None,
-        headers_path: None,
-        gpx_path: None,
-        event_path: Some(event_path),
-    })
-}
+    #[test]
+    fn test_gpx_trackpoint_extensions() -> Result<()> {
+        let gps_coords = vec![
+            GpsCoordinate {
+                latitude: 40.7129,
+                longitude: -74.0061,
+                altitude: 100.0,
+                timestamp_us: 1_000_000,
+                num_sats: Some(9),
+                speed: Some(12.345),
+                ground_course: Some(-90.0),
+            },
+            GpsCoordinate {
+                latitude: 40.713,
+                longitude: -74.0062,
+                altitude: 101.0,
+                timestamp_us: 2_000_000,
+                num_sats: None,
+                speed: None,
+                ground_course: None,
+            },
+        ];
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::io::Read;
-    use tempfile::TempDir;
+        let content = export_gpx_and_read(&gps_coords, &[])?;
+        assert!(content
+            .contains(r#"xmlns:gpxtpx="http://www.garmin.com/xmlschemas/TrackPointExtension/v2""#));
+        assert!(content.contains(
+            "<sat>9</sat><extensions><gpxtpx:TrackPointExtension>\
+             <gpxtpx:speed>12.35</gpxtpx:speed><gpxtpx:course>270.0</gpxtpx:course>\
+             </gpxtpx:TrackPointExtension></extensions></trkpt>"
+        ));
+        // Points without the optional values stay plain
+        assert!(content.contains("<ele>101.00</ele><time>"));
+        assert_eq!(content.matches("<extensions>").count(), 1);
+
+        // A home position adds the distance from home to every point
+        let home = GpsHomeCoordinate {
+            home_latitude: 40.7129,
+            home_longitude: -74.0061,
+            timestamp_us: 0,
+        };
+        let content = export_gpx_and_read(&gps_coords, &[home])?;
+        assert!(content.contains(
+            "</gpxtpx:TrackPointExtension><bbl:homeDistance>0.0</bbl:homeDistance>\
+             <bbl:homeDistance3D>0.0</bbl:homeDistance3D></extensions>"
+        ));
+        assert!(
+            content.contains(
+                "<ele>101.00</ele><time>1970-01-01T00:00:02.000000Z</time><extensions>\
+             <bbl:homeDistance>14.0</bbl:homeDistance><bbl:homeDistance3D>14.0</bbl:homeDistance3D>\
+             </extensions>"
+            ),
+            "{content}"
+        );
+        Ok(())
+    }
 
-    /// Test helper to create a minimal GPX export and read back the content
-    fn export_gpx_and_read(
-        gps_coords: &[GpsCoordinate],
-        home_coords: &[GpsHomeCoordinate],
-    ) -> Result<String> {
-        let temp_dir = TempDir::new()?;
-        let temp_input_path = temp_dir.path().join("test_input.bbl");
+    #[test]
+    fn test_gps_csv_home_distance() -> Result<()> {
+        let fix = |timestamp_us, latitude, altitude| GpsCoordinate {
+            latitude,
+            longitude: 8.5,
+            altitude,
+            timestamp_us,
+            num_sats: Some(12),
+            speed: None,
+            ground_course: None,
+        };
+        let fixes = [fix(1000, 47.0, 400.0), fix(2000, 47.001, 430.0)];
+        let home = [GpsHomeCoordinate {
+            home_latitude: 47.0,
+            home_longitude: 8.5,
+            timestamp_us: 500,
+        }];
+        let mut csv = Vec::new();
+        write_gps_csv(&mut csv, &fixes, HomeReference::new(&fixes, &home))?;
+        let content = String::from_utf8(csv)?;
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines[0].ends_with("GPS_ground_course, homeDistance (m), homeDistance3D (m)"));
+        assert!(lines[1].ends_with(", , 0.0, 0.0"), "{}", lines[1]);
+        assert!(lines[2].ends_with(", , 111.2, 115.2"), "{}", lines[2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_gpx_segments_split_on_gaps_and_dropouts() -> Result<()> {
+        let fix = |timestamp_us: u64, num_sats: i32| GpsCoordinate {
+            latitude: 40.7,
+            longitude: -74.0,
+            altitude: 100.0,
+            timestamp_us,
+            num_sats: Some(num_sats),
+            speed: None,
+            ground_course: None,
+        };
+        let gps_coords = vec![
+            fix(1_000_000, 8),
+            fix(1_200_000, 8),
+            // Satellite dropout
+            fix(1_400_000, 3),
+            fix(1_600_000, 8),
+            // Time gap above the default 2 s threshold
+            fix(5_000_000, 8),
+            fix(5_200_000, 8),
+        ];
+
+        let content = export_gpx_and_read(&gps_coords, &[])?;
+        assert_eq!(content.matches("<trkseg>").count(), 3);
+        assert_eq!(content.matches("</trkseg>").count(), 3);
+        assert_eq!(content.matches("<trkpt ").count(), 5);
 
+        // A zero threshold only splits on dropouts
+        let mut gpx = Vec::new();
+        write_gpx(&mut gpx, &gps_coords, &[], None, 0)?;
+        assert_eq!(String::from_utf8(gpx)?.matches("<trkseg>").count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_gps_csv_export() -> Result<()> {
+        let temp_dir = TempDir::new()?;
         let export_opts = ExportOptions {
-            csv: false,
-            gpx: true,
-            event: false,
+            gps_csv: true,
             output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
-            force_export: false,
+            ..Default::default()
         };
+        let gps_coords = vec![
+            GpsCoordinate {
+                latitude: 40.7129,
+                longitude: -74.0061,
+                altitude: 100.0,
+                timestamp_us: 1_000_000,
+                num_sats: Some(3),
+                speed: Some(4.5),
+                ground_course: Some(180.0),
+            },
+            GpsCoordinate {
+                latitude: 40.713,
+                longitude: -74.0062,
+                altitude: 101.25,
+                timestamp_us: 1_200_000,
+                num_sats: None,
+                speed: None,
+                ground_course: None,
+            },
+        ];
 
-        export_to_gpx(
-            &temp_input_path,
-            0,
+        let report = export_gps_to_csv(
+            Path::new("flight.bbl"),
             1,
-            gps_coords,
-            home_coords,
+            2,
+            &gps_coords,
+            &[],
             &export_opts,
             None,
-            None,
         )?;
+        let path = report.gps_csv_path.unwrap();
+        assert_eq!(path, temp_dir.path().join("flight.02.gps.csv"));
+        assert_eq!(report.rows_written, 2);
 
-        // Read back the generated GPX file
-        let gpx_path = temp_dir.path().join("test_input.gps.gpx");
-        let mut gpx_content = String::new();
-        let mut gpx_file = File::open(&gpx_path)?;
-        gpx_file.read_to_string(&mut gpx_content)?;
-
-        Ok(gpx_content)
+        let content = std::fs::read_to_string(&path)?;
+        assert_eq!(report.bytes_written, content.len() as u64);
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "time (us), GPS_numSat, GPS_coord[0], GPS_coord[1], GPS_altitude, GPS_speed (m/s), GPS_ground_course",
+                "1000000, 3, 40.7129000, -74.0061000, 100.00, 4.50, 180.0",
+                "1200000, , 40.7130000, -74.0062000, 101.25, , ",
+            ]
+        );
+        Ok(())
     }
 
     #[test]
-    fn test_gpx_home_waypoint_with_coordinates() -> Result<()> {
+    fn test_kml_export_track_and_home() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let export_opts = ExportOptions {
+            kml: true,
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            ..Default::default()
+        };
         let home_coords = vec![GpsHomeCoordinate {
             home_latitude: 40.7128,
-            home_longitude: -74.0060,
+            home_longitude: -74.006,
             timestamp_us: 0,
         }];
+        let gps_coords = vec![
+            GpsCoordinate {
+                latitude: 40.7129,
+                longitude: -74.0061,
+                altitude: 100.0,
+                timestamp_us: 1_000_000,
+                num_sats: Some(3),
+                speed: None,
+                ground_course: None,
+            },
+            GpsCoordinate {
+                latitude: 40.713,
+                longitude: -74.0062,
+                altitude: 105.5,
+                timestamp_us: 2_000_000,
+                num_sats: Some(10),
+                speed: None,
+                ground_course: None,
+            },
+        ];
 
-        let gps_coords = vec![GpsCoordinate {
-            latitude: 40.7129,
-            longitude: -74.0061,
-            altitude: 100.0,
-            timestamp_us: 1_000_000,
-            num_sats: Some(10),
-            speed: Some(5.0),
-            ground_course: Some(180.0),
+        let report = export_to_kml(
+            Path::new("flight.bbl"),
+            0,
+            1,
+            &gps_coords,
+            &home_coords,
+            &export_opts,
+            None,
+        )?;
+        let kml_path = report.kml_path.unwrap();
+        assert_eq!(kml_path, temp_dir.path().join("flight.gps.kml"));
+        assert_eq!(report.rows_written, 1);
+
+        let content = std::fs::read_to_string(&kml_path)?;
+        assert_eq!(report.bytes_written, content.len() as u64);
+        assert!(
+            content.contains("<Point><coordinates>-74.0060000,40.7128000</coordinates></Point>")
+        );
+        assert!(content.contains("<extrude>1</extrude>"));
+        assert!(content.contains("<altitudeMode>absolute</altitudeMode>"));
+        assert!(content.contains("-74.0062000,40.7130000,105.50"));
+        assert!(!content.contains("40.7129000"));
+
+        // No GPS data means no file
+        let report = export_to_kml(
+            Path::new("empty.bbl"),
+            0,
+            1,
+            &[],
+            &home_coords,
+            &export_opts,
+            None,
+        )?;
+        assert!(report.kml_path.is_none());
+        assert!(!temp_dir.path().join("empty.gps.kml").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_event_export_failure_is_typed() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let temp_input_path = temp_dir.path().join("test_input.bbl");
+        // A directory where the event file should go makes the export fail
+        std::fs::create_dir(temp_dir.path().join("test_input.event"))?;
+
+        let export_opts = ExportOptions {
+            event: true,
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        let events = [EventFrame {
+            timestamp_us: 0,
+            event_type: 0,
+            event_data: Vec::new(),
+            event_name: "Sync beep".to_string(),
         }];
 
-        let content = export_gpx_and_read(&gps_coords, &home_coords)?;
+        let err = export_to_event(&temp_input_path, 0, 1, &events, &export_opts, None).unwrap_err();
+        match err {
+            BBLError::ExportFailed { path, source } => {
+                assert!(path.ends_with("test_input.event"));
+                assert!(matches!(*source, BBLError::Io(_)));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+        Ok(())
+    }
 
-        // Verify home waypoint element exists
-        assert!(
-            content.contains(r#"<wpt lat="40.7128000" lon="-74.0060000">"#),
-            "Home waypoint coordinates should be formatted to 7 decimal places"
+    #[test]
+    fn test_firmware_prefix_for_revision() {
+        assert_eq!(
+            firmware_prefix_for_revision("EmuFlight 0.4.3"),
+            Some("EMUF_")
+        );
+        assert_eq!(
+            firmware_prefix_for_revision("EMUFLIGHT 0.4.3"),
+            Some("EMUF_")
+        );
+        assert_eq!(
+            firmware_prefix_for_revision("Betaflight 4.5.0"),
+            Some("BTFL_")
+        );
+        assert_eq!(
+            firmware_prefix_for_revision("Betaflight 2025.12.0-beta (abc) STM32H743"),
+            Some("BTFL_")
+        );
+        assert_eq!(firmware_prefix_for_revision("INAV 7.1.2"), Some("INAV_"));
+        assert_eq!(firmware_prefix_for_revision(""), None);
+        assert_eq!(firmware_prefix_for_revision("Unknown firmware"), None);
+    }
+
+    #[test]
+    fn test_corrected_session_base_name_matching_vendor() {
+        let path = std::path::Path::new("/logs/EMUF_BLACKBOX_LOG_QUAD_20260531.BBL");
+        assert_eq!(corrected_session_base_name(path, "EmuFlight 0.4.3"), None);
+
+        let path = std::path::Path::new("/logs/BTFL_BLACKBOX_LOG_QUAD_20260531.BBL");
+        assert_eq!(corrected_session_base_name(path, "Betaflight 4.5.0"), None);
+    }
+
+    #[test]
+    fn test_corrected_session_base_name_mismatched_vendor() {
+        let path = std::path::Path::new("/logs/EMUF_BLACKBOX_LOG_QUAD_20260531.BBL");
+        assert_eq!(
+            corrected_session_base_name(path, "Betaflight 2025.12.0-beta"),
+            Some("BTFL_BLACKBOX_LOG_QUAD_20260531".to_string())
+        );
+
+        let path = std::path::Path::new("/logs/BTFL_BLACKBOX_LOG_QUAD_20260531.BBL");
+        assert_eq!(
+            corrected_session_base_name(path, "EmuFlight 0.4.3"),
+            Some("EMUF_BLACKBOX_LOG_QUAD_20260531".to_string())
+        );
+    }
+
+    #[test]
+    fn test_corrected_session_base_name_unknown_prefix() {
+        // BBL without a known prefix — no correction regardless of firmware
+        let path = std::path::Path::new("/logs/BLACKBOX_LOG_QUAD_20260531.BBL");
+        assert_eq!(corrected_session_base_name(path, "EmuFlight 0.4.3"), None);
+        assert_eq!(corrected_session_base_name(path, "Betaflight 4.5.0"), None);
+    }
+
+    #[test]
+    fn test_corrected_session_base_name_fork_exemption() {
+        // Quicksilver (QUIC_) intentionally writes Betaflight revision headers — must NOT rename
+        let path = std::path::Path::new("/logs/QUIC_Twiglet_2026-06-09_file_0.bfl");
+        assert_eq!(
+            corrected_session_base_name(path, "Betaflight 4.3.0"),
+            None,
+            "QUIC_ files with Betaflight revision must not be renamed to BTFL_"
+        );
+        // Sanity: an actual mismatch on a non-fork prefix still renames
+        let path = std::path::Path::new("/logs/EMUF_BLACKBOX_LOG_20260531.BBL");
+        assert_eq!(
+            corrected_session_base_name(path, "Betaflight 4.3.0"),
+            Some("BTFL_BLACKBOX_LOG_20260531".to_string())
         );
+    }
+
+    /// Test helper building a log whose main frames have the given (loopIteration, time) pairs
+    fn log_with_main_frames(samples: &[(u32, u64)]) -> BBLLog {
+        let mut log = BBLLog::new(1, 1);
+        log.header.i_frame_def = crate::types::FrameDefinition::from_field_names(vec![
+            "loopIteration".to_string(),
+            "time".to_string(),
+        ]);
+        for &(loop_iteration, timestamp_us) in samples {
+            log.push_main_frame(
+                timestamp_us,
+                loop_iteration,
+                [
+                    ("loopIteration", loop_iteration as i32),
+                    ("time", timestamp_us as i32),
+                ],
+            );
+        }
+        log
+    }
+
+    /// Test helper exporting a log to CSV and returning the loopIteration column
+    fn export_csv_loop_column(log: &BBLLog, export_opts: ExportOptions) -> Result<Vec<String>> {
+        let temp_dir = TempDir::new()?;
+        let export_opts = ExportOptions {
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            ..export_opts
+        };
+        let report = export_to_csv(log, Path::new("test_input.bbl"), &export_opts, None)?;
+        let content = std::fs::read_to_string(report.csv_path.unwrap())?;
+        let headers_len = std::fs::metadata(report.headers_path.unwrap())?.len();
+        assert_eq!(report.rows_written, content.lines().count() - 1);
+        assert_eq!(report.bytes_written, content.len() as u64 + headers_len);
+        Ok(content
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').next().unwrap().trim().to_string())
+            .collect())
+    }
+
+    #[test]
+    fn test_export_registered() -> Result<()> {
+        struct RowCountExporter;
+
+        impl Exporter for RowCountExporter {
+            fn name(&self) -> &str {
+                "rows"
+            }
+
+            fn extension(&self) -> &str {
+                "rows.txt"
+            }
 
-        // Verify home waypoint has correct structure
-        assert!(
-            content.contains("<name>Home</name>"),
-            "Home waypoint should have <name>Home</name>"
-        );
-        assert!(
-            content.contains("<sym>Flag</sym>"),
-            "Home waypoint should have <sym>Flag</sym>"
-        );
-        assert!(
-            content.contains("<desc>Home Position</desc>"),
-            "Home waypoint should have <desc>Home Position</desc>"
-        );
+            fn export(&self, log: &BBLLog, sink: &mut dyn Write) -> crate::Result<ExportReport> {
+                writeln!(sink, "{}", log.frames.len())?;
+                Ok(ExportReport {
+                    rows_written: 1,
+                    bytes_written: 2,
+                    ..Default::default()
+                })
+            }
+        }
 
-        // Verify closing tag
-        assert!(
-            content.contains("</wpt>"),
-            "Home waypoint should have closing tag"
+        let temp_dir = TempDir::new()?;
+        let log = log_with_main_frames(&[(0, 1000), (1, 1250), (2, 1500)]);
+        let export_opts = ExportOptions {
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            exporters: vec![Arc::new(RowCountExporter), Arc::new(CsvExporter::default())],
+            ..Default::default()
+        };
+
+        let report = export_registered(&log, Path::new("flight.bbl"), &export_opts, None)?;
+        assert_eq!(
+            report.extra_paths,
+            vec![
+                temp_dir.path().join("flight.rows.txt"),
+                temp_dir.path().join("flight.csv")
+            ]
         );
+        assert_eq!(std::fs::read_to_string(&report.extra_paths[0])?, "3\n");
+        assert_eq!(report.rows_written, 1 + 3);
 
+        // The CSV exporter writes exactly what export_to_csv writes
+        let csv = std::fs::read(&report.extra_paths[1])?;
+        let csv_report = export_to_csv(&log, Path::new("flight.bbl"), &export_opts, None)?;
+        assert_eq!(csv, std::fs::read(csv_report.csv_path.unwrap())?);
         Ok(())
     }
 
     #[test]
-    fn test_gpx_home_waypoint_precision() -> Result<()> {
-        let home_coords = vec![GpsHomeCoordinate {
-            home_latitude: 51.5074123456789,
-            home_longitude: -0.1278123456789,
-            timestamp_us: 0,
-        }];
-
-        let gps_coords = vec![GpsCoordinate {
-            latitude: 51.5075,
-            longitude: -0.1280,
-            altitude: 50.0,
-            timestamp_us: 1_000_000,
-            num_sats: Some(5),
+    fn test_writer_exports_match_files() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut log = log_with_main_frames(&[(0, 1000), (1, 1250)]);
+        log.gps_coordinates.push(GpsCoordinate {
+            latitude: 40.7128,
+            longitude: -74.006,
+            altitude: 10.0,
+            timestamp_us: 1000,
+            num_sats: Some(8),
             speed: None,
             ground_course: None,
-        }];
+        });
+        log.event_frames.push(EventFrame {
+            timestamp_us: 1250,
+            event_type: 0,
+            event_data: Vec::new(),
+            event_name: "Sync beep".to_string(),
+        });
+        let export_opts = ExportOptions {
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        let input = Path::new("flight.bbl");
+
+        let mut csv = Vec::new();
+        let report = export_csv_to_writer(&log, &mut csv, &export_opts)?;
+        let files = export_to_csv(&log, input, &export_opts, None)?;
+        assert_eq!(csv, std::fs::read(files.csv_path.unwrap())?);
+        assert_eq!(report.rows_written, 2);
+        assert_eq!(report.bytes_written, csv.len() as u64);
+
+        let mut headers = Vec::new();
+        export_headers_to_writer(&log, &mut headers)?;
+        assert_eq!(headers, std::fs::read(files.headers_path.unwrap())?);
+
+        let mut gpx = Vec::new();
+        let report = export_gpx_to_writer(&log, &mut gpx)?;
+        let file = export_to_gpx(
+            input,
+            0,
+            1,
+            &log.gps_coordinates,
+            &log.home_coordinates,
+            &export_opts,
+            None,
+            None,
+        )?;
+        assert_eq!(gpx, std::fs::read(file.gpx_path.unwrap())?);
+        assert_eq!(report.rows_written, 1);
 
-        let content = export_gpx_and_read(&gps_coords, &home_coords)?;
+        let mut events = Vec::new();
+        export_events_to_writer(&log, &mut events)?;
+        let file = export_to_event(input, 0, 1, &log.event_frames, &export_opts, None)?;
+        assert_eq!(events, std::fs::read(file.event_path.unwrap())?);
+        Ok(())
+    }
 
-        // Verify coordinates are truncated/rounded to 7 decimal places
-        assert!(
-            content.contains(r#"lat="51.5074123""#),
-            "Latitude should be formatted to 7 decimal places (truncated)"
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_parquet_export_types() -> Result<()> {
+        use arrow_array::cast::AsArray;
+        use arrow_array::types::{Float32Type, Int32Type, UInt64Type};
+        use arrow_array::Array;
+        use arrow_schema::DataType;
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let temp_dir = TempDir::new()?;
+        let mut log = log_with_main_frames(&[(0, 1000), (1, 1250)]);
+        log.header.firmware_revision = "Betaflight 4.5.1 (77d01ba3b) AT32F435M".to_string();
+        let gyro = log.frames.add_field("gyroADC[0]");
+        let vbat = log.frames.add_field("vbatLatest");
+        log.frames.set_value(0, gyro, -12);
+        log.frames.set_value(1, vbat, 1620);
+        let export_opts = ExportOptions {
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+
+        let report = export_to_parquet(&log, Path::new("flight.bbl"), &export_opts, None)?;
+        let path = report.parquet_path.unwrap();
+        assert_eq!(path, temp_dir.path().join("flight.parquet"));
+        assert_eq!(report.rows_written, 2);
+        assert_eq!(report.bytes_written, std::fs::metadata(&path)?.len());
+
+        let batch = ParquetRecordBatchReaderBuilder::try_new(File::open(&path)?)?
+            .build()?
+            .next()
+            .unwrap()?;
+        let schema = batch.schema();
+        let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(
+            names,
+            ["time", "loopIteration", "gyroADC[0]", "vbatLatest (V)"]
         );
-        assert!(
-            content.contains(r#"lon="-0.1278123""#),
-            "Longitude should be formatted to 7 decimal places (truncated)"
+        assert_eq!(schema.field(0).data_type(), &DataType::UInt64);
+        assert_eq!(schema.field(3).data_type(), &DataType::Float32);
+
+        let time = batch.column(0).as_primitive::<UInt64Type>();
+        assert_eq!(time.values().to_vec(), [1000, 1250]);
+        let gyro = batch.column(2).as_primitive::<Int32Type>();
+        assert_eq!(gyro.value(0), -12);
+        assert!(gyro.is_null(1));
+        let vbat = batch.column(3).as_primitive::<Float32Type>();
+        assert!(vbat.is_null(0));
+        assert!((vbat.value(1) - 16.2).abs() < 1e-4);
+        Ok(())
+    }
+
+    #[test]
+    fn test_ndjson_export_and_streaming_writer() -> Result<()> {
+        let mut log = log_with_main_frames(&[(0, 1000), (1, 1250)]);
+        let field = log.frames.add_field("gyro\"[0]");
+        log.frames.set_value(0, field, -5);
+
+        let mut ndjson = Vec::new();
+        let report = export_ndjson_to_writer(&log, &mut ndjson)?;
+        let text = String::from_utf8(ndjson.clone())?;
+        assert_eq!(
+            text,
+            "{\"type\":\"P\",\"time\":1000,\"loopIteration\":0,\"gyro\\\"[0]\":-5}\n\
+             {\"type\":\"P\",\"time\":1250,\"loopIteration\":1}\n"
         );
+        assert_eq!(report.rows_written, 2);
+        assert_eq!(report.bytes_written, ndjson.len() as u64);
 
+        // The streaming writer produces the same lines frame by frame
+        let writer = NdjsonWriter::new(Vec::new());
+        for frame in &log.frames {
+            writer.frame(&frame);
+        }
+        let streamed = writer.finish()?;
+        assert_eq!(streamed.rows_written, 2);
+        assert_eq!(streamed.bytes_written, ndjson.len() as u64);
         Ok(())
     }
 
     #[test]
-    fn test_gpx_no_home_waypoint_when_empty() -> Result<()> {
-        let home_coords = vec![];
+    fn test_csv_preserve_order() -> Result<()> {
+        let log = log_with_main_frames(&[(0, 1000), (1, 1250), (2, 900), (3, 1750)]);
 
-        let gps_coords = vec![GpsCoordinate {
-            latitude: 40.7129,
-            longitude: -74.0061,
-            altitude: 100.0,
-            timestamp_us: 1_000_000,
-            num_sats: Some(10),
-            speed: Some(5.0),
-            ground_course: Some(180.0),
-        }];
+        let rows = export_csv_loop_column(&log, ExportOptions::default())?;
+        assert_eq!(rows, vec!["0", "1", "2", "3"]);
 
-        let content = export_gpx_and_read(&gps_coords, &home_coords)?;
+        let sorted_opts = ExportOptions {
+            preserve_order: false,
+            ..Default::default()
+        };
+        let rows = export_csv_loop_column(&log, sorted_opts)?;
+        assert_eq!(rows, vec!["2", "0", "1", "3"]);
+        Ok(())
+    }
 
-        // Verify no home waypoint appears when home_coordinates is empty
-        assert!(
-            !content.contains("<name>Home</name>"),
-            "No home waypoint should be present when home_coordinates is empty"
-        );
-        assert!(
-            !content.contains("<wpt"),
-            "No waypoint element should be present when home_coordinates is empty"
+    #[test]
+    fn test_csv_high_resolution() -> Result<()> {
+        let mut log = log_with_main_frames(&[(0, 1000)]);
+        log.header.i_frame_def = crate::types::FrameDefinition::from_field_names(vec![
+            "loopIteration".to_string(),
+            "time".to_string(),
+            "gyroADC[0]".to_string(),
+            "rcCommand[3]".to_string(),
+        ]);
+        log.push_main_frame(1250, 1, [("gyroADC[0]", -1234), ("rcCommand[3]", 1500)]);
+        log.header
+            .sysconfig
+            .insert("blackbox_high_resolution".to_string(), 1.into());
+
+        let temp_dir = TempDir::new()?;
+        let export_opts = ExportOptions {
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        let report = export_to_csv(&log, Path::new("highres.bbl"), &export_opts, None)?;
+        let content = std::fs::read_to_string(report.csv_path.unwrap())?;
+        let last_row: Vec<&str> = content.lines().last().unwrap().split(", ").collect();
+        assert_eq!(last_row[2].trim(), "-123.4");
+        assert_eq!(last_row[3].trim(), "1500");
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_gyro_deg_per_sec() -> Result<()> {
+        let mut log = log_with_main_frames(&[(0, 1000)]);
+        log.header = crate::parser::header::parse_headers_from_text(
+            "H Field I name:loopIteration,time,gyroADC[0],gyroUnfilt[0],motor[0]\n\
+             H gyro_scale:0x3f000000\n\
+             H blackbox_high_resolution:1\n",
+            crate::parser::diagnostics::DiagnosticReporter::silent(),
+        )?;
+        assert_eq!(log.header.gyro_scale(), Some(0.5));
+        log.push_main_frame(
+            1250,
+            1,
+            [
+                ("gyroADC[0]", -1234),
+                ("gyroUnfilt[0]", 400),
+                ("motor[0]", 1000),
+            ],
         );
 
+        let temp_dir = TempDir::new()?;
+        let export_opts = ExportOptions {
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            gyro_deg_per_sec: true,
+            ..Default::default()
+        };
+        let report = export_to_csv(&log, Path::new("gyro.bbl"), &export_opts, None)?;
+        let content = std::fs::read_to_string(report.csv_path.unwrap())?;
+        let mut lines = content.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "loopIteration, time (us), gyroADC[0] (deg/s), gyroUnfilt[0] (deg/s), motor[0]"
+        );
+        let last_row: Vec<&str> = content.lines().last().unwrap().split(", ").collect();
+        assert_eq!(last_row[2].trim(), "-61.7");
+        assert_eq!(last_row[3].trim(), "20.0");
+        assert_eq!(last_row[4].trim(), "1000");
         Ok(())
     }
 
     #[test]
-    fn test_gpx_home_waypoint_xml_structure() -> Result<()> {
-        let home_coords = vec![GpsHomeCoordinate {
-            home_latitude: 35.6762,
-            home_longitude: 139.6503,
-            timestamp_us: 0,
-        }];
+    fn test_csv_acc_g() -> Result<()> {
+        let mut log = log_with_main_frames(&[(0, 1000)]);
+        log.header = crate::parser::header::parse_headers_from_text(
+            "H Field I name:loopIteration,time,accSmooth[0],accSmooth[2]\n\
+             H acc_1G:2048\n",
+            crate::parser::diagnostics::DiagnosticReporter::silent(),
+        )?;
+        assert_eq!(log.header.acc_1g(), Some(2048));
+        log.push_main_frame(1250, 1, [("accSmooth[0]", -512), ("accSmooth[2]", 2048)]);
 
-        let gps_coords = vec![GpsCoordinate {
-            latitude: 35.6763,
-            longitude: 139.6504,
-            altitude: 25.0,
-            timestamp_us: 1_000_000,
-            num_sats: Some(8),
-            speed: Some(2.0),
-            ground_course: Some(45.0),
-        }];
+        let temp_dir = TempDir::new()?;
+        let export_opts = ExportOptions {
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            acc_g: true,
+            ..Default::default()
+        };
+        let report = export_to_csv(&log, Path::new("acc.bbl"), &export_opts, None)?;
+        let content = std::fs::read_to_string(report.csv_path.unwrap())?;
+        assert_eq!(
+            content.lines().next().unwrap(),
+            "loopIteration, time (us), accSmooth[0] (g), accSmooth[2] (g)"
+        );
+        let last_row: Vec<&str> = content.lines().last().unwrap().split(", ").collect();
+        assert_eq!(last_row[2].trim(), "-0.250");
+        assert_eq!(last_row[3].trim(), "1.000");
+        Ok(())
+    }
 
-        let content = export_gpx_and_read(&gps_coords, &home_coords)?;
+    #[test]
+    fn test_csv_vbat_per_cell() -> Result<()> {
+        let mut log = BBLLog::new(1, 1);
+        log.header.firmware_revision = "Betaflight 4.5.1 (77d01ba3b) STM32F7X2".to_string();
+        log.header.i_frame_def = crate::types::FrameDefinition::from_field_names(vec![
+            "loopIteration".to_string(),
+            "time".to_string(),
+            "vbatLatest".to_string(),
+        ]);
+        for (time, vbat) in [(1000, 1640), (2000, 1480)] {
+            log.push_main_frame(time, 0, [("vbatLatest", vbat)]);
+        }
 
-        // Verify basic GPX structure
-        assert!(
-            content.contains(r#"<?xml version="1.0" encoding="UTF-8"?>"#),
-            "XML declaration should be present"
+        let temp_dir = TempDir::new()?;
+        let export_opts = ExportOptions {
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            vbat_per_cell: true,
+            ..Default::default()
+        };
+        let report = export_to_csv(&log, Path::new("cells.bbl"), &export_opts, None)?;
+        let content = std::fs::read_to_string(report.csv_path.unwrap())?;
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines[0],
+            "loopIteration, time (us), vbatLatest (V), vbatPerCell (V)"
         );
-        assert!(
-            content.contains("version=\"1.1\""),
-            "GPX version should be 1.1"
+        assert!(lines[1].ends_with("16.4, 4.10"), "{}", lines[1]);
+        assert!(lines[2].ends_with("14.8, 3.70"), "{}", lines[2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_simulate_imu() -> Result<()> {
+        let fields = [
+            "gyroADC[0]",
+            "gyroADC[1]",
+            "gyroADC[2]",
+            "accSmooth[0]",
+            "accSmooth[1]",
+            "accSmooth[2]",
+        ];
+        let mut log = BBLLog::new(1, 1);
+        log.header.i_frame_def = crate::types::FrameDefinition::from_field_names(
+            ["loopIteration", "time"]
+                .iter()
+                .chain(&fields)
+                .map(|name| name.to_string())
+                .collect(),
         );
+        log.header
+            .sysconfig
+            .insert("acc_1G".to_string(), 2048.into());
+        // Held at 30° of roll
+        for time in [1000, 2000] {
+            log.push_main_frame(
+                time,
+                0,
+                fields.iter().copied().zip([0, 0, 0, 0, 1024, 1774]),
+            );
+        }
+
+        let temp_dir = TempDir::new()?;
+        let export_opts = ExportOptions {
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            simulate_imu: true,
+            ..Default::default()
+        };
+        let report = export_to_csv(&log, Path::new("imu.bbl"), &export_opts, None)?;
+        let content = std::fs::read_to_string(report.csv_path.unwrap())?;
+        let lines: Vec<&str> = content.lines().collect();
         assert!(
-            content.contains("xmlns=\"http://www.topografix.com/GPX/1/1\""),
-            "GPX namespace should be present"
+            lines[0].ends_with("accSmooth[2], roll (deg), pitch (deg), heading (deg)"),
+            "{}",
+            lines[0]
         );
+        assert!(lines[2].ends_with("  30.0,    0.0,   0.0"), "{}", lines[2]);
+        Ok(())
+    }
 
-        // Verify home waypoint appears before track element
-        let wpt_index = content.find("<wpt").expect("Should have <wpt element");
-        let trk_index = content.find("<trk").expect("Should have <trk element");
-        assert!(
-            wpt_index < trk_index,
-            "Home waypoint should appear before track element in GPX file"
+    #[test]
+    fn test_csv_mag_heading() -> Result<()> {
+        let fields = [
+            "gyroADC[0]",
+            "gyroADC[1]",
+            "gyroADC[2]",
+            "accSmooth[0]",
+            "accSmooth[1]",
+            "accSmooth[2]",
+            "magADC[0]",
+            "magADC[1]",
+            "magADC[2]",
+        ];
+        let mut log = BBLLog::new(1, 1);
+        log.header.i_frame_def = crate::types::FrameDefinition::from_field_names(
+            ["loopIteration", "time"]
+                .iter()
+                .chain(&fields)
+                .map(|name| name.to_string())
+                .collect(),
         );
+        log.header
+            .sysconfig
+            .insert("acc_1G".to_string(), 2048.into());
+        // Level, with magnetic north 30° to the right of the nose
+        for time in [1000, 2000] {
+            log.push_main_frame(
+                time,
+                0,
+                fields
+                    .iter()
+                    .copied()
+                    .zip([0, 0, 0, 0, 0, 2048, 866, -500, 400]),
+            );
+        }
 
-        // Verify proper nesting and indentation
+        let mut csv = Vec::new();
+        let export_opts = ExportOptions {
+            mag_heading: true,
+            declination_deg: 12.5,
+            ..Default::default()
+        };
+        export_csv_to_writer(&log, &mut csv, &export_opts)?;
+        let content = String::from_utf8(csv)?;
+        let lines: Vec<&str> = content.lines().collect();
         assert!(
-            content.contains("  <wpt"),
-            "Home waypoint should be properly indented"
+            lines[0].ends_with("magADC[2], heading (deg)"),
+            "{}",
+            lines[0]
         );
-        assert!(
-            content.contains("    <name>Home</name>"),
-            "Home waypoint child elements should be properly indented"
+        assert!(lines[2].ends_with("342.5"), "{}", lines[2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_climb_rate() -> Result<()> {
+        let mut log = BBLLog::new(1, 1);
+        log.header.i_frame_def = crate::types::FrameDefinition::from_field_names(
+            ["loopIteration", "time", "baroAlt"]
+                .map(String::from)
+                .to_vec(),
         );
+        // Climbing at 1 m/s for 5 s
+        for i in 0..=50 {
+            log.push_main_frame(1000 + i * 100_000, 0, [("baroAlt", i as i32 * 10)]);
+        }
 
+        let mut csv = Vec::new();
+        let export_opts = ExportOptions {
+            climb_rate: true,
+            ..Default::default()
+        };
+        export_csv_to_writer(&log, &mut csv, &export_opts)?;
+        let content = String::from_utf8(csv)?;
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines[0],
+            "loopIteration, time (us), baroAlt, climbRate (m/s)"
+        );
+        assert!(lines[1].ends_with(",  0.00"), "{}", lines[1]);
+        assert!(lines[51].ends_with(",  1.00"), "{}", lines[51]);
         Ok(())
     }
 
     #[test]
-    fn test_gpx_home_waypoint_with_negative_coordinates() -> Result<()> {
-        let home_coords = vec![GpsHomeCoordinate {
-            home_latitude: -33.8688,
-            home_longitude: 151.2093,
-            timestamp_us: 0,
-        }];
+    fn test_csv_simulate_current_meter() -> Result<()> {
+        let mut log = BBLLog::new(1, 1);
+        log.header.i_frame_def = crate::types::FrameDefinition::from_field_names(
+            ["loopIteration", "time", "rcCommand[3]"]
+                .map(String::from)
+                .to_vec(),
+        );
+        for (time, throttle) in [(1000, 1500), (1_800_001_000, 1500)] {
+            log.push_main_frame(time, 0, [("rcCommand[3]", throttle)]);
+        }
 
-        let gps_coords = vec![GpsCoordinate {
-            latitude: -33.8689,
-            longitude: 151.2094,
-            altitude: 150.0,
-            timestamp_us: 1_000_000,
-            num_sats: Some(12),
-            speed: Some(10.0),
-            ground_course: Some(270.0),
-        }];
+        let mut csv = Vec::new();
+        let mut export_opts = ExportOptions {
+            simulate_current_meter: true,
+            ..Default::default()
+        };
+        export_csv_to_writer(&log, &mut csv, &export_opts)?;
+        let content = String::from_utf8(csv)?;
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines[0],
+            "loopIteration, time (us), rcCommand[3], amperageLatest (A), energyCumulative (mAh)"
+        );
+        // 22 A at half throttle for half an hour
+        assert!(lines[2].ends_with("1500, 22.00, 11000"), "{}", lines[2]);
+
+        let mut csv = Vec::new();
+        export_opts.sim_current_meter_scale = Some(200);
+        export_csv_to_writer(&log, &mut csv, &export_opts)?;
+        let content = String::from_utf8(csv)?;
+        assert!(content.ends_with("1500, 11.00,  5500\n"), "{content}");
+        Ok(())
+    }
 
-        let content = export_gpx_and_read(&gps_coords, &home_coords)?;
+    #[test]
+    fn test_csv_setpoint_deg_per_sec() -> Result<()> {
+        let mut log = log_with_main_frames(&[(0, 1000)]);
+        log.header = crate::parser::header::parse_headers_from_text(
+            "H Field I name:loopIteration,time,rcCommand[0],rcCommand[1],rcCommand[3]\n\
+             H rc_rates:100,100,100\n\
+             H rates:70,70,70\n\
+             H blackbox_high_resolution:1\n",
+            crate::parser::diagnostics::DiagnosticReporter::silent(),
+        )?;
+        log.push_main_frame(
+            1250,
+            1,
+            [
+                ("rcCommand[0]", 5000),
+                ("rcCommand[1]", -2500),
+                ("rcCommand[3]", 1500),
+            ],
+        );
 
-        // Verify negative coordinates are correctly formatted
-        assert!(
-            content.contains(r#"lat="-33.8688000""#),
-            "Negative latitude should be correctly formatted"
+        let temp_dir = TempDir::new()?;
+        let export_opts = ExportOptions {
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            setpoint_deg_per_sec: true,
+            ..Default::default()
+        };
+        let report = export_to_csv(&log, Path::new("rates.bbl"), &export_opts, None)?;
+        let content = std::fs::read_to_string(report.csv_path.unwrap())?;
+        assert_eq!(
+            content.lines().next().unwrap(),
+            "loopIteration, time (us), rcCommand[0], rcCommand[1], rcCommand[3], \
+             setpoint[0] (deg/s), setpoint[1] (deg/s)"
         );
-        assert!(
-            content.contains(r#"lon="151.2093000""#),
-            "Positive longitude should be correctly formatted"
+        let last_row: Vec<&str> = content.lines().last().unwrap().split(", ").collect();
+        assert_eq!(last_row[5].trim(), "666.7");
+        assert_eq!(last_row[6].trim(), "-153.8");
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_motor_percent() -> Result<()> {
+        let mut log = BBLLog::new(1, 1);
+        log.header.i_frame_def = crate::types::FrameDefinition::from_field_names(vec![
+            "loopIteration".to_string(),
+            "time".to_string(),
+            "motor[0]".to_string(),
+        ]);
+        log.header.sysconfig.insert(
+            "motorOutput".to_string(),
+            SysConfigValue::IntArray(vec![48, 2047]),
         );
+        log.push_main_frame(1000, 0, [("motor[0]", 1048)]);
 
-        // Verify structure is still correct with negative values
-        assert!(
-            content.contains("<name>Home</name>"),
-            "Structure should be correct"
+        let temp_dir = TempDir::new()?;
+        let export_opts = ExportOptions {
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            motor_percent: true,
+            ..Default::default()
+        };
+        let report = export_to_csv(&log, Path::new("motors.bbl"), &export_opts, None)?;
+        let content = std::fs::read_to_string(report.csv_path.unwrap())?;
+        let mut lines = content.lines();
+        assert_eq!(
+            lines.next(),
+            Some("loopIteration, time (us), motor[0], motor[0] (%)")
         );
-        assert!(content.contains("</wpt>"), "Closing tag should be present");
+        let row: Vec<&str> = lines.next().unwrap().split(", ").collect();
+        assert_eq!(row[3].trim(), "50.0");
+        Ok(())
+    }
+
+    #[test]
+    fn test_csv_gap_fill() -> Result<()> {
+        let log = log_with_main_frames(&[(0, 1000), (1, 1250), (4, 2000), (5, 2250)]);
+
+        let rows = export_csv_loop_column(&log, ExportOptions::default())?;
+        assert_eq!(rows, vec!["0", "1", "4", "5"]);
+
+        let repeat_opts = ExportOptions {
+            gap_fill: GapFillPolicy::RepeatLast,
+            ..Default::default()
+        };
+        let rows = export_csv_loop_column(&log, repeat_opts)?;
+        assert_eq!(rows, vec!["0", "1", "2", "3", "4", "5"]);
 
+        // Time advances evenly across the gap under every filling policy
+        let temp_dir = TempDir::new()?;
+        let interpolate_opts = ExportOptions {
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            gap_fill: GapFillPolicy::Interpolate,
+            ..Default::default()
+        };
+        let report = export_to_csv(&log, Path::new("gaps.bbl"), &interpolate_opts, None)?;
+        let content = std::fs::read_to_string(report.csv_path.unwrap())?;
+        let times: Vec<&str> = content
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').nth(1).unwrap().trim())
+            .collect();
+        assert_eq!(times, vec!["1000", "1250", "1500", "1750", "2000", "2250"]);
         Ok(())
     }
 
     #[test]
-    fn test_gpx_only_first_home_coordinate_used() -> Result<()> {
-        let home_coords = vec![
-            GpsHomeCoordinate {
-                home_latitude: 40.7128,
-                home_longitude: -74.0060,
-                timestamp_us: 0,
-            },
-            GpsHomeCoordinate {
-                home_latitude: 51.5074,
-                home_longitude: -0.1278,
-                timestamp_us: 1_000_000,
-            },
+    fn test_csv_gap_interpolation_extremes() -> Result<()> {
+        let mut log = BBLLog::new(1, 1);
+        log.header.i_frame_def = crate::types::FrameDefinition::from_field_names(vec![
+            "loopIteration".to_string(),
+            "time".to_string(),
+            "debug[0]".to_string(),
+        ]);
+        let samples = [
+            (0, 1000, i32::MIN + 1),
+            (1, 1250, i32::MIN + 1),
+            (5, 2250, i32::MAX),
+            (6, 2500, i32::MAX),
         ];
+        for (loop_iteration, timestamp_us, value) in samples {
+            log.push_main_frame(
+                timestamp_us,
+                loop_iteration,
+                [
+                    ("loopIteration", loop_iteration as i32),
+                    ("time", timestamp_us as i32),
+                    ("debug[0]", value),
+                ],
+            );
+        }
 
-        let gps_coords = vec![GpsCoordinate {
-            latitude: 40.7129,
-            longitude: -74.0061,
-            altitude: 100.0,
-            timestamp_us: 1_000_000,
-            num_sats: Some(10),
-            speed: Some(5.0),
-            ground_course: Some(180.0),
-        }];
-
-        let content = export_gpx_and_read(&gps_coords, &home_coords)?;
-
-        // Verify only first coordinate is used
-        assert!(
-            content.contains(r#"lat="40.7128000""#),
-            "First home coordinate should be used"
-        );
-        // Count occurrences of <wpt to ensure only one home waypoint
-        let wpt_count = content.matches("<wpt").count();
+        let mut csv = Vec::new();
+        let export_opts = ExportOptions {
+            gap_fill: GapFillPolicy::Interpolate,
+            ..Default::default()
+        };
+        export_csv_to_writer(&log, &mut csv, &export_opts)?;
+        let content = String::from_utf8(csv)?;
+        let values: Vec<&str> = content
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').nth(2).unwrap().trim())
+            .collect();
         assert_eq!(
-            wpt_count, 1,
-            "Only one home waypoint should be present when multiple home_coordinates exist"
+            values,
+            vec![
+                "-2147483647",
+                "-2147483647",
+                "-1073741824",
+                "0",
+                "1073741824",
+                "2147483647",
+                "2147483647"
+            ]
         );
 
+        // Rounding past either end saturates instead of wrapping
+        assert_eq!(interpolate_value(i32::MAX, i32::MAX, 1.5), i32::MAX);
+        assert_eq!(interpolate_value(i32::MIN, i32::MAX, -0.5), i32::MIN);
         Ok(())
     }
 
     #[test]
-    fn test_gpx_empty_gps_coordinates_returns_ok() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        let temp_input_path = temp_dir.path().join("test_input.bbl");
+    fn test_csv_merge_gps() -> Result<()> {
+        let mut log = log_with_main_frames(&[(0, 1000), (1, 1700), (2, 2000), (3, 9000)]);
+        let fix = |timestamp_us, latitude| GpsCoordinate {
+            latitude,
+            longitude: 8.5,
+            altitude: 420.0,
+            timestamp_us,
+            num_sats: Some(12),
+            speed: Some(3.25),
+            ground_course: None,
+        };
+        log.gps_coordinates = vec![fix(1100, 47.1), fix(2100, 47.2)];
 
+        let mut csv = Vec::new();
         let export_opts = ExportOptions {
-            csv: false,
-            gpx: true,
-            event: false,
-            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
-            force_export: false,
+            merge_gps: true,
+            gps_gap_threshold_us: 5000,
+            ..Default::default()
         };
-
-        let home_coords = vec![GpsHomeCoordinate {
-            home_latitude: 40.7128,
-            home_longitude: -74.0060,
+        export_csv_to_writer(&log, &mut csv, &export_opts)?;
+        let content = String::from_utf8(csv)?;
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines[0],
+            "loopIteration, time (us), GPS_numSat, GPS_coord[0], GPS_coord[1], GPS_altitude, GPS_speed (m/s), GPS_ground_course"
+        );
+        let latitude = |line: &str| line.split(", ").nth(3).unwrap().to_string();
+        assert_eq!(latitude(lines[1]), "47.1000000");
+        assert_eq!(latitude(lines[2]), "47.2000000");
+        assert_eq!(latitude(lines[3]), "47.2000000");
+        assert!(lines[2].ends_with(", 12, 47.2000000, 8.5000000, 420.00, 3.25, "));
+        // Further than the gap threshold from any fix
+        assert_eq!(lines[4], "   3, 9000, , , , , , ");
+
+        // Distance from home follows the merged fix
+        log.home_coordinates = vec![GpsHomeCoordinate {
+            home_latitude: 47.1,
+            home_longitude: 8.5,
             timestamp_us: 0,
         }];
+        let mut csv = Vec::new();
+        let home_opts = ExportOptions {
+            home_distance: true,
+            ..export_opts.clone()
+        };
+        export_csv_to_writer(&log, &mut csv, &home_opts)?;
+        let content = String::from_utf8(csv)?;
+        let lines: Vec<&str> = content.lines().collect();
+        assert!(lines[0].ends_with("GPS_ground_course, homeDistance (m), homeDistance3D (m)"));
+        assert!(lines[1].ends_with(", 0.0, 0.0"), "{}", lines[1]);
+        assert!(lines[2].ends_with(", 11119.5, 11119.5"), "{}", lines[2]);
+        assert!(lines[4].ends_with(", , , , , , , , "), "{}", lines[4]);
+
+        // Without GPS data the CSV is unchanged
+        log.gps_coordinates.clear();
+        let mut csv = Vec::new();
+        export_csv_to_writer(&log, &mut csv, &export_opts)?;
+        assert!(String::from_utf8(csv)?.starts_with("loopIteration, time (us)\n"));
+        Ok(())
+    }
 
-        // Should return Ok even with empty GPS coordinates
-        let result = export_to_gpx(
-            &temp_input_path,
-            0,
-            1,
-            &[],
-            &home_coords,
-            &export_opts,
-            None,
-            None,
-        );
-        assert!(
-            result.is_ok(),
-            "Export should succeed with empty GPS coordinates"
-        );
+    #[test]
+    fn test_csv_event_column() -> Result<()> {
+        let mut log = log_with_main_frames(&[(0, 1000), (1, 1250), (2, 1500)]);
+        let event = |timestamp_us, event_name: &str| EventFrame {
+            timestamp_us,
+            event_type: 0,
+            event_data: Vec::new(),
+            event_name: event_name.to_string(),
+        };
+        log.event_frames = vec![
+            event(1000, "Sync beep"),
+            event(1250, "Flight mode change"),
+            event(1250, "Autotune cycle result - Axis: 0, P: 1.000"),
+        ];
 
-        // Verify no GPX file is created when GPS coordinates are empty
-        let gpx_path = temp_dir.path().join("test_input.gps.gpx");
-        assert!(
-            !gpx_path.exists(),
-            "No GPX file should be created when GPS coordinates are empty"
+        let mut csv = Vec::new();
+        let export_opts = ExportOptions {
+            event_column: true,
+            ..Default::default()
+        };
+        export_csv_to_writer(&log, &mut csv, &export_opts)?;
+        let content = String::from_utf8(csv)?;
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "loopIteration, time (us), event",
+                "   0, 1000, Sync beep",
+                "   1, 1250, \"Flight mode change; Autotune cycle result - Axis: 0, P: 1.000\"",
+                "   2, 1500, ",
+            ]
         );
-
         Ok(())
     }
 
     #[test]
-    fn test_gpx_trackpoints_skip_low_satellite_count() -> Result<()> {
-        let home_coords = vec![];
+    fn test_csv_dialects() -> Result<()> {
+        let mut log = log_with_main_frames(&[(0, 1000), (1, 1250)]);
+        log.event_frames.push(EventFrame {
+            timestamp_us: 1250,
+            event_type: 2,
+            event_data: Vec::new(),
+            event_name: "Autotune \"result\", axis 0".to_string(),
+        });
+        let csv_with = |csv_options: CsvOptions| -> Result<String> {
+            let mut csv = Vec::new();
+            let export_opts = ExportOptions {
+                event_column: true,
+                csv_options,
+                ..Default::default()
+            };
+            export_csv_to_writer(&log, &mut csv, &export_opts)?;
+            Ok(String::from_utf8(csv)?)
+        };
 
-        let gps_coords = vec![
-            GpsCoordinate {
-                latitude: 40.7129,
-                longitude: -74.0061,
-                altitude: 100.0,
-                timestamp_us: 1_000_000,
-                num_sats: Some(3), // Below minimum of 5
-                speed: Some(5.0),
-                ground_course: Some(180.0),
-            },
-            GpsCoordinate {
-                latitude: 40.7130,
-                longitude: -74.0062,
-                altitude: 105.0,
-                timestamp_us: 2_000_000,
-                num_sats: Some(10), // Valid
-                speed: Some(5.0),
-                ground_course: Some(180.0),
-            },
-        ];
+        assert_eq!(
+            csv_with(CsvOptions::rfc4180())?,
+            "loopIteration,time (us),event\r\n\
+             0,1000,\r\n\
+             1,1250,\"Autotune \"\"result\"\", axis 0\"\r\n"
+        );
+        assert_eq!(
+            csv_with(CsvOptions::tsv())?,
+            "loopIteration\ttime (us)\tevent\n\
+             0\t1000\t\n\
+             1\t1250\t\"Autotune \"\"result\"\", axis 0\"\n"
+        );
+        let always = CsvOptions {
+            quote_style: CsvQuoteStyle::Always,
+            ..Default::default()
+        };
+        assert!(csv_with(always)?
+            .starts_with("\"loopIteration\", \"time (us)\", \"event\"\n\"   0\", "));
+        Ok(())
+    }
 
-        let content = export_gpx_and_read(&gps_coords, &home_coords)?;
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("gyroADC[*]", "gyroADC[2]"));
+        assert!(wildcard_match("motor[?]", "motor[3]"));
+        assert!(wildcard_match("*", "time (us)"));
+        assert!(wildcard_match("rc*[*]", "rcCommand[3]"));
+        assert!(!wildcard_match("gyroADC[*]", "gyroADC"));
+        assert!(!wildcard_match("motor[?]", "motor[10]"));
+        assert!(!wildcard_match("axisP", "axisP[0]"));
+    }
 
-        // Verify that the trackpoint with low satellite count is not in output
-        assert!(
-            !content.contains("40.7129"),
-            "Trackpoint with low satellite count should be excluded"
+    #[test]
+    fn test_csv_field_selection() -> Result<()> {
+        let mut log = BBLLog::new(1, 1);
+        log.header.i_frame_def = crate::types::FrameDefinition::from_field_names(
+            [
+                "loopIteration",
+                "time",
+                "gyroADC[0]",
+                "gyroADC[1]",
+                "motor[0]",
+                "amperageLatest",
+            ]
+            .map(String::from)
+            .to_vec(),
         );
+        for (loop_iteration, timestamp_us) in [(0, 1000), (1, 1_800_001_000)] {
+            log.push_main_frame(
+                timestamp_us,
+                loop_iteration,
+                [
+                    ("gyroADC[0]", 10),
+                    ("gyroADC[1]", 11),
+                    ("motor[0]", 1500),
+                    ("amperageLatest", 1000),
+                ],
+            );
+        }
 
-        // Verify that the valid trackpoint is in output
-        assert!(
-            content.contains("40.7130"),
-            "Trackpoint with sufficient satellites should be included"
+        let mut csv = Vec::new();
+        let export_opts = ExportOptions {
+            csv_fields: ["motor[*]", "time", "gyroADC[*]", "energy*", "gyroADC[0]"]
+                .map(String::from)
+                .to_vec(),
+            ..Default::default()
+        };
+        export_csv_to_writer(&log, &mut csv, &export_opts)?;
+        let content = String::from_utf8(csv)?;
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "motor[0], time (us), gyroADC[0], gyroADC[1], energyCumulative (mAh)",
+                "1500, 1000,   10,   11,     0",
+                // energyCumulative still integrates the unselected amperage (10 A for 0.5 h)
+                "1500, 1800001000,   10,   11,  5000",
+            ]
         );
-
         Ok(())
     }
 
     #[test]
-    fn test_firmware_prefix_for_revision() {
-        assert_eq!(
-            firmware_prefix_for_revision("EmuFlight 0.4.3"),
-            Some("EMUF_")
+    fn test_csv_raw_units() -> Result<()> {
+        let mut log = BBLLog::new(1, 1);
+        log.header.i_frame_def = crate::types::FrameDefinition::from_field_names(
+            ["loopIteration", "time", "gyroADC[0]", "vbatLatest"]
+                .map(String::from)
+                .to_vec(),
         );
-        assert_eq!(
-            firmware_prefix_for_revision("EMUFLIGHT 0.4.3"),
-            Some("EMUF_")
+        log.header.s_frame_def =
+            crate::types::FrameDefinition::from_field_names(vec!["flightModeFlags".to_string()]);
+        log.header
+            .sysconfig
+            .insert("blackbox_high_resolution".to_string(), 1.into());
+        log.push_main_frame(
+            1000,
+            0,
+            [
+                ("gyroADC[0]", -1234),
+                ("vbatLatest", 1680),
+                ("flightModeFlags", 1),
+            ],
         );
+
+        let csv_with = |raw_units: bool| -> Result<String> {
+            let mut csv = Vec::new();
+            let export_opts = ExportOptions {
+                raw_units,
+                ..Default::default()
+            };
+            export_csv_to_writer(&log, &mut csv, &export_opts)?;
+            Ok(String::from_utf8(csv)?)
+        };
         assert_eq!(
-            firmware_prefix_for_revision("Betaflight 4.5.0"),
-            Some("BTFL_")
+            csv_with(false)?.lines().collect::<Vec<_>>(),
+            [
+                "loopIteration, time (us), gyroADC[0], vbatLatest (V), flightModeFlags (flags)",
+                "   0, 1000, -123.4, 16.8, ANGLE_MODE",
+            ]
         );
         assert_eq!(
-            firmware_prefix_for_revision("Betaflight 2025.12.0-beta (abc) STM32H743"),
-            Some("BTFL_")
+            csv_with(true)?.lines().collect::<Vec<_>>(),
+            [
+                "loopIteration, time (us), gyroADC[0], vbatLatest, flightModeFlags",
+                "   0, 1000, -1234, 1680,    1",
+            ]
         );
-        assert_eq!(firmware_prefix_for_revision("INAV 7.1.2"), Some("INAV_"));
-        assert_eq!(firmware_prefix_for_revision(""), None);
-        assert_eq!(firmware_prefix_for_revision("Unknown firmware"), None);
+        Ok(())
     }
 
     #[test]
-    fn test_corrected_session_base_name_matching_vendor() {
-        let path = std::path::Path::new("/logs/EMUF_BLACKBOX_LOG_QUAD_20260531.BBL");
-        assert_eq!(corrected_session_base_name(path, "EmuFlight 0.4.3"), None);
+    fn test_blackbox_decode_compat() -> Result<()> {
+        let mut log = BBLLog::new(1, 1);
+        log.header.firmware_revision = "Betaflight 4.5.1 (77d01ba3b) AT32F435M".to_string();
+        log.header.i_frame_def = crate::types::FrameDefinition::from_field_names(
+            [
+                "loopIteration",
+                "time",
+                "gyroADC[0]",
+                "rcCommand[3]",
+                "vbatLatest",
+                "amperageLatest",
+            ]
+            .map(String::from)
+            .to_vec(),
+        );
+        log.header
+            .sysconfig
+            .insert("blackbox_high_resolution".to_string(), 1.into());
+        log.push_main_frame(
+            1000,
+            7,
+            [
+                ("gyroADC[0]", -1234),
+                ("rcCommand[3]", 1500),
+                ("loopIteration", 7),
+                ("vbatLatest", 1680),
+                ("amperageLatest", 1234),
+            ],
+        );
 
-        let path = std::path::Path::new("/logs/BTFL_BLACKBOX_LOG_QUAD_20260531.BBL");
-        assert_eq!(corrected_session_base_name(path, "Betaflight 4.5.0"), None);
+        let temp_dir = TempDir::new()?;
+        let export_opts = ExportOptions {
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            throttle_percent: true,
+            compat: CompatMode::BlackboxDecode,
+            ..Default::default()
+        };
+        let report = export_to_csv(&log, Path::new("LOG00001.BFL"), &export_opts, None)?;
+        assert_eq!(report.headers_path, None);
+        assert!(!temp_dir.path().join("LOG00001.01.headers.csv").exists());
+        let csv_path = report.csv_path.unwrap();
+        assert_eq!(csv_path, temp_dir.path().join("LOG00001.01.csv"));
+
+        let content = std::fs::read_to_string(csv_path)?;
+        assert_eq!(
+            content.lines().collect::<Vec<_>>(),
+            [
+                "loopIteration, time (us), gyroADC[0], rcCommand[3], vbatLatest (V), amperageLatest (A), energyCumulative (mAh)",
+                "  7, 1000, -1234, 1500, 16.800, 12.340, 0",
+            ]
+        );
+        Ok(())
     }
 
     #[test]
-    fn test_corrected_session_base_name_mismatched_vendor() {
-        let path = std::path::Path::new("/logs/EMUF_BLACKBOX_LOG_QUAD_20260531.BBL");
+    fn test_pid_analyzer_preset_names_debug_columns() -> Result<()> {
+        let mut log = log_with_main_frames(&[(0, 1000)]);
+        log.header.firmware_revision = "Betaflight 4.5.1 (77d01ba3b) AT32F435M".to_string();
+        log.header.i_frame_def = crate::types::FrameDefinition::from_field_names(
+            [
+                "loopIteration",
+                "time",
+                "rcCommand[3]",
+                "debug[0]",
+                "debug[1]",
+            ]
+            .map(String::from)
+            .to_vec(),
+        );
+        log.header
+            .sysconfig
+            .insert("debug_mode".to_string(), 6.into());
+        assert_eq!(log.header.debug_mode_name(), Some("GYRO_SCALED"));
+
+        let export_opts = ExportOptions::default().with_preset(ExportPreset::PidAnalyzer);
+        assert_eq!(export_opts.compat, CompatMode::BlackboxDecode);
+        let mut csv = Vec::new();
+        export_csv_to_writer(&log, &mut csv, &export_opts)?;
+        let content = String::from_utf8(csv)?;
         assert_eq!(
-            corrected_session_base_name(path, "Betaflight 2025.12.0-beta"),
-            Some("BTFL_BLACKBOX_LOG_QUAD_20260531".to_string())
+            content.lines().next(),
+            Some("loopIteration, time (us), rcCommand[3], debug[0] (GYRO_SCALED), debug[1] (GYRO_SCALED)")
         );
 
-        let path = std::path::Path::new("/logs/BTFL_BLACKBOX_LOG_QUAD_20260531.BBL");
+        // Unknown modes and other firmware keep the plain names
+        log.header
+            .sysconfig
+            .insert("debug_mode".to_string(), 1000.into());
+        assert_eq!(log.header.debug_mode_name(), None);
+        log.header
+            .sysconfig
+            .insert("debug_mode".to_string(), 6.into());
+        log.header.firmware_revision = "INAV 7.1.0".to_string();
+        assert_eq!(log.header.debug_mode_name(), None);
+        Ok(())
+    }
+    #[test]
+    fn test_concat_csv_logs() -> Result<()> {
+        let first = log_with_main_frames(&[(0, 5000), (1, 6000)]);
+        let mut second = log_with_main_frames(&[(0, 9000)]);
+        second.log_number = 2;
+        let export_opts = ExportOptions::default();
+
+        let mut csv = Vec::new();
+        let mut writer = ConcatCsvWriter::new(&mut csv, true);
+        assert_eq!(writer.write_log(&first, &export_opts)?, 2);
+        assert_eq!(writer.write_log(&second, &export_opts)?, 1);
+
+        // A log with other columns is rejected without writing rows
+        let mut other = log_with_main_frames(&[(0, 1000)]);
+        other.log_number = 3;
+        other.header.i_frame_def =
+            crate::types::FrameDefinition::from_field_names(vec!["time".to_string()]);
+        let err = writer.write_log(&other, &export_opts).unwrap_err();
+        assert!(matches!(err, BBLError::ColumnMismatch { log_number: 3 }));
+        let report = writer.finish()?;
+        assert_eq!(report.rows_written, 3);
+
+        let content = String::from_utf8(csv)?;
+        let lines: Vec<_> = content.lines().collect();
         assert_eq!(
-            corrected_session_base_name(path, "EmuFlight 0.4.3"),
-            Some("EMUF_BLACKBOX_LOG_QUAD_20260531".to_string())
+            lines,
+            [
+                "log_number, loopIteration, time (us)",
+                "1,    0, 0",
+                "1,    1, 1000",
+                "2,    0, 0",
+            ]
         );
+        Ok(())
     }
+    #[test]
+    fn test_concat_csv_continuous_time() -> Result<()> {
+        // The clock restarts with the second log and runs on into the third
+        let first = log_with_main_frames(&[(0, 5000), (1, 6000)]);
+        let second = log_with_main_frames(&[(0, 2000), (1, 2500)]);
+        let third = log_with_main_frames(&[(0, 4500)]);
+        let export_opts = ExportOptions::default();
+
+        let mut csv = Vec::new();
+        let mut writer = ConcatCsvWriter::new(&mut csv, true).with_continuous_time();
+        for log in [&first, &second, &third] {
+            writer.write_log(log, &export_opts)?;
+        }
+        writer.finish()?;
 
+        let content = String::from_utf8(csv)?;
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "log_number, loopIteration, time (us)",
+                "1,    0, 5000",
+                "1,    1, 6000",
+                "2,    0, 7000",
+                "2,    1, 7500",
+                "3,    0, 9500",
+            ]
+        );
+        Ok(())
+    }
     #[test]
-    fn test_corrected_session_base_name_unknown_prefix() {
-        // BBL without a known prefix — no correction regardless of firmware
-        let path = std::path::Path::new("/logs/BLACKBOX_LOG_QUAD_20260531.BBL");
-        assert_eq!(corrected_session_base_name(path, "EmuFlight 0.4.3"), None);
-        assert_eq!(corrected_session_base_name(path, "Betaflight 4.5.0"), None);
+    fn test_time_range_csv() -> Result<()> {
+        let mut log = log_with_main_frames(&[(0, 5000), (1, 6000), (2, 7000), (3, 8000)]);
+        log.stats.start_time_us = 5000;
+        for timestamp_us in [5500, 6500] {
+            log.gps_coordinates.push(GpsCoordinate {
+                latitude: 0.0,
+                longitude: 0.0,
+                altitude: 0.0,
+                timestamp_us,
+                num_sats: None,
+                speed: None,
+                ground_course: None,
+            });
+        }
+
+        // Both bounds are inclusive offsets from the first frame
+        let range = TimeRange {
+            start_us: Some(1000),
+            end_us: Some(2000),
+        };
+        let export_opts = ExportOptions {
+            csv: true,
+            time_range: range,
+            ..Default::default()
+        };
+        assert_eq!(export_csv_loop_column(&log, export_opts)?, ["1", "2"]);
+
+        let open_end = TimeRange {
+            start_us: Some(2000),
+            end_us: None,
+        };
+        let export_opts = ExportOptions {
+            csv: true,
+            time_range: open_end,
+            ..Default::default()
+        };
+        assert_eq!(export_csv_loop_column(&log, export_opts)?, ["2", "3"]);
+
+        let gps = range.gps_coordinates(&log);
+        assert_eq!(gps.len(), 1);
+        assert_eq!(gps[0].timestamp_us, 6500);
+        assert!(TimeRange::default().is_unbounded());
+        assert_eq!(TimeRange::default().gps_coordinates(&log).len(), 2);
+        Ok(())
     }
 
     #[test]
-    fn test_corrected_session_base_name_fork_exemption() {
-        // Quicksilver (QUIC_) intentionally writes Betaflight revision headers — must NOT rename
-        let path = std::path::Path::new("/logs/QUIC_Twiglet_2026-06-09_file_0.bfl");
+    fn test_compressed_csv_export() -> Result<()> {
+        let log = log_with_main_frames(&[(0, 1000), (1, 2000)]);
+        let temp_dir = TempDir::new()?;
+        let export_opts = ExportOptions {
+            csv: true,
+            compress: true,
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        let result = export_to_csv(&log, Path::new("flight.bbl"), &export_opts, None);
+
+        #[cfg(feature = "gzip")]
+        {
+            use std::io::Read;
+
+            let report = result?;
+            let csv_path = report.csv_path.unwrap();
+            assert_eq!(csv_path, temp_dir.path().join("flight.csv.gz"));
+            let headers_path = report.headers_path.unwrap();
+            assert_eq!(headers_path, temp_dir.path().join("flight.headers.csv.gz"));
+            let compressed = std::fs::read(&csv_path)?;
+            // Sizes on disk, not of the uncompressed CSV
+            assert_eq!(
+                report.bytes_written,
+                compressed.len() as u64 + std::fs::metadata(headers_path)?.len()
+            );
+            let mut content = String::new();
+            flate2::read::GzDecoder::new(compressed.as_slice()).read_to_string(&mut content)?;
+
+            let mut expected = Vec::new();
+            export_csv_to_writer(&log, &mut expected, &ExportOptions::default())?;
+            assert_eq!(content.as_bytes(), expected);
+        }
+        #[cfg(not(feature = "gzip"))]
+        assert!(result.is_err());
+        Ok(())
+    }
+    #[test]
+    fn test_overwrite_policy() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let mut export_opts = ExportOptions {
+            csv: true,
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        let input = Path::new("flight.bbl");
+        let resolve = |opts: &ExportOptions, log_number| {
+            resolve_export_base_name(input, opts, log_number, 2, None)
+        };
+
+        // Nothing exported yet: every policy keeps the name
+        for policy in [
+            OverwritePolicy::Overwrite,
+            OverwritePolicy::SkipExisting,
+            OverwritePolicy::Rename,
+        ] {
+            export_opts.overwrite = policy;
+            assert_eq!(resolve(&export_opts, 1).as_deref(), Some("flight"));
+        }
+
+        std::fs::write(temp_dir.path().join("flight.01.headers.csv"), "")?;
+        std::fs::write(temp_dir.path().join("flight-1.01.csv"), "")?;
+        export_opts.overwrite = OverwritePolicy::Overwrite;
+        assert_eq!(resolve(&export_opts, 1).as_deref(), Some("flight"));
+        export_opts.overwrite = OverwritePolicy::SkipExisting;
+        assert_eq!(resolve(&export_opts, 1), None);
+        assert_eq!(resolve(&export_opts, 2).as_deref(), Some("flight"));
+        export_opts.overwrite = OverwritePolicy::Rename;
+        assert_eq!(resolve(&export_opts, 1).as_deref(), Some("flight-2"));
+
+        // Only the enabled outputs count
+        export_opts.csv = false;
+        export_opts.gpx = true;
+        export_opts.overwrite = OverwritePolicy::SkipExisting;
+        assert_eq!(resolve(&export_opts, 1).as_deref(), Some("flight"));
+
+        std::fs::write(temp_dir.path().join("flight.02.parquet"), "")?;
+        assert_eq!(resolve(&export_opts, 2).as_deref(), Some("flight"));
+        export_opts.parquet = true;
+        assert_eq!(resolve(&export_opts, 2), None);
+        export_opts.overwrite = OverwritePolicy::Rename;
+        assert_eq!(resolve(&export_opts, 2).as_deref(), Some("flight-1"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_frame_type_csvs() -> Result<()> {
+        let mut log = log_with_main_frames(&[(0, 1000), (1, 2000)]);
+        log.slow_frames = FrameColumns::with_fields(["flightModeFlags", "rxSignalReceived"]);
+        log.slow_frames.push_values('S', 1000, 0, [(0, 1), (1, 1)]);
+        log.slow_frames.push_values('S', 2000, 0, [(0, 0)]);
+        log.gps_frames = FrameColumns::with_fields(["time", "GPS_numSat", "GPS_coord[0]"]);
+        log.gps_frames
+            .push_values('G', 1500, 0, [(0, 1500), (1, 9), (2, -120)]);
+
+        let temp_dir = TempDir::new()?;
+        let export_opts = ExportOptions {
+            csv: true,
+            split_frame_types: true,
+            output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
+            ..Default::default()
+        };
+        let report = export_to_csv(&log, Path::new("flight.bbl"), &export_opts, None)?;
+        assert_eq!(report.rows_written, 2);
+
+        let s_path = report.s_frame_csv_path.unwrap();
+        assert_eq!(s_path, temp_dir.path().join("flight.S.csv"));
+        let content = std::fs::read_to_string(s_path)?;
         assert_eq!(
-            corrected_session_base_name(path, "Betaflight 4.3.0"),
-            None,
-            "QUIC_ files with Betaflight revision must not be renamed to BTFL_"
+            content.lines().collect::<Vec<_>>(),
+            [
+                "time (us), flightModeFlags (flags), rxSignalReceived",
+                "1000, ANGLE_MODE,    1",
+                "2000, 0, ",
+            ]
         );
-        // Sanity: an actual mismatch on a non-fork prefix still renames
-        let path = std::path::Path::new("/logs/EMUF_BLACKBOX_LOG_20260531.BBL");
+
+        let g_path = report.g_frame_csv_path.unwrap();
+        assert_eq!(g_path, temp_dir.path().join("flight.G.csv"));
+        let content = std::fs::read_to_string(g_path)?;
         assert_eq!(
-            corrected_session_base_name(path, "Betaflight 4.3.0"),
-            Some("BTFL_BLACKBOX_LOG_20260531".to_string())
+            content.lines().collect::<Vec<_>>(),
+            ["time (us), GPS_numSat, GPS_coord[0]", "1500,    9, -120"]
+        );
+
+        // Without the option neither file is written
+        let report = export_to_csv(
+            &log,
+            Path::new("other.bbl"),
+            &ExportOptions {
+                split_frame_types: false,
+                ..export_opts
+            },
+            None,
+        )?;
+        assert!(report.s_frame_csv_path.is_none() && report.g_frame_csv_path.is_none());
+        assert!(!temp_dir.path().join("other.S.csv").exists());
+        Ok(())
+    }
+    #[test]
+    fn test_headers_feature_names() -> Result<()> {
+        let mut log = BBLLog::new(1, 1);
+        log.header = crate::parser::header::parse_headers_from_text(
+            "H Firmware revision:Betaflight 4.5.1 (77d01ba3b) STM32F7X2\n\
+             H features:541130760\nH looptime:125\n",
+            crate::parser::diagnostics::DiagnosticReporter::silent(),
+        )?;
+        let names = "RX_SERIAL|LED_STRIP|AIRMODE|DYNAMIC_FILTER";
+        assert_eq!(log.header.features().join("|"), names);
+
+        let mut csv = Vec::new();
+        export_headers_to_writer(&log, &mut csv)?;
+        assert!(String::from_utf8(csv)?.contains(&format!(
+            "features,541130760\nfeatures (names),{names}\nlooptime,125\n"
+        )));
+
+        let mut json = Vec::new();
+        let report = export_headers_json_to_writer(&log, &mut json)?;
+        assert_eq!(report.rows_written, 4);
+        assert!(String::from_utf8(json)?.contains(
+            r#""features (names)": ["RX_SERIAL", "LED_STRIP", "AIRMODE", "DYNAMIC_FILTER"],"#
+        ));
+
+        // INAV numbers its features differently
+        log.header.firmware_revision = "INAV 7.1.0 (abc) MATEKF405".to_string();
+        assert!(log.header.features().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_headers_json_export() -> Result<()> {
+        let mut log = BBLLog::new(1, 1);
+        log.header.all_headers = [
+            "H Product:Blackbox flight data recorder by Nicholas Sherlock",
+            "H Data version:2",
+            "H Field I name:loopIteration,time",
+            "H Firmware revision:Betaflight 4.5.1 (77d01ba3b) STM32F7X2",
+            "H looptime:125",
+            "H rollPID:45,80,30",
+            "H gyro_scale:0x3f800000",
+            "H vbatcellvoltage:330,350,430",
+            "H yaw_lowpass_hz:1.5",
+            "H Craft name:say \"hi\", 2,3",
+            "H looptime:250",
+        ]
+        .map(String::from)
+        .to_vec();
+
+        let mut json = Vec::new();
+        let report = export_headers_json_to_writer(&log, &mut json)?;
+        assert_eq!(report.rows_written, 10);
+        assert_eq!(
+            String::from_utf8(json)?.lines().collect::<Vec<_>>(),
+            [
+                "{",
+                r#"  "Product": "Blackbox flight data recorder by Nicholas Sherlock","#,
+                r#"  "Data version": 2,"#,
+                r#"  "Field I name": ["loopIteration", "time"],"#,
+                r#"  "Firmware revision": "Betaflight 4.5.1 (77d01ba3b) STM32F7X2","#,
+                r#"  "looptime": 125,"#,
+                r#"  "rollPID": [45, 80, 30],"#,
+                r#"  "gyro_scale": "0x3f800000","#,
+                r#"  "vbatcellvoltage": [330, 350, 430],"#,
+                r#"  "yaw_lowpass_hz": 1.5,"#,
+                r#"  "Craft name": "say \"hi\", 2,3""#,
+                "}",
+            ]
         );
+        Ok(())
     }
 }
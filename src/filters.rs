@@ -8,9 +8,47 @@
 //!
 //! These filters are controlled via `ExportOptions`. CLI users get filtering enabled by
 //! default for convenience, while library consumers can opt in/out as needed.
+//! The thresholds live in [`FilterThresholds`] (`ExportOptions::filter_thresholds`).
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 use crate::types::BBLLog;
 
+/// Thresholds of the export filtering heuristics
+///
+/// The defaults skip logs shorter than 5 s, keep 5–15 s logs logged at more
+/// than 1500 frames per second, and skip longer logs whose gyro range stays
+/// below 500 (ground tests).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct FilterThresholds {
+    /// Logs shorter than this are always skipped, in seconds
+    pub min_duration_s: f64,
+    /// Logs shorter than this need `min_data_density_fps`, in seconds
+    pub short_duration_s: f64,
+    /// Minimum frames per second of short logs
+    pub min_data_density_fps: f64,
+    /// Largest gyro axis range (max - min) below which a log is a ground test
+    pub min_gyro_range: f64,
+    /// Minimum frame count of logs without duration information
+    pub fallback_min_frames: u32,
+}
+
+impl Default for FilterThresholds {
+    fn default() -> Self {
+        Self {
+            min_duration_s: 5.0,
+            short_duration_s: 15.0,
+            min_data_density_fps: 1500.0,
+            min_gyro_range: 500.0,
+            // ~5 seconds at 1500 fps, ~1 second at 8000 fps
+            fallback_min_frames: 7_500,
+        }
+    }
+}
+
 /// Determines if a log should be skipped for export based on duration and frame count
 ///
 /// Uses smart filtering: <5s always skip, 5-15s keep if good data density (>1500fps), >15s always keep
@@ -23,15 +61,19 @@ use crate::types::BBLLog;
 /// # Returns
 /// Tuple of (should_skip, reason_description)
 pub fn should_skip_export(log: &BBLLog, force_export: bool) -> (bool, String) {
+    should_skip_export_with_thresholds(log, force_export, &FilterThresholds::default())
+}
+
+/// [`should_skip_export`] with custom thresholds
+pub fn should_skip_export_with_thresholds(
+    log: &BBLLog,
+    force_export: bool,
+    thresholds: &FilterThresholds,
+) -> (bool, String) {
     if force_export {
         return (false, String::new()); // Never skip when forced
     }
 
-    const VERY_SHORT_DURATION_MS: u64 = 5_000; // 5 seconds - always skip
-    const SHORT_DURATION_MS: u64 = 15_000; // 15 seconds - threshold for normal logs
-    const MIN_DATA_DENSITY_FPS: f64 = 1500.0; // Minimum fps for short logs
-    const FALLBACK_MIN_FRAMES: u32 = 7_500; // ~5 seconds at 1500 fps, ~1 second at 8000 fps
-
     // Check if we have duration information
     let duration_us = log.duration_us();
     if duration_us > 0 {
@@ -43,22 +85,27 @@ pub fn should_skip_export(log: &BBLLog, force_export: bool) -> (bool, String) {
             return (true, "duration too small or invalid".to_string());
         }
 
-        let duration_ms = duration_us / 1000;
         let fps = log.stats.total_frames as f64 / duration_s;
 
         // Very short logs: < 5 seconds → Always skip
-        if duration_ms < VERY_SHORT_DURATION_MS {
-            return (true, format!("too short ({:.1}s < 5.0s)", duration_s));
+        if duration_s < thresholds.min_duration_s {
+            return (
+                true,
+                format!(
+                    "too short ({:.1}s < {:.1}s)",
+                    duration_s, thresholds.min_duration_s
+                ),
+            );
         }
 
         // Short logs: 5-15 seconds → Keep if sufficient data density (>1500 fps)
-        if duration_ms < SHORT_DURATION_MS {
-            if fps < MIN_DATA_DENSITY_FPS {
+        if duration_s < thresholds.short_duration_s {
+            if fps < thresholds.min_data_density_fps {
                 return (
                     true,
                     format!(
                         "insufficient data density ({:.0}fps < {:.0}fps for {:.1}s log)",
-                        fps, MIN_DATA_DENSITY_FPS, duration_s
+                        fps, thresholds.min_data_density_fps, duration_s
                     ),
                 );
             }
@@ -67,7 +114,8 @@ pub fn should_skip_export(log: &BBLLog, force_export: bool) -> (bool, String) {
         }
 
         // Normal logs: > 15 seconds → Check for minimal gyro activity (ground tests)
-        let (is_minimal_movement, max_range) = has_minimal_gyro_activity(log);
+        let (is_minimal_movement, max_range) =
+            has_minimal_gyro_activity_with_threshold(log, thresholds.min_gyro_range);
         if is_minimal_movement {
             return (
                 true,
@@ -83,19 +131,20 @@ pub fn should_skip_export(log: &BBLLog, force_export: bool) -> (bool, String) {
 
     // No duration information available, fall back to frame count and gyro variance
     // Skip if very low frame count (equivalent to <10s at minimum viable fps)
-    if log.stats.total_frames < FALLBACK_MIN_FRAMES {
+    if log.stats.total_frames < thresholds.fallback_min_frames {
         return (
             true,
             format!(
                 "too few frames ({} < {}) and no duration info",
-                log.stats.total_frames, FALLBACK_MIN_FRAMES
+                log.stats.total_frames, thresholds.fallback_min_frames
             ),
         );
     }
 
     // For logs without duration but sufficient frames, apply gyro range check
     // This catches INAV logs and older Betaflight logs that lack duration info
-    let (is_minimal_movement, max_range) = has_minimal_gyro_activity(log);
+    let (is_minimal_movement, max_range) =
+        has_minimal_gyro_activity_with_threshold(log, thresholds.min_gyro_range);
     if is_minimal_movement {
         return (
             true,
@@ -125,9 +174,13 @@ pub fn should_skip_export(log: &BBLLog, force_export: bool) -> (bool, String) {
 /// # Returns
 /// Tuple of (is_minimal_movement, max_gyro_range)
 pub fn has_minimal_gyro_activity(log: &BBLLog) -> (bool, f64) {
-    // Conservative thresholds to avoid false-skips
+    has_minimal_gyro_activity_with_threshold(log, FilterThresholds::default().min_gyro_range)
+}
+
+/// [`has_minimal_gyro_activity`] with a custom minimum gyro range
+pub fn has_minimal_gyro_activity_with_threshold(log: &BBLLog, min_gyro_range: f64) -> (bool, f64) {
+    // Conservative threshold to avoid false-skips
     const MIN_SAMPLES_FOR_ANALYSIS: usize = 15; // Reduced for limited sample data
-    const MIN_GYRO_RANGE: f64 = 500.0; // Minimum range to distinguish static bench tests from gentle flights
 
     let mut gyro_x_values = Vec::new();
     let mut gyro_y_values = Vec::new();
@@ -155,15 +208,22 @@ pub fn has_minimal_gyro_activity(log: &BBLLog) -> (bool, f64) {
 
     // Fallback to frames if debug_frames not available or insufficient data
     if gyro_x_values.len() < MIN_SAMPLES_FOR_ANALYSIS {
-        for frame in &log.frames {
-            if let (Some(&gx), Some(&gy), Some(&gz)) = (
-                frame.data.get("gyroADC[0]"),
-                frame.data.get("gyroADC[1]"),
-                frame.data.get("gyroADC[2]"),
-            ) {
-                gyro_x_values.push(gx as f64);
-                gyro_y_values.push(gy as f64);
-                gyro_z_values.push(gz as f64);
+        let frames = &log.frames;
+        if let (Some(gx), Some(gy), Some(gz)) = (
+            frames.field_index("gyroADC[0]"),
+            frames.field_index("gyroADC[1]"),
+            frames.field_index("gyroADC[2]"),
+        ) {
+            for row in 0..frames.len() {
+                if let (Some(x), Some(y), Some(z)) = (
+                    frames.value(row, gx),
+                    frames.value(row, gy),
+                    frames.value(row, gz),
+                ) {
+                    gyro_x_values.push(x as f64);
+                    gyro_y_values.push(y as f64);
+                    gyro_z_values.push(z as f64);
+                }
             }
         }
     }
@@ -181,12 +241,16 @@ pub fn has_minimal_gyro_activity(log: &BBLLog) -> (bool, f64) {
     let range_z = calculate_range(&gyro_z_values);
 
     // Use the maximum range across all axes as the detection metric
-    let max_range = range_x.max(range_y).max(range_z);
+    let mut max_range = range_x.max(range_y).max(range_z);
+    if log.header.is_high_resolution() {
+        // High-resolution logs store gyro values ×10; compare in normal units
+        max_range /= crate::conversion::HIGH_RESOLUTION_SCALE;
+    }
 
     // If maximum axis range is below threshold, classify as ground test
-    // Threshold of MIN_GYRO_RANGE (500.0) catches static bench tests while allowing gentle/beginner flights
+    // The default threshold (500.0) catches static bench tests while allowing gentle/beginner flights
     // True ground tests: <500 (sensor noise), Gentle flights: >500 (real movement)
-    let is_minimal = max_range < MIN_GYRO_RANGE;
+    let is_minimal = max_range < min_gyro_range;
 
     (is_minimal, max_range)
 }
@@ -261,11 +325,13 @@ mod tests {
                 total_frames,
                 ..Default::default()
             },
-            frames: vec![],
+            frames: Default::default(),
             debug_frames: None,
             gps_coordinates: vec![],
             home_coordinates: vec![],
             event_frames: vec![],
+            slow_frames: Default::default(),
+            gps_frames: Default::default(),
         }
     }
 
@@ -344,7 +410,7 @@ mod tests {
         let mut log = create_test_log(0, 0, 16000); // 16000 frames, no duration
 
         // Create frames with minimal gyro variation (ground test pattern)
-        // Gyro range will be < MIN_GYRO_RANGE (500.0) — representing sensor noise only
+        // Gyro range will be below the default min_gyro_range (500.0) — representing sensor noise only
         for i in 0..100 {
             let mut data = HashMap::new();
             data.insert("gyroADC[0]".to_string(), 10 + (i % 5) as i32); // Range: 5
@@ -380,7 +446,7 @@ mod tests {
         let mut log = create_test_log(0, 0, 16000); // 16000 frames, no duration
 
         // Create frames with flight-typical gyro variation (large excursions)
-        // Gyro range will be > MIN_GYRO_RANGE (500.0) (actual flight movement)
+        // Gyro range will be above the default min_gyro_range (500.0) (actual flight movement)
         for i in 0..100 {
             let mut data = HashMap::new();
             // Simulate flight with gyro values ranging -3000 to +3000
@@ -403,6 +469,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_custom_thresholds() {
+        // 3 seconds is kept once the minimum duration drops to 2 seconds
+        let log = create_test_log(0, 3_000_000, 4500);
+        let thresholds = FilterThresholds {
+            min_duration_s: 2.0,
+            ..Default::default()
+        };
+        let (should_skip, _) = should_skip_export_with_thresholds(&log, false, &thresholds);
+        assert!(!should_skip, "Expected to keep 3s flight above 2s minimum");
+
+        // 20 seconds at 1000fps counts as short, and too sparse, below 30 seconds
+        let log = create_test_log(0, 20_000_000, 20_000);
+        let thresholds = FilterThresholds {
+            short_duration_s: 30.0,
+            ..Default::default()
+        };
+        let (should_skip, reason) = should_skip_export_with_thresholds(&log, false, &thresholds);
+        assert!(should_skip, "Expected to skip sparse 20s flight");
+        assert!(reason.contains("insufficient data density"));
+
+        let log = create_test_log(0, 0, 5000);
+        let thresholds = FilterThresholds {
+            fallback_min_frames: 4000,
+            ..Default::default()
+        };
+        let (should_skip, _) = should_skip_export_with_thresholds(&log, false, &thresholds);
+        assert!(
+            !should_skip,
+            "Expected to keep 5000 frames above 4000 minimum"
+        );
+    }
+
     #[test]
     fn test_calculate_range_empty() {
         assert_eq!(calculate_range(&[]), 0.0);
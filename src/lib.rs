@@ -9,6 +9,12 @@
 //! - **`cli`** (default): Build the command-line interface binary
 //! - **`json`**: Enable event export in JSON format
 //! - **`serde`**: Enable serialization/deserialization of types
+//! - **`gzip`**: Transparently read gzip-compressed logs (`.BBL.gz`) and write compressed exports (`ExportOptions::compress`)
+//! - **`zip`**: Transparently read logs stored inside `.zip` archives
+//! - **`tracing`**: Emit `tracing` spans and events for log parsing and exports, plus the `TracingDiagnostics` sink
+//! - **`arrow`**: Convert main frames to an Arrow `RecordBatch` (`BBLLog::to_record_batch`)
+//! - **`parquet`**: Export flight data as typed, compressed Parquet files (`export_to_parquet`; implies `arrow`)
+//! - **`spectrum`**: Welch power spectral density of gyro, D-term and debug traces, optionally per throttle range (`BBLLog::spectrum`), and step response analysis (`BBLLog::step_response`)
 //!
 //! # Quick Start
 //!
@@ -20,6 +26,9 @@
 //! let export_options = ExportOptions::default();
 //! let log = parse_bbl_file(Path::new("flight.BBL"), export_options, false).unwrap();
 //! println!("Parsed {} frames", log.frames.len());
+//! if let Some(gyro_roll) = log.frames.column("gyroADC[0]") {
+//!     println!("Peak roll gyro: {:?}", gyro_roll.iter().map(|v| v.abs()).max());
+//! }
 //! println!("Flight duration: {} μs", log.stats.end_time_us - log.stats.start_time_us);
 //! ```
 //!
@@ -34,6 +43,7 @@
 //!     event: false,
 //!     output_dir: None,
 //!     force_export: false,
+//!     ..Default::default()
 //! };
 //! let log = parse_bbl_file(Path::new("flight.BBL"), export_options.clone(), false).unwrap();
 //! let report = export_to_csv(&log, Path::new("flight.BBL"), &export_options, None).unwrap();
@@ -49,45 +59,130 @@
 //! - [`parse_bbl_file_all_logs`] - Parse a BBL file and return all logs
 //! - [`parse_bbl_bytes`] - Parse BBL data from memory
 //! - [`parse_bbl_bytes_all_logs`] - Parse multiple logs from memory
+//! - [`parse_bbl_reader`] - Parse all logs from any `std::io::Read` source
+//! - [`BBLLogReader`] - Iterator yielding logs incrementally from a reader
+//! - [`parse_bbl_file_log_n`] / [`parse_bbl_bytes_log_n`] - Parse only the Nth log, skipping the others
+//! - [`parse_bbl_file_headers_only`] / [`parse_bbl_bytes_headers_only`] - Parse only the text headers of every log, skipping frame decoding
+//! - [`parse_bbl_file_with_config`] and the other `*_with_config` variants - Parse with custom [`ParserConfig`] limits
 //! - [`parse_single_log`] - Low-level API for streaming scenarios
+//! - [`log_byte_ranges`] / [`write_log_slice`] - Locate each log in a file and copy one out as a standalone `.BBL`
+//! - [`write_trimmed_log`] - Copy one log out as a standalone `.BBL` holding only the frames inside a [`TimeRange`]
+//! - [`write_merged_logs`] - Copy the logs of several files into one multi-log `.BBL`
+//! - [`validate_bbl_file`] / [`validate_bbl_bytes`] - Decode without exporting and report corruption per log ([`LogValidation`])
+//!
+//! ## Writing Functions
+//! - [`BBLWriter`] - Write a header and I, P, S, G, H and E frames as a blackbox log
+//! - [`write_bbl_log`] - Write a parsed log's main and slow frames back to blackbox format
 //!
 //! ## Data Types
 //! - [`BBLLog`] - Complete parsed log with all frames and metadata
 //! - [`ExportOptions`] - Configuration for export operations
-//! - [`ExportReport`] - Results of export operations with output paths
-//! - [`DecodedFrame`] - Individual frame with parsed data
+//! - [`CsvOptions`] - Flight data CSV dialect (delimiter, quoting, padding, line ending)
+//! - [`CompatMode`] - Reproduce another tool's output (`CompatMode::BlackboxDecode`)
+//! - [`TimeRange`] - Window of a log to export ([`ExportOptions::time_range`])
+//! - [`ExportPreset`] - Ready-made export settings for analysis tools ([`ExportOptions::with_preset`])
+//! - [`ParserConfig`] - Parser safety limits (max frames, max failed frames, max log size)
+//! - [`Diagnostics`] - Sink for parser debug output and warnings ([`CollectingDiagnostics`], [`SilentDiagnostics`])
+//! - [`FrameSink`] - Callback receiving each frame as it is decoded ([`ParserConfig::frame_sink`])
+//! - [`ProgressSink`] - Callback receiving decoding progress ([`ParseProgress`], [`ParserConfig::progress`])
+//! - [`ExportReport`] - Results of export operations with output paths, rows and bytes written
+//! - [`BBLError`] - Typed failure causes, returned by the parse and export functions
+//! - [`FrameError`] - Where and why a frame failed to decode (`FrameStats::frame_errors`)
+//! - [`FrameColumns`] - Columnar storage of main frames (`BBLLog::frames`)
+//! - [`FieldTable`] - Interned field names shared by a log's header and frames
+//! - [`DecodedFrame`] - Individual frame with parsed data and typed accessors (`gyro()`, `rc_command()`, `motor(i)`, `vbat_volts(&header)`, ...)
 //! - [`FrameDefinition`] - Frame structure metadata
+//! - [`FieldInfo`] - Unit, signedness, predictor, encoding and [`FieldGroup`] of a field ([`FrameDefinition::field_info`], [`field_unit`])
+//! - [`PidConfig`] - PID gains per axis from the header ([`BBLHeader::pid_config`])
+//! - [`SysConfigValue`] - Typed value of a header in [`BBLHeader::sysconfig`]
+//! - [`FilterConfig`] - Gyro and D-term filter settings from the header ([`BBLHeader::filter_config`])
 //!
 //! ## Export Functions
 //! - [`export_to_csv`] - Export flight data to CSV format
+//! - [`export_logs_to_csv`] - Export every log of a file to one CSV with a `log_number` column ([`ConcatCsvWriter`])
 //! - [`export_to_gpx`] - Export GPS data to GPX format
+//! - [`export_to_kml`] - Export GPS data to KML for Google Earth
+//! - [`export_gps_to_csv`] - Export GPS frames to a blackbox_decode-style `.gps.csv`
 //! - [`export_to_event`] - Export event data to JSON format
+//! - [`export_csv_to_writer`], [`export_headers_to_writer`], [`export_gpx_to_writer`], [`export_events_to_writer`] - Export to any `std::io::Write` sink
+//! - [`export_headers_json_to_writer`] - Export headers as typed JSON ([`HeadersJsonExporter`])
+//! - [`export_ndjson_to_writer`] - Export main frames as NDJSON; [`NdjsonWriter`] streams frames while parsing
+//! - `export_to_parquet` / `export_parquet_to_writer` - Export flight data to Parquet (`parquet` feature)
+//! - [`export_registered`] - Run the custom [`Exporter`]s registered in [`ExportOptions::exporters`]
+//! - [`ExportFile`] - Output file honoring [`ExportOptions::compress`] (`.gz` names via [`ExportOptions::output_path`])
 //! - [`compute_export_paths`] - Helper for consistent path computation
+//! - [`resolve_export_base_name`] - Apply [`ExportOptions::overwrite`] ([`OverwritePolicy`]) to a log's output name
 //!
 //! ## Filtering Functions
 //! - [`should_skip_export`] - Determine if log should be skipped based on heuristics
 //! - [`has_minimal_gyro_activity`] - Detect ground tests vs actual flights
+//! - [`should_skip_export_with_thresholds`] - [`should_skip_export`] with custom [`FilterThresholds`] ([`ExportOptions::filter_thresholds`])
 //! - [`calculate_range`] - Calculate gyro axis range (max - min) for scale-independent analysis
 //! - [`calculate_variance`] - DEPRECATED: Statistical helper (no longer used; kept for backward compatibility)
 //!
+//! ## Analysis Functions
+//! - [`BBLLog::frame_at_time`] / [`BBLLog::frames_in_range`] - Time lookups by binary search over the log's time index ([`FrameColumns::row_at_time`], [`FrameColumns::rows_in_range`])
+//! - [`BBLLog::field_limits`] - Per-field min/max over main frames (`--limits`)
+//! - [`BBLLog::field_stats`] - Per-field count, min/max, mean, standard deviation and peak time ([`FieldStats`], `stats` subcommand)
+//! - [`BBLLog::throttle_percent`] - Throttle percent per main frame
+//! - [`BBLLog::cell_count`] / [`BBLLog::vbat_per_cell`] - Battery cell count and voltage per cell
+//! - [`BBLLog::flight_summary`] - Duration, max altitude/speed/current/gyro, min voltage, mAh, failsafes and GPS trip ([`FlightSummary`])
+//! - [`BBLLog::link_health`] - Failsafe entries and time, RX loss count and longest RX loss ([`LinkHealth`])
+//! - [`BBLLog::rssi_stats`] - RSSI/LQ min, mean, percentiles and a downsampled series ([`RssiStats`])
+//! - [`BBLLog::motor_analysis`] - Motor saturation periods and possible desyncs ([`MotorAnalysis`])
+//! - `BBLLog::spectrum` / `BBLLog::spectrum_by_throttle` - Noise PSD of a field, overall or per throttle range (`spectrum` feature)
+//! - `BBLLog::noise_heatmap` - Throttle-by-frequency noise matrix with CSV/JSON output (`NoiseHeatmap`, `spectrum` feature)
+//! - `BBLLog::step_response` - Setpoint-to-gyro step response per axis with overshoot, delay and rise time (`StepResponse`, `spectrum` feature)
+//! - [`BBLLog::estimate_attitude`] - Roll, pitch and heading per main frame from a simulated IMU ([`AttitudeEstimator`])
+//! - [`BBLLog::climb_rate`] - Smoothed vertical speed from `baroAlt` or GPS altitude ([`ClimbRate`], [`ClimbRateFilter`])
+//! - [`BBLLog::gps_trip_stats`] - Distance flown, farthest distance from home, top speed and highest GPS altitude ([`GpsTripStats`])
+//! - [`BBLLog::home_distances`] - Ground and straight-line distance from home of every GPS fix
+//! - [`BBLLog::magnetic_heading`] - Tilt-compensated true heading per main frame from `magADC` and a declination
+//! - [`BBLLog::battery_analysis`] - Voltage sag under throttle, resting vs loaded voltage and per-cell figures ([`BatteryAnalysis`])
+//! - [`BBLLog::mah_used`] / [`BBLLog::energy_cumulative_mah`] - Charge drawn from the battery ([`EnergyIntegrator`], also used for the CSV's `energyCumulative (mAh)`)
+//! - [`BBLLog::resample`] - Fields interpolated to a uniform sample rate ([`ResampleMethod`], [`ResampledData`])
+//! - [`BBLLog::setpoint_deg_per_sec`] - Setpoint computed from `rcCommand` and the rate headers
+//! - `BBLLog::to_record_batch` / `BBLLog::arrow_schema` - Main frames as an Arrow record batch with unit metadata (`arrow` feature)
+//!
+//! ## Introspection
+//! - [`capabilities()`] - Crate version, enabled features, supported encodings/predictors and export formats
+//!
 //! ## Conversion Utilities
 //! - [`convert_amperage_to_amps`] - Convert raw amperage to amps
+//! - [`convert_amperage_with_meter`] - Convert raw amperage to amps, applying the `currentSensor` calibration ([`BBLHeader::current_meter`]) to ADC readings
+//! - [`convert_throttle_to_virtual_amps`] - Model the current drawn at a throttle like the Cleanflight virtual current meter
 //! - [`convert_vbat_to_volts`] - Convert raw voltage to volts
+//! - [`convert_motor_to_percent`] - Normalize motor output to the `motorOutput` range
+//! - [`convert_rssi_to_percent`] - Convert raw `rssi` (0-1023) to percent
+//! - [`convert_throttle_to_percent`] - Convert `rcCommand[3]` to throttle percent
+//! - [`convert_gyro_to_deg_per_sec`] - Convert raw gyro to deg/s with the `gyro_scale` header ([`BBLHeader::gyro_scale`])
+//! - [`convert_rc_command_to_setpoint`] - Apply a [`RatesType`] rate curve to an RC command
+//! - [`convert_acc_to_g`] - Convert raw accelerometer values to g with the `acc_1G` header ([`BBLHeader::acc_1g`])
 //! - [`format_flight_mode_flags`] - Format flight mode as human-readable text
 //! - [`format_state_flags`] - Format state flags as human-readable text
 //! - [`format_failsafe_phase`] - Format failsafe phase as text
+//! - [`format_flag_field`] - Format any flag or state field for the [`FirmwareKind`] that logged it (EmuFlight and INAV flight modes, INAV `navState`, Rotorflight flight modes and `governorState`)
+//! - [`betaflight_debug_mode_name`] - Name of a Betaflight `debug_mode` value
+//! - [`betaflight_feature_names`] - Names of the features in a `features` bitmask ([`BBLHeader::features`])
 
 // Module declarations
+pub mod analysis;
+pub mod capabilities;
 pub mod conversion;
 pub mod error;
 pub mod export;
 pub mod filters;
 pub mod parser;
 pub mod types;
+pub mod writer;
 
 // Re-export everything from modules for convenience
 // This maintains backward compatibility while keeping the implementation flexible
 #[allow(ambiguous_glob_reexports)]
+pub use analysis::*;
+#[allow(ambiguous_glob_reexports)]
+pub use capabilities::*;
+#[allow(ambiguous_glob_reexports)]
 pub use conversion::*;
 #[allow(ambiguous_glob_reexports)]
 pub use error::*;
@@ -99,6 +194,11 @@ pub use filters::*;
 pub use parser::*;
 #[allow(ambiguous_glob_reexports)]
 pub use types::*;
+#[allow(ambiguous_glob_reexports)]
+pub use writer::*;
 
-// Re-export Result type for convenience
-pub use anyhow::Result;
+// Re-export the Arrow crates so callers build against the same versions
+#[cfg(feature = "arrow")]
+pub use arrow_array;
+#[cfg(feature = "arrow")]
+pub use arrow_schema;
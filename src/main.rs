@@ -1,24 +1,40 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::{Arg, Command};
+use clap_complete::{generate, Shell};
 use glob::glob;
-use std::collections::{HashMap, HashSet};
+use indicatif::{ProgressBar, ProgressStyle};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::HashSet;
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 
 // Import export functions from crate library
 use bbl_parser::export::{
-    corrected_session_base_name, export_to_csv, export_to_event, export_to_gpx,
-    firmware_prefix_for_revision, vendor_name_for_prefix,
+    compute_export_paths, corrected_session_base_name, export_gps_to_csv, export_registered,
+    export_to_csv, export_to_event, export_to_gpx, export_to_kml, firmware_prefix_for_revision,
+    resolve_export_base_name, vendor_name_for_prefix, ConcatCsvWriter, ExportFile, Exporter,
+    HeadersJsonExporter, NdjsonWriter,
 };
 
 // Import parser functions from crate library - using crate's unified implementations
-use bbl_parser::parser::parse_single_log;
+use bbl_parser::parser::{
+    decompress_bbl_reader, is_compressed_log_path, log_byte_ranges, log_byte_ranges_with_config,
+    parse_bbl_bytes_all_logs, parse_bbl_bytes_headers_only, parse_single_log, read_bbl_file,
+    validate_bbl_bytes, write_log_slice, write_merged_logs, write_trimmed_log, BBLLogReader,
+    LogValidation, ParseMode, ParseProgress, ParserConfig, DEFAULT_MAX_FAILED_FRAMES,
+    DEFAULT_MAX_FRAMES,
+};
+
+// Import analysis results printed per log
+use bbl_parser::analysis::FlightSummary;
 
 // Import filtering functions from crate library for export heuristics
-use bbl_parser::filters::should_skip_export;
+use bbl_parser::filters::{should_skip_export_with_thresholds, FilterThresholds};
 
 // Import types from crate library
-use bbl_parser::types::BBLLog;
+use bbl_parser::types::{BBLHeader, BBLLog, FrameErrorReason, FrameStats};
 
 // Test-only imports
 #[cfg(test)]
@@ -27,10 +43,13 @@ use bbl_parser::conversion::{
     format_flight_mode_flags, format_state_flags,
 };
 #[cfg(test)]
-use bbl_parser::types::{BBLHeader, DecodedFrame, FrameDefinition, FrameStats};
+use bbl_parser::types::{DecodedFrame, FrameDefinition};
 
-// Import ExportOptions from crate library
-use bbl_parser::ExportOptions;
+// Import export option types from crate library
+use bbl_parser::{
+    CompatMode, CsvOptions, ExportOptions, ExportPreset, GapFillPolicy, OverwritePolicy, TimeRange,
+    DEFAULT_GPS_GAP_THRESHOLD_US,
+};
 
 // Build version string with semver + git info
 // Format: "0.9.0 14be1ee (2025-12-04)"
@@ -43,18 +62,226 @@ const VERSION_STR: &str = concat!(
     ")"
 );
 
+/// Input path that selects stdin instead of a file
+const STDIN_PATH: &str = "-";
+
+/// Output base name used for stdin input when `--basename` is not given
+const DEFAULT_STDIN_BASENAME: &str = "stdin";
+
 /// Maximum recursion depth to prevent stack overflow
 const MAX_RECURSION_DEPTH: usize = 100;
 
+/// Config file looked up in the current directory, then in the user config directory
+const CONFIG_FILE_NAME: &str = "bbl_parser.toml";
+
+/// Values of `--csv-dialect` and the `csv-dialect` config key
+const CSV_DIALECTS: [&str; 3] = ["blackbox", "rfc4180", "tsv"];
+
+/// What the CLI does with each input file, selected by the subcommand
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum RunMode {
+    /// Print log statistics and export (`export`, or no subcommand)
+    #[default]
+    Export,
+    /// Print firmware, craft and field definitions (`info`)
+    Info,
+    /// Print frame statistics and per-field statistics (`stats`)
+    Stats,
+    /// Copy each log to its own file (`split`)
+    Split,
+    /// Print a corruption report of each log (`validate`)
+    Validate,
+}
+
+/// Per-log processing switches that are not export options
+#[derive(Debug, Clone, Default)]
+struct ProcessOptions {
+    /// Subcommand being run
+    mode: RunMode,
+    /// Only process this log (1-based) of each file
+    index: Option<usize>,
+    /// Enable debug output
+    debug: bool,
+    /// Repair backwards time jumps before export
+    repair_timestamps: bool,
+    /// Print per-field min/max after the log statistics
+    limits: bool,
+    /// Parser safety limits
+    parser: ParserConfig,
+    /// Write all logs of a file into one CSV with a `log_number` column
+    concat: bool,
+    /// Start each log's time at 0 in the concatenated CSV
+    rebase_time: bool,
+    /// Decoding progress of the current log, fed by `parser.progress`
+    progress: Option<ProgressBar>,
+}
+
+/// Export defaults from `bbl_parser.toml`; command-line flags take precedence.
+/// Keys are named after the flags they stand for.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct ConfigFile {
+    output_dir: Option<String>,
+    csv_dialect: Option<String>,
+    gpx: bool,
+    gps_csv: bool,
+    kml: bool,
+    event: bool,
+    ndjson: bool,
+    headers_json: bool,
+    force_export: bool,
+    filter: FilterConfig,
+}
+
+/// `[filter]` table of the config file
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct FilterConfig {
+    /// Seconds below which logs are skipped
+    min_duration: Option<f64>,
+    /// Seconds below which logs need `min-density`
+    short_duration: Option<f64>,
+    /// Frames per second required of short logs
+    min_density: Option<f64>,
+    min_gyro_range: Option<f64>,
+    /// Frames required of logs without duration information
+    min_frames: Option<u32>,
+}
+
+impl FilterConfig {
+    fn thresholds(&self) -> FilterThresholds {
+        let defaults = FilterThresholds::default();
+        FilterThresholds {
+            min_duration_s: self.min_duration.unwrap_or(defaults.min_duration_s),
+            short_duration_s: self.short_duration.unwrap_or(defaults.short_duration_s),
+            min_data_density_fps: self.min_density.unwrap_or(defaults.min_data_density_fps),
+            min_gyro_range: self.min_gyro_range.unwrap_or(defaults.min_gyro_range),
+            fallback_min_frames: self.min_frames.unwrap_or(defaults.fallback_min_frames),
+        }
+    }
+}
+
+/// Outcome of one input file for `--json-summary`
+#[derive(Debug, Default)]
+struct FileSummary {
+    /// Input path as given, or `-` for stdin
+    path: String,
+    /// Why the file failed, if it did
+    error: Option<String>,
+    logs: Vec<LogSummary>,
+}
+
+/// Outcome of one log for `--json-summary`
+#[derive(Debug, Default)]
+struct LogSummary {
+    log_number: usize,
+    total_logs: usize,
+    /// Frame counts, or `None` when the log was not decoded
+    stats: Option<FrameStats>,
+    /// Headline numbers of the flight, when the log was decoded
+    flight: Option<FlightSummary>,
+    /// Why exports were skipped, if they were
+    skip_reason: Option<String>,
+    exported: Vec<PathBuf>,
+    export_errors: Vec<String>,
+}
+
+impl FileSummary {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "path": self.path,
+            "error": self.error,
+            "logs": self.logs.iter().map(LogSummary::to_json).collect::<Vec<_>>(),
+        })
+    }
+}
+
+impl LogSummary {
+    fn new(log_number: usize, total_logs: usize) -> Self {
+        Self {
+            log_number,
+            total_logs,
+            ..Default::default()
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let frames = self.stats.as_ref().map(|stats| {
+            serde_json::json!({
+                "I": stats.i_frames,
+                "P": stats.p_frames,
+                "H": stats.h_frames,
+                "G": stats.g_frames,
+                "E": stats.e_frames,
+                "S": stats.s_frames,
+                "total": stats.total_frames,
+            })
+        });
+        let stats = self.stats.as_ref();
+        serde_json::json!({
+            "log_number": self.log_number,
+            "total_logs": self.total_logs,
+            "frames": frames,
+            "duration_us": stats.map(|stats| stats.end_time_us.saturating_sub(stats.start_time_us)),
+            "failed_frames": stats.map(|stats| stats.failed_frames),
+            "frame_errors": stats.map(|stats| {
+                stats
+                    .frame_errors
+                    .iter()
+                    .map(|error| {
+                        serde_json::json!({
+                            "offset": error.offset,
+                            "frame_type": error.frame_type.to_string(),
+                            "reason": error.reason.to_string(),
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            }),
+            "missing_iterations": stats.map(|stats| stats.missing_iterations),
+            "truncated": stats.map(|stats| stats.truncated),
+            "ended_cleanly": stats.map(|stats| stats.ended_cleanly),
+            "flight_summary": self.flight,
+            "skipped": self.skip_reason.is_some(),
+            "skip_reason": self.skip_reason,
+            "exported": self
+                .exported
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>(),
+            "export_errors": self.export_errors,
+        })
+    }
+}
+
+/// Controls how input directories are traversed when expanding input paths.
+#[derive(Debug, Clone, Copy)]
+struct ScanOptions {
+    /// Maximum directory depth to descend below each input directory
+    max_depth: usize,
+    /// Follow symbolic links found while walking directories
+    follow_symlinks: bool,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: MAX_RECURSION_DEPTH,
+            follow_symlinks: false,
+        }
+    }
+}
+
 /// Expand input paths to a list of BBL files.
 /// If a path is a file, add it directly (will be filtered later for BBL/BFL/TXT extension).
 /// If a path is a directory, recursively find all BBL files within it.
 /// If a path contains glob patterns, expand them first.
+/// Paths given explicitly are always resolved; `scan` only affects directory traversal.
 fn expand_input_paths(
     input_paths: &[String],
     visited: &mut HashSet<PathBuf>,
+    scan: &ScanOptions,
 ) -> Result<Vec<String>> {
-    expand_input_paths_with_depth(input_paths, visited, 0)
+    expand_input_paths_with_depth(input_paths, visited, 0, scan)
 }
 
 /// Internal function with depth tracking for recursion protection
@@ -62,6 +289,7 @@ fn expand_input_paths_with_depth(
     input_paths: &[String],
     visited: &mut HashSet<PathBuf>,
     depth: usize,
+    scan: &ScanOptions,
 ) -> Result<Vec<String>> {
     if depth > MAX_RECURSION_DEPTH {
         return Err(anyhow::anyhow!(
@@ -86,6 +314,7 @@ fn expand_input_paths_with_depth(
                                         &[path_str.to_string()],
                                         visited,
                                         depth + 1,
+                                        scan,
                                     )?;
                                     bbl_files.extend(sub_result);
                                 }
@@ -122,7 +351,7 @@ fn expand_input_paths_with_depth(
                     // It's a directory, find all BBL files recursively
                     // Don't add to visited here since find_bbl_files_in_dir_with_depth will handle it
                     let mut dir_bbl_files =
-                        find_bbl_files_in_dir_with_depth(&canonical_path, visited, depth + 1)?;
+                        find_bbl_files_in_dir_with_depth(&canonical_path, visited, 0, scan)?;
                     bbl_files.append(&mut dir_bbl_files);
                 } else {
                     // Path doesn't exist or isn't accessible
@@ -146,11 +375,14 @@ fn expand_input_paths_with_depth(
     Ok(bbl_files)
 }
 
-/// Recursively find all BBL files in a directory, protecting against symlink cycles and depth overflow
+/// Recursively find all BBL files in a directory, protecting against symlink cycles and depth overflow.
+/// `depth` is the directory level below the input directory; subdirectories deeper than
+/// `scan.max_depth` are skipped, and symlinked entries are skipped unless `scan.follow_symlinks` is set.
 fn find_bbl_files_in_dir_with_depth(
     dir_path: &Path,
     visited: &mut HashSet<PathBuf>,
     depth: usize,
+    scan: &ScanOptions,
 ) -> Result<Vec<String>> {
     if depth > MAX_RECURSION_DEPTH {
         return Err(anyhow::anyhow!(
@@ -199,26 +431,42 @@ fn find_bbl_files_in_dir_with_depth(
                 };
                 let path = entry.path();
 
+                // file_type() does not follow symlinks, so links are detected here
+                let is_symlink = entry
+                    .file_type()
+                    .map(|file_type| file_type.is_symlink())
+                    .unwrap_or(false);
+                if is_symlink && !scan.follow_symlinks {
+                    continue;
+                }
+
                 match path.canonicalize() {
                     Ok(canonical_path) => {
                         if visited.contains(&canonical_path) {
                             continue;
                         }
-                        visited.insert(canonical_path.clone());
 
                         if canonical_path.is_dir() {
-                            // Recursively search subdirectories
+                            if depth >= scan.max_depth {
+                                continue;
+                            }
+                            // Recursively search subdirectories (the callee marks them visited)
                             let mut sub_bbl_files = find_bbl_files_in_dir_with_depth(
                                 &canonical_path,
                                 visited,
                                 depth + 1,
+                                scan,
                             )?;
                             bbl_files.append(&mut sub_bbl_files);
                         } else if canonical_path.is_file() {
+                            visited.insert(canonical_path.clone());
                             // Check if it's a BBL file (only BBL for directories, not TXT)
                             if let Some(extension) = canonical_path.extension() {
                                 let ext_lower = extension.to_string_lossy().to_ascii_lowercase();
-                                if ext_lower == "bbl" || ext_lower == "bfl" {
+                                if ext_lower == "bbl"
+                                    || ext_lower == "bfl"
+                                    || is_compressed_log_path(&canonical_path)
+                                {
                                     if let Some(path_str) = canonical_path.to_str() {
                                         bbl_files.push(path_str.to_string());
                                     }
@@ -252,15 +500,305 @@ fn find_bbl_files_in_dir_with_depth(
     Ok(bbl_files)
 }
 
-#[allow(dead_code)]
-fn should_have_frame(frame_index: u32, sysconfig: &HashMap<String, i32>) -> bool {
-    let frame_interval_i = sysconfig.get("frameIntervalI").copied().unwrap_or(32);
-    let frame_interval_p_num = sysconfig.get("frameIntervalPNum").copied().unwrap_or(1);
-    let frame_interval_p_denom = sysconfig.get("frameIntervalPDenom").copied().unwrap_or(1);
-
-    let left_side = ((frame_index % frame_interval_i as u32) + frame_interval_p_num as u32 - 1)
-        % frame_interval_p_denom as u32;
-    left_side < frame_interval_p_num as u32
+/// Arguments selecting the input files, shared by every subcommand
+fn input_args() -> Vec<Arg> {
+    vec![
+        Arg::new("files")
+            .help("BBL files or directories to parse. Direct file paths: .BBL, .BFL, .TXT extensions supported. Directories: recursively finds .BBL/.BFL files only (TXT files must be specified directly). Case-insensitive, supports globbing. Use - to read from stdin.")
+            .required(false)
+            .num_args(1..)
+            .index(1),
+        Arg::new("debug")
+            .long("debug")
+            .help("Enable debug output and detailed parsing information")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("basename")
+            .long("basename")
+            .help("Output file base name when reading from stdin (default: stdin)")
+            .value_name("NAME"),
+        Arg::new("max-depth")
+            .long("max-depth")
+            .help("Maximum directory depth to search below input directories (default: 100)")
+            .value_name("N")
+            .value_parser(clap::value_parser!(u64).range(0..=MAX_RECURSION_DEPTH as u64)),
+        Arg::new("follow-symlinks")
+            .long("follow-symlinks")
+            .help("Follow symbolic links when searching input directories")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("index")
+            .long("index")
+            .help("Only process log N (1-based) of each file; other logs are not decoded")
+            .value_name("N")
+            .value_parser(clap::value_parser!(u64).range(1..)),
+    ]
+}
+
+/// Arguments controlling how logs are decoded (`export`, `info` and `stats`)
+fn decode_args() -> Vec<Arg> {
+    vec![
+        Arg::new("strict")
+            .long("strict")
+            .help("Abort a file on the first undecodable or invalid frame and exit non-zero")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("max-frames")
+            .long("max-frames")
+            .help("Stop decoding a log after N frames (default: 1000000)")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize)),
+        Arg::new("max-failed-frames")
+            .long("max-failed-frames")
+            .help("Stop decoding a log after N undecodable frames (default: 10000)")
+            .value_name("N")
+            .value_parser(clap::value_parser!(usize)),
+        Arg::new("salvage")
+            .long("salvage")
+            .help("Also decode logs whose leading headers were lost, starting from their field definitions")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("repair-timestamps")
+            .long("repair-timestamps")
+            .help("Repair backwards time jumps using loopIteration and looptime before export")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("limit")
+            .long("limit")
+            .help("Stop each log after N main frames (after decimation)")
+            .value_name("N")
+            .value_parser(clap::value_parser!(u64).range(1..)),
+        Arg::new("decimate")
+            .long("decimate")
+            .help("Keep every Kth main frame, e.g. 8 turns 8 kHz logging into 1 kHz rows")
+            .value_name("K")
+            .value_parser(clap::value_parser!(u64).range(1..)),
+        Arg::new("json-summary")
+            .long("json-summary")
+            .help("Write a JSON summary of the run (frame counts, durations, skip decisions, exported files, errors) to FILE, or - to print it last on stdout")
+            .value_name("FILE"),
+    ]
+}
+
+/// Arguments controlling exports (`export` and the bare invocation)
+fn export_args() -> Vec<Arg> {
+    vec![
+        Arg::new("config")
+            .long("config")
+            .help("Read export defaults from FILE instead of ./bbl_parser.toml or ~/.config/bbl_parser/bbl_parser.toml")
+            .value_name("FILE")
+            .conflicts_with("no-config"),
+        Arg::new("no-config")
+            .long("no-config")
+            .help("Ignore bbl_parser.toml config files")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("output-dir")
+            .long("output-dir")
+            .help("Directory for output files (default: same as input file)")
+            .value_name("DIR"),
+        Arg::new("gpx")
+            .long("gpx")
+            .help("Export GPS data (G and H frames) to GPX XML files")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("gps")
+            .long("gps")
+            .help("Alias for --gpx: Export GPS data to GPX XML files")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("gps-csv")
+            .long("gps-csv")
+            .help("Export GPS frames to .gps.csv files (as blackbox_decode does)")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("merge-gps")
+            .long("merge-gps")
+            .help("Append the nearest GPS fix as extra columns of the flight data CSV")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("home-distance")
+            .long("home-distance")
+            .help("Add the distance from home (m) to .gps.csv and merged GPS columns")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("kml")
+            .long("kml")
+            .help("Export GPS data to KML files for Google Earth")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("event")
+            .long("event")
+            .help("Export event data (E frames) to JSON files")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("event-column")
+            .long("event-column")
+            .help("Add an event column naming the E-frame events at each flight data CSV row")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("ndjson")
+            .long("ndjson")
+            .help("Stream decoded frames as NDJSON (one JSON object per frame) while parsing")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("no-csv")
+            .long("no-csv")
+            .help("Skip the flight data and headers CSVs; with only --ndjson, frames are streamed without being kept in memory")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("headers-json")
+            .long("headers-json")
+            .help("Export headers as typed JSON (numbers, arrays such as rollPID, strings)")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("force-export")
+            .long("force-export")
+            .help("Force export of all logs, bypassing smart filtering")
+            .long_help(
+                "Force export of all logs, bypassing smart filtering.\n\n\
+                Normal filtering behavior:\n\
+                  - Logs <5s: Always skipped\n\
+                  - Logs 5-15s: Kept if data density >1500fps\n\
+                  - Logs >15s or without duration: Checked for gyro activity (ground test detection)"
+            )
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("overwrite")
+            .long("overwrite")
+            .help("Replace existing output files (default)")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with_all(["skip-existing", "rename"]),
+        Arg::new("skip-existing")
+            .long("skip-existing")
+            .help("Skip logs whose output files already exist, without decoding them")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with("rename"),
+        Arg::new("rename")
+            .long("rename")
+            .help("Write to <name>-N.csv and so on when output files already exist")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("limits")
+            .long("limits")
+            .help("Print the minimum and maximum value of every field for each log")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("gap-fill")
+            .long("gap-fill")
+            .help("How to write main frames missing from the log to CSV (default: leave)")
+            .value_name("POLICY")
+            .value_parser(["leave", "repeat", "interpolate"]),
+        Arg::new("raw-units")
+            .long("raw-units")
+            .help("Write every CSV field as its decoded integer (no volts/amps, flag text or high-resolution scaling)")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("debug-mode-names")
+            .long("debug-mode-names")
+            .help("Name debug[n] CSV columns after the log's debug_mode, e.g. \"debug[0] (GYRO_SCALED)\"")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("fields")
+            .long("fields")
+            .help("Comma-separated flight data CSV columns to write, in order; * and ? wildcards (e.g. \"time,gyroADC[*],motor[*]\")")
+            .value_name("PATTERNS")
+            .value_delimiter(','),
+        Arg::new("split-frame-types")
+            .long("split-frame-types")
+            .help("Also write S and G frames at their own rate to .S.csv and .G.csv")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("compress")
+            .long("compress")
+            .help("Gzip CSV, event and NDJSON output as it is written (.csv.gz; needs the gzip feature)")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("concat")
+            .long("concat")
+            .help("Write all logs of a file into one CSV with a log_number column instead of .01.csv, .02.csv, ...")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("rebase-time")
+            .long("rebase-time")
+            .help("With --concat, start each log's time (us) at 0")
+            .requires("concat")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("preset")
+            .long("preset")
+            .help("Export settings for an analysis tool: pidtoolbox (blackbox_decode CSV per log, every log exported) or pid-analyzer (the same, with debug columns named after debug_mode)")
+            .value_name("TOOL")
+            .value_parser(["pidtoolbox", "pid-analyzer"]),
+        Arg::new("compat")
+            .long("compat")
+            .help("Reproduce another tool's output exactly: blackbox_decode (numbered files, no headers CSV, its columns and number formatting)")
+            .value_name("TOOL")
+            .value_parser(["native", "blackbox_decode"]),
+        Arg::new("csv-dialect")
+            .long("csv-dialect")
+            .help("Flight data CSV layout: blackbox (padded, \", \"-separated; default), rfc4180 or tsv")
+            .value_name("DIALECT")
+            .value_parser(CSV_DIALECTS),
+        Arg::new("motor-percent")
+            .long("motor-percent")
+            .help("Add motor[n] (%) CSV columns normalized to the motorOutput range")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("gpx-gap")
+            .long("gpx-gap")
+            .help("Start a new GPX track segment after a GPS gap longer than this (default: 2, 0 disables)")
+            .value_name("SECONDS")
+            .value_parser(clap::value_parser!(f64)),
+        Arg::new("throttle-percent")
+            .long("throttle-percent")
+            .help("Add a throttle (%) CSV column honoring min/max throttle and 3D mode")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("gyro-deg-per-sec")
+            .long("gyro-deg-per-sec")
+            .help("Write gyro CSV columns in deg/s using the gyro_scale header")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("acc-g")
+            .long("acc-g")
+            .help("Write accSmooth CSV columns in g using the acc_1G header")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("setpoint-deg-per-sec")
+            .long("setpoint-deg-per-sec")
+            .help("Add setpoint[0..2] (deg/s) CSV columns computed from rcCommand and the rate headers")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("vbat-per-cell")
+            .long("vbat-per-cell")
+            .help("Add a vbatPerCell (V) CSV column using the detected battery cell count")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("simulate-imu")
+            .long("simulate-imu")
+            .help("Add roll, pitch and heading (deg) CSV columns estimated from gyro and accelerometer")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("mag-heading")
+            .long("mag-heading")
+            .help("Add a tilt-compensated heading (deg) CSV column when the log has magADC fields")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("declination")
+            .long("declination")
+            .help("Magnetic declination added to heading (deg) columns, in degrees.minutes (e.g. -12.58)")
+            .value_name("DEG.MIN")
+            .allow_hyphen_values(true)
+            .value_parser(parse_declination_degrees_minutes)
+            .conflicts_with("declination-dec"),
+        Arg::new("declination-dec")
+            .long("declination-dec")
+            .help("Magnetic declination added to heading (deg) columns, in decimal degrees (e.g. -12.97)")
+            .value_name("DEGREES")
+            .allow_hyphen_values(true)
+            .value_parser(clap::value_parser!(f64)),
+        Arg::new("climb-rate")
+            .long("climb-rate")
+            .help("Add a smoothed climbRate (m/s) CSV column from baroAlt, or GPS altitude without a barometer")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("simulate-current-meter")
+            .long("simulate-current-meter")
+            .help("Add amperageLatest (A) and energyCumulative (mAh) from a virtual current meter when the log has no current sensor")
+            .action(clap::ArgAction::SetTrue),
+        Arg::new("sim-current-meter-offset")
+            .long("sim-current-meter-offset")
+            .help("Virtual current meter offset in 0.01 A (default: currentSensor header)")
+            .value_name("OFFSET")
+            .value_parser(clap::value_parser!(i32)),
+        Arg::new("sim-current-meter-scale")
+            .long("sim-current-meter-scale")
+            .help("Virtual current meter scale (default: currentSensor header, else 400)")
+            .value_name("SCALE")
+            .value_parser(clap::value_parser!(i32)),
+    ]
+}
+
+/// `--start`/`--end`: the window of each log to export, or to keep when splitting
+fn time_range_args() -> [Arg; 2] {
+    [
+        Arg::new("start")
+            .long("start")
+            .help("Only from this far into each log: seconds or [H:]M:SS, e.g. 1:05 (CSV and GPS exports; split trims the logs)")
+            .value_name("TIME")
+            .value_parser(parse_time_offset),
+        Arg::new("end")
+            .long("end")
+            .help("Only up to this far into each log: seconds or [H:]M:SS, e.g. 2:30 (CSV and GPS exports; split trims the logs)")
+            .value_name("TIME")
+            .value_parser(parse_time_offset),
+    ]
 }
 
 fn build_command() -> Command {
@@ -269,6 +807,7 @@ fn build_command() -> Command {
 
     Command::new(env!("CARGO_PKG_NAME"))
         .about(about_text)
+        .after_help("Without a subcommand, FILES are exported as with `export`.")
         .arg(
             Arg::new("version")
                 .short('V')
@@ -278,61 +817,101 @@ fn build_command() -> Command {
                 .exclusive(true),
         )
         .arg(
-            Arg::new("files")
-                .help("BBL files or directories to parse. Direct file paths: .BBL, .BFL, .TXT extensions supported. Directories: recursively finds .BBL/.BFL files only (TXT files must be specified directly). Case-insensitive, supports globbing.")
-                .required(false)
-                .num_args(1..)
-                .index(1),
+            Arg::new("info")
+                .long("info")
+                .help("Only print firmware, craft and field definitions of each log, without decoding frames (same as `info`)")
+                .action(clap::ArgAction::SetTrue),
         )
         .arg(
-            Arg::new("debug")
-                .long("debug")
-                .help("Enable debug output and detailed parsing information")
+            Arg::new("validate")
+                .long("validate")
+                .help("Decode without exporting and report failed frames, stray bytes, loop iteration gaps, time regressions and truncated logs (same as `validate`)")
+                .conflicts_with("info")
                 .action(clap::ArgAction::SetTrue),
         )
-        .arg(
-            Arg::new("output-dir")
-                .long("output-dir")
-                .help("Directory for output files (default: same as input file)")
-                .value_name("DIR"),
+        .args(input_args())
+        .args(decode_args())
+        .args(export_args())
+        .args(time_range_args())
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("export")
+                .visible_alias("parse")
+                .about("Print log statistics and export CSV and the selected formats (default)")
+                .args(input_args())
+                .args(decode_args())
+                .args(export_args())
+                .args(time_range_args()),
         )
-        .arg(
-            Arg::new("gpx")
-                .long("gpx")
-                .help("Export GPS data (G and H frames) to GPX XML files")
-                .action(clap::ArgAction::SetTrue),
+        .subcommand(
+            Command::new("info")
+                .about("Print firmware, craft and field definitions of each log, without decoding frames")
+                .args(input_args()),
         )
-        .arg(
-            Arg::new("gps")
-                .long("gps")
-                .help("Alias for --gpx: Export GPS data to GPX XML files")
-                .action(clap::ArgAction::SetTrue),
+        .subcommand(
+            Command::new("stats")
+                .about("Print frame statistics and per-field min, max, mean, standard deviation and peak time of each log")
+                .args(input_args())
+                .args(decode_args()),
         )
-        .arg(
-            Arg::new("event")
-                .long("event")
-                .help("Export event data (E frames) to JSON files")
-                .action(clap::ArgAction::SetTrue),
+        .subcommand(
+            Command::new("validate")
+                .about("Decode without exporting and report failed frames, stray bytes, loop iteration gaps, time regressions and truncated logs")
+                .args(input_args()),
         )
-        .arg(
-            Arg::new("force-export")
-                .long("force-export")
-                .help("Force export of all logs, bypassing smart filtering")
-                .long_help(
-                    "Force export of all logs, bypassing smart filtering.\n\n\
-                    Normal filtering behavior:\n\
-                      - Logs <5s: Always skipped\n\
-                      - Logs 5-15s: Kept if data density >1500fps\n\
-                      - Logs >15s or without duration: Checked for gyro activity (ground test detection)"
+        .subcommand(
+            Command::new("split")
+                .about("Write each log of a multi-log file to its own <name>.NN.BBL file, optionally trimmed to --start/--end")
+                .args(input_args())
+                .args(time_range_args())
+                .arg(
+                    Arg::new("output-dir")
+                        .long("output-dir")
+                        .help("Directory for output files (default: same as input file)")
+                        .value_name("DIR"),
+                ),
+        )
+        .subcommand(
+            Command::new("merge")
+                .about("Merge the logs of several files, in the given order, into one multi-log BBL or one continuous CSV")
+                .arg(
+                    Arg::new("files")
+                        .help("BBL files to merge, in flight order")
+                        .required(true)
+                        .num_args(1..)
+                        .index(1),
                 )
-                .action(clap::ArgAction::SetTrue),
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .help("Merged file: a .csv name writes one CSV with continuous time and a log_number column, any other name a multi-log BBL")
+                        .required(true)
+                        .value_name("FILE"),
+                ),
+        )
+        .subcommand(
+            Command::new("completions")
+                .about("Print a shell completion script, e.g. bbl_parser completions bash > /etc/bash_completion.d/bbl_parser")
+                .arg(
+                    Arg::new("shell")
+                        .help("Shell to generate completions for")
+                        .required(true)
+                        .value_parser(clap::value_parser!(Shell)),
+                ),
         )
 }
 
 fn main() -> Result<()> {
-    // Print version at start of every execution context
-    println!("{} {}", env!("CARGO_PKG_NAME"), VERSION_STR);
-    println!();
+    // Print version at start of every execution context, except when the
+    // output is a completion script (subcommands always come first)
+    if std::env::args_os()
+        .nth(1)
+        .map_or(true, |arg| arg != "completions")
+    {
+        println!("{} {}", env!("CARGO_PKG_NAME"), VERSION_STR);
+        println!();
+    }
 
     let matches = build_command().get_matches();
 
@@ -341,37 +920,181 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    if let Some(("completions", sub_matches)) = matches.subcommand() {
+        let shell = *sub_matches
+            .get_one::<Shell>("shell")
+            .expect("shell is required");
+        let mut command = build_command();
+        let name = command.get_name().to_string();
+        generate(shell, &mut command, name, &mut std::io::stdout());
+        return Ok(());
+    }
+
+    if let Some(("merge", sub_matches)) = matches.subcommand() {
+        let files: Vec<&String> = sub_matches
+            .get_many::<String>("files")
+            .expect("files are required")
+            .collect();
+        let output = sub_matches
+            .get_one::<String>("output")
+            .expect("output is required");
+        if let Err(e) = merge_files(&files, Path::new(output)) {
+            eprintln!("Error: {e:#}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Without a subcommand the top-level arguments describe an export
+    let subcommand_name = matches.subcommand_name().map(str::to_owned);
+    let (subcommand, matches) = match matches.subcommand() {
+        Some(subcommand) => subcommand,
+        None if matches.get_flag("info") => ("info", &matches),
+        None if matches.get_flag("validate") => ("validate", &matches),
+        None => ("export", &matches),
+    };
+    let mode = match subcommand {
+        "info" => RunMode::Info,
+        "stats" => RunMode::Stats,
+        "split" => RunMode::Split,
+        "validate" => RunMode::Validate,
+        _ => RunMode::Export,
+    };
+
     let debug = matches.get_flag("debug");
-    let export_gpx = matches.get_flag("gpx") || matches.get_flag("gps");
-    let export_event = matches.get_flag("event");
-    let force_export = matches.get_flag("force-export");
-    let output_dir = matches.get_one::<String>("output-dir").cloned();
+    let decodes = matches!(mode, RunMode::Export | RunMode::Stats);
+    let strict = decodes && matches.get_flag("strict");
+    let json_summary = decodes
+        .then(|| matches.get_one::<String>("json-summary"))
+        .flatten();
+    let scan_options = ScanOptions {
+        max_depth: matches
+            .get_one::<u64>("max-depth")
+            .map(|&depth| depth as usize)
+            .unwrap_or(MAX_RECURSION_DEPTH),
+        follow_symlinks: matches.get_flag("follow-symlinks"),
+    };
 
     // Check if no files were provided and show help
     let file_patterns: Vec<&String> = match matches.get_many::<String>("files") {
         Some(files) => files.collect(),
         None => {
             // No files provided, show help and exit
-            build_command().print_help()?;
+            let mut command = build_command();
+            // Propagates "bbl_parser <subcommand>" into the subcommand usage line
+            command.build();
+            match subcommand_name {
+                Some(name) => command
+                    .find_subcommand_mut(&name)
+                    .expect("subcommand is defined")
+                    .print_help()?,
+                None => command.print_help()?,
+            }
             println!();
             return Ok(());
         }
     };
 
-    let export_options = ExportOptions {
-        csv: true, // CSV export is always enabled for the CLI binary
-        gpx: export_gpx,
-        event: export_event,
-        output_dir: output_dir.clone(),
-        force_export,
+    let process_options = process_options_from(matches, mode);
+    let export_options = match mode {
+        RunMode::Export => {
+            let config = match config_path(matches) {
+                Some(path) => match load_config(&path) {
+                    Ok(config) => {
+                        println!("Using config: {}", path.display());
+                        config
+                    }
+                    Err(e) => {
+                        eprintln!("Error: {e:#}");
+                        std::process::exit(1);
+                    }
+                },
+                None => ConfigFile::default(),
+            };
+            export_options_from(matches, &config)
+        }
+        RunMode::Split => ExportOptions {
+            output_dir: matches.get_one::<String>("output-dir").cloned(),
+            time_range: time_range_from(matches),
+            ..Default::default()
+        },
+        RunMode::Info | RunMode::Stats | RunMode::Validate => ExportOptions::default(),
     };
+    if let TimeRange {
+        start_us: Some(start_us),
+        end_us: Some(end_us),
+    } = export_options.time_range
+    {
+        if start_us > end_us {
+            eprintln!("Error: --start must not be later than --end.");
+            std::process::exit(1);
+        }
+    }
+    if export_options.compress && !cfg!(feature = "gzip") {
+        eprintln!("Error: --compress requires bbl_parser built with the `gzip` feature.");
+        std::process::exit(1);
+    }
 
     let mut processed_files = 0;
+    let mut failed_files = 0;
+    let mut file_summaries = Vec::new();
+
+    // "-" selects stdin; everything else is expanded as a path or glob
+    let read_stdin = file_patterns.iter().any(|p| p.as_str() == STDIN_PATH);
+    let file_patterns: Vec<&String> = file_patterns
+        .into_iter()
+        .filter(|p| p.as_str() != STDIN_PATH)
+        .collect();
 
     if debug {
         println!("Input patterns: {file_patterns:?}");
     }
 
+    if read_stdin {
+        let base_name = matches
+            .get_one::<String>("basename")
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_STDIN_BASENAME);
+        println!("Processing: {STDIN_PATH} (as {base_name})");
+
+        let mut file_summary = FileSummary {
+            path: STDIN_PATH.to_string(),
+            ..Default::default()
+        };
+        match parse_bbl_stdin(
+            base_name,
+            &process_options,
+            &export_options,
+            &mut file_summary.logs,
+        ) {
+            Ok(processed_logs) => {
+                if debug {
+                    println!("Successfully processed {processed_logs} log(s) from stdin");
+                }
+                processed_files += 1;
+            }
+            Err(e) => {
+                eprintln!("Error processing stdin: {e:#}");
+                file_summary.error = Some(format!("{e:#}"));
+                failed_files += 1;
+            }
+        }
+        file_summaries.push(file_summary);
+
+        if file_patterns.is_empty() {
+            if let Some(target) = json_summary {
+                write_json_summary(target, &file_summaries, processed_files, failed_files)?;
+            }
+            if processed_files == 0 || ((strict || mode == RunMode::Validate) && failed_files > 0) {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        println!();
+    } else if matches.contains_id("basename") {
+        eprintln!("Warning: --basename only applies to stdin input (-); ignoring");
+    }
+
     // Expand input paths (files and directories) to a list of BBL files
     let mut visited = HashSet::new();
     let mut input_files = match expand_input_paths(
@@ -380,6 +1103,7 @@ fn main() -> Result<()> {
             .map(|s| s.to_string())
             .collect::<Vec<_>>(),
         &mut visited,
+        &scan_options,
     ) {
         Ok(files) => files,
         Err(e) => {
@@ -421,7 +1145,8 @@ fn main() -> Result<()> {
                 let ext_lower = ext.to_ascii_lowercase();
                 ext_lower == "bbl" || ext_lower == "bfl" || ext_lower == "txt"
             })
-            .unwrap_or(false);
+            .unwrap_or(false)
+            || is_compressed_log_path(&path);
 
         if !valid_extension {
             let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("none");
@@ -457,8 +1182,20 @@ fn main() -> Result<()> {
             .and_then(|n| n.to_str())
             .unwrap_or("unknown");
         println!("Processing: {filename}");
+        if let Some(bar) = &process_options.progress {
+            bar.set_prefix(format!("[{}/{}] ", index + 1, valid_paths.len()));
+        }
 
-        match parse_bbl_file_streaming(path, debug, &export_options) {
+        let mut file_summary = FileSummary {
+            path: path.display().to_string(),
+            ..Default::default()
+        };
+        match parse_bbl_file_streaming(
+            path,
+            &process_options,
+            &export_options,
+            &mut file_summary.logs,
+        ) {
             Ok(processed_logs) => {
                 if debug {
                     println!(
@@ -468,10 +1205,26 @@ fn main() -> Result<()> {
                 processed_files += 1;
             }
             Err(e) => {
-                eprintln!("Error processing {filename}: {e}");
+                eprintln!("Error processing {filename}: {e:#}");
                 eprintln!("Continuing with next file...");
+                file_summary.error = Some(format!("{e:#}"));
+                failed_files += 1;
             }
         }
+        file_summaries.push(file_summary);
+    }
+
+    if let Some(target) = json_summary {
+        write_json_summary(target, &file_summaries, processed_files, failed_files)?;
+    }
+
+    if strict && failed_files > 0 {
+        eprintln!("Error: {failed_files} file(s) failed strict parsing.");
+        std::process::exit(1);
+    }
+    if mode == RunMode::Validate && failed_files > 0 {
+        eprintln!("Error: {failed_files} file(s) failed validation.");
+        std::process::exit(1);
     }
 
     if processed_files == 0 {
@@ -490,6 +1243,294 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Write the `--json-summary` document to `target`, or to stdout for `-`
+fn write_json_summary(
+    target: &str,
+    files: &[FileSummary],
+    processed_files: usize,
+    failed_files: usize,
+) -> Result<()> {
+    let summary = serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "processed_files": processed_files,
+        "failed_files": failed_files,
+        "files": files.iter().map(FileSummary::to_json).collect::<Vec<_>>(),
+    });
+    let json = serde_json::to_string_pretty(&summary)?;
+    if target == STDIN_PATH {
+        println!("{json}");
+    } else {
+        fs::write(target, json + "\n")
+            .with_context(|| format!("Failed to write JSON summary to {target}"))?;
+    }
+    Ok(())
+}
+
+/// Parse a `--start`/`--end` time: seconds (`90`, `12.5`) or `[H:]M:SS[.s]`,
+/// returned in microseconds
+fn parse_time_offset(value: &str) -> std::result::Result<u64, String> {
+    let invalid = || format!("invalid time '{value}': expected seconds or [H:]M:SS");
+    let mut parts = value.trim().rsplit(':');
+    let seconds: f64 = parts
+        .next()
+        .and_then(|seconds| seconds.parse().ok())
+        .filter(|seconds: &f64| seconds.is_finite() && *seconds >= 0.0)
+        .ok_or_else(invalid)?;
+    let mut total_seconds = seconds;
+    for unit in [60.0, 3600.0] {
+        match parts.next() {
+            Some(part) => {
+                let count: u64 = part.parse().map_err(|_| invalid())?;
+                total_seconds += count as f64 * unit;
+            }
+            None => break,
+        }
+    }
+    if parts.next().is_some() {
+        return Err(invalid());
+    }
+    Ok((total_seconds * 1_000_000.0).round() as u64)
+}
+
+/// Parse a `--declination` in blackbox_decode's degrees.minutes format
+/// (`-12.58` is 12°58' west), returned in decimal degrees
+fn parse_declination_degrees_minutes(value: &str) -> std::result::Result<f64, String> {
+    let degrees_minutes: f64 = value
+        .trim()
+        .parse()
+        .ok()
+        .filter(|value: &f64| value.is_finite())
+        .ok_or_else(|| format!("invalid declination '{value}': expected degrees.minutes"))?;
+    let magnitude = degrees_minutes.abs();
+    let minutes = (magnitude.fract() * 100.0).round();
+    if minutes >= 60.0 {
+        return Err(format!(
+            "invalid declination '{value}': minutes must be below 60"
+        ));
+    }
+    Ok((magnitude.trunc() + minutes / 60.0).copysign(degrees_minutes))
+}
+
+/// Window given by `--start`/`--end`
+fn time_range_from(matches: &clap::ArgMatches) -> TimeRange {
+    TimeRange {
+        start_us: matches.get_one::<u64>("start").copied(),
+        end_us: matches.get_one::<u64>("end").copied(),
+    }
+}
+
+/// Per-log switches for `mode` from the parsed arguments of its subcommand
+fn process_options_from(matches: &clap::ArgMatches, mode: RunMode) -> ProcessOptions {
+    let index = matches
+        .get_one::<u64>("index")
+        .map(|&log_number| log_number as usize);
+    // None of these modes takes decoding options
+    if matches!(mode, RunMode::Info | RunMode::Split | RunMode::Validate) {
+        return ProcessOptions {
+            debug: matches.get_flag("debug"),
+            mode,
+            index,
+            ..Default::default()
+        };
+    }
+    let debug = matches.get_flag("debug");
+    let strict = matches.get_flag("strict");
+    let export = mode == RunMode::Export;
+    let mut process = ProcessOptions {
+        debug,
+        repair_timestamps: matches.get_flag("repair-timestamps"),
+        // Export-only flags
+        limits: export && matches.get_flag("limits"),
+        concat: export && matches.get_flag("concat"),
+        rebase_time: export && matches.get_flag("rebase-time"),
+        mode,
+        index,
+        // Debug output would scroll the bar away
+        progress: (!debug).then(log_progress_bar),
+        parser: ParserConfig {
+            max_frames: matches
+                .get_one::<usize>("max-frames")
+                .copied()
+                .unwrap_or(DEFAULT_MAX_FRAMES),
+            max_failed_frames: matches
+                .get_one::<usize>("max-failed-frames")
+                .copied()
+                .unwrap_or(DEFAULT_MAX_FAILED_FRAMES),
+            main_frame_limit: matches.get_one::<u64>("limit").map(|&limit| limit as usize),
+            decimate: matches
+                .get_one::<u64>("decimate")
+                .map_or(1, |&decimate| decimate as usize),
+            mode: if strict {
+                ParseMode::Strict
+            } else {
+                ParseMode::Lenient
+            },
+            salvage: matches.get_flag("salvage"),
+            ..Default::default()
+        },
+    };
+    if let Some(bar) = process.progress.clone() {
+        process.parser.progress = Some(std::sync::Arc::new(move |progress: &ParseProgress| {
+            bar.set_length(progress.total_bytes as u64);
+            bar.set_position(progress.bytes_consumed as u64);
+        }));
+    }
+    process
+}
+
+/// Bar on stderr showing the bytes decoded of the current log; hidden when
+/// stderr is not a terminal
+fn log_progress_bar() -> ProgressBar {
+    let bar = ProgressBar::new(0);
+    bar.set_style(
+        ProgressStyle::with_template("{prefix}{msg} [{bar:40}] {bytes}/{total_bytes} ({eta})")
+            .expect("valid progress template")
+            .progress_chars("=> "),
+    );
+    // Stays out of sight until the first log starts
+    bar.finish_and_clear();
+    bar
+}
+
+/// Config file to read: `--config`, else `bbl_parser.toml` in the current
+/// directory or in `$XDG_CONFIG_HOME/bbl_parser/` (`~/.config/bbl_parser/`)
+fn config_path(matches: &clap::ArgMatches) -> Option<PathBuf> {
+    if matches.get_flag("no-config") {
+        return None;
+    }
+    if let Some(path) = matches.get_one::<String>("config") {
+        return Some(PathBuf::from(path));
+    }
+    let local = PathBuf::from(CONFIG_FILE_NAME);
+    if local.is_file() {
+        return Some(local);
+    }
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    let path = config_dir.join("bbl_parser").join(CONFIG_FILE_NAME);
+    path.is_file().then_some(path)
+}
+
+/// Read and check a config file
+fn load_config(path: &Path) -> Result<ConfigFile> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file {}", path.display()))?;
+    parse_config(&text).with_context(|| format!("Invalid config file {}", path.display()))
+}
+
+fn parse_config(text: &str) -> Result<ConfigFile> {
+    let config: ConfigFile = toml::from_str(text)?;
+    if let Some(dialect) = &config.csv_dialect {
+        if !CSV_DIALECTS.contains(&dialect.as_str()) {
+            anyhow::bail!(
+                "csv-dialect must be one of {}, not '{dialect}'",
+                CSV_DIALECTS.join(", ")
+            );
+        }
+    }
+    Ok(config)
+}
+
+/// Export options from the arguments of `export` or the bare invocation,
+/// falling back to `config` for options not given on the command line
+fn export_options_from(matches: &clap::ArgMatches, config: &ConfigFile) -> ExportOptions {
+    let export_gpx = matches.get_flag("gpx") || matches.get_flag("gps") || config.gpx;
+    let export_event = matches.get_flag("event") || config.event;
+    let force_export = matches.get_flag("force-export") || config.force_export;
+    let output_dir = matches
+        .get_one::<String>("output-dir")
+        .or(config.output_dir.as_ref())
+        .cloned();
+    let export_options = ExportOptions {
+        // CSV export is on unless --no-csv leaves e.g. only --ndjson
+        csv: !matches.get_flag("no-csv"),
+        gpx: export_gpx,
+        event: export_event,
+        kml: matches.get_flag("kml") || config.kml,
+        gps_csv: matches.get_flag("gps-csv") || config.gps_csv,
+        output_dir: output_dir.clone(),
+        force_export,
+        filter_thresholds: config.filter.thresholds(),
+        gap_fill: match matches.get_one::<String>("gap-fill").map(String::as_str) {
+            Some("repeat") => GapFillPolicy::RepeatLast,
+            Some("interpolate") => GapFillPolicy::Interpolate,
+            _ => GapFillPolicy::Leave,
+        },
+        motor_percent: matches.get_flag("motor-percent"),
+        throttle_percent: matches.get_flag("throttle-percent"),
+        gyro_deg_per_sec: matches.get_flag("gyro-deg-per-sec"),
+        acc_g: matches.get_flag("acc-g"),
+        setpoint_deg_per_sec: matches.get_flag("setpoint-deg-per-sec"),
+        vbat_per_cell: matches.get_flag("vbat-per-cell"),
+        simulate_imu: matches.get_flag("simulate-imu"),
+        mag_heading: matches.get_flag("mag-heading"),
+        declination_deg: matches
+            .get_one::<f64>("declination")
+            .or(matches.get_one::<f64>("declination-dec"))
+            .copied()
+            .unwrap_or(0.0),
+        climb_rate: matches.get_flag("climb-rate"),
+        simulate_current_meter: matches.get_flag("simulate-current-meter"),
+        sim_current_meter_offset: matches.get_one::<i32>("sim-current-meter-offset").copied(),
+        sim_current_meter_scale: matches.get_one::<i32>("sim-current-meter-scale").copied(),
+        merge_gps: matches.get_flag("merge-gps"),
+        home_distance: matches.get_flag("home-distance"),
+        event_column: matches.get_flag("event-column"),
+        raw_units: matches.get_flag("raw-units"),
+        split_frame_types: matches.get_flag("split-frame-types"),
+        compress: matches.get_flag("compress"),
+        debug_mode_names: matches.get_flag("debug-mode-names"),
+        csv_fields: matches
+            .get_many::<String>("fields")
+            .map(|patterns| patterns.map(|pattern| pattern.trim().to_string()).collect())
+            .unwrap_or_default(),
+        compat: match matches.get_one::<String>("compat").map(String::as_str) {
+            Some("blackbox_decode") => CompatMode::BlackboxDecode,
+            _ => CompatMode::Native,
+        },
+        csv_options: match matches
+            .get_one::<String>("csv-dialect")
+            .or(config.csv_dialect.as_ref())
+            .map(String::as_str)
+        {
+            Some("rfc4180") => CsvOptions::rfc4180(),
+            Some("tsv") => CsvOptions::tsv(),
+            _ => CsvOptions::default(),
+        },
+        gps_gap_threshold_us: matches
+            .get_one::<f64>("gpx-gap")
+            .map(|&seconds| (seconds.max(0.0) * 1_000_000.0) as u64)
+            .unwrap_or(DEFAULT_GPS_GAP_THRESHOLD_US),
+        time_range: time_range_from(matches),
+        overwrite: if matches.get_flag("skip-existing") {
+            OverwritePolicy::SkipExisting
+        } else if matches.get_flag("rename") {
+            OverwritePolicy::Rename
+        } else {
+            OverwritePolicy::Overwrite
+        },
+        // Streamed by the parser's frame sink, see NdjsonOutput
+        ndjson: matches.get_flag("ndjson") || config.ndjson,
+        exporters: [(
+            "headers-json",
+            config.headers_json,
+            std::sync::Arc::new(HeadersJsonExporter) as std::sync::Arc<dyn Exporter>,
+        )]
+        .into_iter()
+        .filter(|(flag, configured, _)| matches.get_flag(flag) || *configured)
+        .map(|(_, _, exporter)| exporter)
+        .collect(),
+        ..Default::default()
+    };
+    match matches.get_one::<String>("preset").map(String::as_str) {
+        Some("pidtoolbox") => export_options.with_preset(ExportPreset::PidToolbox),
+        Some("pid-analyzer") => export_options.with_preset(ExportPreset::PidAnalyzer),
+        _ => export_options,
+    }
+}
+
 #[allow(dead_code)]
 fn display_frame_data(logs: &[BBLLog]) {
     for log in logs {
@@ -663,6 +1704,9 @@ fn display_log_info(log: &BBLLog) {
     if !header.craft_name.is_empty() {
         println!("Craft: {}", header.craft_name);
     }
+    if header.is_high_resolution() {
+        println!("High resolution logging: gyro, RC command and setpoint exported ÷10");
+    }
 
     // Display statistics
     println!("\nStatistics");
@@ -711,11 +1755,192 @@ fn display_log_info(log: &BBLLog) {
     }
 }
 
+/// Print the figures of [`FlightSummary`] the log has data for
+fn display_flight_summary(flight: &FlightSummary) {
+    let lines = [
+        (
+            "Max altitude",
+            flight.max_altitude_m.map(|m| format!("{m:.1} m")),
+        ),
+        (
+            "Max speed",
+            flight.max_speed_m_s.map(|speed| format!("{speed:.1} m/s")),
+        ),
+        (
+            "Max current",
+            flight.max_current_a.map(|amps| format!("{amps:.1} A")),
+        ),
+        (
+            "Min voltage",
+            flight.min_voltage_v.map(|volts| format!("{volts:.2} V")),
+        ),
+        ("Used", flight.mah_used.map(|mah| format!("{mah:.0} mAh"))),
+        (
+            "Max gyro",
+            flight.max_gyro_deg_s.map(|rate| format!("{rate:.0} deg/s")),
+        ),
+        ("Failsafes", Some(flight.failsafes.to_string())),
+        (
+            "Failsafe time",
+            flight
+                .link
+                .as_ref()
+                .filter(|link| link.failsafe_entries > 0)
+                .map(|link| format!("{:.1} s", link.failsafe_time_us as f64 / 1e6)),
+        ),
+        (
+            "RX losses",
+            flight
+                .link
+                .as_ref()
+                .filter(|link| link.rx_losses > 0)
+                .map(|link| {
+                    format!(
+                        "{} ({:.1} s total, longest {:.1} s)",
+                        link.rx_losses,
+                        link.rx_loss_time_us as f64 / 1e6,
+                        link.longest_rx_loss_us as f64 / 1e6
+                    )
+                }),
+        ),
+        (
+            "Motor saturated",
+            flight
+                .motors
+                .as_ref()
+                .filter(|motors| motors.saturated_time_us > 0)
+                .map(|motors| {
+                    format!(
+                        "{:.2} s ({} periods)",
+                        motors.saturated_time_us as f64 / 1e6,
+                        motors.saturation.len()
+                    )
+                }),
+        ),
+        (
+            "GPS distance",
+            flight.gps_distance_m.map(|m| format!("{m:.0} m")),
+        ),
+        (
+            "Max from home",
+            flight
+                .gps
+                .and_then(|gps| gps.max_home_distance_m)
+                .map(|m| format!("{m:.0} m")),
+        ),
+        (
+            "Max GPS alt",
+            flight.gps.map(|gps| format!("{:.1} m", gps.max_altitude_m)),
+        ),
+    ];
+    println!("\nFlight summary");
+    for (label, value) in lines {
+        if let Some(value) = value {
+            println!("{label:<15} {value}");
+        }
+    }
+    for desync in flight.motors.iter().flat_map(|motors| &motors.desyncs) {
+        println!(
+            "Possible desync motor[{}] at {} us for {:.2} s",
+            desync.motor,
+            desync.start_us,
+            desync.duration_us() as f64 / 1e6
+        );
+    }
+}
+
+/// Print the header summary of `info`: firmware, craft and field definitions
+fn display_header_info(header: &BBLHeader, log_number: usize, total_logs: usize) {
+    println!("\nLog {log_number} of {total_logs}");
+    if !header.firmware_revision.is_empty() {
+        println!("Firmware: {}", header.firmware_revision);
+    }
+    if !header.board_info.is_empty() {
+        println!("Board: {}", header.board_info);
+    }
+    if !header.craft_name.is_empty() {
+        println!("Craft: {}", header.craft_name);
+    }
+    if let Some(ref log_start) = header.log_start_datetime {
+        println!("Log start: {log_start}");
+    }
+    println!("Looptime: {}", header.looptime);
+    println!("Data version: {}", header.data_version);
+    for (frame_type, frame_def) in [
+        ('I', &header.i_frame_def),
+        ('P', &header.p_frame_def),
+        ('S', &header.s_frame_def),
+        ('G', &header.g_frame_def),
+        ('H', &header.h_frame_def),
+    ] {
+        if frame_def.count > 0 {
+            println!(
+                "{frame_type} fields ({}): {}",
+                frame_def.count,
+                frame_def.field_names.join(", ")
+            );
+        }
+    }
+}
+
+/// Print per-field ranges in the layout of blackbox_decode's `--limits`
+fn display_field_limits(log: &BBLLog) {
+    println!("\n    Field name          Min          Max        Range");
+    println!("-----------------------------------------------------");
+    for limits in log.field_limits() {
+        println!(
+            "{:>14} {:>12} {:>12} {:>12}",
+            limits.name,
+            limits.min,
+            limits.max,
+            limits.max - limits.min
+        );
+    }
+}
+
+/// Print count, range, mean, standard deviation and peak time of every field
+fn display_field_stats(log: &BBLLog) {
+    println!(
+        "\n{:>22} {:>8} {:>10} {:>10} {:>10} {:>10} {:>12}",
+        "Field name", "Count", "Min", "Max", "Mean", "Stddev", "Peak (us)"
+    );
+    println!("{}", "-".repeat(88));
+    for stats in log.field_stats() {
+        println!(
+            "{:>22} {:>8} {:>10} {:>10} {:>10.1} {:>10.1} {:>12}",
+            stats.name,
+            stats.count,
+            stats.min,
+            stats.max,
+            stats.mean,
+            stats.stddev,
+            stats.peak_time_us
+        );
+    }
+}
+
+/// Print the RSSI distribution of logs that record `rssi`
+fn display_rssi_stats(log: &BBLLog) {
+    if let Ok(Some(rssi)) = log.rssi_stats(1.0) {
+        println!(
+            "\nRSSI            min {:.0}%, 1% {:.0}%, 5% {:.0}%, median {:.0}%, mean {:.0}%, max {:.0}%",
+            rssi.min_percent,
+            rssi.p1_percent,
+            rssi.p5_percent,
+            rssi.median_percent,
+            rssi.mean_percent,
+            rssi.max_percent
+        );
+    }
+}
+
 fn parse_bbl_file_streaming(
     file_path: &Path,
-    debug: bool,
+    process: &ProcessOptions,
     export_options: &ExportOptions,
+    summary: &mut Vec<LogSummary>,
 ) -> Result<usize> {
+    let debug = process.debug;
     if debug {
         println!("=== STREAMING BBL FILE PROCESSING ===");
         let metadata = std::fs::metadata(file_path)?;
@@ -726,82 +1951,380 @@ fn parse_bbl_file_streaming(
         );
     }
 
-    let file_data = std::fs::read(file_path)?;
+    let file_data = read_bbl_file(file_path)?;
 
-    // Look for multiple logs by searching for log start markers
-    let log_start_marker = b"H Product:Blackbox flight data recorder by Nicholas Sherlock";
-    let mut log_positions = Vec::new();
+    process_bbl_data(&file_data, file_path, process, export_options, summary)
+}
 
-    // Find all log start positions
-    for i in 0..file_data.len() {
-        if i + log_start_marker.len() <= file_data.len()
-            && &file_data[i..i + log_start_marker.len()] == log_start_marker
-        {
-            log_positions.push(i);
+/// Process stdin as if it were a file named `base_name`. Output files are
+/// named after `base_name` and written to `--output-dir` or the current
+/// directory.
+///
+/// `export` and `stats` read one log at a time, so memory stays bounded by the
+/// largest log; the other modes need the whole input.
+fn parse_bbl_stdin(
+    base_name: &str,
+    process: &ProcessOptions,
+    export_options: &ExportOptions,
+    summary: &mut Vec<LogSummary>,
+) -> Result<usize> {
+    let debug = process.debug;
+    let mut input = decompress_bbl_reader(std::io::BufReader::new(std::io::stdin().lock()))
+        .context("Failed to read BBL data from stdin")?;
+    if matches!(process.mode, RunMode::Export | RunMode::Stats) {
+        if debug {
+            println!("=== STDIN STREAMING ===");
         }
+        let logs = LogSource::stream(input, &process.parser);
+        return process_logs(
+            logs,
+            &stdin_input_path(base_name),
+            process,
+            export_options,
+            summary,
+        );
+    }
+
+    let mut data = Vec::new();
+    input
+        .read_to_end(&mut data)
+        .context("Failed to read BBL data from stdin")?;
+
+    if debug {
+        println!("=== STDIN PROCESSING ===");
+        println!(
+            "Read {} bytes ({:.2} MB) from stdin",
+            data.len(),
+            data.len() as f64 / 1024.0 / 1024.0
+        );
+    }
+
+    process_bbl_data(
+        &data,
+        &stdin_input_path(base_name),
+        process,
+        export_options,
+        summary,
+    )
+}
+
+/// Input path standing in for stdin; only the last component of `base_name`
+/// is kept, so `--basename ../x` cannot write outside the output directory
+fn stdin_input_path(base_name: &str) -> PathBuf {
+    Path::new(base_name)
+        .file_name()
+        .map_or_else(|| PathBuf::from(DEFAULT_STDIN_BASENAME), PathBuf::from)
+}
+
+/// Parse every log in `file_data` and export each one immediately.
+/// `file_path` names the source for output paths and messages; the outcome of
+/// each log is appended to `summary`.
+fn process_bbl_data(
+    file_data: &[u8],
+    file_path: &Path,
+    process: &ProcessOptions,
+    export_options: &ExportOptions,
+    summary: &mut Vec<LogSummary>,
+) -> Result<usize> {
+    let debug = process.debug;
+    match process.mode {
+        RunMode::Info => return info_bbl_data(file_data, process),
+        RunMode::Split => return split_bbl_data(file_data, file_path, process, export_options),
+        RunMode::Validate => return validate_bbl_data(file_data, process),
+        RunMode::Export | RunMode::Stats => {}
     }
 
+    let log_positions = find_log_positions(file_data, &process.parser);
     if log_positions.is_empty() {
-        return Err(anyhow::anyhow!("No blackbox log headers found in file"));
+        return Err(bbl_parser::BBLError::NoLogsFound.into());
     }
+    check_log_index(process.index, log_positions.len())?;
 
     if debug {
         println!("Found {} log(s) in file", log_positions.len());
     }
 
+    let logs = LogSource::Memory {
+        data: file_data,
+        positions: log_positions,
+    };
+    process_logs(logs, file_path, process, export_options, summary)
+}
+
+/// The logs of one input: slices of a file read into memory, or logs read one
+/// at a time from a stream such as stdin
+enum LogSource<'a> {
+    Memory {
+        data: &'a [u8],
+        positions: Vec<usize>,
+    },
+    /// `next` is the log after the current one, read ahead so that the
+    /// current one knows whether more follow
+    Stream {
+        reader: Box<BBLLogReader<Box<dyn Read + 'a>>>,
+        next: Option<bbl_parser::Result<Vec<u8>>>,
+    },
+}
+
+impl<'a> LogSource<'a> {
+    fn stream(reader: Box<dyn Read + 'a>, config: &ParserConfig) -> Self {
+        let mut reader = Box::new(
+            BBLLogReader::new(reader, ExportOptions::default(), false).with_config(config.clone()),
+        );
+        let next = reader.next_log_bytes();
+        LogSource::Stream { reader, next }
+    }
+
+    /// The log after the one returned by the previous call, which is log
+    /// `log_index`, and the number of logs known so far: every log in memory,
+    /// or up to the one read ahead from a stream
+    fn next_log(&mut self, log_index: usize) -> Option<Result<(Cow<'a, [u8]>, usize)>> {
+        match self {
+            LogSource::Memory { data, positions } => {
+                let data: &'a [u8] = data;
+                let start = *positions.get(log_index)?;
+                // The log ends at the start of the next one or the end of the file
+                let end = positions.get(log_index + 1).copied().unwrap_or(data.len());
+                Some(Ok((Cow::Borrowed(&data[start..end]), positions.len())))
+            }
+            LogSource::Stream { reader, next } => {
+                let log_data = match next.take()? {
+                    Ok(log_data) => log_data,
+                    Err(e) => return Some(Err(e.into())),
+                };
+                *next = reader.next_log_bytes();
+                let known_logs = log_index + 1 + usize::from(next.is_some());
+                Some(Ok((Cow::Owned(log_data), known_logs)))
+            }
+        }
+    }
+}
+
+/// Decode, report and export every log of `logs`, for `export` and `stats`
+fn process_logs(
+    mut logs: LogSource,
+    file_path: &Path,
+    process: &ProcessOptions,
+    export_options: &ExportOptions,
+    summary: &mut Vec<LogSummary>,
+) -> Result<usize> {
+    let debug = process.debug;
     let mut processed_logs = 0;
+    let mut log_count = 0;
     let mut session_firmware: Vec<(usize, String)> = Vec::new();
-
-    for (log_index, &start_pos) in log_positions.iter().enumerate() {
+    // --concat: one CSV for every log, opened when the first log is exported
+    let mut concat_csv: Option<(PathBuf, ConcatCsvWriter<ExportFile>)> = None;
+
+    while let Some(next_log) = logs.next_log(log_count) {
+        let (log_data, total_logs) = next_log?;
+        let log_data: &[u8] = &log_data;
+        let log_index = log_count;
+        log_count += 1;
+        if !is_selected_log(process.index, log_index + 1) {
+            continue;
+        }
         if debug {
             println!(
-                "Processing log {} starting at position {}",
+                "Processing log {} ({} bytes)",
                 log_index + 1,
-                start_pos
+                log_data.len()
             );
         }
 
-        // Determine end position (start of next log or end of file)
-        let end_pos = log_positions
-            .get(log_index + 1)
-            .copied()
-            .unwrap_or(file_data.len());
-        let log_data = &file_data[start_pos..end_pos];
+        // Name the outputs from the headers before decoding frames:
+        // --skip-existing checks them and --ndjson streams frames into them
+        let mut early_base_name = None;
+        if process.mode == RunMode::Export
+            && (export_options.overwrite == OverwritePolicy::SkipExisting || export_options.ndjson)
+        {
+            let header = parse_bbl_bytes_headers_only(log_data, debug)?.remove(0);
+            let base_name_override =
+                corrected_session_base_name(file_path, &header.firmware_revision);
+            early_base_name = resolve_export_base_name(
+                file_path,
+                export_options,
+                log_index + 1,
+                total_logs,
+                base_name_override.as_deref(),
+            );
+            if early_base_name.is_none() {
+                println!(
+                    "Skipping log {} of {}: output files already exist",
+                    log_index + 1,
+                    total_logs
+                );
+                summary.push(LogSummary {
+                    skip_reason: Some("output files already exist".to_string()),
+                    ..LogSummary::new(log_index + 1, total_logs)
+                });
+                processed_logs += 1;
+                continue;
+            }
+        }
+
+        if let Some(bar) = &process.progress {
+            bar.set_message(format!("log {}/{}", log_index + 1, total_logs));
+            bar.set_length(log_data.len() as u64);
+            bar.reset();
+        }
+
+        let ndjson = if process.mode == RunMode::Export && export_options.ndjson {
+            let (csv_path, _, _, _) = compute_export_paths(
+                file_path,
+                export_options,
+                log_index + 1,
+                total_logs,
+                early_base_name.as_deref(),
+            );
+            let path = export_options.output_path(&csv_path.with_extension("ndjson"));
+            Some(NdjsonOutput::create(path, export_options.compress)?)
+        } else {
+            None
+        };
+        // Other outputs read the main frames after parsing; NDJSON alone
+        // needs none stored
+        let retain_frames =
+            export_options.csv || process.limits || !export_options.exporters.is_empty();
+        let ndjson_config = ndjson
+            .as_ref()
+            .map(|output| output.parser_config(&process.parser, retain_frames));
 
         // Parse this individual log
-        let log = parse_single_log(
+        let parsed = parse_single_log(
             log_data,
             log_index + 1,
-            log_positions.len(),
+            total_logs,
             debug,
             export_options,
-        )?;
+            ndjson_config.as_ref().unwrap_or(&process.parser),
+        );
+        drop(ndjson_config);
+        // Clear the bar before anything else is printed
+        if let Some(bar) = &process.progress {
+            bar.finish_and_clear();
+        }
+        let mut log = match parsed {
+            Ok(log) => log,
+            Err(e) => {
+                if let Some(output) = ndjson {
+                    output.discard();
+                }
+                return Err(e.into());
+            }
+        };
+        let mut log_summary = LogSummary::new(log_index + 1, total_logs);
+
+        if log.stats.truncated {
+            println!(
+                "Warning: log {} stopped at a parser safety limit (--max-frames {}, --max-failed-frames {}); later frames were not decoded",
+                log.log_number, process.parser.max_frames, process.parser.max_failed_frames
+            );
+        }
+
+        if process.repair_timestamps {
+            let repaired = log.repair_timestamps();
+            if repaired > 0 {
+                println!("Repaired {repaired} frame timestamp(s)");
+            }
+        }
 
         // Record firmware for transition detection (before any early-continue)
         session_firmware.push((log.log_number, log.header.firmware_revision.clone()));
 
         // Display log info immediately
         display_log_info(&log);
+        if process.limits {
+            display_field_limits(&log);
+        }
+        let flight = log.flight_summary();
+        display_flight_summary(&flight);
+        log_summary.flight = Some(flight);
+        log_summary.stats = Some(log.stats.clone());
+        if process.mode == RunMode::Stats {
+            display_field_stats(&log);
+            display_rssi_stats(&log);
+            summary.push(log_summary);
+            processed_logs += 1;
+            continue;
+        }
 
         // Check if we should skip exports for this log
-        let (should_skip, reason) = should_skip_export(&log, export_options.force_export);
+        let (should_skip, reason) = should_skip_export_with_thresholds(
+            &log,
+            export_options.force_export,
+            &export_options.filter_thresholds,
+        );
         if should_skip {
+            if let Some(output) = ndjson {
+                output.discard();
+            }
             println!("Skipping exports for this log: {}", reason);
+            log_summary.skip_reason = Some(reason);
+            summary.push(log_summary);
             processed_logs += 1;
 
             // Add separator between logs for clarity
-            if log_index + 1 < log_positions.len() {
+            if log_index + 1 < total_logs {
                 println!();
             }
             continue;
         }
 
         // Correct the output prefix when this session's firmware vendor differs from the BBL filename
-        let base_name_override =
+        let corrected_base_name =
             corrected_session_base_name(file_path, &log.header.firmware_revision);
+        // --rename picks a free name here, once for all of this log's outputs,
+        // unless it was picked before parsing
+        let base_name_override = early_base_name.or_else(|| {
+            resolve_export_base_name(
+                file_path,
+                export_options,
+                log_index + 1,
+                total_logs,
+                corrected_base_name.as_deref(),
+            )
+        });
 
         // Export CSV immediately while data is hot in cache
-        if export_options.csv {
+        if export_options.csv && process.concat {
+            let written = match &mut concat_csv {
+                Some((_, writer)) => Ok(writer.write_log(&log, export_options)?),
+                None => {
+                    let (csv_path, _, _, _) = compute_export_paths(
+                        file_path,
+                        export_options,
+                        1,
+                        1,
+                        base_name_override.as_deref(),
+                    );
+                    let csv_path = export_options.output_path(&csv_path);
+                    create_concat_csv(&csv_path, export_options.compress, process.rebase_time)
+                        .and_then(|mut writer| {
+                            let written = writer.write_log(&log, export_options);
+                            concat_csv = Some((csv_path, writer));
+                            Ok(written?)
+                        })
+                }
+            };
+            match written {
+                Ok(_) => {
+                    if let Some((csv_path, _)) = &concat_csv {
+                        log_summary.exported.push(csv_path.clone());
+                    }
+                }
+                Err(e) => {
+                    log_summary.export_errors.push(format!("CSV: {e}"));
+                    let filename = file_path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("unknown");
+                    eprintln!(
+                        "Warning: Failed to export CSV for {filename} log {}: {e}",
+                        log_index + 1
+                    );
+                }
+            }
+        } else if export_options.csv {
             match export_to_csv(
                 &log,
                 file_path,
@@ -811,12 +2334,23 @@ fn parse_bbl_file_streaming(
                 Ok(report) => {
                     if let Some(headers_path) = report.headers_path {
                         println!("Exported headers to: {}", headers_path.display());
+                        log_summary.exported.push(headers_path);
                     }
                     if let Some(csv_path) = report.csv_path {
                         println!("Exported flight data to: {}", csv_path.display());
+                        log_summary.exported.push(csv_path);
+                    }
+                    if let Some(s_frame_csv_path) = report.s_frame_csv_path {
+                        println!("Exported slow frames to: {}", s_frame_csv_path.display());
+                        log_summary.exported.push(s_frame_csv_path);
+                    }
+                    if let Some(g_frame_csv_path) = report.g_frame_csv_path {
+                        println!("Exported GPS frames to: {}", g_frame_csv_path.display());
+                        log_summary.exported.push(g_frame_csv_path);
                     }
                 }
                 Err(e) => {
+                    log_summary.export_errors.push(format!("CSV: {e}"));
                     let filename = file_path
                         .file_name()
                         .and_then(|n| n.to_str())
@@ -829,13 +2363,20 @@ fn parse_bbl_file_streaming(
             }
         }
 
+        // --start/--end trim the GPS exports like the CSV
+        let gps_coordinates = if export_options.time_range.is_unbounded() {
+            Cow::Borrowed(log.gps_coordinates.as_slice())
+        } else {
+            Cow::Owned(export_options.time_range.gps_coordinates(&log))
+        };
+
         // Export GPS data to GPX if requested
-        if export_options.gpx && !log.gps_coordinates.is_empty() {
+        if export_options.gpx && !gps_coordinates.is_empty() {
             match export_to_gpx(
                 file_path,
                 log_index,
-                log_positions.len(),
-                &log.gps_coordinates,
+                total_logs,
+                &gps_coordinates,
                 &log.home_coordinates,
                 export_options,
                 log.header.log_start_datetime.as_deref(),
@@ -844,9 +2385,11 @@ fn parse_bbl_file_streaming(
                 Ok(report) => {
                     if let Some(gpx_path) = report.gpx_path {
                         println!("Exported GPS data to: {}", gpx_path.display());
+                        log_summary.exported.push(gpx_path);
                     }
                 }
                 Err(e) => {
+                    log_summary.export_errors.push(format!("GPX: {e}"));
                     let filename = file_path
                         .file_name()
                         .and_then(|n| n.to_str())
@@ -859,12 +2402,74 @@ fn parse_bbl_file_streaming(
             }
         }
 
+        // Export GPS frames to CSV if requested
+        if export_options.gps_csv && !gps_coordinates.is_empty() {
+            match export_gps_to_csv(
+                file_path,
+                log_index,
+                total_logs,
+                &gps_coordinates,
+                &log.home_coordinates,
+                export_options,
+                base_name_override.as_deref(),
+            ) {
+                Ok(report) => {
+                    if let Some(gps_csv_path) = report.gps_csv_path {
+                        println!("Exported GPS data to: {}", gps_csv_path.display());
+                        log_summary.exported.push(gps_csv_path);
+                    }
+                }
+                Err(e) => {
+                    log_summary.export_errors.push(format!("GPS CSV: {e}"));
+                    let filename = file_path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("unknown");
+                    eprintln!(
+                        "Warning: Failed to export GPS CSV for {filename} log {}: {e}",
+                        log_index + 1
+                    );
+                }
+            }
+        }
+
+        // Export GPS data to KML if requested
+        if export_options.kml && !gps_coordinates.is_empty() {
+            match export_to_kml(
+                file_path,
+                log_index,
+                total_logs,
+                &gps_coordinates,
+                &log.home_coordinates,
+                export_options,
+                base_name_override.as_deref(),
+            ) {
+                Ok(report) => {
+                    if let Some(kml_path) = report.kml_path {
+                        println!("Exported GPS data to: {}", kml_path.display());
+                        log_summary.exported.push(kml_path);
+                    }
+                }
+                Err(e) => {
+                    log_summary.export_errors.push(format!("KML: {e}"));
+                    let filename = file_path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("unknown");
+                    eprintln!(
+                        "Warning: Failed to export KML for {filename} log {}: {e}",
+                        log_index + 1
+                    );
+                }
+            }
+        }
+
         // Export event data to JSON if requested
         if export_options.event && !log.event_frames.is_empty() {
             match export_to_event(
                 file_path,
                 log_index,
-                log_positions.len(),
+                total_logs,
                 &log.event_frames,
                 export_options,
                 base_name_override.as_deref(),
@@ -872,9 +2477,11 @@ fn parse_bbl_file_streaming(
                 Ok(report) => {
                     if let Some(event_path) = report.event_path {
                         println!("Exported event data to: {}", event_path.display());
+                        log_summary.exported.push(event_path);
                     }
                 }
                 Err(e) => {
+                    log_summary.export_errors.push(format!("events: {e}"));
                     let filename = file_path
                         .file_name()
                         .and_then(|n| n.to_str())
@@ -887,24 +2494,390 @@ fn parse_bbl_file_streaming(
             }
         }
 
+        if let Some(output) = ndjson {
+            match output.finish() {
+                Ok(path) => {
+                    println!("Exported to: {}", path.display());
+                    log_summary.exported.push(path);
+                }
+                Err(e) => {
+                    log_summary.export_errors.push(format!("NDJSON: {e}"));
+                    let filename = file_path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("unknown");
+                    eprintln!(
+                        "Warning: Failed to export NDJSON for {filename} log {}: {e}",
+                        log_index + 1
+                    );
+                }
+            }
+        }
+
+        // Run extra exporters such as --headers-json
+        match export_registered(
+            &log,
+            file_path,
+            export_options,
+            base_name_override.as_deref(),
+        ) {
+            Ok(report) => {
+                for path in report.extra_paths {
+                    println!("Exported to: {}", path.display());
+                    log_summary.exported.push(path);
+                }
+            }
+            Err(e) => {
+                log_summary.export_errors.push(e.to_string());
+                let filename = file_path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("unknown");
+                eprintln!(
+                    "Warning: Failed to export {filename} log {}: {e}",
+                    log_index + 1
+                );
+            }
+        }
+
+        summary.push(log_summary);
         processed_logs += 1;
 
         // Add separator between logs for clarity
-        if log_index + 1 < log_positions.len() {
+        if log_index + 1 < total_logs {
             println!();
         }
 
         // Log goes out of scope here, memory is freed immediately
     }
 
+    if let Some((csv_path, writer)) = concat_csv {
+        match writer.into_inner().and_then(|(file, _)| Ok(file.finish()?)) {
+            Ok(_) => println!("Exported flight data to: {}", csv_path.display()),
+            Err(e) => eprintln!(
+                "Warning: Failed to export CSV to {}: {e}",
+                csv_path.display()
+            ),
+        }
+    }
+
+    // A stream's logs are only known once it has been read
+    if log_count == 0 {
+        return Err(bbl_parser::BBLError::NoLogsFound.into());
+    }
+    check_log_index(process.index, log_count)?;
+
     // Warn when sessions within a single BBL file span multiple firmware vendors
-    if log_positions.len() > 1 {
+    if log_count > 1 {
         print_firmware_transition_warning(file_path, &session_firmware);
     }
 
     Ok(processed_logs)
 }
 
+/// Whether `--index` selects log `log_number`
+fn is_selected_log(index: Option<usize>, log_number: usize) -> bool {
+    index.map_or(true, |selected| selected == log_number)
+}
+
+/// Fail when `--index` names a log the file does not have
+fn check_log_index(index: Option<usize>, total_logs: usize) -> Result<()> {
+    match index {
+        Some(log_number) if log_number > total_logs => Err(bbl_parser::BBLError::LogNotFound {
+            log_number,
+            total_logs,
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// Offsets of every log in `file_data` (see [`log_byte_ranges_with_config`])
+fn find_log_positions(file_data: &[u8], config: &ParserConfig) -> Vec<usize> {
+    log_byte_ranges_with_config(file_data, config)
+        .into_iter()
+        .map(|range| range.start)
+        .collect()
+}
+
+/// `info`: print the headers of every log in `file_data` without decoding frames
+fn info_bbl_data(file_data: &[u8], process: &ProcessOptions) -> Result<usize> {
+    let headers = parse_bbl_bytes_headers_only(file_data, process.debug)?;
+    check_log_index(process.index, headers.len())?;
+    println!("Logs: {}", headers.len());
+    let mut shown_logs = 0;
+    for (log_index, header) in headers.iter().enumerate() {
+        if is_selected_log(process.index, log_index + 1) {
+            display_header_info(header, log_index + 1, headers.len());
+            shown_logs += 1;
+        }
+    }
+    Ok(shown_logs)
+}
+
+/// `split`: copy the bytes of each log in `file_data` to `<name>.NN.BBL`,
+/// trimmed to `--start`/`--end` when given
+fn split_bbl_data(
+    file_data: &[u8],
+    file_path: &Path,
+    process: &ProcessOptions,
+    export_options: &ExportOptions,
+) -> Result<usize> {
+    let total_logs = log_byte_ranges(file_data).len();
+    if total_logs == 0 {
+        return Err(bbl_parser::BBLError::NoLogsFound.into());
+    }
+    check_log_index(process.index, total_logs)?;
+
+    let mut written_logs = 0;
+    for log_number in 1..=total_logs {
+        if !is_selected_log(process.index, log_number) {
+            continue;
+        }
+        // Always numbered, so a single-log file is never written over its input
+        let (csv_path, _, _, _) = compute_export_paths(
+            file_path,
+            export_options,
+            log_number,
+            total_logs.max(2),
+            None,
+        );
+        let log_path = csv_path.with_extension("BBL");
+        if let Some(parent) = log_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let time_range = export_options.time_range;
+        fs::File::create(&log_path)
+            .map_err(anyhow::Error::from)
+            .and_then(|mut file| {
+                if time_range.is_unbounded() {
+                    Ok(write_log_slice(file_data, log_number, &mut file)?)
+                } else {
+                    write_trimmed_log(file_data, log_number, time_range, &mut file)
+                }
+            })
+            .with_context(|| format!("Failed to write {}", log_path.display()))?;
+        println!(
+            "Wrote log {log_number} of {total_logs} to: {}",
+            log_path.display()
+        );
+        written_logs += 1;
+    }
+
+    Ok(written_logs)
+}
+
+/// Print the corruption report of each log; fails when any log has problems
+fn validate_bbl_data(file_data: &[u8], process: &ProcessOptions) -> Result<usize> {
+    let reports = validate_bbl_bytes(file_data)?;
+    check_log_index(process.index, reports.len())?;
+    let total_logs = reports.len();
+    let mut shown_logs = 0;
+    let mut problem_logs = 0;
+    for report in reports
+        .iter()
+        .filter(|report| is_selected_log(process.index, report.log_number))
+    {
+        display_validation(report, total_logs);
+        shown_logs += 1;
+        if !report.is_clean() {
+            problem_logs += 1;
+        }
+    }
+    if problem_logs > 0 {
+        return Err(anyhow::anyhow!(
+            "{problem_logs} of {shown_logs} log(s) failed validation"
+        ));
+    }
+    Ok(shown_logs)
+}
+
+/// Most entries of each kind listed by `validate`
+const MAX_LISTED_PROBLEMS: usize = 10;
+
+fn display_validation(report: &LogValidation, total_logs: usize) {
+    let status = if report.is_clean() { "OK" } else { "PROBLEMS" };
+    println!("Log {} of {total_logs}: {status}", report.log_number);
+    if let Some(error) = &report.error {
+        println!("  Error:            {error}");
+        return;
+    }
+    // Lists the first entries of a kind, with the count of the rest
+    let list = |label: &str, items: Vec<String>| {
+        if items.is_empty() {
+            return;
+        }
+        let more = items.len().saturating_sub(MAX_LISTED_PROBLEMS);
+        let mut line = items[..items.len().min(MAX_LISTED_PROBLEMS)].join(", ");
+        if more > 0 {
+            line.push_str(&format!(", ... ({more} more)"));
+        }
+        println!("  {label:<18}{} ({line})", items.len());
+    };
+    println!("  Failed frames:    {}", report.failed_frames);
+    for error in report.frame_errors.iter().take(MAX_LISTED_PROBLEMS) {
+        match error.reason {
+            FrameErrorReason::UnknownFrameType => println!(
+                "    at 0x{:X}: unknown frame type byte 0x{:02X}",
+                error.offset, error.frame_type as u32
+            ),
+            ref reason => println!(
+                "    at 0x{:X}: {} frame, {reason}",
+                error.offset, error.frame_type
+            ),
+        }
+    }
+    list(
+        "Loop gaps:",
+        report
+            .loop_gaps
+            .iter()
+            .map(|gap| {
+                format!(
+                    "{} -> {} at {} us",
+                    gap.from_iteration, gap.to_iteration, gap.time_us
+                )
+            })
+            .collect(),
+    );
+    list(
+        "Time regressions:",
+        report
+            .time_regressions
+            .iter()
+            .map(|regression| {
+                format!(
+                    "{} -> {} us at iteration {}",
+                    regression.from_us, regression.to_us, regression.loop_iteration
+                )
+            })
+            .collect(),
+    );
+    if report.truncated {
+        println!("  Truncated:        no log end event");
+    }
+}
+
+/// Merge the logs of `files` into `output`: a continuous CSV for a `.csv`
+/// name, otherwise a multi-log BBL
+fn merge_files(files: &[&String], output: &Path) -> Result<()> {
+    let inputs = files
+        .iter()
+        .map(|file| {
+            read_bbl_file(Path::new(file.as_str()))
+                .with_context(|| format!("Failed to read {file}"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let as_csv = output
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    if !as_csv {
+        let inputs: Vec<&[u8]> = inputs.iter().map(Vec::as_slice).collect();
+        let mut file = fs::File::create(output)
+            .with_context(|| format!("Failed to create {}", output.display()))?;
+        write_merged_logs(&inputs, &mut file)
+            .with_context(|| format!("Failed to write {}", output.display()))?;
+        let total_logs: usize = inputs.iter().map(|data| log_byte_ranges(data).len()).sum();
+        println!(
+            "Merged {total_logs} log(s) from {} file(s) into: {}",
+            files.len(),
+            output.display()
+        );
+        return Ok(());
+    }
+
+    let export_options = ExportOptions::default();
+    let file = ExportFile::create(output, false)
+        .with_context(|| format!("Failed to create {}", output.display()))?;
+    let mut writer = ConcatCsvWriter::new(file, false).with_continuous_time();
+    let mut total_logs = 0;
+    for (file, data) in files.iter().zip(&inputs) {
+        let logs = parse_bbl_bytes_all_logs(data, export_options.clone(), false)
+            .with_context(|| format!("Failed to parse {file}"))?;
+        for log in &logs {
+            writer
+                .write_log(log, &export_options)
+                .with_context(|| format!("Failed to merge log {} of {file}", log.log_number))?;
+        }
+        total_logs += logs.len();
+    }
+    let (file, report) = writer.into_inner()?;
+    file.finish()?;
+    println!(
+        "Merged {total_logs} log(s) from {} file(s) into: {} ({} rows)",
+        files.len(),
+        output.display(),
+        report.rows_written
+    );
+    Ok(())
+}
+
+/// Create the output file of `--concat`
+fn create_concat_csv(
+    csv_path: &Path,
+    compress: bool,
+    rebase_time: bool,
+) -> Result<ConcatCsvWriter<ExportFile>> {
+    if let Some(parent) = csv_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = ExportFile::create(csv_path, compress)
+        .with_context(|| format!("Failed to create {}", csv_path.display()))?;
+    Ok(ConcatCsvWriter::new(file, rebase_time))
+}
+
+/// `--ndjson` output of one log, written by the parser's frame sink
+struct NdjsonOutput {
+    path: PathBuf,
+    writer: std::sync::Arc<NdjsonWriter<ExportFile>>,
+}
+
+impl NdjsonOutput {
+    fn create(path: PathBuf, compress: bool) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = ExportFile::create(&path, compress)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        Ok(Self {
+            path,
+            writer: std::sync::Arc::new(NdjsonWriter::new(file)),
+        })
+    }
+
+    /// Parser config that streams frames into this file; main frames are
+    /// only kept when `retain_frames` is set
+    fn parser_config(&self, parser: &ParserConfig, retain_frames: bool) -> ParserConfig {
+        ParserConfig {
+            frame_sink: Some(self.writer.clone()),
+            retain_frames,
+            ..parser.clone()
+        }
+    }
+
+    /// Close the file once the parser config holding the sink is dropped
+    fn finish(self) -> Result<PathBuf> {
+        let path = self.path;
+        let written = self.writer.finish().and_then(|_| {
+            let writer = std::sync::Arc::try_unwrap(self.writer)
+                .map_err(|_| anyhow::anyhow!("NDJSON frame sink is still in use"))?;
+            Ok(writer.into_inner().finish()?)
+        });
+        written.with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Remove the file of a log that is not exported
+    fn discard(self) {
+        drop(self.writer);
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
 fn print_firmware_transition_warning(file_path: &Path, session_firmware: &[(usize, String)]) {
     if session_firmware.len() <= 1 {
         return;
@@ -955,6 +2928,7 @@ fn print_firmware_transition_warning(file_path: &Path, session_firmware: &[(usiz
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::collections::HashMap;
     use std::path::PathBuf;
 
     #[test]
@@ -1022,6 +2996,7 @@ mod tests {
             event: false,
             output_dir: Some("/tmp".to_string()),
             force_export: false,
+            ..Default::default()
         };
         assert_eq!(options.output_dir.as_ref().unwrap(), "/tmp");
         assert!(options.csv);
@@ -1036,6 +3011,222 @@ mod tests {
         assert!(!options.gpx);
         assert!(!options.event);
         assert!(!options.force_export);
+        assert!(options.preserve_order);
+    }
+
+    #[test]
+    fn test_subcommand_parsing() {
+        let matches = build_command()
+            .try_get_matches_from(["bbl_parser", "--gpx", "flight.BBL"])
+            .unwrap();
+        assert!(matches.subcommand().is_none());
+        assert!(matches.get_flag("gpx"));
+
+        for (args, expected) in [
+            (["bbl_parser", "export", "flight.BBL"], "export"),
+            (["bbl_parser", "parse", "flight.BBL"], "export"),
+            (["bbl_parser", "info", "flight.BBL"], "info"),
+            (["bbl_parser", "stats", "flight.BBL"], "stats"),
+            (["bbl_parser", "split", "flight.BBL"], "split"),
+            (["bbl_parser", "validate", "flight.BBL"], "validate"),
+        ] {
+            let matches = build_command().try_get_matches_from(args).unwrap();
+            let (name, sub_matches) = matches.subcommand().unwrap();
+            assert_eq!(name, expected);
+            let files: Vec<&String> = sub_matches.get_many("files").unwrap().collect();
+            assert_eq!(files, ["flight.BBL"]);
+        }
+
+        // Log numbers are 1-based
+        assert!(build_command()
+            .try_get_matches_from(["bbl_parser", "--index", "0", "flight.BBL"])
+            .is_err());
+        let matches = build_command()
+            .try_get_matches_from(["bbl_parser", "stats", "--index", "3", "flight.BBL"])
+            .unwrap();
+        let (_, sub_matches) = matches.subcommand().unwrap();
+        assert_eq!(
+            process_options_from(sub_matches, RunMode::Stats).index,
+            Some(3)
+        );
+
+        // split trims to --start/--end
+        let matches = build_command()
+            .try_get_matches_from(["bbl_parser", "split", "--start", "1:05", "flight.BBL"])
+            .unwrap();
+        let (_, sub_matches) = matches.subcommand().unwrap();
+        assert_eq!(
+            time_range_from(sub_matches),
+            TimeRange {
+                start_us: Some(65_000_000),
+                end_us: None,
+            }
+        );
+
+        // Export flags only apply to export
+        assert!(build_command()
+            .try_get_matches_from(["bbl_parser", "info", "--gpx", "flight.BBL"])
+            .is_err());
+    }
+
+    #[test]
+    fn test_merge_arguments() {
+        let matches = build_command()
+            .try_get_matches_from(["bbl_parser", "merge", "a.BFL", "b.BFL", "-o", "session.csv"])
+            .unwrap();
+        let (name, sub_matches) = matches.subcommand().unwrap();
+        assert_eq!(name, "merge");
+        let files: Vec<&String> = sub_matches.get_many("files").unwrap().collect();
+        assert_eq!(files, ["a.BFL", "b.BFL"]);
+        assert_eq!(
+            sub_matches.get_one::<String>("output").map(String::as_str),
+            Some("session.csv")
+        );
+
+        // The merged file must be named
+        assert!(build_command()
+            .try_get_matches_from(["bbl_parser", "merge", "a.BFL", "b.BFL"])
+            .is_err());
+    }
+
+    #[test]
+    fn test_completions() {
+        let matches = build_command()
+            .try_get_matches_from(["bbl_parser", "completions", "bash"])
+            .unwrap();
+        let (name, sub_matches) = matches.subcommand().unwrap();
+        assert_eq!(name, "completions");
+        assert_eq!(sub_matches.get_one::<Shell>("shell"), Some(&Shell::Bash));
+        assert!(build_command()
+            .try_get_matches_from(["bbl_parser", "completions", "cmd.exe"])
+            .is_err());
+
+        let mut script = Vec::new();
+        generate(Shell::Bash, &mut build_command(), "bbl_parser", &mut script);
+        let script = String::from_utf8(script).unwrap();
+        for word in [
+            "export",
+            "info",
+            "stats",
+            "split",
+            "validate",
+            "--json-summary",
+        ] {
+            assert!(script.contains(word), "missing {word}");
+        }
+    }
+
+    #[test]
+    fn test_config_file() {
+        let config = parse_config(
+            r#"
+            output-dir = "exports"
+            csv-dialect = "rfc4180"
+            gpx = true
+            ndjson = true
+
+            [filter]
+            min-duration = 2.5
+            min-frames = 1000
+            "#,
+        )
+        .unwrap();
+        let thresholds = config.filter.thresholds();
+        assert_eq!(thresholds.min_duration_s, 2.5);
+        assert_eq!(thresholds.fallback_min_frames, 1000);
+        assert_eq!(
+            thresholds.min_gyro_range,
+            FilterThresholds::default().min_gyro_range
+        );
+
+        // The config fills in what the command line leaves out
+        let matches = build_command()
+            .try_get_matches_from(["bbl_parser", "--csv-dialect", "tsv", "flight.BBL"])
+            .unwrap();
+        let opts = export_options_from(&matches, &config);
+        assert_eq!(opts.output_dir.as_deref(), Some("exports"));
+        assert_eq!(opts.csv_options, CsvOptions::tsv());
+        assert!(opts.gpx);
+        assert!(!opts.kml);
+        assert!(opts.ndjson);
+        assert!(opts.exporters.is_empty());
+        assert!(opts.csv);
+
+        let matches = build_command()
+            .try_get_matches_from(["bbl_parser", "--no-csv", "flight.BBL"])
+            .unwrap();
+        assert!(!export_options_from(&matches, &config).csv);
+        assert_eq!(opts.filter_thresholds, thresholds);
+
+        let matches = build_command()
+            .try_get_matches_from(["bbl_parser", "--output-dir", "here", "flight.BBL"])
+            .unwrap();
+        let opts = export_options_from(&matches, &config);
+        assert_eq!(opts.output_dir.as_deref(), Some("here"));
+        assert_eq!(opts.csv_options, CsvOptions::rfc4180());
+
+        assert!(parse_config("csv-dialect = \"excel\"").is_err());
+        assert!(parse_config("output_dir = \"out\"").is_err());
+        assert!(parse_config("[filter]\nmin-duration = \"5s\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_time_offset() {
+        assert_eq!(parse_time_offset("90"), Ok(90_000_000));
+        assert_eq!(parse_time_offset("0.25"), Ok(250_000));
+        assert_eq!(parse_time_offset("1:05"), Ok(65_000_000));
+        assert_eq!(parse_time_offset("1:02:03.5"), Ok(3_723_500_000));
+        assert!(parse_time_offset("1:xx").is_err());
+        assert!(parse_time_offset("-5").is_err());
+        assert!(parse_time_offset("").is_err());
+    }
+
+    #[test]
+    fn test_parse_declination_degrees_minutes() {
+        assert_eq!(parse_declination_degrees_minutes("12.30"), Ok(12.5));
+        assert_eq!(parse_declination_degrees_minutes("-0.30"), Ok(-0.5));
+        assert_eq!(parse_declination_degrees_minutes("3"), Ok(3.0));
+        assert!(parse_declination_degrees_minutes("12.75").is_err());
+        assert!(parse_declination_degrees_minutes("east").is_err());
+    }
+
+    #[test]
+    fn test_log_summary_json() {
+        // --skip-existing skips before decoding, so there are no frame counts
+        let skipped = LogSummary {
+            skip_reason: Some("output files already exist".to_string()),
+            ..LogSummary::new(2, 3)
+        }
+        .to_json();
+        assert_eq!(skipped["log_number"], 2);
+        assert_eq!(skipped["total_logs"], 3);
+        assert_eq!(skipped["skipped"], true);
+        assert!(skipped["frames"].is_null());
+        assert!(skipped["duration_us"].is_null());
+
+        let exported = LogSummary {
+            stats: Some(FrameStats {
+                i_frames: 2,
+                p_frames: 6,
+                total_frames: 8,
+                start_time_us: 1_000,
+                end_time_us: 6_000,
+                failed_frames: 1,
+                ..Default::default()
+            }),
+            exported: vec![PathBuf::from("out/flight.csv")],
+            export_errors: vec!["GPX: disk full".to_string()],
+            ..LogSummary::new(1, 1)
+        }
+        .to_json();
+        assert_eq!(exported["skipped"], false);
+        assert!(exported["skip_reason"].is_null());
+        assert_eq!(exported["frames"]["I"], 2);
+        assert_eq!(exported["frames"]["total"], 8);
+        assert_eq!(exported["duration_us"], 5_000);
+        assert_eq!(exported["failed_frames"], 1);
+        assert_eq!(exported["exported"][0], "out/flight.csv");
+        assert_eq!(exported["export_errors"].as_array().unwrap().len(), 1);
     }
 
     #[test]
@@ -1084,6 +3275,7 @@ mod tests {
             s_frame_def: FrameDefinition::new(),
             g_frame_def: FrameDefinition::new(),
             h_frame_def: FrameDefinition::new(),
+            main_fields: Default::default(),
             sysconfig: HashMap::new(),
             all_headers: Vec::new(),
         };
@@ -1169,4 +3361,154 @@ mod tests {
         assert_eq!(format_failsafe_phase(99), "99");
         assert_eq!(format_failsafe_phase(-1), "-1");
     }
+
+    #[test]
+    fn test_stdin_output_names() {
+        let mut data = b"H Product:Blackbox flight data recorder by Nicholas Sherlock\n".to_vec();
+        data.extend_from_slice(
+            b"H Field I name:loopIteration,time\nH Field I signed:0,0\n\
+              H Field I predictor:0,0\nH Field I encoding:1,1\n",
+        );
+        data.extend_from_slice(&[b'I', 1, 0xD0, 0x0F, b'I', 2, 0xD8, 0x0F]);
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let output_dir = temp_dir.path().join("out");
+        let export_options = ExportOptions {
+            csv: true,
+            force_export: true,
+            output_dir: Some(output_dir.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        let mut summary = Vec::new();
+        for base_name in ["flight", "../x"] {
+            let input_path = stdin_input_path(base_name);
+            process_bbl_data(
+                &data,
+                &input_path,
+                &ProcessOptions::default(),
+                &export_options,
+                &mut summary,
+            )
+            .unwrap();
+        }
+
+        assert_eq!(stdin_input_path("../x"), PathBuf::from("x"));
+        assert_eq!(stdin_input_path(".."), PathBuf::from("stdin"));
+        let mut names: Vec<String> = fs::read_dir(&output_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            ["flight.csv", "flight.headers.csv", "x.csv", "x.headers.csv"]
+        );
+        assert!(!temp_dir.path().join("x.csv").exists());
+    }
+
+    #[test]
+    fn test_streamed_logs_match_memory() {
+        let mut log = b"H Product:Blackbox flight data recorder by Nicholas Sherlock\n".to_vec();
+        log.extend_from_slice(
+            b"H Field I name:loopIteration,time\nH Field I signed:0,0\n\
+              H Field I predictor:0,0\nH Field I encoding:1,1\n",
+        );
+        log.extend_from_slice(&[b'I', 1, 0xD0, 0x0F, b'I', 2, 0xD8, 0x0F]);
+        let data = [log.as_slice(), log.as_slice()].concat();
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let export = |logs: LogSource, dir: &str| {
+            let export_options = ExportOptions {
+                csv: true,
+                force_export: true,
+                output_dir: Some(temp_dir.path().join(dir).to_string_lossy().to_string()),
+                ..Default::default()
+            };
+            let process = ProcessOptions::default();
+            let processed = process_logs(
+                logs,
+                Path::new("flight"),
+                &process,
+                &export_options,
+                &mut Vec::new(),
+            )
+            .unwrap();
+            assert_eq!(processed, 2);
+        };
+        let positions = find_log_positions(&data, &ParserConfig::default());
+        export(
+            LogSource::Memory {
+                data: &data,
+                positions,
+            },
+            "memory",
+        );
+        export(
+            LogSource::stream(Box::new(data.as_slice()), &ParserConfig::default()),
+            "stream",
+        );
+
+        for name in ["flight.01.csv", "flight.02.csv", "flight.02.headers.csv"] {
+            assert_eq!(
+                fs::read(temp_dir.path().join("memory").join(name)).unwrap(),
+                fs::read(temp_dir.path().join("stream").join(name)).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_expand_input_paths_max_depth() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let nested = temp_dir.path().join("a").join("b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(temp_dir.path().join("top.BBL"), b"").unwrap();
+        fs::write(temp_dir.path().join("a").join("mid.BBL"), b"").unwrap();
+        fs::write(nested.join("deep.BBL"), b"").unwrap();
+        let input = vec![temp_dir.path().to_string_lossy().to_string()];
+
+        let scan = ScanOptions::default();
+        let files = expand_input_paths(&input, &mut HashSet::new(), &scan).unwrap();
+        assert_eq!(files.len(), 3);
+
+        let scan = ScanOptions {
+            max_depth: 1,
+            ..ScanOptions::default()
+        };
+        let files = expand_input_paths(&input, &mut HashSet::new(), &scan).unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|f| !f.ends_with("deep.BBL")));
+
+        let scan = ScanOptions {
+            max_depth: 0,
+            ..ScanOptions::default()
+        };
+        let files = expand_input_paths(&input, &mut HashSet::new(), &scan).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("top.BBL"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_expand_input_paths_follow_symlinks() {
+        let archive = tempfile::TempDir::new().unwrap();
+        fs::write(archive.path().join("linked.BBL"), b"").unwrap();
+
+        let scan_root = tempfile::TempDir::new().unwrap();
+        fs::write(scan_root.path().join("local.BBL"), b"").unwrap();
+        std::os::unix::fs::symlink(archive.path(), scan_root.path().join("archive")).unwrap();
+        let input = vec![scan_root.path().to_string_lossy().to_string()];
+
+        let files =
+            expand_input_paths(&input, &mut HashSet::new(), &ScanOptions::default()).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("local.BBL"));
+
+        let scan = ScanOptions {
+            follow_symlinks: true,
+            ..ScanOptions::default()
+        };
+        let files = expand_input_paths(&input, &mut HashSet::new(), &scan).unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().any(|f| f.ends_with("linked.BBL")));
+    }
 }
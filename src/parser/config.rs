@@ -0,0 +1,152 @@
+//! Parser configuration
+//!
+//! Safety limits and tuning knobs for the frame decoder.
+
+use crate::parser::diagnostics::{DiagnosticReporter, Diagnostics, StdoutDiagnostics};
+use crate::parser::sink::{FrameSink, ProgressSink};
+use std::sync::Arc;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Default cap on frames decoded per log
+pub const DEFAULT_MAX_FRAMES: usize = 1_000_000;
+
+/// Default cap on undecodable frames per log before parsing stops
+pub const DEFAULT_MAX_FAILED_FRAMES: usize = 10_000;
+
+/// How the frame decoder reacts to undecodable data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ParseMode {
+    /// Skip and count frames that fail to decode or validate
+    #[default]
+    Lenient,
+    /// Abort with a [`crate::BBLError`] on the first unknown predictor or
+    /// encoding, undecodable frame or invalid frame
+    Strict,
+}
+
+/// Parser safety limits and tuning
+///
+/// The defaults match the limits the parser has always applied. Raise
+/// `max_frames` for long endurance flights; when a limit stops parsing early,
+/// the log's [`crate::types::FrameStats::truncated`] flag is set.
+///
+/// # Examples
+/// ```rust
+/// use bbl_parser::ParserConfig;
+///
+/// let config = ParserConfig {
+///     max_frames: 5_000_000,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ParserConfig {
+    /// Stop decoding a log after this many frames
+    pub max_frames: usize,
+    /// Stop decoding a log after this many undecodable frames
+    pub max_failed_frames: usize,
+    /// Reject logs larger than this many bytes (`None` for no limit)
+    pub max_log_size: Option<usize>,
+    /// Stop decoding once this many main frames are stored (`None` for all);
+    /// unlike `max_frames`, this does not mark the log as truncated
+    pub main_frame_limit: Option<usize>,
+    /// Store only every Nth main frame (`1` keeps all); all frames are still
+    /// decoded, and the frame sink only sees the stored ones
+    pub decimate: usize,
+    /// Whether decode failures are tolerated or abort parsing
+    pub mode: ParseMode,
+    /// Keep the decoded main frames in `BBLLog::frames`; turn off when a
+    /// `frame_sink` consumes them, so memory stays flat for long logs
+    pub retain_frames: bool,
+    /// Keep every decoded frame, by type, in `BBLLog::debug_frames` even when
+    /// debug output is off
+    pub store_debug_frames: bool,
+    /// Also start a log at a block of `H Field I name:` headers that has no
+    /// log start marker, to decode logs whose first sectors were lost. Applies
+    /// to in-memory and file parsing, not to `BBLLogReader`
+    pub salvage: bool,
+    /// Receives debug output, progress and warnings; `None` prints them to
+    /// stdout/stderr
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub diagnostics: Option<Arc<dyn Diagnostics>>,
+    /// Receives every decoded frame as it is parsed
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub frame_sink: Option<Arc<dyn FrameSink>>,
+    /// Receives decoding progress of each log; when set, the periodic
+    /// "Parsed N frames so far..." messages are not reported
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub progress: Option<Arc<dyn ProgressSink>>,
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        Self {
+            max_frames: DEFAULT_MAX_FRAMES,
+            max_failed_frames: DEFAULT_MAX_FAILED_FRAMES,
+            max_log_size: None,
+            main_frame_limit: None,
+            decimate: 1,
+            mode: ParseMode::Lenient,
+            retain_frames: true,
+            store_debug_frames: false,
+            salvage: false,
+            diagnostics: None,
+            frame_sink: None,
+            progress: None,
+        }
+    }
+}
+
+impl ParserConfig {
+    /// Reporter for the configured diagnostics sink
+    pub fn reporter(&self, debug: bool) -> DiagnosticReporter<'_> {
+        let sink: &dyn Diagnostics = match &self.diagnostics {
+            Some(sink) => sink.as_ref(),
+            None => &StdoutDiagnostics,
+        };
+        DiagnosticReporter::new(sink, debug)
+    }
+}
+
+impl std::fmt::Debug for ParserConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ParserConfig")
+            .field("max_frames", &self.max_frames)
+            .field("max_failed_frames", &self.max_failed_frames)
+            .field("max_log_size", &self.max_log_size)
+            .field("main_frame_limit", &self.main_frame_limit)
+            .field("decimate", &self.decimate)
+            .field("mode", &self.mode)
+            .field("retain_frames", &self.retain_frames)
+            .field("store_debug_frames", &self.store_debug_frames)
+            .field("salvage", &self.salvage)
+            .field("diagnostics", &self.diagnostics.is_some())
+            .field("frame_sink", &self.frame_sink.is_some())
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+/// Compares the limits and options; the `diagnostics`, `frame_sink` and
+/// `progress` sinks are not compared
+impl PartialEq for ParserConfig {
+    fn eq(&self, other: &Self) -> bool {
+        self.max_frames == other.max_frames
+            && self.max_failed_frames == other.max_failed_frames
+            && self.max_log_size == other.max_log_size
+            && self.main_frame_limit == other.main_frame_limit
+            && self.decimate == other.decimate
+            && self.mode == other.mode
+            && self.retain_frames == other.retain_frames
+            && self.store_debug_frames == other.store_debug_frames
+            && self.salvage == other.salvage
+    }
+}
+
+impl Eq for ParserConfig {}
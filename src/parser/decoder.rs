@@ -1,4 +1,7 @@
+use crate::error::BBLError;
+use crate::parser::diagnostics::DiagnosticReporter;
 use crate::parser::stream::BBLDataStream;
+use crate::types::SysConfigValue;
 use anyhow::Result;
 
 // BBL Encoding constants - directly from JavaScript reference
@@ -25,6 +28,33 @@ pub const PREDICT_VBATREF: u8 = 9;
 pub const PREDICT_LAST_MAIN_FRAME_TIME: u8 = 10;
 pub const PREDICT_MINMOTOR: u8 = 11;
 
+/// Field encodings the frame decoder can read
+pub const SUPPORTED_ENCODINGS: &[u8] = &[
+    ENCODING_SIGNED_VB,
+    ENCODING_UNSIGNED_VB,
+    ENCODING_NEG_14BIT,
+    ENCODING_TAG8_8SVB,
+    ENCODING_TAG2_3S32,
+    ENCODING_TAG8_4S16,
+    ENCODING_NULL,
+];
+
+/// Field predictors the frame decoder applies
+pub const SUPPORTED_PREDICTORS: &[u8] = &[
+    PREDICT_0,
+    PREDICT_PREVIOUS,
+    PREDICT_STRAIGHT_LINE,
+    PREDICT_AVERAGE_2,
+    PREDICT_MINTHROTTLE,
+    PREDICT_MOTOR_0,
+    PREDICT_INC,
+    PREDICT_HOME_COORD,
+    PREDICT_1500,
+    PREDICT_VBATREF,
+    PREDICT_LAST_MAIN_FRAME_TIME,
+    PREDICT_MINMOTOR,
+];
+
 // Domain-specific constants for corruption detection
 //
 // MAX_REASONABLE_VBAT_RAW: Maximum reasonable raw vbatLatest value before considering it corrupted.
@@ -62,7 +92,7 @@ pub fn decode_field_value(
             values[index] = 0;
         }
         _ => {
-            return Err(anyhow::anyhow!("Invalid encoding type: {}", encoding));
+            return Err(BBLError::UnsupportedEncoding(encoding).into());
         }
     }
     Ok(())
@@ -78,7 +108,7 @@ pub fn apply_predictor(
     current_frame: &[i32],
     previous_frame: &[i32],
     previous2_frame: &[i32],
-    sysconfig: &std::collections::HashMap<String, i32>,
+    sysconfig: &std::collections::HashMap<String, SysConfigValue>,
 ) -> Result<i32> {
     // Call the enhanced version with default parameters
     Ok(apply_predictor_with_debug(
@@ -88,15 +118,19 @@ pub fn apply_predictor(
         current_frame,
         Some(previous_frame),
         Some(previous2_frame),
+        None,
         0,
         sysconfig,
         &[],
-        false,
+        DiagnosticReporter::silent(),
     ))
 }
 
 /// Enhanced apply_predictor with debug support, field names lookup, and corruption prevention
 /// This matches the CLI implementation's full feature set
+///
+/// `home_coord` is the `GPS_home[0]`, `GPS_home[1]` pair of the last H-frame,
+/// which `PREDICT_HOME_COORD` adds to `GPS_coord[0]` and `GPS_coord[1]`.
 #[allow(clippy::too_many_arguments)]
 pub fn apply_predictor_with_debug(
     field_index: usize,
@@ -105,10 +139,11 @@ pub fn apply_predictor_with_debug(
     current_frame: &[i32],
     previous_frame: Option<&[i32]>,
     previous2_frame: Option<&[i32]>,
+    home_coord: Option<[i32; 2]>,
     skipped_frames: u32,
-    sysconfig: &std::collections::HashMap<String, i32>,
+    sysconfig: &std::collections::HashMap<String, SysConfigValue>,
     field_names: &[String],
-    debug: bool,
+    diag: DiagnosticReporter,
 ) -> i32 {
     match predictor {
         PREDICT_0 => raw_value,
@@ -126,11 +161,14 @@ pub fn apply_predictor_with_debug(
                     {
                         // Check if previous value is corrupted (way too high for voltage)
                         if prev[field_index] > MAX_REASONABLE_VBAT_RAW {
-                            if debug {
-                                eprintln!("DEBUG: Fixed corrupted vbatLatest previous value {} replaced with reasonable estimate", prev[field_index]);
+                            if diag.debug_enabled() {
+                                diag.debug(format!("DEBUG: Fixed corrupted vbatLatest previous value {} replaced with reasonable estimate", prev[field_index]));
                             }
                             // Use a reasonable voltage estimate based on vbatref
-                            let vbatref = sysconfig.get("vbatref").copied().unwrap_or(4095);
+                            let vbatref = sysconfig
+                                .get("vbatref")
+                                .and_then(SysConfigValue::as_int)
+                                .unwrap_or(4095);
                             return vbatref + raw_value;
                         }
                     }
@@ -144,6 +182,8 @@ pub fn apply_predictor_with_debug(
             }
         }
 
+        // Extrapolated from the last two logged frames, as the firmware does,
+        // so iterations skipped by the P interval do not change the prediction
         PREDICT_STRAIGHT_LINE => {
             if let (Some(prev), Some(prev2)) = (previous_frame, previous2_frame) {
                 if field_index < prev.len() && field_index < prev2.len() {
@@ -169,7 +209,10 @@ pub fn apply_predictor_with_debug(
         }
 
         PREDICT_MINTHROTTLE => {
-            let minthrottle = sysconfig.get("minthrottle").copied().unwrap_or(1150);
+            let minthrottle = sysconfig
+                .get("minthrottle")
+                .and_then(SysConfigValue::as_int)
+                .unwrap_or(1150);
             raw_value + minthrottle
         }
 
@@ -186,11 +229,11 @@ pub fn apply_predictor_with_debug(
             // This is frame-definition-dependent and may not be correct for all firmware versions
             let motor0_index = 39;
             if motor0_index < current_frame.len() {
-                if debug {
-                    eprintln!(
+                if diag.debug_enabled() {
+                    diag.warn(format!(
                         "WARNING: PREDICT_MOTOR_0 falling back to hardcoded index {} (motor[0] not found in field_names)",
                         motor0_index
-                    );
+                    ));
                 }
                 current_frame[motor0_index] + raw_value
             } else {
@@ -209,14 +252,23 @@ pub fn apply_predictor_with_debug(
         }
 
         PREDICT_HOME_COORD => {
-            // GPS home coordinate prediction - for now just return value
-            raw_value
+            // Latitude fields predict from the home latitude, longitude fields
+            // from the home longitude; without a home position the value stands
+            let home_index = usize::from(
+                field_names
+                    .get(field_index)
+                    .is_some_and(|name| name.ends_with("[1]")),
+            );
+            raw_value.wrapping_add(home_coord.map_or(0, |home| home[home_index]))
         }
 
         PREDICT_1500 => raw_value + 1500,
 
         PREDICT_VBATREF => {
-            let vbatref = sysconfig.get("vbatref").copied().unwrap_or(4095);
+            let vbatref = sysconfig
+                .get("vbatref")
+                .and_then(SysConfigValue::as_int)
+                .unwrap_or(4095);
 
             // CRITICAL FIX: Check for corrupted raw values in vbatLatest
             // Uses symmetric range based on MAX_REASONABLE_VBAT_RAW constant
@@ -227,11 +279,11 @@ pub fn apply_predictor_with_debug(
                     .unwrap_or(false)
                 && !(-MAX_REASONABLE_VBAT_RAW..=MAX_REASONABLE_VBAT_RAW).contains(&raw_value)
             {
-                if debug {
-                    eprintln!(
+                if diag.debug_enabled() {
+                    diag.debug(format!(
                         "DEBUG: Fixed corrupted vbatLatest raw_value {} (outside +/-{}) replaced with vbatref",
                         raw_value, MAX_REASONABLE_VBAT_RAW
-                    );
+                    ));
                 }
                 return vbatref;
             }
@@ -240,11 +292,11 @@ pub fn apply_predictor_with_debug(
         }
 
         PREDICT_MINMOTOR => {
-            // Get the min motor value from motorOutput[0] or motorOutput
+            // The min motor value is the first element of motorOutput
             let minmotor = sysconfig
-                .get("motorOutput[0]")
-                .or_else(|| sysconfig.get("motorOutput"))
-                .copied()
+                .get("motorOutput")
+                .and_then(SysConfigValue::as_int_array)
+                .and_then(|values| values.first().copied())
                 .unwrap_or(48);
             raw_value + minmotor
         }
@@ -0,0 +1,207 @@
+//! Parser diagnostics
+//!
+//! The parser reports debug output, progress and warnings as structured
+//! [`Diagnostic`] values through a [`Diagnostics`] sink instead of printing
+//! directly. Without a custom sink, messages go to stdout/stderr as before.
+
+use std::sync::Mutex;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Severity of a parser diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DiagnosticLevel {
+    /// Detailed decoding trace, only produced when debug output is enabled
+    Debug,
+    /// Progress information
+    Info,
+    /// Recoverable problems with the log data
+    Warning,
+}
+
+/// One message reported by the parser
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Diagnostic {
+    pub level: DiagnosticLevel,
+    pub message: String,
+}
+
+/// Receiver for parser diagnostics
+///
+/// Implemented for [`StdoutDiagnostics`], [`SilentDiagnostics`],
+/// [`CollectingDiagnostics`], `TracingDiagnostics` (with the `tracing`
+/// feature) and any `Fn(Diagnostic) + Send + Sync` closure.
+pub trait Diagnostics: Send + Sync {
+    fn report(&self, diagnostic: Diagnostic);
+}
+
+impl<F> Diagnostics for F
+where
+    F: Fn(Diagnostic) + Send + Sync,
+{
+    fn report(&self, diagnostic: Diagnostic) {
+        self(diagnostic)
+    }
+}
+
+/// Default sink: debug and progress messages go to stdout, warnings to stderr
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdoutDiagnostics;
+
+impl Diagnostics for StdoutDiagnostics {
+    fn report(&self, diagnostic: Diagnostic) {
+        match diagnostic.level {
+            DiagnosticLevel::Debug | DiagnosticLevel::Info => {
+                use std::io::Write;
+                println!("{}", diagnostic.message);
+                std::io::stdout().flush().unwrap_or_default();
+            }
+            DiagnosticLevel::Warning => eprintln!("{}", diagnostic.message),
+        }
+    }
+}
+
+/// Sink that discards every diagnostic
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SilentDiagnostics;
+
+impl Diagnostics for SilentDiagnostics {
+    fn report(&self, _diagnostic: Diagnostic) {}
+}
+
+/// Sink that forwards diagnostics as `tracing` events (`tracing` feature)
+#[cfg(feature = "tracing")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingDiagnostics;
+
+#[cfg(feature = "tracing")]
+impl Diagnostics for TracingDiagnostics {
+    fn report(&self, diagnostic: Diagnostic) {
+        match diagnostic.level {
+            DiagnosticLevel::Debug => tracing::debug!("{}", diagnostic.message),
+            DiagnosticLevel::Info => tracing::info!("{}", diagnostic.message),
+            DiagnosticLevel::Warning => tracing::warn!("{}", diagnostic.message),
+        }
+    }
+}
+
+/// Sink that keeps diagnostics in memory for later inspection
+///
+/// # Examples
+/// ```rust
+/// use bbl_parser::{CollectingDiagnostics, ParserConfig};
+/// use std::sync::Arc;
+///
+/// let collector = Arc::new(CollectingDiagnostics::default());
+/// let config = ParserConfig {
+///     diagnostics: Some(collector.clone()),
+///     ..Default::default()
+/// };
+/// // ... parse with `config`, then:
+/// let warnings = collector.take();
+/// assert!(warnings.is_empty());
+/// ```
+#[derive(Debug, Default)]
+pub struct CollectingDiagnostics {
+    entries: Mutex<Vec<Diagnostic>>,
+}
+
+impl CollectingDiagnostics {
+    /// Remove and return everything collected so far
+    pub fn take(&self) -> Vec<Diagnostic> {
+        std::mem::take(&mut *self.entries.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl Diagnostics for CollectingDiagnostics {
+    fn report(&self, diagnostic: Diagnostic) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(diagnostic);
+    }
+}
+
+/// Handle the parser threads through decoding: a sink plus the debug switch
+///
+/// Debug messages are only formatted and reported when debug output is enabled.
+#[derive(Clone, Copy)]
+pub struct DiagnosticReporter<'a> {
+    sink: &'a dyn Diagnostics,
+    debug: bool,
+}
+
+impl<'a> DiagnosticReporter<'a> {
+    pub fn new(sink: &'a dyn Diagnostics, debug: bool) -> Self {
+        Self { sink, debug }
+    }
+
+    /// Reporter that discards everything
+    pub fn silent() -> DiagnosticReporter<'static> {
+        DiagnosticReporter {
+            sink: &SilentDiagnostics,
+            debug: false,
+        }
+    }
+
+    /// Whether debug messages are wanted
+    pub fn debug_enabled(&self) -> bool {
+        self.debug
+    }
+
+    pub fn debug(&self, message: impl Into<String>) {
+        if self.debug {
+            self.report(DiagnosticLevel::Debug, message);
+        }
+    }
+
+    pub fn info(&self, message: impl Into<String>) {
+        self.report(DiagnosticLevel::Info, message);
+    }
+
+    pub fn warn(&self, message: impl Into<String>) {
+        self.report(DiagnosticLevel::Warning, message);
+    }
+
+    fn report(&self, level: DiagnosticLevel, message: impl Into<String>) {
+        self.sink.report(Diagnostic {
+            level,
+            message: message.into(),
+        });
+    }
+}
+
+impl std::fmt::Debug for DiagnosticReporter<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiagnosticReporter")
+            .field("debug", &self.debug)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reporter_levels() {
+        let collector = CollectingDiagnostics::default();
+
+        let quiet = DiagnosticReporter::new(&collector, false);
+        quiet.debug("hidden");
+        quiet.warn("shown");
+        let verbose = DiagnosticReporter::new(&collector, true);
+        verbose.debug("trace");
+
+        let levels: Vec<DiagnosticLevel> = collector.take().into_iter().map(|d| d.level).collect();
+        assert_eq!(
+            levels,
+            vec![DiagnosticLevel::Warning, DiagnosticLevel::Debug]
+        );
+        assert!(collector.take().is_empty());
+    }
+}
@@ -0,0 +1,352 @@
+//! Frame encoding helper module
+//!
+//! The inverse of [`crate::parser::stream`] and [`crate::parser::frame::parse_frame_data`]:
+//! writes field values with the encodings and predictors of a frame definition,
+//! so that the decoder reads back exactly the values that were written.
+
+use crate::error::BBLError;
+use crate::parser::decoder::*;
+use crate::parser::diagnostics::DiagnosticReporter;
+use crate::types::{FrameDefinition, SysConfigValue};
+use anyhow::Result;
+use std::collections::HashMap;
+
+/// Error for a value that does not fit the encoding of its field
+fn out_of_range(value: i32, encoding: &str) -> anyhow::Error {
+    BBLError::InvalidFrame(format!("value {value} does not fit {encoding} encoding")).into()
+}
+
+/// Write an unsigned variable byte (7 bits per byte, least significant first)
+pub(crate) fn write_unsigned_vb(out: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        out.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Write a ZigZag-encoded signed variable byte
+pub(crate) fn write_signed_vb(out: &mut Vec<u8>, value: i32) {
+    write_unsigned_vb(out, ((value << 1) ^ (value >> 31)) as u32);
+}
+
+/// Write a negated 14-bit sign-magnitude value, read back by `read_neg_14bit`
+pub(crate) fn write_neg_14bit(out: &mut Vec<u8>, value: i32) -> Result<()> {
+    if !(-0x1fff..=0x1fff).contains(&value) {
+        return Err(out_of_range(value, "NEG_14BIT"));
+    }
+    let unsigned = if value > 0 {
+        0x2000 | value as u32
+    } else {
+        value.unsigned_abs()
+    };
+    write_unsigned_vb(out, unsigned);
+    Ok(())
+}
+
+/// Write up to 4 values as Tag8_4S16: a selector byte, then nibble-packed 4, 8 or 16-bit values
+pub(crate) fn write_tag8_4s16(out: &mut Vec<u8>, values: &[i32]) -> Result<()> {
+    let mut selector = 0u8;
+    for (i, &value) in values.iter().take(4).enumerate() {
+        let field_type = match value {
+            0 => 0,
+            -8..=7 => 1,
+            -128..=127 => 2,
+            -32768..=32767 => 3,
+            _ => return Err(out_of_range(value, "TAG8_4S16")),
+        };
+        selector |= field_type << (i * 2);
+    }
+    out.push(selector);
+
+    // Half-filled byte whose low nibble is still free
+    let mut pending: Option<u8> = None;
+    for (i, &value) in values.iter().take(4).enumerate() {
+        match (selector >> (i * 2)) & 0x03 {
+            1 => {
+                let nibble = value as u8 & 0x0f;
+                match pending.take() {
+                    Some(high) => out.push(high | nibble),
+                    None => pending = Some(nibble << 4),
+                }
+            }
+            2 => {
+                let byte = value as u8;
+                match pending.take() {
+                    Some(high) => {
+                        out.push(high | (byte >> 4));
+                        pending = Some(byte << 4);
+                    }
+                    None => out.push(byte),
+                }
+            }
+            3 => {
+                let word = value as u16;
+                match pending.take() {
+                    Some(high) => {
+                        out.push(high | (word >> 12) as u8);
+                        out.push((word >> 4) as u8);
+                        pending = Some((word as u8) << 4);
+                    }
+                    None => out.extend_from_slice(&word.to_be_bytes()),
+                }
+            }
+            _ => {}
+        }
+    }
+    out.extend(pending);
+    Ok(())
+}
+
+/// Write 3 values as Tag2_3S32, choosing the smallest of the 2, 4, 6-bit or per-value layouts
+pub(crate) fn write_tag2_3s32(out: &mut Vec<u8>, values: &[i32; 3]) {
+    let fits = |bits: u32| {
+        let limit = 1i32 << (bits - 1);
+        values.iter().all(|&value| (-limit..limit).contains(&value))
+    };
+    if fits(2) {
+        out.push(
+            ((values[0] as u8 & 0x03) << 4)
+                | ((values[1] as u8 & 0x03) << 2)
+                | (values[2] as u8 & 0x03),
+        );
+    } else if fits(4) {
+        out.push(0x40 | (values[0] as u8 & 0x0f));
+        out.push(((values[1] as u8 & 0x0f) << 4) | (values[2] as u8 & 0x0f));
+    } else if fits(6) {
+        out.push(0x80 | (values[0] as u8 & 0x3f));
+        out.push(values[1] as u8 & 0x3f);
+        out.push(values[2] as u8 & 0x3f);
+    } else {
+        let sizes = values.map(|value| match value {
+            -128..=127 => 1usize,
+            -32768..=32767 => 2,
+            -8_388_608..=8_388_607 => 3,
+            _ => 4,
+        });
+        let selector = sizes
+            .iter()
+            .enumerate()
+            .fold(0xc0u8, |selector, (i, &size)| {
+                selector | ((size as u8 - 1) << (i * 2))
+            });
+        out.push(selector);
+        for (value, size) in values.iter().zip(sizes) {
+            out.extend_from_slice(&value.to_le_bytes()[..size]);
+        }
+    }
+}
+
+/// Write a group of Tag8_8SVB values; a group of one is a bare signed VB
+pub(crate) fn write_tag8_8svb(out: &mut Vec<u8>, values: &[i32]) {
+    if let [value] = values {
+        write_signed_vb(out, *value);
+        return;
+    }
+    let header = values
+        .iter()
+        .take(8)
+        .enumerate()
+        .filter(|(_, &value)| value != 0)
+        .fold(0u8, |header, (i, _)| header | (1 << i));
+    out.push(header);
+    for &value in values.iter().take(8).filter(|&&value| value != 0) {
+        write_signed_vb(out, value);
+    }
+}
+
+/// Encode the field values of one I, P or G frame (without the frame type byte)
+///
+/// Mirrors [`crate::parser::frame::parse_frame_data`]: each value is written as
+/// its difference from the field's prediction, computed from the same history
+/// the decoder will hold. `PREDICT_INC` fields are implied and not written;
+/// `skipped_frames` counts the iterations they skip, as when decoding.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn encode_frame_data(
+    out: &mut Vec<u8>,
+    frame_def: &FrameDefinition,
+    values: &[i32],
+    previous_frame: Option<&[i32]>,
+    previous2_frame: Option<&[i32]>,
+    home_coord: Option<[i32; 2]>,
+    skipped_frames: u32,
+    sysconfig: &HashMap<String, SysConfigValue>,
+) -> Result<()> {
+    // Values the decoder has already read when it predicts each field
+    let mut current = vec![0i32; values.len()];
+    let residual = |i: usize, current: &mut [i32]| {
+        let prediction = apply_predictor_with_debug(
+            i,
+            frame_def.fields[i].predictor,
+            0,
+            current,
+            previous_frame,
+            previous2_frame,
+            home_coord,
+            skipped_frames,
+            sysconfig,
+            &frame_def.field_names,
+            DiagnosticReporter::silent(),
+        );
+        let value = values.get(i).copied().unwrap_or(0);
+        if let Some(slot) = current.get_mut(i) {
+            *slot = value;
+        }
+        value.wrapping_sub(prediction)
+    };
+
+    let field_count = frame_def.fields.len();
+    let mut i = 0;
+    while i < field_count {
+        let field = &frame_def.fields[i];
+
+        if field.predictor == PREDICT_INC {
+            residual(i, &mut current);
+            i += 1;
+            continue;
+        }
+
+        match field.encoding {
+            ENCODING_TAG8_4S16 => {
+                let mut group = [0i32; 4];
+                for (j, slot) in group.iter_mut().enumerate() {
+                    if i + j < field_count {
+                        *slot = residual(i + j, &mut current);
+                    }
+                }
+                write_tag8_4s16(out, &group)?;
+                i += 4;
+            }
+            ENCODING_TAG2_3S32 => {
+                let mut group = [0i32; 3];
+                for (j, slot) in group.iter_mut().enumerate() {
+                    if i + j < field_count {
+                        *slot = residual(i + j, &mut current);
+                    }
+                }
+                write_tag2_3s32(out, &group);
+                i += 3;
+            }
+            ENCODING_TAG8_8SVB => {
+                // Same grouping of consecutive fields as the decoder
+                let mut group_count = 1;
+                for j in i + 1..i + 8.min(field_count - i) {
+                    if frame_def.fields[j].encoding != ENCODING_TAG8_8SVB {
+                        break;
+                    }
+                    group_count += 1;
+                }
+                let group: Vec<i32> = (i..i + group_count)
+                    .map(|k| residual(k, &mut current))
+                    .collect();
+                write_tag8_8svb(out, &group);
+                i += group_count;
+            }
+            ENCODING_SIGNED_VB => {
+                write_signed_vb(out, residual(i, &mut current));
+                i += 1;
+            }
+            ENCODING_UNSIGNED_VB => {
+                write_unsigned_vb(out, residual(i, &mut current) as u32);
+                i += 1;
+            }
+            ENCODING_NEG_14BIT => {
+                write_neg_14bit(out, residual(i, &mut current))?;
+                i += 1;
+            }
+            ENCODING_NULL => {
+                residual(i, &mut current);
+                i += 1;
+            }
+            encoding => return Err(BBLError::UnsupportedEncoding(encoding).into()),
+        }
+    }
+
+    Ok(())
+}
+
+/// Encode the field values of one S or H frame (without the frame type byte)
+///
+/// These frames carry their values without prediction. Mirrors
+/// [`crate::parser::frame::parse_s_frame`] when `group_tag2_3s32` is set, and
+/// [`crate::parser::gps::parse_h_frame`], which reads every field on its own,
+/// when it is not.
+pub(crate) fn encode_unpredicted_frame_data(
+    out: &mut Vec<u8>,
+    frame_def: &FrameDefinition,
+    values: &[i32],
+    group_tag2_3s32: bool,
+) -> Result<()> {
+    let value = |i: usize| values.get(i).copied().unwrap_or(0);
+    let mut i = 0;
+    while i < frame_def.fields.len() {
+        match frame_def.fields[i].encoding {
+            ENCODING_TAG2_3S32 if group_tag2_3s32 => {
+                write_tag2_3s32(out, &[value(i), value(i + 1), value(i + 2)]);
+                i += 3;
+                continue;
+            }
+            ENCODING_UNSIGNED_VB => write_unsigned_vb(out, value(i) as u32),
+            ENCODING_NEG_14BIT => write_neg_14bit(out, value(i))?,
+            ENCODING_NULL => {}
+            // Signed VB, and the decoders' fallback for other encodings
+            _ => write_signed_vb(out, value(i)),
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::stream::BBLDataStream;
+
+    #[test]
+    fn test_encodings_round_trip() {
+        let mut out = Vec::new();
+        for value in [0, 1, -1, 63, -64, 1000, -100_000, i32::MAX, i32::MIN] {
+            write_signed_vb(&mut out, value);
+        }
+        for value in [-8191, -1, 0, 1, 8191] {
+            write_neg_14bit(&mut out, value).unwrap();
+        }
+        let tag8_4s16 = [[0, 7, -8, 100], [3, -300, 5, 0], [-32768, 2, 32767, -128]];
+        for values in &tag8_4s16 {
+            write_tag8_4s16(&mut out, values).unwrap();
+        }
+        let tag2_3s32 = [
+            [1, -2, 0],
+            [7, -8, 3],
+            [31, -32, 0],
+            [127, -40000, i32::MIN],
+        ];
+        for values in &tag2_3s32 {
+            write_tag2_3s32(&mut out, values);
+        }
+        write_tag8_8svb(&mut out, &[0, 5, 0, -9, 0]);
+
+        let mut stream = BBLDataStream::new(&out);
+        for value in [0, 1, -1, 63, -64, 1000, -100_000, i32::MAX, i32::MIN] {
+            assert_eq!(stream.read_signed_vb().unwrap(), value);
+        }
+        for value in [-8191, -1, 0, 1, 8191] {
+            assert_eq!(stream.read_neg_14bit().unwrap(), value);
+        }
+        let mut values = [0i32; 8];
+        for expected in &tag8_4s16 {
+            stream.read_tag8_4s16_v2(&mut values).unwrap();
+            assert_eq!(&values[..4], expected);
+        }
+        for expected in &tag2_3s32 {
+            stream.read_tag2_3s32(&mut values).unwrap();
+            assert_eq!(&values[..3], expected);
+        }
+        stream.read_tag8_8svb_counted(&mut values, 5).unwrap();
+        assert_eq!(&values[..5], &[0, 5, 0, -9, 0]);
+        assert_eq!(stream.pos, out.len());
+
+        assert!(write_neg_14bit(&mut out, 8192).is_err());
+        assert!(write_tag8_4s16(&mut out, &[40000]).is_err());
+    }
+}
@@ -3,10 +3,14 @@
 //! Contains functions for parsing E-frames (Event data) from blackbox log data.
 //! These helpers are used by both the library parser and CLI binary.
 
+use crate::parser::diagnostics::DiagnosticReporter;
 use crate::parser::stream::BBLDataStream;
 use crate::types::EventFrame;
 use anyhow::Result;
 
+/// Trailer Betaflight writes after the log end event
+pub(crate) const LOG_END_TRAILER: &[u8] = b"End of log\0";
+
 /// Helper function to parse inflight adjustment events (types 4 and 13)
 /// Returns the event description string
 fn parse_inflight_adjustment(
@@ -35,9 +39,9 @@ fn parse_inflight_adjustment(
 /// E-frames contain various event types such as sync beeps, autotune cycles,
 /// inflight adjustments, logging resume, disarm, flight mode changes, and log end.
 /// Each event type has its own data format that this function decodes.
-pub fn parse_e_frame(stream: &mut BBLDataStream, debug: bool) -> Result<EventFrame> {
-    if debug {
-        println!("Parsing E frame (Event frame)");
+pub fn parse_e_frame(stream: &mut BBLDataStream, diag: DiagnosticReporter) -> Result<EventFrame> {
+    if diag.debug_enabled() {
+        diag.debug("Parsing E frame (Event frame)");
     }
 
     // Read event type (1 byte)
@@ -143,7 +147,10 @@ pub fn parse_e_frame(stream: &mut BBLDataStream, debug: bool) -> Result<EventFra
             "Flight mode change".to_string()
         }
         255 => {
-            // FLIGHT_LOG_EVENT_LOG_END
+            // FLIGHT_LOG_EVENT_LOG_END; the "End of log" trailer belongs to the event
+            if stream.remaining().starts_with(LOG_END_TRAILER) {
+                stream.set_position(stream.pos + LOG_END_TRAILER.len());
+            }
             "Log end".to_string()
         }
         _ => {
@@ -158,11 +165,11 @@ pub fn parse_e_frame(stream: &mut BBLDataStream, debug: bool) -> Result<EventFra
         }
     };
 
-    if debug {
-        println!(
+    if diag.debug_enabled() {
+        diag.debug(format!(
             "DEBUG: Event - Type: {}, Description: {}",
             event_type, event_name
-        );
+        ));
     }
 
     Ok(EventFrame {
@@ -1,22 +1,34 @@
 use crate::conversion::{
     convert_gps_altitude, convert_gps_coordinate, convert_gps_course, convert_gps_speed,
 };
+use crate::error::BBLError;
 use crate::parser::{
-    decoder::apply_predictor_with_debug, decoder::*, event::parse_e_frame, gps::*,
+    config::{ParseMode, ParserConfig},
+    decoder::apply_predictor_with_debug,
+    decoder::*,
+    diagnostics::DiagnosticReporter,
+    event::{parse_e_frame, LOG_END_TRAILER},
+    gps::*,
+    sink::{ParseProgress, PROGRESS_INTERVAL_FRAMES},
     stream::BBLDataStream,
 };
 use crate::types::{
-    DecodedFrame, EventFrame, FrameDefinition, FrameHistory, FrameStats, GpsCoordinate,
-    GpsHomeCoordinate,
+    DecodedFrame, EventFrame, FrameColumns, FrameDefinition, FrameError, FrameErrorReason,
+    FrameHistory, FrameStats, GpsCoordinate, GpsHomeCoordinate, SysConfigValue,
 };
 use crate::ExportOptions;
 use anyhow::Result;
 use std::collections::HashMap;
-use std::io::Write;
+use std::sync::Arc;
 
 /// Parse frames from binary data
 ///
-/// Parses ALL frames from binary data and stores them for CSV export.
+/// Parses ALL frames from binary data. Main (I/P) frames, with the latest slow
+/// data merged in, are stored column-wise for CSV export; GPS, home and event
+/// frames are collected into their own lists. With
+/// `ExportOptions::split_frame_types`, S and raw G frames are also stored
+/// column-wise at their own rate. After a frame fails to decode, decoding
+/// resumes at the next I-frame that continues the log.
 /// This is the unified implementation used by both CLI and crate.
 ///
 /// # Arguments
@@ -30,29 +42,84 @@ pub fn parse_frames(
     header: &crate::types::BBLHeader,
     debug: bool,
     export_options: &ExportOptions,
+    config: &ParserConfig,
 ) -> Result<(
     FrameStats,
-    Vec<DecodedFrame>,
+    FrameColumns,
     Option<HashMap<char, Vec<DecodedFrame>>>,
     Vec<GpsCoordinate>,
     Vec<GpsHomeCoordinate>,
     Vec<EventFrame>,
+    FrameColumns,
+    FrameColumns,
 )> {
+    let diag = config.reporter(debug);
     let mut stats = FrameStats::default();
-    let mut frames = Vec::new();
+    // Main frames share the header's interned field table, so values are
+    // stored by column index and field names are never cloned per frame
+    let mut frames = FrameColumns::with_table(Arc::clone(&header.main_fields));
+    let i_columns: Vec<usize> = header
+        .i_frame_def
+        .field_names
+        .iter()
+        .map(|name| frames.add_field(name.as_str()))
+        .collect();
+    let s_columns: Vec<usize> = header
+        .s_frame_def
+        .field_names
+        .iter()
+        .map(|name| frames.add_field(name.as_str()))
+        .collect();
+    // I-frame position of each P-frame field
+    let p_to_i: Vec<Option<usize>> = header
+        .p_frame_def
+        .field_names
+        .iter()
+        .map(|name| {
+            header
+                .i_frame_def
+                .field_names
+                .iter()
+                .position(|n| n == name)
+        })
+        .collect();
+    // S and G frames at their own rate, for per-frame-type CSVs
+    let split_frame_types = export_options.split_frame_types;
+    let mut slow_frames = FrameColumns::with_fields(header.s_frame_def.field_names.iter().cloned());
+    let mut gps_frames = FrameColumns::with_fields(header.g_frame_def.field_names.iter().cloned());
+    let i_time = field_position(&header.i_frame_def, "time");
+    let i_loop = field_position(&header.i_frame_def, "loopIteration");
+
     let mut debug_frames: HashMap<char, Vec<DecodedFrame>> = HashMap::new();
+    let store_debug_frames = debug || config.store_debug_frames;
+    let frame_sink = config.frame_sink.as_deref();
+    let progress_sink = config.progress.as_deref();
+    let decimate = config.decimate.max(1);
+    let main_frame_limit = config.main_frame_limit;
+    let retain_frames = config.retain_frames;
+    let mut main_frames_decoded = 0usize;
+    let mut main_frames_stored = 0usize;
+    let mut limit_reached = false;
     let mut last_main_frame_timestamp = 0u64; // Track timestamp for S frames
-
-    // Track the most recent S-frame data for merging (following JavaScript approach)
-    let mut last_slow_data: HashMap<String, i32> = HashMap::new();
+                                              // Loop iteration and time of the last accepted main frame
+    let mut last_main_frame: Option<(u32, u64)> = None;
+    // Loop iterations the firmware logs, and the range of those seen
+    let intervals = FrameIntervals::from_sysconfig(&header.sysconfig);
+    let mut iteration_range: Option<(u32, u32)> = None;
+    // Set after a failed frame until an I-frame continues the log
+    let mut resyncing = false;
+
+    // Track the most recent S-frame data for merging (following JavaScript approach),
+    // indexed by S-frame field position
+    let mut last_slow_data: Vec<Option<i32>> = vec![None; header.s_frame_def.field_names.len()];
 
     if debug {
-        println!("Binary data size: {} bytes", binary_data.len());
+        diag.debug(format!("Binary data size: {} bytes", binary_data.len()));
         if !binary_data.is_empty() {
-            println!(
+            diag.debug(format!(
                 "First 16 bytes: {:02X?}",
                 &binary_data[..16.min(binary_data.len())]
-            );
+            ));
         }
     }
 
@@ -64,6 +131,8 @@ pub fn parse_frames(
             Vec::new(),
             Vec::new(),
             Vec::new(),
+            slow_frames,
+            gps_frames,
         ));
     }
 
@@ -82,13 +151,58 @@ pub fn parse_frames(
 
     // GPS frame history for differential encoding
     let mut gps_frame_history: Vec<i32> = Vec::new();
+    // Home position of the last H-frame, which G-frame coordinates are predicted from
+    let mut gps_home: Option<[i32; 2]> = None;
+
+    let strict = config.mode == ParseMode::Strict;
+    let mut log_ended = false;
+    if strict {
+        for (frame_type, frame_def) in [
+            ('I', &header.i_frame_def),
+            ('P', &header.p_frame_def),
+            ('S', &header.s_frame_def),
+            ('G', &header.g_frame_def),
+            ('H', &header.h_frame_def),
+        ] {
+            validate_frame_definition(frame_type, frame_def)?;
+        }
+    }
 
     let mut stream = BBLDataStream::new(binary_data);
 
     // Main frame parsing loop - process frames as a stream
     while !stream.eof {
+        if resyncing {
+            match resync_offset(stream.remaining()) {
+                Some(skip) => stream.set_position(stream.pos + skip),
+                None => break,
+            }
+        }
         let frame_start_pos = stream.pos;
 
+        // Erased flash reads as 0xFF: padding up to the end of the data ends
+        // the log, and a long run inside it is skipped without failed frames
+        let erased = stream
+            .remaining()
+            .iter()
+            .take_while(|&&byte| byte == 0xFF)
+            .count();
+        let at_end = erased == stream.remaining().len();
+        if erased > 0 && (at_end || (erased >= MIN_ERASED_RUN && !strict)) {
+            if debug {
+                diag.debug(format!(
+                    "Skipped {erased} bytes of erased flash at offset {frame_start_pos}"
+                ));
+            }
+            if at_end {
+                break;
+            }
+            stream.set_position(frame_start_pos + erased);
+            frame_history.valid = false;
+            resyncing = true;
+            continue;
+        }
+
         match stream.read_byte() {
             Ok(frame_type_byte) => {
                 let frame_type = match frame_type_byte as char {
@@ -100,97 +214,114 @@ pub fn parse_frames(
                     'S' => 'S',
                     _ => {
                         if debug && stats.failed_frames < 3 {
-                            println!(
+                            diag.debug(format!(
                                 "Unknown frame type byte 0x{:02X} ('{:?}') at offset {}",
                                 frame_type_byte, frame_type_byte as char, frame_start_pos
-                            );
+                            ));
+                        }
+                        record_failed_frame(
+                            &mut stats,
+                            frame_start_pos,
+                            frame_type_byte as char,
+                            FrameErrorReason::UnknownFrameType,
+                        );
+                        if strict {
+                            return Err(invalid_frame(format!(
+                                "unknown frame type byte 0x{frame_type_byte:02X} at offset {frame_start_pos}"
+                            )));
                         }
-                        stats.failed_frames += 1;
+                        frame_history.valid = false;
+                        resyncing = true;
                         continue;
                     }
                 };
 
                 if debug && stats.total_frames < 3 {
-                    println!("Found frame type '{frame_type}' at offset {frame_start_pos}");
+                    diag.debug(format!(
+                        "Found frame type '{frame_type}' at offset {frame_start_pos}"
+                    ));
                 }
 
                 // Parse frame using proper streaming logic
                 let mut frame_data = HashMap::new();
                 let mut parsing_success = false;
+                let mut slow_frame_failed = false;
+                // Why a main frame failed, when it did
+                let mut failure = None;
 
                 match frame_type {
-                    'I' => {
-                        if header.i_frame_def.count > 0 {
-                            // I-frames reset the prediction history
-                            frame_history.current_frame.fill(0);
-
-                            if parse_frame_data(
-                                &mut stream,
-                                &header.i_frame_def,
-                                &mut frame_history.current_frame,
-                                None, // I-frames don't use prediction
-                                None,
-                                0,
-                                false, // Not raw
-                                header.data_version,
-                                &header.sysconfig,
-                                debug,
-                            )
-                            .is_ok()
-                            {
-                                // Update time and loop iteration from parsed frame
-                                for (i, field_name) in
-                                    header.i_frame_def.field_names.iter().enumerate()
-                                {
-                                    if i < frame_history.current_frame.len() {
-                                        let value = frame_history.current_frame[i];
-                                        frame_data.insert(field_name.clone(), value);
-                                    }
-                                }
+                    'I' if header.i_frame_def.count > 0 => {
+                        // I-frames reset the prediction history
+                        frame_history.current_frame.fill(0);
+
+                        let decoded = parse_frame_data(
+                            &mut stream,
+                            &header.i_frame_def,
+                            &mut frame_history.current_frame,
+                            None, // I-frames don't use prediction
+                            None,
+                            None,
+                            0,
+                            false, // Not raw
+                            header.data_version,
+                            &header.sysconfig,
+                            diag,
+                        );
+                        if let Err(e) = &decoded {
+                            failure = Some(FrameErrorReason::Decode(format!("{e:#}")));
+                        }
+                        if decoded.is_ok() {
+                            // lastSlow data is merged into the frame when it is stored
+                            if debug && stats.i_frames < 3 {
+                                diag.debug(format!("DEBUG: I-frame merged lastSlow. rxSignalReceived: {:?}, rxFlightChannelsValid: {:?}", 
+                                             slow_value(header, &last_slow_data, "rxSignalReceived"), slow_value(header, &last_slow_data, "rxFlightChannelsValid")));
+                            }
 
-                                // Merge lastSlow data into I-frame (following JavaScript approach)
-                                for (key, value) in &last_slow_data {
-                                    frame_data.insert(key.clone(), *value);
-                                }
+                            // Update history for future P-frames
+                            frame_history
+                                .previous_frame
+                                .copy_from_slice(&frame_history.current_frame);
+                            frame_history
+                                .previous2_frame
+                                .copy_from_slice(&frame_history.current_frame);
+                            frame_history.valid = true;
+
+                            // Validate frame before accepting
+                            let current_time =
+                                main_value(&frame_history.current_frame, i_time) as u64;
+                            let current_loop =
+                                main_value(&frame_history.current_frame, i_loop) as u32;
+
+                            let is_valid_frame = current_time > 0
+                                && (current_loop > 0 || current_time > 1000)
+                                && (!resyncing
+                                    || continues_log(
+                                        last_main_frame,
+                                        current_loop,
+                                        current_time,
+                                        stream.remaining(),
+                                    ));
+
+                            if is_valid_frame {
+                                parsing_success = true;
+                                stats.i_frames += 1;
 
-                                if debug && stats.i_frames < 3 {
-                                    println!("DEBUG: I-frame merged lastSlow. rxSignalReceived: {:?}, rxFlightChannelsValid: {:?}", 
-                                             frame_data.get("rxSignalReceived"), frame_data.get("rxFlightChannelsValid"));
+                                if debug && stats.i_frames <= 3 {
+                                    diag.debug(format!(
+                                        "DEBUG: Accepted I-frame - time:{}, loop:{}",
+                                        current_time, current_loop
+                                    ));
                                 }
-
-                                // Update history for future P-frames
-                                frame_history
-                                    .previous_frame
-                                    .copy_from_slice(&frame_history.current_frame);
-                                frame_history
-                                    .previous2_frame
-                                    .copy_from_slice(&frame_history.current_frame);
-                                frame_history.valid = true;
-
-                                // Validate frame before accepting
-                                let current_time =
-                                    frame_data.get("time").copied().unwrap_or(0) as u64;
-                                let current_loop =
-                                    frame_data.get("loopIteration").copied().unwrap_or(0) as u32;
-
-                                let is_valid_frame =
-                                    current_time > 0 && (current_loop > 0 || current_time > 1000);
-
-                                if is_valid_frame {
-                                    parsing_success = true;
-                                    stats.i_frames += 1;
-
-                                    if debug && stats.i_frames <= 3 {
-                                        println!(
-                                            "DEBUG: Accepted I-frame - time:{}, loop:{}",
-                                            current_time, current_loop
-                                        );
-                                    }
-                                } else if debug && stats.i_frames < 5 {
-                                    println!(
+                            } else {
+                                failure = Some(FrameErrorReason::Implausible {
+                                    time_us: current_time,
+                                    loop_iteration: current_loop,
+                                });
+                                if debug && stats.i_frames < 5 {
+                                    diag.debug(format!(
                                         "DEBUG: Rejected I-frame - time:{}, loop:{} (invalid)",
                                         current_time, current_loop
-                                    );
+                                    ));
                                 }
                             }
                         }
@@ -198,63 +329,46 @@ pub fn parse_frames(
                     'P' => {
                         if header.p_frame_def.count > 0 && frame_history.valid {
                             let mut p_frame_values = vec![0i32; header.p_frame_def.count];
+                            // Iterations the firmware did not log since the previous frame
+                            let skipped_frames = i_loop.map_or(0, |i| {
+                                intervals.skipped_after(frame_history.previous_frame[i] as u32)
+                            });
 
-                            if parse_frame_data(
+                            let decoded = parse_frame_data(
                                 &mut stream,
                                 &header.p_frame_def,
                                 &mut p_frame_values,
                                 Some(&frame_history.previous_frame),
                                 Some(&frame_history.previous2_frame),
-                                0,
+                                None,
+                                skipped_frames,
                                 false,
                                 header.data_version,
                                 &header.sysconfig,
-                                debug,
-                            )
-                            .is_ok()
-                            {
+                                diag,
+                            );
+                            if let Err(e) = &decoded {
+                                failure = Some(FrameErrorReason::Decode(format!("{e:#}")));
+                            }
+                            if decoded.is_ok() {
                                 // Copy previous frame as base, then update P-frame fields
                                 frame_history
                                     .current_frame
                                     .copy_from_slice(&frame_history.previous_frame);
 
                                 // Update only the fields present in P-frame
-                                for (i, field_name) in
-                                    header.p_frame_def.field_names.iter().enumerate()
-                                {
-                                    if i < p_frame_values.len() {
-                                        if let Some(i_frame_idx) = header
-                                            .i_frame_def
-                                            .field_names
-                                            .iter()
-                                            .position(|name| name == field_name)
-                                        {
-                                            if i_frame_idx < frame_history.current_frame.len() {
-                                                frame_history.current_frame[i_frame_idx] =
-                                                    p_frame_values[i];
-                                            }
+                                for (&i_frame_idx, &value) in p_to_i.iter().zip(&p_frame_values) {
+                                    if let Some(i_frame_idx) = i_frame_idx {
+                                        if i_frame_idx < frame_history.current_frame.len() {
+                                            frame_history.current_frame[i_frame_idx] = value;
                                         }
                                     }
                                 }
 
-                                // Copy current frame to output
-                                for (i, field_name) in
-                                    header.i_frame_def.field_names.iter().enumerate()
-                                {
-                                    if i < frame_history.current_frame.len() {
-                                        let value = frame_history.current_frame[i];
-                                        frame_data.insert(field_name.clone(), value);
-                                    }
-                                }
-
-                                // Merge lastSlow data
-                                for (key, value) in &last_slow_data {
-                                    frame_data.insert(key.clone(), *value);
-                                }
-
+                                // lastSlow data is merged into the frame when it is stored
                                 if debug && stats.p_frames < 3 {
-                                    println!("DEBUG: P-frame merged lastSlow. rxSignalReceived: {:?}, rxFlightChannelsValid: {:?}", 
-                                             frame_data.get("rxSignalReceived"), frame_data.get("rxFlightChannelsValid"));
+                                    diag.debug(format!("DEBUG: P-frame merged lastSlow. rxSignalReceived: {:?}, rxFlightChannelsValid: {:?}", 
+                                             slow_value(header, &last_slow_data, "rxSignalReceived"), slow_value(header, &last_slow_data, "rxFlightChannelsValid")));
                                 }
 
                                 // Update history
@@ -267,9 +381,9 @@ pub fn parse_frames(
 
                                 // Validate P-frame
                                 let current_time =
-                                    frame_data.get("time").copied().unwrap_or(0) as u64;
+                                    main_value(&frame_history.current_frame, i_time) as u64;
                                 let current_loop =
-                                    frame_data.get("loopIteration").copied().unwrap_or(0) as u32;
+                                    main_value(&frame_history.current_frame, i_loop) as u32;
 
                                 let is_valid_frame =
                                     current_time > 0 && (current_loop > 0 || current_time > 1000);
@@ -277,71 +391,105 @@ pub fn parse_frames(
                                 if is_valid_frame {
                                     parsing_success = true;
                                     stats.p_frames += 1;
+                                    stats.intentionally_absent_iterations +=
+                                        u64::from(skipped_frames);
 
                                     if debug && stats.p_frames <= 3 {
-                                        println!(
+                                        diag.debug(format!(
                                             "DEBUG: Accepted P-frame - time:{}, loop:{}",
                                             current_time, current_loop
-                                        );
+                                        ));
+                                    }
+                                } else {
+                                    failure = Some(FrameErrorReason::Implausible {
+                                        time_us: current_time,
+                                        loop_iteration: current_loop,
+                                    });
+                                    if debug && stats.p_frames < 5 {
+                                        diag.debug(format!(
+                                            "DEBUG: Rejected P-frame - time:{}, loop:{} (invalid)",
+                                            current_time, current_loop
+                                        ));
                                     }
-                                } else if debug && stats.p_frames < 5 {
-                                    println!(
-                                        "DEBUG: Rejected P-frame - time:{}, loop:{} (invalid)",
-                                        current_time, current_loop
-                                    );
                                 }
                             }
-                        } else {
-                            skip_frame(&mut stream, frame_type, debug)?;
-                            stats.failed_frames += 1;
+                        } else if header.p_frame_def.count > 0 {
+                            failure = Some(FrameErrorReason::NoPreviousFrame);
                         }
                     }
                     'S' => {
                         if debug && stats.s_frames < 5 {
-                            println!(
+                            diag.debug(format!(
                                 "DEBUG: Found S-frame, header.s_frame_def.count={}",
                                 header.s_frame_def.count
-                            );
+                            ));
                         }
                         if header.s_frame_def.count > 0 {
-                            if let Ok(data) = parse_s_frame(&mut stream, &header.s_frame_def, debug)
+                            if let Ok(data) = parse_s_frame(&mut stream, &header.s_frame_def, diag)
                             {
                                 if debug && stats.s_frames < 3 {
-                                    println!("DEBUG: Processing S-frame with data: {data:?}");
+                                    diag.debug(format!(
+                                        "DEBUG: Processing S-frame with data: {data:?}"
+                                    ));
                                 }
 
-                                for (key, value) in &data {
-                                    last_slow_data.insert(key.clone(), *value);
+                                for (slot, name) in last_slow_data
+                                    .iter_mut()
+                                    .zip(&header.s_frame_def.field_names)
+                                {
+                                    if let Some(&value) = data.get(name) {
+                                        *slot = Some(value);
+                                    }
+                                }
+                                if split_frame_types {
+                                    let values = header
+                                        .s_frame_def
+                                        .field_names
+                                        .iter()
+                                        .enumerate()
+                                        .filter_map(|(i, name)| data.get(name).map(|&v| (i, v)));
+                                    slow_frames.push_values(
+                                        'S',
+                                        last_main_frame_timestamp,
+                                        0,
+                                        values,
+                                    );
                                 }
 
                                 if debug && stats.s_frames < 3 {
-                                    println!(
+                                    diag.debug(format!(
                                         "DEBUG: S-frame data updated lastSlow: {last_slow_data:?}"
-                                    );
+                                    ));
                                 }
 
                                 stats.s_frames += 1;
 
                                 if debug && stats.s_frames <= 3 {
-                                    println!("DEBUG: S-frame count incremented to {} (data merged into lastSlow)", stats.s_frames);
+                                    diag.debug(format!("DEBUG: S-frame count incremented to {} (data merged into lastSlow)", stats.s_frames));
+                                }
+                            } else {
+                                slow_frame_failed = true;
+                                if debug && stats.s_frames < 5 {
+                                    diag.debug("DEBUG: S-frame parsing failed");
                                 }
-                            } else if debug && stats.s_frames < 5 {
-                                println!("DEBUG: S-frame parsing failed");
                             }
                         } else if debug && stats.s_frames < 5 {
-                            println!("DEBUG: Skipping S-frame - header.s_frame_def.count is 0");
+                            diag.debug("DEBUG: Skipping S-frame - header.s_frame_def.count is 0");
                         }
                     }
                     'H' => {
                         if header.h_frame_def.count > 0 {
-                            if let Ok(data) = parse_h_frame(&mut stream, &header.h_frame_def, debug)
+                            if let Ok(data) = parse_h_frame(&mut stream, &header.h_frame_def, diag)
                             {
                                 frame_data = data.clone();
                                 parsing_success = true;
                                 stats.h_frames += 1;
+                                if let Some(home) = extract_home_coord_raw(&frame_data) {
+                                    gps_home = Some(home);
+                                }
 
-                                // Extract GPS home coordinates for GPX export if enabled
-                                if export_options.gpx {
+                                // Extract GPS home coordinates for GPS exports if enabled
+                                if export_options.wants_gps() {
                                     let timestamp = last_main_frame_timestamp;
 
                                     if let (Some(&home_lat_raw), Some(&home_lon_raw)) = (
@@ -349,12 +497,12 @@ pub fn parse_frames(
                                         frame_data.get("GPS_home[1]"),
                                     ) {
                                         if debug && home_coordinates.is_empty() {
-                                            println!("DEBUG: HOME raw values - home_lat_raw: {}, home_lon_raw: {}", home_lat_raw, home_lon_raw);
-                                            println!(
+                                            diag.debug(format!("DEBUG: HOME raw values - home_lat_raw: {}, home_lon_raw: {}", home_lat_raw, home_lon_raw));
+                                            diag.debug(format!(
                                                 "DEBUG: HOME converted - lat: {:.7}, lon: {:.7}",
                                                 convert_gps_coordinate(home_lat_raw),
                                                 convert_gps_coordinate(home_lon_raw)
-                                            );
+                                            ));
                                         }
 
                                         let home_coordinate = GpsHomeCoordinate {
@@ -367,7 +515,7 @@ pub fn parse_frames(
                                 }
                             }
                         } else {
-                            skip_frame(&mut stream, frame_type, debug)?;
+                            skip_frame(&mut stream, frame_type, diag)?;
                             stats.h_frames += 1;
                             parsing_success = true;
                         }
@@ -387,11 +535,12 @@ pub fn parse_frames(
                                 &mut g_frame_values,
                                 Some(&gps_frame_history),
                                 None,
+                                gps_home,
                                 0,
                                 false,
                                 header.data_version,
                                 &header.sysconfig,
-                                debug,
+                                diag,
                             )
                             .is_ok()
                             {
@@ -411,8 +560,23 @@ pub fn parse_frames(
                                 parsing_success = true;
                                 stats.g_frames += 1;
 
-                                // Extract GPS coordinates for GPX export if enabled
-                                if export_options.gpx {
+                                if split_frame_types {
+                                    let gps_time =
+                                        frame_data.get("time").copied().unwrap_or(0) as u64;
+                                    gps_frames.push_values(
+                                        'G',
+                                        if gps_time > 0 {
+                                            gps_time
+                                        } else {
+                                            last_main_frame_timestamp
+                                        },
+                                        0,
+                                        g_frame_values.iter().copied().enumerate(),
+                                    );
+                                }
+
+                                // Extract GPS coordinates for GPS exports if enabled
+                                if export_options.wants_gps() {
                                     let gps_time =
                                         frame_data.get("time").copied().unwrap_or(0) as u64;
                                     let timestamp = if gps_time > 0 {
@@ -426,27 +590,16 @@ pub fn parse_frames(
                                         frame_data.get("GPS_coord[1]"),
                                         frame_data.get("GPS_altitude"),
                                     ) {
-                                        let actual_lat =
-                                            if let Some(home_coord) = home_coordinates.first() {
-                                                home_coord.home_latitude
-                                                    + convert_gps_coordinate(lat_raw)
-                                            } else {
-                                                convert_gps_coordinate(lat_raw)
-                                            };
-
-                                        let actual_lon =
-                                            if let Some(home_coord) = home_coordinates.first() {
-                                                home_coord.home_longitude
-                                                    + convert_gps_coordinate(lon_raw)
-                                            } else {
-                                                convert_gps_coordinate(lon_raw)
-                                            };
+                                        // PREDICT_HOME_COORD already added the most
+                                        // recent home position to the coordinates
+                                        let actual_lat = convert_gps_coordinate(lat_raw);
+                                        let actual_lon = convert_gps_coordinate(lon_raw);
 
                                         if debug && gps_coordinates.len() < 3 {
-                                            println!("DEBUG: GPS raw values - lat_raw: {}, lon_raw: {}, alt_raw: {}", lat_raw, lon_raw, alt_raw);
-                                            println!("DEBUG: GPS converted - lat: {:.7}, lon: {:.7}, alt: {:.2}", 
+                                            diag.debug(format!("DEBUG: GPS raw values - lat_raw: {}, lon_raw: {}, alt_raw: {}", lat_raw, lon_raw, alt_raw));
+                                            diag.debug(format!("DEBUG: GPS converted - lat: {:.7}, lon: {:.7}, alt: {:.2}", 
                                                    actual_lat, actual_lon,
-                                                   convert_gps_altitude(alt_raw, &header.firmware_revision));
+                                                   convert_gps_altitude(alt_raw, &header.firmware_revision)));
                                         }
 
                                         let coordinate = GpsCoordinate {
@@ -470,33 +623,38 @@ pub fn parse_frames(
                                 }
                             }
                         } else {
-                            skip_frame(&mut stream, frame_type, debug)?;
+                            skip_frame(&mut stream, frame_type, diag)?;
                             stats.g_frames += 1;
                             parsing_success = true;
                         }
                     }
                     'E' => {
-                        if let Ok(mut event_frame) = parse_e_frame(&mut stream, debug) {
+                        if let Ok(mut event_frame) = parse_e_frame(&mut stream, diag) {
                             frame_data
                                 .insert("event_type".to_string(), event_frame.event_type as i32);
                             frame_data.insert("event_description".to_string(), 0);
                             parsing_success = true;
                             stats.e_frames += 1;
+                            // Only the event followed by its "End of log"
+                            // trailer ends the log; a lone 0xFF type byte may be corruption
+                            log_ended = event_frame.event_type == LOG_END_EVENT
+                                && binary_data[..stream.pos.min(binary_data.len())]
+                                    .ends_with(LOG_END_TRAILER);
 
                             // Collect event frames for JSON export if enabled
-                            if export_options.event {
+                            if export_options.wants_events() {
                                 event_frame.timestamp_us = last_main_frame_timestamp;
                                 event_frames.push(event_frame);
                             }
 
                             if debug && stats.e_frames <= 3 {
-                                println!(
+                                diag.debug(format!(
                                     "DEBUG: Parsed E-frame - Type: {}",
                                     frame_data.get("event_type").unwrap_or(&0)
-                                );
+                                ));
                             }
                         } else {
-                            skip_frame(&mut stream, frame_type, debug)?;
+                            skip_frame(&mut stream, frame_type, diag)?;
                             stats.e_frames += 1;
                             parsing_success = true;
                         }
@@ -506,28 +664,89 @@ pub fn parse_frames(
 
                 // S-frames don't set parsing_success but are processed successfully
                 // (they update lastSlow data merged into I/P frames)
-                if !parsing_success && frame_type != 'S' {
-                    stats.failed_frames += 1;
+                let frame_failed = !parsing_success && frame_type != 'S';
+                // I-frames rejected while resynchronizing are not counted:
+                // the byte they start at is most likely not a frame at all
+                if frame_failed && !resyncing {
+                    record_failed_frame(
+                        &mut stats,
+                        frame_start_pos,
+                        frame_type,
+                        failure.unwrap_or(FrameErrorReason::NoFieldDefinition),
+                    );
+                }
+                if strict && (frame_failed || slow_frame_failed) {
+                    return Err(BBLError::FrameDecode {
+                        offset: frame_start_pos,
+                        frame_type,
+                    }
+                    .into());
+                }
+                if frame_failed {
+                    // Where the frame ended is unknown: drop the prediction
+                    // history and look for the next I-frame from the byte
+                    // after this frame's start
+                    let was_resyncing = resyncing;
+                    frame_history.valid = false;
+                    resyncing = true;
+                    stream.set_position(frame_start_pos + 1);
+                    if was_resyncing {
+                        continue;
+                    }
+                } else if frame_type == 'I' {
+                    resyncing = false;
                 }
 
                 stats.total_frames += 1;
 
                 // Show progress for large files
-                if (debug && stats.total_frames % 50000 == 0) || stats.total_frames % 100000 == 0 {
-                    println!("Parsed {} frames so far...", stats.total_frames);
-                    std::io::stdout().flush().unwrap_or_default();
+                if let Some(sink) = progress_sink {
+                    if stats.total_frames % PROGRESS_INTERVAL_FRAMES == 0 {
+                        sink.progress(&ParseProgress {
+                            bytes_consumed: stream.pos,
+                            total_bytes: binary_data.len(),
+                            frames: stats.total_frames,
+                        });
+                    }
+                } else if (debug && stats.total_frames % 50000 == 0)
+                    || stats.total_frames % 100000 == 0
+                {
+                    diag.info(format!("Parsed {} frames so far...", stats.total_frames));
                 }
 
                 // Store ALL successfully parsed frames
+                let is_main_frame = frame_type == 'I' || frame_type == 'P';
+                let frame_time = if is_main_frame {
+                    i_time.and_then(|i| frame_history.current_frame.get(i).copied())
+                } else {
+                    frame_data.get("time").copied()
+                };
                 if parsing_success {
-                    let timestamp_us = frame_data.get("time").copied().unwrap_or(0) as u64;
-                    let loop_iteration =
-                        frame_data.get("loopIteration").copied().unwrap_or(0) as u32;
+                    let timestamp_us = frame_time.unwrap_or(0) as u64;
+                    let loop_iteration = if is_main_frame {
+                        main_value(&frame_history.current_frame, i_loop)
+                    } else {
+                        frame_data.get("loopIteration").copied().unwrap_or(0)
+                    } as u32;
 
                     // Update last timestamp for main frames (I, P)
                     if (frame_type == 'I' || frame_type == 'P') && timestamp_us > 0 {
                         last_main_frame_timestamp = timestamp_us;
                     }
+                    if is_main_frame {
+                        if let (Some((last_iteration, _)), 'I') = (last_main_frame, frame_type) {
+                            stats.intentionally_absent_iterations +=
+                                intervals.skipped_between(last_iteration, loop_iteration);
+                        }
+                        last_main_frame = Some((loop_iteration, timestamp_us));
+                        iteration_range =
+                            Some(iteration_range.map_or(
+                                (loop_iteration, loop_iteration),
+                                |(first, last)| {
+                                    (first.min(loop_iteration), last.max(loop_iteration))
+                                },
+                            ));
+                    }
 
                     // S frames inherit timestamp from last main frame
                     let final_timestamp = if frame_type == 'S' && timestamp_us == 0 {
@@ -536,40 +755,89 @@ pub fn parse_frames(
                         timestamp_us
                     };
 
-                    if debug && (frame_type == 'I' || frame_type == 'P') && frames.len() < 3 {
-                        println!(
-                            "DEBUG: Frame {:?} has timestamp {}. Available fields: {:?}",
-                            frame_type,
-                            timestamp_us,
-                            frame_data.keys().collect::<Vec<_>>()
-                        );
-                        if let Some(time_val) = frame_data.get("time") {
-                            println!("DEBUG: 'time' field value: {time_val}");
+                    if is_main_frame {
+                        // Decimation drops stored frames only; every frame is
+                        // still decoded to keep the prediction history
+                        let keep_frame = main_frames_decoded % decimate == 0;
+                        main_frames_decoded += 1;
+                        if keep_frame {
+                            // Merge lastSlow data after the I-frame fields (following JavaScript approach)
+                            let main_values = i_columns
+                                .iter()
+                                .copied()
+                                .zip(frame_history.current_frame.iter().copied());
+                            let slow_values = s_columns
+                                .iter()
+                                .zip(&last_slow_data)
+                                .filter_map(|(&column, value)| value.map(|value| (column, value)));
+                            let row = frames.push_values(
+                                frame_type,
+                                final_timestamp,
+                                loop_iteration,
+                                main_values.chain(slow_values),
+                            );
+
+                            main_frames_stored += 1;
+
+                            if debug && main_frames_stored <= 3 {
+                                diag.debug(format!(
+                                    "DEBUG: Frame {:?} has timestamp {}. Available fields: {:?}",
+                                    frame_type,
+                                    timestamp_us,
+                                    frames.field_names()
+                                ));
+                                diag.debug(format!("DEBUG: 'time' field value: {frame_time:?}"));
+                                diag.debug(format!(
+                                    "DEBUG: 'loopIteration' field value: {:?}",
+                                    i_loop
+                                        .and_then(|i| frame_history.current_frame.get(i).copied())
+                                ));
+                            }
+
+                            if let Some(sink) = frame_sink {
+                                if let Some(frame) = frames.row(row) {
+                                    sink.frame(&frame);
+                                }
+                            }
+
+                            // Also store in debug_frames for debug purposes
+                            if store_debug_frames {
+                                debug_frames
+                                    .entry(frame_type)
+                                    .or_default()
+                                    .extend(frames.row(row));
+                            }
+
+                            // The sink has seen the frame; don't keep it
+                            if !retain_frames {
+                                frames.clear();
+                            }
                         }
-                        if let Some(loop_val) = frame_data.get("loopIteration") {
-                            println!("DEBUG: 'loopIteration' field value: {loop_val}");
+                        if main_frame_limit.is_some_and(|limit| main_frames_stored >= limit) {
+                            limit_reached = true;
+                        }
+                    } else if store_debug_frames || frame_sink.is_some() {
+                        let frame = DecodedFrame {
+                            frame_type,
+                            timestamp_us: final_timestamp,
+                            loop_iteration,
+                            data: frame_data,
+                        };
+                        if let Some(sink) = frame_sink {
+                            sink.frame(&frame);
                         }
-                    }
-
-                    let decoded_frame = DecodedFrame {
-                        frame_type,
-                        timestamp_us: final_timestamp,
-                        loop_iteration,
-                        data: frame_data.clone(),
-                    };
-                    frames.push(decoded_frame.clone());
 
-                    // Also store in debug_frames for debug purposes
-                    if debug {
-                        let debug_frame_list = debug_frames.entry(frame_type).or_default();
-                        debug_frame_list.push(decoded_frame);
+                        // Also store in debug_frames for debug purposes
+                        if store_debug_frames {
+                            debug_frames.entry(frame_type).or_default().push(frame);
+                        }
                     }
                 }
 
                 // Update timing from first and last valid frames with time data
                 if parsing_success {
-                    if let Some(time_us) = frame_data.get("time") {
-                        let time_val = *time_us as u64;
+                    if let Some(time_us) = frame_time {
+                        let time_val = time_us as u64;
                         if stats.start_time_us == 0 {
                             stats.start_time_us = time_val;
                         }
@@ -580,19 +848,58 @@ pub fn parse_frames(
             Err(_) => break,
         }
 
+        // Frame data ends at the log end event; what follows is padding or junk
+        if log_ended {
+            stats.ended_cleanly = true;
+            if debug {
+                diag.debug("Found the log end event - stopping frame parsing");
+            }
+            break;
+        }
+
+        // Requested main frames are all stored
+        if limit_reached {
+            if debug {
+                diag.debug("Reached the main frame limit - stopping frame parsing");
+            }
+            break;
+        }
+
         // Safety limits to prevent hanging
-        if stats.total_frames > 1000000 || stats.failed_frames > 10000 {
+        if stats.total_frames as usize >= config.max_frames
+            || stats.failed_frames as usize >= config.max_failed_frames
+        {
+            stats.truncated = true;
+            #[cfg(feature = "tracing")]
+            tracing::warn!(
+                total_frames = stats.total_frames,
+                failed_frames = stats.failed_frames,
+                "parser safety limit reached"
+            );
             if debug {
-                println!("Hit safety limit - stopping frame parsing");
+                diag.debug("Hit safety limit - stopping frame parsing");
             }
             break;
         }
     }
 
     stats.total_bytes = binary_data.len() as u64;
+    if let Some((first, last)) = iteration_range {
+        let main_frames = u64::from(stats.i_frames) + u64::from(stats.p_frames);
+        stats.missing_iterations = (u64::from(last - first) + 1)
+            .saturating_sub(main_frames)
+            .saturating_sub(stats.intentionally_absent_iterations);
+    }
+    if let Some(sink) = progress_sink {
+        sink.progress(&ParseProgress {
+            bytes_consumed: stream.pos.min(binary_data.len()),
+            total_bytes: binary_data.len(),
+            frames: stats.total_frames,
+        });
+    }
 
     if debug {
-        println!(
+        diag.debug(format!(
             "Parsed {} frames: {} I, {} P, {} H, {} G, {} E, {} S",
             stats.total_frames,
             stats.i_frames,
@@ -601,8 +908,8 @@ pub fn parse_frames(
             stats.g_frames,
             stats.e_frames,
             stats.s_frames
-        );
-        println!("Failed to parse: {} frames", stats.failed_frames);
+        ));
+        diag.debug(format!("Failed to parse: {} frames", stats.failed_frames));
     }
 
     Ok((
@@ -612,10 +919,217 @@ pub fn parse_frames(
         gps_coordinates,
         home_coordinates,
         event_frames,
+        slow_frames,
+        gps_frames,
     ))
 }
 
+/// Most failed frames recorded per log in `FrameStats::frame_errors`
+pub const MAX_FRAME_ERRORS: usize = 1000;
+
+/// Count a failed frame, recording where and why while fewer than
+/// [`MAX_FRAME_ERRORS`] are kept
+fn record_failed_frame(
+    stats: &mut FrameStats,
+    offset: usize,
+    frame_type: char,
+    reason: FrameErrorReason,
+) {
+    stats.failed_frames += 1;
+    if stats.frame_errors.len() < MAX_FRAME_ERRORS {
+        stats.frame_errors.push(FrameError {
+            offset: offset as u64,
+            frame_type,
+            reason,
+        });
+    }
+}
+
+/// Event type Betaflight writes when logging stops (followed by an "End of log" trailer)
+pub(crate) const LOG_END_EVENT: u8 = 255;
+
+/// Largest loop iteration jump from the last main frame that an I-frame found
+/// while resynchronizing may make (as blackbox_decode)
+const MAX_RESYNC_ITERATION_JUMP: u32 = 5000;
+
+/// Largest time jump from the last main frame that an I-frame found while
+/// resynchronizing may make (as blackbox_decode)
+const MAX_RESYNC_TIME_JUMP_US: u64 = 10_000_000;
+
+/// Bytes that start a frame
+pub(crate) const FRAME_MARKERS: &[u8] = b"IPSGHE";
+
+/// Shortest run of 0xFF bytes inside frame data treated as erased flash
+/// rather than a corrupt frame
+const MIN_ERASED_RUN: usize = 16;
+
+/// Which loop iterations the firmware logs a main frame for, from the
+/// `I interval` and `P interval` headers (as blackbox_decode)
+///
+/// The headers are untrusted, so the intervals are clamped to the `u16`
+/// range the firmware stores them in, and skipped iterations are counted in
+/// closed form rather than by stepping through them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FrameIntervals {
+    i_interval: u32,
+    p_num: u32,
+    p_denom: u32,
+    /// Iterations logged in each I interval
+    logged_per_interval: u32,
+}
+
+impl FrameIntervals {
+    pub(crate) fn from_sysconfig(sysconfig: &HashMap<String, SysConfigValue>) -> Self {
+        let value = |key: &str, default: i32| {
+            sysconfig
+                .get(key)
+                .and_then(SysConfigValue::as_int)
+                .unwrap_or(default)
+                .clamp(1, u16::MAX.into()) as u32
+        };
+        let p_denom = value("frameIntervalPDenom", 1);
+        let mut intervals = Self {
+            i_interval: value("frameIntervalI", 32),
+            p_num: value("frameIntervalPNum", 1).min(p_denom),
+            p_denom,
+            logged_per_interval: 0,
+        };
+        intervals.logged_per_interval = intervals.logged_below(intervals.i_interval);
+        intervals
+    }
+
+    /// Whether the firmware logs a main frame at `iteration`, the rule the
+    /// closed forms below count
+    #[cfg(test)]
+    pub(crate) fn should_have_frame(&self, iteration: u32) -> bool {
+        (iteration % self.i_interval + self.p_num - 1) % self.p_denom < self.p_num
+    }
+
+    /// Logged iterations among the first `offset` of an I interval
+    /// (`offset <= i_interval`)
+    fn logged_below(&self, offset: u32) -> u32 {
+        // Offset r is logged when (r + p_num - 1) % p_denom < p_num; count the
+        // shifted values s = r + p_num - 1 in [p_num - 1, offset + p_num - 1)
+        let below =
+            |end: u32| end / self.p_denom * self.p_num + (end % self.p_denom).min(self.p_num);
+        below(offset + self.p_num - 1) - below(self.p_num - 1)
+    }
+
+    /// Iterations after `iteration` the firmware does not log before the next
+    /// one it does, which `PREDICT_INC` skips over
+    pub(crate) fn skipped_after(&self, iteration: u32) -> u32 {
+        let offset = iteration.wrapping_add(1) % self.i_interval;
+        let phase = (offset + self.p_num - 1) % self.p_denom;
+        let to_next_p = if phase < self.p_num {
+            0
+        } else {
+            self.p_denom - phase
+        };
+        // The next I interval always starts with a logged iteration
+        to_next_p.min(self.i_interval - offset)
+    }
+
+    /// Iterations strictly between `from` and `to` the firmware does not log
+    pub(crate) fn skipped_between(&self, from: u32, to: u32) -> u64 {
+        if to <= from {
+            return 0;
+        }
+        // The pattern repeats every I interval
+        let between = to - from - 1;
+        let start = from.wrapping_add(1) % self.i_interval;
+        let remainder = between % self.i_interval;
+        let remainder_logged = if start + remainder <= self.i_interval {
+            self.logged_below(start + remainder) - self.logged_below(start)
+        } else {
+            self.logged_per_interval - self.logged_below(start)
+                + self.logged_below(start + remainder - self.i_interval)
+        };
+        let logged = u64::from(between / self.i_interval) * u64::from(self.logged_per_interval)
+            + u64::from(remainder_logged);
+        u64::from(between) - logged
+    }
+}
+
+/// Offset in `data` of the next byte that may start an I-frame, or the log
+/// end event, after a failed frame
+fn resync_offset(data: &[u8]) -> Option<usize> {
+    (0..data.len()).find(|&offset| {
+        let rest = &data[offset..];
+        rest[0] == b'I'
+            || (rest.starts_with(&[b'E', LOG_END_EVENT]) && rest[2..].starts_with(LOG_END_TRAILER))
+    })
+}
+
+/// Whether an I-frame decoded while resynchronizing continues the log: its
+/// loop iteration and time follow the last accepted main frame, and the bytes
+/// after it start another frame or erased flash
+fn continues_log(
+    last_main_frame: Option<(u32, u64)>,
+    loop_iteration: u32,
+    time_us: u64,
+    next: &[u8],
+) -> bool {
+    let follows = match last_main_frame {
+        Some((last_iteration, last_time_us)) => {
+            (last_iteration..=last_iteration.saturating_add(MAX_RESYNC_ITERATION_JUMP))
+                .contains(&loop_iteration)
+                && (last_time_us..=last_time_us + MAX_RESYNC_TIME_JUMP_US).contains(&time_us)
+        }
+        None => true,
+    };
+    follows
+        && next
+            .first()
+            .map_or(true, |byte| FRAME_MARKERS.contains(byte) || *byte == 0xFF)
+}
+
+/// Typed error for a frame rejected in strict mode
+fn invalid_frame(message: String) -> anyhow::Error {
+    BBLError::InvalidFrame(message).into()
+}
+
+/// Reject predictors and encodings the decoder does not implement (strict mode)
+fn validate_frame_definition(frame_type: char, frame_def: &FrameDefinition) -> Result<()> {
+    for field in &frame_def.fields {
+        if !SUPPORTED_PREDICTORS.contains(&field.predictor) {
+            return Err(
+                anyhow::Error::new(BBLError::InvalidPredictor(field.predictor))
+                    .context(format!("{frame_type} frame field {}", field.name)),
+            );
+        }
+        if !SUPPORTED_ENCODINGS.contains(&field.encoding) {
+            return Err(
+                anyhow::Error::new(BBLError::UnsupportedEncoding(field.encoding))
+                    .context(format!("{frame_type} frame field {}", field.name)),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Position of a named field within a frame definition
+fn field_position(frame_def: &FrameDefinition, name: &str) -> Option<usize> {
+    frame_def.field_names.iter().position(|n| n == name)
+}
+
+/// Value of a main-frame field by I-frame position, or 0 when absent
+fn main_value(values: &[i32], position: Option<usize>) -> i32 {
+    position.and_then(|i| values.get(i)).copied().unwrap_or(0)
+}
+
+/// Most recent S-frame value of a named field, for debug output
+fn slow_value(
+    header: &crate::types::BBLHeader,
+    last_slow_data: &[Option<i32>],
+    name: &str,
+) -> Option<i32> {
+    field_position(&header.s_frame_def, name).and_then(|i| last_slow_data[i])
+}
+
 /// Parse frame data using the specified frame definition
+///
+/// `home_coord` is the home position of the last H-frame, used by G-frame
+/// fields with `PREDICT_HOME_COORD` (see [`apply_predictor_with_debug`]).
 #[allow(clippy::too_many_arguments)]
 pub fn parse_frame_data(
     stream: &mut BBLDataStream,
@@ -623,11 +1137,12 @@ pub fn parse_frame_data(
     current_frame: &mut [i32],
     previous_frame: Option<&[i32]>,
     previous2_frame: Option<&[i32]>,
+    home_coord: Option<[i32; 2]>,
     skipped_frames: u32,
     raw: bool,
     _data_version: u8,
-    sysconfig: &HashMap<String, i32>,
-    debug: bool,
+    sysconfig: &HashMap<String, SysConfigValue>,
+    diag: DiagnosticReporter,
 ) -> Result<()> {
     let mut i = 0;
     let mut values = [0i32; 8];
@@ -643,10 +1158,11 @@ pub fn parse_frame_data(
                 current_frame,
                 previous_frame,
                 previous2_frame,
+                home_coord,
                 skipped_frames,
                 sysconfig,
                 &frame_def.field_names,
-                debug,
+                diag,
             );
             i += 1;
             continue;
@@ -673,10 +1189,11 @@ pub fn parse_frame_data(
                         current_frame,
                         previous_frame,
                         previous2_frame,
+                        home_coord,
                         skipped_frames,
                         sysconfig,
                         &frame_def.field_names,
-                        debug,
+                        diag,
                     );
                 }
                 i += 4;
@@ -703,10 +1220,11 @@ pub fn parse_frame_data(
                         current_frame,
                         previous_frame,
                         previous2_frame,
+                        home_coord,
                         skipped_frames,
                         sysconfig,
                         &frame_def.field_names,
-                        debug,
+                        diag,
                     );
                 }
                 i += 3;
@@ -742,10 +1260,11 @@ pub fn parse_frame_data(
                         current_frame,
                         previous_frame,
                         previous2_frame,
+                        home_coord,
                         skipped_frames,
                         sysconfig,
                         &frame_def.field_names,
-                        debug,
+                        diag,
                     );
                 }
                 i += group_count;
@@ -763,10 +1282,11 @@ pub fn parse_frame_data(
                     current_frame,
                     previous_frame,
                     previous2_frame,
+                    home_coord,
                     skipped_frames,
                     sysconfig,
                     &frame_def.field_names,
-                    debug,
+                    diag,
                 );
             }
         }
@@ -785,7 +1305,7 @@ pub fn parse_frame_data(
 pub fn parse_s_frame(
     stream: &mut BBLDataStream,
     frame_def: &FrameDefinition,
-    debug: bool,
+    diag: DiagnosticReporter,
 ) -> Result<HashMap<String, i32>> {
     let mut data = HashMap::new();
     let mut field_index = 0;
@@ -828,11 +1348,11 @@ pub fn parse_s_frame(
                 field_index += 1;
             }
             _ => {
-                if debug {
-                    println!(
+                if diag.debug_enabled() {
+                    diag.debug(format!(
                         "Unsupported S-frame encoding {} for field {}",
                         field.encoding, field.name
-                    );
+                    ));
                 }
                 // For unsupported encodings, try to read as signed VB
                 let value = stream.read_signed_vb().unwrap_or(0);
@@ -845,9 +1365,13 @@ pub fn parse_s_frame(
     Ok(data)
 }
 
-fn skip_frame(stream: &mut BBLDataStream, frame_type: char, debug: bool) -> Result<()> {
-    if debug {
-        println!("Skipping {} frame", frame_type);
+pub(crate) fn skip_frame(
+    stream: &mut BBLDataStream,
+    frame_type: char,
+    diag: DiagnosticReporter,
+) -> Result<()> {
+    if diag.debug_enabled() {
+        diag.debug(format!("Skipping {} frame", frame_type));
     }
 
     // Skip frame by reading a few bytes - this is a simple heuristic
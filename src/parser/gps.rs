@@ -9,9 +9,10 @@ use crate::conversion::{
 use crate::parser::decoder::{
     ENCODING_NEG_14BIT, ENCODING_NULL, ENCODING_SIGNED_VB, ENCODING_UNSIGNED_VB,
 };
+use crate::parser::diagnostics::DiagnosticReporter;
 use crate::parser::frame::parse_frame_data;
 use crate::parser::stream::BBLDataStream;
-use crate::types::{FrameDefinition, GpsCoordinate, GpsHomeCoordinate};
+use crate::types::{FrameDefinition, GpsCoordinate, GpsHomeCoordinate, SysConfigValue};
 use anyhow::Result;
 use std::collections::HashMap;
 
@@ -22,12 +23,12 @@ use std::collections::HashMap;
 pub fn parse_h_frame(
     stream: &mut BBLDataStream,
     frame_def: &FrameDefinition,
-    debug: bool,
+    diag: DiagnosticReporter,
 ) -> Result<HashMap<String, i32>> {
     let mut data = HashMap::new();
 
-    if debug {
-        println!("Parsing H frame with {} fields", frame_def.count);
+    if diag.debug_enabled() {
+        diag.debug(format!("Parsing H frame with {} fields", frame_def.count));
     }
 
     // H frames contain GPS home position data
@@ -44,11 +45,11 @@ pub fn parse_h_frame(
             _ => {
                 // Unsupported H-frame encoding - gracefully fall back to signed VB
                 // This matches CLI behavior for maximum compatibility with various logs
-                if debug {
-                    println!(
+                if diag.debug_enabled() {
+                    diag.debug(format!(
                         "Unsupported H-frame encoding {} for field {}",
                         field.encoding, field.name
-                    );
+                    ));
                 }
                 stream.read_signed_vb().unwrap_or(0)
             }
@@ -60,27 +61,36 @@ pub fn parse_h_frame(
     Ok(data)
 }
 
+/// Raw `GPS_home[0]`, `GPS_home[1]` values of parsed H-frame data, which
+/// G-frame coordinates are predicted from
+pub fn extract_home_coord_raw(frame_data: &HashMap<String, i32>) -> Option<[i32; 2]> {
+    Some([
+        *frame_data.get("GPS_home[0]")?,
+        *frame_data.get("GPS_home[1]")?,
+    ])
+}
+
 /// Extract GPS home coordinate from parsed H-frame data
 ///
 /// Converts raw H-frame field values to a `GpsHomeCoordinate` struct.
 pub fn extract_home_coordinate(
     frame_data: &HashMap<String, i32>,
     timestamp_us: u64,
-    debug: bool,
+    diag: DiagnosticReporter,
 ) -> Option<GpsHomeCoordinate> {
     if let (Some(&home_lat_raw), Some(&home_lon_raw)) =
         (frame_data.get("GPS_home[0]"), frame_data.get("GPS_home[1]"))
     {
-        if debug {
-            println!(
+        if diag.debug_enabled() {
+            diag.debug(format!(
                 "DEBUG: HOME raw values - home_lat_raw: {}, home_lon_raw: {}",
                 home_lat_raw, home_lon_raw
-            );
-            println!(
+            ));
+            diag.debug(format!(
                 "DEBUG: HOME converted - lat: {:.7}, lon: {:.7}",
                 convert_gps_coordinate(home_lat_raw),
                 convert_gps_coordinate(home_lon_raw)
-            );
+            ));
         }
 
         Some(GpsHomeCoordinate {
@@ -97,18 +107,20 @@ pub fn extract_home_coordinate(
 ///
 /// G-frames use differential encoding similar to P-frames, where values are
 /// encoded as deltas from the previous G-frame. This function properly decodes
-/// the G-frame using the GPS frame history for prediction.
+/// the G-frame using the GPS frame history for prediction, and the home
+/// position of the last H-frame (see [`extract_home_coord_raw`]) for `GPS_coord` fields.
 #[allow(clippy::too_many_arguments)]
 pub fn parse_g_frame(
     stream: &mut BBLDataStream,
     frame_def: &FrameDefinition,
     gps_frame_history: &mut Vec<i32>,
+    home_coord: Option<[i32; 2]>,
     data_version: u8,
-    sysconfig: &HashMap<String, i32>,
-    debug: bool,
+    sysconfig: &HashMap<String, SysConfigValue>,
+    diag: DiagnosticReporter,
 ) -> Result<HashMap<String, i32>> {
-    if debug {
-        println!("Parsing G frame with {} fields", frame_def.count);
+    if diag.debug_enabled() {
+        diag.debug(format!("Parsing G frame with {} fields", frame_def.count));
     }
 
     // Initialize or resize GPS frame history if needed
@@ -125,11 +137,12 @@ pub fn parse_g_frame(
         &mut g_frame_values,
         Some(gps_frame_history), // Use GPS frame history for differential encoding
         None,                    // GPS frames typically don't use previous2
-        0,                       // skipped frames
-        false,                   // Not raw
+        home_coord,
+        0,     // skipped frames
+        false, // Not raw
         data_version,
         sysconfig,
-        debug,
+        diag,
     )?;
 
     // Update GPS frame history with new values
@@ -148,47 +161,34 @@ pub fn parse_g_frame(
 
 /// Extract GPS coordinate from parsed G-frame data
 ///
-/// Converts raw G-frame field values to a `GpsCoordinate` struct,
-/// applying the home coordinate offset if available.
-#[allow(clippy::too_many_arguments)]
+/// Converts raw G-frame field values to a `GpsCoordinate` struct. The
+/// coordinates must already include the home position, as [`parse_g_frame`]
+/// returns them.
 pub fn extract_gps_coordinate(
     frame_data: &HashMap<String, i32>,
-    home_coordinates: &[GpsHomeCoordinate],
     timestamp_us: u64,
     firmware_revision: &str,
-    debug: bool,
+    diag: DiagnosticReporter,
 ) -> Option<GpsCoordinate> {
     if let (Some(&lat_raw), Some(&lon_raw), Some(&alt_raw)) = (
         frame_data.get("GPS_coord[0]"),
         frame_data.get("GPS_coord[1]"),
         frame_data.get("GPS_altitude"),
     ) {
-        // GPS coordinates are deltas from home position
-        // Need to add home coordinates to get actual GPS position
-        let (home_lat, home_lon) = home_coordinates
-            .first()
-            .map(|h| (h.home_latitude, h.home_longitude))
-            .unwrap_or_else(|| {
-                if debug {
-                    println!("DEBUG: No home coordinates available, using (0.0, 0.0)");
-                }
-                (0.0, 0.0)
-            });
-
-        let actual_lat = home_lat + convert_gps_coordinate(lat_raw);
-        let actual_lon = home_lon + convert_gps_coordinate(lon_raw);
+        let actual_lat = convert_gps_coordinate(lat_raw);
+        let actual_lon = convert_gps_coordinate(lon_raw);
 
-        if debug {
-            println!(
+        if diag.debug_enabled() {
+            diag.debug(format!(
                 "DEBUG: GPS raw values - lat_raw: {}, lon_raw: {}, alt_raw: {}",
                 lat_raw, lon_raw, alt_raw
-            );
-            println!(
+            ));
+            diag.debug(format!(
                 "DEBUG: GPS converted - lat: {:.7}, lon: {:.7}, alt: {:.2}",
                 actual_lat,
                 actual_lon,
                 convert_gps_altitude(alt_raw, firmware_revision)
-            );
+            ));
         }
 
         Some(GpsCoordinate {
@@ -1,9 +1,12 @@
-use crate::types::{BBLHeader, FrameDefinition};
+use crate::conversion::FirmwareKind;
+use crate::error::BBLError;
+use crate::parser::diagnostics::DiagnosticReporter;
+use crate::types::{BBLHeader, FrameDefinition, SysConfigValue};
 use anyhow::Result;
 use std::collections::HashMap;
 
 /// Parse BBL headers from text
-pub fn parse_headers_from_text(header_text: &str, debug: bool) -> Result<BBLHeader> {
+pub fn parse_headers_from_text(header_text: &str, diag: DiagnosticReporter) -> Result<BBLHeader> {
     let mut header = BBLHeader::default();
 
     for line in header_text.lines() {
@@ -14,8 +17,8 @@ pub fn parse_headers_from_text(header_text: &str, debug: bool) -> Result<BBLHead
 
         header.all_headers.push(line.to_string());
 
-        if debug {
-            println!("Processing header: {}", line);
+        if diag.debug_enabled() {
+            diag.debug(format!("Processing header: {}", line));
         }
 
         // Parse specific headers following JavaScript reference
@@ -148,9 +151,37 @@ pub fn parse_headers_from_text(header_text: &str, debug: bool) -> Result<BBLHead
         }
     }
 
+    if header.firmware() == FirmwareKind::EmuFlight {
+        add_emuflight_aliases(&mut header.sysconfig);
+    }
+    header.update_main_fields();
+
     Ok(header)
 }
 
+/// Header names EmuFlight kept from before Betaflight 4.3 renamed them, with
+/// their Betaflight names
+const EMUFLIGHT_HEADER_ALIASES: &[(&str, &str)] = &[
+    ("gyro_lowpass_hz", "gyro_lpf1_static_hz"),
+    ("gyro_lowpass2_hz", "gyro_lpf2_static_hz"),
+    ("gyro_lowpass_type", "gyro_lpf1_type"),
+    ("gyro_lowpass2_type", "gyro_lpf2_type"),
+    ("dterm_lowpass_hz", "dterm_lpf1_static_hz"),
+    ("dterm_lowpass2_hz", "dterm_lpf2_static_hz"),
+    ("dterm_filter_type", "dterm_lpf1_type"),
+    ("dterm_filter2_type", "dterm_lpf2_type"),
+];
+
+/// Also store EmuFlight's header values under their Betaflight names, so
+/// lookups by the Betaflight name work for both
+fn add_emuflight_aliases(sysconfig: &mut HashMap<String, SysConfigValue>) {
+    for &(emuflight, betaflight) in EMUFLIGHT_HEADER_ALIASES {
+        if let Some(value) = sysconfig.get(emuflight).cloned() {
+            sysconfig.entry(betaflight.to_string()).or_insert(value);
+        }
+    }
+}
+
 fn parse_signed_info(line: &str, frame_def: &mut FrameDefinition) -> Result<()> {
     let parts: Vec<&str> = line.split(':').collect();
     if parts.len() < 2 {
@@ -179,7 +210,9 @@ fn parse_predictor_info(line: &str, frame_def: &mut FrameDefinition) -> Result<(
             frame_def.update_predictors(&predictors);
             Ok(())
         }
-        Err(_) => Err(anyhow::anyhow!("Invalid header: Invalid predictor values")),
+        Err(_) => {
+            Err(BBLError::HeaderParse(format!("invalid predictor values in {line:?}")).into())
+        }
     }
 }
 
@@ -198,28 +231,34 @@ fn parse_encoding_info(line: &str, frame_def: &mut FrameDefinition) -> Result<()
             frame_def.update_encoding(&encodings);
             Ok(())
         }
-        Err(_) => Err(anyhow::anyhow!("Invalid header: Invalid encoding values")),
+        Err(_) => Err(BBLError::HeaderParse(format!("invalid encoding values in {line:?}")).into()),
     }
 }
 
-fn parse_sysconfig_line(line: &str, sysconfig: &mut HashMap<String, i32>) {
+fn parse_sysconfig_line(line: &str, sysconfig: &mut HashMap<String, SysConfigValue>) {
     if let Some(config_str) = line.strip_prefix("H ") {
         let parts: Vec<&str> = config_str.splitn(2, ':').collect();
         if parts.len() == 2 {
             let key = parts[0].trim();
             let value_str = parts[1].trim();
 
-            // Handle array values like motorOutput:48,2047
-            if key == "motorOutput" && value_str.contains(',') {
-                let values: Vec<&str> = value_str.split(',').collect();
-                for (i, val) in values.iter().enumerate() {
-                    if let Ok(int_val) = val.trim().parse::<i32>() {
-                        sysconfig.insert(format!("{}[{}]", key, i), int_val);
-                    }
+            // Frame intervals, also stored under the names blackbox-log-viewer uses:
+            // "P interval" is "num/denom", or the denominator alone on newer firmware
+            if key == "I interval" {
+                if let Ok(value) = value_str.parse::<i32>() {
+                    sysconfig.insert("frameIntervalI".to_string(), value.into());
+                }
+            } else if key == "P interval" {
+                let (num, denom) = value_str.split_once('/').unwrap_or(("1", value_str));
+                if let (Ok(num), Ok(denom)) =
+                    (num.trim().parse::<i32>(), denom.trim().parse::<i32>())
+                {
+                    sysconfig.insert("frameIntervalPNum".to_string(), num.into());
+                    sysconfig.insert("frameIntervalPDenom".to_string(), denom.into());
                 }
-            } else if let Ok(value) = value_str.parse::<i32>() {
-                sysconfig.insert(key.to_string(), value);
             }
+
+            sysconfig.insert(key.to_string(), SysConfigValue::parse(value_str));
         }
     }
 }
@@ -0,0 +1,303 @@
+//! Input loading helper module
+//!
+//! Reads log files from disk and transparently decompresses gzip (`.BBL.gz`)
+//! and zip containers when the `gzip` / `zip` features are enabled.
+
+use crate::error::BBLError;
+use anyhow::{anyhow, Context, Result};
+use std::path::Path;
+
+/// Magic bytes at the start of a gzip stream
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/// Magic bytes at the start of a zip archive (local file header)
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Largest decompressed size accepted from a gzip stream or zip archive, so a
+/// small compressed file cannot expand without bound (2 GiB)
+pub const MAX_DECOMPRESSED_SIZE: u64 = 2 << 30;
+
+/// File extensions recognised as blackbox logs (lowercase)
+const LOG_EXTENSIONS: &[&str] = &["bbl", "bfl", "txt"];
+
+/// Container format detected from the leading bytes of an input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputCompression {
+    None,
+    Gzip,
+    Zip,
+}
+
+/// Detect the container format of `data` from its magic bytes
+pub fn detect_compression(data: &[u8]) -> InputCompression {
+    if data.starts_with(GZIP_MAGIC) {
+        InputCompression::Gzip
+    } else if data.starts_with(ZIP_MAGIC) {
+        InputCompression::Zip
+    } else {
+        InputCompression::None
+    }
+}
+
+/// Returns true if `path` names a compressed log this build can open:
+/// `*.bbl.gz`/`*.bfl.gz`/`*.txt.gz` with the `gzip` feature, `*.zip` with the `zip` feature.
+pub fn is_compressed_log_path(path: &Path) -> bool {
+    let extension = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => ext.to_ascii_lowercase(),
+        None => return false,
+    };
+
+    match extension.as_str() {
+        "gz" => {
+            cfg!(feature = "gzip")
+                && path
+                    .file_stem()
+                    .map(Path::new)
+                    .map(has_log_extension)
+                    .unwrap_or(false)
+        }
+        "zip" => cfg!(feature = "zip"),
+        _ => false,
+    }
+}
+
+/// Returns true if `path` has a plain blackbox log extension
+fn has_log_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| LOG_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Read a log file, decompressing gzip or zip containers when supported.
+///
+/// Zip archives yield the concatenation of every `.BBL`/`.BFL`/`.TXT` entry in
+/// name order, so each contained log is found by the usual log marker scan.
+pub fn read_bbl_file(file_path: &Path) -> Result<Vec<u8>> {
+    let data = std::fs::read(file_path)
+        .map_err(BBLError::Io)
+        .with_context(|| format!("Failed to read BBL file: {:?}", file_path))?;
+    decompress_bbl_data(data).with_context(|| format!("Failed to decompress {:?}", file_path))
+}
+
+/// Decompress `data` if it is a gzip stream or zip archive; otherwise return it unchanged
+///
+/// Fails with [`BBLError::InputTooLarge`] when the decompressed data would
+/// exceed [`MAX_DECOMPRESSED_SIZE`].
+pub fn decompress_bbl_data(data: Vec<u8>) -> Result<Vec<u8>> {
+    decompress_with_limit(data, MAX_DECOMPRESSED_SIZE)
+}
+
+fn decompress_with_limit(data: Vec<u8>, limit: u64) -> Result<Vec<u8>> {
+    match detect_compression(&data) {
+        InputCompression::None => Ok(data),
+        InputCompression::Gzip => decompress_gzip(&data, limit),
+        InputCompression::Zip => extract_zip_logs(&data, limit),
+    }
+}
+
+/// Wrap `reader` so gzip-compressed input is decompressed as it is read,
+/// e.g. to stream stdin into [`crate::BBLLogReader`]; plain input passes through.
+///
+/// Zip archives need random access, so they are read whole (up to
+/// [`MAX_DECOMPRESSED_SIZE`]) and their logs concatenated as in [`read_bbl_file`].
+pub fn decompress_bbl_reader<'a, R: std::io::BufRead + 'a>(
+    mut reader: R,
+) -> Result<Box<dyn std::io::Read + 'a>> {
+    use std::io::Read;
+
+    match detect_compression(reader.fill_buf()?) {
+        InputCompression::None => Ok(Box::new(reader)),
+        InputCompression::Gzip => gzip_reader(reader),
+        InputCompression::Zip => {
+            let mut archive = Vec::new();
+            reader
+                .take(MAX_DECOMPRESSED_SIZE + 1)
+                .read_to_end(&mut archive)?;
+            if archive.len() as u64 > MAX_DECOMPRESSED_SIZE {
+                return Err(BBLError::InputTooLarge {
+                    limit: MAX_DECOMPRESSED_SIZE,
+                }
+                .into());
+            }
+            Ok(Box::new(std::io::Cursor::new(decompress_bbl_data(
+                archive,
+            )?)))
+        }
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn gzip_reader<'a, R: std::io::BufRead + 'a>(reader: R) -> Result<Box<dyn std::io::Read + 'a>> {
+    Ok(Box::new(flate2::bufread::MultiGzDecoder::new(reader)))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip_reader<'a, R: std::io::BufRead + 'a>(_reader: R) -> Result<Box<dyn std::io::Read + 'a>> {
+    Err(anyhow!(
+        "Input is gzip-compressed but bbl_parser was built without the `gzip` feature"
+    ))
+}
+
+/// Append everything `reader` yields to `out`, failing once `out` would grow
+/// past `limit` bytes
+#[cfg(any(feature = "gzip", feature = "zip"))]
+fn read_to_end_limited(reader: impl std::io::Read, out: &mut Vec<u8>, limit: u64) -> Result<()> {
+    use std::io::Read;
+
+    // One byte past the limit tells a stream that ends exactly at it from a longer one
+    let remaining = limit.saturating_sub(out.len() as u64);
+    reader.take(remaining + 1).read_to_end(out)?;
+    if out.len() as u64 > limit {
+        return Err(BBLError::InputTooLarge { limit }.into());
+    }
+    Ok(())
+}
+
+#[cfg(feature = "gzip")]
+fn decompress_gzip(data: &[u8], limit: u64) -> Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    read_to_end_limited(
+        flate2::read::MultiGzDecoder::new(data),
+        &mut decompressed,
+        limit,
+    )?;
+    Ok(decompressed)
+}
+
+#[cfg(not(feature = "gzip"))]
+fn decompress_gzip(_data: &[u8], _limit: u64) -> Result<Vec<u8>> {
+    Err(anyhow!(
+        "Input is gzip-compressed but bbl_parser was built without the `gzip` feature"
+    ))
+}
+
+#[cfg(feature = "zip")]
+fn extract_zip_logs(data: &[u8], limit: u64) -> Result<Vec<u8>> {
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(data))?;
+
+    let mut log_entries: Vec<(String, usize)> = (0..archive.len())
+        .filter_map(|index| {
+            let entry = archive.by_index(index).ok()?;
+            let name = entry.name().to_string();
+            (entry.is_file() && has_log_extension(Path::new(&name))).then_some((name, index))
+        })
+        .collect();
+    log_entries.sort();
+
+    if log_entries.is_empty() {
+        return Err(anyhow!("Zip archive contains no .BBL/.BFL/.TXT files"));
+    }
+
+    let mut combined = Vec::new();
+    for (_, index) in log_entries {
+        read_to_end_limited(archive.by_index(index)?, &mut combined, limit)?;
+    }
+    Ok(combined)
+}
+
+#[cfg(not(feature = "zip"))]
+fn extract_zip_logs(_data: &[u8], _limit: u64) -> Result<Vec<u8>> {
+    Err(anyhow!(
+        "Input is a zip archive but bbl_parser was built without the `zip` feature"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_compression() {
+        assert_eq!(detect_compression(b"H Product:"), InputCompression::None);
+        assert_eq!(
+            detect_compression(&[0x1f, 0x8b, 0x08]),
+            InputCompression::Gzip
+        );
+        assert_eq!(detect_compression(b"PK\x03\x04rest"), InputCompression::Zip);
+        assert_eq!(detect_compression(b""), InputCompression::None);
+    }
+
+    #[test]
+    fn test_plain_data_passes_through() {
+        use std::io::Read;
+
+        let data = b"H Product:Blackbox".to_vec();
+        assert_eq!(decompress_bbl_data(data.clone()).unwrap(), data);
+        let mut streamed = Vec::new();
+        decompress_bbl_reader(&data[..])
+            .unwrap()
+            .read_to_end(&mut streamed)
+            .unwrap();
+        assert_eq!(streamed, data);
+    }
+
+    #[test]
+    fn test_is_compressed_log_path() {
+        assert_eq!(
+            is_compressed_log_path(Path::new("flight.BBL.gz")),
+            cfg!(feature = "gzip")
+        );
+        assert_eq!(
+            is_compressed_log_path(Path::new("logs.ZIP")),
+            cfg!(feature = "zip")
+        );
+        assert!(!is_compressed_log_path(Path::new("notes.md.gz")));
+        assert!(!is_compressed_log_path(Path::new("flight.BBL")));
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_roundtrip() {
+        use std::io::{Read, Write};
+
+        let original = b"H Product:Blackbox flight data recorder by Nicholas Sherlock\n".to_vec();
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&original).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        assert_eq!(decompress_bbl_data(compressed.clone()).unwrap(), original);
+        let mut streamed = Vec::new();
+        decompress_bbl_reader(&compressed[..])
+            .unwrap()
+            .read_to_end(&mut streamed)
+            .unwrap();
+        assert_eq!(streamed, original);
+
+        let limit = original.len() as u64;
+        assert_eq!(
+            decompress_with_limit(compressed.clone(), limit).unwrap(),
+            original
+        );
+        let err = decompress_with_limit(compressed, limit - 1).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<BBLError>(),
+            Some(BBLError::InputTooLarge { limit: l }) if *l == limit - 1
+        ));
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn test_zip_concatenates_log_entries() {
+        use std::io::Write;
+
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, contents) in [
+            ("b.BBL", &b"second"[..]),
+            ("readme.md", &b"ignored"[..]),
+            ("a.BFL", &b"first"[..]),
+        ] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        let archive = writer.finish().unwrap().into_inner();
+
+        assert_eq!(
+            decompress_bbl_data(archive.clone()).unwrap(),
+            b"firstsecond"
+        );
+        // The limit covers all entries together
+        assert!(decompress_with_limit(archive, 10).is_err());
+    }
+}
@@ -1,27 +1,52 @@
+use crate::error::{BBLError, Result};
+use crate::parser::config::ParserConfig;
+use crate::parser::diagnostics::DiagnosticReporter;
+use crate::parser::frame::FRAME_MARKERS;
 use crate::types::*;
-use anyhow::{anyhow, Context, Result};
+use std::io::{Read, Write};
+use std::ops::Range;
 use std::path::Path;
 
+/// Marker that begins every log within a BBL file
+pub(crate) const LOG_START_MARKER: &[u8] =
+    b"H Product:Blackbox flight data recorder by Nicholas Sherlock";
+
+/// Header line defining the I-frame fields, present in every complete log header
+const FIELD_I_NAME_HEADER: &[u8] = b"H Field I name:";
+
+/// Number of bytes requested from the reader per read call
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
 /// Parse BBL file and return all logs (for CLI and multi-log processing)
 pub fn parse_bbl_file_all_logs(
     file_path: &Path,
     export_options: crate::ExportOptions,
     debug: bool,
 ) -> Result<Vec<BBLLog>> {
+    parse_bbl_file_all_logs_with_config(file_path, export_options, debug, &ParserConfig::default())
+}
+
+/// Parse BBL file and return all logs, applying the given parser limits
+pub fn parse_bbl_file_all_logs_with_config(
+    file_path: &Path,
+    export_options: crate::ExportOptions,
+    debug: bool,
+    config: &ParserConfig,
+) -> Result<Vec<BBLLog>> {
+    let diag = config.reporter(debug);
     if debug {
-        println!("=== PARSING BBL FILE ===");
+        diag.debug("=== PARSING BBL FILE ===");
         let metadata = std::fs::metadata(file_path)?;
-        println!(
+        diag.debug(format!(
             "File size: {} bytes ({:.2} MB)",
             metadata.len(),
             metadata.len() as f64 / 1024.0 / 1024.0
-        );
+        ));
     }
 
-    let file_data = std::fs::read(file_path)
-        .with_context(|| format!("Failed to read BBL file: {:?}", file_path))?;
+    let file_data = crate::parser::input::read_bbl_file(file_path)?;
 
-    parse_bbl_bytes_all_logs(&file_data, export_options, debug)
+    parse_bbl_bytes_all_logs_with_config(&file_data, export_options, debug, config)
 }
 
 /// Parse BBL file and return first log (for library API compatibility)
@@ -30,10 +55,18 @@ pub fn parse_bbl_file(
     export_options: crate::ExportOptions,
     debug: bool,
 ) -> Result<BBLLog> {
-    let logs = parse_bbl_file_all_logs(file_path, export_options, debug)?;
-    logs.into_iter()
-        .next()
-        .ok_or_else(|| anyhow!("No logs found in BBL file"))
+    parse_bbl_file_with_config(file_path, export_options, debug, &ParserConfig::default())
+}
+
+/// Parse BBL file and return first log, applying the given parser limits
+pub fn parse_bbl_file_with_config(
+    file_path: &Path,
+    export_options: crate::ExportOptions,
+    debug: bool,
+    config: &ParserConfig,
+) -> Result<BBLLog> {
+    let logs = parse_bbl_file_all_logs_with_config(file_path, export_options, debug, config)?;
+    logs.into_iter().next().ok_or(BBLError::NoLogsFound)
 }
 
 /// Parse BBL data from memory and return all logs
@@ -42,42 +75,46 @@ pub fn parse_bbl_bytes_all_logs(
     export_options: crate::ExportOptions,
     debug: bool,
 ) -> Result<Vec<BBLLog>> {
+    parse_bbl_bytes_all_logs_with_config(data, export_options, debug, &ParserConfig::default())
+}
+
+/// Parse BBL data from memory and return all logs, applying the given parser limits
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "info", skip_all, fields(bytes = data.len()))
+)]
+pub fn parse_bbl_bytes_all_logs_with_config(
+    data: &[u8],
+    export_options: crate::ExportOptions,
+    debug: bool,
+    config: &ParserConfig,
+) -> Result<Vec<BBLLog>> {
+    let diag = config.reporter(debug);
     if debug {
-        println!("=== PARSING BBL DATA ===");
-        println!("Data size: {} bytes", data.len());
+        diag.debug("=== PARSING BBL DATA ===");
+        diag.debug(format!("Data size: {} bytes", data.len()));
     }
 
     // Look for multiple logs by searching for log start markers
-    let log_start_marker = b"H Product:Blackbox flight data recorder by Nicholas Sherlock";
-    let mut log_positions = Vec::new();
-
-    // Find all log start positions
-    for i in 0..data.len() {
-        if i + log_start_marker.len() <= data.len()
-            && &data[i..i + log_start_marker.len()] == log_start_marker
-        {
-            log_positions.push(i);
-        }
-    }
-
+    let log_positions = find_log_positions(data, config.salvage);
     if log_positions.is_empty() {
-        return Err(anyhow!("No blackbox log headers found in data"));
+        return Err(BBLError::NoLogsFound);
     }
 
     if debug {
-        println!("Found {} log(s) in data", log_positions.len());
+        diag.debug(format!("Found {} log(s) in data", log_positions.len()));
     }
 
     // Parse all logs
     let mut logs = Vec::new();
     for (log_index, &start_pos) in log_positions.iter().enumerate() {
         if debug {
-            println!(
+            diag.debug(format!(
                 "Parsing log {} of {} (starting at position {})",
                 log_index + 1,
                 log_positions.len(),
                 start_pos
-            );
+            ));
         }
 
         let end_pos = log_positions
@@ -92,6 +129,7 @@ pub fn parse_bbl_bytes_all_logs(
             log_positions.len(),
             debug,
             &export_options,
+            config,
         )?;
         logs.push(log);
     }
@@ -105,14 +143,593 @@ pub fn parse_bbl_bytes(
     export_options: crate::ExportOptions,
     debug: bool,
 ) -> Result<BBLLog> {
-    let logs = parse_bbl_bytes_all_logs(data, export_options, debug)?;
-    logs.into_iter()
-        .next()
-        .ok_or_else(|| anyhow!("No logs found in BBL data"))
+    parse_bbl_bytes_with_config(data, export_options, debug, &ParserConfig::default())
+}
+
+/// Parse BBL data from memory (returns first log), applying the given parser limits
+pub fn parse_bbl_bytes_with_config(
+    data: &[u8],
+    export_options: crate::ExportOptions,
+    debug: bool,
+    config: &ParserConfig,
+) -> Result<BBLLog> {
+    let logs = parse_bbl_bytes_all_logs_with_config(data, export_options, debug, config)?;
+    logs.into_iter().next().ok_or(BBLError::NoLogsFound)
+}
+
+/// Parse only log `log_number` (1-based) of a BBL file.
+///
+/// The other logs are skipped without being decoded, which is much faster than
+/// [`parse_bbl_file_all_logs`] for one log of a long multi-log file. Fails with
+/// [`BBLError::LogNotFound`] when the file has fewer logs.
+pub fn parse_bbl_file_log_n(
+    file_path: &Path,
+    log_number: usize,
+    export_options: crate::ExportOptions,
+    debug: bool,
+) -> Result<BBLLog> {
+    parse_bbl_file_log_n_with_config(
+        file_path,
+        log_number,
+        export_options,
+        debug,
+        &ParserConfig::default(),
+    )
+}
+
+/// Parse only log `log_number` (1-based) of a BBL file, applying the given parser limits
+pub fn parse_bbl_file_log_n_with_config(
+    file_path: &Path,
+    log_number: usize,
+    export_options: crate::ExportOptions,
+    debug: bool,
+    config: &ParserConfig,
+) -> Result<BBLLog> {
+    let file_data = crate::parser::input::read_bbl_file(file_path)?;
+    parse_bbl_bytes_log_n_with_config(&file_data, log_number, export_options, debug, config)
+}
+
+/// Parse only log `log_number` (1-based) of BBL data held in memory
+pub fn parse_bbl_bytes_log_n(
+    data: &[u8],
+    log_number: usize,
+    export_options: crate::ExportOptions,
+    debug: bool,
+) -> Result<BBLLog> {
+    parse_bbl_bytes_log_n_with_config(
+        data,
+        log_number,
+        export_options,
+        debug,
+        &ParserConfig::default(),
+    )
+}
+
+/// Parse only log `log_number` (1-based) of BBL data held in memory, applying the given parser limits
+pub fn parse_bbl_bytes_log_n_with_config(
+    data: &[u8],
+    log_number: usize,
+    export_options: crate::ExportOptions,
+    debug: bool,
+    config: &ParserConfig,
+) -> Result<BBLLog> {
+    let (range, total_logs) = log_byte_range(data, log_number, config)?;
+    parse_single_log(
+        &data[range],
+        log_number,
+        total_logs,
+        debug,
+        &export_options,
+        config,
+    )
+}
+
+/// Byte range of every log in BBL data, in file order.
+///
+/// A log runs from its start marker to the next log's start marker (or the end
+/// of the data), covering its text headers and its binary frames.
+pub fn log_byte_ranges(data: &[u8]) -> Vec<Range<usize>> {
+    log_byte_ranges_with_config(data, &ParserConfig::default())
+}
+
+/// Byte range of every log in BBL data, including logs salvaged from partial
+/// headers when [`ParserConfig::salvage`] is set
+pub fn log_byte_ranges_with_config(data: &[u8], config: &ParserConfig) -> Vec<Range<usize>> {
+    let log_positions = find_log_positions(data, config.salvage);
+    log_positions
+        .iter()
+        .enumerate()
+        .map(|(log_index, &start_pos)| {
+            let end_pos = log_positions
+                .get(log_index + 1)
+                .copied()
+                .unwrap_or(data.len());
+            start_pos..end_pos
+        })
+        .collect()
+}
+
+/// Copy the raw bytes of log `log_number` (1-based) of BBL data to `writer`.
+///
+/// The copy is a standalone single-log BBL file, headers included, for tools
+/// that handle single-log files better than multi-log ones. Returns the number
+/// of bytes written; fails with [`BBLError::LogNotFound`] when the data has
+/// fewer logs.
+///
+/// # Examples
+/// ```rust,no_run
+/// use bbl_parser::{log_byte_ranges, read_bbl_file, write_log_slice};
+/// use std::fs::File;
+/// use std::path::Path;
+///
+/// let data = read_bbl_file(Path::new("flight.BBL")).unwrap();
+/// for log_number in 1..=log_byte_ranges(&data).len() {
+///     let mut file = File::create(format!("flight.{log_number:02}.BBL")).unwrap();
+///     write_log_slice(&data, log_number, &mut file).unwrap();
+/// }
+/// ```
+pub fn write_log_slice<W: Write + ?Sized>(
+    data: &[u8],
+    log_number: usize,
+    writer: &mut W,
+) -> Result<u64> {
+    let (range, _) = log_byte_range(data, log_number, &ParserConfig::default())?;
+    writer.write_all(&data[range.clone()])?;
+    writer.flush()?;
+    Ok(range.len() as u64)
+}
+
+/// Copy every log of several BBL files, in order, to `writer` as one multi-log
+/// BBL file.
+///
+/// For flights that the flight controller split across files when logging
+/// restarted. Each log keeps its raw bytes, so the merged file holds the logs
+/// of `inputs[0]`, then those of `inputs[1]`, and so on. Returns the number of
+/// bytes written; fails with [`BBLError::NoLogsFound`] when no input holds a
+/// log.
+///
+/// # Examples
+/// ```rust,no_run
+/// use bbl_parser::{read_bbl_file, write_merged_logs};
+/// use std::fs::File;
+/// use std::path::Path;
+///
+/// let first = read_bbl_file(Path::new("LOG00001.BFL")).unwrap();
+/// let second = read_bbl_file(Path::new("LOG00002.BFL")).unwrap();
+/// let mut file = File::create("session.BBL").unwrap();
+/// write_merged_logs(&[&first, &second], &mut file).unwrap();
+/// ```
+pub fn write_merged_logs<W: Write + ?Sized>(inputs: &[&[u8]], writer: &mut W) -> Result<u64> {
+    let mut written = 0;
+    for data in inputs {
+        for range in log_byte_ranges(data) {
+            writer.write_all(&data[range.clone()])?;
+            written += range.len() as u64;
+        }
+    }
+    if written == 0 {
+        return Err(BBLError::NoLogsFound);
+    }
+    writer.flush()?;
+    Ok(written)
+}
+
+/// Byte range of log `log_number` (1-based) and the number of logs in `data`
+pub(crate) fn log_byte_range(
+    data: &[u8],
+    log_number: usize,
+    config: &ParserConfig,
+) -> Result<(Range<usize>, usize)> {
+    let mut ranges = log_byte_ranges_with_config(data, config);
+    if ranges.is_empty() {
+        return Err(BBLError::NoLogsFound);
+    }
+    let total_logs = ranges.len();
+    if log_number == 0 || log_number > total_logs {
+        return Err(BBLError::LogNotFound {
+            log_number,
+            total_logs,
+        });
+    }
+    Ok((ranges.swap_remove(log_number - 1), total_logs))
+}
+
+/// Parse only the text headers of every log in a BBL file.
+///
+/// Binary frames are not decoded, so this is much faster than
+/// [`parse_bbl_file_all_logs`] when only firmware, craft or field definitions
+/// are needed. The header of log `n` is at index `n - 1`.
+pub fn parse_bbl_file_headers_only(file_path: &Path, debug: bool) -> Result<Vec<BBLHeader>> {
+    let file_data = crate::parser::input::read_bbl_file(file_path)?;
+    parse_bbl_bytes_headers_only(&file_data, debug)
+}
+
+/// Parse only the text headers of every log in BBL data held in memory
+pub fn parse_bbl_bytes_headers_only(data: &[u8], debug: bool) -> Result<Vec<BBLHeader>> {
+    let config = ParserConfig::default();
+    let diag = config.reporter(debug);
+    let ranges = log_byte_ranges(data);
+    if ranges.is_empty() {
+        return Err(BBLError::NoLogsFound);
+    }
+
+    ranges
+        .into_iter()
+        .map(|range| {
+            let log_data = &data[range];
+            let header_end = header_section_end(log_data);
+            parse_header_section(&log_data[..header_end], diag)
+        })
+        .collect()
+}
+
+/// Parse BBL data from any reader and return all logs.
+///
+/// Data is buffered one log at a time, so memory use is bounded by the largest
+/// log rather than the whole stream. Use [`BBLLogReader`] to receive each log as
+/// soon as it has been read.
+pub fn parse_bbl_reader<R: Read>(
+    reader: R,
+    export_options: crate::ExportOptions,
+    debug: bool,
+) -> Result<Vec<BBLLog>> {
+    let mut logs = BBLLogReader::new(reader, export_options, debug).collect::<Result<Vec<_>>>()?;
+
+    if logs.is_empty() {
+        return Err(BBLError::NoLogsFound);
+    }
+
+    let total_logs = logs.len();
+    for log in &mut logs {
+        log.total_logs = total_logs;
+    }
+
+    Ok(logs)
+}
+
+/// Incremental log parser over an [`std::io::Read`] source.
+///
+/// Yields one [`BBLLog`] per log start marker found in the stream. The total
+/// number of logs is not known while streaming, so `total_logs` on each yielded
+/// log equals its own `log_number`.
+pub struct BBLLogReader<R: Read> {
+    reader: R,
+    buffer: Vec<u8>,
+    /// Offset from which the next marker search resumes
+    search_from: usize,
+    reader_eof: bool,
+    log_number: usize,
+    export_options: crate::ExportOptions,
+    debug: bool,
+    config: ParserConfig,
+}
+
+impl<R: Read> BBLLogReader<R> {
+    pub fn new(reader: R, export_options: crate::ExportOptions, debug: bool) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            search_from: 0,
+            reader_eof: false,
+            log_number: 0,
+            export_options,
+            debug,
+            config: ParserConfig::default(),
+        }
+    }
+
+    /// Apply parser limits to every log read from the stream
+    pub fn with_config(mut self, config: ParserConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Read the next chunk into the buffer; returns false once the reader is exhausted
+    fn fill_buffer(&mut self) -> Result<bool> {
+        if self.reader_eof {
+            return Ok(false);
+        }
+        let mut chunk = [0u8; READ_CHUNK_SIZE];
+        let read = loop {
+            match self.reader.read(&mut chunk) {
+                Ok(read) => break read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(BBLError::Io(e)),
+            }
+        };
+        if read == 0 {
+            self.reader_eof = true;
+            return Ok(false);
+        }
+        self.buffer.extend_from_slice(&chunk[..read]);
+        Ok(true)
+    }
+
+    /// Locate the next log start marker at or after `search_from`, `inside_log`
+    /// when the buffer begins with a log
+    fn find_marker(&mut self, inside_log: bool) -> Option<usize> {
+        let mut from = self.search_from;
+        while let Some(pos) = find_log_marker(&self.buffer, from) {
+            match log_start_validity(&self.buffer, pos, inside_log, self.reader_eof) {
+                Some(true) => return Some(pos),
+                Some(false) => from = pos + 1,
+                None => {
+                    // Its headers are not fully read yet
+                    self.search_from = pos;
+                    return None;
+                }
+            }
+        }
+        // Keep a marker-sized tail so markers spanning two reads are still found
+        self.search_from = self
+            .buffer
+            .len()
+            .saturating_sub(LOG_START_MARKER.len() - 1)
+            .max(from);
+        None
+    }
+
+    /// Discard the log at the start of the buffer up to the next log start
+    /// marker, reading no more than needed to find it
+    fn skip_log(&mut self) -> Result<()> {
+        loop {
+            if let Some(end) = self.find_marker(true) {
+                self.buffer.drain(..end);
+                self.search_from = 0;
+                return Ok(());
+            }
+            // Keep the byte before the search position for the boundary check
+            let keep_from = self.search_from.saturating_sub(1);
+            self.buffer.drain(..keep_from);
+            self.search_from -= keep_from;
+            if !self.fill_buffer()? {
+                self.buffer.clear();
+                self.search_from = 0;
+                return Ok(());
+            }
+        }
+    }
+
+    /// Read until the buffer holds one complete log, returning its length
+    ///
+    /// A log growing past [`ParserConfig::max_log_size`] is skipped as soon as
+    /// the limit is exceeded, without buffering the rest of it.
+    fn next_log_len(&mut self) -> Result<Option<usize>> {
+        // Discard anything before the first marker
+        loop {
+            if let Some(start) = self.find_marker(false) {
+                self.buffer.drain(..start);
+                self.search_from = LOG_START_MARKER.len();
+                break;
+            }
+            self.buffer.drain(..self.search_from);
+            self.search_from = 0;
+            if !self.fill_buffer()? {
+                return Ok(None);
+            }
+        }
+
+        // The log extends to the next marker or the end of the stream
+        loop {
+            if let Some(end) = self.find_marker(true) {
+                return Ok(Some(end));
+            }
+            // Everything before the search position belongs to this log
+            if let Some(limit) = self.config.max_log_size {
+                if self.search_from > limit {
+                    self.skip_log()?;
+                    self.log_number += 1;
+                    return Err(BBLError::LogTooLarge {
+                        log_number: self.log_number,
+                        limit,
+                    });
+                }
+            }
+            if !self.fill_buffer()? {
+                return Ok(Some(self.buffer.len()));
+            }
+        }
+    }
+}
+
+impl<R: Read> BBLLogReader<R> {
+    /// Read the next log without decoding it, e.g. to decode it later with
+    /// [`parse_single_log`]; it counts towards the log numbers as if it had
+    /// been yielded by the iterator
+    pub fn next_log_bytes(&mut self) -> Option<Result<Vec<u8>>> {
+        let log_len = match self.next_log_len() {
+            Ok(Some(len)) => len,
+            Ok(None) => return None,
+            Err(e) => return Some(Err(e)),
+        };
+
+        self.log_number += 1;
+        let rest = self.buffer.split_off(log_len);
+        self.search_from = 0;
+        Some(Ok(std::mem::replace(&mut self.buffer, rest)))
+    }
+}
+
+impl<R: Read> Iterator for BBLLogReader<R> {
+    type Item = Result<BBLLog>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let log_data = match self.next_log_bytes()? {
+            Ok(log_data) => log_data,
+            Err(e) => return Some(Err(e)),
+        };
+        if self.debug {
+            self.config.reporter(true).debug(format!(
+                "Parsing log {} from stream ({} bytes)",
+                self.log_number,
+                log_data.len()
+            ));
+        }
+
+        Some(parse_single_log(
+            &log_data,
+            self.log_number,
+            self.log_number,
+            self.debug,
+            &self.export_options,
+            &self.config,
+        ))
+    }
+}
+
+/// Find the first log start marker in `data` at or after `from`
+fn find_log_marker(data: &[u8], from: usize) -> Option<usize> {
+    if from >= data.len() {
+        return None;
+    }
+    data[from..]
+        .windows(LOG_START_MARKER.len())
+        .position(|window| window == LOG_START_MARKER)
+        .map(|pos| pos + from)
+}
+
+/// Offsets of every log in `data`: each log start marker that begins a log,
+/// plus with `salvage` each header block that lost its marker
+fn find_log_positions(data: &[u8], salvage: bool) -> Vec<usize> {
+    let mut log_positions = Vec::new();
+    let mut from = 0;
+    while let Some(pos) = find_log_marker(data, from) {
+        if log_start_validity(data, pos, !log_positions.is_empty(), true) == Some(true) {
+            log_positions.push(pos);
+            from = pos + LOG_START_MARKER.len();
+        } else {
+            from = pos + 1;
+        }
+    }
+    if salvage {
+        log_positions = add_salvaged_positions(data, log_positions);
+    }
+    log_positions
+}
+
+/// Add to `log_positions` the start of every block of header lines holding
+/// `H Field I name:` outside the headers of those logs: a log whose leading
+/// headers, log start marker included, were lost
+fn add_salvaged_positions(data: &[u8], mut log_positions: Vec<usize>) -> Vec<usize> {
+    let header_spans: Vec<Range<usize>> = log_positions
+        .iter()
+        .map(|&pos| pos..pos + header_section_end(&data[pos..]))
+        .collect();
+    let mut from = 0;
+    while let Some(offset) = data[from..]
+        .windows(FIELD_I_NAME_HEADER.len())
+        .position(|window| window == FIELD_I_NAME_HEADER)
+    {
+        let pos = from + offset;
+        let at_line_start = pos == 0 || data[pos - 1] == b'\n';
+        let block_start = header_block_start(data, pos);
+        let block_end = block_start + header_section_end(&data[block_start..]);
+        if at_line_start && block_end > pos && !header_spans.iter().any(|span| span.contains(&pos))
+        {
+            log_positions.push(block_start);
+            from = block_end;
+        } else {
+            from = pos + 1;
+        }
+    }
+    log_positions.sort_unstable();
+    log_positions
+}
+
+/// Start of the run of complete header lines that ends at line start `pos`
+fn header_block_start(data: &[u8], mut pos: usize) -> usize {
+    while pos > 0 && data[pos - 1] == b'\n' {
+        // A header line holds no control bytes besides tab and CR
+        let line_start = data[..pos - 1]
+            .iter()
+            .rposition(|&byte| byte < 0x20 && byte != b'\t' && byte != b'\r')
+            .map_or(0, |control| control + 1);
+        if header_line_len(&data[line_start..]) != Some(pos - line_start) {
+            break;
+        }
+        pos = line_start;
+    }
+    pos
+}
+
+/// Whether the log start marker at `pos` in `data` begins a log, or `None`
+/// when more data is needed to tell
+///
+/// The marker must be followed by further header lines. Within a log
+/// (`inside_log`), it must also sit at a log boundary (flash padding or the end
+/// of the previous log) or be followed by field definitions, so a marker
+/// matched inside frame data does not split the log. `complete` is set when
+/// `data` holds the whole input.
+fn log_start_validity(data: &[u8], pos: usize, inside_log: bool, complete: bool) -> Option<bool> {
+    let rest = &data[pos..];
+    let header_len = header_section_end(rest);
+    let header = &rest[..header_len];
+    let at_boundary = !inside_log || pos == 0 || matches!(data[pos - 1], 0xFF | 0x00 | b'\n');
+    let has_field_definitions = header
+        .windows(FIELD_I_NAME_HEADER.len())
+        .any(|window| window == FIELD_I_NAME_HEADER);
+    let header_lines = header.iter().filter(|&&byte| byte == b'\n').count();
+    let valid = header_lines >= 2 && (at_boundary || has_field_definitions);
+    // Without a control byte after the headers, more data may extend them
+    let headers_ended = rest[header_len..]
+        .iter()
+        .any(|&byte| byte < 0x20 && byte != b'\t' && byte != b'\r');
+    (valid || headers_ended || complete).then_some(valid)
+}
+
+/// Length of the text header section at the start of `log_data`: the end of
+/// the last complete `H name:value` line
+///
+/// Binary frame data may itself start with an `H` (GPS home) frame, so a line
+/// only counts as a header when it is text up to its newline.
+pub(crate) fn header_section_end(log_data: &[u8]) -> usize {
+    let mut end = 0;
+    while let Some(line_len) = header_line_len(&log_data[end..]) {
+        end += line_len;
+    }
+    end
 }
 
-// Note: The rest of the parsing functions will be migrated from src/main.rs
-// This is a placeholder for the systematic migration process
+/// Length, newline included, of the header line `data` starts with
+fn header_line_len(data: &[u8]) -> Option<usize> {
+    if !data.starts_with(b"H ") {
+        return None;
+    }
+    // Text only: binary data almost always has control bytes before a newline
+    let newline = data
+        .iter()
+        .position(|&byte| byte == b'\n' || (byte < 0x20 && byte != b'\t' && byte != b'\r'))?;
+    (data[newline] == b'\n' && data[..newline].contains(&b':')).then_some(newline + 1)
+}
+
+/// Offset in `log_data` where frame data starts, given the end of its headers
+///
+/// Frames normally follow the headers directly. When the byte there does not
+/// start a frame, stray bytes are skipped up to the first I-frame, which every
+/// log's frame data starts decoding from.
+pub(crate) fn frame_data_start(log_data: &[u8], header_end: usize) -> usize {
+    let frame_data = &log_data[header_end..];
+    match frame_data.first() {
+        Some(byte) if !FRAME_MARKERS.contains(byte) => frame_data
+            .iter()
+            .position(|&byte| byte == b'I')
+            .map_or(log_data.len(), |offset| header_end + offset),
+        _ => header_end,
+    }
+}
+
+/// Parse the text header section of a log
+pub(crate) fn parse_header_section(
+    header_data: &[u8],
+    diag: DiagnosticReporter,
+) -> Result<BBLHeader> {
+    let header_text = std::str::from_utf8(header_data)
+        .map_err(|e| BBLError::HeaderParse(format!("header section is not valid UTF-8: {e}")))?;
+    Ok(crate::parser::header::parse_headers_from_text(
+        header_text,
+        diag,
+    )?)
+}
 
 /// Parse a single BBL log from binary data.
 ///
@@ -126,40 +743,83 @@ pub fn parse_bbl_bytes(
 /// * `total_logs` - Total number of logs in the file
 /// * `debug` - Enable debug output
 /// * `export_options` - Export options controlling GPS/event collection
+/// * `config` - Parser safety limits
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "info", skip_all, fields(log_number = log_number, bytes = log_data.len()))
+)]
 pub fn parse_single_log(
     log_data: &[u8],
     log_number: usize,
     total_logs: usize,
     debug: bool,
     export_options: &crate::ExportOptions,
+    config: &ParserConfig,
 ) -> Result<BBLLog> {
-    // Find where headers end and binary data begins
-    let mut header_end = 0;
-    for i in 1..log_data.len() {
-        if log_data[i - 1] == b'\n' && log_data[i] != b'H' {
-            header_end = i;
-            break;
+    if let Some(max_log_size) = config.max_log_size {
+        if log_data.len() > max_log_size {
+            return Err(BBLError::LogTooLarge {
+                log_number,
+                limit: max_log_size,
+            });
         }
     }
 
-    if header_end == 0 {
-        header_end = log_data.len();
+    #[cfg(feature = "tracing")]
+    let started = std::time::Instant::now();
+
+    if config.salvage && !log_data.starts_with(LOG_START_MARKER) {
+        config.reporter(debug).warn(format!(
+            "Log {log_number}: no log start marker, decoding from partial headers"
+        ));
     }
 
-    // Parse headers from the text section
-    let header_text = std::str::from_utf8(&log_data[0..header_end])?;
-    let header = crate::parser::header::parse_headers_from_text(header_text, debug)?;
+    // Find where headers end and binary data begins
+    let header_end = header_section_end(log_data);
+    let header = parse_header_section(&log_data[..header_end], config.reporter(debug))?;
+    // Strict mode reports stray bytes as an invalid frame instead
+    let frame_start = match config.mode {
+        crate::ParseMode::Strict => header_end,
+        _ => frame_data_start(log_data, header_end),
+    };
+    if frame_start > header_end {
+        config.reporter(debug).warn(format!(
+            "Log {log_number}: skipped {} stray bytes between the headers and the first frame",
+            frame_start - header_end
+        ));
+    }
 
     // Parse binary frame data
-    let binary_data = &log_data[header_end..];
-    let (mut stats, frames, debug_frames, gps_coordinates, home_coordinates, event_frames) =
-        crate::parser::frame::parse_frames(binary_data, &header, debug, export_options)?;
+    let binary_data = &log_data[frame_start..];
+    let (
+        mut stats,
+        mut frames,
+        debug_frames,
+        gps_coordinates,
+        home_coordinates,
+        event_frames,
+        slow_frames,
+        gps_frames,
+    ) = crate::parser::frame::parse_frames(binary_data, &header, debug, export_options, config)?;
 
     // Update frame stats timing from actual frame data
-    if !frames.is_empty() {
-        stats.start_time_us = frames.first().unwrap().timestamp_us;
-        stats.end_time_us = frames.last().unwrap().timestamp_us;
+    if let (Some(&first), Some(&last)) = (frames.timestamps().first(), frames.timestamps().last()) {
+        stats.start_time_us = first;
+        stats.end_time_us = last;
     }
+    frames.build_time_index();
+
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        total_frames = stats.total_frames,
+        i_frames = stats.i_frames,
+        p_frames = stats.p_frames,
+        failed_frames = stats.failed_frames,
+        truncated = stats.truncated,
+        ended_cleanly = stats.ended_cleanly,
+        elapsed_ms = started.elapsed().as_millis() as u64,
+        "parsed log"
+    );
 
     let log = BBLLog {
         log_number,
@@ -171,7 +831,971 @@ pub fn parse_single_log(
         gps_coordinates,
         home_coordinates,
         event_frames,
+        slow_frames,
+        gps_frames,
     };
 
     Ok(log)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FrameErrorReason;
+    use std::sync::Arc;
+
+    /// Reader that returns at most a few bytes per call to exercise chunk boundaries
+    struct TrickleReader<'a> {
+        data: &'a [u8],
+    }
+
+    impl Read for TrickleReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(self.data.len()).min(7);
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+            Ok(len)
+        }
+    }
+
+    fn two_log_data() -> Vec<u8> {
+        let mut data = b"garbage".to_vec();
+        for craft in ["first", "second"] {
+            data.extend_from_slice(LOG_START_MARKER);
+            data.extend_from_slice(b"\nH Firmware revision:Betaflight 4.5.1\n");
+            data.extend_from_slice(format!("H Craft name:{craft}\n").as_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_bbl_reader_matches_bytes() {
+        let data = two_log_data();
+        let from_bytes =
+            parse_bbl_bytes_all_logs(&data, crate::ExportOptions::default(), false).unwrap();
+        let from_reader = parse_bbl_reader(
+            TrickleReader { data: &data },
+            crate::ExportOptions::default(),
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(from_reader.len(), 2);
+        for (a, b) in from_bytes.iter().zip(&from_reader) {
+            assert_eq!(a.header.craft_name, b.header.craft_name);
+            assert_eq!(a.log_number, b.log_number);
+            assert_eq!(a.total_logs, b.total_logs);
+        }
+        assert_eq!(from_reader[1].header.craft_name, "second");
+    }
+
+    #[test]
+    fn test_parse_log_n() {
+        let data = two_log_data();
+        let log = parse_bbl_bytes_log_n(&data, 2, crate::ExportOptions::default(), false).unwrap();
+        assert_eq!(log.header.craft_name, "second");
+        assert_eq!(log.log_number, 2);
+        assert_eq!(log.total_logs, 2);
+
+        for log_number in [0, 3] {
+            let err =
+                parse_bbl_bytes_log_n(&data, log_number, crate::ExportOptions::default(), false)
+                    .unwrap_err();
+            assert!(matches!(err, BBLError::LogNotFound { total_logs: 2, .. }));
+        }
+    }
+
+    #[test]
+    fn test_write_log_slice() {
+        let data = two_log_data();
+        let ranges = log_byte_ranges(&data);
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[0].end, ranges[1].start);
+        assert_eq!(ranges[1].end, data.len());
+
+        // Each slice is a standalone single-log file
+        let mut second = Vec::new();
+        let written = write_log_slice(&data, 2, &mut second).unwrap();
+        assert_eq!(written, second.len() as u64);
+        assert_eq!(second, &data[ranges[1].clone()]);
+        let headers = parse_bbl_bytes_headers_only(&second, false).unwrap();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers[0].craft_name, "second");
+
+        let err = write_log_slice(&data, 3, &mut Vec::new()).unwrap_err();
+        assert!(matches!(
+            err,
+            BBLError::LogNotFound {
+                log_number: 3,
+                total_logs: 2
+            }
+        ));
+    }
+
+    #[test]
+    fn test_write_merged_logs() {
+        let data = two_log_data();
+        let ranges = log_byte_ranges(&data);
+        let first = &data[ranges[0].clone()];
+
+        let mut merged = Vec::new();
+        let written = write_merged_logs(&[&data, b"no logs here", first], &mut merged).unwrap();
+        assert_eq!(written, merged.len() as u64);
+        let headers = parse_bbl_bytes_headers_only(&merged, false).unwrap();
+        let crafts: Vec<&str> = headers.iter().map(|h| h.craft_name.as_str()).collect();
+        assert_eq!(crafts, ["first", "second", "first"]);
+
+        let err = write_merged_logs(&[b"no logs here"], &mut Vec::new()).unwrap_err();
+        assert!(matches!(err, BBLError::NoLogsFound));
+    }
+
+    #[test]
+    fn test_parse_headers_only() {
+        let mut data = two_log_data();
+        // Binary frame data after the headers is never decoded
+        data.extend_from_slice(&[b'I', 0xFF, 0xFF, 0xFF]);
+        let headers = parse_bbl_bytes_headers_only(&data, false).unwrap();
+
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[0].craft_name, "first");
+        assert_eq!(headers[1].craft_name, "second");
+        assert_eq!(headers[1].firmware_revision, "Betaflight 4.5.1");
+
+        let err = parse_bbl_bytes_headers_only(b"no logs here", false).unwrap_err();
+        assert!(matches!(err, BBLError::NoLogsFound));
+    }
+
+    #[test]
+    fn test_max_log_size() {
+        let data = two_log_data();
+        let config = ParserConfig {
+            max_log_size: Some(16),
+            ..Default::default()
+        };
+        let result = parse_bbl_bytes_all_logs_with_config(
+            &data,
+            crate::ExportOptions::default(),
+            false,
+            &config,
+        );
+        assert!(result.is_err());
+
+        let logs = BBLLogReader::new(TrickleReader { data: &data }, Default::default(), false)
+            .with_config(config)
+            .collect::<Vec<_>>();
+        assert!(logs.iter().all(|log| log.is_err()));
+    }
+
+    #[test]
+    fn test_reader_skips_oversized_log_while_reading() {
+        let data = two_log_data();
+        let second = find_log_marker(&data, LOG_START_MARKER.len()).unwrap();
+        let filler_len = 1 << 20;
+        let stream = std::io::Read::chain(
+            std::io::Read::chain(&data[..second], std::io::repeat(0x01).take(filler_len)),
+            std::io::Read::chain(&b"\n"[..], &data[second..]),
+        );
+        let config = ParserConfig {
+            max_log_size: Some(1024),
+            ..Default::default()
+        };
+
+        let mut reader = BBLLogReader::new(stream, Default::default(), false).with_config(config);
+        let err = reader.next().unwrap().unwrap_err();
+        assert!(matches!(
+            err,
+            BBLError::LogTooLarge {
+                log_number: 1,
+                limit: 1024
+            }
+        ));
+        let log = reader.next().unwrap().unwrap();
+        assert_eq!(
+            (log.log_number, log.header.craft_name.as_str()),
+            (2, "second")
+        );
+        assert!(reader.next().is_none());
+        // The oversized log was never held in memory
+        assert!(reader.buffer.capacity() < 4 * READ_CHUNK_SIZE);
+    }
+
+    #[test]
+    fn test_strict_mode_rejects_invalid_frame() {
+        let mut data = LOG_START_MARKER.to_vec();
+        data.extend_from_slice(
+            b"\nH Field I name:loopIteration,time\nH Field I signed:0,0\n\
+              H Field I predictor:0,0\nH Field I encoding:1,1\n",
+        );
+        // One valid I frame (loopIteration 1, time 2000) followed by a stray byte
+        data.extend_from_slice(&[b'I', 1, 0xD0, 0x0F, 0x00]);
+
+        let lenient = parse_bbl_bytes_all_logs(&data, crate::ExportOptions::default(), false)
+            .expect("lenient mode tolerates the stray byte");
+        assert_eq!(lenient[0].frames.len(), 1);
+        assert_eq!(lenient[0].stats.failed_frames, 1);
+
+        let strict = ParserConfig {
+            mode: crate::ParseMode::Strict,
+            ..Default::default()
+        };
+        let err = parse_bbl_bytes_all_logs_with_config(
+            &data,
+            crate::ExportOptions::default(),
+            false,
+            &strict,
+        )
+        .unwrap_err();
+        assert!(matches!(err, crate::BBLError::InvalidFrame(_)));
+    }
+
+    #[test]
+    fn test_frame_errors_locate_failed_frames() {
+        let mut data = LOG_START_MARKER.to_vec();
+        data.extend_from_slice(
+            b"\nH Field I name:loopIteration,time\nH Field I signed:0,0\n\
+              H Field I predictor:0,0\nH Field I encoding:1,1\n",
+        );
+        // An I frame at time 5 of loop 0, a valid I frame, a P frame the
+        // header defines no fields for, an I frame followed by a stray byte
+        // (not a frame end, so skipped while resynchronizing) and a valid I frame
+        data.extend_from_slice(&[
+            b'I', 0, 5, b'I', 1, 0xD0, 0x0F, b'P', b'I', 2, 0xB8, 0x17, 0x00, b'I', 3, 0xA0, 0x1F,
+        ]);
+
+        let log = parse_bbl_bytes(&data, crate::ExportOptions::default(), false).unwrap();
+        assert_eq!(log.stats.failed_frames, 2);
+        let errors: Vec<_> = log
+            .stats
+            .frame_errors
+            .iter()
+            .map(|error| (error.offset, error.frame_type, &error.reason))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (
+                    0,
+                    'I',
+                    &FrameErrorReason::Implausible {
+                        time_us: 5,
+                        loop_iteration: 0
+                    }
+                ),
+                (7, 'P', &FrameErrorReason::NoFieldDefinition),
+            ]
+        );
+        assert_eq!(log.frames.loop_iterations(), [1, 3]);
+    }
+
+    #[test]
+    fn test_resync_after_corrupt_frame() {
+        let mut i_frame_def =
+            FrameDefinition::from_field_names(vec!["loopIteration".into(), "time".into()]);
+        i_frame_def.update_encoding(&[1, 1]);
+        let mut p_frame_def = i_frame_def.clone();
+        p_frame_def.update_predictors(&[6, 2]);
+        p_frame_def.update_encoding(&[9, 0]);
+        let header = BBLHeader {
+            i_frame_def,
+            p_frame_def,
+            ..Default::default()
+        };
+
+        // An I frame every 8 frames; the frame type byte of frame 3 is lost
+        let mut data = Vec::new();
+        let mut writer = crate::BBLWriter::new(&mut data, &header).unwrap();
+        let mut frame_3 = 0;
+        for iteration in 1..=24 {
+            let values = [iteration, iteration * 1000];
+            if iteration == 3 {
+                frame_3 = writer.bytes_written() as usize;
+            }
+            if iteration % 8 == 1 {
+                writer.write_i_frame(&values).unwrap();
+            } else {
+                writer.write_p_frame(&values).unwrap();
+            }
+        }
+        writer.finish().unwrap();
+        data[frame_3] = 0x00;
+
+        let log = parse_bbl_bytes(&data, crate::ExportOptions::default(), false).unwrap();
+        assert_eq!(log.stats.failed_frames, 1);
+        let expected: Vec<u32> = [1, 2].into_iter().chain(9..=24).collect();
+        assert_eq!(log.frames.loop_iterations(), expected);
+    }
+
+    #[test]
+    fn test_erased_flash_is_skipped() {
+        let mut i_frame_def =
+            FrameDefinition::from_field_names(vec!["loopIteration".into(), "time".into()]);
+        i_frame_def.update_encoding(&[1, 1]);
+        let header = BBLHeader {
+            i_frame_def,
+            ..Default::default()
+        };
+        let log_with_gap = |gap: usize| {
+            let mut data = Vec::new();
+            let mut writer = crate::BBLWriter::new(&mut data, &header).unwrap();
+            writer.write_i_frame(&[1, 1000]).unwrap();
+            drop(writer);
+            data.extend(std::iter::repeat(0xFF).take(gap));
+            data.extend_from_slice(&[b'I', 2, 0xD0, 0x0F]);
+            data
+        };
+
+        // Erased flash inside the first log and after it, which stops without
+        // a log end event
+        let mut data = log_with_gap(64);
+        data.extend_from_slice(&[0xFF; 4096]);
+        let mut writer = crate::BBLWriter::new(&mut data, &header).unwrap();
+        writer.write_i_frame(&[1, 1000]).unwrap();
+        writer.finish().unwrap();
+
+        let logs = parse_bbl_bytes_all_logs(&data, crate::ExportOptions::default(), false).unwrap();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].stats.failed_frames, 0);
+        assert_eq!(logs[0].frames.loop_iterations(), [1, 2]);
+        assert!(!logs[0].stats.ended_cleanly);
+        assert_eq!(logs[1].stats.failed_frames, 0);
+
+        // A short run is still a corrupt frame
+        let log =
+            parse_bbl_bytes(&log_with_gap(2), crate::ExportOptions::default(), false).unwrap();
+        assert_eq!(log.stats.failed_frames, 1);
+        assert_eq!(log.frames.loop_iterations(), [1, 2]);
+    }
+
+    #[test]
+    fn test_log_end_event_stops_decoding() {
+        let mut i_frame_def =
+            FrameDefinition::from_field_names(vec!["loopIteration".into(), "time".into()]);
+        i_frame_def.update_encoding(&[1, 1]);
+        let header = BBLHeader {
+            i_frame_def,
+            ..Default::default()
+        };
+
+        // Junk after the log end event is not decoded
+        let mut data = Vec::new();
+        let mut writer = crate::BBLWriter::new(&mut data, &header).unwrap();
+        writer.write_i_frame(&[1, 1000]).unwrap();
+        writer.finish().unwrap();
+        data.extend_from_slice(&[0x00, 0x01, b'I', 2, 0xD0, 0x0F]);
+        let log = parse_bbl_bytes(&data, crate::ExportOptions::default(), false).unwrap();
+        assert!(log.stats.ended_cleanly);
+        assert_eq!(log.stats.failed_frames, 0);
+        assert_eq!(log.frames.len(), 1);
+
+        // A log end event without its trailer does not end the log
+        let mut data = Vec::new();
+        let mut writer = crate::BBLWriter::new(&mut data, &header).unwrap();
+        writer.write_i_frame(&[1, 1000]).unwrap();
+        writer.write_event(255, &[]).unwrap();
+        writer.write_i_frame(&[2, 2000]).unwrap();
+        drop(writer);
+        let log = parse_bbl_bytes(&data, crate::ExportOptions::default(), false).unwrap();
+        assert!(!log.stats.ended_cleanly);
+        assert_eq!(log.frames.len(), 2);
+    }
+
+    #[test]
+    fn test_emuflight_header_aliases() {
+        let parse = |firmware: &str| {
+            crate::parser::header::parse_headers_from_text(
+                &format!(
+                    "H Firmware revision:{firmware}\nH gyro_lowpass_hz:150\n\
+                     H dterm_lowpass_hz:120\nH dterm_lpf1_static_hz:100\n"
+                ),
+                crate::parser::diagnostics::DiagnosticReporter::silent(),
+            )
+            .unwrap()
+        };
+
+        let header = parse("EmuFlight 0.4.1 (9d8fe8bde) HELIOSPRING");
+        assert_eq!(header.sysconfig_int("gyro_lpf1_static_hz"), Some(150));
+        assert_eq!(header.sysconfig_int("gyro_lowpass_hz"), Some(150));
+        // A header logged under the Betaflight name is kept
+        assert_eq!(header.sysconfig_int("dterm_lpf1_static_hz"), Some(100));
+
+        let header = parse("Betaflight 4.2.0 (b2d7b1d8b) STM32F405");
+        assert_eq!(header.sysconfig.get("gyro_lpf1_static_hz"), None);
+    }
+
+    #[test]
+    fn test_pid_config() {
+        let parse = |text: &str| {
+            crate::parser::header::parse_headers_from_text(
+                text,
+                crate::parser::diagnostics::DiagnosticReporter::silent(),
+            )
+            .unwrap()
+            .pid_config()
+        };
+        let pid = |p, i, d, ff| AxisPid { p, i, d, ff };
+
+        // Betaflight: feedforward from ff_weight
+        let pids = parse(
+            "H rollPID:45,80,40\nH pitchPID:47,84,46\nH yawPID:45,80,0\n\
+             H ff_weight:120,125,120\n",
+        )
+        .unwrap();
+        assert_eq!(pids.roll, pid(45, 80, 40, Some(120)));
+        assert_eq!(pids.yaw, pid(45, 80, 0, Some(120)));
+
+        // INAV: P,I,D,FF
+        let pids = parse("H rollPID:40,30,23,60\nH pitchPID:44,33,25,63\nH yawPID:35,45,0,60\n");
+        assert_eq!(pids.unwrap().pitch, pid(44, 33, 25, Some(63)));
+
+        // EmuFlight: P,I,D only, possibly quoted
+        let pids = parse("H rollPID:\"58,50,35\"\nH pitchPID:\"58,50,35\"\nH yawPID:\"70,45,0\"\n");
+        assert_eq!(pids.unwrap().roll, pid(58, 50, 35, None));
+
+        assert_eq!(parse("H rollPID:45,80,40\nH pitchPID:47,84,46\n"), None);
+    }
+
+    #[test]
+    fn test_sysconfig_values() {
+        let header = crate::parser::header::parse_headers_from_text(
+            "H minthrottle:1070\nH gyro_scale:0x3f000000\nH motorOutput:48,2047\n\
+             H rollPID:\"58,50,35\"\nH Firmware type:Cleanflight\nH P interval:1/2\n",
+            crate::parser::diagnostics::DiagnosticReporter::silent(),
+        )
+        .unwrap();
+        let value = |key: &str| header.sysconfig.get(key).cloned();
+        assert_eq!(value("minthrottle"), Some(SysConfigValue::Int(1070)));
+        assert_eq!(value("gyro_scale"), Some(SysConfigValue::Float(0.5)));
+        assert_eq!(
+            value("motorOutput"),
+            Some(SysConfigValue::IntArray(vec![48, 2047]))
+        );
+        assert_eq!(header.sysconfig_element("rollPID", 2), Some(35));
+        assert_eq!(
+            value("Firmware type"),
+            Some(SysConfigValue::Str("Cleanflight".to_string()))
+        );
+        assert_eq!(value("P interval").unwrap().to_string(), "1/2");
+        assert_eq!(header.sysconfig_int("frameIntervalPDenom"), Some(2));
+        assert_eq!(header.sysconfig_int("motorOutput"), None);
+        assert_eq!(SysConfigValue::Int(7).as_int_array(), Some(&[7][..]));
+        assert_eq!(SysConfigValue::parse("0.75").as_float(), Some(0.75));
+    }
+
+    #[test]
+    fn test_filter_config() {
+        let parse = |text: &str| {
+            crate::parser::header::parse_headers_from_text(
+                text,
+                crate::parser::diagnostics::DiagnosticReporter::silent(),
+            )
+            .unwrap()
+            .filter_config()
+        };
+
+        let filters = parse(
+            "H gyro_lpf1_type:0\nH gyro_lpf1_static_hz:0\nH gyro_lpf1_dyn_hz:250,500\n\
+             H gyro_lpf2_type:0\nH gyro_lpf2_static_hz:500\n\
+             H gyro_notch_hz:0,0\nH gyro_notch_cutoff:0,0\n\
+             H dterm_lpf1_type:0\nH dterm_lpf1_static_hz:75\nH dterm_lpf1_dyn_hz:75,150\n\
+             H dterm_lpf2_type:0\nH dterm_lpf2_static_hz:150\n\
+             H dterm_notch_hz:0\nH dterm_notch_cutoff:0\nH yaw_lowpass_hz:100\n\
+             H dyn_notch_count:3\nH dyn_notch_q:300\nH dyn_notch_min_hz:100\nH dyn_notch_max_hz:600\n\
+             H rpm_filter_harmonics:3\nH rpm_filter_q:500\nH rpm_filter_min_hz:100\n\
+             H rpm_filter_fade_range_hz:50\nH rpm_filter_lpf_hz:150\n",
+        );
+        let gyro_lpf1 = filters.gyro_lpf1.unwrap();
+        assert_eq!(gyro_lpf1.type_name(), Some("PT1"));
+        assert_eq!(
+            (gyro_lpf1.dyn_min_hz, gyro_lpf1.dyn_max_hz),
+            (Some(250), Some(500))
+        );
+        assert!(gyro_lpf1.is_enabled());
+        assert_eq!(filters.gyro_lpf2.unwrap().static_hz, Some(500));
+        assert!(filters.gyro_notches.is_empty());
+        assert_eq!(filters.dterm_notch, None);
+        assert_eq!(filters.yaw_lowpass_hz, Some(100));
+        assert_eq!(filters.dyn_notch.unwrap().count, Some(3));
+        assert_eq!(filters.rpm_filter.unwrap().lpf_hz, Some(150));
+
+        // Betaflight 4.2 and EmuFlight names
+        let filters = parse(
+            "H gyro_lowpass_type:1\nH gyro_lowpass_hz:200\nH gyro_notch_hz:300,0\n\
+             H gyro_notch_cutoff:200,0\nH dterm_filter_type:0\nH dterm_lowpass_hz:100\n\
+             H dterm_lowpass_dyn_hz:70,170\nH dterm_notch_hz:260\nH dterm_notch_cutoff:160\n",
+        );
+        let gyro_lpf1 = filters.gyro_lpf1.unwrap();
+        assert_eq!(gyro_lpf1.type_name(), Some("BIQUAD"));
+        assert_eq!(gyro_lpf1.static_hz, Some(200));
+        assert_eq!(
+            filters.gyro_notches,
+            [NotchFilter {
+                center_hz: 300,
+                cutoff_hz: 200,
+            }]
+        );
+        assert_eq!(filters.dterm_lpf1.unwrap().dyn_max_hz, Some(170));
+        assert_eq!(filters.dterm_notch.unwrap().center_hz, 260);
+        assert_eq!(filters.gyro_lpf2, None);
+        assert_eq!(filters.rpm_filter, None);
+    }
+
+    #[test]
+    fn test_missing_iterations_follow_frame_intervals() {
+        let header = crate::parser::header::parse_headers_from_text(
+            "H Product:Blackbox flight data recorder by Nicholas Sherlock\n\
+             H I interval:8\nH P interval:1/2\n\
+             H Field I name:loopIteration,time\nH Field I signed:0,0\n\
+             H Field I predictor:0,0\nH Field I encoding:1,1\n\
+             H Field P predictor:6,2\nH Field P encoding:9,0\n",
+            crate::parser::diagnostics::DiagnosticReporter::silent(),
+        )
+        .unwrap();
+
+        // Every other iteration is logged; iterations 10 and 12 are lost
+        let iterations: Vec<u32> = (0..=30)
+            .step_by(2)
+            .filter(|i| ![10, 12].contains(i))
+            .collect();
+        let mut data = Vec::new();
+        let mut writer = crate::BBLWriter::new(&mut data, &header).unwrap();
+        for &iteration in &iterations {
+            let values = [iteration as i32, 2000 + iteration as i32 * 100];
+            if iteration % 8 == 0 {
+                writer.write_i_frame(&values).unwrap();
+            } else {
+                writer.write_p_frame(&values).unwrap();
+            }
+        }
+        writer.finish().unwrap();
+
+        let log = parse_bbl_bytes(&data, crate::ExportOptions::default(), false).unwrap();
+        assert_eq!(log.frames.loop_iterations(), iterations);
+        // 0, 8, 16, 24 and 14, written as an I-frame after the gap
+        assert_eq!(log.stats.i_frames, 5);
+        assert_eq!(log.stats.intentionally_absent_iterations, 15);
+        assert_eq!(log.stats.missing_iterations, 2);
+    }
+
+    #[test]
+    fn test_frame_intervals_closed_form() {
+        use crate::parser::frame::FrameIntervals;
+
+        for (i_interval, p_num, p_denom) in
+            [(32, 1, 1), (8, 1, 2), (32, 3, 8), (7, 2, 3), (5, 4, 4)]
+        {
+            let header = crate::parser::header::parse_headers_from_text(
+                &format!("H I interval:{i_interval}\nH P interval:{p_num}/{p_denom}\n"),
+                crate::parser::diagnostics::DiagnosticReporter::silent(),
+            )
+            .unwrap();
+            let intervals = FrameIntervals::from_sysconfig(&header.sysconfig);
+            for from in 0..40u32 {
+                let skipped_after = (1..=i_interval)
+                    .take_while(|&step| !intervals.should_have_frame(from + step))
+                    .count() as u32;
+                assert_eq!(intervals.skipped_after(from), skipped_after, "{from}");
+                for to in from..from + 40 {
+                    let skipped = (from + 1..to)
+                        .filter(|&iteration| !intervals.should_have_frame(iteration))
+                        .count() as u64;
+                    assert_eq!(intervals.skipped_between(from, to), skipped, "{from}..{to}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_hostile_frame_intervals() {
+        use crate::parser::frame::FrameIntervals;
+
+        // Intervals far beyond what firmware stores are clamped to u16 and
+        // counted in closed form, so they cannot stall the parser
+        let header = crate::parser::header::parse_headers_from_text(
+            "H Product:Blackbox flight data recorder by Nicholas Sherlock\n\
+             H I interval:2000000000\nH P interval:1/2000000000\n\
+             H Field I name:loopIteration,time\nH Field I signed:0,0\n\
+             H Field I predictor:0,0\nH Field I encoding:1,1\n",
+            crate::parser::diagnostics::DiagnosticReporter::silent(),
+        )
+        .unwrap();
+        let mut data = Vec::new();
+        let mut writer = crate::BBLWriter::new(&mut data, &header).unwrap();
+        for iteration in [0, 10, 20] {
+            writer
+                .write_i_frame(&[iteration, 2000 + iteration * 100])
+                .unwrap();
+        }
+        writer.finish().unwrap();
+
+        let intervals = FrameIntervals::from_sysconfig(&header.sysconfig);
+        // Only the first iteration of each 65535-iteration I interval is logged
+        assert_eq!(intervals.skipped_after(0), 65534);
+        assert_eq!(intervals.skipped_after(65533), 1);
+        assert_eq!(intervals.skipped_after(u32::MAX), 0);
+        assert_eq!(intervals.skipped_between(0, 10), 9);
+        assert_eq!(intervals.skipped_between(0, u32::MAX), 4_294_901_758);
+
+        let log = parse_bbl_bytes(&data, crate::ExportOptions::default(), false).unwrap();
+        assert_eq!(log.frames.len(), 3);
+
+        // Zero intervals are raised to 1: every iteration is logged
+        let header = crate::parser::header::parse_headers_from_text(
+            "H I interval:0\nH P interval:0/0\n",
+            crate::parser::diagnostics::DiagnosticReporter::silent(),
+        )
+        .unwrap();
+        let intervals = FrameIntervals::from_sysconfig(&header.sysconfig);
+        assert_eq!(intervals.skipped_after(7), 0);
+        assert_eq!(intervals.skipped_between(0, u32::MAX), 0);
+    }
+
+    #[test]
+    fn test_header_boundary() {
+        let mut data = LOG_START_MARKER.to_vec();
+        data.extend_from_slice(
+            b"\nH Field I name:loopIteration,time\nH Field I signed:0,0\n\
+              H Field I predictor:0,0\nH Field I encoding:1,1\n\
+              H Field H name:GPS_home[0],GPS_home[1]\nH Field H signed:1,1\n\
+              H Field H predictor:0,0\nH Field H encoding:0,0\n",
+        );
+        let header_end = data.len();
+
+        // Frame data starting with an H frame, then an I frame holding a newline byte
+        let mut log = data.clone();
+        log.push(b'H');
+        log.extend(signed_vb(100_000_000));
+        log.extend(signed_vb(200_000_000));
+        log.extend_from_slice(&[b'I', 10, 0xD0, 0x0F]);
+        assert_eq!(header_section_end(&log), header_end);
+        let parsed = parse_bbl_bytes(&log, crate::ExportOptions::default(), false).unwrap();
+        assert_eq!(parsed.stats.h_frames, 1);
+        assert_eq!(parsed.frames.loop_iterations(), [10]);
+
+        // Stray bytes before the first frame are skipped
+        let mut log = data.clone();
+        log.extend_from_slice(&[0x00, 0x07, b'I', 1, 0xD0, 0x0F]);
+        assert_eq!(frame_data_start(&log, header_end), header_end + 2);
+        let parsed = parse_bbl_bytes(&log, crate::ExportOptions::default(), false).unwrap();
+        assert_eq!(parsed.stats.failed_frames, 0);
+        assert_eq!(parsed.frames.len(), 1);
+
+        // A header line cut off before its newline is not part of the headers
+        let mut log = data;
+        log.extend_from_slice(b"H Field G name:GPS_numSat");
+        assert_eq!(header_section_end(&log), header_end);
+    }
+
+    #[test]
+    fn test_phantom_log_markers_are_ignored() {
+        let mut log = LOG_START_MARKER.to_vec();
+        log.extend_from_slice(
+            b"\nH Field I name:loopIteration,time\nH Field I signed:0,0\n\
+              H Field I predictor:0,0\nH Field I encoding:1,1\n",
+        );
+        log.extend_from_slice(&[b'I', 1, 0xA0, 0x1F]);
+
+        // Markers inside frame data: one followed by a header line but no field
+        // definitions, and one followed by binary data
+        let mut data = log.clone();
+        data.extend_from_slice(LOG_START_MARKER);
+        data.extend_from_slice(b"\nH Craft name:phantom\n");
+        data.extend_from_slice(&[b'I', 2, 0x88, 0x27]);
+        data.extend_from_slice(LOG_START_MARKER);
+        data.extend_from_slice(&[b'\n', 0x05, b'I', 3, 0xF0, 0x2E]);
+        let second_start = data.len();
+        data.extend_from_slice(&log);
+
+        let ranges = log_byte_ranges(&data);
+        assert_eq!(ranges, [0..second_start, second_start..data.len()]);
+        let logs = parse_bbl_reader(
+            TrickleReader { data: &data },
+            crate::ExportOptions::default(),
+            false,
+        )
+        .unwrap();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].header.craft_name, "");
+        assert_eq!(logs[1].frames.loop_iterations(), [1]);
+
+        // A marker whose headers were cut off is not a log
+        let mut data = log;
+        data.extend_from_slice(LOG_START_MARKER);
+        assert_eq!(log_byte_ranges(&data).len(), 1);
+    }
+
+    #[test]
+    fn test_salvage_log_without_start_marker() {
+        let field_headers: &[u8] = b"H Field I name:loopIteration,time\nH Field I signed:0,0\n\
+              H Field I predictor:0,0\nH Field I encoding:1,1\n";
+        // The first sectors, marker included, were lost mid-line
+        let mut data = vec![0x05, 0x07];
+        data.extend_from_slice(b"ion:2\n");
+        let salvaged_start = data.len();
+        data.extend_from_slice(b"H Craft name:partial\n");
+        data.extend_from_slice(field_headers);
+        data.extend_from_slice(&[b'I', 1, 0xA0, 0x1F, b'I', 2, 0x88, 0x27]);
+        let second_start = data.len();
+        data.extend_from_slice(LOG_START_MARKER);
+        data.push(b'\n');
+        data.extend_from_slice(field_headers);
+        data.extend_from_slice(&[b'I', 9, 0xA0, 0x1F]);
+
+        let logs = parse_bbl_bytes_all_logs(&data, crate::ExportOptions::default(), false).unwrap();
+        assert_eq!(logs.len(), 1);
+
+        let config = ParserConfig {
+            salvage: true,
+            diagnostics: Some(Arc::new(crate::SilentDiagnostics)),
+            ..Default::default()
+        };
+        assert_eq!(
+            log_byte_ranges_with_config(&data, &config),
+            [salvaged_start..second_start, second_start..data.len()]
+        );
+        let logs = parse_bbl_bytes_all_logs_with_config(
+            &data,
+            crate::ExportOptions::default(),
+            false,
+            &config,
+        )
+        .unwrap();
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].header.craft_name, "partial");
+        assert_eq!(logs[0].frames.loop_iterations(), [1, 2]);
+        assert_eq!(logs[1].frames.loop_iterations(), [9]);
+    }
+
+    #[test]
+    fn test_frame_sink_sees_decoded_frames() {
+        let mut data = LOG_START_MARKER.to_vec();
+        data.extend_from_slice(
+            b"\nH Field I name:loopIteration,time\nH Field I signed:0,0\n\
+              H Field I predictor:0,0\nH Field I encoding:1,1\n",
+        );
+        data.extend_from_slice(&[b'I', 1, 0xD0, 0x0F]);
+
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink_seen = seen.clone();
+        let config = ParserConfig {
+            frame_sink: Some(Arc::new(move |frame: &crate::DecodedFrame| {
+                sink_seen
+                    .lock()
+                    .unwrap()
+                    .push((frame.frame_type, frame.timestamp_us));
+            })),
+            ..Default::default()
+        };
+        parse_bbl_bytes_all_logs_with_config(
+            &data,
+            crate::ExportOptions::default(),
+            false,
+            &config,
+        )
+        .unwrap();
+
+        assert_eq!(*seen.lock().unwrap(), vec![('I', 2000)]);
+    }
+
+    #[test]
+    fn test_decimate_and_main_frame_limit() {
+        let mut data = LOG_START_MARKER.to_vec();
+        data.extend_from_slice(
+            b"\nH Field I name:loopIteration,time\nH Field I signed:0,0\n\
+              H Field I predictor:0,0\nH Field I encoding:1,1\n",
+        );
+        // Four I frames at loopIteration 1-4, time 2000-2024
+        for (loop_iteration, time_low) in [(1, 0xD0), (2, 0xD8), (3, 0xE0), (4, 0xE8)] {
+            data.extend_from_slice(&[b'I', loop_iteration, time_low, 0x0F]);
+        }
+        let parse = |config: &ParserConfig| {
+            let logs = parse_bbl_bytes_all_logs_with_config(
+                &data,
+                crate::ExportOptions::default(),
+                false,
+                config,
+            )
+            .unwrap();
+            logs[0].frames.timestamps().to_vec()
+        };
+
+        let decimated = ParserConfig {
+            decimate: 2,
+            ..Default::default()
+        };
+        assert_eq!(parse(&decimated), vec![2000, 2016]);
+
+        let limited = ParserConfig {
+            main_frame_limit: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(parse(&limited), vec![2000, 2008, 2016]);
+
+        let both = ParserConfig {
+            decimate: 3,
+            main_frame_limit: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(parse(&both), vec![2000]);
+    }
+
+    #[test]
+    fn test_frame_sink_without_retained_frames() {
+        let mut data = LOG_START_MARKER.to_vec();
+        data.extend_from_slice(
+            b"\nH Field I name:loopIteration,time\nH Field I signed:0,0\n\
+              H Field I predictor:0,0\nH Field I encoding:1,1\n",
+        );
+        for (loop_iteration, time_low) in [(1, 0xD0), (2, 0xD8), (3, 0xE0), (4, 0xE8)] {
+            data.extend_from_slice(&[b'I', loop_iteration, time_low, 0x0F]);
+        }
+
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink_seen = seen.clone();
+        let config = ParserConfig {
+            frame_sink: Some(Arc::new(move |frame: &crate::DecodedFrame| {
+                sink_seen.lock().unwrap().push(frame.timestamp_us);
+            })),
+            retain_frames: false,
+            main_frame_limit: Some(3),
+            ..Default::default()
+        };
+        let logs = parse_bbl_bytes_all_logs_with_config(
+            &data,
+            crate::ExportOptions::default(),
+            false,
+            &config,
+        )
+        .unwrap();
+
+        // The sink sees every stored frame and the limit still applies
+        assert_eq!(*seen.lock().unwrap(), vec![2000, 2008, 2016]);
+        assert!(logs[0].frames.is_empty());
+        assert_eq!(logs[0].stats.i_frames, 3);
+        assert_eq!(logs[0].stats.start_time_us, 2000);
+        assert_eq!(logs[0].stats.end_time_us, 2016);
+    }
+
+    #[test]
+    fn test_progress_sink_reports_bytes() {
+        let mut data = LOG_START_MARKER.to_vec();
+        data.extend_from_slice(
+            b"\nH Field I name:loopIteration,time\nH Field I signed:0,0\n\
+              H Field I predictor:0,0\nH Field I encoding:1,1\n",
+        );
+        data.extend_from_slice(&[b'I', 1, 0xD0, 0x0F, b'I', 2, 0xD8, 0x0F]);
+
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink_seen = seen.clone();
+        let config = ParserConfig {
+            progress: Some(Arc::new(move |progress: &crate::ParseProgress| {
+                sink_seen.lock().unwrap().push(*progress);
+            })),
+            ..Default::default()
+        };
+        parse_bbl_bytes_all_logs_with_config(
+            &data,
+            crate::ExportOptions::default(),
+            false,
+            &config,
+        )
+        .unwrap();
+
+        // Two small frames stay below the reporting interval: one final report
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![crate::ParseProgress {
+                bytes_consumed: 8,
+                total_bytes: 8,
+                frames: 2,
+            }]
+        );
+    }
+
+    /// Zigzag + variable-byte encoding used by ENCODING_SIGNED_VB
+    fn signed_vb(value: i32) -> Vec<u8> {
+        let mut zigzag = ((value << 1) ^ (value >> 31)) as u32;
+        let mut bytes = Vec::new();
+        while zigzag >= 0x80 {
+            bytes.push((zigzag as u8) | 0x80);
+            zigzag >>= 7;
+        }
+        bytes.push(zigzag as u8);
+        bytes
+    }
+
+    #[test]
+    fn test_gps_uses_latest_home_position() {
+        let mut data = LOG_START_MARKER.to_vec();
+        data.extend_from_slice(
+            b"\nH Field I name:loopIteration,time\nH Field I signed:0,0\n\
+              H Field I predictor:0,0\nH Field I encoding:1,1\n\
+              H Field H name:GPS_home[0],GPS_home[1]\nH Field H signed:1,1\n\
+              H Field H predictor:0,0\nH Field H encoding:0,0\n\
+              H Field G name:GPS_numSat,GPS_coord[0],GPS_coord[1],GPS_altitude\n\
+              H Field G signed:0,1,1,1\nH Field G predictor:0,7,7,0\n\
+              H Field G encoding:1,0,0,0\n",
+        );
+        // I frame at time 2000, then two home updates each followed by a fix
+        // 10/20 units (1e-7 degrees) north-east of home
+        data.extend_from_slice(&[b'I', 1, 0xD0, 0x0F]);
+        for (home_lat, home_lon) in [(100_000_000, 200_000_000), (300_000_000, 400_000_000)] {
+            data.push(b'H');
+            data.extend(signed_vb(home_lat));
+            data.extend(signed_vb(home_lon));
+            data.extend_from_slice(&[b'G', 8]);
+            data.extend(signed_vb(10));
+            data.extend(signed_vb(20));
+            data.extend(signed_vb(50));
+        }
+
+        let export_options = crate::ExportOptions {
+            gpx: true,
+            ..Default::default()
+        };
+        let logs = parse_bbl_bytes_all_logs(&data, export_options, false).unwrap();
+        let gps = &logs[0].gps_coordinates;
+        assert_eq!(logs[0].home_coordinates.len(), 2);
+        assert_eq!(gps.len(), 2);
+        assert!((gps[0].latitude - 10.000_001).abs() < 1e-9);
+        assert!((gps[0].longitude - 20.000_002).abs() < 1e-9);
+        assert!((gps[1].latitude - 30.000_001).abs() < 1e-9);
+        assert!((gps[1].longitude - 40.000_002).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_diagnostics_sink_captures_debug_output() {
+        let collector = Arc::new(crate::CollectingDiagnostics::default());
+        let config = ParserConfig {
+            diagnostics: Some(collector.clone()),
+            ..Default::default()
+        };
+        parse_bbl_bytes_all_logs_with_config(
+            &two_log_data(),
+            crate::ExportOptions::default(),
+            true,
+            &config,
+        )
+        .unwrap();
+
+        let messages = collector.take();
+        assert!(messages
+            .iter()
+            .all(|d| d.level == crate::DiagnosticLevel::Debug));
+        assert!(messages
+            .iter()
+            .any(|d| d.message == "Found 2 log(s) in data"));
+        assert!(messages
+            .iter()
+            .any(|d| d.message.starts_with("Processing header: H Craft name:")));
+    }
+
+    #[test]
+    fn test_parse_bbl_reader_without_marker() {
+        let result = parse_bbl_reader(
+            TrickleReader { data: b"not a log" },
+            crate::ExportOptions::default(),
+            false,
+        );
+        assert!(result.is_err());
+    }
+}
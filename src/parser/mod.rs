@@ -1,17 +1,30 @@
+pub mod config;
 pub mod decoder;
+pub mod diagnostics;
+pub(crate) mod encoder;
 pub mod event;
 pub mod frame;
 pub mod gps;
 pub mod header;
 pub mod helpers;
+pub mod input;
 pub mod main;
+pub mod sink;
 pub mod stream;
+pub mod trim;
+pub mod validate;
 
+pub use config::*;
 pub use decoder::*;
+pub use diagnostics::*;
 pub use event::*;
 pub use frame::*;
 pub use gps::*;
 pub use header::*;
 pub use helpers::*;
+pub use input::*;
 pub use main::*;
+pub use sink::*;
 pub use stream::*;
+pub use trim::*;
+pub use validate::*;
@@ -0,0 +1,59 @@
+//! Per-frame callbacks
+//!
+//! A [`FrameSink`] set in [`crate::ParserConfig::frame_sink`] sees every
+//! decoded frame as soon as it is parsed, so streaming consumers such as
+//! [`crate::NdjsonWriter`] can write output while the log is still being read.
+//! A [`ProgressSink`] set in [`crate::ParserConfig::progress`] is told how far
+//! decoding has got, e.g. to drive a progress bar.
+
+use crate::types::DecodedFrame;
+
+/// Receiver for frames as the parser decodes them
+///
+/// Main (I/P) frames arrive with the latest slow-frame fields merged in, as
+/// stored in `BBLLog::frames`; G, H and E frames carry their own fields.
+/// Implemented for any `Fn(&DecodedFrame) + Send + Sync` closure.
+pub trait FrameSink: Send + Sync {
+    fn frame(&self, frame: &DecodedFrame);
+}
+
+impl<F> FrameSink for F
+where
+    F: Fn(&DecodedFrame) + Send + Sync,
+{
+    fn frame(&self, frame: &DecodedFrame) {
+        self(frame)
+    }
+}
+
+/// How far the decoder has got through one log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseProgress {
+    /// Bytes of the log's binary frame data consumed so far
+    pub bytes_consumed: usize,
+    /// Size of the log's binary frame data
+    pub total_bytes: usize,
+    /// Frames decoded so far
+    pub frames: u32,
+}
+
+/// Receiver for decoding progress
+///
+/// Called every [`PROGRESS_INTERVAL_FRAMES`] frames and once more when the
+/// log's frames are done. Implemented for any `Fn(&ParseProgress) + Send + Sync`
+/// closure.
+pub trait ProgressSink: Send + Sync {
+    fn progress(&self, progress: &ParseProgress);
+}
+
+impl<F> ProgressSink for F
+where
+    F: Fn(&ParseProgress) + Send + Sync,
+{
+    fn progress(&self, progress: &ParseProgress) {
+        self(progress)
+    }
+}
+
+/// Frames decoded between two [`ProgressSink`] calls
+pub const PROGRESS_INTERVAL_FRAMES: u32 = 4096;
@@ -1,3 +1,4 @@
+use crate::error::BBLError;
 use crate::parser::helpers::{
     sign_extend_14bit, sign_extend_16bit, sign_extend_24bit, sign_extend_2bit, sign_extend_4bit,
     sign_extend_6bit, sign_extend_8bit,
@@ -27,6 +28,11 @@ impl<'a> BBLDataStream<'a> {
         self.eof = pos >= self.end;
     }
 
+    /// Bytes not read yet
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.data[self.pos.min(self.end)..self.end]
+    }
+
     pub fn read_byte(&mut self) -> Result<u8> {
         if self.pos < self.end {
             let byte = self.data[self.pos];
@@ -34,7 +40,7 @@ impl<'a> BBLDataStream<'a> {
             Ok(byte)
         } else {
             self.eof = true;
-            Err(anyhow::anyhow!("EOF"))
+            Err(BBLError::UnexpectedEof.into())
         }
     }
 
@@ -0,0 +1,423 @@
+//! BBL trimming helper module
+//!
+//! Re-emits one log of a BBL file as a standalone, valid log holding only the
+//! frames inside a time window.
+
+use crate::error::BBLError;
+use crate::export::TimeRange;
+use crate::parser::config::ParserConfig;
+use crate::parser::diagnostics::DiagnosticReporter;
+use crate::parser::event::parse_e_frame;
+use crate::parser::frame::{
+    parse_frame_data, parse_s_frame, skip_frame, FrameIntervals, LOG_END_EVENT,
+};
+use crate::parser::gps::{extract_home_coord_raw, parse_h_frame};
+use crate::parser::main::{
+    frame_data_start, header_section_end, log_byte_range, parse_header_section,
+};
+use crate::parser::stream::BBLDataStream;
+use crate::types::{BBLHeader, FrameDefinition};
+use crate::writer::BBLWriter;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::io::Write;
+
+/// Write log `log_number` (1-based) of BBL data to `writer`, keeping only the
+/// frames inside `range`.
+///
+/// The result is a standalone single-log BBL file: the text headers are copied
+/// unchanged, the most recent S and H frames before the window are repeated,
+/// and the window opens with a fresh I-frame, so decoders need nothing from the
+/// frames that were cut. Frames inside the window are re-encoded with a
+/// [`BBLWriter`] against the new prediction history, and a log end event closes
+/// the log.
+/// Window offsets count from the log's first main frame, as in
+/// [`crate::ExportOptions::time_range`]. Returns the number of bytes written;
+/// fails with [`BBLError::LogNotFound`] when the data has fewer logs.
+///
+/// # Examples
+/// ```rust,no_run
+/// use bbl_parser::{read_bbl_file, write_trimmed_log, TimeRange};
+/// use std::fs::File;
+/// use std::path::Path;
+///
+/// let data = read_bbl_file(Path::new("flight.BBL")).unwrap();
+/// // 1:05 to 2:30 into the flight
+/// let range = TimeRange {
+///     start_us: Some(65_000_000),
+///     end_us: Some(150_000_000),
+/// };
+/// let mut file = File::create("flight.trimmed.BBL").unwrap();
+/// write_trimmed_log(&data, 1, range, &mut file).unwrap();
+/// ```
+pub fn write_trimmed_log<W: Write + ?Sized>(
+    data: &[u8],
+    log_number: usize,
+    range: TimeRange,
+    writer: &mut W,
+) -> Result<u64> {
+    let (log_range, _) = log_byte_range(data, log_number, &ParserConfig::default())?;
+    let log_data = &data[log_range];
+    let header_end = header_section_end(log_data);
+    let header = parse_header_section(&log_data[..header_end], DiagnosticReporter::silent())?;
+    if header.i_frame_def.count == 0 {
+        return Err(BBLError::InvalidFrame("log has no I-frame field definitions".into()).into());
+    }
+
+    let mut out = BBLWriter::with_header_bytes(writer, &header, &log_data[..header_end])?;
+    let frame_start = frame_data_start(log_data, header_end);
+    trim_frames(&log_data[frame_start..], &header, range, &mut out)?;
+    out.finish()
+}
+
+/// Write the frames of `binary_data` inside `range` to `out`
+fn trim_frames<W: Write>(
+    binary_data: &[u8],
+    header: &BBLHeader,
+    range: TimeRange,
+    out: &mut BBLWriter<'_, W>,
+) -> Result<()> {
+    let diag = DiagnosticReporter::silent();
+    let i_def = &header.i_frame_def;
+    let p_def = &header.p_frame_def;
+    let g_def = &header.g_frame_def;
+    let i_time = i_def.field_names.iter().position(|name| name == "time");
+    let i_loop = i_def
+        .field_names
+        .iter()
+        .position(|name| name == "loopIteration");
+    let main_value = |values: &[i32], position: Option<usize>| {
+        position.and_then(|i| values.get(i)).copied().unwrap_or(0)
+    };
+    let p_to_i: Vec<Option<usize>> = p_def
+        .field_names
+        .iter()
+        .map(|name| i_def.field_names.iter().position(|n| n == name))
+        .collect();
+    // S and H frame values in field order
+    let ordered = |frame_def: &FrameDefinition, data: &HashMap<String, i32>| -> Vec<i32> {
+        frame_def
+            .field_names
+            .iter()
+            .map(|name| data.get(name).copied().unwrap_or(0))
+            .collect()
+    };
+
+    // Prediction history of a decoder reading the original frames
+    let mut current = vec![0i32; i_def.count];
+    let mut previous = vec![0i32; i_def.count];
+    let mut previous2 = vec![0i32; i_def.count];
+    let mut history_valid = false;
+    let mut gps_history = vec![0i32; g_def.count];
+    let mut gps_home: Option<[i32; 2]> = None;
+    let intervals = FrameIntervals::from_sysconfig(&header.sysconfig);
+
+    // Latest S and H frame values before the window, repeated when it opens
+    let mut last_slow: Option<Vec<i32>> = None;
+    let mut last_home: Option<Vec<i32>> = None;
+    // First and last timestamp inside the window, once the first main frame is known
+    let mut window: Option<(u64, u64)> = None;
+    let mut inside = false;
+
+    let mut stream = BBLDataStream::new(binary_data);
+    while !stream.eof {
+        let frame_start = stream.pos;
+        let Ok(frame_type) = stream.read_byte() else {
+            break;
+        };
+
+        match frame_type {
+            b'I' | b'P' => {
+                if frame_type == b'I' {
+                    current.fill(0);
+                    let decoded = parse_frame_data(
+                        &mut stream,
+                        i_def,
+                        &mut current,
+                        None,
+                        None,
+                        None,
+                        0,
+                        false,
+                        header.data_version,
+                        &header.sysconfig,
+                        diag,
+                    );
+                    if decoded.is_err() {
+                        continue;
+                    }
+                    previous.copy_from_slice(&current);
+                    previous2.copy_from_slice(&current);
+                    history_valid = true;
+                } else {
+                    if p_def.count == 0 || !history_valid {
+                        skip_frame(&mut stream, 'P', diag)?;
+                        continue;
+                    }
+                    let mut p_values = vec![0i32; p_def.count];
+                    let skipped_frames =
+                        i_loop.map_or(0, |i| intervals.skipped_after(previous[i] as u32));
+                    let decoded = parse_frame_data(
+                        &mut stream,
+                        p_def,
+                        &mut p_values,
+                        Some(&previous),
+                        Some(&previous2),
+                        None,
+                        skipped_frames,
+                        false,
+                        header.data_version,
+                        &header.sysconfig,
+                        diag,
+                    );
+                    if decoded.is_err() {
+                        continue;
+                    }
+                    current.copy_from_slice(&previous);
+                    for (&i_index, &value) in p_to_i.iter().zip(&p_values) {
+                        if let Some(slot) = i_index.and_then(|i| current.get_mut(i)) {
+                            *slot = value;
+                        }
+                    }
+                    previous2.copy_from_slice(&previous);
+                    previous.copy_from_slice(&current);
+                }
+
+                // Same acceptance test as the decoder; rejected frames never move the window
+                let time = main_value(&current, i_time) as u64;
+                let loop_iteration = main_value(&current, i_loop);
+                if time > 0 && (loop_iteration > 0 || time > 1000) {
+                    let (start_us, end_us) = *window.get_or_insert_with(|| {
+                        (
+                            range.start_us.map_or(0, |start| time.saturating_add(start)),
+                            range
+                                .end_us
+                                .map_or(u64::MAX, |end| time.saturating_add(end)),
+                        )
+                    });
+                    if time > end_us {
+                        return Ok(());
+                    }
+                    if !inside && time >= start_us {
+                        inside = true;
+                        // The window opens with the state a decoder needs
+                        if let Some(slow) = last_slow.take() {
+                            out.write_s_frame(&slow)?;
+                        }
+                        if let Some(home) = last_home.take() {
+                            out.write_h_frame(&home)?;
+                        }
+                        out.write_i_frame(&current)?;
+                        continue;
+                    }
+                }
+                if !inside {
+                    continue;
+                }
+                if frame_type == b'I' {
+                    out.write_i_frame(&current)?;
+                } else {
+                    out.write_p_frame(&current)?;
+                }
+            }
+            b'S' if header.s_frame_def.count > 0 => {
+                let Ok(data) = parse_s_frame(&mut stream, &header.s_frame_def, diag) else {
+                    continue;
+                };
+                let slow = ordered(&header.s_frame_def, &data);
+                if inside {
+                    out.write_s_frame(&slow)?;
+                } else {
+                    last_slow = Some(slow);
+                }
+            }
+            b'H' => {
+                if header.h_frame_def.count == 0 {
+                    skip_frame(&mut stream, 'H', diag)?;
+                    continue;
+                }
+                let Ok(data) = parse_h_frame(&mut stream, &header.h_frame_def, diag) else {
+                    continue;
+                };
+                if let Some(home) = extract_home_coord_raw(&data) {
+                    gps_home = Some(home);
+                }
+                let home = ordered(&header.h_frame_def, &data);
+                if inside {
+                    out.write_h_frame(&home)?;
+                } else {
+                    last_home = Some(home);
+                }
+            }
+            b'G' => {
+                if g_def.count == 0 {
+                    skip_frame(&mut stream, 'G', diag)?;
+                    continue;
+                }
+                let mut g_values = vec![0i32; g_def.count];
+                let decoded = parse_frame_data(
+                    &mut stream,
+                    g_def,
+                    &mut g_values,
+                    Some(&gps_history),
+                    None,
+                    gps_home,
+                    0,
+                    false,
+                    header.data_version,
+                    &header.sysconfig,
+                    diag,
+                );
+                if decoded.is_err() {
+                    continue;
+                }
+                gps_history.copy_from_slice(&g_values);
+                if inside {
+                    out.write_g_frame(&g_values)?;
+                }
+            }
+            b'E' => match parse_e_frame(&mut stream, diag) {
+                // The writer ends the log with its own log end event
+                Ok(event) if event.event_type == LOG_END_EVENT => return Ok(()),
+                Ok(event) => {
+                    if inside {
+                        out.write_event(
+                            event.event_type,
+                            &binary_data[frame_start + 2..stream.pos],
+                        )?;
+                    }
+                }
+                Err(_) => skip_frame(&mut stream, 'E', diag)?,
+            },
+            // Not a frame start (or an S frame without fields); the decoder
+            // resynchronizes on the next byte
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::main::parse_bbl_bytes;
+    use crate::ExportOptions;
+
+    /// One log with I, P and S frames every 1000 us, starting at time 1000
+    fn trim_test_log() -> Vec<u8> {
+        let mut data = b"H Product:Blackbox flight data recorder by Nicholas Sherlock\n\
+            H Field I name:loopIteration,time,gyroADC[0],gyroADC[1]\n\
+            H Field I signed:0,0,1,1\n\
+            H Field I predictor:0,0,0,0\n\
+            H Field I encoding:1,1,0,0\n\
+            H Field P predictor:6,2,3,1\n\
+            H Field P encoding:9,0,6,6\n\
+            H Field S name:flightModeFlags\n\
+            H Field S signed:0\n\
+            H Field S predictor:0\n\
+            H Field S encoding:1\n"
+            .to_vec();
+        let header = parse_header_section(&data, DiagnosticReporter::silent()).unwrap();
+        let header_bytes = std::mem::take(&mut data);
+        let mut writer = BBLWriter::with_header_bytes(&mut data, &header, &header_bytes).unwrap();
+        for frame in 0..10 {
+            if frame == 3 {
+                writer.write_s_frame(&[4]).unwrap();
+            }
+            let values = [frame + 1, (frame + 1) * 1000, frame * frame, -3 * frame];
+            if frame % 4 == 0 {
+                writer.write_i_frame(&values).unwrap();
+            } else {
+                writer.write_p_frame(&values).unwrap();
+            }
+        }
+        writer.finish().unwrap();
+        data
+    }
+
+    #[test]
+    fn test_write_trimmed_log() {
+        let data = trim_test_log();
+        let original = parse_bbl_bytes(&data, ExportOptions::default(), false).unwrap();
+        assert_eq!(original.frames.len(), 10);
+
+        // 2.5 ms to 6 ms after the first frame: frames at times 4000-7000
+        let range = TimeRange {
+            start_us: Some(2_500),
+            end_us: Some(6_000),
+        };
+        let mut trimmed_data = Vec::new();
+        let written = write_trimmed_log(&data, 1, range, &mut trimmed_data).unwrap();
+        assert_eq!(written, trimmed_data.len() as u64);
+        assert!(trimmed_data.ends_with(b"End of log\0"));
+
+        let trimmed = parse_bbl_bytes(&trimmed_data, ExportOptions::default(), false).unwrap();
+        assert_eq!(trimmed.frames.timestamps(), &[4000, 5000, 6000, 7000]);
+        assert_eq!(trimmed.frames.frame_types(), &['I', 'I', 'P', 'P']);
+        for name in [
+            "loopIteration",
+            "gyroADC[0]",
+            "gyroADC[1]",
+            "flightModeFlags",
+        ] {
+            assert_eq!(
+                trimmed.frames.column(name).unwrap(),
+                &original.frames.column(name).unwrap()[3..7],
+                "{name}"
+            );
+        }
+
+        // An unbounded range keeps every frame
+        let mut full = Vec::new();
+        write_trimmed_log(&data, 1, TimeRange::default(), &mut full).unwrap();
+        let full = parse_bbl_bytes(&full, ExportOptions::default(), false).unwrap();
+        assert_eq!(full.frames.timestamps(), original.frames.timestamps());
+        assert_eq!(
+            full.frames.column("gyroADC[0]").unwrap(),
+            original.frames.column("gyroADC[0]").unwrap()
+        );
+
+        let err = write_trimmed_log(&data, 2, range, &mut Vec::new()).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<BBLError>(),
+            Some(BBLError::LogNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_trim_keeps_p_interval_iterations() {
+        let header = crate::parser::header::parse_headers_from_text(
+            "H Product:Blackbox flight data recorder by Nicholas Sherlock\n\
+             H I interval:8\nH P interval:1/2\n\
+             H Field I name:loopIteration,time\nH Field I signed:0,0\n\
+             H Field I predictor:0,0\nH Field I encoding:1,1\n\
+             H Field P predictor:6,2\nH Field P encoding:9,0\n",
+            DiagnosticReporter::silent(),
+        )
+        .unwrap();
+        // Every other iteration is logged
+        let mut data = Vec::new();
+        let mut writer = BBLWriter::new(&mut data, &header).unwrap();
+        for iteration in (2..=32).step_by(2) {
+            let values = [iteration, 1000 + iteration * 125];
+            if iteration % 8 == 2 {
+                writer.write_i_frame(&values).unwrap();
+            } else {
+                writer.write_p_frame(&values).unwrap();
+            }
+        }
+        writer.finish().unwrap();
+        let original = parse_bbl_bytes(&data, ExportOptions::default(), false).unwrap();
+
+        let mut trimmed = Vec::new();
+        write_trimmed_log(&data, 1, TimeRange::default(), &mut trimmed).unwrap();
+        let trimmed = parse_bbl_bytes(&trimmed, ExportOptions::default(), false).unwrap();
+        assert_eq!(
+            trimmed.frames.loop_iterations(),
+            original.frames.loop_iterations()
+        );
+        assert_eq!(trimmed.frames.frame_types(), original.frames.frame_types());
+        assert_eq!(trimmed.stats.missing_iterations, 0);
+    }
+}
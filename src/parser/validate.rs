@@ -0,0 +1,259 @@
+//! Log validation helper module
+//!
+//! Decodes every log of a BBL file without exporting it and reports signs of
+//! corruption: failed frames and where they are in the file, loop iteration
+//! gaps, time running backwards and logs that stop without a log end event.
+
+use crate::error::BBLError;
+use crate::export::typical_loop_step;
+use crate::parser::config::ParserConfig;
+use crate::parser::main::{
+    frame_data_start, header_section_end, log_byte_ranges, parse_single_log,
+};
+use crate::types::{BBLLog, FrameError};
+use anyhow::Result;
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Jump in `loopIteration` larger than the log's usual step between main frames
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LoopGap {
+    /// Time of the first frame after the gap
+    pub time_us: u64,
+    pub from_iteration: u32,
+    pub to_iteration: u32,
+}
+
+/// Main frame whose time is earlier than the time of the frame before it
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimeRegression {
+    pub loop_iteration: u32,
+    pub from_us: u64,
+    pub to_us: u64,
+}
+
+/// Corruption report of one log
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LogValidation {
+    pub log_number: usize,
+    /// Frames that failed to decode
+    pub failed_frames: u32,
+    /// The first failed frames (see [`crate::MAX_FRAME_ERRORS`]), with offsets
+    /// from the start of the file
+    pub frame_errors: Vec<FrameError>,
+    pub loop_gaps: Vec<LoopGap>,
+    pub time_regressions: Vec<TimeRegression>,
+    /// The log stops without a log end event, or decoding hit a safety limit
+    pub truncated: bool,
+    /// Why the log could not be decoded at all
+    pub error: Option<String>,
+}
+
+impl LogValidation {
+    /// Whether no problem was found
+    pub fn is_clean(&self) -> bool {
+        self.failed_frames == 0
+            && self.loop_gaps.is_empty()
+            && self.time_regressions.is_empty()
+            && !self.truncated
+            && self.error.is_none()
+    }
+}
+
+/// Validate every log of a BBL file (see [`validate_bbl_bytes`])
+pub fn validate_bbl_file(file_path: &Path) -> Result<Vec<LogValidation>> {
+    let data = crate::parser::input::read_bbl_file(file_path)?;
+    validate_bbl_bytes(&data)
+}
+
+/// Decode every log of BBL data held in memory and report signs of corruption.
+///
+/// Logs are decoded leniently, so one damaged log does not hide the others; a
+/// log that cannot be decoded at all is reported with [`LogValidation::error`].
+/// Fails with [`BBLError::NoLogsFound`] when the data holds no log.
+///
+/// # Examples
+/// ```rust,no_run
+/// use bbl_parser::validate_bbl_file;
+/// use std::path::Path;
+///
+/// for report in validate_bbl_file(Path::new("flight.BBL")).unwrap() {
+///     if !report.is_clean() {
+///         println!("log {}: {} failed frames", report.log_number, report.failed_frames);
+///     }
+/// }
+/// ```
+pub fn validate_bbl_bytes(data: &[u8]) -> Result<Vec<LogValidation>> {
+    let ranges = log_byte_ranges(data);
+    if ranges.is_empty() {
+        return Err(BBLError::NoLogsFound.into());
+    }
+
+    let export_options = Default::default();
+    let config = ParserConfig::default();
+    let total_logs = ranges.len();
+    Ok(ranges
+        .into_iter()
+        .enumerate()
+        .map(|(log_index, range)| {
+            let log_data = &data[range.clone()];
+            let frame_data_start =
+                (range.start + frame_data_start(log_data, header_section_end(log_data))) as u64;
+            match parse_single_log(
+                log_data,
+                log_index + 1,
+                total_logs,
+                false,
+                &export_options,
+                &config,
+            ) {
+                Ok(log) => validate_log(&log, frame_data_start),
+                Err(e) => LogValidation {
+                    log_number: log_index + 1,
+                    error: Some(format!("{e:#}")),
+                    ..Default::default()
+                },
+            }
+        })
+        .collect())
+}
+
+/// Report of a decoded log whose frame data starts at file offset `frame_data_start`
+fn validate_log(log: &BBLLog, frame_data_start: u64) -> LogValidation {
+    let frames = &log.frames;
+    let timestamps = frames.timestamps();
+    let loop_iterations = frames.loop_iterations();
+
+    let rows: Vec<usize> = (0..frames.len()).collect();
+    let loop_step = frames
+        .field_index("loopIteration")
+        .and_then(|column| typical_loop_step(frames, &rows, column));
+    let mut loop_gaps = Vec::new();
+    let mut time_regressions = Vec::new();
+    for pair in rows.windows(2) {
+        let (before, after) = (pair[0], pair[1]);
+        let (from_iteration, to_iteration) = (loop_iterations[before], loop_iterations[after]);
+        if loop_step.is_some_and(|step| to_iteration > from_iteration.saturating_add(step)) {
+            loop_gaps.push(LoopGap {
+                time_us: timestamps[after],
+                from_iteration,
+                to_iteration,
+            });
+        }
+        if timestamps[after] < timestamps[before] {
+            time_regressions.push(TimeRegression {
+                loop_iteration: to_iteration,
+                from_us: timestamps[before],
+                to_us: timestamps[after],
+            });
+        }
+    }
+
+    LogValidation {
+        log_number: log.log_number,
+        failed_frames: log.stats.failed_frames,
+        frame_errors: log
+            .stats
+            .frame_errors
+            .iter()
+            .map(|error| FrameError {
+                offset: frame_data_start + error.offset,
+                ..error.clone()
+            })
+            .collect(),
+        loop_gaps,
+        time_regressions,
+        truncated: !log.stats.ended_cleanly || log.stats.truncated,
+        error: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{BBLHeader, FrameDefinition, FrameErrorReason};
+    use crate::writer::BBLWriter;
+
+    #[test]
+    fn test_validate_bbl_bytes() {
+        let mut i_frame_def =
+            FrameDefinition::from_field_names(vec!["loopIteration".into(), "time".into()]);
+        i_frame_def.update_encoding(&[1, 1]);
+        let mut p_frame_def = i_frame_def.clone();
+        p_frame_def.update_predictors(&[6, 2]);
+        p_frame_def.update_encoding(&[9, 0]);
+        let header = BBLHeader {
+            i_frame_def,
+            p_frame_def,
+            ..Default::default()
+        };
+
+        // A clean log, then one with a gap, a time regression and no log end
+        let mut data = Vec::new();
+        let mut writer = BBLWriter::new(&mut data, &header).unwrap();
+        writer.write_i_frame(&[1, 1000]).unwrap();
+        for iteration in 2..6 {
+            writer
+                .write_p_frame(&[iteration, iteration * 1000])
+                .unwrap();
+        }
+        writer.finish().unwrap();
+        let mut writer = BBLWriter::new(&mut data, &header).unwrap();
+        for (iteration, time) in [(1, 1000), (2, 2000), (3, 3000), (9, 9000), (10, 8500)] {
+            if iteration == 1 {
+                writer.write_i_frame(&[iteration, time]).unwrap();
+            } else {
+                writer.write_p_frame(&[iteration, time]).unwrap();
+            }
+        }
+        drop(writer);
+        let stray_byte = data.len() as u64;
+        data.push(0x01);
+
+        let reports = validate_bbl_bytes(&data).unwrap();
+        assert_eq!(reports.len(), 2);
+        assert_eq!(reports[0].log_number, 1);
+        assert!(reports[0].is_clean(), "{:?}", reports[0]);
+
+        let report = &reports[1];
+        assert!(!report.is_clean());
+        assert!(report.truncated);
+        assert_eq!(report.failed_frames, 1);
+        assert_eq!(
+            report.frame_errors,
+            [FrameError {
+                offset: stray_byte,
+                frame_type: '\u{1}',
+                reason: FrameErrorReason::UnknownFrameType,
+            }]
+        );
+        assert_eq!(
+            report.loop_gaps,
+            [LoopGap {
+                time_us: 9000,
+                from_iteration: 3,
+                to_iteration: 9,
+            }]
+        );
+        assert_eq!(
+            report.time_regressions,
+            [TimeRegression {
+                loop_iteration: 10,
+                from_us: 9000,
+                to_us: 8500,
+            }]
+        );
+
+        assert!(matches!(
+            validate_bbl_bytes(b"no logs here")
+                .unwrap_err()
+                .downcast_ref::<BBLError>(),
+            Some(BBLError::NoLogsFound)
+        ));
+    }
+}
@@ -0,0 +1,537 @@
+use crate::types::DecodedFrame;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Interned field names: each name is stored once and referred to by its index
+///
+/// A log's table is built from its headers and shared (via `Arc`) between
+/// [`crate::types::BBLHeader::main_fields`] and the log's [`FrameColumns`], so
+/// decoding never allocates field-name strings per frame.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FieldTable {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+}
+
+impl FieldTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a table from names in order; repeated names keep their first index
+    pub fn from_names<I, S>(names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut table = Self::new();
+        for name in names {
+            table.insert(name);
+        }
+        table
+    }
+
+    /// Number of distinct fields
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Field names in index order
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Index of a field, if it exists
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.index.get(name).copied()
+    }
+
+    /// Name of the field at `index`
+    pub fn name(&self, index: usize) -> Option<&str> {
+        self.names.get(index).map(String::as_str)
+    }
+
+    /// Intern a name, returning its index (existing names keep their index)
+    pub fn insert<S: Into<String>>(&mut self, name: S) -> usize {
+        let name = name.into();
+        if let Some(&index) = self.index.get(&name) {
+            return index;
+        }
+        let index = self.names.len();
+        self.index.insert(name.clone(), index);
+        self.names.push(name);
+        index
+    }
+}
+
+/// Columnar storage for decoded main (I/P) frames
+///
+/// Each field is stored as one `Vec<i32>` indexed by row, with the field name
+/// resolved once through a shared [`FieldTable`] instead of a per-frame `HashMap`.
+/// A per-column presence bitmap records which rows actually carried the field
+/// (slow-frame fields, for example, are absent until the first S frame).
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FrameColumns {
+    fields: Arc<FieldTable>,
+    frame_types: Vec<char>,
+    timestamps: Vec<u64>,
+    loop_iterations: Vec<u32>,
+    columns: Vec<Vec<i32>>,
+    /// One bit per row for each column; set when the row has a value for the field
+    present: Vec<Vec<u64>>,
+    /// Rows sorted by timestamp; stale unless it holds every row
+    #[cfg_attr(feature = "serde", serde(skip))]
+    time_index: Vec<usize>,
+}
+
+impl FrameColumns {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty store with the given fields pre-registered in order
+    pub fn with_fields<I, S>(field_names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::with_table(Arc::new(FieldTable::from_names(field_names)))
+    }
+
+    /// Create an empty store with one column per field of a shared table
+    pub fn with_table(fields: Arc<FieldTable>) -> Self {
+        let field_count = fields.len();
+        Self {
+            fields,
+            columns: vec![Vec::new(); field_count],
+            present: vec![Vec::new(); field_count],
+            ..Self::default()
+        }
+    }
+
+    /// Number of stored frames
+    pub fn len(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timestamps.is_empty()
+    }
+
+    /// Field names in column order
+    pub fn field_names(&self) -> &[String] {
+        self.fields.names()
+    }
+
+    /// Field table mapping names to column indices
+    pub fn fields(&self) -> &Arc<FieldTable> {
+        &self.fields
+    }
+
+    /// Column index of a field, if it exists
+    pub fn field_index(&self, name: &str) -> Option<usize> {
+        self.fields.index_of(name)
+    }
+
+    /// Register a field, returning its column index (existing fields keep their index)
+    ///
+    /// Adding a new field to a store whose table is shared copies the table first.
+    pub fn add_field<S: Into<String>>(&mut self, name: S) -> usize {
+        let name = name.into();
+        if let Some(index) = self.fields.index_of(&name) {
+            return index;
+        }
+        let index = Arc::make_mut(&mut self.fields).insert(name);
+        self.columns.push(vec![0; self.len()]);
+        self.present.push(vec![0; (self.len() + 63) / 64]);
+        index
+    }
+
+    /// Frame type of every row ('I' or 'P')
+    pub fn frame_types(&self) -> &[char] {
+        &self.frame_types
+    }
+
+    /// Timestamp of every row in microseconds
+    pub fn timestamps(&self) -> &[u64] {
+        &self.timestamps
+    }
+
+    /// Loop iteration of every row
+    pub fn loop_iterations(&self) -> &[u32] {
+        &self.loop_iterations
+    }
+
+    /// Raw values of one field for every row; rows without the field read as 0
+    pub fn column(&self, name: &str) -> Option<&[i32]> {
+        self.field_index(name)
+            .map(|index| self.columns[index].as_slice())
+    }
+
+    /// Raw values of the field at `field` for every row; rows without the field read as 0
+    pub fn column_at(&self, field: usize) -> Option<&[i32]> {
+        self.columns.get(field).map(Vec::as_slice)
+    }
+
+    /// Value of the field at column index `field` in `row`, if present
+    pub fn value(&self, row: usize, field: usize) -> Option<i32> {
+        if row >= self.len() || !self.is_present(row, field) {
+            return None;
+        }
+        Some(self.columns[field][row])
+    }
+
+    /// Value of the named field in `row`, if present
+    pub fn get(&self, row: usize, name: &str) -> Option<i32> {
+        self.value(row, self.field_index(name)?)
+    }
+
+    /// Whether `row` carries a value for the field at column index `field`
+    pub fn is_present(&self, row: usize, field: usize) -> bool {
+        self.present
+            .get(field)
+            .and_then(|bits| bits.get(row / 64))
+            .map(|word| word & (1 << (row % 64)) != 0)
+            .unwrap_or(false)
+    }
+
+    /// Append a frame, registering any fields not seen before
+    pub fn push(&mut self, frame: DecodedFrame) {
+        let row = self.start_row(frame.frame_type, frame.timestamp_us, frame.loop_iteration);
+        for (name, value) in frame.data {
+            let field = self.add_field(name);
+            self.set_value(row, field, value);
+        }
+    }
+
+    /// Append a row from `(column index, value)` pairs and return its index
+    ///
+    /// Columns not listed are absent in the new row.
+    pub fn push_values<I>(
+        &mut self,
+        frame_type: char,
+        timestamp_us: u64,
+        loop_iteration: u32,
+        values: I,
+    ) -> usize
+    where
+        I: IntoIterator<Item = (usize, i32)>,
+    {
+        let row = self.start_row(frame_type, timestamp_us, loop_iteration);
+        for (field, value) in values {
+            self.set_value(row, field, value);
+        }
+        row
+    }
+
+    /// Remove every row, keeping the fields
+    pub fn clear(&mut self) {
+        self.frame_types.clear();
+        self.timestamps.clear();
+        self.loop_iterations.clear();
+        for column in &mut self.columns {
+            column.clear();
+        }
+        for bits in &mut self.present {
+            bits.clear();
+        }
+        self.time_index.clear();
+    }
+
+    /// Append an empty row and return its index
+    fn start_row(&mut self, frame_type: char, timestamp_us: u64, loop_iteration: u32) -> usize {
+        let row = self.len();
+        self.frame_types.push(frame_type);
+        self.timestamps.push(timestamp_us);
+        self.loop_iterations.push(loop_iteration);
+        for column in &mut self.columns {
+            column.push(0);
+        }
+        if row % 64 == 0 {
+            for bits in &mut self.present {
+                bits.push(0);
+            }
+        }
+        row
+    }
+
+    /// Set the value of the field at column index `field` in `row`
+    pub fn set_value(&mut self, row: usize, field: usize, value: i32) {
+        self.columns[field][row] = value;
+        self.present[field][row / 64] |= 1 << (row % 64);
+    }
+
+    /// Overwrite the timestamp of `row`
+    pub fn set_timestamp(&mut self, row: usize, timestamp_us: u64) {
+        self.timestamps[row] = timestamp_us;
+        self.time_index.clear();
+    }
+
+    /// Sort the rows by timestamp for [`FrameColumns::row_at_time`] and
+    /// [`FrameColumns::rows_in_range`]
+    ///
+    /// Parsing builds the index; appending frames or changing timestamps makes
+    /// it stale, and lookups then sort the rows on every call until it is rebuilt.
+    pub fn build_time_index(&mut self) {
+        self.time_index = self.sorted_rows();
+    }
+
+    /// Rows in timestamp order, keeping decode order for equal timestamps
+    fn sorted_rows(&self) -> Vec<usize> {
+        let mut rows: Vec<usize> = (0..self.len()).collect();
+        rows.sort_by_key(|&row| self.timestamps[row]);
+        rows
+    }
+
+    fn time_index(&self) -> Cow<'_, [usize]> {
+        if self.time_index.len() == self.len() {
+            Cow::Borrowed(&self.time_index)
+        } else {
+            Cow::Owned(self.sorted_rows())
+        }
+    }
+
+    /// Row shown at `time_us`: the latest row with a timestamp at or before it
+    ///
+    /// Returns `None` when `time_us` is before the first frame.
+    pub fn row_at_time(&self, time_us: u64) -> Option<usize> {
+        let index = self.time_index();
+        let end = index.partition_point(|&row| self.timestamps[row] <= time_us);
+        end.checked_sub(1).map(|position| index[position])
+    }
+
+    /// Rows with timestamps from `start_us` to `end_us` (both inclusive), in timestamp order
+    pub fn rows_in_range(&self, start_us: u64, end_us: u64) -> Vec<usize> {
+        let index = self.time_index();
+        let start = index.partition_point(|&row| self.timestamps[row] < start_us);
+        let end = index.partition_point(|&row| self.timestamps[row] <= end_us);
+        index[start..end.max(start)].to_vec()
+    }
+
+    /// Materialize one row as a [`DecodedFrame`]
+    pub fn row(&self, row: usize) -> Option<DecodedFrame> {
+        if row >= self.len() {
+            return None;
+        }
+        let data = self
+            .field_names()
+            .iter()
+            .enumerate()
+            .filter_map(|(field, name)| self.value(row, field).map(|v| (name.clone(), v)))
+            .collect();
+        Some(DecodedFrame {
+            frame_type: self.frame_types[row],
+            timestamp_us: self.timestamps[row],
+            loop_iteration: self.loop_iterations[row],
+            data,
+        })
+    }
+
+    pub fn first(&self) -> Option<DecodedFrame> {
+        self.row(0)
+    }
+
+    pub fn last(&self) -> Option<DecodedFrame> {
+        self.row(self.len().checked_sub(1)?)
+    }
+
+    /// Iterate over rows, materializing each as a [`DecodedFrame`]
+    ///
+    /// Prefer [`FrameColumns::column`] for bulk numeric access.
+    pub fn iter(&self) -> FrameColumnsIter<'_> {
+        FrameColumnsIter {
+            columns: self,
+            row: 0,
+        }
+    }
+}
+
+impl FromIterator<DecodedFrame> for FrameColumns {
+    fn from_iter<T: IntoIterator<Item = DecodedFrame>>(iter: T) -> Self {
+        let mut columns = Self::new();
+        columns.extend(iter);
+        columns
+    }
+}
+
+impl Extend<DecodedFrame> for FrameColumns {
+    fn extend<T: IntoIterator<Item = DecodedFrame>>(&mut self, iter: T) {
+        for frame in iter {
+            self.push(frame);
+        }
+    }
+}
+
+/// Iterator over [`FrameColumns`] rows as owned [`DecodedFrame`] values
+pub struct FrameColumnsIter<'a> {
+    columns: &'a FrameColumns,
+    row: usize,
+}
+
+impl Iterator for FrameColumnsIter<'_> {
+    type Item = DecodedFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame = self.columns.row(self.row)?;
+        self.row += 1;
+        Some(frame)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.columns.len().saturating_sub(self.row);
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for FrameColumnsIter<'_> {}
+
+impl<'a> IntoIterator for &'a FrameColumns {
+    type Item = DecodedFrame;
+    type IntoIter = FrameColumnsIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(frame_type: char, timestamp_us: u64, fields: &[(&str, i32)]) -> DecodedFrame {
+        DecodedFrame {
+            frame_type,
+            timestamp_us,
+            loop_iteration: 0,
+            data: fields
+                .iter()
+                .map(|&(name, value)| (name.to_string(), value))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_push_and_lookup() {
+        let mut columns = FrameColumns::with_fields(["time", "gyroADC[0]"]);
+        columns.push(frame('I', 100, &[("time", 100), ("gyroADC[0]", -5)]));
+        columns.push(frame(
+            'P',
+            200,
+            &[("time", 200), ("gyroADC[0]", 7), ("flightModeFlags", 1)],
+        ));
+
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns.column("gyroADC[0]"), Some(&[-5, 7][..]));
+        assert_eq!(columns.get(1, "flightModeFlags"), Some(1));
+        // Field added later is absent for earlier rows
+        assert_eq!(columns.get(0, "flightModeFlags"), None);
+        assert_eq!(columns.frame_types(), &['I', 'P']);
+        assert_eq!(columns.get(2, "time"), None);
+    }
+
+    #[test]
+    fn test_row_roundtrip() {
+        let original = frame('P', 250, &[("time", 250), ("motor[0]", 1200)]);
+        let columns: FrameColumns = vec![original.clone()].into_iter().collect();
+
+        let row = columns.row(0).unwrap();
+        assert_eq!(row.frame_type, original.frame_type);
+        assert_eq!(row.timestamp_us, original.timestamp_us);
+        assert_eq!(row.data, original.data);
+        assert_eq!(columns.iter().count(), 1);
+    }
+
+    #[test]
+    fn test_shared_field_table() {
+        let table = Arc::new(FieldTable::from_names(["time", "motor[0]", "time"]));
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.index_of("motor[0]"), Some(1));
+
+        let mut columns = FrameColumns::with_table(Arc::clone(&table));
+        assert!(Arc::ptr_eq(columns.fields(), &table));
+        let row = columns.push_values('I', 100, 3, [(0, 100), (1, 1500)]);
+        assert_eq!(columns.get(row, "motor[0]"), Some(1500));
+        assert_eq!(columns.loop_iterations(), &[3]);
+
+        // A new field copies the shared table instead of mutating it
+        columns.add_field("debug[0]");
+        assert_eq!(table.len(), 2);
+        assert_eq!(columns.field_names().len(), 3);
+        assert_eq!(columns.get(row, "debug[0]"), None);
+    }
+
+    #[test]
+    fn test_presence_across_word_boundary() {
+        let mut columns = FrameColumns::with_fields(["a"]);
+        for i in 0..130 {
+            if i % 2 == 0 {
+                columns.push(frame('P', i as u64, &[("a", i)]));
+            } else {
+                columns.push(frame('P', i as u64, &[]));
+            }
+        }
+        assert_eq!(columns.get(64, "a"), Some(64));
+        assert_eq!(columns.get(65, "a"), None);
+        assert_eq!(columns.get(128, "a"), Some(128));
+    }
+
+    #[test]
+    fn test_decoded_frame_accessors() {
+        let frame = frame(
+            'P',
+            0,
+            &[
+                ("gyroADC[0]", 10),
+                ("gyroADC[1]", -20),
+                ("gyroADC[2]", 30),
+                ("rcCommand[0]", 1),
+                ("motor[1]", 1500),
+                ("vbatLatest", 1680),
+            ],
+        );
+        assert_eq!(frame.gyro(), Some([10, -20, 30]));
+        assert_eq!(frame.rc_command(), None);
+        assert_eq!(frame.motor(1), Some(1500));
+        assert_eq!(frame.motor(0), None);
+
+        let header = crate::types::BBLHeader {
+            firmware_revision: "Betaflight 4.5.1 (77d01ba3b) STM32F7X2".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(frame.vbat_volts(&header), Some(16.8));
+        assert_eq!(frame.amperage_amps(&header), None);
+    }
+
+    #[test]
+    fn test_time_index() {
+        let mut columns = FrameColumns::new();
+        for time in [100, 200, 150, 300] {
+            columns.push(frame('P', time, &[("time", time as i32)]));
+        }
+        columns.build_time_index();
+
+        assert_eq!(columns.row_at_time(50), None);
+        assert_eq!(columns.row_at_time(100), Some(0));
+        assert_eq!(columns.row_at_time(199), Some(2));
+        assert_eq!(columns.row_at_time(1000), Some(3));
+        assert_eq!(columns.rows_in_range(120, 300), [2, 1, 3]);
+        assert_eq!(columns.rows_in_range(301, 400), [] as [usize; 0]);
+        assert_eq!(columns.rows_in_range(300, 100), [] as [usize; 0]);
+
+        // A stale index is not used
+        columns.set_timestamp(3, 120);
+        columns.push(frame('P', 50, &[]));
+        assert_eq!(columns.row_at_time(50), Some(4));
+        assert_eq!(columns.rows_in_range(100, 150), [0, 3, 2]);
+    }
+}
@@ -0,0 +1,183 @@
+//! Field metadata: units and groups of well-known blackbox fields
+
+use crate::types::FrameDefinition;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// What a field measures, for grouping fields in UIs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[non_exhaustive]
+pub enum FieldGroup {
+    /// `loopIteration`, `time`
+    Loop,
+    /// PID controller terms (`axisP`, `axisI`, `axisD`, `axisF`)
+    Pid,
+    /// RC commands and receiver state (`rcCommand`, `rssi`, ...)
+    Rc,
+    Setpoint,
+    Gyro,
+    Accelerometer,
+    Magnetometer,
+    Barometer,
+    /// Motor outputs, motor eRPM and servos
+    Motor,
+    /// Battery voltage and current
+    Battery,
+    Debug,
+    Gps,
+    /// Flight mode, state and failsafe flags
+    Flags,
+    Other,
+}
+
+/// Metadata of one field of a frame definition
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct FieldInfo {
+    /// Unit of the raw logged value (see [`field_unit`]), if known
+    pub unit: Option<&'static str>,
+    pub signed: bool,
+    pub predictor: u8,
+    pub encoding: u8,
+    pub group: FieldGroup,
+}
+
+/// Units of array elements that differ from the rest of the array
+const ELEMENT_UNITS: &[(&str, &str)] = &[("rcCommand[3]", "us"), ("setpoint[3]", "0.1 %")];
+
+/// Group and raw unit of well-known fields, by name without the `[n]` index.
+///
+/// Units are those of Betaflight 4 logs; older firmware and other firmware
+/// families may scale some fields differently (see [`crate::conversion`]).
+const FIELD_TABLE: &[(&str, FieldGroup, Option<&str>)] = &[
+    ("loopIteration", FieldGroup::Loop, None),
+    ("time", FieldGroup::Loop, Some("us")),
+    ("axisP", FieldGroup::Pid, None),
+    ("axisI", FieldGroup::Pid, None),
+    ("axisD", FieldGroup::Pid, None),
+    ("axisF", FieldGroup::Pid, None),
+    ("axisS", FieldGroup::Pid, None),
+    ("rcCommand", FieldGroup::Rc, None),
+    ("rcCommands", FieldGroup::Rc, None),
+    ("rssi", FieldGroup::Rc, Some("raw (0-1023)")),
+    ("rxSignalReceived", FieldGroup::Rc, None),
+    ("rxFlightChannelsValid", FieldGroup::Rc, None),
+    ("setpoint", FieldGroup::Setpoint, Some("deg/s")),
+    ("gyroADC", FieldGroup::Gyro, Some("raw gyro counts")),
+    ("gyroUnfilt", FieldGroup::Gyro, Some("raw gyro counts")),
+    (
+        "accSmooth",
+        FieldGroup::Accelerometer,
+        Some("raw acc counts"),
+    ),
+    ("magADC", FieldGroup::Magnetometer, Some("raw mag counts")),
+    ("baroAlt", FieldGroup::Barometer, Some("cm")),
+    ("motor", FieldGroup::Motor, None),
+    ("eRPM", FieldGroup::Motor, Some("100 eRPM")),
+    ("servo", FieldGroup::Motor, Some("us")),
+    ("vbatLatest", FieldGroup::Battery, Some("0.01 V")),
+    ("amperageLatest", FieldGroup::Battery, Some("0.01 A")),
+    ("debug", FieldGroup::Debug, None),
+    ("GPS_numSat", FieldGroup::Gps, None),
+    ("GPS_coord", FieldGroup::Gps, Some("1e-7 deg")),
+    ("GPS_home", FieldGroup::Gps, Some("1e-7 deg")),
+    ("GPS_altitude", FieldGroup::Gps, Some("dm")),
+    ("GPS_speed", FieldGroup::Gps, Some("cm/s")),
+    ("GPS_ground_course", FieldGroup::Gps, Some("0.1 deg")),
+    ("flightModeFlags", FieldGroup::Flags, None),
+    ("stateFlags", FieldGroup::Flags, None),
+    ("failsafePhase", FieldGroup::Flags, None),
+];
+
+/// Table entry of a field name such as `gyroADC[0]`
+fn table_entry(name: &str) -> Option<&'static (&'static str, FieldGroup, Option<&'static str>)> {
+    let base = name.split_once('[').map_or(name, |(base, _)| base);
+    FIELD_TABLE.iter().find(|(field, _, _)| *field == base)
+}
+
+/// Unit of the raw logged value of a well-known field, e.g. `0.01 V` for
+/// `vbatLatest` or `raw gyro counts` for `gyroADC[0]`
+pub fn field_unit(name: &str) -> Option<&'static str> {
+    ELEMENT_UNITS
+        .iter()
+        .find(|(field, _)| *field == name)
+        .map(|&(_, unit)| unit)
+        .or_else(|| table_entry(name)?.2)
+}
+
+impl FieldGroup {
+    /// Group of a field name; [`FieldGroup::Other`] for unknown fields
+    pub fn of(name: &str) -> Self {
+        table_entry(name).map_or(FieldGroup::Other, |&(_, group, _)| group)
+    }
+}
+
+impl FrameDefinition {
+    /// Unit, signedness, predictor, encoding and group of the named field,
+    /// or `None` when the frame has no such field
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bbl_parser::{FieldGroup, FrameDefinition};
+    ///
+    /// let frame_def = FrameDefinition::from_field_names(vec!["vbatLatest".into()]);
+    /// let info = frame_def.field_info("vbatLatest").unwrap();
+    /// assert_eq!(info.unit, Some("0.01 V"));
+    /// assert_eq!(info.group, FieldGroup::Battery);
+    /// ```
+    pub fn field_info(&self, name: &str) -> Option<FieldInfo> {
+        let field = self.fields.iter().find(|field| field.name == name)?;
+        Some(FieldInfo {
+            unit: field_unit(name),
+            signed: field.signed,
+            predictor: field.predictor,
+            encoding: field.encoding,
+            group: FieldGroup::of(name),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_info() {
+        let mut frame_def = FrameDefinition::from_field_names(vec![
+            "time".into(),
+            "rcCommand[0]".into(),
+            "rcCommand[3]".into(),
+            "gyroADC[1]".into(),
+            "customField".into(),
+        ]);
+        frame_def.update_signed(&[false, true, false, true, false]);
+        frame_def.update_predictors(&[2, 0, 4, 1, 0]);
+        frame_def.update_encoding(&[1, 0, 1, 0, 1]);
+
+        assert_eq!(
+            frame_def.field_info("gyroADC[1]"),
+            Some(FieldInfo {
+                unit: Some("raw gyro counts"),
+                signed: true,
+                predictor: 1,
+                encoding: 0,
+                group: FieldGroup::Gyro,
+            })
+        );
+        assert_eq!(frame_def.field_info("time").unwrap().unit, Some("us"));
+        assert_eq!(frame_def.field_info("rcCommand[0]").unwrap().unit, None);
+        let throttle = frame_def.field_info("rcCommand[3]").unwrap();
+        assert_eq!(
+            (throttle.unit, throttle.group),
+            (Some("us"), FieldGroup::Rc)
+        );
+        let custom = frame_def.field_info("customField").unwrap();
+        assert_eq!((custom.unit, custom.group), (None, FieldGroup::Other));
+        assert_eq!(frame_def.field_info("vbatLatest"), None);
+
+        assert_eq!(field_unit("amperageLatest"), Some("0.01 A"));
+        assert_eq!(FieldGroup::of("GPS_coord[0]"), FieldGroup::Gps);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::conversion::{convert_amperage_with_meter, convert_vbat_to_volts};
+use crate::types::BBLHeader;
 use std::collections::HashMap;
 
 #[cfg(feature = "serde")]
@@ -90,8 +92,62 @@ pub struct DecodedFrame {
     pub data: HashMap<String, i32>,
 }
 
+impl DecodedFrame {
+    /// Value of the named field, if the frame has it
+    pub fn value(&self, name: &str) -> Option<i32> {
+        self.data.get(name).copied()
+    }
+
+    /// Values of `name[0]` to `name[N - 1]`, if the frame has all of them
+    fn array<const N: usize>(&self, name: &str) -> Option<[i32; N]> {
+        let mut values = [0; N];
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = self.value(&format!("{name}[{i}]"))?;
+        }
+        Some(values)
+    }
+
+    /// Raw roll, pitch and yaw gyro values (`gyroADC[0..3]`)
+    pub fn gyro(&self) -> Option<[i32; 3]> {
+        self.array("gyroADC")
+    }
+
+    /// Raw X, Y and Z accelerometer values (`accSmooth[0..3]`)
+    pub fn acc(&self) -> Option<[i32; 3]> {
+        self.array("accSmooth")
+    }
+
+    /// Roll, pitch, yaw and throttle RC commands (`rcCommand[0..4]`)
+    pub fn rc_command(&self) -> Option<[i32; 4]> {
+        self.array("rcCommand")
+    }
+
+    /// Roll, pitch, yaw and throttle setpoints (`setpoint[0..4]`)
+    pub fn setpoint(&self) -> Option<[i32; 4]> {
+        self.array("setpoint")
+    }
+
+    /// Raw output of motor `index` (`motor[index]`)
+    pub fn motor(&self, index: usize) -> Option<i32> {
+        self.value(&format!("motor[{index}]"))
+    }
+
+    /// Battery voltage in volts, scaled for the firmware that logged `header`
+    pub fn vbat_volts(&self, header: &BBLHeader) -> Option<f32> {
+        self.value("vbatLatest")
+            .map(|raw| convert_vbat_to_volts(raw, &header.firmware_revision))
+    }
+
+    /// Battery current in amps, applying the current meter calibration of `header`
+    pub fn amperage_amps(&self, header: &BBLHeader) -> Option<f32> {
+        self.value("amperageLatest").map(|raw| {
+            convert_amperage_with_meter(raw, &header.firmware_revision, header.current_meter())
+        })
+    }
+}
+
 /// Frame statistics
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FrameStats {
     pub i_frames: u32,
@@ -105,7 +161,68 @@ pub struct FrameStats {
     pub start_time_us: u64,
     pub end_time_us: u64,
     pub failed_frames: u32,
+    /// Loop iterations between the first and last main frame that should have
+    /// been logged but were not (dropped, corrupt or skipped while resynchronizing)
     pub missing_iterations: u64,
+    /// Loop iterations the firmware does not log by design, following the
+    /// `I interval` / `P interval` headers
+    pub intentionally_absent_iterations: u64,
+    /// Main frames whose timestamp was rewritten by [`crate::types::BBLLog::repair_timestamps`]
+    pub repaired_timestamps: u32,
+    /// Parsing stopped at a [`crate::ParserConfig`] safety limit before the end of the log
+    pub truncated: bool,
+    /// Decoding stopped at the log end event (with its "End of log" trailer)
+    /// the firmware writes when logging stops; `false` for a log that was cut off
+    pub ended_cleanly: bool,
+    /// Where and why frames failed, one entry per failed frame up to
+    /// [`crate::MAX_FRAME_ERRORS`]
+    pub frame_errors: Vec<FrameError>,
+}
+
+/// Frame that failed to decode, recorded in [`FrameStats::frame_errors`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FrameError {
+    /// Offset of the frame type byte from the start of the log's frame data
+    /// (after the text headers)
+    pub offset: u64,
+    /// Frame type, or the unrecognized byte as a character
+    pub frame_type: char,
+    pub reason: FrameErrorReason,
+}
+
+/// Why a frame failed to decode
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FrameErrorReason {
+    /// The byte does not start a known frame type
+    UnknownFrameType,
+    /// The header defines no fields for this frame type
+    NoFieldDefinition,
+    /// A P-frame without a decoded I-frame to predict from
+    NoPreviousFrame,
+    /// Reading the frame's field values failed
+    Decode(String),
+    /// The decoded `time` and `loopIteration` are not plausible
+    Implausible { time_us: u64, loop_iteration: u32 },
+}
+
+impl std::fmt::Display for FrameErrorReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownFrameType => write!(f, "unknown frame type"),
+            Self::NoFieldDefinition => write!(f, "no field definition"),
+            Self::NoPreviousFrame => write!(f, "no previous frame to predict from"),
+            Self::Decode(message) => write!(f, "{message}"),
+            Self::Implausible {
+                time_us,
+                loop_iteration,
+            } => write!(
+                f,
+                "implausible time {time_us} us at loop iteration {loop_iteration}"
+            ),
+        }
+    }
 }
 
 /// Frame history for prediction during parsing
@@ -1,5 +1,8 @@
+use crate::conversion::{AxisRates, FirmwareKind, RatesType};
+use crate::types::columns::FieldTable;
 use crate::types::frame::FrameDefinition;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -22,10 +25,471 @@ pub struct BBLHeader {
     pub s_frame_def: FrameDefinition,
     pub g_frame_def: FrameDefinition,
     pub h_frame_def: FrameDefinition,
-    pub sysconfig: HashMap<String, i32>,
+    /// Interned main-frame fields (I-frame fields, then S-frame fields), shared
+    /// with the log's [`crate::types::FrameColumns`]
+    pub main_fields: Arc<FieldTable>,
+    /// Values of the other `H name:value` headers, by name
+    pub sysconfig: HashMap<String, SysConfigValue>,
     pub all_headers: Vec<String>,
 }
 
+/// Value of a `sysconfig` header, typed from its text
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SysConfigValue {
+    Int(i32),
+    /// A decimal or hex-encoded (IEEE 754 bits, e.g. `gyro_scale:0x3f800000`) float
+    Float(f32),
+    /// Comma-separated integers, e.g. `motorOutput:48,2047`
+    IntArray(Vec<i32>),
+    /// Any other text
+    Str(String),
+}
+
+impl SysConfigValue {
+    /// Type a header value: an integer, a float, a list of integers or text
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        if let Ok(int) = value.parse() {
+            return Self::Int(int);
+        }
+        if let Some(bits) = value
+            .strip_prefix("0x")
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+        {
+            return Self::Float(f32::from_bits(bits));
+        }
+        if let Ok(float) = value.parse::<f32>() {
+            if float.is_finite() {
+                return Self::Float(float);
+            }
+        }
+        // Some firmware quotes the whole list, e.g. rollPID:"45,80,30"
+        let list = value.trim_matches('"');
+        if list.contains(',') {
+            if let Ok(values) = list.split(',').map(|v| v.trim().parse()).collect() {
+                return Self::IntArray(values);
+            }
+        }
+        Self::Str(value.to_string())
+    }
+
+    /// The value of an integer header
+    pub fn as_int(&self) -> Option<i32> {
+        match self {
+            Self::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// The value of a float or integer header
+    pub fn as_float(&self) -> Option<f32> {
+        match self {
+            Self::Float(value) => Some(*value),
+            Self::Int(value) => Some(*value as f32),
+            _ => None,
+        }
+    }
+
+    /// The values of a list header; a single integer is a one-element list
+    pub fn as_int_array(&self) -> Option<&[i32]> {
+        match self {
+            Self::IntArray(values) => Some(values),
+            Self::Int(value) => Some(std::slice::from_ref(value)),
+            _ => None,
+        }
+    }
+
+    /// The text of a header that is not a number or list
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Str(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl From<i32> for SysConfigValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl std::fmt::Display for SysConfigValue {
+    /// The value as header text (floats in decimal)
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value}"),
+            Self::IntArray(values) => {
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                Ok(())
+            }
+            Self::Str(value) => f.write_str(value),
+        }
+    }
+}
+
+/// PID gains of one axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AxisPid {
+    pub p: i32,
+    pub i: i32,
+    pub d: i32,
+    /// Feedforward gain: the fourth `rollPID` value on INAV, `ff_weight` on
+    /// Betaflight; EmuFlight and older firmware log none
+    pub ff: Option<i32>,
+}
+
+/// PID gains of the three axes from the `rollPID`, `pitchPID`, `yawPID` and
+/// `ff_weight` headers ([`BBLHeader::pid_config`])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PidConfig {
+    pub roll: AxisPid,
+    pub pitch: AxisPid,
+    pub yaw: AxisPid,
+}
+
+/// A gyro or D-term lowpass filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LowpassFilter {
+    /// Filter type (see [`LowpassFilter::type_name`])
+    pub filter_type: Option<i32>,
+    /// Cutoff in Hz when the cutoff is static; `0` disables the filter
+    pub static_hz: Option<i32>,
+    /// Throttle-dependent cutoff range in Hz, used instead of `static_hz` when set
+    pub dyn_min_hz: Option<i32>,
+    pub dyn_max_hz: Option<i32>,
+}
+
+impl LowpassFilter {
+    /// Betaflight name of the filter type
+    pub fn type_name(&self) -> Option<&'static str> {
+        match self.filter_type? {
+            0 => Some("PT1"),
+            1 => Some("BIQUAD"),
+            2 => Some("PT2"),
+            3 => Some("PT3"),
+            _ => None,
+        }
+    }
+
+    /// Whether the filter has a static or dynamic cutoff
+    pub fn is_enabled(&self) -> bool {
+        self.static_hz.unwrap_or(0) > 0 || self.dyn_min_hz.unwrap_or(0) > 0
+    }
+}
+
+/// A static notch filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NotchFilter {
+    pub center_hz: i32,
+    pub cutoff_hz: i32,
+}
+
+/// Dynamic notch filters tracking the strongest gyro noise peaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DynamicNotch {
+    /// Notches per axis (Betaflight 4.3+)
+    pub count: Option<i32>,
+    pub q: Option<i32>,
+    pub min_hz: Option<i32>,
+    pub max_hz: Option<i32>,
+}
+
+/// Notch filters following the motor RPM reported by bidirectional DShot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RpmFilter {
+    /// Motor harmonics filtered; `0` disables the RPM filter
+    pub harmonics: i32,
+    pub q: Option<i32>,
+    pub min_hz: Option<i32>,
+    pub fade_range_hz: Option<i32>,
+    pub lpf_hz: Option<i32>,
+}
+
+/// Gyro and D-term filter settings from the header ([`BBLHeader::filter_config`])
+///
+/// Each filter is `None` when the log has none of its headers.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FilterConfig {
+    pub gyro_lpf1: Option<LowpassFilter>,
+    pub gyro_lpf2: Option<LowpassFilter>,
+    /// Enabled static gyro notches
+    pub gyro_notches: Vec<NotchFilter>,
+    pub dterm_lpf1: Option<LowpassFilter>,
+    pub dterm_lpf2: Option<LowpassFilter>,
+    pub dterm_notch: Option<NotchFilter>,
+    pub yaw_lowpass_hz: Option<i32>,
+    pub dyn_notch: Option<DynamicNotch>,
+    pub rpm_filter: Option<RpmFilter>,
+}
+
+/// Betaflight `FEATURE_3D` bit in the `features` header
+const FEATURE_3D: i32 = 1 << 12;
+
+impl BBLHeader {
+    /// Value of an integer `sysconfig` header
+    pub fn sysconfig_int(&self, key: &str) -> Option<i32> {
+        self.sysconfig.get(key)?.as_int()
+    }
+
+    /// Element `index` of a list `sysconfig` header such as `motorOutput:48,2047`
+    pub fn sysconfig_element(&self, key: &str, index: usize) -> Option<i32> {
+        self.sysconfig.get(key)?.as_int_array()?.get(index).copied()
+    }
+
+    /// Motor output range from the `motorOutput: min,max` header, if present
+    ///
+    /// DShot logs typically report `48,2047`; PWM logs report the ESC pulse range.
+    pub fn motor_output_range(&self) -> Option<(i32, i32)> {
+        let min = self.sysconfig_element("motorOutput", 0)?;
+        let max = self.sysconfig_element("motorOutput", 1)?;
+        (max > min).then_some((min, max))
+    }
+
+    /// Throttle command range from the `minthrottle` / `maxthrottle` headers,
+    /// defaulting to 1000–2000 when either is missing
+    pub fn throttle_range(&self) -> (i32, i32) {
+        match (
+            self.sysconfig_int("minthrottle"),
+            self.sysconfig_int("maxthrottle"),
+        ) {
+            (Some(min), Some(max)) if max > min => (min, max),
+            _ => (1000, 2000),
+        }
+    }
+
+    /// Names of the features enabled in the `features` header (`AIRMODE`,
+    /// `GPS`, `TELEMETRY`, ...)
+    ///
+    /// Uses Betaflight's feature bits, which EmuFlight and Rotorflight share;
+    /// INAV numbers its features differently, so its logs give no names.
+    pub fn features(&self) -> Vec<&'static str> {
+        if self.firmware() == FirmwareKind::Inav {
+            return Vec::new();
+        }
+        self.sysconfig_int("features")
+            .map_or_else(Vec::new, crate::conversion::betaflight_feature_names)
+    }
+
+    /// Whether the `features` header has 3D mode (reversible motors) enabled
+    pub fn is_3d_mode(&self) -> bool {
+        self.sysconfig_int("features")
+            .is_some_and(|features| features & FEATURE_3D != 0)
+    }
+
+    /// Rebuild [`BBLHeader::main_fields`] from the I and S frame definitions
+    pub fn update_main_fields(&mut self) {
+        self.main_fields = Arc::new(FieldTable::from_names(
+            self.i_frame_def
+                .field_names
+                .iter()
+                .chain(&self.s_frame_def.field_names)
+                .map(|name| name.as_str()),
+        ));
+    }
+
+    /// Firmware family that recorded the log, from its firmware revision
+    pub fn firmware(&self) -> FirmwareKind {
+        FirmwareKind::from_revision(&self.firmware_revision)
+    }
+
+    /// Name of the `debug_mode` the `debug[n]` fields were recorded in, for
+    /// Betaflight logs with a known mode
+    pub fn debug_mode_name(&self) -> Option<&'static str> {
+        if self.firmware() != FirmwareKind::Betaflight {
+            return None;
+        }
+        crate::conversion::betaflight_debug_mode_name(self.sysconfig_int("debug_mode")?)
+    }
+
+    /// Gyro scale from the `gyro_scale` header (a hex-encoded float), in degrees
+    /// per second per raw `gyroADC` unit
+    pub fn gyro_scale(&self) -> Option<f32> {
+        let scale = self.sysconfig.get("gyro_scale")?.as_float()?;
+        (scale.is_normal() && scale > 0.0).then_some(scale)
+    }
+
+    /// Current meter calibration `(offset, scale)` from the `currentSensor`
+    /// (or older `currentMeter`) header, if present
+    pub fn current_meter(&self) -> Option<(i32, i32)> {
+        let key = if self.sysconfig.contains_key("currentSensor") {
+            "currentSensor"
+        } else {
+            "currentMeter"
+        };
+        Some((
+            self.sysconfig_element(key, 0)?,
+            self.sysconfig_element(key, 1)?,
+        ))
+    }
+
+    /// Maximum cell voltage in volts, from the `vbatcellvoltage: min,warning,max`
+    /// header (older firmware logs tenths of a volt, newer hundredths)
+    pub fn max_cell_voltage(&self) -> Option<f32> {
+        let raw = self.sysconfig_element("vbatcellvoltage", 2)?;
+        match raw {
+            1..=99 => Some(raw as f32 / 10.0),
+            100.. => Some(raw as f32 / 100.0),
+            _ => None,
+        }
+    }
+
+    /// Rate curve from the `rates_type` header; logs from before Betaflight 4.1
+    /// have no such header and use [`RatesType::Betaflight`]
+    pub fn rates_type(&self) -> RatesType {
+        self.sysconfig_int("rates_type")
+            .and_then(RatesType::from_header)
+            .unwrap_or_default()
+    }
+
+    /// Rate settings of roll (0), pitch (1) or yaw (2) from the `rc_rates`,
+    /// `rates` and `rc_expo` headers
+    ///
+    /// A single-valued header applies to every axis. Returns `None` when the
+    /// log has no RC rate or super rate for the axis.
+    pub fn axis_rates(&self, axis: usize) -> Option<AxisRates> {
+        let value = |key: &str| match self.sysconfig.get(key)?.as_int_array()? {
+            [single] => Some(*single),
+            values => values.get(axis).copied(),
+        };
+        Some(AxisRates {
+            rc_rate: value("rc_rates").or_else(|| value("rc_rate"))?,
+            super_rate: value("rates")?,
+            expo: value("rc_expo").unwrap_or(0),
+        })
+    }
+
+    /// PID gains from the `rollPID`, `pitchPID` and `yawPID` headers
+    ///
+    /// Handles Betaflight (`P,I,D` plus feedforward from `ff_weight`), INAV
+    /// (`P,I,D,FF`) and EmuFlight (`P,I,D`). Returns `None` unless all three
+    /// axes have at least P, I and D.
+    pub fn pid_config(&self) -> Option<PidConfig> {
+        let axis = |key: &str, index: usize| {
+            let value = |i: usize| self.sysconfig_element(key, i);
+            Some(AxisPid {
+                p: value(0)?,
+                i: value(1)?,
+                d: value(2)?,
+                ff: value(3).or_else(|| self.sysconfig_element("ff_weight", index)),
+            })
+        };
+        Some(PidConfig {
+            roll: axis("rollPID", 0)?,
+            pitch: axis("pitchPID", 1)?,
+            yaw: axis("yawPID", 2)?,
+        })
+    }
+
+    /// Gyro and D-term filter settings from the filter headers
+    ///
+    /// Reads the Betaflight 4.3+ names (`gyro_lpf1_static_hz`, ...) and falls
+    /// back to the names older Betaflight and EmuFlight log (`gyro_lowpass_hz`, ...).
+    pub fn filter_config(&self) -> FilterConfig {
+        // Value of the first of `names` present
+        let value = |names: &[&str]| names.iter().find_map(|name| self.sysconfig_int(name));
+        let element = |names: &[&str], index: usize| {
+            names
+                .iter()
+                .find_map(|name| self.sysconfig_element(name, index))
+        };
+        let lowpass = |type_names: &[&str], hz_names: &[&str], dyn_names: &[&str]| {
+            let filter = LowpassFilter {
+                filter_type: value(type_names),
+                static_hz: value(hz_names),
+                dyn_min_hz: element(dyn_names, 0),
+                dyn_max_hz: element(dyn_names, 1),
+            };
+            (filter != LowpassFilter::default()).then_some(filter)
+        };
+
+        let gyro_notches = (0..2)
+            .filter_map(|i| {
+                let center_hz = self.sysconfig_element("gyro_notch_hz", i)?;
+                let cutoff_hz = self.sysconfig_element("gyro_notch_cutoff", i)?;
+                (center_hz > 0).then_some(NotchFilter {
+                    center_hz,
+                    cutoff_hz,
+                })
+            })
+            .collect();
+        let dterm_notch = value(&["dterm_notch_hz"])
+            .zip(value(&["dterm_notch_cutoff"]))
+            .filter(|&(center_hz, _)| center_hz > 0)
+            .map(|(center_hz, cutoff_hz)| NotchFilter {
+                center_hz,
+                cutoff_hz,
+            });
+        let dyn_notch = DynamicNotch {
+            count: value(&["dyn_notch_count"]),
+            q: value(&["dyn_notch_q"]),
+            min_hz: value(&["dyn_notch_min_hz"]),
+            max_hz: value(&["dyn_notch_max_hz"]),
+        };
+        let rpm_filter = value(&["rpm_filter_harmonics"]).map(|harmonics| RpmFilter {
+            harmonics,
+            q: value(&["rpm_filter_q"]),
+            min_hz: value(&["rpm_filter_min_hz"]),
+            fade_range_hz: value(&["rpm_filter_fade_range_hz"]),
+            lpf_hz: value(&["rpm_filter_lpf_hz"]),
+        });
+
+        FilterConfig {
+            gyro_lpf1: lowpass(
+                &["gyro_lpf1_type", "gyro_lowpass_type"],
+                &["gyro_lpf1_static_hz", "gyro_lowpass_hz"],
+                &["gyro_lpf1_dyn_hz", "gyro_lowpass_dyn_hz"],
+            ),
+            gyro_lpf2: lowpass(
+                &["gyro_lpf2_type", "gyro_lowpass2_type"],
+                &["gyro_lpf2_static_hz", "gyro_lowpass2_hz"],
+                &[],
+            ),
+            gyro_notches,
+            dterm_lpf1: lowpass(
+                &["dterm_lpf1_type", "dterm_filter_type"],
+                &["dterm_lpf1_static_hz", "dterm_lowpass_hz"],
+                &["dterm_lpf1_dyn_hz", "dterm_lowpass_dyn_hz"],
+            ),
+            dterm_lpf2: lowpass(
+                &["dterm_lpf2_type", "dterm_filter2_type"],
+                &["dterm_lpf2_static_hz", "dterm_lowpass2_hz"],
+                &[],
+            ),
+            dterm_notch,
+            yaw_lowpass_hz: value(&["yaw_lowpass_hz"]),
+            dyn_notch: (dyn_notch != DynamicNotch::default()).then_some(dyn_notch),
+            rpm_filter,
+        }
+    }
+
+    /// Raw accelerometer reading of 1 g from the `acc_1G` header
+    pub fn acc_1g(&self) -> Option<i32> {
+        self.sysconfig_int("acc_1G").filter(|&acc_1g| acc_1g > 0)
+    }
+
+    /// Whether the log was recorded with Betaflight's `blackbox_high_resolution`
+    /// setting, which stores gyro, RC command and setpoint values multiplied by 10
+    pub fn is_high_resolution(&self) -> bool {
+        self.sysconfig_int("blackbox_high_resolution")
+            .is_some_and(|value| value > 0)
+    }
+}
+
 impl Default for BBLHeader {
     fn default() -> Self {
         Self {
@@ -40,6 +504,7 @@ impl Default for BBLHeader {
             s_frame_def: FrameDefinition::new(),
             g_frame_def: FrameDefinition::new(),
             h_frame_def: FrameDefinition::new(),
+            main_fields: Arc::default(),
             sysconfig: HashMap::new(),
             all_headers: Vec::new(),
         }
@@ -1,5 +1,5 @@
 use crate::types::{
-    BBLHeader, DecodedFrame, EventFrame, FrameStats, GpsCoordinate, GpsHomeCoordinate,
+    BBLHeader, DecodedFrame, EventFrame, FrameColumns, FrameStats, GpsCoordinate, GpsHomeCoordinate,
 };
 use std::collections::HashMap;
 
@@ -14,12 +14,18 @@ pub struct BBLLog {
     pub total_logs: usize,
     pub header: BBLHeader,
     pub stats: FrameStats,
-    /// All parsed frames (I and P frames with decoded values)
-    pub frames: Vec<DecodedFrame>,
+    /// All parsed main frames (I and P frames with decoded values), stored by column
+    pub frames: FrameColumns,
     pub debug_frames: Option<HashMap<char, Vec<DecodedFrame>>>,
     pub gps_coordinates: Vec<GpsCoordinate>,
     pub home_coordinates: Vec<GpsHomeCoordinate>,
     pub event_frames: Vec<EventFrame>,
+    /// S frames at their own rate, collected when
+    /// `ExportOptions::split_frame_types` is set (empty otherwise)
+    pub slow_frames: FrameColumns,
+    /// Raw G frames at their own rate, collected when
+    /// `ExportOptions::split_frame_types` is set (empty otherwise)
+    pub gps_frames: FrameColumns,
 }
 
 impl BBLLog {
@@ -29,11 +35,13 @@ impl BBLLog {
             total_logs,
             header: BBLHeader::default(),
             stats: FrameStats::default(),
-            frames: Vec::new(),
+            frames: FrameColumns::new(),
             debug_frames: None,
             gps_coordinates: Vec::new(),
             home_coordinates: Vec::new(),
             event_frames: Vec::new(),
+            slow_frames: FrameColumns::new(),
+            gps_frames: FrameColumns::new(),
         }
     }
 
@@ -63,6 +71,82 @@ impl BBLLog {
     pub fn get_frames_by_type(&self, frame_type: char) -> Option<&Vec<DecodedFrame>> {
         self.debug_frames.as_ref()?.get(&frame_type)
     }
+
+    /// Main frame shown at `time_us`: the latest frame logged at or before it
+    ///
+    /// Uses the log's time index, so it is a binary search rather than a scan
+    /// (see [`FrameColumns::row_at_time`]).
+    pub fn frame_at_time(&self, time_us: u64) -> Option<DecodedFrame> {
+        self.frames.row(self.frames.row_at_time(time_us)?)
+    }
+
+    /// Main frames logged from `start_us` to `end_us` (both inclusive), in time order
+    pub fn frames_in_range(
+        &self,
+        start_us: u64,
+        end_us: u64,
+    ) -> impl Iterator<Item = DecodedFrame> + '_ {
+        self.frames
+            .rows_in_range(start_us, end_us)
+            .into_iter()
+            .filter_map(|row| self.frames.row(row))
+    }
+
+    /// Logged loop period in microseconds (`looptime` × `pid_process_denom`), if known
+    fn iteration_period_us(&self) -> Option<u64> {
+        if self.header.looptime == 0 {
+            return None;
+        }
+        let pid_denom = self
+            .header
+            .sysconfig_int("pid_process_denom")
+            .filter(|&denom| denom > 0)
+            .unwrap_or(1) as u64;
+        Some(self.header.looptime as u64 * pid_denom)
+    }
+
+    /// Make main-frame (I/P) timestamps monotonic.
+    ///
+    /// Any main frame whose time falls behind the previous main frame is re-timed
+    /// from that frame using the `loopIteration` delta and the logged loop period.
+    /// Returns the number of frames adjusted, which is also added to
+    /// `stats.repaired_timestamps`.
+    pub fn repair_timestamps(&mut self) -> usize {
+        let period_us = self.iteration_period_us();
+        let time_field = self.frames.field_index("time");
+        let mut previous: Option<(u64, u32)> = None;
+        let mut repaired = 0usize;
+
+        for row in 0..self.frames.len() {
+            let timestamp_us = self.frames.timestamps()[row];
+            let loop_iteration = self.frames.loop_iterations()[row];
+
+            let timestamp_us = match previous {
+                Some((prev_time, prev_loop)) if timestamp_us < prev_time => {
+                    let iterations = loop_iteration.saturating_sub(prev_loop).max(1) as u64;
+                    let repaired_time = prev_time + iterations * period_us.unwrap_or(0);
+                    self.frames.set_timestamp(row, repaired_time);
+                    if let Some(field) = time_field {
+                        self.frames.set_value(row, field, repaired_time as i32);
+                    }
+                    repaired += 1;
+                    repaired_time
+                }
+                _ => timestamp_us,
+            };
+            previous = Some((timestamp_us, loop_iteration));
+        }
+
+        if repaired > 0 {
+            self.frames.build_time_index();
+            if let Some(&last) = self.frames.timestamps().last() {
+                self.stats.end_time_us = self.stats.end_time_us.max(last);
+            }
+            self.stats.repaired_timestamps += repaired as u32;
+        }
+
+        repaired
+    }
 }
 
 /// Container for multiple BBL logs from a single file
@@ -100,3 +184,89 @@ impl BBLFile {
         self.logs.iter().any(|log| log.has_gps_data())
     }
 }
+
+/// Fixtures for the analysis and export tests
+#[cfg(test)]
+impl BBLLog {
+    /// Log 1 of 1 with one P frame per `(timestamp_us, fields)` row
+    pub(crate) fn with_main_frames<R, F, N>(rows: R) -> Self
+    where
+        R: IntoIterator<Item = (u64, F)>,
+        F: IntoIterator<Item = (N, i32)>,
+        N: Into<String>,
+    {
+        let mut log = Self::new(1, 1);
+        for (timestamp_us, fields) in rows {
+            log.push_main_frame(timestamp_us, 0, fields);
+        }
+        log
+    }
+
+    /// Append a P frame holding `fields`
+    pub(crate) fn push_main_frame<N: Into<String>>(
+        &mut self,
+        timestamp_us: u64,
+        loop_iteration: u32,
+        fields: impl IntoIterator<Item = (N, i32)>,
+    ) {
+        self.frames.push(DecodedFrame {
+            frame_type: 'P',
+            timestamp_us,
+            loop_iteration,
+            data: fields
+                .into_iter()
+                .map(|(name, value)| (name.into(), value))
+                .collect(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn main_frame(frame_type: char, timestamp_us: u64, loop_iteration: u32) -> DecodedFrame {
+        let mut data = HashMap::new();
+        data.insert("time".to_string(), timestamp_us as i32);
+        data.insert("loopIteration".to_string(), loop_iteration as i32);
+        DecodedFrame {
+            frame_type,
+            timestamp_us,
+            loop_iteration,
+            data,
+        }
+    }
+
+    #[test]
+    fn test_repair_timestamps_fixes_regressions() {
+        let mut log = BBLLog::new(1, 1);
+        log.header.looptime = 125;
+        log.header
+            .sysconfig
+            .insert("pid_process_denom".to_string(), 2.into());
+        log.frames = vec![
+            main_frame('I', 10_000, 0),
+            main_frame('P', 10_250, 1),
+            main_frame('P', 3, 2), // corrupted time
+            main_frame('P', 10_750, 3),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(log.repair_timestamps(), 1);
+        assert_eq!(log.frames.timestamps()[2], 10_500);
+        assert_eq!(log.frames.get(2, "time"), Some(10_500));
+        assert_eq!(log.stats.repaired_timestamps, 1);
+
+        // Already monotonic logs are left untouched
+        assert_eq!(log.repair_timestamps(), 0);
+
+        // The repaired frame is found at its new time
+        assert_eq!(log.frame_at_time(10_600).unwrap().loop_iteration, 2);
+        let iterations: Vec<u32> = log
+            .frames_in_range(10_250, 10_750)
+            .map(|frame| frame.loop_iteration)
+            .collect();
+        assert_eq!(iterations, [1, 2, 3]);
+    }
+}
@@ -1,9 +1,17 @@
+pub mod columns;
+pub mod field_info;
 pub mod frame;
 pub mod gps;
 pub mod header;
 pub mod log;
+#[cfg(feature = "arrow")]
+pub mod record_batch;
 
+pub use columns::*;
+pub use field_info::*;
 pub use frame::*;
 pub use gps::*;
 pub use header::*;
 pub use log::*;
+#[cfg(feature = "arrow")]
+pub use record_batch::*;
@@ -0,0 +1,241 @@
+//! Arrow conversion of main frames (`arrow` feature)
+
+use crate::conversion::{convert_amperage_with_meter, convert_vbat_to_volts};
+use crate::types::{field_unit, BBLLog};
+use anyhow::Result;
+use arrow_array::builder::{Float32Builder, Int32Builder};
+use arrow_array::{ArrayRef, RecordBatch, UInt32Array, UInt64Array};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Field metadata key holding a column's unit
+pub const ARROW_UNIT_METADATA_KEY: &str = "unit";
+
+/// How one frame field becomes an Arrow column
+enum ArrowColumn {
+    Raw(usize),
+    Volts(usize),
+    Amps(usize),
+}
+
+impl BBLLog {
+    /// Arrow schema of [`BBLLog::to_record_batch`] for this log
+    ///
+    /// `time` is `UInt64` microseconds and `loopIteration` `UInt32`; every other
+    /// main and slow frame field is a nullable `Int32`, except `vbatLatest (V)`
+    /// and `amperageLatest (A)` which are `Float32` volts and amps, as in the
+    /// CSV export. Every column with a known unit ([`crate::field_unit`] for
+    /// raw fields) carries it under the [`ARROW_UNIT_METADATA_KEY`] metadata
+    /// key; the schema metadata records
+    /// the firmware revision, craft name and log number.
+    pub fn arrow_schema(&self) -> SchemaRef {
+        self.arrow_layout().0
+    }
+
+    /// All main frames as one Arrow record batch, in decode order
+    ///
+    /// Columns are described by [`BBLLog::arrow_schema`]; values missing from
+    /// a frame are null. The values are copied out of the log's frame store
+    /// into new Arrow buffers, so the batch does not borrow from the log.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use bbl_parser::BBLLog;
+    ///
+    /// let log = BBLLog::new(1, 1);
+    /// let batch = log.to_record_batch().unwrap();
+    /// assert_eq!(batch.num_rows(), 0);
+    /// assert_eq!(batch.schema().field(0).name(), "time");
+    /// ```
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        let rows: Vec<usize> = (0..self.frames.len()).collect();
+        let (schema, columns) = self.arrow_layout();
+        self.record_batch_for(&schema, &columns, &rows)
+    }
+
+    /// Record batches of at most `batch_rows` rows each, taking main frames
+    /// in the order given by `rows`
+    #[cfg(feature = "parquet")]
+    pub(crate) fn record_batches<'a>(
+        &'a self,
+        rows: &'a [usize],
+        batch_rows: usize,
+    ) -> impl Iterator<Item = Result<RecordBatch>> + 'a {
+        let (schema, columns) = self.arrow_layout();
+        rows.chunks(batch_rows.max(1))
+            .map(move |chunk| self.record_batch_for(&schema, &columns, chunk))
+    }
+
+    fn arrow_layout(&self) -> (SchemaRef, Vec<ArrowColumn>) {
+        let with_unit = |field: Field, unit: Option<&str>| match unit {
+            Some(unit) => field.with_metadata(HashMap::from([(
+                ARROW_UNIT_METADATA_KEY.to_string(),
+                unit.to_string(),
+            )])),
+            None => field,
+        };
+
+        let mut fields = vec![
+            with_unit(
+                Field::new("time", DataType::UInt64, false),
+                field_unit("time"),
+            ),
+            with_unit(
+                Field::new("loopIteration", DataType::UInt32, false),
+                field_unit("loopIteration"),
+            ),
+        ];
+        let mut columns = Vec::new();
+        for (index, name) in self.frames.field_names().iter().enumerate() {
+            match name.as_str() {
+                "time" | "loopIteration" => continue,
+                "vbatLatest" => {
+                    fields.push(with_unit(
+                        Field::new("vbatLatest (V)", DataType::Float32, true),
+                        Some("V"),
+                    ));
+                    columns.push(ArrowColumn::Volts(index));
+                }
+                "amperageLatest" => {
+                    fields.push(with_unit(
+                        Field::new("amperageLatest (A)", DataType::Float32, true),
+                        Some("A"),
+                    ));
+                    columns.push(ArrowColumn::Amps(index));
+                }
+                _ => {
+                    fields.push(with_unit(
+                        Field::new(name.as_str(), DataType::Int32, true),
+                        field_unit(name),
+                    ));
+                    columns.push(ArrowColumn::Raw(index));
+                }
+            }
+        }
+
+        let metadata = HashMap::from([
+            (
+                "firmware_revision".to_string(),
+                self.header.firmware_revision.clone(),
+            ),
+            ("craft_name".to_string(), self.header.craft_name.clone()),
+            ("log_number".to_string(), self.log_number.to_string()),
+        ]);
+        (
+            Arc::new(Schema::new_with_metadata(fields, metadata)),
+            columns,
+        )
+    }
+
+    fn record_batch_for(
+        &self,
+        schema: &SchemaRef,
+        columns: &[ArrowColumn],
+        rows: &[usize],
+    ) -> Result<RecordBatch> {
+        let frames = &self.frames;
+        let mut arrays: Vec<ArrayRef> = vec![
+            Arc::new(UInt64Array::from_iter_values(
+                rows.iter().map(|&row| frames.timestamps()[row]),
+            )),
+            Arc::new(UInt32Array::from_iter_values(
+                rows.iter().map(|&row| frames.loop_iterations()[row]),
+            )),
+        ];
+        for column in columns {
+            let array: ArrayRef =
+                match *column {
+                    ArrowColumn::Raw(field) => {
+                        let mut builder = Int32Builder::with_capacity(rows.len());
+                        for &row in rows {
+                            builder.append_option(frames.value(row, field));
+                        }
+                        Arc::new(builder.finish())
+                    }
+                    ArrowColumn::Volts(field) => {
+                        let firmware = &self.header.firmware_revision;
+                        let mut builder = Float32Builder::with_capacity(rows.len());
+                        for &row in rows {
+                            builder.append_option(
+                                frames
+                                    .value(row, field)
+                                    .map(|raw| convert_vbat_to_volts(raw, firmware)),
+                            );
+                        }
+                        Arc::new(builder.finish())
+                    }
+                    ArrowColumn::Amps(field) => {
+                        let firmware = &self.header.firmware_revision;
+                        let current_meter = self.header.current_meter();
+                        let mut builder = Float32Builder::with_capacity(rows.len());
+                        for &row in rows {
+                            builder.append_option(frames.value(row, field).map(|raw| {
+                                convert_amperage_with_meter(raw, firmware, current_meter)
+                            }));
+                        }
+                        Arc::new(builder.finish())
+                    }
+                };
+            arrays.push(array);
+        }
+        Ok(RecordBatch::try_new(Arc::clone(schema), arrays)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float32Type, Int32Type, UInt64Type};
+    use arrow_array::Array;
+
+    #[test]
+    fn test_to_record_batch() {
+        let mut log = BBLLog::new(2, 3);
+        log.header.firmware_revision = "Betaflight 4.5.1 (77d01ba3b) AT32F435M".to_string();
+        for (timestamp_us, loop_iteration) in [(1000, 0), (1250, 1)] {
+            let row = log
+                .frames
+                .push_values('P', timestamp_us, loop_iteration, []);
+            let time = log.frames.add_field("time");
+            log.frames.set_value(row, time, timestamp_us as i32);
+        }
+        let gyro = log.frames.add_field("gyroADC[0]");
+        let amps = log.frames.add_field("amperageLatest");
+        log.frames.set_value(0, gyro, 7);
+        log.frames.set_value(1, amps, 250);
+
+        let batch = log.to_record_batch().unwrap();
+        let schema = batch.schema();
+        let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(
+            names,
+            ["time", "loopIteration", "gyroADC[0]", "amperageLatest (A)"]
+        );
+        assert_eq!(schema, log.arrow_schema());
+        assert_eq!(schema.metadata()["log_number"], "2");
+
+        let unit = |name: &str| {
+            schema
+                .field_with_name(name)
+                .unwrap()
+                .metadata()
+                .get(ARROW_UNIT_METADATA_KEY)
+                .cloned()
+        };
+        assert_eq!(unit("time").as_deref(), Some("us"));
+        assert_eq!(unit("amperageLatest (A)").as_deref(), Some("A"));
+        assert_eq!(unit("gyroADC[0]").as_deref(), Some("raw gyro counts"));
+        assert_eq!(unit("loopIteration"), None);
+
+        let time = batch.column(0).as_primitive::<UInt64Type>();
+        assert_eq!(time.values().to_vec(), [1000, 1250]);
+        let gyro = batch.column(2).as_primitive::<Int32Type>();
+        assert_eq!(gyro.value(0), 7);
+        assert!(gyro.is_null(1));
+        let amps = batch.column(3).as_primitive::<Float32Type>();
+        assert!(amps.is_null(0));
+        assert!((amps.value(1) - 2.5).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,624 @@
+//! BBL writer for producing blackbox logs
+//!
+//! [`BBLWriter`] serializes a [`BBLHeader`] and I, P, S, G, H and E frames back
+//! into blackbox binary format, with each field written in the encoding and
+//! against the predictor its header declares. Anything it writes decodes back to
+//! the same values with this crate's parser, which makes it the basis for
+//! trimming ([`crate::write_trimmed_log`]), round-trip tests and synthetic logs.
+//!
+//! # Usage
+//!
+//! ```rust
+//! use bbl_parser::{parse_bbl_bytes, BBLHeader, BBLWriter, ExportOptions, FrameDefinition};
+//!
+//! // Two fields: loopIteration (unsigned) and time (unsigned), predicted in
+//! // P-frames by incrementing and by the straight line through the last two frames
+//! let mut header = BBLHeader::default();
+//! header.i_frame_def =
+//!     FrameDefinition::from_field_names(vec!["loopIteration".into(), "time".into()]);
+//! header.i_frame_def.update_encoding(&[1, 1]);
+//! header.p_frame_def = header.i_frame_def.clone();
+//! header.p_frame_def.update_predictors(&[6, 2]);
+//! header.p_frame_def.update_encoding(&[9, 0]);
+//!
+//! let mut data = Vec::new();
+//! let mut writer = BBLWriter::new(&mut data, &header).unwrap();
+//! writer.write_i_frame(&[1, 1000]).unwrap();
+//! writer.write_p_frame(&[2, 1125]).unwrap();
+//! writer.finish().unwrap();
+//!
+//! let log = parse_bbl_bytes(&data, ExportOptions::default(), false).unwrap();
+//! assert_eq!(log.frames.timestamps(), &[1000, 1125]);
+//! ```
+
+use crate::error::BBLError;
+use crate::parser::decoder::PREDICT_INC;
+use crate::parser::encoder::{encode_frame_data, encode_unpredicted_frame_data};
+use crate::parser::event::LOG_END_TRAILER;
+use crate::parser::frame::{FrameIntervals, LOG_END_EVENT};
+use crate::parser::main::LOG_START_MARKER;
+use crate::types::{BBLHeader, BBLLog, FrameDefinition, SysConfigValue};
+use anyhow::Result;
+use std::io::Write;
+
+/// Writer producing one blackbox log
+///
+/// Main frame values are given in I-frame field order for both I and P frames;
+/// S, G and H frame values in the order of their own field definitions. Fields
+/// missing from a slice are written as 0. The writer keeps the same prediction
+/// history as the decoder, so P and G frames are written as differences from the
+/// frames before them.
+pub struct BBLWriter<'a, W: Write> {
+    writer: W,
+    header: &'a BBLHeader,
+    /// I-frame position of each P-frame field
+    p_to_i: Vec<Option<usize>>,
+    previous: Option<Vec<i32>>,
+    previous2: Vec<i32>,
+    gps_previous: Vec<i32>,
+    /// Iterations logged per the header's frame intervals, and the I-frame
+    /// position of `loopIteration`, which `PREDICT_INC` steps over
+    intervals: FrameIntervals,
+    i_loop: Option<usize>,
+    /// Home position of the last H-frame, which G-frame coordinates are predicted from
+    gps_home: Option<[i32; 2]>,
+    buffer: Vec<u8>,
+    bytes_written: u64,
+}
+
+impl<'a, W: Write> BBLWriter<'a, W> {
+    /// Start a log, writing `header`'s text headers
+    ///
+    /// Headers read from a log (`BBLHeader::all_headers`) are written as they
+    /// are; a header built in code gets the product line, version, firmware,
+    /// craft, looptime, field definitions and `sysconfig` values.
+    pub fn new(writer: W, header: &'a BBLHeader) -> Result<Self> {
+        let mut text = String::new();
+        if header.all_headers.is_empty() {
+            text = header_lines(header).join("\n");
+        } else {
+            text.push_str(&header.all_headers.join("\n"));
+        }
+        text.push('\n');
+        Self::with_header_bytes(writer, header, text.as_bytes())
+    }
+
+    /// Start a log, writing `header_bytes` as its text headers
+    ///
+    /// For copying a log's original header section unchanged; `header` must
+    /// describe the same frame definitions.
+    pub fn with_header_bytes(
+        mut writer: W,
+        header: &'a BBLHeader,
+        header_bytes: &[u8],
+    ) -> Result<Self> {
+        writer.write_all(header_bytes)?;
+        let p_to_i = header
+            .p_frame_def
+            .field_names
+            .iter()
+            .map(|name| {
+                header
+                    .i_frame_def
+                    .field_names
+                    .iter()
+                    .position(|n| n == name)
+            })
+            .collect();
+        Ok(Self {
+            writer,
+            header,
+            p_to_i,
+            previous: None,
+            previous2: Vec::new(),
+            gps_previous: vec![0; header.g_frame_def.count],
+            gps_home: None,
+            intervals: FrameIntervals::from_sysconfig(&header.sysconfig),
+            i_loop: header
+                .i_frame_def
+                .field_names
+                .iter()
+                .position(|name| name == "loopIteration"),
+            buffer: Vec::new(),
+            bytes_written: header_bytes.len() as u64,
+        })
+    }
+
+    /// Bytes written so far, headers included
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Write an I-frame, which resets the prediction history
+    pub fn write_i_frame(&mut self, values: &[i32]) -> Result<()> {
+        let frame_def = &self.header.i_frame_def;
+        if frame_def.count == 0 {
+            return Err(no_fields('I'));
+        }
+        let values = resized(values, frame_def.count);
+        self.buffer.clear();
+        self.buffer.push(b'I');
+        encode_frame_data(
+            &mut self.buffer,
+            frame_def,
+            &values,
+            None,
+            None,
+            None,
+            0,
+            &self.header.sysconfig,
+        )?;
+        self.previous2 = values.clone();
+        self.previous = Some(values);
+        self.flush_frame()
+    }
+
+    /// Write a P-frame, predicted from the main frames before it
+    ///
+    /// Fields predicted by `PREDICT_INC` (usually `loopIteration`) are implied by
+    /// the frame order; when `values` does not follow that count, an I-frame is
+    /// written instead so the values still decode exactly. Fails before the
+    /// first I-frame.
+    pub fn write_p_frame(&mut self, values: &[i32]) -> Result<()> {
+        let header = self.header;
+        let Some(previous) = self.previous.as_deref() else {
+            return Err(BBLError::InvalidFrame("P frame before the first I frame".into()).into());
+        };
+        let values = resized(values, header.i_frame_def.count);
+        let p_values: Vec<i32> = self
+            .p_to_i
+            .iter()
+            .map(|i_index| i_index.map_or(0, |i| values[i]))
+            .collect();
+        // Iterations the firmware would not have logged since the previous frame
+        let skipped_frames = self
+            .i_loop
+            .and_then(|i| previous.get(i))
+            .map_or(0, |&iteration| {
+                self.intervals.skipped_after(iteration as u32)
+            });
+        let implied_fields_follow = header
+            .p_frame_def
+            .fields
+            .iter()
+            .zip(&p_values)
+            .zip(&self.p_to_i)
+            .filter(|((field, _), _)| field.predictor == PREDICT_INC)
+            .all(|((_, &value), i_index)| {
+                let previous_value = i_index.and_then(|i| previous.get(i)).copied();
+                previous_value.unwrap_or(0) + 1 + skipped_frames as i32 == value
+            });
+        if header.p_frame_def.count == 0 || !implied_fields_follow {
+            return self.write_i_frame(&values);
+        }
+
+        self.buffer.clear();
+        self.buffer.push(b'P');
+        encode_frame_data(
+            &mut self.buffer,
+            &header.p_frame_def,
+            &p_values,
+            Some(previous),
+            Some(&self.previous2),
+            None,
+            skipped_frames,
+            &header.sysconfig,
+        )?;
+        // The decoder copies fields missing from P-frames from the previous frame
+        let mut current = previous.to_vec();
+        for (i_index, value) in self.p_to_i.iter().zip(p_values) {
+            if let Some(slot) = i_index.and_then(|i| current.get_mut(i)) {
+                *slot = value;
+            }
+        }
+        self.previous2 = self.previous.replace(current).unwrap_or_default();
+        self.flush_frame()
+    }
+
+    /// Write an S-frame (slow, unpredicted fields such as flight mode flags)
+    pub fn write_s_frame(&mut self, values: &[i32]) -> Result<()> {
+        self.write_unpredicted_frame(b'S', values)
+    }
+
+    /// Write an H-frame (GPS home position)
+    pub fn write_h_frame(&mut self, values: &[i32]) -> Result<()> {
+        self.write_unpredicted_frame(b'H', values)?;
+        let field_names = &self.header.h_frame_def.field_names;
+        let home_value = |name: &str| {
+            let i = field_names.iter().position(|n| n == name)?;
+            Some(values.get(i).copied().unwrap_or(0))
+        };
+        if let (Some(lat), Some(lon)) = (home_value("GPS_home[0]"), home_value("GPS_home[1]")) {
+            self.gps_home = Some([lat, lon]);
+        }
+        Ok(())
+    }
+
+    /// Write a G-frame (GPS fix), predicted from the G-frame before it
+    pub fn write_g_frame(&mut self, values: &[i32]) -> Result<()> {
+        let frame_def = &self.header.g_frame_def;
+        if frame_def.count == 0 {
+            return Err(no_fields('G'));
+        }
+        let values = resized(values, frame_def.count);
+        self.buffer.clear();
+        self.buffer.push(b'G');
+        encode_frame_data(
+            &mut self.buffer,
+            frame_def,
+            &values,
+            Some(&self.gps_previous),
+            None,
+            self.gps_home,
+            0,
+            &self.header.sysconfig,
+        )?;
+        self.gps_previous = values;
+        self.flush_frame()
+    }
+
+    /// Write an E-frame: the event type followed by its already encoded payload
+    pub fn write_event(&mut self, event_type: u8, payload: &[u8]) -> Result<()> {
+        self.buffer.clear();
+        self.buffer.extend_from_slice(&[b'E', event_type]);
+        self.buffer.extend_from_slice(payload);
+        self.flush_frame()
+    }
+
+    /// End the log with a log end event and its "End of log" trailer, returning
+    /// the total number of bytes written
+    pub fn finish(mut self) -> Result<u64> {
+        self.write_event(LOG_END_EVENT, LOG_END_TRAILER)?;
+        self.writer.flush()?;
+        Ok(self.bytes_written)
+    }
+
+    fn write_unpredicted_frame(&mut self, frame_type: u8, values: &[i32]) -> Result<()> {
+        let frame_def = match frame_type {
+            b'S' => &self.header.s_frame_def,
+            _ => &self.header.h_frame_def,
+        };
+        if frame_def.count == 0 {
+            return Err(no_fields(frame_type as char));
+        }
+        self.buffer.clear();
+        self.buffer.push(frame_type);
+        encode_unpredicted_frame_data(&mut self.buffer, frame_def, values, frame_type == b'S')?;
+        self.flush_frame()
+    }
+
+    fn flush_frame(&mut self) -> Result<()> {
+        self.writer.write_all(&self.buffer)?;
+        self.bytes_written += self.buffer.len() as u64;
+        Ok(())
+    }
+}
+
+/// Error for a frame type the header defines no fields for
+fn no_fields(frame_type: char) -> anyhow::Error {
+    BBLError::InvalidFrame(format!("header defines no {frame_type} frame fields")).into()
+}
+
+/// `values` padded with zeros or cut to `count` fields
+fn resized(values: &[i32], count: usize) -> Vec<i32> {
+    let mut values = values[..values.len().min(count)].to_vec();
+    values.resize(count, 0);
+    values
+}
+
+/// Text header lines describing a header built in code
+fn header_lines(header: &BBLHeader) -> Vec<String> {
+    let mut lines = vec![String::from_utf8_lossy(LOG_START_MARKER).into_owned()];
+    lines.push(format!("H Data version:{}", header.data_version));
+    if !header.firmware_revision.is_empty() {
+        lines.push(format!("H Firmware revision:{}", header.firmware_revision));
+    }
+    if !header.craft_name.is_empty() {
+        lines.push(format!("H Craft name:{}", header.craft_name));
+    }
+    if header.looptime > 0 {
+        lines.push(format!("H looptime:{}", header.looptime));
+    }
+    let join = |values: Vec<String>| values.join(",");
+    for (frame_type, frame_def) in [
+        ('I', &header.i_frame_def),
+        ('P', &header.p_frame_def),
+        ('S', &header.s_frame_def),
+        ('G', &header.g_frame_def),
+        ('H', &header.h_frame_def),
+    ] {
+        if frame_def.count == 0 {
+            continue;
+        }
+        let fields = &frame_def.fields;
+        lines.push(format!(
+            "H Field {frame_type} name:{}",
+            frame_def.field_names.join(",")
+        ));
+        lines.push(format!(
+            "H Field {frame_type} signed:{}",
+            join(
+                fields
+                    .iter()
+                    .map(|f| u8::from(f.signed).to_string())
+                    .collect()
+            )
+        ));
+        lines.push(format!(
+            "H Field {frame_type} predictor:{}",
+            join(fields.iter().map(|f| f.predictor.to_string()).collect())
+        ));
+        lines.push(format!(
+            "H Field {frame_type} encoding:{}",
+            join(fields.iter().map(|f| f.encoding.to_string()).collect())
+        ));
+    }
+    // The product header is the log start marker written above
+    let mut sysconfig: Vec<_> = header
+        .sysconfig
+        .iter()
+        .filter(|(key, _)| key.as_str() != "Product")
+        .collect();
+    sysconfig.sort_by_key(|(key, _)| key.as_str());
+    lines.extend(sysconfig.into_iter().map(|(key, value)| match value {
+        SysConfigValue::Float(scale) if key == "gyro_scale" => {
+            format!("H {key}:0x{:08x}", scale.to_bits())
+        }
+        _ => format!("H {key}:{value}"),
+    }));
+    lines
+}
+
+/// Write `log` as a standalone blackbox log, returning the number of bytes written
+///
+/// Writes the header, every main frame with its original frame type, and an
+/// S-frame wherever the merged slow fields change. GPS and event frames are
+/// not kept in full in a [`BBLLog`], so they are not written; use [`BBLWriter`]
+/// directly for those.
+///
+/// # Examples
+/// ```rust,no_run
+/// use bbl_parser::{parse_bbl_file, write_bbl_log, ExportOptions};
+/// use std::fs::File;
+/// use std::path::Path;
+///
+/// let mut log = parse_bbl_file(Path::new("flight.BBL"), ExportOptions::default(), false).unwrap();
+/// log.repair_timestamps();
+/// let mut file = File::create("flight.repaired.BBL").unwrap();
+/// write_bbl_log(&log, &mut file).unwrap();
+/// ```
+pub fn write_bbl_log<W: Write + ?Sized>(log: &BBLLog, writer: &mut W) -> Result<u64> {
+    let header = &log.header;
+    let frames = &log.frames;
+    let columns = |frame_def: &FrameDefinition| -> Vec<Option<usize>> {
+        frame_def
+            .field_names
+            .iter()
+            .map(|name| frames.field_index(name))
+            .collect()
+    };
+    let main_columns = columns(&header.i_frame_def);
+    let slow_columns = columns(&header.s_frame_def);
+
+    let mut out = BBLWriter::new(writer, header)?;
+    let mut last_slow: Option<Vec<i32>> = None;
+    for (row, &frame_type) in frames.frame_types().iter().enumerate() {
+        let value = |column: &Option<usize>| column.and_then(|c| frames.value(row, c));
+        if header.s_frame_def.count > 0 && slow_columns.iter().any(|c| value(c).is_some()) {
+            let slow: Vec<i32> = slow_columns.iter().map(|c| value(c).unwrap_or(0)).collect();
+            if last_slow.as_ref() != Some(&slow) {
+                out.write_s_frame(&slow)?;
+                last_slow = Some(slow);
+            }
+        }
+        let values: Vec<i32> = main_columns.iter().map(|c| value(c).unwrap_or(0)).collect();
+        if frame_type == 'P' {
+            out.write_p_frame(&values)?;
+        } else {
+            out.write_i_frame(&values)?;
+        }
+    }
+    out.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::main::parse_bbl_bytes;
+    use crate::ExportOptions;
+
+    /// Frame definition with the given field names, signedness, predictors and encodings
+    fn frame_def(
+        names: &[&str],
+        signed: &[bool],
+        predictors: &[u8],
+        encodings: &[u8],
+    ) -> FrameDefinition {
+        let mut frame_def =
+            FrameDefinition::from_field_names(names.iter().map(|name| name.to_string()).collect());
+        frame_def.update_signed(signed);
+        frame_def.update_predictors(predictors);
+        frame_def.update_encoding(encodings);
+        frame_def
+    }
+
+    fn test_header() -> BBLHeader {
+        let main_fields = [
+            "loopIteration",
+            "time",
+            "gyroADC[0]",
+            "gyroADC[1]",
+            "gyroADC[2]",
+        ];
+        let main_signed = [false, false, true, true, true];
+        let mut header = BBLHeader {
+            firmware_revision: "Betaflight 4.5.0".into(),
+            craft_name: "writer".into(),
+            looptime: 125,
+            i_frame_def: frame_def(&main_fields, &main_signed, &[0; 5], &[1, 1, 0, 0, 0]),
+            p_frame_def: frame_def(
+                &main_fields,
+                &main_signed,
+                &[6, 2, 1, 1, 1],
+                &[9, 0, 7, 7, 7],
+            ),
+            s_frame_def: frame_def(
+                &["flightModeFlags", "stateFlags"],
+                &[false; 2],
+                &[0; 2],
+                &[1; 2],
+            ),
+            g_frame_def: frame_def(
+                &["GPS_numSat", "GPS_coord[0]", "GPS_coord[1]", "GPS_altitude"],
+                &[false, true, true, false],
+                &[0, 7, 7, 0],
+                &[1, 0, 0, 1],
+            ),
+            h_frame_def: frame_def(
+                &["GPS_home[0]", "GPS_home[1]"],
+                &[true; 2],
+                &[0; 2],
+                &[0; 2],
+            ),
+            ..Default::default()
+        };
+        header.data_version = 2;
+        header
+    }
+
+    #[test]
+    fn test_writer_round_trip() {
+        let header = test_header();
+        let main_frames: Vec<[i32; 5]> = (0..12)
+            .map(|frame| {
+                [
+                    frame + 1,
+                    1000 + frame * 125,
+                    frame * 7,
+                    -frame * 300,
+                    40_000 - frame,
+                ]
+            })
+            .collect();
+
+        let mut data = Vec::new();
+        let mut writer = BBLWriter::new(&mut data, &header).unwrap();
+        writer.write_s_frame(&[1, 2]).unwrap();
+        writer.write_h_frame(&[473_977_000, 85_449_000]).unwrap();
+        for (frame, values) in main_frames.iter().enumerate() {
+            if frame % 5 == 0 {
+                writer.write_i_frame(values).unwrap();
+            } else {
+                writer.write_p_frame(values).unwrap();
+            }
+            if frame == 6 {
+                writer.write_event(30, &[1, 2, 3, 4]).unwrap();
+            }
+            if frame % 4 == 0 {
+                let gps_frame = frame as i32;
+                writer
+                    .write_g_frame(&[9, 473_977_000 + gps_frame, 85_449_000 - gps_frame, 500])
+                    .unwrap();
+            }
+        }
+        let written = writer.finish().unwrap();
+        assert_eq!(written, data.len() as u64);
+
+        let options = ExportOptions {
+            gpx: true,
+            event: true,
+            split_frame_types: true,
+            ..Default::default()
+        };
+        let log = parse_bbl_bytes(&data, options, false).unwrap();
+        assert_eq!(log.header.firmware_revision, "Betaflight 4.5.0");
+        assert_eq!(log.header.craft_name, "writer");
+        assert_eq!(log.frames.len(), main_frames.len());
+        assert_eq!(
+            log.frames
+                .frame_types()
+                .iter()
+                .filter(|&&t| t == 'I')
+                .count(),
+            3
+        );
+        for (i, name) in header.i_frame_def.field_names.iter().enumerate() {
+            let expected: Vec<i32> = main_frames.iter().map(|values| values[i]).collect();
+            assert_eq!(log.frames.column(name).unwrap(), &expected[..], "{name}");
+        }
+        assert!(log
+            .frames
+            .column("stateFlags")
+            .unwrap()
+            .iter()
+            .all(|&v| v == 2));
+
+        assert_eq!(log.gps_frames.len(), 3);
+        assert_eq!(
+            log.gps_frames.column("GPS_coord[1]").unwrap(),
+            &[85_449_000, 85_448_996, 85_448_992]
+        );
+        assert_eq!(log.home_coordinates.len(), 1);
+        assert!((log.gps_coordinates[1].latitude - 47.397_700_4).abs() < 1e-9);
+        assert_eq!(log.event_frames[0].event_type, 30);
+        assert_eq!(log.event_frames[0].event_data, [1, 2, 3, 4]);
+        assert_eq!(log.stats.failed_frames, 0);
+
+        // P-frames need an I-frame to predict from
+        let mut writer = BBLWriter::new(Vec::new(), &header).unwrap();
+        assert!(writer.write_p_frame(&main_frames[0]).is_err());
+    }
+
+    #[test]
+    fn test_write_bbl_log_round_trip() {
+        let header = test_header();
+        let mut data = Vec::new();
+        let mut writer = BBLWriter::new(&mut data, &header).unwrap();
+        for frame in 0..20 {
+            if frame == 8 {
+                writer.write_s_frame(&[4, 0]).unwrap();
+            }
+            let values = [
+                frame + 1,
+                1000 + frame * 125,
+                frame % 3,
+                -frame,
+                frame * frame,
+            ];
+            // A gap in loopIteration is written as an I-frame
+            let values = if frame >= 10 {
+                [frame + 6, values[1], values[2], values[3], values[4]]
+            } else {
+                values
+            };
+            if frame == 0 {
+                writer.write_i_frame(&values).unwrap();
+            } else {
+                writer.write_p_frame(&values).unwrap();
+            }
+        }
+        writer.finish().unwrap();
+        let log = parse_bbl_bytes(&data, ExportOptions::default(), false).unwrap();
+        assert_eq!(
+            log.frames
+                .frame_types()
+                .iter()
+                .filter(|&&t| t == 'I')
+                .count(),
+            2
+        );
+
+        let mut rewritten = Vec::new();
+        write_bbl_log(&log, &mut rewritten).unwrap();
+        let reparsed = parse_bbl_bytes(&rewritten, ExportOptions::default(), false).unwrap();
+        assert_eq!(reparsed.frames.frame_types(), log.frames.frame_types());
+        assert_eq!(reparsed.frames.timestamps(), log.frames.timestamps());
+        for name in log.frames.field_names() {
+            assert_eq!(
+                reparsed.frames.column(name),
+                log.frames.column(name),
+                "{name}"
+            );
+        }
+    }
+}
@@ -34,6 +34,7 @@ fn test_export_gpx_creates_output_directory() {
         event: false,
         output_dir: Some(nonexistent_dir.to_str().unwrap().to_string()),
         force_export: false,
+        ..Default::default()
     };
 
     let result = export_to_gpx(&bbl_path, 0, 1, &gps_coords, &[], &export_opts, None, None);
@@ -83,6 +84,7 @@ fn test_export_event_creates_output_directory() {
         event: true,
         output_dir: Some(nonexistent_dir.to_str().unwrap().to_string()),
         force_export: false,
+        ..Default::default()
     };
 
     let result = export_to_event(&bbl_path, 0, 1, &event_frames, &export_opts, None);
@@ -122,6 +124,7 @@ fn test_export_event_empty_returns_ok() {
         event: true,
         output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
         force_export: false,
+        ..Default::default()
     };
 
     let result = export_to_event(&bbl_path, 0, 1, &[], &export_opts, None);
@@ -150,6 +153,7 @@ fn test_compute_export_paths_single_log() {
         event: true,
         output_dir: Some(output_dir.to_str().unwrap().to_string()),
         force_export: false,
+        ..Default::default()
     };
 
     let (csv_path, _headers_path, gpx_path, event_path) =
@@ -182,6 +186,7 @@ fn test_compute_export_paths_multi_log() {
         event: true,
         output_dir: Some(output_dir.to_str().unwrap().to_string()),
         force_export: false,
+        ..Default::default()
     };
 
     let (csv_path, _headers_path, gpx_path, event_path) =
@@ -223,6 +228,7 @@ fn test_export_options_custom() {
         event: false,
         output_dir: Some("/tmp/test".to_string()),
         force_export: true,
+        ..Default::default()
     };
 
     assert!(opts.csv);
@@ -243,6 +249,7 @@ fn test_gpx_empty_coordinates_returns_ok() {
         event: false,
         output_dir: Some(temp_dir.path().to_str().unwrap().to_string()),
         force_export: false,
+        ..Default::default()
     };
 
     // Should return Ok even with empty GPS coordinates